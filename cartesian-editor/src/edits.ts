@@ -0,0 +1,62 @@
+// Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
+// Licensed under the MIT License
+
+import { get_element_by_symbol } from './periodic_table';
+import type { AtomicCoordinates, EditEvent } from './types';
+
+// DOM event with an EditEventDetail, dispatched by the visualizer of a structure
+export const EDIT_EVENT = 'mircmd:chemistry:edit';
+
+// DOM event with a HydrogensEventDetail asking the visualizer of the structure to add the missing hydrogens or
+// strip them
+export const HYDROGENS_EVENT = 'mircmd:chemistry:hydrogens';
+
+// Key of the structure in the DOM events of its plugins: FNV-1a of the data they were opened with, the same as
+// structure_key of the visualizer
+export function structure_key(data: Uint8Array): string {
+  let hash = 0x811c9dc5;
+  for (const byte of data) {
+    hash = Math.imul(hash ^ byte, 0x01000193);
+  }
+  return (hash >>> 0).toString(16).padStart(8, '0');
+}
+
+function append_atom(coords: AtomicCoordinates, symbol: string, position: [number, number, number]): void {
+  const element = get_element_by_symbol(symbol);
+  if (!element) throw new Error(`Unknown element ${symbol}`);
+  coords.atomic_num.push(element.atomic_number);
+  coords.x.push(position[0]);
+  coords.y.push(position[1]);
+  coords.z.push(position[2]);
+}
+
+function delete_atoms(coords: AtomicCoordinates, atoms: number[]): void {
+  const deleted = new Set(atoms.map((atom) => atom - 1));
+  const kept = (_: number, i: number) => !deleted.has(i);
+  coords.atomic_num = coords.atomic_num.filter(kept);
+  coords.x = coords.x.filter(kept);
+  coords.y = coords.y.filter(kept);
+  coords.z = coords.z.filter(kept);
+}
+
+// Applies the edit to the coordinates, returns false if it changes no atoms (bonds are not shown in the table)
+export function apply_edit(coords: AtomicCoordinates, event: EditEvent): boolean {
+  switch (event.kind) {
+    case 'add_atom':
+      append_atom(coords, event.element, event.position);
+      return true;
+    case 'delete_atoms':
+      delete_atoms(coords, event.atoms);
+      return true;
+    case 'attach_template':
+      if (event.replaced) delete_atoms(coords, [event.atom]);
+      event.elements.forEach((symbol, i) => append_atom(coords, symbol, event.positions[i]));
+      return true;
+    case 'add_hydrogens':
+      event.positions.forEach((position) => append_atom(coords, 'H', position));
+      return true;
+    case 'add_bond':
+    case 'remove_bond':
+      return false;
+  }
+}
@@ -3,7 +3,9 @@
 
 import type { Element } from './types';
 
+// Pseudo-elements (atomic numbers below 1): dummy atom, point charge and ghost atom
 const ELEMENTS: Record<number, Element> = {
+  [-3]: { atomic_number: -3, symbol: 'Bq', covalent_radius: 0.0 },
   [-1]: { atomic_number: -1, symbol: 'X', covalent_radius: 0.0 },
   [-2]: { atomic_number: -2, symbol: 'Q', covalent_radius: 0.0 },
   [1]: { atomic_number: 1, symbol: 'H', covalent_radius: 0.32 },
@@ -129,3 +131,18 @@ const ELEMENTS: Record<number, Element> = {
 export function get_element_by_number(atomic_number: number): Element | undefined {
   return ELEMENTS[atomic_number];
 }
+
+export function get_element_by_symbol(symbol: string): Element | undefined {
+  return Object.values(ELEMENTS).find((element) => element.symbol === symbol);
+}
+
+export function is_pseudo_element(atomic_number: number): boolean {
+  return atomic_number < 1;
+}
+
+// Symbol of the element, other atomic numbers below 1 are dummy atoms
+export function get_symbol(atomic_number: number): string {
+  const element = get_element_by_number(atomic_number);
+  if (element) return element.symbol;
+  return is_pseudo_element(atomic_number) ? 'X' : `?(${atomic_number})`;
+}
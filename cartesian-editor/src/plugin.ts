@@ -1,10 +1,11 @@
 // Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
 // Licensed under the MIT License
 
-import { get_element_by_number } from './periodic_table';
+import { apply_edit, EDIT_EVENT, HYDROGENS_EVENT, structure_key } from './edits';
+import { get_symbol, is_pseudo_element } from './periodic_table';
 import type { ProgramPluginContext } from './program_context';
 import styles from './style.css';
-import type { AtomicCoordinates } from './types';
+import type { AtomicCoordinates, EditEventDetail, HydrogensEventDetail } from './types';
 
 const ROW_HEIGHT = 28;
 const CELL_PADDING = 4;
@@ -15,6 +16,8 @@ const COL_COORD_MIN_WIDTH = 40;
 const SCROLL_BUFFER = 20;
 
 interface VirtualTableConfig {
+    // Zero-based indices of the atoms in the rows
+    rows: number[];
     data: {
         symbols: string[];
         x: number[];
@@ -37,19 +40,81 @@ function run(ctx: ProgramPluginContext, data: Uint8Array): void {
         return;
     }
 
-    const symbols = get_symbols(parsed.value);
+    const coords = parsed.value;
     const css_vars = build_css_vars();
     ctx.addStyles(`${css_vars}\n${styles}`);
 
-    const config: VirtualTableConfig = {
-        data: { symbols, x: parsed.value.x, y: parsed.value.y, z: parsed.value.z },
-        row_height: ROW_HEIGHT,
-        scroll_buffer: SCROLL_BUFFER,
+    let hide_pseudo_atoms = false;
+    let container: HTMLDivElement | null = null;
+    let pseudo_filter: HTMLLabelElement | null = null;
+    const key = structure_key(data);
+    const toolbar = create_toolbar((edit) => {
+        const detail: HydrogensEventDetail = { structure: key, edit };
+        ctx.host.dispatchEvent(new CustomEvent(HYDROGENS_EVENT, { detail, bubbles: true, composed: true }));
+    });
+    const show_table = (): void => {
+        const all_rows = coords.atomic_num.map((_, i) => i);
+        const real_rows = all_rows.filter((i) => !is_pseudo_element(coords.atomic_num[i]));
+        if (!pseudo_filter && real_rows.length < all_rows.length) {
+            pseudo_filter = create_pseudo_filter((hide) => {
+                hide_pseudo_atoms = hide;
+                show_table();
+            });
+            toolbar.appendChild(pseudo_filter);
+        }
+        container?.remove();
+        container = create_table_container();
+        container.prepend(toolbar);
+        ctx.root.appendChild(container);
+        init_virtual_table(container, {
+            rows: hide_pseudo_atoms ? real_rows : all_rows,
+            data: { symbols: get_symbols(coords), x: coords.x, y: coords.y, z: coords.z },
+            row_height: ROW_HEIGHT,
+            scroll_buffer: SCROLL_BUFFER,
+        });
+    };
+    show_table();
+
+    // Atoms added or deleted in the visualizer of the structure, edits of other structures are ignored
+    const on_edit = (event: Event): void => {
+        if (!ctx.host.isConnected) {
+            window.removeEventListener(EDIT_EVENT, on_edit);
+            return;
+        }
+        const detail = (event as CustomEvent<EditEventDetail>).detail;
+        if (detail.structure !== key) return;
+        try {
+            if (apply_edit(coords, detail)) show_table();
+        } catch (e) {
+            console.warn(e);
+        }
     };
+    window.addEventListener(EDIT_EVENT, on_edit);
+}
 
-    const container = create_table_container();
-    ctx.root.appendChild(container);
-    init_virtual_table(container, config);
+// Buttons asking the molecular visualizer of the structure to add the missing hydrogens or strip them, the table
+// follows the edits it reports
+function create_toolbar(on_hydrogens: (edit: 'add' | 'strip') => void): HTMLDivElement {
+    const toolbar = document.createElement('div');
+    toolbar.className = 'vt-toolbar';
+    const add_button = document.createElement('button');
+    add_button.textContent = 'Add hydrogens';
+    add_button.addEventListener('click', () => on_hydrogens('add'));
+    const strip_button = document.createElement('button');
+    strip_button.textContent = 'Strip hydrogens';
+    strip_button.addEventListener('click', () => on_hydrogens('strip'));
+    toolbar.append(add_button, strip_button);
+    return toolbar;
+}
+
+// Option to hide dummy atoms, point charges and ghost atoms
+function create_pseudo_filter(on_change: (hide_pseudo_atoms: boolean) => void): HTMLLabelElement {
+    const label = document.createElement('label');
+    const checkbox = document.createElement('input');
+    checkbox.type = 'checkbox';
+    checkbox.addEventListener('change', () => on_change(checkbox.checked));
+    label.append(checkbox, ' Hide dummy atoms, point charges and ghost atoms');
+    return label;
 }
 
 function parse_coords(data: Uint8Array):
@@ -75,10 +140,7 @@ function render_error(root: ShadowRoot, message: string): void {
 }
 
 function get_symbols(coords: AtomicCoordinates): string[] {
-    return coords.atomic_num.map((n) => {
-        const element = get_element_by_number(n);
-        return element ? element.symbol : `?(${n})`;
-    });
+    return coords.atomic_num.map(get_symbol);
 }
 
 function build_css_vars(): string {
@@ -163,7 +225,7 @@ function prepare_viewport(
     viewport: HTMLElement,
     config: VirtualTableConfig,
 ): void {
-    const total = config.data.symbols.length;
+    const total = config.rows.length;
     viewport.style.height = `${total * config.row_height}px`;
     const scrollbar_width = body.offsetWidth - body.clientWidth;
     header.style.paddingRight = `${scrollbar_width}px`;
@@ -200,7 +262,7 @@ function render_visible_rows(
     config: VirtualTableConfig,
     state: RenderState,
 ): void {
-    const total = config.data.symbols.length;
+    const total = config.rows.length;
     const range = get_visible_range(body, config.row_height, config.scroll_buffer, total);
     if (range.start === state.visible_start && range.end === state.visible_end) return;
     prune_rows(state, range);
@@ -247,10 +309,11 @@ function append_rows(
     viewport.appendChild(fragment);
 }
 
-function build_row(index: number, config: VirtualTableConfig): HTMLDivElement {
+function build_row(row_index: number, config: VirtualTableConfig): HTMLDivElement {
+    const index = config.rows[row_index];
     const row = document.createElement('div');
     row.className = 'vt-row';
-    row.style.top = `${index * config.row_height}px`;
+    row.style.top = `${row_index * config.row_height}px`;
     row.innerHTML =
         `<div class="vt-cell col-tag">${index + 1}</div>` +
         `<div class="vt-cell col-symbol">${config.data.symbols[index]}</div>` +
@@ -13,3 +13,28 @@ export interface Element {
   symbol: string;
   covalent_radius: number;
 }
+
+// Structural edit of the molecule builder of the visualizer, atom indices start from 1
+export type EditEvent =
+  | { kind: 'add_atom'; atom: number; element: string; position: [number, number, number] }
+  | { kind: 'delete_atoms'; atoms: number[] }
+  | { kind: 'add_bond'; atoms: [number, number]; order: number }
+  | { kind: 'remove_bond'; atoms: [number, number] }
+  | {
+      kind: 'attach_template';
+      atom: number;
+      template: string;
+      replaced: boolean;
+      elements: string[];
+      positions: [number, number, number][];
+    }
+  | { kind: 'add_hydrogens'; positions: [number, number, number][] };
+
+// Detail of the edit events with the key of the edited structure
+export type EditEventDetail = EditEvent & { structure: string };
+
+// Detail of the requests to add the missing hydrogens or strip them
+export interface HydrogensEventDetail {
+  structure: string;
+  edit: 'add' | 'strip';
+}
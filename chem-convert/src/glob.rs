@@ -0,0 +1,96 @@
+// Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
+// Licensed under the MIT License
+
+use std::path::{Component, Path, PathBuf};
+
+fn has_wildcards(text: &str) -> bool {
+    text.contains(['*', '?', '['])
+}
+
+/// Matches the name against the pattern with `*` (any characters), `?` (one character) and `[abc]`, `[a-z]`,
+/// `[!abc]` (one character of the set or not of the set).
+fn matches(pattern: &[char], name: &[char]) -> bool {
+    match pattern.first() {
+        None => name.is_empty(),
+        Some('*') => (0..=name.len()).any(|skip| matches(&pattern[1..], &name[skip..])),
+        Some('?') => !name.is_empty() && matches(&pattern[1..], &name[1..]),
+        Some('[') => {
+            let Some(end) = pattern.iter().skip(2).position(|&c| c == ']').map(|i| i + 2) else {
+                // Unclosed bracket is a literal character
+                return name.first() == Some(&'[') && matches(&pattern[1..], &name[1..]);
+            };
+            let Some(&c) = name.first() else {
+                return false;
+            };
+            let (negated, set) = match pattern[1] {
+                '!' | '^' => (true, &pattern[2..end]),
+                _ => (false, &pattern[1..end]),
+            };
+            let mut found = false;
+            let mut i = 0;
+            while i < set.len() {
+                if i + 2 < set.len() && set[i + 1] == '-' {
+                    found |= (set[i]..=set[i + 2]).contains(&c);
+                    i += 3;
+                } else {
+                    found |= set[i] == c;
+                    i += 1;
+                }
+            }
+            found != negated && matches(&pattern[end + 1..], &name[1..])
+        }
+        Some(&p) => name.first() == Some(&p) && matches(&pattern[1..], &name[1..]),
+    }
+}
+
+/// Expands wildcards in any component of the path to the existing files, sorted by name. Hidden files are only
+/// matched by patterns starting with a dot. A path without wildcards is returned as is, even if it does not exist,
+/// so that the error is reported for it later. Shells on Windows do not expand wildcards, hence the expansion here.
+pub fn expand(pattern: &str) -> Result<Vec<PathBuf>, String> {
+    if !has_wildcards(pattern) {
+        return Ok(vec![PathBuf::from(pattern)]);
+    }
+
+    let mut paths = vec![PathBuf::new()];
+    for component in Path::new(pattern).components() {
+        let name = match component {
+            Component::Normal(name) => name.to_string_lossy(),
+            _ => {
+                paths.iter_mut().for_each(|path| path.push(component));
+                continue;
+            }
+        };
+        if !has_wildcards(&name) {
+            paths.iter_mut().for_each(|path| path.push(&*name));
+            continue;
+        }
+
+        let pattern: Vec<char> = name.chars().collect();
+        let mut expanded = Vec::new();
+        for path in &paths {
+            let directory = if path.as_os_str().is_empty() {
+                Path::new(".")
+            } else {
+                path
+            };
+            let Ok(entries) = std::fs::read_dir(directory) else {
+                continue;
+            };
+            let mut names: Vec<String> = entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.file_name().to_string_lossy().into_owned())
+                .filter(|entry| !entry.starts_with('.') || pattern[0] == '.')
+                .filter(|entry| matches(&pattern, &entry.chars().collect::<Vec<char>>()))
+                .collect();
+            names.sort();
+            expanded.extend(names.into_iter().map(|entry| path.join(entry)));
+        }
+        paths = expanded;
+    }
+
+    let files: Vec<PathBuf> = paths.into_iter().filter(|path| path.is_file()).collect();
+    if files.is_empty() {
+        return Err(format!("No files match {}", pattern));
+    }
+    Ok(files)
+}
@@ -0,0 +1,236 @@
+// Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
+// Licensed under the MIT License
+
+mod glob;
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use files_exporter::options::ExportOptions;
+use files_importer::import_file;
+use files_importer::options::{ImportOptions, LengthUnit};
+use shared_lib::cancellation::CancellationToken;
+use shared_lib::ordering::{AtomMapping, AtomOrder};
+
+const USAGE: &str = "\
+Converts chemistry files with the parsers and writers of the Mir Commander plugins.
+
+Usage: chem-convert [OPTIONS] --to <FORMAT> <INPUT>...
+
+Inputs may contain wildcards: *, ? and [...].
+
+Options:
+  -t, --to <FORMAT>       Output format
+  -o, --out-dir <DIR>     Directory of the converted files, next to the inputs by default
+      --last              Convert only the last geometry, e.g. of an optimization
+      --stride <N>        Convert only every n-th geometry
+      --unit <UNIT>       Unit of coordinates in XYZ files: angstrom (default) or bohr
+      --options <JSON>    Import options as for load-with-options, the options above override them
+      --export-options <JSON>
+                          Precision, unit and layout of coordinates as for save-with-options
+      --order <ORDER>     Order of the atoms: element, centroid, canonical or a list of indices
+                          like 3,1,2. The mapping of the indices is written to <OUTPUT>.mapping.json
+  -f, --force             Overwrite existing files
+  -h, --help              Print this help";
+
+struct Args {
+    format: String,
+    out_dir: Option<PathBuf>,
+    options: ImportOptions,
+    export_options: ExportOptions,
+    order: Option<AtomOrder>,
+    force: bool,
+    inputs: Vec<String>,
+}
+
+fn next_value(args: &mut impl Iterator<Item = String>, option: &str) -> Result<String, String> {
+    args.next().ok_or_else(|| format!("{} needs a value", option))
+}
+
+/// Parses the arguments, `None` if the help is requested.
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Option<Args>, String> {
+    let mut format = None;
+    let mut out_dir = None;
+    let mut options_json = None;
+    let mut export_options_json = None;
+    let mut order = None;
+    let (mut last, mut stride, mut unit) = (false, None, None);
+    let mut force = false;
+    let mut inputs = Vec::new();
+
+    while let Some(arg) = args.next() {
+        // --option=value is the same as --option value
+        let (option, inline) = match arg.split_once('=') {
+            Some((option, value)) if option.starts_with("--") => (option.to_string(), Some(value.to_string())),
+            _ => (arg.clone(), None),
+        };
+        let mut value = || match &inline {
+            Some(value) => Ok(value.clone()),
+            None => next_value(&mut args, &option),
+        };
+        match option.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "-t" | "--to" => format = Some(value()?),
+            "-o" | "--out-dir" => out_dir = Some(PathBuf::from(value()?)),
+            "--last" => last = true,
+            "--stride" => {
+                let text = value()?;
+                stride = Some(
+                    text.parse::<usize>()
+                        .ok()
+                        .filter(|&n| n > 0)
+                        .ok_or_else(|| format!("Invalid stride: {}", text))?,
+                );
+            }
+            "--unit" => {
+                unit = Some(match value()?.to_ascii_lowercase().as_str() {
+                    "angstrom" => LengthUnit::Angstrom,
+                    "bohr" => LengthUnit::Bohr,
+                    other => return Err(format!("Unknown unit: {}", other)),
+                });
+            }
+            "--options" => options_json = Some(value()?),
+            "--export-options" => export_options_json = Some(value()?),
+            "--order" => order = Some(AtomOrder::parse(&value()?)?),
+            "-f" | "--force" => force = true,
+            _ if option.starts_with('-') && option.len() > 1 => return Err(format!("Unknown option: {}", option)),
+            _ => inputs.push(arg),
+        }
+    }
+
+    let format = format.ok_or_else(|| "The output format is not given, use --to".to_string())?;
+    if !files_exporter::formats().any(|(name, _)| name.eq_ignore_ascii_case(&format)) {
+        let names: Vec<&str> = files_exporter::formats().map(|(name, _)| name).collect();
+        return Err(format!("Unknown format {}, supported: {}", format, names.join(", ")));
+    }
+    if inputs.is_empty() {
+        return Err("No input files".to_string());
+    }
+    let mut options = ImportOptions::from_json(options_json.as_deref().unwrap_or(""))?;
+    options.trajectory.last_only |= last;
+    if let Some(stride) = stride {
+        options.trajectory.stride = stride;
+    }
+    if let Some(unit) = unit {
+        options.xyz.unit = unit;
+    }
+
+    // The atoms are reordered before the export to report the mapping
+    let mut export_options = ExportOptions::from_json(export_options_json.as_deref().unwrap_or(""))?;
+    let order = order.or(export_options.order.take());
+
+    Ok(Some(Args {
+        format,
+        out_dir,
+        options,
+        export_options,
+        order,
+        force,
+        inputs,
+    }))
+}
+
+/// Output file of the input in the directory: the name without its extensions and with the one of the format.
+fn output_path(input: &Path, out_dir: Option<&Path>, extension: &str) -> PathBuf {
+    let name = input.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+    let name = files_importer::strip_compression_extension(&name);
+    let stem = Path::new(name)
+        .file_stem()
+        .map_or(name.into(), |stem| stem.to_string_lossy());
+    let directory = out_dir.unwrap_or_else(|| input.parent().unwrap_or(Path::new("")));
+    directory.join(format!("{}.{}", stem, extension))
+}
+
+fn convert(input: &Path, output: &Path, args: &Args) -> Result<(), String> {
+    if !args.force && output.exists() {
+        return Err(format!("{} exists, use --force to overwrite it", output.display()));
+    }
+    let mut node = import_file(&input.to_string_lossy(), &args.options, &CancellationToken::none())?;
+    if let Some(order) = &args.order {
+        let mapping_path = output.with_extension("mapping.json");
+        if !args.force && mapping_path.exists() {
+            return Err(format!(
+                "{} exists, use --force to overwrite it",
+                mapping_path.display()
+            ));
+        }
+        let (reordered, permutation) = files_exporter::reorder_node(&node, order)?;
+        let mapping = serde_json::to_string(&AtomMapping::new(&permutation)).map_err(|e| e.to_string())?;
+        write(&mapping_path, mapping)?;
+        node = reordered;
+    }
+    let content = files_exporter::export_node_with_options(&node, &args.format, &args.export_options)?;
+    write(output, content)
+}
+
+fn write(path: &Path, content: String) -> Result<(), String> {
+    std::fs::write(path, content).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+fn run(args: &Args) -> Result<usize, String> {
+    let mut inputs = Vec::new();
+    for pattern in &args.inputs {
+        inputs.extend(glob::expand(pattern)?);
+    }
+    let extension = files_exporter::formats()
+        .find(|(name, _)| name.eq_ignore_ascii_case(&args.format))
+        .map(|(_, extension)| extension)
+        .unwrap_or("out");
+    if let Some(out_dir) = &args.out_dir {
+        std::fs::create_dir_all(out_dir).map_err(|e| format!("Failed to create {}: {}", out_dir.display(), e))?;
+    }
+
+    // Inputs with the same name in different directories would overwrite each other in the output directory
+    let mut outputs: HashMap<PathBuf, &Path> = HashMap::new();
+    let mut failed = 0;
+    for input in &inputs {
+        let output = output_path(input, args.out_dir.as_deref(), extension);
+        let result = match outputs.get(&output) {
+            Some(previous) => Err(format!(
+                "{} is already converted from {}",
+                output.display(),
+                previous.display()
+            )),
+            None => convert(input, &output, args),
+        };
+        match result {
+            Ok(()) => println!("{} -> {}", input.display(), output.display()),
+            Err(e) => {
+                eprintln!("{}: {}", input.display(), e);
+                failed += 1;
+            }
+        }
+        outputs.entry(output).or_insert(input);
+    }
+
+    println!(
+        "Converted {} of {} files, {} failed",
+        inputs.len() - failed,
+        inputs.len(),
+        failed
+    );
+    Ok(failed)
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args(std::env::args().skip(1)) {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+    match run(&args) {
+        Ok(0) => ExitCode::SUCCESS,
+        Ok(_) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
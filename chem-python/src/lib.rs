@@ -0,0 +1,720 @@
+// Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
+// Licensed under the MIT License
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use shared_lib::atom_types::{self, TypingScheme};
+use shared_lib::cancellation::CancellationToken;
+use shared_lib::cell::UnitCell;
+use shared_lib::comparison::{self, ToleranceProfile, Tolerances};
+use shared_lib::connectivity::{self, DEFAULT_BOND_TOLERANCE};
+use shared_lib::contacts::{
+    self, DEFAULT_CLASH_FRACTION, DEFAULT_COVALENT_CLASH_FRACTION, DEFAULT_HBOND_ANGLE, DEFAULT_HBOND_DISTANCE,
+};
+use shared_lib::force_field::ForceField;
+use shared_lib::ordering::{self, AtomOrder};
+use shared_lib::periodic_table::element_symbol;
+use shared_lib::symmetry::{Symmetry, SymmetryElement};
+use shared_lib::types::{self, AtomicCoordinates, Connection};
+use shared_lib::{generators, geometry, inertia, mass_spectrum, optimization, properties, templates};
+
+use files_exporter::options::ExportOptions;
+use files_importer::options::ImportOptions;
+
+const ATOMIC_COORDINATES_TYPE: &str = "mircmd:chemistry:atomic_coordinates";
+const MOLECULE_TYPE: &str = "mircmd:chemistry:molecule";
+
+fn value_error(e: String) -> PyErr {
+    PyValueError::new_err(e)
+}
+
+/// Tolerances of structure comparisons from a profile name ("loose", "normal" or "strict") or JSON changing any
+/// of "distance", "rmsd" and "convergence" of the normal profile, the normal profile if not given.
+fn parse_tolerances(settings: Option<&str>) -> Result<Tolerances, String> {
+    let tolerances = match settings {
+        Some(json) if json.trim_start().starts_with('{') => {
+            serde_json::from_str(json).map_err(|e| format!("Failed to parse tolerances: {}", e))?
+        }
+        Some(name) => ToleranceProfile::from_name(name)?.tolerances(),
+        None => Tolerances::default(),
+    };
+    tolerances.validate()?;
+    Ok(tolerances)
+}
+
+/// Atomic coordinates in Angstroms with the bonds and the periodic cell given by the file.
+#[pyclass(module = "mircmd_chem")]
+#[derive(Clone)]
+struct Structure {
+    #[pyo3(get, set)]
+    name: String,
+    #[pyo3(get, set)]
+    atomic_num: Vec<i32>,
+    #[pyo3(get, set)]
+    x: Vec<f64>,
+    #[pyo3(get, set)]
+    y: Vec<f64>,
+    #[pyo3(get, set)]
+    z: Vec<f64>,
+    /// Lattice vectors a, b and c, `None` for finite structures.
+    #[pyo3(get, set)]
+    cell: Option<[[f64; 3]; 3]>,
+    /// Bonds given by the file as (atom_1, atom_2, order) with zero-based atoms, `None` if they are perceived.
+    #[pyo3(get, set)]
+    connectivity: Option<Vec<(usize, usize, u8)>>,
+    #[pyo3(get, set)]
+    charges: Option<Vec<f64>>,
+    #[pyo3(get, set)]
+    chains: Option<Vec<String>>,
+    /// Mass numbers of the isotopes, 0 for the natural abundance.
+    #[pyo3(get, set)]
+    isotopes: Option<Vec<u32>>,
+}
+
+impl Structure {
+    fn from_coordinates(name: String, data: AtomicCoordinates) -> Self {
+        Self {
+            name,
+            atomic_num: data.atomic_num,
+            x: data.x,
+            y: data.y,
+            z: data.z,
+            cell: data.cell.map(|cell| cell.vectors),
+            connectivity: data
+                .connectivity
+                .map(|connections| connections.iter().map(|c| (c.atom_1, c.atom_2, c.order)).collect()),
+            charges: data.charges,
+            chains: data.chains,
+            isotopes: data.isotopes,
+        }
+    }
+
+    /// Checks the lengths of the arrays and the cell, the same data the plugins get.
+    fn coordinates(&self) -> Result<AtomicCoordinates, String> {
+        let num_atoms = self.atomic_num.len();
+        if self.x.len() != num_atoms || self.y.len() != num_atoms || self.z.len() != num_atoms {
+            return Err(format!(
+                "The structure has {} atomic numbers but {}, {} and {} coordinates",
+                num_atoms,
+                self.x.len(),
+                self.y.len(),
+                self.z.len()
+            ));
+        }
+        if self.charges.as_ref().is_some_and(|charges| charges.len() != num_atoms)
+            || self.chains.as_ref().is_some_and(|chains| chains.len() != num_atoms)
+            || self
+                .isotopes
+                .as_ref()
+                .is_some_and(|isotopes| isotopes.len() != num_atoms)
+        {
+            return Err(format!(
+                "Charges, chains and isotopes must be given for all {} atoms",
+                num_atoms
+            ));
+        }
+        let coordinates = AtomicCoordinates {
+            atomic_num: self.atomic_num.clone(),
+            x: self.x.clone(),
+            y: self.y.clone(),
+            z: self.z.clone(),
+            connectivity: self.connectivity.as_ref().map(|bonds| {
+                bonds
+                    .iter()
+                    .map(|&(atom_1, atom_2, order)| Connection { atom_1, atom_2, order })
+                    .collect()
+            }),
+            charges: self.charges.clone(),
+            chains: self.chains.clone(),
+            cell: self.cell.map(UnitCell::new).transpose()?,
+            isotopes: self.isotopes.clone(),
+        };
+        coordinates.mass_numbers()?;
+        Ok(coordinates)
+    }
+
+    fn node(&self) -> Result<types::Node, String> {
+        let data = serde_json::to_vec(&self.coordinates()?).map_err(|e| e.to_string())?;
+        Ok(types::Node {
+            name: self.name.clone(),
+            r#type: ATOMIC_COORDINATES_TYPE.to_string(),
+            data,
+            children: Vec::new(),
+        })
+    }
+
+    /// Position of the atom (zero-based index) in Angstroms.
+    fn position(&self, index: usize) -> PyResult<[f64; 3]> {
+        let num_atoms = [self.atomic_num.len(), self.x.len(), self.y.len(), self.z.len()];
+        if index >= num_atoms.into_iter().min().unwrap_or(0) {
+            return Err(value_error(format!(
+                "Atom index {} is out of range, the structure has {} atoms",
+                index,
+                self.atomic_num.len()
+            )));
+        }
+        Ok([self.x[index], self.y[index], self.z[index]])
+    }
+}
+
+#[pymethods]
+impl Structure {
+    #[new]
+    #[pyo3(signature = (atomic_num, x, y, z, cell=None, name=String::new()))]
+    fn new(
+        atomic_num: Vec<i32>,
+        x: Vec<f64>,
+        y: Vec<f64>,
+        z: Vec<f64>,
+        cell: Option<[[f64; 3]; 3]>,
+        name: String,
+    ) -> PyResult<Self> {
+        let structure = Self {
+            name,
+            atomic_num,
+            x,
+            y,
+            z,
+            cell,
+            connectivity: None,
+            charges: None,
+            chains: None,
+            isotopes: None,
+        };
+        structure.coordinates().map_err(value_error)?;
+        Ok(structure)
+    }
+
+    fn __len__(&self) -> usize {
+        self.atomic_num.len()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Structure(name={:?}, formula={:?})",
+            self.name,
+            mass_spectrum::formula(&self.atomic_num)
+        )
+    }
+
+    /// Element symbols of the atoms, "X" for dummy atoms.
+    fn symbols(&self) -> Vec<&'static str> {
+        self.atomic_num.iter().map(|&n| element_symbol(n)).collect()
+    }
+
+    /// Molecular formula in the Hill order.
+    fn formula(&self) -> String {
+        mass_spectrum::formula(&self.atomic_num)
+    }
+
+    /// Molar mass in g/mol from the standard atomic weights.
+    fn molar_mass(&self) -> PyResult<f64> {
+        properties::molar_mass(&self.atomic_num).map_err(value_error)
+    }
+
+    /// Bonds given by the file, otherwise perceived the same way as in the visualizer: atoms closer than
+    /// the sum of their covalent radii enlarged by `tolerance`, through the boundaries of the periodic cell.
+    /// Returns (atom_1, atom_2, order, image) with zero-based atoms, `image` is the cell of atom_2 relative
+    /// to atom_1 in lattice vectors.
+    #[pyo3(signature = (tolerance=DEFAULT_BOND_TOLERANCE))]
+    fn bonds(&self, tolerance: f64) -> PyResult<Vec<(usize, usize, u8, [i32; 3])>> {
+        let data = self.coordinates().map_err(value_error)?;
+        Ok(connectivity::find_bonds(&data, tolerance)
+            .iter()
+            .map(|bond| (bond.atom_index_1, bond.atom_index_2, bond.order, bond.image))
+            .collect())
+    }
+
+    /// Hydrogen bonds D–H···A between N, O and F atoms with H···A shorter than `max_distance` Angstroms and
+    /// the D–H···A angle of at least `min_angle` degrees. Returns (donor, hydrogen, acceptor, image, distance,
+    /// angle), `image` is the cell of the acceptor relative to the hydrogen.
+    #[pyo3(signature = (max_distance=DEFAULT_HBOND_DISTANCE, min_angle=DEFAULT_HBOND_ANGLE))]
+    fn hydrogen_bonds(
+        &self,
+        max_distance: f64,
+        min_angle: f64,
+    ) -> PyResult<Vec<(usize, usize, usize, [i32; 3], f64, f64)>> {
+        let data = self.coordinates().map_err(value_error)?;
+        let bonds = connectivity::find_bonds(&data, DEFAULT_BOND_TOLERANCE);
+        Ok(contacts::find_hydrogen_bonds(&data, &bonds, max_distance, min_angle)
+            .iter()
+            .map(|hb| (hb.donor, hb.hydrogen, hb.acceptor, hb.image, hb.distance, hb.angle))
+            .collect())
+    }
+
+    /// Atoms closer than `fraction` of the sum of their van der Waals radii which are neither bonded nor
+    /// bonded to the same atom. Returns (atom_1, atom_2, image, distance, fraction of the radii).
+    #[pyo3(signature = (fraction=DEFAULT_CLASH_FRACTION))]
+    fn clashes(&self, fraction: f64) -> PyResult<Vec<(usize, usize, [i32; 3], f64, f64)>> {
+        let data = self.coordinates().map_err(value_error)?;
+        let bonds = connectivity::find_bonds(&data, DEFAULT_BOND_TOLERANCE);
+        Ok(contacts::find_clashes(&data, &bonds, fraction)
+            .iter()
+            .map(|clash| {
+                (
+                    clash.atom_index_1,
+                    clash.atom_index_2,
+                    clash.image,
+                    clash.distance,
+                    clash.fraction,
+                )
+            })
+            .collect())
+    }
+
+    /// Atoms closer than `fraction` of the sum of their covalent radii, i.e. overlapping each other, bonded atoms
+    /// included. Returns (atom_1, atom_2, image, distance, fraction of the radii).
+    #[pyo3(signature = (fraction=DEFAULT_COVALENT_CLASH_FRACTION))]
+    fn covalent_clashes(&self, fraction: f64) -> PyResult<Vec<(usize, usize, [i32; 3], f64, f64)>> {
+        let data = self.coordinates().map_err(value_error)?;
+        Ok(contacts::find_covalent_clashes(&data, fraction)
+            .iter()
+            .map(|clash| {
+                (
+                    clash.atom_index_1,
+                    clash.atom_index_2,
+                    clash.image,
+                    clash.distance,
+                    clash.fraction,
+                )
+            })
+            .collect())
+    }
+
+    /// Force-field atom type of every atom, "uff" (e.g. "C_R", "O_2") or "gaff" (e.g. "ca", "o"), perceived from
+    /// the elements, the bonds and rings.
+    #[pyo3(signature = (scheme="uff"))]
+    fn atom_types(&self, scheme: &str) -> PyResult<Vec<String>> {
+        let scheme = TypingScheme::from_name(scheme).map_err(value_error)?;
+        let data = self.coordinates().map_err(value_error)?;
+        let bonds = connectivity::find_bonds(&data, DEFAULT_BOND_TOLERANCE);
+        atom_types::perceive(&data, &bonds, scheme).map_err(value_error)
+    }
+
+    /// Energy terms of the simple force field in kcal/mol as (bond, angle, torsion, vdw, total, clashes). The
+    /// force field is set up for `reference` (the structure itself by default) with its bonds, e.g. the geometry
+    /// before an edit, so the terms show the strain of this geometry relative to it.
+    #[pyo3(signature = (reference=None))]
+    fn energy_terms(&self, reference: Option<&Structure>) -> PyResult<(f64, f64, f64, f64, f64, usize)> {
+        let data = self.coordinates().map_err(value_error)?;
+        let reference = match reference {
+            Some(reference) => reference.coordinates().map_err(value_error)?,
+            None => self.coordinates().map_err(value_error)?,
+        };
+        let bonds = connectivity::find_bonds(&reference, DEFAULT_BOND_TOLERANCE);
+        let force_field = ForceField::new(&reference, &bonds).map_err(value_error)?;
+        let terms = force_field.energy_terms(&data).map_err(value_error)?;
+        Ok((
+            terms.bond,
+            terms.angle,
+            terms.torsion,
+            terms.vdw,
+            terms.total,
+            terms.clashes,
+        ))
+    }
+
+    /// Optimizes the geometry with the simple force field of `energy_terms` by steepest descent for at most
+    /// `max_steps` steps or until the largest force on an atom is below `convergence` in kcal/(mol·Å). Returns
+    /// the accepted steps as structures, starting with this geometry and ending with the optimized one, and
+    /// whether the optimization converged.
+    #[pyo3(signature = (max_steps=optimization::DEFAULT_MAX_STEPS, convergence=optimization::DEFAULT_CONVERGENCE))]
+    fn optimized(&self, max_steps: usize, convergence: f64) -> PyResult<(Vec<Structure>, bool)> {
+        let data = self.coordinates().map_err(value_error)?;
+        let bonds = connectivity::find_bonds(&data, DEFAULT_BOND_TOLERANCE);
+        let optimization = optimization::optimize(&data, &bonds, max_steps, convergence).map_err(value_error)?;
+        let frames = optimization
+            .frames
+            .iter()
+            .map(|frame| Structure::from_coordinates(self.name.clone(), frame.coordinates(&data)))
+            .collect();
+        Ok((frames, optimization.converged))
+    }
+
+    /// The structure with the atoms in the order: "element" (Hill order), "centroid" (nearest to the centroid
+    /// first), "canonical" (by ranks in the bond graph) or a list of zero-based atom indices. Charges, chains
+    /// and bonds follow their atoms. Returns the structure with the original index of every atom in the new
+    /// order, e.g. to permute other per-atom data as `[values[i] for i in order]`.
+    fn reordered(&self, order: &Bound<'_, PyAny>) -> PyResult<(Structure, Vec<usize>)> {
+        let order = match order.extract::<Vec<usize>>() {
+            Ok(indices) => AtomOrder::Manual(indices.iter().map(|i| i + 1).collect()),
+            Err(_) => AtomOrder::parse(&order.extract::<String>()?).map_err(value_error)?,
+        };
+        let data = self.coordinates().map_err(value_error)?;
+        let permutation = ordering::atom_order(&data, &order).map_err(value_error)?;
+        let reordered = ordering::reorder(&data, &permutation).map_err(value_error)?;
+        Ok((Structure::from_coordinates(self.name.clone(), reordered), permutation))
+    }
+
+    /// Copy with the functional group or ligand template (e.g. "methyl" or "pyridine", see `templates()`)
+    /// attached to the atom (zero-based) at a standard geometry: a terminal hydrogen is replaced by the template,
+    /// any other atom gets the template bonded to it, a ligand through its donor atom. Atoms of the template
+    /// follow the atoms of the structure.
+    fn attached(&self, index: usize, template: &str) -> PyResult<Structure> {
+        let template = templates::find_template(template).map_err(value_error)?;
+        let data = self.coordinates().map_err(value_error)?;
+        let result = templates::attach(&data, index, template).map_err(value_error)?;
+        Ok(Structure::from_coordinates(self.name.clone(), result))
+    }
+
+    /// Copy with every coordinate shifted by a normally distributed amount with the standard deviation
+    /// `amplitude` in Angstroms. The same seed always gives the same structure.
+    #[pyo3(signature = (amplitude, seed=0))]
+    fn displaced(&self, amplitude: f64, seed: u64) -> PyResult<Structure> {
+        let data = self.coordinates().map_err(value_error)?;
+        let result = generators::displace(&data, amplitude, seed).map_err(value_error)?;
+        Ok(Structure::from_coordinates(self.name.clone(), result))
+    }
+
+    /// Atoms (zero-based) displaced by more than the distance tolerance in the other geometry of the same atoms,
+    /// compared without alignment. `tolerances` is a profile name or JSON, see `find_duplicates`.
+    #[pyo3(signature = (other, tolerances=None))]
+    fn moved_atoms(&self, other: &Structure, tolerances: Option<&str>) -> PyResult<Vec<usize>> {
+        let tolerances = parse_tolerances(tolerances).map_err(value_error)?;
+        let a = self.coordinates().map_err(value_error)?;
+        let b = other.coordinates().map_err(value_error)?;
+        comparison::moved_atoms(&a, &b, &tolerances).map_err(value_error)
+    }
+
+    /// Distance between the atoms (zero-based) in Angstroms.
+    fn distance(&self, i: usize, j: usize) -> PyResult<f64> {
+        Ok(geometry::distance(self.position(i)?, self.position(j)?))
+    }
+
+    /// Angle i–j–k in degrees.
+    fn angle(&self, i: usize, j: usize, k: usize) -> PyResult<f64> {
+        Ok(geometry::angle(self.position(i)?, self.position(j)?, self.position(k)?))
+    }
+
+    /// Dihedral angle i–j–k–l in degrees from -180 to 180.
+    fn dihedral(&self, i: usize, j: usize, k: usize, l: usize) -> PyResult<f64> {
+        Ok(geometry::dihedral(
+            self.position(i)?,
+            self.position(j)?,
+            self.position(k)?,
+            self.position(l)?,
+        ))
+    }
+
+    /// Center of mass in Angstroms with the masses of the main isotopologue.
+    fn center_of_mass(&self) -> PyResult<[f64; 3]> {
+        let data = self.coordinates().map_err(value_error)?;
+        inertia::center_of_mass(&data).map_err(value_error)
+    }
+
+    /// Inertia tensor about the center of mass in u·Å².
+    fn inertia_tensor(&self) -> PyResult<[[f64; 3]; 3]> {
+        let data = self.coordinates().map_err(value_error)?;
+        inertia::inertia_tensor(&data).map_err(value_error)
+    }
+
+    /// Principal moments of inertia in u·Å² in increasing order.
+    fn principal_moments(&self) -> PyResult<[f64; 3]> {
+        let data = self.coordinates().map_err(value_error)?;
+        inertia::principal_moments(&data).map_err(value_error)
+    }
+
+    /// Rotational constants A, B, C in GHz, `None` for zero moments of inertia.
+    fn rotational_constants(&self) -> PyResult<[Option<f64>; 3]> {
+        let data = self.coordinates().map_err(value_error)?;
+        inertia::rotational_constants(&data).map_err(value_error)
+    }
+
+    /// Point group as the Schoenflies symbol with the symmetry elements through the centroid as (kind, direction,
+    /// order): "axis" and "improper_axis" with the order (0 for the axis of linear molecules), "mirror" with the
+    /// normal and "inversion" with zeros. Atoms within the distance tolerance are equivalent, see `find_duplicates`.
+    #[pyo3(signature = (tolerances=None))]
+    fn symmetry(&self, tolerances: Option<&str>) -> PyResult<(String, Vec<(String, [f64; 3], u32)>)> {
+        let tolerances = parse_tolerances(tolerances).map_err(value_error)?;
+        let data = self.coordinates().map_err(value_error)?;
+        let symmetry = Symmetry::from_coordinates(&data, tolerances.distance).map_err(value_error)?;
+        let elements = symmetry
+            .elements()
+            .into_iter()
+            .map(|element| match element {
+                SymmetryElement::Axis { direction, order } => ("axis".to_string(), direction, order),
+                SymmetryElement::ImproperAxis { direction, order } => ("improper_axis".to_string(), direction, order),
+                SymmetryElement::Mirror { normal } => ("mirror".to_string(), normal, 0),
+                SymmetryElement::Inversion => ("inversion".to_string(), [0.0; 3], 0),
+            })
+            .collect();
+        Ok((symmetry.point_group().to_string(), elements))
+    }
+
+    /// Cell lengths a, b, c in Angstroms and angles α, β, γ in degrees.
+    fn cell_parameters(&self) -> PyResult<Option<[f64; 6]>> {
+        let data = self.coordinates().map_err(value_error)?;
+        Ok(data.cell.map(|cell| cell.parameters()))
+    }
+}
+
+/// Node of the tree of an imported file, e.g. a molecule with its geometries and volume cubes.
+#[pyclass(module = "mircmd_chem")]
+struct Node {
+    #[pyo3(get)]
+    name: String,
+    #[pyo3(get, name = "type")]
+    kind: String,
+    /// Data of the node as JSON text.
+    #[pyo3(get)]
+    data: String,
+    children: Vec<Py<Node>>,
+    structure: Option<Py<Structure>>,
+}
+
+impl Node {
+    fn new(py: Python<'_>, node: types::Node) -> PyResult<Self> {
+        let structure = if node.r#type == ATOMIC_COORDINATES_TYPE {
+            let data: AtomicCoordinates = serde_json::from_slice(&node.data)
+                .map_err(|e| value_error(format!("Failed to deserialize coordinates of {}: {}", node.name, e)))?;
+            Some(Py::new(py, Structure::from_coordinates(node.name.clone(), data))?)
+        } else {
+            None
+        };
+        let children = node
+            .children
+            .into_iter()
+            .map(|child| Py::new(py, Node::new(py, child)?))
+            .collect::<PyResult<Vec<_>>>()?;
+        Ok(Self {
+            name: node.name,
+            kind: node.r#type,
+            data: String::from_utf8_lossy(&node.data).into_owned(),
+            children,
+            structure,
+        })
+    }
+
+    fn collect_structures(&self, py: Python<'_>, result: &mut Vec<Py<Structure>>) {
+        result.extend(self.structure.as_ref().map(|structure| structure.clone_ref(py)));
+        for child in &self.children {
+            child.borrow(py).collect_structures(py, result);
+        }
+    }
+}
+
+#[pymethods]
+impl Node {
+    #[getter]
+    fn children(&self, py: Python<'_>) -> Vec<Py<Node>> {
+        self.children.iter().map(|child| child.clone_ref(py)).collect()
+    }
+
+    /// Atomic coordinates of the node, `None` for other types of nodes.
+    #[getter]
+    fn structure(&self, py: Python<'_>) -> Option<Py<Structure>> {
+        self.structure.as_ref().map(|structure| structure.clone_ref(py))
+    }
+
+    /// Atomic coordinates of the node and of all its descendants in the order of the tree,
+    /// e.g. the frames of a trajectory.
+    fn structures(&self, py: Python<'_>) -> Vec<Py<Structure>> {
+        let mut result = Vec::new();
+        self.collect_structures(py, &mut result);
+        result
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Node(name={:?}, type={:?})", self.name, self.kind)
+    }
+}
+
+/// Names of the parsers in the order they are tried.
+#[pyfunction]
+fn parsers() -> Vec<&'static str> {
+    files_importer::parsers().collect()
+}
+
+/// Names of the export formats with the extensions of their files.
+#[pyfunction]
+fn export_formats() -> Vec<(&'static str, &'static str)> {
+    files_exporter::formats().collect()
+}
+
+/// Names and categories of the built-in templates, and whether each of them can be attached to an atom.
+#[pyfunction(name = "templates")]
+fn list_templates() -> Vec<(&'static str, &'static str, bool)> {
+    templates::templates()
+        .iter()
+        .map(|t| (t.name, t.category.name(), t.is_attachable()))
+        .collect()
+}
+
+/// The built-in template (a molecule or a functional group as its parent hydride) at a standard geometry.
+#[pyfunction]
+fn template(name: &str) -> PyResult<Structure> {
+    let template = templates::find_template(name).map_err(value_error)?;
+    Ok(Structure::from_coordinates(
+        template.name.to_string(),
+        template.coordinates(),
+    ))
+}
+
+/// Periodic box with the edges `lengths` in Angstroms of `count` solvent molecules at random positions and
+/// orientations without clashes, around the solute at the center if given. The same seed always gives
+/// the same box.
+#[pyfunction]
+#[pyo3(signature = (solvent, lengths, count, seed=0, solute=None))]
+fn solvent_box(
+    solvent: &Structure,
+    lengths: [f64; 3],
+    count: usize,
+    seed: u64,
+    solute: Option<&Structure>,
+) -> PyResult<Structure> {
+    let solute = solute.map(Structure::coordinates).transpose().map_err(value_error)?;
+    let result = generators::solvent_box(
+        solute.as_ref(),
+        &solvent.coordinates().map_err(value_error)?,
+        lengths,
+        count,
+        seed,
+    )
+    .map_err(value_error)?;
+    Ok(Structure::from_coordinates(format!("{} box", solvent.name), result))
+}
+
+/// For every structure the index of the first earlier structure it duplicates, `None` for the first ones,
+/// e.g. to drop repeated frames or conformers. `tolerances` is a profile name ("loose", "normal" or "strict",
+/// the default) or JSON changing any of "distance", "rmsd" and "convergence" of the normal profile.
+#[pyfunction]
+#[pyo3(signature = (structures, tolerances=None))]
+fn find_duplicates(structures: Vec<Structure>, tolerances: Option<&str>) -> PyResult<Vec<Option<usize>>> {
+    let tolerances = parse_tolerances(tolerances).map_err(value_error)?;
+    let frames = structures
+        .iter()
+        .map(Structure::coordinates)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(value_error)?;
+    Ok(comparison::find_duplicates(&frames, &tolerances))
+}
+
+/// Imports the file with the first parser that recognizes it, the file may be compressed.
+/// `options` are import options as JSON, the same as for load-with-options of the plugin.
+#[pyfunction]
+#[pyo3(signature = (path, options=None))]
+fn load(py: Python<'_>, path: &str, options: Option<&str>) -> PyResult<Node> {
+    let options = ImportOptions::from_json(options.unwrap_or("")).map_err(value_error)?;
+    let node = py
+        .allow_threads(|| files_importer::import_file(path, &options, &CancellationToken::none()))
+        .map_err(value_error)?;
+    Node::new(py, node)
+}
+
+/// Structures as a molecule node with a node of atomic coordinates for each of them, as the importer makes.
+/// The molecule has the total charge and the multiplicity, the lowest one for the electrons if not given.
+fn molecule_node(structures: &[Structure], charge: i32, multiplicity: Option<u32>) -> Result<types::Node, String> {
+    let first = structures
+        .first()
+        .ok_or_else(|| "No structures to export".to_string())?;
+    let molecule = types::Molecule {
+        n_atoms: first.atomic_num.len() as i32,
+        atomic_num: first.atomic_num.clone(),
+        charge,
+        name: first.name.clone(),
+        multiplicity,
+    };
+    Ok(types::Node {
+        name: first.name.clone(),
+        r#type: MOLECULE_TYPE.to_string(),
+        data: serde_json::to_vec(&molecule).map_err(|e| e.to_string())?,
+        children: structures.iter().map(Structure::node).collect::<Result<_, _>>()?,
+    })
+}
+
+/// Content of the structures in the export format, several structures are written as frames where the format
+/// supports them. `options` are export options as JSON, the same as for save-with-options of the plugin.
+/// The total charge and the multiplicity go to formats which store them, e.g. Gaussian input.
+#[pyfunction]
+#[pyo3(signature = (structures, format, options=None, charge=0, multiplicity=None))]
+fn export(
+    structures: Vec<Structure>,
+    format: &str,
+    options: Option<&str>,
+    charge: i32,
+    multiplicity: Option<u32>,
+) -> PyResult<String> {
+    let options = ExportOptions::from_json(options.unwrap_or("")).map_err(value_error)?;
+    let node = molecule_node(&structures, charge, multiplicity).map_err(value_error)?;
+    files_exporter::export_node_with_options(&node, format, &options).map_err(value_error)
+}
+
+/// Writes the structures to the file in the export format, see `export`.
+#[pyfunction]
+#[pyo3(signature = (structures, format, path, options=None, charge=0, multiplicity=None))]
+fn save(
+    structures: Vec<Structure>,
+    format: &str,
+    path: &str,
+    options: Option<&str>,
+    charge: i32,
+    multiplicity: Option<u32>,
+) -> PyResult<()> {
+    let content = export(structures, format, options, charge, multiplicity)?;
+    std::fs::write(path, content).map_err(|e| value_error(format!("Failed to write {}: {}", path, e)))
+}
+
+/// Parsers, writers and structure analysis of the Mir Commander chemistry plugins.
+#[pymodule]
+fn mircmd_chem(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add("DEFAULT_BOND_TOLERANCE", DEFAULT_BOND_TOLERANCE)?;
+    m.add_class::<Node>()?;
+    m.add_class::<Structure>()?;
+    m.add_function(wrap_pyfunction!(parsers, m)?)?;
+    m.add_function(wrap_pyfunction!(export_formats, m)?)?;
+    m.add_function(wrap_pyfunction!(load, m)?)?;
+    m.add_function(wrap_pyfunction!(export, m)?)?;
+    m.add_function(wrap_pyfunction!(save, m)?)?;
+    m.add_function(wrap_pyfunction!(list_templates, m)?)?;
+    m.add_function(wrap_pyfunction!(template, m)?)?;
+    m.add_function(wrap_pyfunction!(solvent_box, m)?)?;
+    m.add_function(wrap_pyfunction!(find_duplicates, m)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn templates_build_structures() {
+        let names = list_templates();
+        assert!(names.iter().any(|&(name, _, _)| name == "benzene"));
+        for (name, _, _) in names {
+            let structure = template(name).unwrap();
+            assert!(structure.__len__() > 0, "{}", name);
+            assert!(
+                structure
+                    .covalent_clashes(DEFAULT_COVALENT_CLASH_FRACTION)
+                    .unwrap()
+                    .is_empty(),
+                "{}",
+                name
+            );
+        }
+        assert!(template("unobtainium").is_err());
+    }
+
+    #[test]
+    fn water_geometry() {
+        let water = Structure::from_coordinates(
+            "water".to_string(),
+            AtomicCoordinates {
+                atomic_num: vec![8, 1, 1],
+                x: vec![0.0, 0.757, -0.757],
+                y: vec![0.0, 0.586, 0.586],
+                z: vec![0.0, 0.0, 0.0],
+                connectivity: None,
+                charges: None,
+                chains: None,
+                cell: None,
+                isotopes: None,
+            },
+        );
+        assert_eq!(water.formula(), "H2O");
+        assert_eq!(water.bonds(DEFAULT_BOND_TOLERANCE).unwrap().len(), 2);
+        assert!((water.distance(0, 1).unwrap() - 0.957).abs() < 1e-3);
+        assert!((water.angle(1, 0, 2).unwrap() - 104.5).abs() < 0.2);
+        assert!(water.clashes(DEFAULT_CLASH_FRACTION).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,91 @@
+// Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
+// Licensed under the MIT License
+
+#[cfg(feature = "plugin")]
+#[allow(warnings)]
+mod bindings {
+    wit_bindgen::generate!({
+        path: "wit",
+        world: "plugin",
+        generate_all,
+    });
+
+    use super::ChemistryExporter;
+
+    export!(ChemistryExporter);
+}
+
+pub mod options;
+mod writers;
+
+#[cfg(feature = "plugin")]
+use bindings::Guest;
+use options::{CoordinateOptions, ExportOptions};
+use shared_lib::ordering::{self, AtomOrder};
+use shared_lib::types;
+
+#[cfg(feature = "plugin")]
+struct ChemistryExporter;
+
+type WriterFn = fn(&types::Node, &CoordinateOptions) -> Result<String, String>;
+
+// Format name, file extension and writer
+const WRITERS: &[(&str, &str, WriterFn)] = &[
+    ("xyz", "xyz", writers::xyz::write),
+    ("mdlmol2000", "mol", writers::mdlmol2000::write),
+    ("pdb", "pdb", writers::pdb::write),
+    ("gaussian", "gjf", writers::gaussian::write),
+];
+
+/// Names of the export formats with the extensions of their files, e.g. ("mdlmol2000", "mol").
+pub fn formats() -> impl Iterator<Item = (&'static str, &'static str)> {
+    WRITERS.iter().map(|&(name, extension, _)| (name, extension))
+}
+
+/// Content of the file in the format (case insensitive) with its default precision, unit and layout.
+pub fn export_node(node: &types::Node, format: &str) -> Result<String, String> {
+    export_node_with_options(node, format, &ExportOptions::default())
+}
+
+/// Content of the file in the format (case insensitive), the same for the host and for native tools.
+pub fn export_node_with_options(node: &types::Node, format: &str, options: &ExportOptions) -> Result<String, String> {
+    let write_fn = WRITERS
+        .iter()
+        .find(|(name, _, _)| name.eq_ignore_ascii_case(format))
+        .map(|(_, _, write_fn)| write_fn)
+        .ok_or_else(|| format!("Unsupported export format {}.", format))?;
+    match &options.order {
+        Some(order) => write_fn(&reorder_node(node, order)?.0, &options.coordinates(format)),
+        None => write_fn(node, &options.coordinates(format)),
+    }
+}
+
+/// The node with the atoms of all its structures in the order, which is found for the first structure and
+/// applied to the others, e.g. to the frames of a trajectory. Returns the node with the zero-based original
+/// index of every atom in the new order.
+pub fn reorder_node(node: &types::Node, order: &AtomOrder) -> Result<(types::Node, Vec<usize>), String> {
+    let first = writers::collect_coordinates(node)?.swap_remove(0).1;
+    let permutation = ordering::atom_order(&first, order)?;
+    Ok((writers::reorder_node(node, &permutation)?, permutation))
+}
+
+#[cfg(feature = "plugin")]
+fn save(node_json: &[u8], format: &str, file_path: &str, options: &ExportOptions) -> Result<(), String> {
+    let node: types::Node =
+        serde_json::from_slice(node_json).map_err(|e| format!("Failed to deserialize node: {}", e))?;
+
+    let content = export_node_with_options(&node, format, options)?;
+    std::fs::write(file_path, content).map_err(|e| e.to_string())
+}
+
+#[cfg(feature = "plugin")]
+impl Guest for ChemistryExporter {
+    #[cfg(feature = "plugin")]
+fn save(node_json: Vec<u8>, format: String, file_path: String) -> Result<(), String> {
+        save(&node_json, &format, &file_path, &ExportOptions::default())
+    }
+
+    fn save_with_options(node_json: Vec<u8>, format: String, file_path: String, options: String) -> Result<(), String> {
+        save(&node_json, &format, &file_path, &ExportOptions::from_json(&options)?)
+    }
+}
@@ -0,0 +1,80 @@
+// Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
+// Licensed under the MIT License
+
+use serde::Deserialize;
+use shared_lib::ordering::AtomOrder;
+
+const BOHR2ANGSTROM: f64 = 0.529177210903;
+
+/// Options of a single export call, passed by the host as JSON, e.g.
+/// `{"xyz": {"precision": 10, "unit": "bohr"}, "gaussian": {"layout": "free"}, "order": "element"}`.
+/// Every section and field is optional, missing ones take the defaults of the format.
+#[derive(Default, Deserialize)]
+#[serde(default)]
+pub struct ExportOptions {
+    /// Order of the atoms in the file, the order of the structure if absent. Manual orders are given as
+    /// `{"manual": [3, 1, 2]}` with atom indices starting from 1.
+    pub order: Option<AtomOrder>,
+    pub xyz: CoordinateOptions,
+    pub mdlmol2000: CoordinateOptions,
+    pub pdb: CoordinateOptions,
+    pub gaussian: CoordinateOptions,
+}
+
+impl ExportOptions {
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        if json.trim().is_empty() {
+            return Ok(Self::default());
+        }
+        serde_json::from_str(json).map_err(|e| format!("Invalid export options: {}", e))
+    }
+
+    /// Options of the format (case insensitive), the defaults for formats without a section.
+    pub fn coordinates(&self, format: &str) -> CoordinateOptions {
+        match format.to_ascii_lowercase().as_str() {
+            "xyz" => self.xyz,
+            "mdlmol2000" => self.mdlmol2000,
+            "pdb" => self.pdb,
+            "gaussian" => self.gaussian,
+            _ => CoordinateOptions::default(),
+        }
+    }
+}
+
+#[derive(Default, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum LengthUnit {
+    #[default]
+    Angstrom,
+    Bohr,
+}
+
+impl LengthUnit {
+    /// Factor converting Angstroms to values in this unit.
+    pub fn from_angstrom(self) -> f64 {
+        match self {
+            LengthUnit::Angstrom => 1.0,
+            LengthUnit::Bohr => 1.0 / BOHR2ANGSTROM,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum Layout {
+    /// Values are right-aligned in columns of constant width.
+    Fixed,
+    /// Values are separated by single spaces.
+    Free,
+}
+
+#[derive(Default, Deserialize, Clone, Copy)]
+#[serde(default)]
+pub struct CoordinateOptions {
+    /// Decimals of coordinates, the default of the format if absent.
+    pub precision: Option<usize>,
+    /// Unit of coordinates in the file, only formats which can declare or assume Bohrs accept it.
+    pub unit: LengthUnit,
+    /// Layout of coordinate columns, the default of the format if absent.
+    pub layout: Option<Layout>,
+}
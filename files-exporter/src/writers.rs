@@ -0,0 +1,218 @@
+// Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
+// Licensed under the MIT License
+
+pub mod gaussian;
+pub mod mdlmol2000;
+pub mod pdb;
+pub mod xyz;
+
+use shared_lib::ordering;
+pub use shared_lib::periodic_table::element_symbol;
+use shared_lib::types::{AtomicCoordinates, Molecule, Node};
+
+use crate::options::{CoordinateOptions, Layout, LengthUnit};
+
+const ATOMIC_COORDINATES_TYPE: &str = "mircmd:chemistry:atomic_coordinates";
+const MOLECULE_TYPE: &str = "mircmd:chemistry:molecule";
+// Decimals beyond the precision of doubles are noise
+const MAX_PRECISION: usize = 15;
+
+/// Collects all atomic coordinates sets of the tree in depth-first order as (name, coordinates) pairs.
+pub fn collect_coordinates(node: &Node) -> Result<Vec<(&str, AtomicCoordinates)>, String> {
+    let mut result = Vec::new();
+    collect_coordinates_into(node, &mut result)?;
+
+    if result.is_empty() {
+        return Err(format!("No atomic coordinates found in {}.", node.name));
+    }
+    Ok(result)
+}
+
+fn collect_coordinates_into<'a>(node: &'a Node, result: &mut Vec<(&'a str, AtomicCoordinates)>) -> Result<(), String> {
+    if node.r#type == ATOMIC_COORDINATES_TYPE {
+        let coords: AtomicCoordinates = serde_json::from_slice(&node.data)
+            .map_err(|e| format!("Failed to deserialize coordinates of {}: {}", node.name, e))?;
+        check_coordinates(&node.name, &coords)?;
+        result.push((node.name.as_str(), coords));
+    }
+
+    for child in &node.children {
+        collect_coordinates_into(child, result)?;
+    }
+    Ok(())
+}
+
+/// Rejects sets which no writer can represent: without atoms, with missing or non-finite coordinates, with
+/// isotope labels which are no isotopes of their elements.
+fn check_coordinates(name: &str, coords: &AtomicCoordinates) -> Result<(), String> {
+    let num_atoms = coords.atomic_num.len();
+    if num_atoms == 0 {
+        return Err(format!("No atoms in {}.", name));
+    }
+    if coords.x.len() != num_atoms || coords.y.len() != num_atoms || coords.z.len() != num_atoms {
+        return Err(format!("Numbers of atoms and coordinates differ in {}.", name));
+    }
+    coords.mass_numbers().map_err(|e| format!("{} in {}.", e, name))?;
+    let atom = (0..num_atoms).find(|&i| ![coords.x[i], coords.y[i], coords.z[i]].iter().all(|v| v.is_finite()));
+    match atom {
+        Some(i) => Err(format!("Invalid coordinates of atom {} in {}.", i + 1, name)),
+        None => Ok(()),
+    }
+}
+
+/// Copy of the tree with the atoms of the structures and of the molecules permuted, `order` holds the original
+/// index of every atom in the new order.
+pub fn reorder_node(node: &Node, order: &[usize]) -> Result<Node, String> {
+    let data = match node.r#type.as_str() {
+        ATOMIC_COORDINATES_TYPE => {
+            let coords: AtomicCoordinates = serde_json::from_slice(&node.data)
+                .map_err(|e| format!("Failed to deserialize coordinates of {}: {}", node.name, e))?;
+            check_order(&node.name, coords.atomic_num.len(), order)?;
+            let coords = ordering::reorder(&coords, order).map_err(|e| format!("{} in {}.", e, node.name))?;
+            serde_json::to_vec(&coords).map_err(|e| e.to_string())?
+        }
+        MOLECULE_TYPE => match serde_json::from_slice::<Molecule>(&node.data) {
+            Ok(mut molecule) => {
+                check_order(&node.name, molecule.atomic_num.len(), order)?;
+                molecule.atomic_num = ordering::permute(&molecule.atomic_num, order);
+                serde_json::to_vec(&molecule).map_err(|e| e.to_string())?
+            }
+            Err(_) => node.data.clone(),
+        },
+        _ => node.data.clone(),
+    };
+    Ok(Node {
+        name: node.name.clone(),
+        r#type: node.r#type.clone(),
+        data,
+        children: node
+            .children
+            .iter()
+            .map(|child| reorder_node(child, order))
+            .collect::<Result<_, _>>()?,
+    })
+}
+
+fn check_order(name: &str, num_atoms: usize, order: &[usize]) -> Result<(), String> {
+    if num_atoms != order.len() {
+        return Err(format!(
+            "{} has {} atoms instead of {}, its atoms cannot be reordered as the first structure.",
+            name,
+            num_atoms,
+            order.len()
+        ));
+    }
+    Ok(())
+}
+
+/// Coordinate columns of a file, resolved from the export options and the defaults of the format.
+pub struct Columns {
+    format: &'static str,
+    precision: usize,
+    // Width of a column, `None` for values separated by single spaces
+    width: Option<usize>,
+    // Longest value the columns of a fixed-column format hold, longer ones are errors
+    limit: Option<usize>,
+    scale: f64,
+}
+
+impl Columns {
+    /// Columns of formats whose readers split lines by whitespace. Fixed columns are aligned for values below 10⁵,
+    /// wider values only shift the rest of the line.
+    pub fn free_format(
+        options: &CoordinateOptions,
+        default_precision: usize,
+        format: &'static str,
+    ) -> Result<Self, String> {
+        let precision = options.precision.unwrap_or(default_precision);
+        if precision > MAX_PRECISION {
+            return Err(format!(
+                "{} coordinates are written with at most {} decimals, requested {}.",
+                format, MAX_PRECISION, precision
+            ));
+        }
+        Ok(Self {
+            format,
+            precision,
+            width: match options.layout.unwrap_or(Layout::Fixed) {
+                Layout::Fixed => Some(precision + 7),
+                Layout::Free => None,
+            },
+            limit: None,
+            scale: options.unit.from_angstrom(),
+        })
+    }
+
+    /// Columns of `width` characters in Angstroms as the format requires, values must leave `blank` leading
+    /// characters of each column empty.
+    pub fn fixed(
+        options: &CoordinateOptions,
+        default_precision: usize,
+        width: usize,
+        blank: usize,
+        format: &'static str,
+    ) -> Result<Self, String> {
+        if options.unit != LengthUnit::Angstrom {
+            return Err(format!("{} stores coordinates only in Angstroms.", format));
+        }
+        if options.layout == Some(Layout::Free) {
+            return Err(format!("{} supports only the fixed layout.", format));
+        }
+        let limit = width - blank;
+        // Sign, a digit and the decimal point
+        let max_precision = limit - 3;
+        let precision = options.precision.unwrap_or(default_precision);
+        if precision > max_precision {
+            return Err(format!(
+                "Precision {} does not fit into the {}-character columns of {}, at most {} decimals.",
+                precision, width, format, max_precision
+            ));
+        }
+        Ok(Self {
+            format,
+            precision,
+            width: Some(width),
+            limit: Some(limit),
+            scale: 1.0,
+        })
+    }
+
+    /// Value converted to the unit of the file, padded to the column width.
+    pub fn column(&self, value: f64) -> String {
+        match self.width {
+            Some(width) => format!("{:>width$}", self.value(value)),
+            None => self.value(value),
+        }
+    }
+
+    /// Value converted to the unit of the file without padding.
+    pub fn value(&self, value: f64) -> String {
+        format!("{:.*}", self.precision, value * self.scale)
+    }
+
+    /// Checks that the coordinates fit into the columns of a fixed-column format.
+    pub fn check(&self, coords: &AtomicCoordinates) -> Result<(), String> {
+        let Some(limit) = self.limit else {
+            return Ok(());
+        };
+        let fits = |v: f64| self.value(v).len() <= limit;
+        match (0..coords.atomic_num.len()).find(|&i| !(fits(coords.x[i]) && fits(coords.y[i]) && fits(coords.z[i]))) {
+            Some(i) => Err(format!(
+                "Coordinates of atom {} are too large for {} format.",
+                i + 1,
+                self.format
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Returns the first molecule found in the tree, if any.
+pub fn find_molecule(node: &Node) -> Option<Molecule> {
+    if node.r#type == MOLECULE_TYPE
+        && let Ok(molecule) = serde_json::from_slice::<Molecule>(&node.data)
+    {
+        return Some(molecule);
+    }
+    node.children.iter().find_map(find_molecule)
+}
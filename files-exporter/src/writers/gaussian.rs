@@ -0,0 +1,58 @@
+// Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
+// Licensed under the MIT License
+
+use std::fmt::Write;
+
+use shared_lib::properties::{check_multiplicity, lowest_multiplicity};
+use shared_lib::types::Node;
+
+use super::{Columns, collect_coordinates, element_symbol, find_molecule};
+use crate::options::{CoordinateOptions, LengthUnit};
+
+const DEFAULT_ROUTE: &str = "#P HF/STO-3G SP";
+
+/// Writes the first coordinate set of the tree as Gaussian input file.
+/// Charge and multiplicity are taken from the molecule, the multiplicity is the lowest one compatible with
+/// the number of electrons if the molecule has none. Labelled atoms are given their isotopes, e.g. `H(Iso=2)`.
+pub fn write(node: &Node, options: &CoordinateOptions) -> Result<String, String> {
+    let columns = Columns::free_format(options, 8, "Gaussian input")?;
+    let coords = collect_coordinates(node)?;
+    let (name, coords) = &coords[0];
+    let molecule = find_molecule(node);
+    let charge = molecule.as_ref().map_or(0, |m| m.charge);
+    let multiplicity = molecule
+        .and_then(|m| m.multiplicity)
+        .unwrap_or_else(|| lowest_multiplicity(&coords.atomic_num, charge));
+    check_multiplicity(&coords.atomic_num, charge, multiplicity)?;
+
+    let mut result = String::new();
+    let units = match options.unit {
+        LengthUnit::Angstrom => "",
+        LengthUnit::Bohr => " Units=Bohr",
+    };
+    let _ = writeln!(result, "{}{}", DEFAULT_ROUTE, units);
+    let _ = writeln!(result);
+    let _ = writeln!(result, "{}", name);
+    let _ = writeln!(result);
+    let _ = writeln!(result, "{} {}", charge, multiplicity);
+
+    let mass_numbers = coords.mass_numbers()?;
+    for (i, &mass_number) in mass_numbers.iter().enumerate() {
+        let symbol = match mass_number {
+            0 => element_symbol(coords.atomic_num[i]).to_string(),
+            mass_number => format!("{}(Iso={})", element_symbol(coords.atomic_num[i]), mass_number),
+        };
+        let _ = writeln!(
+            result,
+            "{:<3} {} {} {}",
+            symbol,
+            columns.column(coords.x[i]),
+            columns.column(coords.y[i]),
+            columns.column(coords.z[i])
+        );
+    }
+
+    // Gaussian requires a blank line after the molecule specification.
+    let _ = writeln!(result);
+    Ok(result)
+}
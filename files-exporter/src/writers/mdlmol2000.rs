@@ -0,0 +1,166 @@
+// Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
+// Licensed under the MIT License
+
+use std::fmt::Write;
+
+use shared_lib::periodic_table::get_element_by_number;
+use shared_lib::types::{AtomicCoordinates, Connection, Node};
+
+use super::{Columns, collect_coordinates, element_symbol};
+use crate::options::CoordinateOptions;
+
+// V2000 counts line stores numbers of atoms and bonds in 3 characters.
+const MAX_ATOMS: usize = 999;
+const BOND_TOLERANCE: f64 = 0.15;
+// Entries of a M  CHG or M  ISO line and the largest charge it holds
+const VALUES_PER_LINE: usize = 8;
+const MAX_CHARGE: f64 = 15.0;
+
+/// Writes the first coordinate set of the tree as MDL Mol V2000 file.
+/// Integer charges of the atoms are written as formal charges, partial charges are left out. Mass numbers of
+/// labelled atoms are written as M  ISO.
+pub fn write(node: &Node, options: &CoordinateOptions) -> Result<String, String> {
+    // One column of the 10 is kept blank, readers often split the atom lines by whitespace
+    let columns = Columns::fixed(options, 4, 10, 1, "MDL Mol V2000")?;
+    let coords = collect_coordinates(node)?;
+    let (name, coords) = &coords[0];
+    let num_atoms = coords.atomic_num.len();
+
+    if num_atoms > MAX_ATOMS {
+        return Err(format!(
+            "MDL Mol V2000 supports at most {} atoms, found {}.",
+            MAX_ATOMS, num_atoms
+        ));
+    }
+    columns.check(coords)?;
+
+    let bonds = match &coords.connectivity {
+        Some(connectivity) => connectivity.clone(),
+        None => find_bonds(coords),
+    };
+    if bonds.len() > MAX_ATOMS {
+        return Err(format!(
+            "MDL Mol V2000 supports at most {} bonds, found {}.",
+            MAX_ATOMS,
+            bonds.len()
+        ));
+    }
+    if let Some(bond) = bonds.iter().find(|b| b.atom_1 >= num_atoms || b.atom_2 >= num_atoms) {
+        return Err(format!(
+            "Bond {}-{} refers to a missing atom.",
+            bond.atom_1 + 1,
+            bond.atom_2 + 1
+        ));
+    }
+
+    let mut result = String::new();
+    let _ = writeln!(result, "{}", name);
+    let _ = writeln!(result, "  mircmd");
+    let _ = writeln!(result);
+    let _ = writeln!(
+        result,
+        "{:>3}{:>3}  0  0  0  0  0  0  0  0999 V2000",
+        num_atoms,
+        bonds.len()
+    );
+
+    let charges = formal_charges(coords);
+    for i in 0..num_atoms {
+        // Charge code of the atom block: 1, 2, 3 are +3, +2, +1; 5, 6, 7 are -1, -2, -3
+        let code = match charges.get(i) {
+            Some(&charge @ -3..=3) if charge != 0 => 4 - charge,
+            _ => 0,
+        };
+        let _ = writeln!(
+            result,
+            "{}{}{} {:<3} 0{:>3}  0  0  0  0  0  0  0  0  0  0",
+            columns.column(coords.x[i]),
+            columns.column(coords.y[i]),
+            columns.column(coords.z[i]),
+            element_symbol(coords.atomic_num[i]),
+            code
+        );
+    }
+
+    for bond in bonds {
+        let _ = writeln!(
+            result,
+            "{:>3}{:>3}{:>3}  0  0  0  0",
+            bond.atom_1 + 1,
+            bond.atom_2 + 1,
+            bond.order
+        );
+    }
+
+    // Readers take all charges from the property block if it has any, including the ones beyond ±3
+    let charged: Vec<(usize, i32)> = charges
+        .iter()
+        .enumerate()
+        .filter(|&(_, &charge)| charge != 0)
+        .map(|(i, &charge)| (i + 1, charge))
+        .collect();
+    write_atom_values(&mut result, "CHG", &charged);
+    let labelled: Vec<(usize, i32)> = coords
+        .mass_numbers()?
+        .iter()
+        .enumerate()
+        .filter(|&(_, &mass_number)| mass_number != 0)
+        .map(|(i, &mass_number)| (i + 1, mass_number as i32))
+        .collect();
+    write_atom_values(&mut result, "ISO", &labelled);
+
+    let _ = writeln!(result, "M  END");
+    Ok(result)
+}
+
+/// Lines of the property block with the values of the atoms (index starts from 1), e.g. `M  CHG  1   2  -1`.
+fn write_atom_values(result: &mut String, property: &str, values: &[(usize, i32)]) {
+    for chunk in values.chunks(VALUES_PER_LINE) {
+        let _ = write!(result, "M  {}{:>3}", property, chunk.len());
+        for (atom, value) in chunk {
+            let _ = write!(result, " {:>3} {:>3}", atom, value);
+        }
+        let _ = writeln!(result);
+    }
+}
+
+/// Charges of the atoms if all of them are integers within ±15 (the range of M  CHG), empty for partial charges.
+fn formal_charges(coords: &AtomicCoordinates) -> Vec<i32> {
+    let Some(charges) = &coords.charges else {
+        return Vec::new();
+    };
+    let is_formal = |c: &f64| (c - c.round()).abs() < 1e-6 && c.abs() <= MAX_CHARGE;
+    if charges.len() != coords.atomic_num.len() || !charges.iter().all(is_formal) {
+        return Vec::new();
+    }
+    charges.iter().map(|c| c.round() as i32).collect()
+}
+
+/// Finds single bonds between atoms closer than the sum of their covalent radii (with tolerance).
+fn find_bonds(coords: &AtomicCoordinates) -> Vec<Connection> {
+    let radii: Vec<Option<f64>> = coords
+        .atomic_num
+        .iter()
+        .map(|&n| get_element_by_number(n).filter(|_| n > 0).map(|e| e.covalent_radius))
+        .collect();
+
+    let mut result = Vec::new();
+    for i in 0..radii.len() {
+        let Some(r_i) = radii[i] else { continue };
+        for (j, r_j) in radii.iter().enumerate().skip(i + 1) {
+            let Some(r_j) = *r_j else { continue };
+            let dx = coords.x[i] - coords.x[j];
+            let dy = coords.y[i] - coords.y[j];
+            let dz = coords.z[i] - coords.z[j];
+            let limit = (r_i + r_j) * (1.0 + BOND_TOLERANCE);
+            if dx * dx + dy * dy + dz * dz <= limit * limit {
+                result.push(Connection {
+                    atom_1: i,
+                    atom_2: j,
+                    order: 1,
+                });
+            }
+        }
+    }
+    result
+}
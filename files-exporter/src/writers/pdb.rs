@@ -0,0 +1,70 @@
+// Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
+// Licensed under the MIT License
+
+use std::fmt::Write;
+
+use shared_lib::types::{AtomicCoordinates, Node};
+
+use super::{Columns, collect_coordinates, element_symbol};
+use crate::options::CoordinateOptions;
+
+// Atom serial number occupies 5 columns.
+const MAX_SERIAL: usize = 99_999;
+
+/// Writes coordinate sets of the tree as PDB file, one MODEL record per set.
+pub fn write(node: &Node, options: &CoordinateOptions) -> Result<String, String> {
+    let columns = Columns::fixed(options, 3, 8, 0, "PDB")?;
+    let coords = collect_coordinates(node)?;
+    let multiple_models = coords.len() > 1;
+    for (_, set) in &coords {
+        columns.check(set)?;
+    }
+
+    let mut result = String::new();
+    let _ = writeln!(result, "COMPND    {}", node.name);
+    // Space group is unknown, the cell is written as P 1 with one molecule
+    if let Some(cell) = coords.first().and_then(|(_, set)| set.cell) {
+        let [a, b, c, alpha, beta, gamma] = cell.parameters();
+        let _ = writeln!(
+            result,
+            "CRYST1{:>9.3}{:>9.3}{:>9.3}{:>7.2}{:>7.2}{:>7.2} {:<11}{:>4}",
+            a, b, c, alpha, beta, gamma, "P 1", 1
+        );
+    }
+
+    for (model_number, (_, set)) in coords.iter().enumerate() {
+        if multiple_models {
+            let _ = writeln!(result, "MODEL     {:>4}", model_number + 1);
+        }
+        write_atoms(&mut result, set, &columns);
+        if multiple_models {
+            let _ = writeln!(result, "ENDMDL");
+        }
+    }
+
+    let _ = writeln!(result, "END");
+    Ok(result)
+}
+
+fn write_atoms(result: &mut String, coords: &AtomicCoordinates, columns: &Columns) {
+    for i in 0..coords.atomic_num.len() {
+        let symbol = element_symbol(coords.atomic_num[i]);
+        // One-letter element names start at column 14, two-letter ones at column 13.
+        let atom_name = if symbol.len() == 1 {
+            format!(" {:<3}", symbol)
+        } else {
+            format!("{:<4}", symbol)
+        };
+
+        let _ = writeln!(
+            result,
+            "HETATM{:>5} {} MOL A   1    {}{}{}  1.00  0.00          {:>2}",
+            (i % MAX_SERIAL) + 1,
+            atom_name,
+            columns.column(coords.x[i]),
+            columns.column(coords.y[i]),
+            columns.column(coords.z[i]),
+            symbol.to_uppercase()
+        );
+    }
+}
@@ -0,0 +1,54 @@
+// Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
+// Licensed under the MIT License
+
+use std::fmt::Write;
+
+use shared_lib::periodic_table::isotope_label;
+use shared_lib::types::Node;
+
+use super::{Columns, collect_coordinates, element_symbol};
+use crate::options::CoordinateOptions;
+
+/// Writes all coordinate sets of the tree as consecutive XYZ frames, sets with a periodic cell
+/// as extended XYZ frames. Labelled atoms are written with their isotope labels, e.g. D or 13C.
+pub fn write(node: &Node, options: &CoordinateOptions) -> Result<String, String> {
+    let columns = Columns::free_format(options, 8, "XYZ")?;
+    let mut result = String::new();
+
+    for (name, coords) in collect_coordinates(node)? {
+        let _ = writeln!(result, "{}", coords.atomic_num.len());
+        match &coords.cell {
+            // Extended XYZ, the name is kept as a comment property
+            Some(cell) => {
+                let vectors: Vec<String> = cell.vectors.iter().flatten().map(|&v| columns.value(v)).collect();
+                let _ = writeln!(
+                    result,
+                    "Lattice=\"{}\" Properties=species:S:1:pos:R:3 Comment=\"{}\"",
+                    vectors.join(" "),
+                    name.replace('"', "'")
+                );
+            }
+            None => {
+                let _ = writeln!(result, "{}", name);
+            }
+        }
+
+        let mass_numbers = coords.mass_numbers()?;
+        for (i, &mass_number) in mass_numbers.iter().enumerate() {
+            let symbol = match mass_number {
+                0 => None,
+                mass_number => isotope_label(coords.atomic_num[i], mass_number),
+            };
+            let _ = writeln!(
+                result,
+                "{:<3} {} {} {}",
+                symbol.as_deref().unwrap_or(element_symbol(coords.atomic_num[i])),
+                columns.column(coords.x[i]),
+                columns.column(coords.y[i]),
+                columns.column(coords.z[i])
+            );
+        }
+    }
+
+    Ok(result)
+}
@@ -0,0 +1,748 @@
+// Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
+// Licensed under the MIT License
+
+//! Property tests of the writers: random structures are written, parsed back and compared,
+//! and malformed or extreme inputs must give either an error or a file which reads back unchanged.
+
+use files_exporter::options::ExportOptions;
+use files_exporter::{export_node, export_node_with_options, formats};
+use files_importer::import_content;
+use files_importer::options::{ImportOptions, LengthUnit};
+use shared_lib::cancellation::CancellationToken;
+use shared_lib::cell::UnitCell;
+use shared_lib::ordering::{self, AtomOrder};
+use shared_lib::periodic_table::get_element_by_symbol;
+use shared_lib::random::Rng;
+use shared_lib::types::{AtomicCoordinates, Connection, Molecule, Node};
+
+const MOLECULE_TYPE: &str = "mircmd:chemistry:molecule";
+const ATOMIC_COORDINATES_TYPE: &str = "mircmd:chemistry:atomic_coordinates";
+const CASES: u64 = 64;
+const BOHR2ANGSTROM: f64 = 0.529177210903;
+
+struct Format {
+    name: &'static str,
+    // Decimals of the written coordinates
+    precision: i32,
+    // Coordinates up to this magnitude always fit into the columns
+    extent: f64,
+    // Most decimals the columns hold
+    max_precision: usize,
+    all_sets: bool,
+    bonds: bool,
+    cell: bool,
+    // Coordinates may be written in Bohrs and separated by single spaces
+    free_format: bool,
+}
+
+const FORMATS: &[Format] = &[
+    Format {
+        name: "xyz",
+        precision: 8,
+        extent: f64::INFINITY,
+        max_precision: 15,
+        all_sets: true,
+        bonds: false,
+        cell: true,
+        free_format: true,
+    },
+    Format {
+        name: "mdlmol2000",
+        precision: 4,
+        extent: 999.0,
+        max_precision: 6,
+        all_sets: false,
+        bonds: true,
+        cell: false,
+        free_format: false,
+    },
+    Format {
+        name: "pdb",
+        precision: 3,
+        extent: 999.0,
+        max_precision: 5,
+        all_sets: true,
+        bonds: false,
+        cell: false,
+        free_format: false,
+    },
+    Format {
+        name: "gaussian",
+        precision: 8,
+        extent: f64::INFINITY,
+        max_precision: 15,
+        all_sets: false,
+        bonds: false,
+        cell: false,
+        free_format: true,
+    },
+];
+
+fn random_coords(rng: &mut Rng, extent: f64) -> AtomicCoordinates {
+    let num_atoms = 1 + rng.below(40);
+    // Mostly real elements, a few dummy (X) and ghost (Q) atoms
+    let atomic_num: Vec<i32> = (0..num_atoms)
+        .map(|_| match rng.below(20) {
+            0 => -1,
+            1 => -2,
+            _ => 1 + rng.below(86) as i32,
+        })
+        .collect();
+    let mut coordinate = || {
+        (0..num_atoms)
+            .map(|_| rng.uniform(-extent, extent))
+            .collect::<Vec<f64>>()
+    };
+    let (x, y, z) = (coordinate(), coordinate(), coordinate());
+
+    let connectivity = (num_atoms > 1 && rng.below(2) == 0).then(|| {
+        (0..1 + rng.below(num_atoms))
+            .map(|_| {
+                let atom_1 = rng.below(num_atoms);
+                let atom_2 = (atom_1 + 1 + rng.below(num_atoms - 1)) % num_atoms;
+                Connection {
+                    atom_1,
+                    atom_2,
+                    order: 1 + rng.below(4) as u8,
+                }
+            })
+            .collect()
+    });
+    let cell = (rng.below(4) == 0).then(|| {
+        let length = || 2.0 * extent + 1.0;
+        UnitCell::from_parameters([length(), length(), length(), 90.0, 90.0, 90.0]).unwrap()
+    });
+
+    AtomicCoordinates {
+        atomic_num,
+        x,
+        y,
+        z,
+        connectivity,
+        charges: None,
+        chains: None,
+        cell,
+        isotopes: None,
+    }
+}
+
+fn molecule_node(sets: &[AtomicCoordinates]) -> Node {
+    let atomic_num = sets.first().map(|set| set.atomic_num.clone()).unwrap_or_default();
+    let molecule = Molecule {
+        n_atoms: atomic_num.len() as i32,
+        atomic_num,
+        charge: 0,
+        name: "random".to_string(),
+        multiplicity: None,
+    };
+    Node {
+        name: "random".to_string(),
+        r#type: MOLECULE_TYPE.to_string(),
+        data: serde_json::to_vec(&molecule).unwrap(),
+        children: sets
+            .iter()
+            .enumerate()
+            .map(|(i, set)| Node {
+                name: format!("set {}", i + 1),
+                r#type: ATOMIC_COORDINATES_TYPE.to_string(),
+                data: serde_json::to_vec(set).unwrap(),
+                children: vec![],
+            })
+            .collect(),
+    }
+}
+
+fn collect_coordinates(node: &Node, result: &mut Vec<AtomicCoordinates>) {
+    if node.r#type == ATOMIC_COORDINATES_TYPE {
+        result.push(serde_json::from_slice(&node.data).unwrap());
+    }
+    for child in &node.children {
+        collect_coordinates(child, result);
+    }
+}
+
+/// Parses the written content with the importer and collects all coordinate sets.
+fn import(content: &str, file_name: &str, options: &ImportOptions) -> Result<Vec<AtomicCoordinates>, String> {
+    let node = import_content(content, file_name, options, &CancellationToken::none())?;
+    let mut result = Vec::new();
+    collect_coordinates(&node, &mut result);
+    Ok(result)
+}
+
+fn parse_symbol(symbol: &str) -> Result<i32, String> {
+    let mut chars = symbol.chars();
+    let symbol: String = chars
+        .next()
+        .into_iter()
+        .chain(chars.flat_map(char::to_lowercase))
+        .collect();
+    get_element_by_symbol(&symbol)
+        .map(|e| e.atomic_number)
+        .ok_or_else(|| format!("Unknown element {}", symbol))
+}
+
+fn parse_number(text: &str) -> Result<f64, String> {
+    text.trim().parse().map_err(|_| format!("Invalid number {:?}", text))
+}
+
+fn new_set() -> AtomicCoordinates {
+    AtomicCoordinates {
+        atomic_num: vec![],
+        x: vec![],
+        y: vec![],
+        z: vec![],
+        connectivity: None,
+        charges: None,
+        chains: None,
+        cell: None,
+        isotopes: None,
+    }
+}
+
+/// Reads HETATM records by their columns, MODEL records start new sets.
+fn read_pdb(content: &str) -> Result<Vec<AtomicCoordinates>, String> {
+    let mut result = vec![];
+    for line in content.lines() {
+        if line.starts_with("MODEL") || (result.is_empty() && line.starts_with("HETATM")) {
+            result.push(new_set());
+        }
+        if !line.starts_with("HETATM") {
+            continue;
+        }
+        let column = |start: usize, end: usize| line.get(start..end).ok_or_else(|| format!("Short record {}", line));
+        let set = result.last_mut().unwrap();
+        set.x.push(parse_number(column(30, 38)?)?);
+        set.y.push(parse_number(column(38, 46)?)?);
+        set.z.push(parse_number(column(46, 54)?)?);
+        set.atomic_num.push(parse_symbol(column(76, 78)?.trim())?);
+    }
+    Ok(result)
+}
+
+/// Reads the molecule specification after the charge and multiplicity line up to the blank line.
+fn read_gaussian(content: &str) -> Result<Vec<AtomicCoordinates>, String> {
+    let mut set = new_set();
+    let to_angstrom = if content.lines().next().is_some_and(|route| route.contains("Units=Bohr")) {
+        BOHR2ANGSTROM
+    } else {
+        1.0
+    };
+    let cards = content.split("\n\n").nth(2).ok_or("No molecule specification")?;
+    for line in cards.lines().skip(1) {
+        let items: Vec<&str> = line.split_whitespace().collect();
+        let [symbol, x, y, z] = items[..] else {
+            return Err(format!("Invalid atom card {}", line));
+        };
+        set.atomic_num.push(parse_symbol(symbol)?);
+        set.x.push(parse_number(x)? * to_angstrom);
+        set.y.push(parse_number(y)? * to_angstrom);
+        set.z.push(parse_number(z)? * to_angstrom);
+    }
+    Ok(vec![set])
+}
+
+fn read(format: &Format, content: &str) -> Result<Vec<AtomicCoordinates>, String> {
+    read_in(format, content, LengthUnit::Angstrom)
+}
+
+/// Reads the file, `unit` is the unit of coordinates of XYZ files, which do not store it.
+fn read_in(format: &Format, content: &str, unit: LengthUnit) -> Result<Vec<AtomicCoordinates>, String> {
+    let mut options = ImportOptions::default();
+    options.xyz.unit = unit;
+    match format.name {
+        "xyz" => import(content, "written.xyz", &options),
+        "mdlmol2000" => import(content, "written.mol", &options),
+        "pdb" => read_pdb(content),
+        "gaussian" => read_gaussian(content),
+        name => panic!("No reader for {}", name),
+    }
+}
+
+fn write(format: &Format, sets: &[AtomicCoordinates]) -> Result<String, String> {
+    export_node(&molecule_node(sets), format.name)
+}
+
+/// Atoms the file can store: unknown atomic numbers are written as dummy atoms.
+fn writable_element(atomic_num: i32) -> i32 {
+    match atomic_num {
+        -2 | 1..=118 => atomic_num,
+        _ => -1,
+    }
+}
+
+fn assert_same(format: &Format, expected: &[AtomicCoordinates], actual: &[AtomicCoordinates], case: &str) {
+    assert_same_to(format, format.precision, expected, actual, case);
+}
+
+/// Compares sets written with `precision` decimals, coordinates may also differ by rounding of unit conversions.
+fn assert_same_to(
+    format: &Format,
+    precision: i32,
+    expected: &[AtomicCoordinates],
+    actual: &[AtomicCoordinates],
+    case: &str,
+) {
+    let expected = if format.all_sets { expected } else { &expected[..1] };
+    assert_eq!(expected.len(), actual.len(), "{} {}: number of sets", format.name, case);
+
+    let close = |e: f64, a: f64| (e - a).abs() <= 0.51 * 10f64.powi(-precision) + e.abs() * 1e-13;
+    for (set, (expected, actual)) in expected.iter().zip(actual).enumerate() {
+        let elements: Vec<i32> = expected.atomic_num.iter().map(|&n| writable_element(n)).collect();
+        assert_eq!(
+            elements, actual.atomic_num,
+            "{} {}: elements of set {}",
+            format.name, case, set
+        );
+
+        let pairs = [
+            (&expected.x, &actual.x),
+            (&expected.y, &actual.y),
+            (&expected.z, &actual.z),
+        ];
+        for (axis, (expected, actual)) in pairs.into_iter().enumerate() {
+            for (atom, (e, a)) in expected.iter().zip(actual).enumerate() {
+                assert!(
+                    close(*e, *a),
+                    "{} {}: coordinate {} of atom {} in set {}: {} != {}",
+                    format.name,
+                    case,
+                    axis,
+                    atom,
+                    set,
+                    e,
+                    a
+                );
+            }
+        }
+
+        if format.bonds
+            && let Some(bonds) = &expected.connectivity
+        {
+            assert_eq!(
+                Some(bonds),
+                actual.connectivity.as_ref(),
+                "{} {}: bonds",
+                format.name,
+                case
+            );
+        }
+        if format.cell {
+            match (&expected.cell, &actual.cell) {
+                (Some(e), Some(a)) => {
+                    let mut vectors = e.vectors.iter().flatten().zip(a.vectors.iter().flatten());
+                    assert!(vectors.all(|(e, a)| close(*e, *a)), "{} {}: cell", format.name, case);
+                }
+                (e, a) => assert_eq!(e.is_some(), a.is_some(), "{} {}: cell", format.name, case),
+            }
+        }
+    }
+}
+
+#[test]
+fn every_writer_is_tested() {
+    for (name, _) in formats() {
+        assert!(
+            FORMATS.iter().any(|format| format.name == name),
+            "No round trip test for {}",
+            name
+        );
+    }
+}
+
+#[test]
+fn write_and_parse_back() {
+    for format in FORMATS {
+        for seed in 0..CASES {
+            let mut rng = Rng::new(seed);
+            let extent = format.extent.min(100.0);
+            let sets: Vec<AtomicCoordinates> = (0..1 + rng.below(3)).map(|_| random_coords(&mut rng, extent)).collect();
+            let case = format!("seed {}", seed);
+
+            let content = write(format, &sets).unwrap_or_else(|e| panic!("{} {}: {}", format.name, case, e));
+            let parsed = read(format, &content).unwrap_or_else(|e| panic!("{} {}: {}", format.name, case, e));
+            assert_same(format, &sets, &parsed, &case);
+
+            // Parsed structures, e.g. with perceived bonds, are written and parsed again without changes
+            let content = write(format, &parsed).unwrap_or_else(|e| panic!("{} {}: {}", format.name, case, e));
+            let reparsed = read(format, &content).unwrap_or_else(|e| panic!("{} {}: {}", format.name, case, e));
+            assert_same(format, &parsed, &reparsed, &case);
+            if format.bonds {
+                let bonds = |sets: &[AtomicCoordinates]| sets[0].connectivity.clone();
+                assert_eq!(bonds(&parsed), bonds(&reparsed), "{} {}: bonds", format.name, case);
+            }
+        }
+    }
+}
+
+#[test]
+fn huge_and_invalid_coordinates() {
+    let values = [
+        1e3,
+        9999.9996,
+        1e4,
+        99999.9999,
+        1e5,
+        1e12,
+        1e300,
+        f64::NAN,
+        f64::INFINITY,
+    ];
+    for format in FORMATS {
+        for (seed, value) in values.iter().enumerate() {
+            for sign in [1.0, -1.0] {
+                let mut rng = Rng::new(seed as u64);
+                let mut set = random_coords(&mut rng, 10.0);
+                let atom = rng.below(set.atomic_num.len());
+                set.y[atom] = sign * value;
+                let case = format!("coordinate {}", sign * value);
+
+                // Either an error or a file that reads back unchanged, never a corrupted file
+                match write(format, std::slice::from_ref(&set)) {
+                    Ok(content) => {
+                        let parsed =
+                            read(format, &content).unwrap_or_else(|e| panic!("{} {}: {}", format.name, case, e));
+                        assert_same(format, std::slice::from_ref(&set), &parsed, &case);
+                    }
+                    Err(_) => assert!(
+                        !value.is_finite() || *value >= format.extent,
+                        "{} {}: unexpected error",
+                        format.name,
+                        case
+                    ),
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn dummy_atoms() {
+    for format in FORMATS {
+        let mut rng = Rng::new(7);
+        let mut set = random_coords(&mut rng, 10.0);
+        set.connectivity = None;
+        let n = set.atomic_num.len();
+        // Dummy, ghost and atomic numbers no element has
+        set.atomic_num = (0..n).map(|i| [-1, -2, 0, -7, 119, 1000][i % 6]).collect();
+
+        let content = write(format, std::slice::from_ref(&set)).unwrap();
+        let parsed = read(format, &content).unwrap_or_else(|e| panic!("{}: {}", format.name, e));
+        assert_same(format, std::slice::from_ref(&set), &parsed, "dummy atoms");
+        if format.bonds {
+            assert_eq!(
+                parsed[0].connectivity, None,
+                "{}: dummy atoms are never bonded",
+                format.name
+            );
+        }
+    }
+}
+
+/// Integer charges are written as formal charges, beyond ±3 only in the property block, and read back.
+#[test]
+fn formal_charges() {
+    let format = FORMATS.iter().find(|format| format.name == "mdlmol2000").unwrap();
+    let mut set = random_coords(&mut Rng::new(11), 10.0);
+    set.connectivity = None;
+    set.atomic_num = (0..set.atomic_num.len()).map(|i| 6 + (i % 3) as i32).collect();
+    let n = set.atomic_num.len();
+    let charges: Vec<f64> = (0..n).map(|i| [0.0, 1.0, -2.0, 5.0, 0.0][i % 5]).collect();
+    set.charges = Some(charges.clone());
+
+    let content = write(format, std::slice::from_ref(&set)).unwrap();
+    assert!(content.lines().any(|line| line.starts_with("M  CHG")));
+    let parsed = read(format, &content).unwrap();
+    assert_eq!(parsed[0].charges.as_ref(), Some(&charges));
+
+    // Partial charges have no place in the file
+    set.charges = Some((0..n).map(|i| 0.1 * i as f64).collect());
+    let content = write(format, std::slice::from_ref(&set)).unwrap();
+    assert!(!content.contains("M  CHG"));
+    assert_eq!(read(format, &content).unwrap()[0].charges, None);
+}
+
+/// The charge and the multiplicity of the molecule end up on the line before the atoms of Gaussian input.
+#[test]
+fn charge_and_multiplicity() {
+    let oxygen = AtomicCoordinates {
+        atomic_num: vec![8, 8],
+        x: vec![0.0, 0.0],
+        y: vec![0.0, 0.0],
+        z: vec![0.0, 1.21],
+        ..new_set()
+    };
+    let state_line = |charge: i32, multiplicity: Option<u32>| {
+        let mut node = molecule_node(std::slice::from_ref(&oxygen));
+        let mut molecule: Molecule = serde_json::from_slice(&node.data).unwrap();
+        molecule.charge = charge;
+        molecule.multiplicity = multiplicity;
+        node.data = serde_json::to_vec(&molecule).unwrap();
+        export_node(&node, "gaussian").map(|content| {
+            content
+                .split("\n\n")
+                .nth(2)
+                .unwrap()
+                .lines()
+                .next()
+                .unwrap()
+                .to_string()
+        })
+    };
+    assert_eq!(state_line(0, None).unwrap(), "0 1");
+    assert_eq!(state_line(0, Some(3)).unwrap(), "0 3");
+    assert_eq!(state_line(-1, None).unwrap(), "-1 2");
+    assert!(state_line(0, Some(2)).is_err());
+    assert!(state_line(0, Some(0)).is_err());
+    assert!(state_line(0, Some(19)).is_err());
+    assert!(state_line(17, None).is_err());
+}
+
+/// Isotope labels are written as symbols to XYZ, as M  ISO lines to MDL Mol and as Iso= to Gaussian input, and
+/// read back from XYZ and MDL Mol.
+#[test]
+fn isotope_labels() {
+    let labelled = |isotopes: Vec<u32>| AtomicCoordinates {
+        atomic_num: vec![6, 1, 1, 1, 1],
+        x: vec![0.0, 0.63, -0.63, -0.63, 0.63],
+        y: vec![0.0, 0.63, -0.63, 0.63, -0.63],
+        z: vec![0.0, 0.63, 0.63, -0.63, -0.63],
+        isotopes: Some(isotopes),
+        ..new_set()
+    };
+    let methane = labelled(vec![13, 2, 2, 3, 0]);
+    for name in ["xyz", "mdlmol2000"] {
+        let format = FORMATS.iter().find(|format| format.name == name).unwrap();
+        let content = write(format, std::slice::from_ref(&methane)).unwrap();
+        let parsed = read(format, &content).unwrap();
+        assert_eq!(parsed[0].atomic_num, methane.atomic_num, "{}", name);
+        assert_eq!(parsed[0].isotopes, methane.isotopes, "{}", name);
+    }
+    let xyz = export_node(&molecule_node(std::slice::from_ref(&methane)), "xyz").unwrap();
+    let symbols: Vec<&str> = xyz
+        .lines()
+        .skip(2)
+        .map(|line| line.split_whitespace().next().unwrap())
+        .collect();
+    assert_eq!(symbols, vec!["13C", "D", "D", "T", "H"]);
+    let mol = export_node(&molecule_node(std::slice::from_ref(&methane)), "mdlmol2000").unwrap();
+    assert!(mol.contains("M  ISO  4   1  13   2   2   3   2   4   3"), "{}", mol);
+    let gaussian = export_node(&molecule_node(std::slice::from_ref(&methane)), "gaussian").unwrap();
+    assert!(gaussian.contains("C(Iso=13) "), "{}", gaussian);
+
+    // Labels must be given for all atoms and be isotopes of their elements
+    for isotopes in [vec![13, 2], vec![5, 0, 0, 0, 0]] {
+        let invalid = labelled(isotopes);
+        assert!(export_node(&molecule_node(std::slice::from_ref(&invalid)), "xyz").is_err());
+    }
+}
+
+#[test]
+fn empty_and_inconsistent_structures() {
+    let mut missing_coordinates = random_coords(&mut Rng::new(3), 10.0);
+    missing_coordinates.z.pop();
+    let mut missing_atom = random_coords(&mut Rng::new(4), 10.0);
+    missing_atom.connectivity = Some(vec![Connection {
+        atom_1: 0,
+        atom_2: missing_atom.atomic_num.len(),
+        order: 1,
+    }]);
+
+    for format in FORMATS {
+        assert!(write(format, &[]).is_err(), "{}: no coordinates", format.name);
+        assert!(write(format, &[new_set()]).is_err(), "{}: no atoms", format.name);
+        assert!(
+            write(format, std::slice::from_ref(&missing_coordinates)).is_err(),
+            "{}: missing coordinates",
+            format.name
+        );
+        if format.bonds {
+            assert!(
+                write(format, std::slice::from_ref(&missing_atom)).is_err(),
+                "{}: bond to a missing atom",
+                format.name
+            );
+        }
+
+        // An empty set among others is not written either
+        let sets = [random_coords(&mut Rng::new(5), 10.0), new_set()];
+        assert!(write(format, &sets).is_err(), "{}: empty set", format.name);
+    }
+}
+
+#[test]
+fn precision_unit_and_layout() {
+    // Small enough for the most decimals of fixed columns
+    let set = random_coords(&mut Rng::new(11), 9.0);
+    let sets = std::slice::from_ref(&set);
+
+    for format in FORMATS {
+        for precision in 0..=format.max_precision + 1 {
+            for (unit, unit_name) in [(LengthUnit::Angstrom, "angstrom"), (LengthUnit::Bohr, "bohr")] {
+                for layout in ["fixed", "free"] {
+                    let options = ExportOptions::from_json(&format!(
+                        r#"{{"{}": {{"precision": {}, "unit": "{}", "layout": "{}"}}}}"#,
+                        format.name, precision, unit_name, layout
+                    ))
+                    .unwrap();
+                    let case = format!("precision {}, {}, {} layout", precision, unit_name, layout);
+                    let supported = precision <= format.max_precision
+                        && (format.free_format || (unit == LengthUnit::Angstrom && layout == "fixed"));
+
+                    let content = export_node_with_options(&molecule_node(sets), format.name, &options);
+                    let content = match content {
+                        Ok(content) => content,
+                        Err(_) if !supported => continue,
+                        Err(e) => panic!("{} {}: {}", format.name, case, e),
+                    };
+                    assert!(supported, "{} {}: unsupported options are accepted", format.name, case);
+
+                    let parsed =
+                        read_in(format, &content, unit).unwrap_or_else(|e| panic!("{} {}: {}", format.name, case, e));
+                    assert_same_to(format, precision as i32, sets, &parsed, &case);
+
+                    // Atom cards of XYZ-like files: symbol and three coordinates. In the fixed layout they have
+                    // the same length, in the free one values are separated by single spaces
+                    let atom_lines: Vec<&str> = content
+                        .lines()
+                        .filter(|line| {
+                            let items: Vec<&str> = line.split_whitespace().collect();
+                            items.len() == 4 && parse_symbol(items[0]).is_ok()
+                        })
+                        .collect();
+                    if format.free_format {
+                        assert_eq!(
+                            atom_lines.len(),
+                            set.atomic_num.len(),
+                            "{} {}: atom cards",
+                            format.name,
+                            case
+                        );
+                    }
+                    if layout == "fixed" {
+                        assert!(
+                            atom_lines.iter().all(|line| line.len() == atom_lines[0].len()),
+                            "{} {}: columns are not aligned",
+                            format.name,
+                            case
+                        );
+                    } else {
+                        assert!(
+                            atom_lines.iter().all(|line| !line[3..].contains("  ")),
+                            "{} {}: values are padded",
+                            format.name,
+                            case
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn atom_order() {
+    for seed in 0..CASES {
+        let mut rng = Rng::new(seed);
+        let first = random_coords(&mut rng, 100.0);
+        let num_atoms = first.atomic_num.len();
+        // A second frame of the same atoms
+        let mut shift = || first.x.iter().map(|_| rng.uniform(-0.5, 0.5)).collect::<Vec<f64>>();
+        let (dx, dy, dz) = (shift(), shift(), shift());
+        let second = AtomicCoordinates {
+            atomic_num: first.atomic_num.clone(),
+            x: first.x.iter().zip(&dx).map(|(x, d)| x + d).collect(),
+            y: first.y.iter().zip(&dy).map(|(y, d)| y + d).collect(),
+            z: first.z.iter().zip(&dz).map(|(z, d)| z + d).collect(),
+            connectivity: first.connectivity.clone(),
+            charges: None,
+            chains: None,
+            cell: first.cell,
+            isotopes: None,
+        };
+        let sets = [first, second];
+        let reversed: Vec<usize> = (1..=num_atoms).rev().collect();
+        let orders = [
+            ("element", r#""element""#.to_string()),
+            ("centroid", r#""centroid""#.to_string()),
+            ("canonical", r#""canonical""#.to_string()),
+            ("manual", format!(r#"{{"manual": {:?}}}"#, reversed)),
+        ];
+
+        for (name, json) in orders {
+            let options = ExportOptions::from_json(&format!(r#"{{"order": {}}}"#, json)).unwrap();
+            let order = options.order.clone().unwrap();
+            let case = format!("seed {}, {} order", seed, name);
+
+            let permutation = ordering::atom_order(&sets[0], &order).unwrap();
+            let mut sorted = permutation.clone();
+            sorted.sort_unstable();
+            assert!(sorted.iter().copied().eq(0..num_atoms), "{}: not a permutation", case);
+            let expected: Vec<AtomicCoordinates> = sets
+                .iter()
+                .map(|set| ordering::reorder(set, &permutation).unwrap())
+                .collect();
+
+            for format in FORMATS {
+                let content = export_node_with_options(&molecule_node(&sets), format.name, &options)
+                    .unwrap_or_else(|e| panic!("{} {}: {}", format.name, case, e));
+                let parsed = read(format, &content).unwrap_or_else(|e| panic!("{} {}: {}", format.name, case, e));
+                assert_same(format, &expected, &parsed, &case);
+            }
+        }
+
+        // Ranks do not depend on the input order, so shuffled atoms get the same elements and bonds
+        let mut shuffled: Vec<usize> = (0..num_atoms).collect();
+        for i in (1..num_atoms).rev() {
+            shuffled.swap(i, rng.below(i + 1));
+        }
+        let shuffled = ordering::reorder(&sets[0], &shuffled).unwrap();
+        let canonical = |set: &AtomicCoordinates| {
+            let reordered = ordering::reorder(set, &ordering::atom_order(set, &AtomOrder::Canonical).unwrap()).unwrap();
+            let mut bonds: Vec<(usize, usize)> = shared_lib::connectivity::find_bonds(&reordered, 0.15)
+                .iter()
+                .map(|bond| {
+                    let (i, j) = (bond.atom_index_1, bond.atom_index_2);
+                    (i.min(j), i.max(j))
+                })
+                .collect();
+            bonds.sort_unstable();
+            (reordered.atomic_num, bonds)
+        };
+        assert!(
+            canonical(&sets[0]) == canonical(&shuffled),
+            "seed {}: canonical order",
+            seed
+        );
+    }
+}
+
+#[test]
+fn invalid_atom_order() {
+    let set = random_coords(&mut Rng::new(3), 10.0);
+    let num_atoms = set.atomic_num.len();
+    let node = molecule_node(std::slice::from_ref(&set));
+    for order in [
+        "[1, 1]".to_string(),
+        "[0]".to_string(),
+        format!("{:?}", (1..num_atoms).collect::<Vec<usize>>()),
+        format!("{:?}", (1..=num_atoms + 1).collect::<Vec<usize>>()),
+    ] {
+        let options = ExportOptions::from_json(&format!(r#"{{"order": {{"manual": {}}}}}"#, order)).unwrap();
+        assert!(export_node_with_options(&node, "xyz", &options).is_err(), "{}", order);
+    }
+    assert!(ExportOptions::from_json(r#"{"order": "random"}"#).is_err());
+
+    // Frames of different structures cannot be reordered alike
+    let other = random_coords(&mut Rng::new(4), 10.0);
+    assert_ne!(other.atomic_num.len(), num_atoms);
+    let options = ExportOptions::from_json(r#"{"order": "element"}"#).unwrap();
+    assert!(export_node_with_options(&molecule_node(&[set, other]), "xyz", &options).is_err());
+}
+
+#[test]
+fn invalid_export_options() {
+    assert!(ExportOptions::from_json(r#"{"xyz": {"unit": "nm"}}"#).is_err());
+    assert!(ExportOptions::from_json(r#"{"pdb": {"layout": "csv"}}"#).is_err());
+    assert!(ExportOptions::from_json(r#"{"gaussian": {"precision": -1}}"#).is_err());
+    assert!(ExportOptions::from_json("").is_ok());
+}
@@ -0,0 +1,44 @@
+// Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
+// Licensed under the MIT License
+
+use std::borrow::Cow;
+use std::io::Read;
+
+use flate2::read::MultiGzDecoder;
+use ruzstd::decoding::StreamingDecoder;
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+
+const COMPRESSED_EXTENSIONS: &[&str] = &[".gz", ".zst", ".zstd"];
+
+/// Decompresses gzip and zstd streams detected by their magic bytes.
+/// Borrows the input without copying if it is not compressed.
+pub fn decompress(data: &[u8]) -> Result<Cow<'_, [u8]>, String> {
+    let mut result = Vec::new();
+
+    if data.starts_with(GZIP_MAGIC) {
+        MultiGzDecoder::new(data)
+            .read_to_end(&mut result)
+            .map_err(|e| format!("Failed to decompress gzip stream: {}", e))?;
+    } else if data.starts_with(ZSTD_MAGIC) {
+        StreamingDecoder::new(data)
+            .map_err(|e| format!("Failed to decompress zstd stream: {}", e))?
+            .read_to_end(&mut result)
+            .map_err(|e| format!("Failed to decompress zstd stream: {}", e))?;
+    } else {
+        return Ok(Cow::Borrowed(data));
+    }
+
+    Ok(Cow::Owned(result))
+}
+
+/// Strips the compression extension, e.g. "water.xyz.gz" -> "water.xyz".
+pub fn strip_extension(file_name: &str) -> &str {
+    for extension in COMPRESSED_EXTENSIONS {
+        if let Some(stripped) = file_name.strip_suffix(extension) {
+            return stripped;
+        }
+    }
+    file_name
+}
@@ -3,63 +3,218 @@
 
 #[allow(warnings)]
 mod bindings {
+    #[cfg(not(feature = "cancellation"))]
     wit_bindgen::generate!({
         path: "wit",
         world: "plugin",
         generate_all,
     });
 
+    #[cfg(feature = "cancellation")]
+    wit_bindgen::generate!({
+        path: "wit",
+        world: "cancellable-plugin",
+        generate_all,
+    });
+
     use super::ChemistryImporter;
 
     export!(ChemistryImporter);
 }
 
+mod decompress;
+pub mod options;
 mod parsers;
+mod source;
+mod trajectory;
+mod warnings;
 
 use bindings::Guest;
-use shared_lib::types;
+pub use decompress::strip_extension as strip_compression_extension;
+use options::ImportOptions;
+use shared_lib::cancellation::{CANCELLED_MESSAGE, CancellationToken};
+use shared_lib::{profiling, smiles, templates, types};
 
 struct ChemistryImporter;
 
 type ParserTestFn = fn(&str) -> Result<bool, String>;
-type ParserParseFn = fn(&str, &str) -> Result<types::Node, String>;
+type ParserParseFn = fn(&str, &str, &ImportOptions, &CancellationToken) -> Result<types::Node, String>;
 
 const PARSERS: &[(&str, ParserTestFn, ParserParseFn)] = &[
     ("XYZ", parsers::xyz::test, parsers::xyz::parse),
     ("Gaussian Cube", parsers::cube::test, parsers::cube::parse),
     ("UNEX", parsers::unex::test, parsers::unex::parse),
     ("Cfour", parsers::cfour::test, parsers::cfour::parse),
+    ("Molden normal modes", parsers::molden::test, parsers::molden::parse),
     ("MDL Mol V2000", parsers::mdlmol2000::test, parsers::mdlmol2000::parse),
 ];
 
-impl Guest for ChemistryImporter {
-    fn load(file_path: String) -> Result<Vec<u8>, String> {
-        let content = std::fs::read_to_string(&file_path).map_err(|e| e.to_string())?;
-
-        let file_name = std::path::Path::new(&file_path)
-            .file_name()
-            .and_then(|n| n.to_str())
-            .unwrap_or("unknown");
-
-        let mut errors: Vec<String> = Vec::new();
-
-        for (name, test_fn, parse_fn) in PARSERS {
-            match test_fn(&file_path) {
-                Ok(true) => match parse_fn(&content, file_name) {
-                    Ok(node) => {
-                        return serde_json::to_vec(&node).map_err(|e| format!("Failed to serialize result: {}", e));
-                    }
-                    Err(e) => {
-                        errors.push(format!("{}: {}", name, e));
-                    }
-                },
-                Ok(false) => continue,
+/// Names of the parsers in the order they are tried.
+pub fn parsers() -> impl Iterator<Item = &'static str> {
+    PARSERS.iter().map(|(name, _, _)| *name)
+}
+
+/// Reads, decompresses and decodes the file, then passes its content to `f`.
+fn with_content<T>(
+    file_path: &str,
+    cancel: &CancellationToken,
+    f: impl FnOnce(&str) -> Result<T, String>,
+) -> Result<T, String> {
+    let file = {
+        let _span = profiling::span("read file");
+        source::FileContent::open(file_path)?
+    };
+    let data = {
+        let _span = profiling::span("decompress");
+        decompress::decompress(&file)?
+    };
+    if cancel.is_cancelled() {
+        return Err(CANCELLED_MESSAGE.to_string());
+    }
+    let content = std::str::from_utf8(&data).map_err(|e| e.to_string())?;
+    f(content)
+}
+
+/// Imports the file with the first parser that recognizes its content, the same way for the host and
+/// for native tools. The file may be compressed.
+pub fn import_file(
+    file_path: &str,
+    options: &ImportOptions,
+    cancel: &CancellationToken,
+) -> Result<types::Node, String> {
+    let file_name = std::path::Path::new(file_path)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .map(decompress::strip_extension)
+        .unwrap_or("unknown");
+
+    let _span = profiling::span("import");
+    with_content(file_path, cancel, |content| {
+        import_content(content, file_name, options, cancel)
+    })
+}
+
+/// Imports the decoded content of a file with the first parser that recognizes it. `file_name` names the
+/// imported nodes.
+pub fn import_content(
+    content: &str,
+    file_name: &str,
+    options: &ImportOptions,
+    cancel: &CancellationToken,
+) -> Result<types::Node, String> {
+    let mut errors: Vec<String> = Vec::new();
+
+    for (name, test_fn, parse_fn) in PARSERS {
+        // Spans are named after the parsers
+        let _span = profiling::span(name);
+        match test_fn(content) {
+            Ok(true) => match parse_fn(content, file_name, options, cancel) {
+                Ok(node) => return Ok(node),
+                Err(e) if cancel.is_cancelled() => return Err(e),
                 Err(e) => {
                     errors.push(format!("{}: {}", name, e));
                 }
+            },
+            Ok(false) => continue,
+            Err(e) => {
+                errors.push(format!("{}: {}", name, e));
             }
         }
+    }
+
+    Err(format!("No suitable parser found for file. {}", errors.join("; ")))
+}
+
+/// Molecule node of a built-in template with its atomic coordinates, as imported from a file.
+pub fn template_node(name: &str) -> Result<types::Node, String> {
+    let template = templates::find_template(name)?;
+    molecule_node(template.name, &template.coordinates(), 0)
+}
+
+/// Builds the molecule of a SMILES string with embedded coordinates, see `shared_lib::smiles`. The charge of the
+/// molecule is the sum of the formal charges of the atoms.
+pub fn smiles_node(smiles: &str) -> Result<types::Node, String> {
+    let coordinates = smiles::build(smiles)?;
+    let charge = coordinates
+        .charges
+        .as_ref()
+        .map_or(0, |charges| charges.iter().sum::<f64>().round() as i32);
+    molecule_node(smiles.trim(), &coordinates, charge)
+}
+
+fn molecule_node(name: &str, coordinates: &types::AtomicCoordinates, charge: i32) -> Result<types::Node, String> {
+    let molecule = types::Molecule {
+        n_atoms: coordinates.atomic_num.len() as i32,
+        atomic_num: coordinates.atomic_num.clone(),
+        charge,
+        name: name.to_string(),
+        multiplicity: None,
+    };
+    Ok(types::Node {
+        name: name.to_string(),
+        r#type: "mircmd:chemistry:molecule".to_string(),
+        data: serde_json::to_vec(&molecule).map_err(|e| format!("Failed to serialize molecule: {}", e))?,
+        children: vec![types::Node {
+            name: name.to_string(),
+            r#type: "mircmd:chemistry:atomic_coordinates".to_string(),
+            data: serde_json::to_vec(coordinates).map_err(|e| format!("Failed to serialize coordinates: {}", e))?,
+            children: vec![],
+        }],
+    })
+}
+
+/// Token polling the host for cancellation if the plugin is built for hosts able to abort imports.
+fn cancellation_token() -> CancellationToken {
+    #[cfg(feature = "cancellation")]
+    return CancellationToken::new(bindings::mircmd::plugin::cancellation::is_cancelled);
+    #[cfg(not(feature = "cancellation"))]
+    CancellationToken::none()
+}
+
+fn import(file_path: &str, options: &ImportOptions) -> Result<Vec<u8>, String> {
+    let cancel = cancellation_token();
+    let node = import_file(file_path, options, &cancel)?;
+    serde_json::to_vec(&node).map_err(|e| format!("Failed to serialize result: {}", e))
+}
+
+impl Guest for ChemistryImporter {
+    fn load(file_path: String) -> Result<Vec<u8>, String> {
+        import(&file_path, &ImportOptions::default())
+    }
+
+    fn load_with_options(file_path: String, options: String) -> Result<Vec<u8>, String> {
+        import(&file_path, &ImportOptions::from_json(&options)?)
+    }
+
+    fn load_volume_data(file_path: String, volume_cube: Vec<u8>) -> Result<Vec<u8>, String> {
+        let cancel = cancellation_token();
+        let mut volume_cube: types::VolumeCube =
+            serde_json::from_slice(&volume_cube).map_err(|e| format!("Failed to deserialize volume cube: {}", e))?;
+
+        let _span = profiling::span("load volume data");
+        with_content(&file_path, &cancel, |content| {
+            parsers::cube::load_volume_data(content, &mut volume_cube, &cancel)
+        })?;
+
+        serde_json::to_vec(&volume_cube).map_err(|e| format!("Failed to serialize volume cube: {}", e))
+    }
+
+    fn list_templates() -> Vec<u8> {
+        let infos: Vec<templates::TemplateInfo> = templates::templates().iter().map(|t| t.info()).collect();
+        serde_json::to_vec(&infos).unwrap_or_default()
+    }
+
+    fn load_template(name: String) -> Result<Vec<u8>, String> {
+        let node = template_node(&name)?;
+        serde_json::to_vec(&node).map_err(|e| format!("Failed to serialize result: {}", e))
+    }
+
+    fn load_smiles(smiles: String) -> Result<Vec<u8>, String> {
+        let node = smiles_node(&smiles)?;
+        serde_json::to_vec(&node).map_err(|e| format!("Failed to serialize result: {}", e))
+    }
 
-        Err(format!("No suitable parser found for file. {}", errors.join("; ")))
+    fn take_profile() -> Vec<u8> {
+        serde_json::to_vec(&profiling::take_records()).unwrap_or_default()
     }
 }
@@ -0,0 +1,91 @@
+// Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
+// Licensed under the MIT License
+
+use serde::Deserialize;
+
+const BOHR2ANGSTROM: f64 = 0.529177210903;
+
+/// Options of a single import call, passed by the host as JSON, e.g.
+/// `{"xyz": {"unit": "bohr"}, "cube": {"skip_volume_data": true}, "trajectory": {"stride": 10}}`.
+/// Every section and field is optional.
+#[derive(Default, Deserialize)]
+#[serde(default)]
+pub struct ImportOptions {
+    pub xyz: XyzOptions,
+    pub cube: CubeOptions,
+    pub trajectory: TrajectoryOptions,
+}
+
+impl ImportOptions {
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        if json.trim().is_empty() {
+            return Ok(Self::default());
+        }
+        serde_json::from_str(json).map_err(|e| format!("Invalid import options: {}", e))
+    }
+}
+
+#[derive(Default, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum LengthUnit {
+    #[default]
+    Angstrom,
+    Bohr,
+}
+
+impl LengthUnit {
+    /// Factor converting values in this unit to Angstroms.
+    pub fn to_angstrom(self) -> f64 {
+        match self {
+            LengthUnit::Angstrom => 1.0,
+            LengthUnit::Bohr => BOHR2ANGSTROM,
+        }
+    }
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+pub struct XyzOptions {
+    /// Unit of coordinates in the file, XYZ files do not store it.
+    pub unit: LengthUnit,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+pub struct CubeOptions {
+    /// Do not parse volumetric data, it is loaded later on request.
+    pub skip_volume_data: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct TrajectoryOptions {
+    /// Import only every n-th coordinate set of multi-geometry files.
+    pub stride: usize,
+    /// Import only the last coordinate set, e.g. the final geometry of an optimization.
+    /// QM log parsers search for it from the end of the file instead of parsing all sets.
+    pub last_only: bool,
+}
+
+impl Default for TrajectoryOptions {
+    fn default() -> Self {
+        Self {
+            stride: 1,
+            last_only: false,
+        }
+    }
+}
+
+impl TrajectoryOptions {
+    /// Returns true if the coordinate set with zero-based `index` must be imported.
+    pub fn keep(&self, index: usize) -> bool {
+        index.is_multiple_of(self.stride.max(1))
+    }
+
+    /// Drops all but the last coordinate set if `last_only` is set.
+    pub fn apply_last_only<T>(&self, sets: &mut Vec<T>) {
+        if self.last_only && sets.len() > 1 {
+            sets.drain(..sets.len() - 1);
+        }
+    }
+}
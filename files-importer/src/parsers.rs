@@ -4,5 +4,6 @@
 pub mod cfour;
 pub mod cube;
 pub mod mdlmol2000;
+pub mod molden;
 pub mod unex;
 pub mod xyz;
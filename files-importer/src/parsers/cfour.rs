@@ -1,28 +1,22 @@
 // Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
 // Licensed under the MIT License
 
-use std::fs::File;
-use std::io::{BufRead, BufReader};
-use std::path::Path;
-
+use shared_lib::cancellation::CancellationToken;
 use shared_lib::types::{AtomicCoordinates, Molecule, Node};
 
+use crate::options::ImportOptions;
+use crate::trajectory;
+use crate::warnings::WarningCollector;
+
 const MAX_VALIDATION_LINES: usize = 20;
 const BOHR2ANGSTROM: f64 = 0.529177210903;
 
 const CFOUR_SIGNATURE: &str = "<<<     CCCCCC     CCCCCC   |||     CCCCCC     CCCCCC   >>>";
+const GEOMETRY_HEADER: &str = "Z-matrix   Atomic            Coordinates (in bohr)";
 
 /// Validates if the file is in Cfour log format.
-pub fn test(file_path: &str) -> Result<bool, String> {
-    let path = Path::new(file_path);
-    let file = File::open(path).map_err(|e| e.to_string())?;
-    let reader = BufReader::new(file);
-
-    let lines: Vec<String> = reader
-        .lines()
-        .take(MAX_VALIDATION_LINES)
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| e.to_string())?;
+pub fn test(content: &str) -> Result<bool, String> {
+    let lines: Vec<&str> = content.lines().take(MAX_VALIDATION_LINES).collect();
 
     // Check if any line (except the first) contains the Cfour signature
     for line in lines.iter().skip(1) {
@@ -34,8 +28,43 @@ pub fn test(file_path: &str) -> Result<bool, String> {
     Ok(false)
 }
 
+/// Charge and multiplicity from the table of control parameters printed before the first geometry,
+/// e.g. `CHARGE  ICHRGE  0`.
+fn control_parameters(content: &str) -> (Option<i32>, Option<u32>) {
+    let end = content.find(GEOMETRY_HEADER).unwrap_or(content.len());
+    let (mut charge, mut multiplicity) = (None, None);
+    for line in content[..end].lines() {
+        let items: Vec<&str> = line.split_whitespace().collect();
+        match items.as_slice() {
+            ["CHARGE", "ICHRGE", value, ..] => charge = value.parse().ok(),
+            ["MULTIPLICITY", "IMULTP", value, ..] => multiplicity = value.parse().ok(),
+            _ => {}
+        }
+    }
+    (charge, multiplicity)
+}
+
+/// Returns the content starting from the line of the last occurrence of `header`
+/// and the zero-based number of that line, or an empty string if there is none.
+/// The search runs from the end of the file.
+fn last_block<'a>(content: &'a str, header: &str) -> (&'a str, usize) {
+    match content.rfind(header) {
+        Some(position) => {
+            let start = content[..position].rfind('\n').map_or(0, |p| p + 1);
+            let line_number = content.as_bytes()[..start].iter().filter(|&&b| b == b'\n').count();
+            (&content[start..], line_number)
+        }
+        None => ("", 0),
+    }
+}
+
 /// Parses a Cfour log file.
-pub fn parse(content: &str, file_name: &str) -> Result<Node, String> {
+pub fn parse(
+    content: &str,
+    file_name: &str,
+    options: &ImportOptions,
+    cancel: &CancellationToken,
+) -> Result<Node, String> {
     let mut result = Node {
         name: file_name.to_string(),
         r#type: "mircmd:chemistry:molecule".to_string(),
@@ -44,16 +73,28 @@ pub fn parse(content: &str, file_name: &str) -> Result<Node, String> {
             atomic_num: vec![],
             charge: 0,
             name: file_name.to_string(),
+            multiplicity: None,
         })
         .map_err(|e| format!("Failed to serialize molecule: {}", e))?,
         children: vec![],
     };
 
-    let mut cart_set_number = 0;
-    let mut lines = content.lines().peekable();
+    let (charge, multiplicity) = control_parameters(content);
+
+    // Fast path for the final geometry: only the last block is parsed
+    let (content, first_line) = if options.trajectory.last_only {
+        last_block(content, GEOMETRY_HEADER)
+    } else {
+        (content, 0)
+    };
+
+    let mut warnings = WarningCollector::new();
+    let mut cart_set_number: usize = 0;
+    let mut lines = content.lines().enumerate().map(|(n, line)| (first_line + n, line));
 
-    while let Some(line) = lines.next() {
-        if line.contains("Z-matrix   Atomic            Coordinates (in bohr)") {
+    while let Some((_, line)) = lines.next() {
+        cancel.check()?;
+        if line.contains(GEOMETRY_HEADER) {
             cart_set_number += 1;
 
             // Skip header of the table (2 lines)
@@ -67,7 +108,7 @@ pub fn parse(content: &str, file_name: &str) -> Result<Node, String> {
             let mut atom_coord_y: Vec<f64> = vec![];
             let mut atom_coord_z: Vec<f64> = vec![];
 
-            for block_line in lines.by_ref() {
+            for (line_number, block_line) in lines.by_ref() {
                 if block_line.contains("--") {
                     break;
                 }
@@ -77,12 +118,22 @@ pub fn parse(content: &str, file_name: &str) -> Result<Node, String> {
                     let at_num = if items[1] == "0" {
                         -1
                     } else {
-                        items[1].parse::<i32>().unwrap_or(-1)
+                        items[1].parse::<i32>().unwrap_or_else(|_| {
+                            warnings.warn(
+                                line_number,
+                                format!("Invalid atomic number {}, imported as a dummy atom.", items[1]),
+                            );
+                            -1
+                        })
                     };
 
-                    let x: f64 = items[2].parse::<f64>().unwrap_or(0.0) * BOHR2ANGSTROM;
-                    let y: f64 = items[3].parse::<f64>().unwrap_or(0.0) * BOHR2ANGSTROM;
-                    let z: f64 = items[4].parse::<f64>().unwrap_or(0.0) * BOHR2ANGSTROM;
+                    let [x, y, z] = [items[2], items[3], items[4]].map(|item| match item.parse::<f64>() {
+                        Ok(value) => value * BOHR2ANGSTROM,
+                        Err(_) => {
+                            warnings.warn(line_number, format!("Invalid coordinate value {}, set to zero.", item));
+                            0.0
+                        }
+                    });
 
                     atomic_num.push(at_num);
                     atom_coord_x.push(x);
@@ -91,23 +142,46 @@ pub fn parse(content: &str, file_name: &str) -> Result<Node, String> {
                 }
             }
 
+            result.data = serde_json::to_vec(&Molecule {
+                n_atoms: atomic_num.len() as i32,
+                atomic_num: atomic_num.clone(),
+                charge: charge.unwrap_or(0),
+                name: file_name.to_string(),
+                multiplicity,
+            })
+            .map_err(|e| format!("Failed to serialize molecule: {}", e))?;
+
             let coords = AtomicCoordinates {
                 atomic_num,
                 x: atom_coord_x,
                 y: atom_coord_y,
                 z: atom_coord_z,
+                connectivity: None,
+                charges: None,
+                chains: None,
+                cell: None,
+                isotopes: None,
             };
 
             let at_coord_node = Node {
-                name: format!("Set#{}", cart_set_number),
+                name: if options.trajectory.last_only {
+                    "Last".to_string()
+                } else {
+                    format!("Set#{}", cart_set_number)
+                },
                 r#type: "mircmd:chemistry:atomic_coordinates".to_string(),
                 data: serde_json::to_vec(&coords).map_err(|e| format!("Failed to serialize coordinates: {}", e))?,
                 children: vec![],
             };
 
-            result.children.push(at_coord_node);
+            if options.trajectory.keep(cart_set_number - 1) {
+                result.children.push(at_coord_node);
+            }
         }
     }
 
+    trajectory::attach(&mut result)?;
+    warnings.attach(&mut result)?;
+
     Ok(result)
 }
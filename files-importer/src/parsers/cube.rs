@@ -1,11 +1,10 @@
 // Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
 // Licensed under the MIT License
 
-use std::fs::File;
-use std::io::{BufRead, BufReader};
-use std::path::Path;
+use shared_lib::cancellation::CancellationToken;
+use shared_lib::types::{AtomicCoordinates, DeferredGrid, Node, VolumeCube};
 
-use shared_lib::types::{AtomicCoordinates, Node, VolumeCube};
+use crate::options::ImportOptions;
 
 const MAX_VALIDATION_LINES: usize = 10;
 const BOHR2ANGSTROM: f64 = 0.529177210903;
@@ -33,18 +32,80 @@ fn parse_grid_line(line: &str, line_number: usize) -> Result<(i32, Vec<f64>), St
     Ok((n, vec))
 }
 
+/// Reads `total_points` whitespace separated values from the volumetric data lines.
+fn read_volume_data<'a>(
+    lines: impl Iterator<Item = (usize, &'a str)>,
+    total_points: usize,
+    cancel: &CancellationToken,
+) -> Result<Vec<f64>, String> {
+    let mut cube_data_flat: Vec<f64> = Vec::with_capacity(total_points);
+
+    for (line_number, data_line) in lines {
+        cancel.check()?;
+        for value_str in data_line.split_whitespace() {
+            let value: f64 = value_str
+                .parse()
+                .map_err(|_| format!("Invalid volumetric data value at line {}.", line_number + 1))?;
+            cube_data_flat.push(value);
+        }
+    }
+
+    if cube_data_flat.len() != total_points {
+        return Err(format!(
+            "Mismatch in volumetric data: expected {} points, found {}.",
+            total_points,
+            cube_data_flat.len()
+        ));
+    }
+    Ok(cube_data_flat)
+}
+
+/// Number of values of the grid with `num_values` values per voxel. Every value takes at least a digit and a
+/// separator, so more values than the file can hold come from a corrupt header and are an error.
+fn count_values(content: &str, dims: [usize; 3], num_values: usize) -> Result<usize, String> {
+    let max_values = content.len().div_ceil(2);
+    dims.iter()
+        .try_fold(num_values, |total, &n| total.checked_mul(n))
+        .filter(|&total| num_values <= max_values && total <= max_values)
+        .ok_or_else(|| {
+            format!(
+                "Grid of {}x{}x{} points with {} values per point does not fit into the file.",
+                dims[0], dims[1], dims[2], num_values
+            )
+        })
+}
+
+/// Extracts data set `index` of `num_values` interleaved values per voxel.
+fn select_dataset(flat: &[f64], num_values: usize, index: usize) -> Vec<f64> {
+    flat.iter().skip(index).step_by(num_values).copied().collect()
+}
+
+/// Parses the deferred volumetric data of a cube previously imported with `skip_volume_data`.
+/// `content` is the full content of the same cube file.
+pub fn load_volume_data(content: &str, volume_cube: &mut VolumeCube, cancel: &CancellationToken) -> Result<(), String> {
+    let Some(deferred) = volume_cube.deferred.take() else {
+        return Ok(());
+    };
+
+    let data = content
+        .get(deferred.offset..)
+        .ok_or_else(|| "Invalid location of volumetric data.".to_string())?;
+    let lines = data.lines().enumerate().map(|(n, line)| (n + deferred.line, line));
+    let total_points = count_values(content, volume_cube.dims(), deferred.num_values)?;
+    let flat = read_volume_data(lines, total_points, cancel)?;
+
+    volume_cube.cube_data = if deferred.num_values == 1 {
+        flat
+    } else {
+        select_dataset(&flat, deferred.num_values, deferred.index)
+    };
+    Ok(())
+}
+
 /// Validates if the file is in Gaussian cube format by reading only first few lines.
 /// Returns true if the file appears to be a valid cube file, false otherwise.
-pub fn test(file_path: &str) -> Result<bool, String> {
-    let path = Path::new(file_path);
-    let file = File::open(path).map_err(|e| e.to_string())?;
-    let reader = BufReader::new(file);
-
-    let lines: Vec<String> = reader
-        .lines()
-        .take(MAX_VALIDATION_LINES)
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| e.to_string())?;
+pub fn test(content: &str) -> Result<bool, String> {
+    let lines: Vec<&str> = content.lines().take(MAX_VALIDATION_LINES).collect();
 
     // Need at least 6 lines: 2 comments + 1 header + 3 grid lines
     if lines.len() < 6 {
@@ -107,15 +168,27 @@ pub fn test(file_path: &str) -> Result<bool, String> {
 /// Atom1 Z1 x y z          # Atomic number, charge, and coordinates (in Bohr)
 /// ...
 /// AtomN ZN x y z
-/// [DSET_IDS]              # Data set identifiers if N_atom is negative
-/// Data on grids           # Volumetric data
+/// [DSET_IDS]              # Data set identifiers if N_atom is negative: "m id1 ... idm"
+/// Data on grids           # Volumetric data, values of all data sets are interleaved per voxel
 /// ```
 ///
+/// A file with a single data set is returned as a `volume_cube` node. Files with multiple values
+/// per voxel (e.g. several orbitals written by cubegen) are returned as a `volume_cube_group` node
+/// with one `volume_cube` child per data set.
+///
+/// With `options.cube.skip_volume_data` the grid is not parsed, each `VolumeCube` gets an empty
+/// `cube_data` and the location of the data in the file, see `load_volume_data`.
+///
 /// References:
 /// - http://paulbourke.net/dataformats/cube/
 /// - https://h5cube-spec.readthedocs.io/en/latest/cubeformat.html
 /// - http://gaussian.com/cubegen/
-pub fn parse(content: &str, file_name: &str) -> Result<Node, String> {
+pub fn parse(
+    content: &str,
+    file_name: &str,
+    options: &ImportOptions,
+    cancel: &CancellationToken,
+) -> Result<Node, String> {
     let mut lines = content.lines().enumerate();
 
     // Line 1: Comment 1
@@ -147,16 +220,13 @@ pub fn parse(content: &str, file_name: &str) -> Result<Node, String> {
         .parse()
         .map_err(|_| format!("Invalid number of atoms at line {}.", line_number + 1))?;
 
-    // Check for multiple values per voxel (unsupported)
-    if natm_raw > 0 && header_parts.len() > 4 {
-        let nval: i32 = header_parts[4].parse().unwrap_or(1);
-        if nval > 1 {
-            return Err(format!(
-                "Unsupported number of data values per voxel {} in cube file.",
-                nval
-            ));
-        }
-    }
+    let nval: usize = match header_parts.get(4) {
+        Some(value) => value
+            .parse::<usize>()
+            .map_err(|_| format!("Invalid number of values per voxel at line {}.", line_number + 1))?
+            .max(1),
+        None => 1,
+    };
 
     let dset_ids = natm_raw < 0;
     let natm = natm_raw.unsigned_abs() as usize;
@@ -225,81 +295,100 @@ pub fn parse(content: &str, file_name: &str) -> Result<Node, String> {
         atom_coord_z.push(z);
     }
 
-    // Handle DSET_IDS line if present
+    // Handle DSET_IDS line(s) if present, the list of identifiers may span several lines
+    let mut dataset_ids: Vec<i32> = vec![];
     if dset_ids {
-        let (line_number, dset_line) = lines
-            .next()
-            .ok_or_else(|| "Unexpected end of file, expected DSET_IDS line.".to_string())?;
-        let parts: Vec<&str> = dset_line.trim().split_whitespace().collect();
-
-        if !parts.is_empty() {
-            let num_ids: i32 = parts[0].parse().unwrap_or(1);
-            if num_ids != 1 {
-                return Err(format!(
-                    "Unsupported number of identifiers per voxel {} at line {}.",
-                    num_ids,
-                    line_number + 1
-                ));
+        let mut num_ids: Option<usize> = None;
+        while num_ids.is_none_or(|n| dataset_ids.len() < n) {
+            let (line_number, dset_line) = lines
+                .next()
+                .ok_or_else(|| "Unexpected end of file, expected DSET_IDS line.".to_string())?;
+            for part in dset_line.split_whitespace() {
+                let value: i32 = part
+                    .parse()
+                    .map_err(|_| format!("Invalid DSET_IDS value at line {}.", line_number + 1))?;
+                match num_ids {
+                    None => num_ids = Some(value.max(0) as usize),
+                    Some(n) if dataset_ids.len() < n => dataset_ids.push(value),
+                    Some(n) => {
+                        return Err(format!("More than {} DSET_IDS values at line {}.", n, line_number + 1));
+                    }
+                }
             }
         }
-    }
-
-    // Read volumetric data
-    let total_points = (steps_number[0] as usize) * (steps_number[1] as usize) * (steps_number[2] as usize);
-    let mut cube_data_flat: Vec<f64> = Vec::with_capacity(total_points);
-
-    // Collect remaining lines and parse all values
-    for (line_number, data_line) in lines {
-        for value_str in data_line.trim().split_whitespace() {
-            let value: f64 = value_str
-                .parse()
-                .map_err(|_| format!("Invalid volumetric data value at line {}.", line_number + 1))?;
-            cube_data_flat.push(value);
+        if nval > 1 && !dataset_ids.is_empty() {
+            return Err(format!(
+                "Unsupported combination of DSET_IDS and {} values per voxel in cube file.",
+                nval
+            ));
         }
     }
 
-    if cube_data_flat.len() != total_points {
-        return Err(format!(
-            "Mismatch in volumetric data: expected {} points, found {}.",
-            total_points,
-            cube_data_flat.len()
-        ));
+    // Read volumetric data
+    let num_values = if dataset_ids.is_empty() {
+        nval
+    } else {
+        dataset_ids.len()
+    };
+    let total_points = count_values(content, std::array::from_fn(|i| steps_number[i] as usize), num_values)?;
+
+    let mut datasets: Vec<Vec<f64>> = vec![vec![]; num_values];
+    let mut deferred: Option<DeferredGrid> = None;
+
+    if options.cube.skip_volume_data {
+        // Remember where the data starts, so it can be parsed later without parsing the header again
+        let (line, offset) = match lines.clone().next() {
+            Some((line_number, data_line)) => (line_number, data_line.as_ptr() as usize - content.as_ptr() as usize),
+            None => (content.lines().count(), content.len()),
+        };
+        deferred = Some(DeferredGrid {
+            offset,
+            line,
+            num_values,
+            index: 0,
+        });
+    } else {
+        let cube_data_flat = read_volume_data(lines, total_points, cancel)?;
+        datasets = if num_values == 1 {
+            vec![cube_data_flat]
+        } else {
+            (0..num_values)
+                .map(|index| select_dataset(&cube_data_flat, num_values, index))
+                .collect()
+        };
     }
 
-    // Reshape flat data into 3D array [n1][n2][n3]
-    let n1 = steps_number[0] as usize;
-    let n2 = steps_number[1] as usize;
-    let n3 = steps_number[2] as usize;
-
-    let mut cube_data: Vec<Vec<Vec<f64>>> = Vec::with_capacity(n1);
-    let mut idx = 0;
-
-    for _ in 0..n1 {
-        let mut plane: Vec<Vec<f64>> = Vec::with_capacity(n2);
-        for _ in 0..n2 {
-            let row: Vec<f64> = cube_data_flat[idx..idx + n3].to_vec();
-            plane.push(row);
-            idx += n3;
-        }
-        cube_data.push(plane);
+    let mut volume_cubes: Vec<(String, VolumeCube)> = Vec::with_capacity(num_values);
+    for (index, cube_data) in datasets.into_iter().enumerate() {
+        let dataset_id = dataset_ids.get(index).copied();
+        let name = match dataset_id {
+            Some(id) => format!("MO {}", id),
+            None => format!("Dataset {}", index + 1),
+        };
+        let volume_cube = VolumeCube {
+            comment1: comment_1.clone(),
+            comment2: comment_2.clone(),
+            box_origin: box_origin.clone(),
+            steps_number: steps_number.clone(),
+            steps_size: steps_size.clone(),
+            cube_data,
+            dataset_id,
+            deferred: deferred.clone().map(|grid| DeferredGrid { index, ..grid }),
+        };
+        volume_cubes.push((name, volume_cube));
     }
 
-    // Create VolumeCube data
-    let volume_cube = VolumeCube {
-        comment1: comment_1,
-        comment2: comment_2,
-        box_origin,
-        steps_number,
-        steps_size,
-        cube_data,
-    };
-
     // Create atomic coordinates node
     let coords = AtomicCoordinates {
         atomic_num: atom_atomic_num,
         x: atom_coord_x,
         y: atom_coord_y,
         z: atom_coord_z,
+        connectivity: None,
+        charges: None,
+        chains: None,
+        cell: None,
+        isotopes: None,
     };
 
     let at_coord_node = Node {
@@ -310,12 +399,30 @@ pub fn parse(content: &str, file_name: &str) -> Result<Node, String> {
     };
 
     // Create result node
-    let result = Node {
-        name: file_name.to_string(),
-        r#type: "mircmd:chemistry:volume_cube".to_string(),
-        data: serde_json::to_vec(&volume_cube).map_err(|e| format!("Failed to serialize volume cube: {}", e))?,
-        children: vec![at_coord_node],
-    };
+    if volume_cubes.len() == 1 {
+        let (_, volume_cube) = &volume_cubes[0];
+        return Ok(Node {
+            name: file_name.to_string(),
+            r#type: "mircmd:chemistry:volume_cube".to_string(),
+            data: serde_json::to_vec(volume_cube).map_err(|e| format!("Failed to serialize volume cube: {}", e))?,
+            children: vec![at_coord_node],
+        });
+    }
+
+    let mut children = vec![at_coord_node];
+    for (name, volume_cube) in &volume_cubes {
+        children.push(Node {
+            name: name.clone(),
+            r#type: "mircmd:chemistry:volume_cube".to_string(),
+            data: serde_json::to_vec(volume_cube).map_err(|e| format!("Failed to serialize volume cube: {}", e))?,
+            children: vec![],
+        });
+    }
 
-    Ok(result)
+    Ok(Node {
+        name: file_name.to_string(),
+        r#type: "mircmd:chemistry:volume_cube_group".to_string(),
+        data: vec![],
+        children,
+    })
 }
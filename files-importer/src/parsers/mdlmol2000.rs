@@ -1,33 +1,31 @@
 // Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
 // Licensed under the MIT License
 
-use std::fs::File;
-use std::io::{BufRead, BufReader};
-use std::path::Path;
+use shared_lib::cancellation::CancellationToken;
+use shared_lib::periodic_table::{get_element_by_symbol, get_isotope_mass, parse_isotope_label};
+use shared_lib::types::{AtomicCoordinates, Connection, Molecule, Node};
 
-use shared_lib::periodic_table::get_element_by_symbol;
-use shared_lib::types::{AtomicCoordinates, Molecule, Node};
+use crate::options::ImportOptions;
+use crate::warnings::WarningCollector;
 
 const MAX_VALIDATION_LINES: usize = 4;
+// Largest numbers of atoms and bonds of V2000, counts of corrupt files are not trusted beyond it for allocations
+const MAX_COUNT: usize = 999;
+// Bond types of V2000: single, double, triple, aromatic and the query types 5-8
+const BOND_TYPES: std::ops::RangeInclusive<u8> = 1..=8;
 
 #[derive(PartialEq)]
 enum ParserState {
     Init,
     Control,
     Atom,
+    Bond,
+    Properties,
 }
 
 /// Validates if the file is in MDL Mol V2000 format.
-pub fn test(file_path: &str) -> Result<bool, String> {
-    let path = Path::new(file_path);
-    let file = File::open(path).map_err(|e| e.to_string())?;
-    let reader = BufReader::new(file);
-
-    let lines: Vec<String> = reader
-        .lines()
-        .take(MAX_VALIDATION_LINES)
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| e.to_string())?;
+pub fn test(content: &str) -> Result<bool, String> {
+    let lines: Vec<&str> = content.lines().take(MAX_VALIDATION_LINES).collect();
 
     // Need at least 4 lines
     if lines.len() < 4 {
@@ -39,7 +37,12 @@ pub fn test(file_path: &str) -> Result<bool, String> {
 }
 
 /// Parses a MDL Mol V2000 file.
-pub fn parse(content: &str, file_name: &str) -> Result<Node, String> {
+pub fn parse(
+    content: &str,
+    file_name: &str,
+    _options: &ImportOptions,
+    cancel: &CancellationToken,
+) -> Result<Node, String> {
     let mut result = Node {
         name: file_name.to_string(),
         r#type: "mircmd:chemistry:molecule".to_string(),
@@ -48,21 +51,31 @@ pub fn parse(content: &str, file_name: &str) -> Result<Node, String> {
             atomic_num: vec![],
             charge: 0,
             name: file_name.to_string(),
+            multiplicity: None,
         })
         .map_err(|e| format!("Failed to serialize molecule: {}", e))?,
         children: vec![],
     };
 
+    let mut warnings = WarningCollector::new();
     let mut title = String::new();
     let mut state = ParserState::Init;
     let mut num_atoms: usize = 0;
+    let mut num_bonds: usize = 0;
     let mut num_read_at_cards: usize = 0;
+    let mut num_read_bond_cards: usize = 0;
+    let mut connectivity: Vec<Connection> = vec![];
     let mut atom_atomic_num: Vec<i32> = vec![];
     let mut atom_coord_x: Vec<f64> = vec![];
     let mut atom_coord_y: Vec<f64> = vec![];
     let mut atom_coord_z: Vec<f64> = vec![];
+    let mut atom_charges: Vec<f64> = vec![];
+    let mut charge_lines_read = false;
+    // Mass numbers of labelled atoms, 0 for the natural composition
+    let mut atom_isotopes: Vec<u32> = vec![];
 
     for (line_number, line) in content.lines().enumerate() {
+        cancel.check()?;
         match state {
             ParserState::Init => {
                 if title.is_empty() {
@@ -93,7 +106,7 @@ pub fn parse(content: &str, file_name: &str) -> Result<Node, String> {
                     ));
                 }
 
-                let num_bonds: i32 = items[1]
+                let bonds: i32 = items[1]
                     .parse()
                     .map_err(|_| format!("Invalid control line {}, expected number of bonds.", line_number + 1))?;
 
@@ -105,19 +118,23 @@ pub fn parse(content: &str, file_name: &str) -> Result<Node, String> {
                     ));
                 }
 
-                if num_bonds < 0 {
+                if bonds < 0 {
                     return Err(format!(
                         "Invalid number of bonds {} defined in line {}.",
-                        num_bonds,
+                        bonds,
                         line_number + 1
                     ));
                 }
+                num_bonds = bonds as usize;
 
                 num_read_at_cards = 0;
-                atom_atomic_num = Vec::with_capacity(num_atoms);
-                atom_coord_x = Vec::with_capacity(num_atoms);
-                atom_coord_y = Vec::with_capacity(num_atoms);
-                atom_coord_z = Vec::with_capacity(num_atoms);
+                let capacity = num_atoms.min(MAX_COUNT);
+                atom_atomic_num = Vec::with_capacity(capacity);
+                atom_coord_x = Vec::with_capacity(capacity);
+                atom_coord_y = Vec::with_capacity(capacity);
+                atom_coord_z = Vec::with_capacity(capacity);
+                atom_charges = Vec::with_capacity(capacity);
+                atom_isotopes = Vec::with_capacity(capacity);
                 state = ParserState::Atom;
             }
             ParserState::Atom => {
@@ -127,9 +144,20 @@ pub fn parse(content: &str, file_name: &str) -> Result<Node, String> {
                     return Err(format!("Invalid atom coordinate value(s) at line {}.", line_number + 1));
                 }
 
-                let atomic_num = get_element_by_symbol(items[3])
-                    .ok_or(format!("Invalid atom symbol at line {}.", line_number + 1))?
-                    .atomic_number;
+                // D and T are common symbols of hydrogen isotopes
+                let (atomic_num, mass_number) = match get_element_by_symbol(items[3]) {
+                    Some(element) => (element.atomic_number, 0),
+                    None => match parse_isotope_label(items[3]) {
+                        Some(isotope) => isotope,
+                        None => {
+                            warnings.warn(
+                                line_number,
+                                format!("Unrecognized atom symbol {}, imported as a dummy atom.", items[3]),
+                            );
+                            (-1, 0)
+                        }
+                    },
+                };
                 let coord_x: f64 = items[0]
                     .parse()
                     .map_err(|_| format!("Invalid atom coordinate value(s) at line {}.", line_number + 1))?;
@@ -140,38 +168,158 @@ pub fn parse(content: &str, file_name: &str) -> Result<Node, String> {
                     .parse()
                     .map_err(|_| format!("Invalid atom coordinate value(s) at line {}.", line_number + 1))?;
 
+                // Charge code after the mass difference: 1, 2, 3 are +3, +2, +1; 5, 6, 7 are -1, -2, -3
+                let charge = match items.get(5).and_then(|v| v.parse::<i32>().ok()) {
+                    Some(code @ 1..=7) => (4 - code) as f64,
+                    _ => 0.0,
+                };
+
+                atom_isotopes.push(mass_number);
                 num_read_at_cards += 1;
                 atom_atomic_num.push(atomic_num);
+                atom_charges.push(charge);
                 atom_coord_x.push(coord_x);
                 atom_coord_y.push(coord_y);
                 atom_coord_z.push(coord_z);
 
                 if num_read_at_cards == num_atoms {
-                    if title.is_empty() {
-                        title = file_name.to_string();
+                    if num_bonds == 0 {
+                        state = ParserState::Properties;
+                    } else {
+                        num_read_bond_cards = 0;
+                        connectivity = Vec::with_capacity(num_bonds.min(MAX_COUNT));
+                        state = ParserState::Bond;
                     }
+                }
+            }
+            ParserState::Bond => {
+                // Fixed columns: first atom, second atom, bond type (3 characters each)
+                let atom = |range| field::<usize>(line, range).filter(|atom| (1..=num_atoms).contains(atom));
+                let order = field::<u8>(line, 6..9).filter(|order| BOND_TYPES.contains(order));
+                match (atom(0..3), atom(3..6), order) {
+                    (Some(atom_1), Some(atom_2), Some(order)) => {
+                        connectivity.push(Connection {
+                            atom_1: atom_1 - 1,
+                            atom_2: atom_2 - 1,
+                            order,
+                        });
+                    }
+                    _ => warnings.warn(line_number, "Invalid bond line was skipped.".to_string()),
+                }
 
-                    let coords = AtomicCoordinates {
-                        atomic_num: atom_atomic_num.clone(),
-                        x: atom_coord_x.clone(),
-                        y: atom_coord_y.clone(),
-                        z: atom_coord_z.clone(),
-                    };
-
-                    let at_coord_node = Node {
-                        name: title.clone(),
-                        r#type: "mircmd:chemistry:atomic_coordinates".to_string(),
-                        data: serde_json::to_vec(&coords)
-                            .map_err(|e| format!("Failed to serialize coordinates: {}", e))?,
-                        children: vec![],
+                num_read_bond_cards += 1;
+                if num_read_bond_cards == num_bonds {
+                    state = ParserState::Properties;
+                }
+            }
+            ParserState::Properties => {
+                if line.starts_with("M  END") {
+                    break;
+                }
+                if line.starts_with("M  CHG") {
+                    // Charges of the property block supersede the ones of the atom block
+                    if !charge_lines_read {
+                        atom_charges.iter_mut().for_each(|charge| *charge = 0.0);
+                        charge_lines_read = true;
+                    }
+                    match parse_atom_values(line, num_atoms) {
+                        Some(charges) => {
+                            for (atom, charge) in charges {
+                                atom_charges[atom] = charge as f64;
+                            }
+                        }
+                        None => warnings.warn(line_number, "Invalid charge line was skipped.".to_string()),
+                    }
+                }
+                if line.starts_with("M  ISO") {
+                    // Absolute mass numbers, which must be isotopes of the elements of their atoms
+                    let is_isotope = |&(atom, mass_number): &(usize, i32)| {
+                        u32::try_from(mass_number)
+                            .is_ok_and(|mass_number| get_isotope_mass(atom_atomic_num[atom], mass_number).is_some())
                     };
-
-                    result.children.push(at_coord_node);
-                    break; // Stop after reading atoms (skip bonds section)
+                    match parse_atom_values(line, num_atoms).filter(|isotopes| isotopes.iter().all(is_isotope)) {
+                        Some(isotopes) => {
+                            for (atom, mass_number) in isotopes {
+                                atom_isotopes[atom] = mass_number as u32;
+                            }
+                        }
+                        None => warnings.warn(line_number, "Invalid isotope line was skipped.".to_string()),
+                    }
                 }
             }
         }
     }
 
+    if num_atoms > 0 && num_read_at_cards == num_atoms {
+        if title.is_empty() {
+            title = file_name.to_string();
+        }
+
+        // Charges of the file are formal, their sum is the charge of the molecule
+        result.data = serde_json::to_vec(&Molecule {
+            n_atoms: num_atoms as i32,
+            atomic_num: atom_atomic_num.clone(),
+            charge: atom_charges.iter().sum::<f64>().round() as i32,
+            name: file_name.to_string(),
+            multiplicity: None,
+        })
+        .map_err(|e| format!("Failed to serialize molecule: {}", e))?;
+
+        let coords = AtomicCoordinates {
+            atomic_num: atom_atomic_num,
+            x: atom_coord_x,
+            y: atom_coord_y,
+            z: atom_coord_z,
+            connectivity: if num_bonds > 0 { Some(connectivity) } else { None },
+            charges: if atom_charges.iter().any(|&c| c != 0.0) {
+                Some(atom_charges)
+            } else {
+                None
+            },
+            chains: None,
+            cell: None,
+            isotopes: if atom_isotopes.iter().any(|&n| n != 0) {
+                Some(atom_isotopes)
+            } else {
+                None
+            },
+        };
+
+        let at_coord_node = Node {
+            name: title,
+            r#type: "mircmd:chemistry:atomic_coordinates".to_string(),
+            data: serde_json::to_vec(&coords).map_err(|e| format!("Failed to serialize coordinates: {}", e))?,
+            children: vec![],
+        };
+
+        result.children.push(at_coord_node);
+    }
+
+    warnings.attach(&mut result)?;
+
     Ok(result)
 }
+
+/// Value of a fixed-width column, `None` if the line is too short or the value is malformed.
+fn field<T: std::str::FromStr>(line: &str, range: std::ops::Range<usize>) -> Option<T> {
+    line.get(range)?.trim().parse().ok()
+}
+
+/// Zero-based atoms with their values from a `M  CHGnn8 aaa vvv ...` or `M  ISOnn8 aaa vvv ...` line, `None` if
+/// it is malformed.
+fn parse_atom_values(line: &str, num_atoms: usize) -> Option<Vec<(usize, i32)>> {
+    let items: Vec<&str> = line.get(6..)?.split_whitespace().collect();
+    let (count, pairs) = items.split_first()?;
+    let count: usize = count.parse().ok()?;
+    if count == 0 || pairs.len() != 2 * count {
+        return None;
+    }
+    pairs
+        .chunks(2)
+        .map(|pair| {
+            let atom: usize = pair[0].parse().ok()?;
+            let value: i32 = pair[1].parse().ok()?;
+            (1..=num_atoms).contains(&atom).then_some((atom - 1, value))
+        })
+        .collect()
+}
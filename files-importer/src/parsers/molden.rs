@@ -0,0 +1,183 @@
+// Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
+// Licensed under the MIT License
+
+use shared_lib::cancellation::CancellationToken;
+use shared_lib::periodic_table::get_element_by_symbol;
+use shared_lib::types::{AtomicCoordinates, Molecule, Node, VibrationalModes};
+
+use crate::options::ImportOptions;
+use crate::warnings::WarningCollector;
+
+const MAX_VALIDATION_LINES: usize = 5;
+const BOHR2ANGSTROM: f64 = 0.529177210903;
+
+const MOLDEN_SIGNATURE: &str = "[molden format]";
+const FREQUENCIES_SECTION: &str = "[freq]";
+const NORMAL_MODES_SECTION: &str = "[fr-norm-coord]";
+
+#[derive(PartialEq)]
+enum Section {
+    Other,
+    Frequencies,
+    Coordinates,
+    NormalModes,
+}
+
+/// Validates if the file is a Molden file with normal modes, e.g. MOLDEN_NORMAL of Cfour or the frequency output
+/// of ORCA and Psi4.
+pub fn test(content: &str) -> Result<bool, String> {
+    // Files with only the normal modes may start right with the frequencies
+    let signed = content.lines().take(MAX_VALIDATION_LINES).any(|line| {
+        let line = line.trim();
+        line.eq_ignore_ascii_case(MOLDEN_SIGNATURE) || line.eq_ignore_ascii_case(FREQUENCIES_SECTION)
+    });
+    Ok(signed
+        && content
+            .lines()
+            .any(|line| line.trim().eq_ignore_ascii_case(NORMAL_MODES_SECTION)))
+}
+
+/// Element symbols are written in any case, e.g. `CL` or `cl`.
+fn atomic_number(symbol: &str) -> Option<i32> {
+    let mut chars = symbol.chars();
+    let first = chars.next()?;
+    let symbol = first.to_ascii_uppercase().to_string() + &chars.as_str().to_ascii_lowercase();
+    get_element_by_symbol(&symbol).map(|element| element.atomic_number)
+}
+
+fn parse_floats(items: &[&str]) -> Option<Vec<f64>> {
+    items.iter().map(|item| item.parse().ok()).collect()
+}
+
+/// Parses the `[FREQ]`, `[FR-COORD]` and `[FR-NORM-COORD]` sections of a Molden file. The equilibrium structure
+/// and the modes are returned in a `mircmd:chemistry:molecule` node, other sections are skipped.
+pub fn parse(
+    content: &str,
+    file_name: &str,
+    _options: &ImportOptions,
+    cancel: &CancellationToken,
+) -> Result<Node, String> {
+    let mut warnings = WarningCollector::new();
+    let mut section = Section::Other;
+    let mut frequencies: Vec<f64> = vec![];
+    let mut coords = AtomicCoordinates {
+        atomic_num: vec![],
+        x: vec![],
+        y: vec![],
+        z: vec![],
+        connectivity: None,
+        charges: None,
+        chains: None,
+        cell: None,
+        isotopes: None,
+    };
+    let mut displacements: Vec<Vec<[f64; 3]>> = vec![];
+
+    for (line_number, line) in content.lines().enumerate() {
+        cancel.check()?;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        if line.starts_with('[') {
+            section = match line.to_ascii_lowercase().as_str() {
+                FREQUENCIES_SECTION => Section::Frequencies,
+                "[fr-coord]" => Section::Coordinates,
+                NORMAL_MODES_SECTION => Section::NormalModes,
+                _ => Section::Other,
+            };
+            continue;
+        }
+
+        let items: Vec<&str> = line.split_whitespace().collect();
+        match section {
+            Section::Other => {}
+            Section::Frequencies => match items[0].parse() {
+                Ok(frequency) => frequencies.push(frequency),
+                Err(_) => return Err(format!("Invalid frequency at line {}.", line_number + 1)),
+            },
+            Section::Coordinates => {
+                let position = (items.len() >= 4).then(|| parse_floats(&items[1..4])).flatten();
+                let Some(position) = position else {
+                    return Err(format!("Invalid atom at line {}.", line_number + 1));
+                };
+                let at_num = atomic_number(items[0]).unwrap_or_else(|| {
+                    warnings.warn(
+                        line_number,
+                        format!("Unrecognized atom symbol {}, imported as a dummy atom.", items[0]),
+                    );
+                    -1
+                });
+                coords.atomic_num.push(at_num);
+                coords.x.push(position[0] * BOHR2ANGSTROM);
+                coords.y.push(position[1] * BOHR2ANGSTROM);
+                coords.z.push(position[2] * BOHR2ANGSTROM);
+            }
+            Section::NormalModes => {
+                if items[0].eq_ignore_ascii_case("vibration") {
+                    displacements.push(vec![]);
+                    continue;
+                }
+                let displacement = (items.len() >= 3).then(|| parse_floats(&items[..3])).flatten();
+                match (displacements.last_mut(), displacement) {
+                    (Some(mode), Some(d)) => mode.push([d[0], d[1], d[2]]),
+                    _ => return Err(format!("Invalid displacement at line {}.", line_number + 1)),
+                }
+            }
+        }
+    }
+
+    let num_atoms = coords.atomic_num.len();
+    if num_atoms == 0 || displacements.is_empty() {
+        return Err("No normal modes found.".to_string());
+    }
+    if frequencies.len() != displacements.len() {
+        return Err(format!(
+            "Numbers of frequencies ({}) and normal modes ({}) differ.",
+            frequencies.len(),
+            displacements.len()
+        ));
+    }
+    if let Some(mode) = displacements.iter().position(|mode| mode.len() != num_atoms) {
+        return Err(format!(
+            "Normal mode {} has {} displacements for {} atoms.",
+            mode + 1,
+            displacements[mode].len(),
+            num_atoms
+        ));
+    }
+
+    let molecule = Molecule {
+        n_atoms: num_atoms as i32,
+        atomic_num: coords.atomic_num.clone(),
+        charge: 0,
+        name: file_name.to_string(),
+        multiplicity: None,
+    };
+    let coords_node = Node {
+        name: "Equilibrium".to_string(),
+        r#type: "mircmd:chemistry:atomic_coordinates".to_string(),
+        data: serde_json::to_vec(&coords).map_err(|e| format!("Failed to serialize coordinates: {}", e))?,
+        children: vec![],
+    };
+    let modes = VibrationalModes {
+        frequencies,
+        displacements,
+        atomic_coordinates: Some(coords),
+    };
+    let modes_node = Node {
+        name: format!("Vibrational modes ({})", modes.frequencies.len()),
+        r#type: "mircmd:chemistry:vibrational_modes".to_string(),
+        data: serde_json::to_vec(&modes).map_err(|e| format!("Failed to serialize vibrational modes: {}", e))?,
+        children: vec![],
+    };
+    let mut result = Node {
+        name: file_name.to_string(),
+        r#type: "mircmd:chemistry:molecule".to_string(),
+        data: serde_json::to_vec(&molecule).map_err(|e| format!("Failed to serialize molecule: {}", e))?,
+        children: vec![coords_node, modes_node],
+    };
+    warnings.attach(&mut result)?;
+
+    Ok(result)
+}
@@ -1,15 +1,16 @@
 // Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
 // Licensed under the MIT License
 
-use std::fs::File;
-use std::io::{BufRead, BufReader};
-use std::path::Path;
-
 use regex::Regex;
 
+use shared_lib::cancellation::CancellationToken;
 use shared_lib::periodic_table::get_element_by_symbol;
 use shared_lib::types::{AtomicCoordinates, Node};
 
+use crate::options::ImportOptions;
+use crate::trajectory;
+use crate::warnings::WarningCollector;
+
 const MAX_VALIDATION_LINES: usize = 1;
 
 #[derive(PartialEq)]
@@ -38,26 +39,23 @@ fn get_format_version(line: &str) -> Option<i32> {
 }
 
 /// Validates if the file is in UNEX format.
-pub fn test(file_path: &str) -> Result<bool, String> {
-    let path = Path::new(file_path);
-    let file = File::open(path).map_err(|e| e.to_string())?;
-    let reader = BufReader::new(file);
-
-    let lines: Vec<String> = reader
-        .lines()
-        .take(MAX_VALIDATION_LINES)
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| e.to_string())?;
+pub fn test(content: &str) -> Result<bool, String> {
+    let lines: Vec<&str> = content.lines().take(MAX_VALIDATION_LINES).collect();
 
     if lines.is_empty() {
         return Ok(false);
     }
 
-    Ok(get_format_version(&lines[0]).is_some())
+    Ok(get_format_version(lines[0]).is_some())
 }
 
 /// Parses UNEX 1.x format.
-fn parse_unex1x(content: &str, file_name: &str) -> Result<Node, String> {
+fn parse_unex1x(
+    content: &str,
+    file_name: &str,
+    options: &ImportOptions,
+    cancel: &CancellationToken,
+) -> Result<Node, String> {
     let mut result = Node {
         name: file_name.to_string(),
         r#type: "mircmd:chemistry:unex".to_string(),
@@ -68,9 +66,11 @@ fn parse_unex1x(content: &str, file_name: &str) -> Result<Node, String> {
     let mut molecules: std::collections::HashMap<String, usize> = std::collections::HashMap::new();
     let mut mol_cart_set_number: std::collections::HashMap<String, i32> = std::collections::HashMap::new();
 
-    let mut lines = content.lines().peekable();
+    let mut warnings = WarningCollector::new();
+    let mut lines = content.lines().enumerate();
 
-    while let Some(line) = lines.next() {
+    while let Some((_, line)) = lines.next() {
+        cancel.check()?;
         if line.contains("> Cartesian coordinates of all atoms (Angstroms) in") {
             let molecule_name = line.split('>').next().unwrap_or("").trim().to_string();
 
@@ -102,7 +102,7 @@ fn parse_unex1x(content: &str, file_name: &str) -> Result<Node, String> {
             let mut atom_coord_y: Vec<f64> = vec![];
             let mut atom_coord_z: Vec<f64> = vec![];
 
-            for block_line in lines.by_ref() {
+            for (line_number, block_line) in lines.by_ref() {
                 if block_line.contains("--") {
                     break;
                 }
@@ -118,6 +118,8 @@ fn parse_unex1x(content: &str, file_name: &str) -> Result<Node, String> {
                         atom_coord_x.push(x);
                         atom_coord_y.push(y);
                         atom_coord_z.push(z);
+                    } else {
+                        warnings.warn(line_number, "Unreadable atom row was skipped.".to_string());
                     }
                 }
             }
@@ -127,6 +129,11 @@ fn parse_unex1x(content: &str, file_name: &str) -> Result<Node, String> {
                 x: atom_coord_x,
                 y: atom_coord_y,
                 z: atom_coord_z,
+                connectivity: None,
+                charges: None,
+                chains: None,
+                cell: None,
+                isotopes: None,
             };
 
             let at_coord_node = Node {
@@ -136,15 +143,28 @@ fn parse_unex1x(content: &str, file_name: &str) -> Result<Node, String> {
                 children: vec![],
             };
 
-            result.children[mol_idx].children.push(at_coord_node);
+            if options.trajectory.keep((*set_num - 1) as usize) {
+                result.children[mol_idx].children.push(at_coord_node);
+            }
         }
     }
 
+    for molecule in result.children.iter_mut() {
+        options.trajectory.apply_last_only(&mut molecule.children);
+        trajectory::attach(molecule)?;
+    }
+    warnings.attach(&mut result)?;
+
     Ok(result)
 }
 
 /// Parses UNEX 2.x format.
-fn parse_unex2x(content: &str, file_name: &str) -> Result<Node, String> {
+fn parse_unex2x(
+    content: &str,
+    file_name: &str,
+    options: &ImportOptions,
+    cancel: &CancellationToken,
+) -> Result<Node, String> {
     let mut result = Node {
         name: file_name.to_string(),
         r#type: "mircmd:chemistry:unex".to_string(),
@@ -155,9 +175,11 @@ fn parse_unex2x(content: &str, file_name: &str) -> Result<Node, String> {
     let mut molecules: std::collections::HashMap<String, usize> = std::collections::HashMap::new();
     let mut mol_cart_set_number: std::collections::HashMap<String, i32> = std::collections::HashMap::new();
 
-    let mut lines = content.lines().peekable();
+    let mut warnings = WarningCollector::new();
+    let mut lines = content.lines().enumerate();
 
-    while let Some(line) = lines.next() {
+    while let Some((_, line)) = lines.next() {
+        cancel.check()?;
         if line.contains("Cartesian coordinates (Angstroms) of atoms in") {
             let parts: Vec<&str> = line.split_whitespace().collect();
             let molecule_name = if parts.len() > 6 {
@@ -187,7 +209,7 @@ fn parse_unex2x(content: &str, file_name: &str) -> Result<Node, String> {
             let mut delimiter_number = 0;
 
             // Read header to determine format
-            for header_line in lines.by_ref() {
+            for (_, header_line) in lines.by_ref() {
                 if header_line.contains("Format:") {
                     let format_parts: Vec<&str> = header_line.split_whitespace().collect();
                     if format_parts.len() >= 2 {
@@ -224,7 +246,7 @@ fn parse_unex2x(content: &str, file_name: &str) -> Result<Node, String> {
             let mut atom_coord_y: Vec<f64> = vec![];
             let mut atom_coord_z: Vec<f64> = vec![];
 
-            for block_line in lines.by_ref() {
+            for (line_number, block_line) in lines.by_ref() {
                 if block_line.contains("--") {
                     break;
                 }
@@ -244,14 +266,23 @@ fn parse_unex2x(content: &str, file_name: &str) -> Result<Node, String> {
                                 atom_coord_x.push(x);
                                 atom_coord_y.push(y);
                                 atom_coord_z.push(z);
+                            } else {
+                                warnings.warn(line_number, "Unreadable atom row was skipped.".to_string());
                             }
                         }
                     }
                     Unex2XyzFormat::Mol => {
                         if items.len() >= 4 {
-                            let at_num = get_element_by_symbol(items[0])
-                                .ok_or(format!("Invalid atom symbol {}", items[0]))?
-                                .atomic_number;
+                            let at_num = match get_element_by_symbol(items[0]) {
+                                Some(element) => element.atomic_number,
+                                None => {
+                                    warnings.warn(
+                                        line_number,
+                                        format!("Unrecognized atom symbol {}, imported as a dummy atom.", items[0]),
+                                    );
+                                    -1
+                                }
+                            };
 
                             let x: f64 = items[1].parse().map_err(|_| "Invalid x coordinate".to_string())?;
                             let y: f64 = items[2].parse().map_err(|_| "Invalid y coordinate".to_string())?;
@@ -272,6 +303,11 @@ fn parse_unex2x(content: &str, file_name: &str) -> Result<Node, String> {
                 x: atom_coord_x,
                 y: atom_coord_y,
                 z: atom_coord_z,
+                connectivity: None,
+                charges: None,
+                chains: None,
+                cell: None,
+                isotopes: None,
             };
 
             let at_coord_node = Node {
@@ -281,24 +317,37 @@ fn parse_unex2x(content: &str, file_name: &str) -> Result<Node, String> {
                 children: vec![],
             };
 
-            result.children[mol_idx].children.push(at_coord_node);
+            if options.trajectory.keep((*set_num - 1) as usize) {
+                result.children[mol_idx].children.push(at_coord_node);
+            }
         }
     }
 
+    for molecule in result.children.iter_mut() {
+        options.trajectory.apply_last_only(&mut molecule.children);
+        trajectory::attach(molecule)?;
+    }
+    warnings.attach(&mut result)?;
+
     Ok(result)
 }
 
 /// Parses a UNEX file.
-pub fn parse(content: &str, file_name: &str) -> Result<Node, String> {
+pub fn parse(
+    content: &str,
+    file_name: &str,
+    options: &ImportOptions,
+    cancel: &CancellationToken,
+) -> Result<Node, String> {
     let first_line = content.lines().next().unwrap_or("");
 
     let version = get_format_version(first_line).ok_or_else(|| "Invalid UNEX file format.".to_string())?;
 
     // UNEX 1.x
     if version < 2_000_000 {
-        parse_unex1x(content, file_name)
+        parse_unex1x(content, file_name, options, cancel)
     } else {
         // UNEX >= 2.x
-        parse_unex2x(content, file_name)
+        parse_unex2x(content, file_name, options, cancel)
     }
 }
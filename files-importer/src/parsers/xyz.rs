@@ -1,15 +1,17 @@
 // Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
 // Licensed under the MIT License
 
-use std::fs::File;
-use std::io::{BufRead, BufReader};
-use std::path::Path;
-
 use regex::Regex;
 
-use shared_lib::periodic_table::get_element_by_symbol;
+use shared_lib::cancellation::CancellationToken;
+use shared_lib::cell::UnitCell;
+use shared_lib::periodic_table::{get_element_by_symbol, parse_isotope_label};
 use shared_lib::types::{AtomicCoordinates, Molecule, Node};
 
+use crate::options::ImportOptions;
+use crate::trajectory;
+use crate::warnings::WarningCollector;
+
 #[derive(PartialEq)]
 enum ParserState {
     Init,
@@ -19,18 +21,28 @@ enum ParserState {
 
 const MAX_VALIDATION_LINES: usize = 10;
 
+/// Periodic cell from the `Lattice="ax ay az bx by bz cx cy cz"` key of an extended XYZ comment line.
+fn parse_lattice(comment: &str, to_angstrom: f64) -> Option<UnitCell> {
+    let start = comment.find("Lattice=\"")? + "Lattice=\"".len();
+    let end = start + comment[start..].find('"')?;
+    let values: Vec<f64> = comment[start..end]
+        .split_whitespace()
+        .map(|value| value.parse::<f64>().map(|v| v * to_angstrom))
+        .collect::<Result<_, _>>()
+        .ok()?;
+    let vectors: [f64; 9] = values.try_into().ok()?;
+    UnitCell::new([
+        [vectors[0], vectors[1], vectors[2]],
+        [vectors[3], vectors[4], vectors[5]],
+        [vectors[6], vectors[7], vectors[8]],
+    ])
+    .ok()
+}
+
 /// Validates if the file is in XYZ format by reading only first few lines.
 /// Returns true if the file appears to be a valid XYZ file, false otherwise.
-pub fn test(file_path: &str) -> Result<bool, String> {
-    let path = Path::new(file_path);
-    let file = File::open(path).map_err(|e| e.to_string())?;
-    let reader = BufReader::new(file);
-
-    let lines: Vec<String> = reader
-        .lines()
-        .take(MAX_VALIDATION_LINES)
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| e.to_string())?;
+pub fn test(content: &str) -> Result<bool, String> {
+    let lines: Vec<&str> = content.lines().take(MAX_VALIDATION_LINES).collect();
 
     if lines.is_empty() {
         return Ok(false);
@@ -48,8 +60,9 @@ pub fn test(file_path: &str) -> Result<bool, String> {
 
     // Second line is comment, it can be anything (even empty)
     // Validate coordinate cards starting from line 3 (index 2)
-    // Regex pattern from Python: ^([A-Z][a-z]?|[0-9]+)([\s]+[-+]?[0-9]*\.?[0-9]+([eE][-+]?[0-9]+)?){3}$
-    let card_validator = Regex::new(r"^([A-Z][a-z]?|[0-9]+)([\s]+[-+]?[0-9]*\.?[0-9]+([eE][-+]?[0-9]+)?){3}$")
+    // Regex pattern from Python: ^([A-Z][a-z]?|[0-9]+)([\s]+[-+]?[0-9]*\.?[0-9]+([eE][-+]?[0-9]+)?){3}$,
+    // the symbol may be prefixed with the mass number of the isotope, e.g. 13C
+    let card_validator = Regex::new(r"^([0-9]*[A-Z][a-z]?|[0-9]+)([\s]+[-+]?[0-9]*\.?[0-9]+([eE][-+]?[0-9]+)?){3}$")
         .map_err(|e| format!("Failed to compile regex: {}", e))?;
 
     // Validate available cards (from line 3 up to numat + 2, limited by what we've read)
@@ -67,7 +80,12 @@ pub fn test(file_path: &str) -> Result<bool, String> {
     Ok(true)
 }
 
-pub fn parse(content: &str, file_name: &str) -> Result<Node, String> {
+pub fn parse(
+    content: &str,
+    file_name: &str,
+    options: &ImportOptions,
+    cancel: &CancellationToken,
+) -> Result<Node, String> {
     let mut result = Node {
         name: file_name.to_string(),
         r#type: "mircmd:chemistry:molecule".to_string(),
@@ -76,21 +94,28 @@ pub fn parse(content: &str, file_name: &str) -> Result<Node, String> {
             atomic_num: vec![],
             charge: 0,
             name: file_name.to_string(),
+            multiplicity: None,
         })
         .map_err(|e| format!("Failed to serialize molecule: {}", e))?,
         children: vec![],
     };
 
+    let mut warnings = WarningCollector::new();
     let mut state = ParserState::Init;
     let mut num_atoms: usize = 0;
     let mut num_read_cards: usize = 0;
+    let mut num_frames: usize = 0;
+    let to_angstrom = options.xyz.unit.to_angstrom();
     let mut title = String::new();
+    let mut cell: Option<UnitCell> = None;
     let mut atom_atomic_num: Vec<i32> = vec![];
+    let mut atom_isotopes: Vec<u32> = vec![];
     let mut atom_coord_x: Vec<f64> = vec![];
     let mut atom_coord_y: Vec<f64> = vec![];
     let mut atom_coord_z: Vec<f64> = vec![];
 
     for (line_number, line) in content.lines().enumerate() {
+        cancel.check()?;
         match state {
             ParserState::Init => {
                 let trimmed = line.trim();
@@ -111,12 +136,14 @@ pub fn parse(content: &str, file_name: &str) -> Result<Node, String> {
             }
             ParserState::Comment => {
                 title = line.trim().to_string();
+                cell = parse_lattice(&title, to_angstrom);
                 if title.is_empty() {
                     title = format!("Set@line={}", line_number);
                 }
                 state = ParserState::Cards;
                 num_read_cards = 0;
                 atom_atomic_num = Vec::with_capacity(num_atoms);
+                atom_isotopes = Vec::with_capacity(num_atoms);
                 atom_coord_x = Vec::with_capacity(num_atoms);
                 atom_coord_y = Vec::with_capacity(num_atoms);
                 atom_coord_z = Vec::with_capacity(num_atoms);
@@ -127,27 +154,40 @@ pub fn parse(content: &str, file_name: &str) -> Result<Node, String> {
                     return Err(format!("Invalid atom card at line {}.", line_number + 1));
                 }
 
-                let atomic_num = match items[0].parse::<i32>() {
-                    Ok(num) => num,
-                    Err(_) => {
-                        get_element_by_symbol(items[0])
-                            .ok_or(format!("Invalid atom at line {}.", line_number + 1))?
-                            .atomic_number
-                    }
+                // Isotope labels such as D or 13C keep their mass numbers
+                let (atomic_num, mass_number) = match items[0].parse::<i32>() {
+                    Ok(num) => (num, 0),
+                    Err(_) => match get_element_by_symbol(items[0]) {
+                        Some(element) => (element.atomic_number, 0),
+                        None => match parse_isotope_label(items[0]) {
+                            Some(isotope) => isotope,
+                            None => {
+                                warnings.warn(
+                                    line_number,
+                                    format!("Unrecognized atom symbol {}, imported as a dummy atom.", items[0]),
+                                );
+                                (-1, 0)
+                            }
+                        },
+                    },
                 };
 
                 let coord_x: f64 = items[1]
-                    .parse()
-                    .map_err(|_| format!("Invalid coordinate value(s) at line {}.", line_number + 1))?;
+                    .parse::<f64>()
+                    .map_err(|_| format!("Invalid coordinate value(s) at line {}.", line_number + 1))?
+                    * to_angstrom;
                 let coord_y: f64 = items[2]
-                    .parse()
-                    .map_err(|_| format!("Invalid coordinate value(s) at line {}.", line_number + 1))?;
+                    .parse::<f64>()
+                    .map_err(|_| format!("Invalid coordinate value(s) at line {}.", line_number + 1))?
+                    * to_angstrom;
                 let coord_z: f64 = items[3]
-                    .parse()
-                    .map_err(|_| format!("Invalid coordinate value(s) at line {}.", line_number + 1))?;
+                    .parse::<f64>()
+                    .map_err(|_| format!("Invalid coordinate value(s) at line {}.", line_number + 1))?
+                    * to_angstrom;
 
                 num_read_cards += 1;
                 atom_atomic_num.push(atomic_num);
+                atom_isotopes.push(mass_number);
                 atom_coord_x.push(coord_x);
                 atom_coord_y.push(coord_y);
                 atom_coord_z.push(coord_z);
@@ -158,6 +198,14 @@ pub fn parse(content: &str, file_name: &str) -> Result<Node, String> {
                         x: atom_coord_x.clone(),
                         y: atom_coord_y.clone(),
                         z: atom_coord_z.clone(),
+                        connectivity: None,
+                        charges: None,
+                        chains: None,
+                        cell,
+                        isotopes: atom_isotopes
+                            .iter()
+                            .any(|&mass_number| mass_number != 0)
+                            .then(|| atom_isotopes.clone()),
                     };
 
                     let at_coord_node = Node {
@@ -168,7 +216,10 @@ pub fn parse(content: &str, file_name: &str) -> Result<Node, String> {
                         children: vec![],
                     };
 
-                    result.children.push(at_coord_node);
+                    if options.trajectory.keep(num_frames) {
+                        result.children.push(at_coord_node);
+                    }
+                    num_frames += 1;
 
                     // Update molecule data with parsed values
                     result.data = serde_json::to_vec(&Molecule {
@@ -176,6 +227,7 @@ pub fn parse(content: &str, file_name: &str) -> Result<Node, String> {
                         atomic_num: atom_atomic_num.clone(),
                         charge: 0,
                         name: file_name.to_string(),
+                        multiplicity: None,
                     })
                     .map_err(|e| format!("Failed to serialize molecule: {}", e))?;
 
@@ -185,5 +237,9 @@ pub fn parse(content: &str, file_name: &str) -> Result<Node, String> {
         }
     }
 
+    options.trajectory.apply_last_only(&mut result.children);
+    trajectory::attach(&mut result)?;
+    warnings.attach(&mut result)?;
+
     Ok(result)
 }
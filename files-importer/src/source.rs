@@ -0,0 +1,46 @@
+// Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
+// Licensed under the MIT License
+
+use std::ops::Deref;
+
+/// Raw content of an imported file.
+/// On native hosts the file is memory-mapped, so parsers iterate lines directly over the mapping
+/// without copying the whole file to the heap. In WASI components it is read into memory.
+pub enum FileContent {
+    #[cfg(not(target_family = "wasm"))]
+    Mapped(memmap2::Mmap),
+    Owned(Vec<u8>),
+}
+
+impl FileContent {
+    pub fn open(file_path: &str) -> Result<Self, String> {
+        #[cfg(not(target_family = "wasm"))]
+        {
+            let file = std::fs::File::open(file_path).map_err(|e| e.to_string())?;
+            let len = file.metadata().map_err(|e| e.to_string())?.len();
+            // Mapping of an empty file fails on some platforms.
+            if len > 0 {
+                // SAFETY: the mapping is read-only and lives only during the import.
+                // Truncation of the file by another process while importing is not supported.
+                let mmap = unsafe { memmap2::Mmap::map(&file) }.map_err(|e| e.to_string())?;
+                return Ok(FileContent::Mapped(mmap));
+            }
+        }
+
+        std::fs::read(file_path)
+            .map(FileContent::Owned)
+            .map_err(|e| e.to_string())
+    }
+}
+
+impl Deref for FileContent {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            #[cfg(not(target_family = "wasm"))]
+            FileContent::Mapped(mmap) => mmap,
+            FileContent::Owned(data) => data,
+        }
+    }
+}
@@ -0,0 +1,30 @@
+// Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
+// Licensed under the MIT License
+
+use shared_lib::types::{AtomicCoordinates, Node};
+
+const ATOMIC_COORDINATES_TYPE: &str = "mircmd:chemistry:atomic_coordinates";
+
+/// Appends a `mircmd:chemistry:trajectory` node with the coordinate sets of `node` as frames, e.g. steps of a
+/// geometry optimization, so a viewer opened on it can play them. Nothing is appended for a single set or for sets
+/// with different atoms.
+pub fn attach(node: &mut Node) -> Result<(), String> {
+    let frames = node
+        .children
+        .iter()
+        .filter(|child| child.r#type == ATOMIC_COORDINATES_TYPE)
+        .map(|child| serde_json::from_slice(&child.data))
+        .collect::<Result<Vec<AtomicCoordinates>, _>>()
+        .map_err(|e| format!("Failed to deserialize coordinates: {}", e))?;
+    if frames.len() < 2 || frames.iter().any(|frame| frame.atomic_num != frames[0].atomic_num) {
+        return Ok(());
+    }
+
+    node.children.push(Node {
+        name: format!("Trajectory ({} frames)", frames.len()),
+        r#type: "mircmd:chemistry:trajectory".to_string(),
+        data: serde_json::to_vec(&frames).map_err(|e| format!("Failed to serialize trajectory: {}", e))?,
+        children: vec![],
+    });
+    Ok(())
+}
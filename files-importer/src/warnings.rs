@@ -0,0 +1,42 @@
+// Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
+// Licensed under the MIT License
+
+use shared_lib::types::{Node, Severity, Warning, Warnings};
+
+/// Collects recoverable issues found by a parser, so they can be shown to the user
+/// in a `mircmd:chemistry:warnings` node instead of being silently ignored.
+#[derive(Default)]
+pub struct WarningCollector {
+    items: Vec<Warning>,
+}
+
+impl WarningCollector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `line_number` is zero-based, as produced by `lines().enumerate()`.
+    pub fn warn(&mut self, line_number: usize, message: String) {
+        self.items.push(Warning {
+            severity: Severity::Warning,
+            message,
+            line: Some(line_number + 1),
+        });
+    }
+
+    /// Appends the warnings node to `node` if anything was collected.
+    pub fn attach(self, node: &mut Node) -> Result<(), String> {
+        if self.items.is_empty() {
+            return Ok(());
+        }
+
+        node.children.push(Node {
+            name: format!("Warnings ({})", self.items.len()),
+            r#type: "mircmd:chemistry:warnings".to_string(),
+            data: serde_json::to_vec(&Warnings { items: self.items })
+                .map_err(|e| format!("Failed to serialize warnings: {}", e))?,
+            children: vec![],
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,91 @@
+// Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
+// Licensed under the MIT License
+
+//! Bond blocks of MDL Mol files.
+
+use files_importer::import_content;
+use files_importer::options::ImportOptions;
+use shared_lib::cancellation::CancellationToken;
+use shared_lib::types::{AtomicCoordinates, Node};
+
+const ATOMIC_COORDINATES_TYPE: &str = "mircmd:chemistry:atomic_coordinates";
+const WARNINGS_TYPE: &str = "mircmd:chemistry:warnings";
+
+/// Water with the bond count of the counts line and the given bond lines.
+fn water(num_bonds: usize, bond_lines: &[&str]) -> String {
+    let mut content = format!(
+        "water\n  test\n\n  3{:>3}  0  0  0  0  0  0  0  0999 V2000\n",
+        num_bonds
+    );
+    content += "    0.0000    0.0000    0.0000 O   0  0  0  0  0  0  0  0  0  0  0  0\n";
+    content += "    0.7570    0.5860    0.0000 H   0  0  0  0  0  0  0  0  0  0  0  0\n";
+    content += "   -0.7570    0.5860    0.0000 H   0  0  0  0  0  0  0  0  0  0  0  0\n";
+    for line in bond_lines {
+        content += line;
+        content += "\n";
+    }
+    content + "M  END\n"
+}
+
+fn import(content: &str) -> Node {
+    import_content(
+        content,
+        "water.mol",
+        &ImportOptions::default(),
+        &CancellationToken::none(),
+    )
+    .unwrap()
+}
+
+fn coordinates(node: &Node) -> AtomicCoordinates {
+    let child = node
+        .children
+        .iter()
+        .find(|child| child.r#type == ATOMIC_COORDINATES_TYPE)
+        .unwrap();
+    serde_json::from_slice(&child.data).unwrap()
+}
+
+fn has_warnings(node: &Node) -> bool {
+    node.children.iter().any(|child| child.r#type == WARNINGS_TYPE)
+}
+
+#[test]
+fn bonds_with_types() {
+    let node = import(&water(2, &["  1  2  1  0  0  0  0", "  1  3  2  0  0  0  0"]));
+    let connectivity = coordinates(&node).connectivity.unwrap();
+    let bonds: Vec<_> = connectivity.iter().map(|c| (c.atom_1, c.atom_2, c.order)).collect();
+    assert_eq!(bonds, vec![(0, 1, 1), (0, 2, 2)]);
+    assert!(!has_warnings(&node));
+}
+
+#[test]
+fn invalid_bond_types_are_skipped() {
+    for order in ["  0", "  9", "256", " -1"] {
+        let node = import(&water(
+            2,
+            &["  1  2  1  0  0  0  0", &format!("  1  3{}  0  0  0  0", order)],
+        ));
+        assert_eq!(coordinates(&node).connectivity.unwrap().len(), 1, "bond type {}", order);
+        assert!(has_warnings(&node));
+    }
+}
+
+/// Corrupt atom and bond counts must not be trusted for allocations.
+#[test]
+fn huge_bond_count() {
+    let content = water(2, &["  1  2  1  0  0  0  0", "  1  3  1  0  0  0  0"]).replacen("  3  2", "  3 999999999", 1);
+    let node = import(&content);
+    assert_eq!(coordinates(&node).connectivity.unwrap().len(), 2);
+
+    let content = water(0, &[]).replacen("  3  0", "999999999  0", 1);
+    assert!(
+        import_content(
+            &content,
+            "water.mol",
+            &ImportOptions::default(),
+            &CancellationToken::none()
+        )
+        .is_err()
+    );
+}
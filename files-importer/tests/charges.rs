@@ -0,0 +1,108 @@
+// Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
+// Licensed under the MIT License
+
+//! Charges and multiplicities of the molecules given by the files.
+
+use files_importer::import_content;
+use files_importer::options::ImportOptions;
+use shared_lib::cancellation::CancellationToken;
+use shared_lib::types::{AtomicCoordinates, Molecule, Node};
+
+const ATOMIC_COORDINATES_TYPE: &str = "mircmd:chemistry:atomic_coordinates";
+const CFOUR_SIGNATURE: &str = "<<<     CCCCCC     CCCCCC   |||     CCCCCC     CCCCCC   >>>";
+
+fn import(content: &str, file_name: &str, options: &ImportOptions) -> Node {
+    import_content(content, file_name, options, &CancellationToken::none()).unwrap()
+}
+
+fn molecule(node: &Node) -> Molecule {
+    serde_json::from_slice(&node.data).unwrap()
+}
+
+fn coordinates(node: &Node) -> Vec<AtomicCoordinates> {
+    node.children
+        .iter()
+        .filter(|child| child.r#type == ATOMIC_COORDINATES_TYPE)
+        .map(|child| serde_json::from_slice(&child.data).unwrap())
+        .collect()
+}
+
+fn mdl_file(atom_charge_codes: [u8; 3], property_lines: &[&str]) -> String {
+    let mut content = "nitromethane\n  test\n\n  3  2  0  0  0  0  0  0  0  0999 V2000\n".to_string();
+    for (symbol, code) in ["C", "N", "O"].iter().zip(atom_charge_codes) {
+        content += &format!(
+            "    0.0000    0.0000    0.0000 {:<3} 0{:>3}  0  0  0  0  0  0  0  0  0  0\n",
+            symbol, code
+        );
+    }
+    content += "  1  2  1  0  0  0  0\n  2  3  1  0  0  0  0\n";
+    for line in property_lines {
+        content += line;
+        content += "\n";
+    }
+    content + "M  END\n"
+}
+
+#[test]
+fn mdl_atom_block_charges() {
+    let node = import(&mdl_file([0, 3, 5], &[]), "nitromethane.mol", &ImportOptions::default());
+    assert_eq!(coordinates(&node)[0].charges, Some(vec![0.0, 1.0, -1.0]));
+    let molecule = molecule(&node);
+    assert_eq!((molecule.n_atoms, molecule.charge, molecule.multiplicity), (3, 0, None));
+    assert_eq!(molecule.atomic_num, vec![6, 7, 8]);
+}
+
+#[test]
+fn mdl_charge_lines_supersede_atom_block() {
+    let content = mdl_file([0, 3, 5], &["M  CHG  2   1  -1   2   1", "M  CHG  1   3   4"]);
+    let node = import(&content, "nitromethane.mol", &ImportOptions::default());
+    assert_eq!(coordinates(&node)[0].charges, Some(vec![-1.0, 1.0, 4.0]));
+    assert_eq!(molecule(&node).charge, 4);
+
+    let content = mdl_file([0, 0, 0], &["M  CHG  2   1  -1", "M  CHG  1   7   1"]);
+    let node = import(&content, "nitromethane.mol", &ImportOptions::default());
+    assert_eq!(coordinates(&node)[0].charges, None);
+    assert!(
+        node.children
+            .iter()
+            .any(|child| child.r#type == "mircmd:chemistry:warnings")
+    );
+}
+
+fn cfour_log(parameters: &[&str]) -> String {
+    let mut content = format!("\n {}\n", CFOUR_SIGNATURE);
+    for line in parameters {
+        content += line;
+        content += "\n";
+    }
+    for z in [1.8, 1.9] {
+        content += " Z-matrix   Atomic            Coordinates (in bohr)\n";
+        content += "  Symbol    Number           X              Y              Z\n";
+        content += " ----------------------------------------------------------------\n";
+        content += "     O         8         0.00000000     0.00000000     0.00000000\n";
+        content += &format!("     H         1         0.00000000     0.00000000     {:.8}\n", z);
+        content += &format!("     H         1         0.00000000     {:.8}     0.00000000\n", z);
+        content += " ----------------------------------------------------------------\n";
+    }
+    content
+}
+
+#[test]
+fn cfour_control_parameters() {
+    let content = cfour_log(&[
+        "       CHARGE               ICHRGE               1",
+        "       MULTIPLICITY         IMULTP               2",
+    ]);
+    for options in [
+        ImportOptions::default(),
+        ImportOptions::from_json(r#"{"trajectory": {"last_only": true}}"#).unwrap(),
+    ] {
+        let node = import(&content, "water.log", &options);
+        let molecule = molecule(&node);
+        assert_eq!((molecule.charge, molecule.multiplicity), (1, Some(2)));
+        assert_eq!(molecule.atomic_num, vec![8, 1, 1]);
+    }
+
+    let molecule = molecule(&import(&cfour_log(&[]), "water.log", &ImportOptions::default()));
+    assert_eq!((molecule.charge, molecule.multiplicity), (0, None));
+}
@@ -0,0 +1,63 @@
+// Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
+// Licensed under the MIT License
+
+//! Headers of Gaussian cube files.
+
+use files_importer::import_content;
+use files_importer::options::ImportOptions;
+use shared_lib::cancellation::CancellationToken;
+use shared_lib::types::Node;
+
+/// Hydrogen atom on a 2x2x2 grid with `header` as the number of atoms and the origin, followed by `extra_lines`
+/// and `num_values` values per point.
+fn hydrogen(header: &str, extra_lines: &[&str], num_values: usize) -> String {
+    let mut content = format!("Hydrogen\n  density\n{}\n", header);
+    content += "    2    0.500000    0.000000    0.000000\n";
+    content += "    2    0.000000    0.500000    0.000000\n";
+    content += "    2    0.000000    0.000000    0.500000\n";
+    content += "    1    1.000000    0.000000    0.000000    0.000000\n";
+    for line in extra_lines {
+        content += line;
+        content += "\n";
+    }
+    for i in 0..8 * num_values {
+        content += &format!(" {:.5E}\n", 0.1 * i as f64);
+    }
+    content
+}
+
+fn import(content: &str) -> Result<Node, String> {
+    import_content(
+        content,
+        "hydrogen.cube",
+        &ImportOptions::default(),
+        &CancellationToken::none(),
+    )
+}
+
+#[test]
+fn values_per_point() {
+    let node = import(&hydrogen("    1    0.000000    0.000000    0.000000    2", &[], 2)).unwrap();
+    assert_eq!(node.r#type, "mircmd:chemistry:volume_cube_group");
+    let cubes = node
+        .children
+        .iter()
+        .filter(|child| child.r#type == "mircmd:chemistry:volume_cube");
+    assert_eq!(cubes.count(), 2);
+}
+
+/// A number of values per point beyond the size of the file must not be trusted for allocations.
+#[test]
+fn huge_number_of_values() {
+    let content = hydrogen("    1    0.000000    0.000000    0.000000    999999999", &[], 1);
+    let error = import(&content).err().unwrap();
+    assert!(error.contains("does not fit into the file"), "{}", error);
+}
+
+#[test]
+fn dataset_ids_beyond_declared_count() {
+    let header = "   -1    0.000000    0.000000    0.000000";
+    assert!(import(&hydrogen(header, &["    2    5    6"], 2)).is_ok());
+    let error = import(&hydrogen(header, &["    2    5    6    7"], 2)).err().unwrap();
+    assert!(error.contains("More than 2 DSET_IDS values"), "{}", error);
+}
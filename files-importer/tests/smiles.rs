@@ -0,0 +1,165 @@
+// Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
+// Licensed under the MIT License
+
+//! Molecules built from SMILES strings.
+
+use files_importer::smiles_node;
+use shared_lib::geometry;
+use shared_lib::smiles;
+use shared_lib::types::{AtomicCoordinates, Connection, Molecule};
+
+fn build(smiles: &str) -> (Molecule, AtomicCoordinates) {
+    let node = smiles_node(smiles).unwrap();
+    let molecule = serde_json::from_slice(&node.data).unwrap();
+    let coordinates = serde_json::from_slice(&node.children[0].data).unwrap();
+    (molecule, coordinates)
+}
+
+fn position(data: &AtomicCoordinates, i: usize) -> [f64; 3] {
+    [data.x[i], data.y[i], data.z[i]]
+}
+
+fn bond_length(data: &AtomicCoordinates, i: usize, j: usize) -> f64 {
+    geometry::distance(position(data, i), position(data, j))
+}
+
+fn formula(data: &AtomicCoordinates) -> Vec<(i32, usize)> {
+    let mut numbers = data.atomic_num.clone();
+    numbers.sort_unstable();
+    numbers.dedup();
+    numbers
+        .iter()
+        .map(|&n| (n, data.atomic_num.iter().filter(|&&m| m == n).count()))
+        .collect()
+}
+
+#[test]
+fn implicit_hydrogens() {
+    let (molecule, ethanol) = build("CCO");
+    assert_eq!(molecule.name, "CCO");
+    assert_eq!(molecule.charge, 0);
+    assert_eq!(formula(&ethanol), vec![(1, 6), (6, 2), (8, 1)]);
+    assert_eq!(ethanol.connectivity.as_ref().unwrap().len(), 8);
+    assert!(ethanol.charges.is_none());
+    assert!((bond_length(&ethanol, 0, 1) - 1.52).abs() < 0.05);
+    assert!((bond_length(&ethanol, 1, 2) - 1.42).abs() < 0.05);
+    let angle = geometry::angle(position(&ethanol, 0), position(&ethanol, 1), position(&ethanol, 2));
+    assert!((angle - 109.5).abs() < 5.0, "C-C-O angle {}", angle);
+
+    let (_, acid) = build("OC(=O)CCl");
+    assert_eq!(formula(&acid), vec![(1, 3), (6, 2), (8, 2), (17, 1)]);
+    let (_, sulfone) = build("CS(=O)(=O)C");
+    assert_eq!(formula(&sulfone), vec![(1, 6), (6, 2), (8, 2), (16, 1)]);
+}
+
+#[test]
+fn aromatic_rings_are_planar() {
+    let (_, benzene) = build("c1ccccc1");
+    assert_eq!(formula(&benzene), vec![(1, 6), (6, 6)]);
+    let connectivity = benzene.connectivity.as_ref().unwrap();
+    assert_eq!(
+        connectivity.iter().filter(|c| c.order == Connection::AROMATIC).count(),
+        6
+    );
+    for i in 0..6 {
+        assert!((bond_length(&benzene, i, (i + 1) % 6) - 1.39).abs() < 0.05);
+    }
+    // All atoms lie in the plane of the first three carbons
+    let normal = {
+        let a: [f64; 3] = std::array::from_fn(|k| position(&benzene, 1)[k] - position(&benzene, 0)[k]);
+        let b: [f64; 3] = std::array::from_fn(|k| position(&benzene, 2)[k] - position(&benzene, 0)[k]);
+        let n = [
+            a[1] * b[2] - a[2] * b[1],
+            a[2] * b[0] - a[0] * b[2],
+            a[0] * b[1] - a[1] * b[0],
+        ];
+        let length = (n[0] * n[0] + n[1] * n[1] + n[2] * n[2]).sqrt();
+        n.map(|x| x / length)
+    };
+    for i in 0..12 {
+        let offset: f64 = (0..3)
+            .map(|k| (position(&benzene, i)[k] - position(&benzene, 0)[k]) * normal[k])
+            .sum();
+        assert!(offset.abs() < 0.05, "atom {} is {} Å off the plane", i + 1, offset);
+    }
+
+    let (_, pyrrole) = build("c1cc[nH]c1");
+    assert_eq!(formula(&pyrrole), vec![(1, 5), (6, 4), (7, 1)]);
+    let (_, naphthalene) = build("c1ccc2ccccc2c1");
+    assert_eq!(formula(&naphthalene), vec![(1, 8), (6, 10)]);
+}
+
+#[test]
+fn rings_and_branches() {
+    let (_, cyclohexane) = build("C1CCCCC1");
+    assert_eq!(formula(&cyclohexane), vec![(1, 12), (6, 6)]);
+    assert!((bond_length(&cyclohexane, 0, 5) - 1.52).abs() < 0.05);
+
+    // Two-digit ring closures and bond orders given at the opening
+    let (_, ring) = build("C=%12CCCC%12");
+    assert!(ring.connectivity.unwrap().contains(&Connection {
+        atom_1: 0,
+        atom_2: 4,
+        order: 2
+    }));
+
+    let (_, acetylene) = build("C#C");
+    let angle = geometry::angle(
+        position(&acetylene, 2),
+        position(&acetylene, 0),
+        position(&acetylene, 1),
+    );
+    assert!(angle > 170.0, "H-C-C angle {}", angle);
+
+    // Disconnected parts are kept apart
+    let (_, salt) = build("[Na+].[Cl-]");
+    assert!(bond_length(&salt, 0, 1) > 2.0);
+}
+
+#[test]
+fn bracket_atoms() {
+    let (molecule, ammonium) = build("[NH4+]");
+    assert_eq!(molecule.charge, 1);
+    assert_eq!(formula(&ammonium), vec![(1, 4), (7, 1)]);
+    assert_eq!(ammonium.charges.unwrap(), vec![1.0, 0.0, 0.0, 0.0, 0.0]);
+
+    let (molecule, _) = build("[O-]C(=O)CC[N+](C)(C)C");
+    assert_eq!(molecule.charge, 0);
+    let (molecule, _) = build("[Fe+++]");
+    assert_eq!(molecule.charge, 3);
+
+    let (_, heavy_water) = build("[2H]O[2H]");
+    assert_eq!(heavy_water.isotopes.unwrap(), vec![2, 0, 2]);
+    let (_, labelled) = build("[13CH4]");
+    assert_eq!(labelled.isotopes.unwrap(), vec![13, 0, 0, 0, 0]);
+
+    // Stereochemistry and atom classes are read but do not change the structure
+    let graph = smiles::parse("N[C@@H](C)C(=O)O").unwrap();
+    assert_eq!(graph.atoms[1].hydrogens, 1);
+    let graph = smiles::parse("F/C=C/F.[CH3:1]Cl").unwrap();
+    assert_eq!(graph.bonds.len(), 4);
+    assert_eq!(graph.atoms[4].hydrogens, 3);
+
+    let (_, selenophene) = build("c1cc[se]c1");
+    assert_eq!(formula(&selenophene), vec![(1, 4), (6, 4), (34, 1)]);
+}
+
+#[test]
+fn invalid_strings() {
+    for (smiles, message) in [
+        ("", "Empty SMILES string"),
+        ("C1CC", "Unclosed ring 1 in SMILES"),
+        ("CC(C", "Unclosed branch in SMILES"),
+        ("CC)C", "Unopened branch at position 3 of SMILES"),
+        ("CC=", "SMILES ends with a bond"),
+        ("C=#C", "Unexpected bond '#' at position 3 of SMILES"),
+        ("CQ", "Unexpected 'Q' at position 2 of SMILES"),
+        ("[Xy]", "Unexpected 'X' at position 2 of SMILES"),
+        ("[CH4", "Unclosed bracket atom in SMILES"),
+        ("C11", "Atom bonded to itself at position 3 of SMILES"),
+        ("C=1CCCC#1", "Ring closure 1 with different bond orders"),
+        ("[1C]", "Atom 1 (C) has no isotope 1"),
+    ] {
+        assert_eq!(smiles_node(smiles).err().as_deref(), Some(message), "{}", smiles);
+    }
+}
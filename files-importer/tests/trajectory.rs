@@ -0,0 +1,58 @@
+// Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
+// Licensed under the MIT License
+
+//! Coordinate sets of multi-geometry files collected into a trajectory.
+
+use files_importer::import_content;
+use files_importer::options::ImportOptions;
+use shared_lib::cancellation::CancellationToken;
+use shared_lib::types::{AtomicCoordinates, Node};
+
+const TRAJECTORY_TYPE: &str = "mircmd:chemistry:trajectory";
+
+fn import(content: &str, options: &ImportOptions) -> Node {
+    import_content(content, "water.xyz", options, &CancellationToken::none()).unwrap()
+}
+
+/// Water stretched by 0.1 Angstrom along X in every frame.
+fn water_frames(num_frames: usize) -> String {
+    (0..num_frames)
+        .map(|i| {
+            let x = 0.757 + 0.1 * i as f64;
+            format!(
+                "3\nstep {}\nO 0.0 0.0 0.0\nH {} 0.586 0.0\nH {} 0.586 0.0\n",
+                i + 1,
+                x,
+                -x
+            )
+        })
+        .collect()
+}
+
+fn trajectory(node: &Node) -> Option<Vec<AtomicCoordinates>> {
+    node.children
+        .iter()
+        .find(|child| child.r#type == TRAJECTORY_TYPE)
+        .map(|child| serde_json::from_slice(&child.data).unwrap())
+}
+
+#[test]
+fn frames_follow_coordinate_sets() {
+    let node = import(&water_frames(3), &ImportOptions::default());
+    let frames = trajectory(&node).unwrap();
+    assert_eq!(frames.len(), 3);
+    for (i, frame) in frames.iter().enumerate() {
+        assert_eq!(frame.atomic_num, vec![8, 1, 1]);
+        assert!((frame.x[1] - (0.757 + 0.1 * i as f64)).abs() < 1e-9);
+    }
+
+    let options = ImportOptions::from_json(r#"{"trajectory": {"stride": 2}}"#).unwrap();
+    assert_eq!(trajectory(&import(&water_frames(5), &options)).unwrap().len(), 3);
+}
+
+#[test]
+fn single_set_has_no_trajectory() {
+    assert!(trajectory(&import(&water_frames(1), &ImportOptions::default())).is_none());
+    let options = ImportOptions::from_json(r#"{"trajectory": {"last_only": true}}"#).unwrap();
+    assert!(trajectory(&import(&water_frames(3), &options)).is_none());
+}
@@ -0,0 +1,63 @@
+// Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
+// Licensed under the MIT License
+
+//! Normal modes of Molden files.
+
+use files_importer::import_content;
+use files_importer::options::ImportOptions;
+use shared_lib::cancellation::CancellationToken;
+use shared_lib::types::{Node, VibrationalModes};
+
+const VIBRATIONAL_MODES_TYPE: &str = "mircmd:chemistry:vibrational_modes";
+
+fn import(content: &str) -> Result<Node, String> {
+    import_content(
+        content,
+        "water.molden",
+        &ImportOptions::default(),
+        &CancellationToken::none(),
+    )
+}
+
+/// Bend and stretches of water in Bohrs, as written by frequency calculations.
+fn water_modes(num_frequencies: usize) -> String {
+    let frequencies = ["1595.3", "3657.1", "3755.9"];
+    let mut content = "[Molden Format]\n[FREQ]\n".to_string();
+    for frequency in &frequencies[..num_frequencies] {
+        content += &format!("  {}\n", frequency);
+    }
+    content += "[FR-COORD]\nO 0.0 0.0 0.2214\nh 0.0 1.4305 -0.8857\nH 0.0 -1.4305 -0.8857\n[FR-NORM-COORD]\n";
+    content += "vibration 1\n0.0 0.0 -0.07\n0.0 -0.43 0.56\n0.0 0.43 0.56\n";
+    content += "vibration 2\n0.0 0.0 0.05\n0.0 0.58 -0.40\n0.0 -0.58 -0.40\n";
+    content += "vibration 3\n0.0 0.07 0.0\n0.0 -0.56 0.43\n0.0 -0.56 -0.43\n";
+    content + "[INT]\n  70.1\n  4.5\n  52.3\n"
+}
+
+#[test]
+fn modes_with_equilibrium_structure() {
+    let node = import(&water_modes(3)).unwrap();
+    let child = node
+        .children
+        .iter()
+        .find(|child| child.r#type == VIBRATIONAL_MODES_TYPE)
+        .unwrap();
+    let modes: VibrationalModes = serde_json::from_slice(&child.data).unwrap();
+    assert_eq!(modes.frequencies, vec![1595.3, 3657.1, 3755.9]);
+    assert_eq!(modes.displacements.len(), 3);
+    assert_eq!(modes.displacements[1][1], [0.0, 0.58, -0.40]);
+
+    // Bohrs are converted to Angstroms, element symbols may be in any case
+    let structure = modes.atomic_coordinates.unwrap();
+    assert_eq!(structure.atomic_num, vec![8, 1, 1]);
+    assert!((structure.y[1] - 0.757).abs() < 1e-3);
+}
+
+#[test]
+fn modes_without_frequencies_are_rejected() {
+    let error = import(&water_modes(2)).err().unwrap();
+    assert!(
+        error.contains("Numbers of frequencies (2) and normal modes (3) differ"),
+        "{}",
+        error
+    );
+}
@@ -0,0 +1,22 @@
+// Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
+// Licensed under the MIT License
+
+pub mod report;
+
+use shared_lib::types::AtomicCoordinates;
+use wasm_bindgen::prelude::*;
+
+use report::GeometryReport;
+
+pub use report::DEFAULT_DIHEDRAL_CUTOFF;
+
+/// Bond lengths, angles and dihedrals of the structure (serialized `AtomicCoordinates`) as JSON, see
+/// `GeometryReport`. Dihedrals with the terminal atoms farther apart than the cutoff in Angstroms are left out.
+#[wasm_bindgen]
+pub fn analyze(data: &[u8], dihedral_cutoff: Option<f64>) -> Result<Vec<u8>, JsValue> {
+    let data: AtomicCoordinates =
+        serde_json::from_slice(data).map_err(|e| JsValue::from_str(&format!("Failed to deserialize data: {e}")))?;
+    let report = GeometryReport::new(&data, dihedral_cutoff.unwrap_or(DEFAULT_DIHEDRAL_CUTOFF))
+        .map_err(|e| JsValue::from_str(&e))?;
+    serde_json::to_vec(&report).map_err(|e| JsValue::from_str(&format!("Failed to serialize report: {e}")))
+}
@@ -0,0 +1,131 @@
+// Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
+// Licensed under the MIT License
+
+//! Internal geometry of a structure: lengths of all bonds, angles between the bonds at every atom and dihedral
+//! angles about every bond whose terminal atoms are closer than a cutoff. Atom indices start from 1.
+
+use serde::Serialize;
+use shared_lib::connectivity::{self, DEFAULT_BOND_TOLERANCE};
+use shared_lib::geometry;
+use shared_lib::periodic_table::element_symbol;
+use shared_lib::types::AtomicCoordinates;
+
+/// Dihedrals i–j–k–l are reported if the atoms i and l are at most this far apart in Angstroms.
+pub const DEFAULT_DIHEDRAL_CUTOFF: f64 = 4.0;
+
+// Atom (zero-based) and the cell of its periodic image
+type Site = (usize, [i32; 3]);
+
+#[derive(Serialize)]
+pub struct BondLength {
+    pub atoms: [usize; 2],
+    /// Angstroms.
+    pub length: f64,
+}
+
+#[derive(Serialize)]
+pub struct Angle {
+    /// The middle atom is the vertex.
+    pub atoms: [usize; 3],
+    /// Degrees from 0 to 180.
+    pub angle: f64,
+}
+
+#[derive(Serialize)]
+pub struct Dihedral {
+    /// The angle is about the bond of the middle atoms.
+    pub atoms: [usize; 4],
+    /// Degrees from -180 to 180.
+    pub angle: f64,
+    /// Distance between the terminal atoms in Angstroms.
+    pub distance: f64,
+}
+
+#[derive(Serialize)]
+pub struct GeometryReport {
+    /// Element symbols of the atoms, "X" for dummy atoms and unknown atomic numbers.
+    pub symbols: Vec<&'static str>,
+    pub bonds: Vec<BondLength>,
+    pub angles: Vec<Angle>,
+    pub dihedrals: Vec<Dihedral>,
+    pub dihedral_cutoff: f64,
+}
+
+impl GeometryReport {
+    /// Report of the bonds given by the structure, otherwise perceived from interatomic distances, through
+    /// the boundaries of the periodic cell if the structure has one.
+    pub fn new(data: &AtomicCoordinates, dihedral_cutoff: f64) -> Result<Self, String> {
+        let num_atoms = data.atomic_num.len();
+        if data.x.len() != num_atoms || data.y.len() != num_atoms || data.z.len() != num_atoms {
+            return Err("Numbers of atoms and coordinates differ".to_string());
+        }
+        if !dihedral_cutoff.is_finite() || dihedral_cutoff < 0.0 {
+            return Err(format!("Invalid dihedral cutoff: {}", dihedral_cutoff));
+        }
+        let position = |(i, image): Site| -> [f64; 3] {
+            let shift = match &data.cell {
+                Some(cell) => cell.to_cartesian(image.map(f64::from)),
+                None => [0.0; 3],
+            };
+            [data.x[i] + shift[0], data.y[i] + shift[1], data.z[i] + shift[2]]
+        };
+
+        let bonds_list = connectivity::find_bonds(data, DEFAULT_BOND_TOLERANCE);
+        // Bonded atoms with the cells of their images and the index of the bond
+        let mut neighbors: Vec<Vec<(Site, usize)>> = vec![Vec::new(); num_atoms];
+        let mut bonds = Vec::with_capacity(bonds_list.len());
+        for (index, bond) in bonds_list.iter().enumerate() {
+            let (i, j) = (bond.atom_index_1, bond.atom_index_2);
+            neighbors[i].push(((j, bond.image), index));
+            neighbors[j].push(((i, bond.image.map(|n| -n)), index));
+            bonds.push(BondLength {
+                atoms: [i + 1, j + 1],
+                length: geometry::distance(position((i, [0; 3])), position((j, bond.image))),
+            });
+        }
+
+        let mut angles = Vec::new();
+        for (center, bonded) in neighbors.iter().enumerate() {
+            for (k, &(a, _)) in bonded.iter().enumerate() {
+                for &(b, _) in &bonded[k + 1..] {
+                    angles.push(Angle {
+                        atoms: [a.0 + 1, center + 1, b.0 + 1],
+                        angle: geometry::angle(position(a), position((center, [0; 3])), position(b)),
+                    });
+                }
+            }
+        }
+
+        let mut dihedrals = Vec::new();
+        for (index, bond) in bonds_list.iter().enumerate() {
+            let (j, k) = (bond.atom_index_1, bond.atom_index_2);
+            for &((i, to_i), _) in neighbors[j].iter().filter(|n| n.1 != index) {
+                for &((l, to_l), _) in neighbors[k].iter().filter(|n| n.1 != index) {
+                    let to_l = std::array::from_fn(|c| bond.image[c] + to_l[c]);
+                    // Three-membered rings have no dihedrals
+                    if i == l && to_i == to_l {
+                        continue;
+                    }
+                    let sites = [(i, to_i), (j, [0; 3]), (k, bond.image), (l, to_l)].map(position);
+                    let distance = geometry::distance(sites[0], sites[3]);
+                    if distance > dihedral_cutoff {
+                        continue;
+                    }
+                    dihedrals.push(Dihedral {
+                        atoms: [i + 1, j + 1, k + 1, l + 1],
+                        angle: geometry::dihedral(sites[0], sites[1], sites[2], sites[3]),
+                        distance,
+                    });
+                }
+            }
+        }
+
+        Ok(Self {
+            symbols: data.atomic_num.iter().map(|&n| element_symbol(n)).collect(),
+            bonds,
+            angles,
+            dihedrals,
+            dihedral_cutoff,
+        })
+    }
+}
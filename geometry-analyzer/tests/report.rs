@@ -0,0 +1,124 @@
+// Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
+// Licensed under the MIT License
+
+use geometry_analyzer::DEFAULT_DIHEDRAL_CUTOFF;
+use geometry_analyzer::report::GeometryReport;
+use shared_lib::geometry;
+use shared_lib::types::AtomicCoordinates;
+
+fn structure(atomic_num: Vec<i32>, positions: &[[f64; 3]]) -> AtomicCoordinates {
+    AtomicCoordinates {
+        atomic_num,
+        x: positions.iter().map(|p| p[0]).collect(),
+        y: positions.iter().map(|p| p[1]).collect(),
+        z: positions.iter().map(|p| p[2]).collect(),
+        connectivity: None,
+        charges: None,
+        chains: None,
+        cell: None,
+        isotopes: None,
+    }
+}
+
+// H–N–N–H with the dihedral of 115 degrees
+fn diazane() -> AtomicCoordinates {
+    let n1 = [0.0, 0.0, 0.0];
+    let n2 = [1.45, 0.0, 0.0];
+    let h1 = geometry::place_atom([0.0, 1.0, 0.0], n2, n1, 1.01, 106.0, 0.0);
+    let h2 = geometry::place_atom(h1, n1, n2, 1.01, 106.0, 115.0);
+    structure(vec![1, 7, 7, 1], &[h1, n1, n2, h2])
+}
+
+#[test]
+fn bonds_angles_and_dihedrals() {
+    let report = GeometryReport::new(&diazane(), DEFAULT_DIHEDRAL_CUTOFF).unwrap();
+    assert_eq!(report.symbols, vec!["H", "N", "N", "H"]);
+
+    let mut bonds: Vec<[usize; 2]> = report.bonds.iter().map(|b| b.atoms).collect();
+    bonds.iter_mut().for_each(|atoms| atoms.sort());
+    bonds.sort();
+    assert_eq!(bonds, vec![[1, 2], [2, 3], [3, 4]]);
+    let nn = report
+        .bonds
+        .iter()
+        .find(|b| b.atoms.contains(&2) && b.atoms.contains(&3))
+        .unwrap();
+    assert!((nn.length - 1.45).abs() < 1e-9);
+
+    assert_eq!(report.angles.len(), 2);
+    let hnn = report.angles.iter().find(|a| a.atoms[1] == 3).unwrap();
+    assert!((hnn.angle - 106.0).abs() < 1e-6);
+
+    assert_eq!(report.dihedrals.len(), 1);
+    let dihedral = &report.dihedrals[0];
+    assert_eq!(dihedral.atoms[1..3].iter().sum::<usize>(), 5);
+    assert!((dihedral.angle.abs() - 115.0).abs() < 1e-6);
+}
+
+#[test]
+fn dihedral_cutoff() {
+    let data = diazane();
+    let report = GeometryReport::new(&data, DEFAULT_DIHEDRAL_CUTOFF).unwrap();
+    let distance = report.dihedrals[0].distance;
+    assert!(GeometryReport::new(&data, distance + 1e-6).unwrap().dihedrals.len() == 1);
+    assert!(
+        GeometryReport::new(&data, distance - 1e-6)
+            .unwrap()
+            .dihedrals
+            .is_empty()
+    );
+    assert!(GeometryReport::new(&data, -1.0).is_err());
+    assert!(GeometryReport::new(&data, f64::NAN).is_err());
+}
+
+#[test]
+fn three_membered_ring_has_no_dihedrals() {
+    let side = 1.5;
+    let ring = structure(
+        vec![6, 6, 6],
+        &[
+            [0.0, 0.0, 0.0],
+            [side, 0.0, 0.0],
+            [side / 2.0, side * 3f64.sqrt() / 2.0, 0.0],
+        ],
+    );
+    let report = GeometryReport::new(&ring, DEFAULT_DIHEDRAL_CUTOFF).unwrap();
+    assert_eq!(report.bonds.len(), 3);
+    assert_eq!(report.angles.len(), 3);
+    assert!(report.angles.iter().all(|a| (a.angle - 60.0).abs() < 1e-9));
+    assert!(report.dihedrals.is_empty());
+}
+
+#[test]
+fn pseudo_atoms_are_not_bonded() {
+    let mut data = diazane();
+    // A ghost atom and a dummy atom of an unknown atomic number within a bond length of the nitrogen atoms
+    for (number, y) in [(-3, 0.5), (0, -0.5)] {
+        data.atomic_num.push(number);
+        data.x.push(0.7);
+        data.y.push(y);
+        data.z.push(0.0);
+    }
+    let report = GeometryReport::new(&data, DEFAULT_DIHEDRAL_CUTOFF).unwrap();
+    assert_eq!(report.symbols, vec!["H", "N", "N", "H", "Bq", "X"]);
+    assert_eq!(report.bonds.len(), 3);
+    assert!(report.bonds.iter().all(|b| b.atoms.iter().all(|&i| i <= 4)));
+}
+
+#[test]
+fn mismatched_coordinates() {
+    let mut data = diazane();
+    data.z.pop();
+    assert!(GeometryReport::new(&data, DEFAULT_DIHEDRAL_CUTOFF).is_err());
+}
+
+#[test]
+fn json_format() {
+    let report = GeometryReport::new(&diazane(), DEFAULT_DIHEDRAL_CUTOFF).unwrap();
+    let json: serde_json::Value = serde_json::to_value(&report).unwrap();
+    assert_eq!(json["symbols"][1], "N");
+    assert!(json["bonds"][0]["length"].is_f64());
+    assert_eq!(json["angles"][0]["atoms"].as_array().unwrap().len(), 3);
+    assert!(json["dihedrals"][0]["distance"].is_f64());
+    assert_eq!(json["dihedral_cutoff"], DEFAULT_DIHEDRAL_CUTOFF);
+}
@@ -0,0 +1,368 @@
+// Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
+// Licensed under the MIT License
+
+import type { ProgramPluginContext } from './program_context';
+import styles from './style.css';
+
+const ROW_HEIGHT = 28;
+const CELL_PADDING = 4;
+const COL_ATOMS_WIDTH = 160;
+const COL_VALUE_WIDTH = 90;
+const SCROLL_BUFFER = 20;
+const DEFAULT_DIHEDRAL_CUTOFF = 4.0;
+
+interface BondLength {
+    atoms: number[]; // start from 1
+    length: number; // Angstroms
+}
+
+interface Angle {
+    atoms: number[]; // the middle atom is the vertex
+    angle: number; // degrees
+}
+
+interface Dihedral {
+    atoms: number[];
+    angle: number; // degrees from -180 to 180
+    distance: number; // between the terminal atoms, Angstroms
+}
+
+interface GeometryReport {
+    symbols: string[];
+    bonds: BondLength[];
+    angles: Angle[];
+    dihedrals: Dihedral[];
+    dihedral_cutoff: number;
+}
+
+interface WasmModule {
+    default: (module_or_path?: URL) => Promise<unknown>;
+    analyze(data: Uint8Array, dihedral_cutoff?: number): Uint8Array; // JSON GeometryReport
+}
+
+type Section = 'bonds' | 'angles' | 'dihedrals';
+
+interface Column {
+    title: string;
+    class_name: string;
+    // Cell text and the key the rows are sorted by
+    text: (row: number) => string;
+    key: (row: number) => number | number[];
+}
+
+interface TableState {
+    columns: Column[];
+    // Indices of the entries of the section in the order of the rows
+    order: number[];
+    sort_column: number;
+    ascending: boolean;
+}
+
+let wasm_module: WasmModule | null = null;
+
+function supportedTypes(): string[] {
+    return ['mircmd:chemistry:atomic_coordinates'];
+}
+
+async function run(ctx: ProgramPluginContext, data: Uint8Array): Promise<void> {
+    clear_root(ctx.root);
+    if (!wasm_module) {
+        const module_url = new URL('./geometry_analyzer.js', import.meta.url);
+        wasm_module = (await import(module_url.href)) as WasmModule;
+        const wasm_url = new URL('./geometry_analyzer_bg.wasm', import.meta.url);
+        await wasm_module.default(wasm_url);
+    }
+    const analyze = (cutoff: number) => run_analysis(wasm_module!, data, cutoff);
+    const report = analyze(DEFAULT_DIHEDRAL_CUTOFF);
+    if (!report.ok) {
+        render_error(ctx.root, report.error);
+        return;
+    }
+    ctx.addStyles(`${build_css_vars()}\n${styles}`);
+
+    const container = document.createElement('div');
+    container.className = 'ga';
+    ctx.root.appendChild(container);
+    const view = { report: report.value, section: 'bonds' as Section };
+    const toolbar = create_toolbar();
+    container.appendChild(toolbar.element);
+    let table = document.createElement('div');
+    container.appendChild(table);
+
+    const show = () => {
+        const next = create_table(view.report, view.section);
+        table.replaceWith(next);
+        table = next;
+        update_tabs(toolbar.tabs, view.report, view.section);
+    };
+    for (const [section, tab] of toolbar.tabs) {
+        tab.addEventListener('click', () => {
+            view.section = section;
+            show();
+        });
+    }
+    toolbar.cutoff.addEventListener('change', () => {
+        const cutoff = Number(toolbar.cutoff.value);
+        const next = Number.isFinite(cutoff) && cutoff >= 0 ? analyze(cutoff) : null;
+        if (!next || !next.ok) {
+            toolbar.cutoff.value = String(view.report.dihedral_cutoff);
+            return;
+        }
+        view.report = next.value;
+        show();
+    });
+    toolbar.export.addEventListener('click', () => export_json(view.report));
+    show();
+}
+
+function run_analysis(
+    wasm: WasmModule,
+    data: Uint8Array,
+    cutoff: number,
+): { ok: true; value: GeometryReport } | { ok: false; error: string } {
+    try {
+        const json = new TextDecoder().decode(wasm.analyze(data, cutoff));
+        return { ok: true, value: JSON.parse(json) as GeometryReport };
+    } catch (e) {
+        return { ok: false, error: String(e) };
+    }
+}
+
+function clear_root(root: ShadowRoot): void {
+    root.textContent = '';
+}
+
+function render_error(root: ShadowRoot, message: string): void {
+    const div = document.createElement('div');
+    div.style.color = 'red';
+    div.textContent = `Error: ${message}`;
+    root.appendChild(div);
+}
+
+function build_css_vars(): string {
+    const min_width = COL_ATOMS_WIDTH + 2 * COL_VALUE_WIDTH;
+    return `.ga {
+    --row-height: ${ROW_HEIGHT}px;
+    --cell-padding: ${CELL_PADDING}px;
+    --col-atoms: ${COL_ATOMS_WIDTH}px;
+    --col-value: ${COL_VALUE_WIDTH}px;
+    --min-width: ${min_width}px;
+}`;
+}
+
+function create_toolbar(): {
+    element: HTMLDivElement;
+    tabs: Map<Section, HTMLButtonElement>;
+    cutoff: HTMLInputElement;
+    export: HTMLButtonElement;
+} {
+    const element = document.createElement('div');
+    element.className = 'ga-toolbar';
+    const tabs = new Map<Section, HTMLButtonElement>();
+    for (const section of ['bonds', 'angles', 'dihedrals'] as Section[]) {
+        const tab = document.createElement('button');
+        tab.className = 'ga-tab';
+        tabs.set(section, tab);
+        element.appendChild(tab);
+    }
+
+    const label = document.createElement('label');
+    label.className = 'ga-cutoff';
+    label.title = 'Dihedrals with the terminal atoms farther apart are left out';
+    label.textContent = 'Dihedral cutoff, Å ';
+    const cutoff = document.createElement('input');
+    cutoff.type = 'number';
+    cutoff.min = '0';
+    cutoff.step = '0.5';
+    cutoff.value = String(DEFAULT_DIHEDRAL_CUTOFF);
+    label.appendChild(cutoff);
+
+    const export_button = document.createElement('button');
+    export_button.className = 'ga-export';
+    export_button.textContent = 'Export JSON';
+    element.append(label, export_button);
+    return { element, tabs, cutoff, export: export_button };
+}
+
+function update_tabs(tabs: Map<Section, HTMLButtonElement>, report: GeometryReport, active: Section): void {
+    const titles: Record<Section, string> = { bonds: 'Bonds', angles: 'Angles', dihedrals: 'Dihedrals' };
+    for (const [section, tab] of tabs) {
+        tab.textContent = `${titles[section]} (${report[section].length})`;
+        tab.classList.toggle('active', section === active);
+    }
+}
+
+function atoms_text(report: GeometryReport, atoms: number[]): string {
+    return atoms.map((i) => `${report.symbols[i - 1]}${i}`).join('–');
+}
+
+function get_columns(report: GeometryReport, section: Section): Column[] {
+    const atoms_column = (entries: { atoms: number[] }[]): Column => ({
+        title: 'Atoms',
+        class_name: 'col-atoms',
+        text: (row) => atoms_text(report, entries[row].atoms),
+        key: (row) => entries[row].atoms,
+    });
+    const value_column = (title: string, value: (row: number) => number, digits: number): Column => ({
+        title,
+        class_name: 'col-value',
+        text: (row) => value(row).toFixed(digits),
+        key: value,
+    });
+    switch (section) {
+        case 'bonds':
+            return [atoms_column(report.bonds), value_column('Length, Å', (row) => report.bonds[row].length, 4)];
+        case 'angles':
+            return [atoms_column(report.angles), value_column('Angle, °', (row) => report.angles[row].angle, 2)];
+        case 'dihedrals':
+            return [
+                atoms_column(report.dihedrals),
+                value_column('Dihedral, °', (row) => report.dihedrals[row].angle, 2),
+                value_column('1–4 distance, Å', (row) => report.dihedrals[row].distance, 4),
+            ];
+    }
+}
+
+function compare_keys(a: number | number[], b: number | number[]): number {
+    if (typeof a === 'number' || typeof b === 'number') {
+        return (a as number) - (b as number);
+    }
+    for (let i = 0; i < Math.min(a.length, b.length); i++) {
+        if (a[i] !== b[i]) return a[i] - b[i];
+    }
+    return a.length - b.length;
+}
+
+function sort_rows(state: TableState): void {
+    const key = state.columns[state.sort_column].key;
+    const sign = state.ascending ? 1 : -1;
+    state.order.sort((a, b) => sign * compare_keys(key(a), key(b)) || a - b);
+}
+
+function create_table(report: GeometryReport, section: Section): HTMLDivElement {
+    const state: TableState = {
+        columns: get_columns(report, section),
+        order: report[section].map((_, i) => i),
+        sort_column: 0,
+        ascending: true,
+    };
+
+    const container = document.createElement('div');
+    container.className = 'vt';
+    const header = document.createElement('div');
+    header.className = 'vt-header';
+    const header_wrapper = document.createElement('div');
+    header_wrapper.className = 'vt-header-wrapper';
+    header_wrapper.appendChild(header);
+    const body = document.createElement('div');
+    body.className = 'vt-body';
+    const viewport = document.createElement('div');
+    viewport.className = 'vt-viewport';
+    viewport.style.height = `${state.order.length * ROW_HEIGHT}px`;
+    body.appendChild(viewport);
+    container.append(header_wrapper, body);
+
+    const render_state = { row_cache: new Map<number, HTMLDivElement>(), visible_start: -1, visible_end: -1 };
+    const render = () => render_visible_rows(body, viewport, state, render_state);
+    const header_cells = state.columns.map((column, index) => {
+        const cell = document.createElement('div');
+        cell.className = column.class_name;
+        cell.textContent = column.title;
+        cell.addEventListener('click', () => {
+            // Clicking the sorted column again reverses the order
+            state.ascending = state.sort_column === index ? !state.ascending : true;
+            state.sort_column = index;
+            sort_rows(state);
+            update_sort_marks(header_cells, state);
+            for (const row of render_state.row_cache.values()) row.remove();
+            render_state.row_cache.clear();
+            render_state.visible_start = -1;
+            render();
+        });
+        return cell;
+    });
+    header.append(...header_cells);
+    update_sort_marks(header_cells, state);
+
+    body.addEventListener(
+        'scroll',
+        () => {
+            header.style.transform = `translateX(-${body.scrollLeft}px)`;
+            render();
+        },
+        { passive: true },
+    );
+    // Rows are rendered once the table is in the document and has a height
+    requestAnimationFrame(() => {
+        header.style.paddingRight = `${body.offsetWidth - body.clientWidth}px`;
+        render();
+    });
+    return container;
+}
+
+function update_sort_marks(cells: HTMLDivElement[], state: TableState): void {
+    cells.forEach((cell, index) => {
+        cell.classList.toggle('sorted-ascending', index === state.sort_column && state.ascending);
+        cell.classList.toggle('sorted-descending', index === state.sort_column && !state.ascending);
+    });
+}
+
+function render_visible_rows(
+    body: HTMLElement,
+    viewport: HTMLElement,
+    state: TableState,
+    render_state: { row_cache: Map<number, HTMLDivElement>; visible_start: number; visible_end: number },
+): void {
+    const total = state.order.length;
+    const start = Math.max(0, Math.floor(body.scrollTop / ROW_HEIGHT) - SCROLL_BUFFER);
+    const end = Math.min(total, Math.ceil((body.scrollTop + body.clientHeight) / ROW_HEIGHT) + SCROLL_BUFFER);
+    if (start === render_state.visible_start && end === render_state.visible_end) return;
+    for (const [index, row] of render_state.row_cache) {
+        if (index < start || index >= end) {
+            row.remove();
+            render_state.row_cache.delete(index);
+        }
+    }
+    const fragment = document.createDocumentFragment();
+    for (let index = start; index < end; index++) {
+        if (render_state.row_cache.has(index)) continue;
+        const row = build_row(index, state);
+        fragment.appendChild(row);
+        render_state.row_cache.set(index, row);
+    }
+    viewport.appendChild(fragment);
+    render_state.visible_start = start;
+    render_state.visible_end = end;
+}
+
+function build_row(index: number, state: TableState): HTMLDivElement {
+    const row = document.createElement('div');
+    row.className = 'vt-row';
+    row.style.top = `${index * ROW_HEIGHT}px`;
+    const entry = state.order[index];
+    for (const column of state.columns) {
+        const cell = document.createElement('div');
+        cell.className = `vt-cell ${column.class_name}`;
+        cell.textContent = column.text(entry);
+        row.appendChild(cell);
+    }
+    return row;
+}
+
+function export_json(report: GeometryReport): void {
+    const blob = new Blob([JSON.stringify(report, null, 2)], { type: 'application/json' });
+    const url = URL.createObjectURL(blob);
+    const link = document.createElement('a');
+    link.href = url;
+    link.download = 'geometry.json';
+    link.click();
+    URL.revokeObjectURL(url);
+}
+
+// Export instantiate function compatible with current plugin loader
+export function instantiate(): {
+    run: (ctx: ProgramPluginContext, data: Uint8Array) => Promise<void>;
+    supportedTypes: () => string[];
+} {
+    return { run, supportedTypes };
+}
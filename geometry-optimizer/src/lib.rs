@@ -0,0 +1,45 @@
+// Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
+// Licensed under the MIT License
+
+use serde::Serialize;
+use shared_lib::connectivity::{self, DEFAULT_BOND_TOLERANCE};
+use shared_lib::optimization::{self, DEFAULT_CONVERGENCE, DEFAULT_MAX_STEPS, Optimization};
+use shared_lib::periodic_table::element_symbol;
+use shared_lib::types::AtomicCoordinates;
+use wasm_bindgen::prelude::*;
+
+/// Optimization with the element symbols of the atoms, for writing the frames as a trajectory.
+#[derive(Serialize)]
+pub struct Trajectory {
+    pub symbols: Vec<&'static str>,
+    #[serde(flatten)]
+    pub optimization: Optimization,
+}
+
+impl Trajectory {
+    /// Optimizes the structure with the bonds given by it or perceived from interatomic distances, see
+    /// `optimization::optimize`.
+    pub fn new(data: &AtomicCoordinates, max_steps: usize, convergence: f64) -> Result<Self, String> {
+        let bonds = connectivity::find_bonds(data, DEFAULT_BOND_TOLERANCE);
+        Ok(Self {
+            symbols: data.atomic_num.iter().map(|&n| element_symbol(n)).collect(),
+            optimization: optimization::optimize(data, &bonds, max_steps, convergence)?,
+        })
+    }
+}
+
+/// Optimizes the structure (serialized `AtomicCoordinates`) with the generic force field and returns the
+/// trajectory as JSON, see `Trajectory`. The optimization stops after `max_steps` steps or once the largest force
+/// on an atom is below `convergence` in kcal/(mol·Å).
+#[wasm_bindgen]
+pub fn optimize(data: &[u8], max_steps: Option<usize>, convergence: Option<f64>) -> Result<Vec<u8>, JsValue> {
+    let data: AtomicCoordinates =
+        serde_json::from_slice(data).map_err(|e| JsValue::from_str(&format!("Failed to deserialize data: {e}")))?;
+    let trajectory = Trajectory::new(
+        &data,
+        max_steps.unwrap_or(DEFAULT_MAX_STEPS),
+        convergence.unwrap_or(DEFAULT_CONVERGENCE),
+    )
+    .map_err(|e| JsValue::from_str(&e))?;
+    serde_json::to_vec(&trajectory).map_err(|e| JsValue::from_str(&format!("Failed to serialize trajectory: {e}")))
+}
@@ -0,0 +1,123 @@
+// Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
+// Licensed under the MIT License
+
+use geometry_optimizer::Trajectory;
+use shared_lib::connectivity::{self, DEFAULT_BOND_TOLERANCE};
+use shared_lib::force_field::ForceField;
+use shared_lib::optimization::{DEFAULT_CONVERGENCE, DEFAULT_MAX_STEPS, optimize};
+use shared_lib::random::Rng;
+use shared_lib::smiles;
+use shared_lib::types::AtomicCoordinates;
+
+// Generated structure with every atom moved randomly by up to a quarter of an Ångström
+fn distorted(smiles: &str, seed: u64) -> AtomicCoordinates {
+    let mut data = smiles::build(smiles).unwrap();
+    let mut rng = Rng::new(seed);
+    for i in 0..data.atomic_num.len() {
+        data.x[i] += rng.uniform(-0.25, 0.25);
+        data.y[i] += rng.uniform(-0.25, 0.25);
+        data.z[i] += rng.uniform(-0.25, 0.25);
+    }
+    data
+}
+
+fn coordinate(data: &mut AtomicCoordinates, i: usize, k: usize) -> &mut f64 {
+    match k {
+        0 => &mut data.x[i],
+        1 => &mut data.y[i],
+        _ => &mut data.z[i],
+    }
+}
+
+#[test]
+fn gradient_matches_finite_differences() {
+    for smiles in ["CCCC", "CC=CC", "c1ccccc1O"] {
+        let mut data = distorted(smiles, 7);
+        let bonds = connectivity::find_bonds(&data, DEFAULT_BOND_TOLERANCE);
+        let force_field = ForceField::new(&data, &bonds).unwrap();
+        let (terms, gradient) = force_field.energy_and_gradient(&data).unwrap();
+        assert_eq!(terms, force_field.energy_terms(&data).unwrap());
+
+        let h = 1e-5;
+        for (i, derivatives) in gradient.iter().enumerate() {
+            for (k, &derivative) in derivatives.iter().enumerate() {
+                *coordinate(&mut data, i, k) += h;
+                let plus = force_field.energy_terms(&data).unwrap().total;
+                *coordinate(&mut data, i, k) -= 2.0 * h;
+                let minus = force_field.energy_terms(&data).unwrap().total;
+                *coordinate(&mut data, i, k) += h;
+                let numerical = (plus - minus) / (2.0 * h);
+                assert!(
+                    (numerical - derivative).abs() < 1e-4 * (1.0 + numerical.abs()),
+                    "{}: atom {} component {}: {} analytical, {} numerical",
+                    smiles,
+                    i,
+                    k,
+                    derivative,
+                    numerical
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn energy_decreases_to_convergence() {
+    let data = distorted("CC(=O)Nc1ccc(O)cc1", 3);
+    let bonds = connectivity::find_bonds(&data, DEFAULT_BOND_TOLERANCE);
+    let optimization = optimize(&data, &bonds, 5000, DEFAULT_CONVERGENCE).unwrap();
+    assert!(optimization.converged);
+
+    let frames = &optimization.frames;
+    assert_eq!(frames[0].step, 0);
+    assert_eq!(frames[0].x, data.x);
+    assert!(
+        frames
+            .windows(2)
+            .all(|pair| pair[1].energy.total < pair[0].energy.total)
+    );
+    assert!(frames.windows(2).all(|pair| pair[1].step > pair[0].step));
+    let last = frames.last().unwrap();
+    assert!(last.max_force < DEFAULT_CONVERGENCE);
+    assert!(last.rms_force <= last.max_force);
+    assert!(last.energy.total < frames[0].energy.total - 10.0);
+    assert_eq!(last.energy.clashes, 0);
+
+    // Frames keep the atoms and bonds of the structure
+    let optimized = last.coordinates(&data);
+    assert_eq!(optimized.atomic_num, data.atomic_num);
+    assert_eq!(optimized.connectivity, data.connectivity);
+    let [a, b] = [0, 1].map(|i| [optimized.x[i], optimized.y[i], optimized.z[i]]);
+    assert!((shared_lib::geometry::distance(a, b) - 1.52).abs() < 0.05);
+
+    // Optimizing again from the optimized geometry changes nothing
+    let again = optimize(&optimized, &bonds, DEFAULT_MAX_STEPS, DEFAULT_CONVERGENCE).unwrap();
+    assert!(again.converged);
+    assert_eq!(again.frames.len(), 1);
+    assert_eq!(again.steps, 0);
+}
+
+#[test]
+fn step_limit_and_invalid_threshold() {
+    let data = distorted("CCO", 5);
+    let bonds = connectivity::find_bonds(&data, DEFAULT_BOND_TOLERANCE);
+    let optimization = optimize(&data, &bonds, 0, DEFAULT_CONVERGENCE).unwrap();
+    assert!(!optimization.converged);
+    assert_eq!(optimization.frames.len(), 1);
+
+    let optimization = optimize(&data, &bonds, 10, DEFAULT_CONVERGENCE).unwrap();
+    assert_eq!(optimization.steps, 10);
+    assert!(optimization.frames.len() <= 11);
+
+    for threshold in [0.0, -1.0, f64::NAN] {
+        assert!(optimize(&data, &bonds, DEFAULT_MAX_STEPS, threshold).is_err());
+    }
+
+    // The plugin returns the frames with the symbols of the atoms
+    let trajectory = Trajectory::new(&data, 10, DEFAULT_CONVERGENCE).unwrap();
+    assert_eq!(trajectory.symbols, vec!["C", "C", "O", "H", "H", "H", "H", "H", "H"]);
+    let json: serde_json::Value = serde_json::to_value(&trajectory).unwrap();
+    assert_eq!(json["steps"], 10);
+    assert!(json["frames"][0]["energy"]["total"].is_number());
+    assert!(Trajectory::new(&data, 10, 0.0).is_err());
+}
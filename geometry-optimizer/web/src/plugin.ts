@@ -0,0 +1,224 @@
+// Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
+// Licensed under the MIT License
+
+import type { ProgramPluginContext } from './program_context';
+import styles from './style.css';
+
+const DEFAULT_MAX_STEPS = 500;
+const DEFAULT_CONVERGENCE = 0.5;
+
+interface EnergyTerms {
+    bond: number; // kcal/mol
+    angle: number;
+    torsion: number;
+    vdw: number;
+    total: number;
+    clashes: number;
+}
+
+interface Frame {
+    step: number; // 0 for the starting geometry
+    energy: EnergyTerms;
+    max_force: number; // kcal/(mol·Å)
+    rms_force: number;
+    x: number[]; // Angstroms
+    y: number[];
+    z: number[];
+}
+
+interface Trajectory {
+    symbols: string[];
+    frames: Frame[]; // the last one is the optimized geometry
+    converged: boolean;
+    steps: number; // including the rejected ones
+}
+
+interface WasmModule {
+    default: (module_or_path?: URL) => Promise<unknown>;
+    optimize(data: Uint8Array, max_steps?: number, convergence?: number): Uint8Array; // JSON Trajectory
+}
+
+let wasm_module: WasmModule | null = null;
+
+function supportedTypes(): string[] {
+    return ['mircmd:chemistry:atomic_coordinates'];
+}
+
+async function run(ctx: ProgramPluginContext, data: Uint8Array): Promise<void> {
+    clear_root(ctx.root);
+    if (!wasm_module) {
+        const module_url = new URL('./geometry_optimizer.js', import.meta.url);
+        wasm_module = (await import(module_url.href)) as WasmModule;
+        const wasm_url = new URL('./geometry_optimizer_bg.wasm', import.meta.url);
+        await wasm_module.default(wasm_url);
+    }
+    ctx.addStyles(styles);
+
+    const container = document.createElement('div');
+    container.className = 'go';
+    ctx.root.appendChild(container);
+    const toolbar = create_toolbar();
+    container.appendChild(toolbar.element);
+    let result = document.createElement('div');
+    container.appendChild(result);
+    let trajectory: Trajectory | null = null;
+
+    const show = (next: HTMLDivElement) => {
+        result.replaceWith(next);
+        result = next;
+    };
+    toolbar.optimize.addEventListener('click', () => {
+        const max_steps = Number(toolbar.max_steps.value);
+        const convergence = Number(toolbar.convergence.value);
+        if (!Number.isInteger(max_steps) || max_steps < 0 || !(convergence > 0)) {
+            show(create_error('Steps must be a non-negative integer and the convergence positive'));
+            return;
+        }
+        try {
+            const json = new TextDecoder().decode(wasm_module!.optimize(data, max_steps, convergence));
+            trajectory = JSON.parse(json) as Trajectory;
+        } catch (e) {
+            trajectory = null;
+            show(create_error(String(e)));
+            toolbar.export.disabled = true;
+            return;
+        }
+        show(create_report(trajectory));
+        toolbar.export.disabled = false;
+    });
+    toolbar.export.addEventListener('click', () => {
+        if (trajectory) export_xyz(trajectory);
+    });
+}
+
+function clear_root(root: ShadowRoot): void {
+    root.textContent = '';
+}
+
+function create_error(message: string): HTMLDivElement {
+    const div = document.createElement('div');
+    div.style.color = 'red';
+    div.textContent = `Error: ${message}`;
+    return div;
+}
+
+function create_toolbar(): {
+    element: HTMLDivElement;
+    max_steps: HTMLInputElement;
+    convergence: HTMLInputElement;
+    optimize: HTMLButtonElement;
+    export: HTMLButtonElement;
+} {
+    const element = document.createElement('div');
+    element.className = 'go-toolbar';
+    const number_input = (title: string, value: number, step: string): [HTMLLabelElement, HTMLInputElement] => {
+        const label = document.createElement('label');
+        label.textContent = `${title} `;
+        const input = document.createElement('input');
+        input.type = 'number';
+        input.min = '0';
+        input.step = step;
+        input.value = String(value);
+        label.appendChild(input);
+        return [label, input];
+    };
+    const [steps_label, max_steps] = number_input('Steps', DEFAULT_MAX_STEPS, '100');
+    const [convergence_label, convergence] = number_input('Max force, kcal/(mol·Å)', DEFAULT_CONVERGENCE, '0.1');
+    convergence_label.title = 'The optimization stops once the largest force on an atom is below this';
+
+    const optimize = document.createElement('button');
+    optimize.textContent = 'Optimize';
+    const export_button = document.createElement('button');
+    export_button.className = 'go-export';
+    export_button.textContent = 'Export XYZ';
+    export_button.title = 'Trajectory of the optimization as a multi-frame XYZ file';
+    export_button.disabled = true;
+    element.append(steps_label, convergence_label, optimize, export_button);
+    return { element, max_steps, convergence, optimize, export: export_button };
+}
+
+function create_report(trajectory: Trajectory): HTMLDivElement {
+    const report = document.createElement('div');
+    report.className = 'go-report';
+    const first = trajectory.frames[0];
+    const last = trajectory.frames[trajectory.frames.length - 1];
+
+    const status = document.createElement('p');
+    status.textContent = trajectory.converged
+        ? `Converged after ${trajectory.steps} steps`
+        : `Not converged after ${trajectory.steps} steps`;
+    status.className = trajectory.converged ? 'go-converged' : 'go-unconverged';
+
+    const summary = document.createElement('table');
+    add_row(summary, 'th', ['', 'Start', 'Optimized']);
+    const terms: [string, keyof EnergyTerms][] = [
+        ['Bonds, kcal/mol', 'bond'],
+        ['Angles, kcal/mol', 'angle'],
+        ['Torsions, kcal/mol', 'torsion'],
+        ['Van der Waals, kcal/mol', 'vdw'],
+        ['Total, kcal/mol', 'total'],
+    ];
+    for (const [title, key] of terms) {
+        add_row(summary, 'td', [title, first.energy[key].toFixed(3), last.energy[key].toFixed(3)]);
+    }
+    add_row(summary, 'td', ['Clashes', String(first.energy.clashes), String(last.energy.clashes)]);
+    add_row(summary, 'td', ['Max force, kcal/(mol·Å)', first.max_force.toFixed(3), last.max_force.toFixed(3)]);
+    add_row(summary, 'td', ['RMS force, kcal/(mol·Å)', first.rms_force.toFixed(3), last.rms_force.toFixed(3)]);
+
+    const steps = document.createElement('table');
+    steps.className = 'go-steps';
+    add_row(steps, 'th', ['Step', 'Energy, kcal/mol', 'Max force', 'RMS force']);
+    for (const frame of trajectory.frames) {
+        add_row(steps, 'td', [
+            String(frame.step),
+            frame.energy.total.toFixed(4),
+            frame.max_force.toFixed(4),
+            frame.rms_force.toFixed(4),
+        ]);
+    }
+
+    report.append(status, summary, steps);
+    return report;
+}
+
+function add_row(table: HTMLTableElement, cell_tag: 'th' | 'td', values: string[]): void {
+    const row = table.insertRow();
+    for (const value of values) {
+        const cell = document.createElement(cell_tag);
+        cell.textContent = value;
+        row.appendChild(cell);
+    }
+}
+
+// Frames in the XYZ format with the step and the energy in the comment lines, which the files importer reads
+// as a trajectory for the visualizer
+function trajectory_xyz(trajectory: Trajectory): string {
+    const lines: string[] = [];
+    for (const frame of trajectory.frames) {
+        lines.push(String(trajectory.symbols.length));
+        lines.push(`Step ${frame.step} E = ${frame.energy.total.toFixed(6)} kcal/mol`);
+        trajectory.symbols.forEach((symbol, i) => {
+            const coordinates = [frame.x[i], frame.y[i], frame.z[i]].map((value) => value.toFixed(8).padStart(16));
+            lines.push(`${symbol.padEnd(3)}${coordinates.join('')}`);
+        });
+    }
+    return `${lines.join('\n')}\n`;
+}
+
+function export_xyz(trajectory: Trajectory): void {
+    const blob = new Blob([trajectory_xyz(trajectory)], { type: 'chemical/x-xyz' });
+    const url = URL.createObjectURL(blob);
+    const link = document.createElement('a');
+    link.href = url;
+    link.download = 'optimization.xyz';
+    link.click();
+    URL.revokeObjectURL(url);
+}
+
+// Export instantiate function compatible with current plugin loader
+export function instantiate(): {
+    run: (ctx: ProgramPluginContext, data: Uint8Array) => Promise<void>;
+    supportedTypes: () => string[];
+} {
+    return { run, supportedTypes };
+}
@@ -0,0 +1,26 @@
+// Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
+// Licensed under the MIT License
+
+use shared_lib::properties::{ElectronicState, MolecularProperties};
+use shared_lib::types::{AtomicCoordinates, Molecule};
+use wasm_bindgen::prelude::*;
+
+/// Formula, masses and rotational properties of the structure (serialized `AtomicCoordinates`) as JSON, see
+/// `MolecularProperties`.
+#[wasm_bindgen]
+pub fn properties(data: &[u8]) -> Result<Vec<u8>, JsValue> {
+    let data: AtomicCoordinates =
+        serde_json::from_slice(data).map_err(|e| JsValue::from_str(&format!("Failed to deserialize data: {e}")))?;
+    let properties = MolecularProperties::new(&data).map_err(|e| JsValue::from_str(&e))?;
+    serde_json::to_vec(&properties).map_err(|e| JsValue::from_str(&format!("Failed to serialize properties: {e}")))
+}
+
+/// Formula, charge, number of electrons and multiplicity of the molecule (serialized `Molecule`) as JSON, see
+/// `ElectronicState`.
+#[wasm_bindgen]
+pub fn electronic_state(data: &[u8]) -> Result<Vec<u8>, JsValue> {
+    let molecule: Molecule =
+        serde_json::from_slice(data).map_err(|e| JsValue::from_str(&format!("Failed to deserialize molecule: {e}")))?;
+    let state = ElectronicState::new(&molecule).map_err(|e| JsValue::from_str(&e))?;
+    serde_json::to_vec(&state).map_err(|e| JsValue::from_str(&format!("Failed to serialize state: {e}")))
+}
@@ -0,0 +1,174 @@
+// Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
+// Licensed under the MIT License
+
+use shared_lib::properties::{ElectronicState, MolecularProperties, check_multiplicity, molar_mass};
+use shared_lib::types::{AtomicCoordinates, Molecule};
+
+fn structure(atomic_num: Vec<i32>, positions: &[[f64; 3]]) -> AtomicCoordinates {
+    AtomicCoordinates {
+        atomic_num,
+        x: positions.iter().map(|p| p[0]).collect(),
+        y: positions.iter().map(|p| p[1]).collect(),
+        z: positions.iter().map(|p| p[2]).collect(),
+        connectivity: None,
+        charges: None,
+        chains: None,
+        cell: None,
+        isotopes: None,
+    }
+}
+
+fn water() -> AtomicCoordinates {
+    structure(
+        vec![8, 1, 1],
+        &[[0.0, 0.0, 0.0], [0.757, 0.586, 0.0], [-0.757, 0.586, 0.0]],
+    )
+}
+
+#[test]
+fn water_properties() {
+    let properties = MolecularProperties::new(&water()).unwrap();
+    assert_eq!(properties.formula, "H2O");
+    assert_eq!(properties.num_atoms, 3);
+    assert!((properties.molar_mass - 18.015).abs() < 1e-3);
+
+    // On the C2 axis, shifted from O towards the hydrogens
+    let [x, y, z] = properties.center_of_mass;
+    assert!(x.abs() < 1e-12 && z.abs() < 1e-12);
+    assert!((y - 0.0656).abs() < 1e-4, "{}", y);
+
+    // The axes of the planar symmetric molecule are principal, the out-of-plane moment is the sum of the others
+    let tensor = properties.inertia_tensor;
+    for i in 0..3 {
+        for j in 0..3 {
+            assert!((tensor[i][j] - tensor[j][i]).abs() < 1e-12);
+            assert!(i == j || tensor[i][j].abs() < 1e-12, "{:?}", tensor);
+        }
+    }
+    let mut diagonal = [tensor[0][0], tensor[1][1], tensor[2][2]];
+    diagonal.sort_by(f64::total_cmp);
+    for (moment, expected) in properties.principal_moments.iter().zip(diagonal) {
+        assert!((moment - expected).abs() < 1e-9);
+    }
+    assert!((tensor[2][2] - tensor[0][0] - tensor[1][1]).abs() < 1e-9);
+
+    let constants = properties.rotational_constants.map(Option::unwrap);
+    for (constant, expected) in constants.iter().zip([822.2, 437.5, 285.6]) {
+        assert!((constant - expected).abs() < 0.1, "{:?}", constants);
+    }
+}
+
+#[test]
+fn dummy_atoms_are_massless() {
+    let mut data = water();
+    let properties = MolecularProperties::new(&data).unwrap();
+    data.atomic_num.push(0);
+    data.x.push(10.0);
+    data.y.push(10.0);
+    data.z.push(10.0);
+    assert_eq!(MolecularProperties::new(&data).unwrap(), properties);
+    assert!(MolecularProperties::new(&structure(vec![0], &[[0.0; 3]])).is_err());
+}
+
+#[test]
+fn single_atom() {
+    let properties = MolecularProperties::new(&structure(vec![18], &[[1.0, 2.0, 3.0]])).unwrap();
+    assert_eq!(properties.formula, "Ar");
+    assert_eq!(properties.center_of_mass, [1.0, 2.0, 3.0]);
+    assert_eq!(properties.principal_moments, [0.0; 3]);
+    assert_eq!(properties.rotational_constants, [None; 3]);
+}
+
+#[test]
+fn isotope_labels() {
+    let properties = MolecularProperties::new(&water()).unwrap();
+    let heavy = AtomicCoordinates {
+        isotopes: Some(vec![0, 2, 2]),
+        ..water()
+    };
+    let heavy_properties = MolecularProperties::new(&heavy).unwrap();
+    assert_eq!(heavy_properties.formula, "H2O");
+    assert!(
+        (heavy_properties.molar_mass - 20.028).abs() < 1e-3,
+        "{}",
+        heavy_properties.molar_mass
+    );
+    // The heavier hydrogens pull the center of mass towards them and slow the rotation
+    assert!(heavy_properties.center_of_mass[1] > properties.center_of_mass[1]);
+    for (heavy, light) in heavy_properties
+        .rotational_constants
+        .iter()
+        .zip(properties.rotational_constants)
+    {
+        assert!(heavy.unwrap() < light.unwrap());
+    }
+
+    for isotopes in [vec![0, 2], vec![5, 0, 0]] {
+        let invalid = AtomicCoordinates {
+            isotopes: Some(isotopes),
+            ..water()
+        };
+        assert!(MolecularProperties::new(&invalid).is_err());
+    }
+}
+
+#[test]
+fn invalid_structures() {
+    let mut data = water();
+    data.y.pop();
+    assert!(MolecularProperties::new(&data).is_err());
+    assert!(molar_mass(&[1, 200]).is_err());
+    assert!(MolecularProperties::new(&structure(vec![], &[])).is_err());
+}
+
+#[test]
+fn json_format() {
+    let properties = MolecularProperties::new(&structure(vec![6, 8], &[[0.0; 3], [0.0, 0.0, 1.128]])).unwrap();
+    let json: serde_json::Value = serde_json::to_value(&properties).unwrap();
+    assert_eq!(json["formula"], "CO");
+    assert_eq!(json["center_of_mass"].as_array().unwrap().len(), 3);
+    assert_eq!(json["inertia_tensor"][2].as_array().unwrap().len(), 3);
+    assert!(json["rotational_constants"][0].is_null());
+    assert!(json["rotational_constants"][2].is_f64());
+}
+
+fn molecule(atomic_num: Vec<i32>, charge: i32, multiplicity: Option<u32>) -> Molecule {
+    Molecule {
+        n_atoms: atomic_num.len() as i32,
+        atomic_num,
+        charge,
+        name: "test".to_string(),
+        multiplicity,
+    }
+}
+
+#[test]
+fn electronic_state() {
+    // Hydroxide has 10 electrons, the hydroxyl radical 9
+    let state = ElectronicState::new(&molecule(vec![8, 1], -1, None)).unwrap();
+    assert_eq!(state.formula, "HO");
+    assert_eq!((state.charge, state.num_electrons, state.multiplicity), (-1, 10, 1));
+    assert!(!state.multiplicity_given);
+    let state = ElectronicState::new(&molecule(vec![8, 1, 0], 0, None)).unwrap();
+    assert_eq!((state.num_atoms, state.num_electrons, state.multiplicity), (2, 9, 2));
+
+    let state = ElectronicState::new(&molecule(vec![8, 8], 0, Some(3))).unwrap();
+    assert_eq!(state.multiplicity, 3);
+    assert!(state.multiplicity_given);
+    assert!(ElectronicState::new(&molecule(vec![8, 8], 0, Some(2))).is_err());
+
+    // Molecules written before the multiplicity was stored
+    let old: Molecule = serde_json::from_str(r#"{"n_atoms": 1, "atomic_num": [7], "charge": 0, "name": "N"}"#).unwrap();
+    assert_eq!(old.multiplicity, None);
+    assert!(!serde_json::to_string(&old).unwrap().contains("multiplicity"));
+}
+
+#[test]
+fn impossible_multiplicities() {
+    assert!(check_multiplicity(&[1], 0, 2).is_ok());
+    assert!(check_multiplicity(&[1], 1, 1).is_ok());
+    assert!(check_multiplicity(&[1], 2, 1).is_err());
+    assert!(check_multiplicity(&[1], 0, 0).is_err());
+    assert!(check_multiplicity(&[1], 0, 4).is_err());
+    assert!(check_multiplicity(&[8], 0, 5).is_ok());
+}
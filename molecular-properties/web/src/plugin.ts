@@ -0,0 +1,155 @@
+// Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
+// Licensed under the MIT License
+
+import type { ProgramPluginContext } from './program_context';
+import styles from './style.css';
+
+// GHz to cm⁻¹
+const GHZ_TO_WAVENUMBER = 1 / 29.9792458;
+
+interface MolecularProperties {
+    formula: string; // Hill order
+    num_atoms: number;
+    molar_mass: number; // g/mol
+    center_of_mass: number[]; // Angstroms
+    inertia_tensor: number[][]; // u·Å² about the center of mass
+    principal_moments: number[]; // u·Å² in increasing order
+    rotational_constants: (number | null)[]; // GHz, null for zero moments
+}
+
+interface ElectronicState {
+    formula: string;
+    num_atoms: number;
+    molar_mass: number; // g/mol
+    charge: number;
+    num_electrons: number;
+    multiplicity: number;
+    multiplicity_given: boolean; // otherwise the lowest one for the number of electrons
+}
+
+interface WasmModule {
+    default: (module_or_path?: URL) => Promise<unknown>;
+    properties(data: Uint8Array): Uint8Array; // JSON MolecularProperties
+    electronic_state(data: Uint8Array): Uint8Array; // JSON ElectronicState
+}
+
+let wasm_module: WasmModule | null = null;
+
+function supportedTypes(): string[] {
+    return ['mircmd:chemistry:atomic_coordinates', 'mircmd:chemistry:molecule'];
+}
+
+async function run(ctx: ProgramPluginContext, data: Uint8Array): Promise<void> {
+    clear_root(ctx.root);
+    if (!wasm_module) {
+        const module_url = new URL('./molecular_properties.js', import.meta.url);
+        wasm_module = (await import(module_url.href)) as WasmModule;
+        const wasm_url = new URL('./molecular_properties_bg.wasm', import.meta.url);
+        await wasm_module.default(wasm_url);
+    }
+    let rows: [string, string | HTMLElement][];
+    try {
+        const decode = (json: Uint8Array) => JSON.parse(new TextDecoder().decode(json));
+        // Molecule nodes have the composition and the charge, atomic coordinates have positions
+        rows = is_molecule(data)
+            ? state_rows(decode(wasm_module.electronic_state(data)) as ElectronicState)
+            : property_rows(decode(wasm_module.properties(data)) as MolecularProperties);
+    } catch (e) {
+        render_error(ctx.root, String(e));
+        return;
+    }
+    ctx.addStyles(styles);
+    ctx.root.appendChild(create_panel(rows));
+}
+
+function is_molecule(data: Uint8Array): boolean {
+    try {
+        const json = JSON.parse(new TextDecoder().decode(data)) as Record<string, unknown>;
+        return 'charge' in json && !('x' in json);
+    } catch {
+        return false;
+    }
+}
+
+function clear_root(root: ShadowRoot): void {
+    root.textContent = '';
+}
+
+function render_error(root: ShadowRoot, message: string): void {
+    const div = document.createElement('div');
+    div.style.color = 'red';
+    div.textContent = `Error: ${message}`;
+    root.appendChild(div);
+}
+
+function format_vector(values: number[], digits: number): string {
+    return values.map((value) => value.toFixed(digits)).join(', ');
+}
+
+function format_constants(values: (number | null)[], scale: number, digits: number): string {
+    return values.map((value) => (value === null ? '—' : (value * scale).toFixed(digits))).join(', ');
+}
+
+function state_rows(state: ElectronicState): [string, string | HTMLElement][] {
+    const multiplicity = state.multiplicity_given ? String(state.multiplicity) : `${state.multiplicity} (lowest)`;
+    return [
+        ['Formula', state.formula],
+        ['Atoms', String(state.num_atoms)],
+        ['Molar mass, g/mol', state.molar_mass.toFixed(3)],
+        ['Charge', state.charge > 0 ? `+${state.charge}` : String(state.charge)],
+        ['Electrons', String(state.num_electrons)],
+        ['Multiplicity', multiplicity],
+    ];
+}
+
+function property_rows(properties: MolecularProperties): [string, string | HTMLElement][] {
+    return [
+        ['Formula', properties.formula],
+        ['Atoms', String(properties.num_atoms)],
+        ['Molar mass, g/mol', properties.molar_mass.toFixed(3)],
+        ['Center of mass, Å', format_vector(properties.center_of_mass, 4)],
+        ['Inertia tensor, u·Å²', create_matrix(properties.inertia_tensor, 4)],
+        ['Principal moments, u·Å²', format_vector(properties.principal_moments, 4)],
+        ['Rotational constants A, B, C, GHz', format_constants(properties.rotational_constants, 1, 5)],
+        [
+            'Rotational constants A, B, C, cm⁻¹',
+            format_constants(properties.rotational_constants, GHZ_TO_WAVENUMBER, 6),
+        ],
+    ];
+}
+
+function create_panel(rows: [string, string | HTMLElement][]): HTMLDivElement {
+    const panel = document.createElement('div');
+    panel.className = 'mp';
+    const table = document.createElement('table');
+    for (const [title, value] of rows) {
+        const row = table.insertRow();
+        const header = document.createElement('th');
+        header.textContent = title;
+        const cell = row.insertCell();
+        cell.append(value);
+        row.prepend(header);
+    }
+    panel.appendChild(table);
+    return panel;
+}
+
+function create_matrix(matrix: number[][], digits: number): HTMLTableElement {
+    const table = document.createElement('table');
+    table.className = 'mp-matrix';
+    for (const values of matrix) {
+        const row = table.insertRow();
+        for (const value of values) {
+            row.insertCell().textContent = value.toFixed(digits);
+        }
+    }
+    return table;
+}
+
+// Export instantiate function compatible with current plugin loader
+export function instantiate(): {
+    run: (ctx: ProgramPluginContext, data: Uint8Array) => Promise<void>;
+    supportedTypes: () => string[];
+} {
+    return { run, supportedTypes };
+}
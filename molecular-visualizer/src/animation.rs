@@ -0,0 +1,245 @@
+use shared_lib::types::AtomicCoordinates;
+
+use super::core::Vec3;
+
+pub const DEFAULT_FRAME_RATE: f32 = 10.0;
+pub const MAX_FRAME_RATE: f32 = 120.0;
+// Longest step of the playback, e.g. when frames were not drawn while the page was hidden
+const MAX_STEP_MS: f64 = 100.0;
+
+/// Playback of coordinate sets of the structure, e.g. steps of a geometry optimization or a trajectory.
+/// Atom positions are interpolated linearly between neighbouring frames.
+pub struct Animation {
+    // Atom positions of every frame relative to the origin of the molecule
+    frames: Vec<Vec<Vec3<f32>>>,
+    // Scalar of every atom in every frame for the property color scheme with its range over all frames
+    properties: Option<(Vec<Vec<f64>>, [f64; 2])>,
+    frame_rate: f32,
+    // Current frame with the fraction of the way to the next one
+    position: f64,
+    playing: bool,
+    // Time of the last advance in milliseconds, `None` right after the playback has started
+    last_time: Option<f64>,
+    // Playback starts again from the first frame after the last one
+    pub looping: bool,
+}
+
+impl Animation {
+    /// Frames must have the atoms of the structure in the same order. `origin` is subtracted from positions
+    /// the same way as for the atoms of the molecule.
+    pub fn new(frames: &[AtomicCoordinates], atomic_num: &[i32], origin: [f64; 3]) -> Result<Self, String> {
+        if frames.is_empty() {
+            return Err("No frames are given".to_string());
+        }
+        let mut positions = Vec::with_capacity(frames.len());
+        for (index, frame) in frames.iter().enumerate() {
+            let num_atoms = frame.atomic_num.len();
+            if num_atoms != atomic_num.len()
+                || frame.x.len() != num_atoms
+                || frame.y.len() != num_atoms
+                || frame.z.len() != num_atoms
+            {
+                return Err(format!(
+                    "Frame {} has {} atoms, the structure has {}",
+                    index + 1,
+                    num_atoms,
+                    atomic_num.len()
+                ));
+            }
+            if frame.atomic_num != atomic_num {
+                return Err(format!("Atoms of frame {} differ from the structure", index + 1));
+            }
+            positions.push(
+                (0..num_atoms)
+                    .map(|i| {
+                        Vec3::new(
+                            (frame.x[i] - origin[0]) as f32,
+                            (frame.y[i] - origin[1]) as f32,
+                            (frame.z[i] - origin[2]) as f32,
+                        )
+                    })
+                    .collect(),
+            );
+        }
+        Ok(Self {
+            frames: positions,
+            properties: None,
+            frame_rate: DEFAULT_FRAME_RATE,
+            position: 0.0,
+            playing: false,
+            last_time: None,
+            looping: true,
+        })
+    }
+
+    pub fn num_frames(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Current frame (starts from 0) with the fraction of the way to the next one.
+    pub fn frame(&self) -> f64 {
+        self.position
+    }
+
+    /// Binds values of every atom in every frame, e.g. per-atom RMSD or charges, `None` unbinds them.
+    pub fn set_properties(&mut self, properties: Option<Vec<Vec<f64>>>) -> Result<(), String> {
+        let Some(properties) = properties else {
+            self.properties = None;
+            return Ok(());
+        };
+        if properties.len() != self.frames.len() {
+            return Err(format!(
+                "Values are given for {} frames, {} frames are loaded",
+                properties.len(),
+                self.frames.len()
+            ));
+        }
+        let num_atoms = self.frames[0].len();
+        let mut range = [f64::MAX, f64::MIN];
+        for (index, values) in properties.iter().enumerate() {
+            if values.len() != num_atoms {
+                return Err(format!(
+                    "Frame {} has {} values, the structure has {} atoms",
+                    index + 1,
+                    values.len(),
+                    num_atoms
+                ));
+            }
+            for &value in values {
+                if !value.is_finite() {
+                    return Err(format!("Values of frame {} must be finite, got {}", index + 1, value));
+                }
+                range = [range[0].min(value), range[1].max(value)];
+            }
+        }
+        if num_atoms == 0 {
+            range = [0.0, 1.0];
+        }
+        self.properties = Some((properties, range));
+        Ok(())
+    }
+
+    pub fn frame_rate(&self) -> f32 {
+        self.frame_rate
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    /// Frames per second.
+    pub fn set_frame_rate(&mut self, frame_rate: f32) -> Result<(), String> {
+        if frame_rate.is_nan() || frame_rate <= 0.0 || frame_rate > MAX_FRAME_RATE {
+            return Err(format!(
+                "Frame rate must be in range (0, {}], got {}",
+                MAX_FRAME_RATE, frame_rate
+            ));
+        }
+        self.frame_rate = frame_rate;
+        Ok(())
+    }
+
+    pub fn play(&mut self) {
+        if self.frames.len() > 1 {
+            self.playing = true;
+            self.last_time = None;
+            // Playing from the end starts over
+            if !self.looping && self.position >= self.last_frame() {
+                self.position = 0.0;
+            }
+        }
+    }
+
+    pub fn pause(&mut self) {
+        self.playing = false;
+    }
+
+    /// Jumps to the frame (starts from 0), the playback is paused.
+    pub fn set_frame(&mut self, index: usize) -> Result<(), String> {
+        if index >= self.frames.len() {
+            return Err(format!(
+                "Frame index {} is out of range 0..{}",
+                index,
+                self.frames.len()
+            ));
+        }
+        self.playing = false;
+        self.position = index as f64;
+        Ok(())
+    }
+
+    /// Moves by `delta` whole frames from the current one, wrapping around if looping. The playback is paused.
+    pub fn step(&mut self, delta: i32) {
+        let num_frames = self.frames.len() as i64;
+        let index = self.position.floor() as i64 + delta as i64;
+        let index = if self.looping {
+            index.rem_euclid(num_frames)
+        } else {
+            index.clamp(0, num_frames - 1)
+        };
+        self.playing = false;
+        self.position = index as f64;
+    }
+
+    /// Advances the playback to the time in milliseconds, returns `true` if the frame has changed.
+    pub fn advance(&mut self, time: f64) -> bool {
+        if !self.playing {
+            return false;
+        }
+        let elapsed = match self.last_time {
+            Some(last_time) => (time - last_time).clamp(0.0, MAX_STEP_MS),
+            None => 0.0,
+        };
+        self.last_time = Some(time);
+        if elapsed == 0.0 {
+            return false;
+        }
+
+        // The last frame is shown as long as the others before the playback starts over
+        let position = self.position + elapsed * self.frame_rate as f64 / 1000.0;
+        let num_frames = self.frames.len() as f64;
+        self.position = if self.looping {
+            position % num_frames
+        } else if position >= self.last_frame() {
+            self.playing = false;
+            self.last_frame()
+        } else {
+            position
+        };
+        true
+    }
+
+    fn last_frame(&self) -> f64 {
+        (self.frames.len() - 1) as f64
+    }
+
+    /// Atom positions of the frame (starts from 0) relative to the origin of the molecule.
+    pub fn frame_positions(&self, index: usize) -> Option<&[Vec3<f32>]> {
+        self.frames.get(index).map(Vec::as_slice)
+    }
+
+    /// Atom positions at the current frame, interpolated towards the next frame.
+    pub fn positions(&self) -> Vec<Vec3<f32>> {
+        let index = self.position.floor() as usize;
+        let t = (self.position - index as f64) as f32;
+        let current = &self.frames[index];
+        match self.frames.get(index + 1) {
+            Some(next) if t > 0.0 => current.iter().zip(next).map(|(&a, &b)| a + (b - a) * t).collect(),
+            _ => current.clone(),
+        }
+    }
+
+    /// Bound values of the atoms at the current frame, interpolated towards the next frame the same way as
+    /// positions, with their range over all frames.
+    pub fn property_values(&self) -> Option<(Vec<f64>, [f64; 2])> {
+        let (properties, range) = self.properties.as_ref()?;
+        let index = self.position.floor() as usize;
+        let t = self.position - index as f64;
+        let current = &properties[index];
+        let values = match properties.get(index + 1) {
+            Some(next) if t > 0.0 => current.iter().zip(next).map(|(&a, &b)| a + (b - a) * t).collect(),
+            _ => current.clone(),
+        };
+        Some((values, *range))
+    }
+}
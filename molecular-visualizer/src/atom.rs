@@ -1,9 +1,18 @@
+use shared_lib::periodic_table::{element_symbol, isotope_label};
 use wasm_bindgen::prelude::*;
 
-use super::core::mesh::InstanceData;
-use super::core::{Mat4, Vec3};
-use super::types::Color;
-use super::utils::get_model_matrix;
+use super::core::Vec3;
+use super::core::mesh::{HIDDEN_FLAG, InstanceData, LIGHTING_FLAG};
+use super::types::{Color, Dimming};
+
+// Warning color of clashing atoms and its weight mixed with the color of the atom
+const CLASH_COLOR: Color = Color {
+    r: 1.0,
+    g: 0.0,
+    b: 1.0,
+    a: 1.0,
+};
+const CLASH_COLOR_WEIGHT: f32 = 0.7;
 
 #[wasm_bindgen]
 pub struct AtomInfo {
@@ -29,17 +38,37 @@ impl AtomInfo {
     }
 }
 
+/// Radius and color of an atom set apart from the style, e.g. to emphasize a reactive center.
+/// `None` keeps the value of the style.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AtomStyle {
+    pub radius: Option<f32>,
+    pub color: Option<Color>,
+}
+
+impl AtomStyle {
+    pub fn is_empty(&self) -> bool {
+        self.radius.is_none() && self.color.is_none()
+    }
+}
+
 pub struct Atom {
     pub number: i32,
     pub position: Vec3<f32>,
     pub radius: f32,
     pub color: Color,
-    pub picking_color: Color,
+    pub picking_id: u32,
     pub bounding_sphere_color: Color,
     pub bounding_sphere_scale_factor: f32,
     pub visible: bool,
     pub highlighted: bool,
     pub selected: bool,
+    /// Out of focus, drawn darker or grayer.
+    pub dimmed: Option<Dimming>,
+    /// Too close to another atom, drawn in the warning color.
+    pub clashing: bool,
+    /// Mass number of an isotopically labelled atom, 0 for the natural composition.
+    pub mass_number: u32,
 }
 
 impl Atom {
@@ -48,7 +77,7 @@ impl Atom {
         position: Vec3<f32>,
         radius: f32,
         color: Color,
-        picking_color: Color,
+        picking_id: u32,
         bounding_sphere_color: Color,
         bounding_sphere_scale_factor: f32,
     ) -> Self {
@@ -57,12 +86,23 @@ impl Atom {
             position,
             radius,
             color,
-            picking_color,
+            picking_id,
             bounding_sphere_color,
             bounding_sphere_scale_factor,
             visible: true,
             highlighted: false,
             selected: false,
+            dimmed: None,
+            clashing: false,
+            mass_number: 0,
+        }
+    }
+
+    /// Element symbol, or the isotope label of a labelled atom such as "D" or "13C".
+    pub fn symbol(&self) -> Option<String> {
+        match self.mass_number {
+            0 => Some(element_symbol(self.number).to_string()),
+            mass_number => isotope_label(self.number, mass_number),
         }
     }
 
@@ -70,7 +110,7 @@ impl Atom {
         self.selected = !self.selected;
     }
 
-    pub fn get_instance_data(&self, bounding_sphere: bool) -> InstanceData {
+    pub fn get_instance_data(&self, bounding_sphere: bool, ray_casting: bool) -> InstanceData {
         let radius_factor = if bounding_sphere {
             self.bounding_sphere_scale_factor
         } else {
@@ -85,21 +125,25 @@ impl Atom {
 
         let color = if bounding_sphere {
             self.bounding_sphere_color
+        } else if self.clashing {
+            self.color.mixed(CLASH_COLOR, CLASH_COLOR_WEIGHT)
+        } else if let Some(dimming) = self.dimmed {
+            self.color.dimmed_by(dimming)
         } else {
             self.color
         };
 
-        let mut transform: Mat4<f32> = Mat4::new();
-
-        transform.translate(self.position);
-        transform.scale(Vec3::new(radius, radius, radius));
+        let ray_casting_type = if ray_casting { 1 } else { 0 };
+        let lighting = if bounding_sphere { 0 } else { LIGHTING_FLAG };
+        let hidden = if self.visible { 0 } else { HIDDEN_FLAG };
 
         InstanceData {
-            model_matrix: get_model_matrix(&transform),
-            color: color,
-            picking_color: self.picking_color,
-            lighting_model: if bounding_sphere { 0 } else { 1 },
-            ray_casting_type: 1,
+            rotation: [0.0, 0.0, 0.0, 1.0],
+            position: [self.position.x, self.position.y, self.position.z],
+            scale: [radius, radius],
+            color: color.to_rgba8(),
+            picking_id: self.picking_id,
+            flags: ray_casting_type | lighting | hidden,
         }
     }
 }
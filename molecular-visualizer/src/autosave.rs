@@ -0,0 +1,115 @@
+use serde::{Deserialize, Serialize};
+use shared_lib::types::AtomicCoordinates;
+
+use super::thumbnails::structure_hash;
+
+// Version of the snapshot format, snapshots of other versions are not restored
+const SNAPSHOT_VERSION: u32 = 1;
+// Most entries of the edit history, the oldest ones are dropped first
+const MAX_EDITS: usize = 100;
+
+/// Entry of the edit history, consecutive edits with the same description (e.g. steps of dragging an atom)
+/// are counted in one entry.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct Edit {
+    pub description: String,
+    pub count: u32,
+}
+
+/// Unsaved edits of a structure as they are written to the host storage.
+#[derive(Serialize, Deserialize)]
+pub struct Snapshot {
+    pub version: u32,
+    /// Hash of the structure as it was opened in 16 hex digits, the edits are restored only onto it.
+    pub original: String,
+    /// Time of the snapshot in milliseconds since the Unix epoch.
+    pub time: f64,
+    /// The edited structure.
+    pub coordinates: AtomicCoordinates,
+    pub edits: Vec<Edit>,
+}
+
+/// Crash-safe autosave of the edits of a structure: edits mark the state as dirty, and a snapshot of the edited
+/// structure with the history of edits is taken for the host to store until the edits are saved or discarded.
+/// On the next opening of the same structure the host may offer to restore the snapshot.
+pub struct Autosave {
+    original: String,
+    edits: Vec<Edit>,
+    dirty: bool,
+}
+
+impl Autosave {
+    pub fn new(original: &AtomicCoordinates) -> Self {
+        Self {
+            original: format!("{:016x}", structure_hash(original)),
+            edits: Vec::new(),
+            dirty: false,
+        }
+    }
+
+    /// Key of the snapshots of the structure in the host storage, the hash of the structure as it was opened.
+    pub fn key(&self) -> &str {
+        &self.original
+    }
+
+    /// Whether there are edits since the last snapshot.
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// Whether the structure has been edited since it was opened or the edits were saved.
+    pub fn has_edits(&self) -> bool {
+        !self.edits.is_empty()
+    }
+
+    /// History of the edits, the oldest first.
+    pub fn edits(&self) -> &[Edit] {
+        &self.edits
+    }
+
+    pub fn record(&mut self, description: String) {
+        match self.edits.last_mut() {
+            Some(last) if last.description == description => last.count += 1,
+            _ => self.edits.push(Edit { description, count: 1 }),
+        }
+        if self.edits.len() > MAX_EDITS {
+            self.edits.remove(0);
+        }
+        self.dirty = true;
+    }
+
+    /// JSON snapshot of the edited structure taken at `time` (milliseconds since the Unix epoch), the state
+    /// is clean until the next edit.
+    pub fn snapshot(&mut self, coordinates: AtomicCoordinates, time: f64) -> Result<String, String> {
+        let snapshot = Snapshot {
+            version: SNAPSHOT_VERSION,
+            original: self.original.clone(),
+            time,
+            coordinates,
+            edits: self.edits.clone(),
+        };
+        let json = serde_json::to_string(&snapshot).map_err(|e| format!("Failed to serialize snapshot: {}", e))?;
+        self.dirty = false;
+        Ok(json)
+    }
+
+    /// Parses the stored snapshot and takes over its history of edits. The snapshot must be taken from
+    /// the same structure as the one opened.
+    pub fn restore(&mut self, json: &str) -> Result<AtomicCoordinates, String> {
+        let snapshot: Snapshot = serde_json::from_str(json).map_err(|e| format!("Failed to parse snapshot: {}", e))?;
+        if snapshot.version != SNAPSHOT_VERSION {
+            return Err(format!("Unsupported snapshot version {}", snapshot.version));
+        }
+        if snapshot.original != self.original {
+            return Err("The snapshot was taken from another structure".to_string());
+        }
+        self.edits = snapshot.edits;
+        self.dirty = false;
+        Ok(snapshot.coordinates)
+    }
+
+    /// Starts over from the structure without edits, e.g. after the edits are saved into it.
+    pub fn reset(&mut self, structure: &AtomicCoordinates) {
+        *self = Self::new(structure);
+    }
+}
@@ -1,43 +1,61 @@
-use super::core::mesh::InstanceData;
-use super::core::{Mat4, Quaternion, Vec3};
-use super::types::Color;
-use super::utils::get_model_matrix;
+use super::core::mesh::{HIDDEN_FLAG, InstanceData, LIGHTING_FLAG};
+use super::core::{Quaternion, Vec3};
+use super::types::{Color, Dimming};
+use super::utils::PickedObject;
 
 pub struct Bond {
+    /// Zero-based index of the bond in the list of bonds, cylinders of a multiple bond share it.
+    pub index: usize,
+    /// Zero-based indices of the bonded atoms.
+    pub atoms: (usize, usize),
     pub position: Vec3<f32>,
     pub direction: Vec3<f32>,
     pub thickness: f32,
     pub lenght: f32,
     pub color: Color,
     pub visible: bool,
+    /// Out of focus, drawn darker or grayer.
+    pub dimmed: Option<Dimming>,
 }
 
 impl Bond {
-    pub fn new(position: Vec3<f32>, direction: Vec3<f32>, thickness: f32, lenght: f32, color: Color) -> Self {
+    pub fn new(
+        index: usize,
+        atoms: (usize, usize),
+        position: Vec3<f32>,
+        direction: Vec3<f32>,
+        thickness: f32,
+        lenght: f32,
+        color: Color,
+    ) -> Self {
         Self {
+            index,
+            atoms,
             position,
             direction,
             thickness,
             lenght,
             color,
             visible: true,
+            dimmed: None,
         }
     }
 
-    pub fn get_instance_data(&self) -> InstanceData {
+    pub fn get_instance_data(&self, ray_casting: bool) -> InstanceData {
         let rotation = Quaternion::rotation_to(Vec3::new(0.0, 0.0, 1.0), self.direction);
-        let mut transform: Mat4<f32> = Mat4::new();
-
-        transform.translate(self.position);
-        transform.rotate(rotation);
-        transform.scale(Vec3::new(self.thickness, self.thickness, self.lenght));
+        let ray_casting_type = if ray_casting { 2 } else { 0 };
+        let hidden = if self.visible { 0 } else { HIDDEN_FLAG };
 
         InstanceData {
-            model_matrix: get_model_matrix(&transform),
-            color: self.color,
-            picking_color: Color::new(0.0, 0.0, 0.0, 1.0),
-            lighting_model: 1,
-            ray_casting_type: 2,
+            rotation: [rotation.x, rotation.y, rotation.z, rotation.w],
+            position: [self.position.x, self.position.y, self.position.z],
+            scale: [self.thickness, self.lenght],
+            color: self
+                .dimmed
+                .map_or(self.color, |dimming| self.color.dimmed_by(dimming))
+                .to_rgba8(),
+            picking_id: PickedObject::Bond(self.index).picking_id(),
+            flags: ray_casting_type | LIGHTING_FLAG | hidden,
         }
     }
 }
@@ -0,0 +1,293 @@
+use super::core::Vec3;
+
+// Maximum number of primitives in a leaf node
+const LEAF_SIZE: usize = 4;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Target {
+    /// Zero-based atom index.
+    Atom(usize),
+    /// Zero-based index of the bond in the list of bonds.
+    Bond(usize),
+    /// Lines and arcs of the measurement.
+    Measurement,
+    /// Zero-based index of the measured plane.
+    Plane(usize),
+}
+
+#[derive(Clone, Copy)]
+pub enum Shape {
+    Sphere {
+        center: Vec3<f32>,
+        radius: f32,
+    },
+    /// Open cylinder, `axis` is a unit vector.
+    Cylinder {
+        center: Vec3<f32>,
+        axis: Vec3<f32>,
+        half_length: f32,
+        radius: f32,
+    },
+    /// Flat disk, `normal` is a unit vector.
+    Disk {
+        center: Vec3<f32>,
+        normal: Vec3<f32>,
+        radius: f32,
+    },
+}
+
+#[derive(Clone, Copy)]
+pub struct Primitive {
+    pub shape: Shape,
+    pub target: Target,
+}
+
+pub struct Ray {
+    pub origin: Vec3<f32>,
+    /// Unit vector.
+    pub direction: Vec3<f32>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Hit {
+    pub target: Target,
+    /// Distance from the ray origin.
+    pub distance: f32,
+}
+
+#[derive(Clone, Copy)]
+struct Aabb {
+    min: Vec3<f32>,
+    max: Vec3<f32>,
+}
+
+impl Aabb {
+    fn empty() -> Self {
+        Self {
+            min: Vec3::new(f32::MAX, f32::MAX, f32::MAX),
+            max: Vec3::new(f32::MIN, f32::MIN, f32::MIN),
+        }
+    }
+
+    fn grow(&mut self, other: &Aabb) {
+        self.min = Vec3::new(
+            self.min.x.min(other.min.x),
+            self.min.y.min(other.min.y),
+            self.min.z.min(other.min.z),
+        );
+        self.max = Vec3::new(
+            self.max.x.max(other.max.x),
+            self.max.y.max(other.max.y),
+            self.max.z.max(other.max.z),
+        );
+    }
+
+    /// Entry distance of the ray into the box (slab test), None if it misses or enters beyond `t_max`.
+    fn intersect(&self, ray: &Ray, t_max: f32) -> Option<f32> {
+        let origin = [ray.origin.x, ray.origin.y, ray.origin.z];
+        let direction = [ray.direction.x, ray.direction.y, ray.direction.z];
+        let min = [self.min.x, self.min.y, self.min.z];
+        let max = [self.max.x, self.max.y, self.max.z];
+
+        let (mut t_enter, mut t_exit) = (0.0f32, t_max);
+        for a in 0..3 {
+            let inv = 1.0 / direction[a];
+            let (mut t0, mut t1) = ((min[a] - origin[a]) * inv, (max[a] - origin[a]) * inv);
+            if t0 > t1 {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+            t_enter = t_enter.max(t0);
+            t_exit = t_exit.min(t1);
+            if t_enter > t_exit {
+                return None;
+            }
+        }
+        Some(t_enter)
+    }
+}
+
+impl Shape {
+    fn bounds(&self) -> Aabb {
+        let (center, extent) = match *self {
+            Shape::Sphere { center, radius } => (center, Vec3::new(radius, radius, radius)),
+            Shape::Cylinder {
+                center,
+                axis,
+                half_length,
+                radius,
+            } => {
+                let e = |a: f32| a.abs() * half_length + radius * (1.0 - a * a).max(0.0).sqrt();
+                (center, Vec3::new(e(axis.x), e(axis.y), e(axis.z)))
+            }
+            Shape::Disk { center, normal, radius } => {
+                let e = |n: f32| radius * (1.0 - n * n).max(0.0).sqrt();
+                (center, Vec3::new(e(normal.x), e(normal.y), e(normal.z)))
+            }
+        };
+        Aabb {
+            min: center - extent,
+            max: center + extent,
+        }
+    }
+
+    fn center(&self) -> Vec3<f32> {
+        match *self {
+            Shape::Sphere { center, .. } | Shape::Cylinder { center, .. } | Shape::Disk { center, .. } => center,
+        }
+    }
+
+    /// Nearest positive intersection distance.
+    fn intersect(&self, ray: &Ray) -> Option<f32> {
+        match *self {
+            Shape::Sphere { center, radius } => {
+                let oc = ray.origin - center;
+                let b = Vec3::dot_product(oc, ray.direction);
+                let c = oc.length_squared() - radius * radius;
+                let discriminant = b * b - c;
+                if discriminant < 0.0 {
+                    return None;
+                }
+                let sqrt = discriminant.sqrt();
+                [-b - sqrt, -b + sqrt].into_iter().find(|&t| t > 0.0)
+            }
+            Shape::Cylinder {
+                center,
+                axis,
+                half_length,
+                radius,
+            } => {
+                // Solve |(oc + t d) x axis|^2 = r^2 using components perpendicular to the axis
+                let oc = ray.origin - center;
+                let d_perp = ray.direction - axis * Vec3::dot_product(ray.direction, axis);
+                let oc_perp = oc - axis * Vec3::dot_product(oc, axis);
+                let a = d_perp.length_squared();
+                if a < f32::EPSILON {
+                    return None;
+                }
+                let b = Vec3::dot_product(oc_perp, d_perp);
+                let c = oc_perp.length_squared() - radius * radius;
+                let discriminant = b * b - a * c;
+                if discriminant < 0.0 {
+                    return None;
+                }
+                let sqrt = discriminant.sqrt();
+                [(-b - sqrt) / a, (-b + sqrt) / a]
+                    .into_iter()
+                    .find(|&t| t > 0.0 && Vec3::dot_product(oc + ray.direction * t, axis).abs() <= half_length)
+            }
+            Shape::Disk { center, normal, radius } => {
+                let denominator = Vec3::dot_product(ray.direction, normal);
+                if denominator.abs() < f32::EPSILON {
+                    return None;
+                }
+                let t = Vec3::dot_product(center - ray.origin, normal) / denominator;
+                let inside = (ray.origin + ray.direction * t - center).length_squared() <= radius * radius;
+                (t > 0.0 && inside).then_some(t)
+            }
+        }
+    }
+}
+
+struct Node {
+    bounds: Aabb,
+    // Leaf: range of `primitives`; inner node: `count` is 0 and `start`, `right` are indices of children
+    start: usize,
+    count: usize,
+    right: usize,
+}
+
+/// Bounding volume hierarchy over spheres, cylinders and disks for CPU ray casting.
+pub struct Bvh {
+    nodes: Vec<Node>,
+    primitives: Vec<Primitive>,
+}
+
+impl Bvh {
+    pub fn new(mut primitives: Vec<Primitive>) -> Self {
+        let mut bvh = Self {
+            nodes: Vec::with_capacity(2 * primitives.len() / LEAF_SIZE + 1),
+            primitives: Vec::new(),
+        };
+        if !primitives.is_empty() {
+            let count = primitives.len();
+            bvh.build(&mut primitives, 0, count);
+        }
+        bvh.primitives = primitives;
+        bvh
+    }
+
+    // Median split along the longest axis of primitive centers
+    fn build(&mut self, primitives: &mut [Primitive], start: usize, count: usize) -> usize {
+        let slice = &mut primitives[start..start + count];
+        let mut bounds = Aabb::empty();
+        let mut centers = Aabb::empty();
+        for primitive in slice.iter() {
+            bounds.grow(&primitive.shape.bounds());
+            let c = primitive.shape.center();
+            centers.grow(&Aabb { min: c, max: c });
+        }
+
+        let index = self.nodes.len();
+        self.nodes.push(Node {
+            bounds,
+            start,
+            count,
+            right: 0,
+        });
+        if count <= LEAF_SIZE {
+            return index;
+        }
+
+        let extent = centers.max - centers.min;
+        let key: fn(&Vec3<f32>) -> f32 = if extent.x >= extent.y && extent.x >= extent.z {
+            |v| v.x
+        } else if extent.y >= extent.z {
+            |v| v.y
+        } else {
+            |v| v.z
+        };
+        let half = count / 2;
+        slice.select_nth_unstable_by(half, |a, b| key(&a.shape.center()).total_cmp(&key(&b.shape.center())));
+
+        let left = self.build(primitives, start, half);
+        let right = self.build(primitives, start + half, count - half);
+        self.nodes[index].start = left;
+        self.nodes[index].count = 0;
+        self.nodes[index].right = right;
+        index
+    }
+
+    /// Nearest primitive hit by the ray.
+    pub fn intersect(&self, ray: &Ray) -> Option<Hit> {
+        let mut nearest: Option<Hit> = None;
+        if self.nodes.is_empty() {
+            return nearest;
+        }
+
+        let mut stack = vec![0usize];
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            let t_max = nearest.map_or(f32::MAX, |hit| hit.distance);
+            if node.bounds.intersect(ray, t_max).is_none() {
+                continue;
+            }
+
+            if node.count > 0 {
+                for primitive in &self.primitives[node.start..node.start + node.count] {
+                    if let Some(distance) = primitive.shape.intersect(ray)
+                        && distance < nearest.map_or(f32::MAX, |hit| hit.distance)
+                    {
+                        nearest = Some(Hit {
+                            target: primitive.target,
+                            distance,
+                        });
+                    }
+                }
+            } else {
+                stack.push(node.right);
+                stack.push(node.start);
+            }
+        }
+        nearest
+    }
+}
@@ -0,0 +1,210 @@
+use std::io::Write;
+
+use flate2::Crc;
+use flate2::write::ZlibEncoder;
+
+pub const DEFAULT_SUPERSAMPLING: u32 = 2;
+pub const MAX_SUPERSAMPLING: u32 = 4;
+
+/// Offscreen render target of the captured image with the buffer its pixels are read back through.
+pub struct CaptureTarget {
+    texture: wgpu::Texture,
+    pub view: wgpu::TextureView,
+    buffer: wgpu::Buffer,
+    // Rows of the buffer are padded to `wgpu::COPY_BYTES_PER_ROW_ALIGNMENT`
+    padded_row: u32,
+}
+
+impl CaptureTarget {
+    /// Only 8-bit RGBA and BGRA formats can be captured.
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat, width: u32, height: u32) -> Result<Self, String> {
+        if bgra(format).is_none() {
+            return Err(format!("Images can not be captured from the {:?} format", format));
+        }
+        let max_size = device.limits().max_texture_dimension_2d;
+        if width == 0 || height == 0 || width > max_size || height > max_size {
+            return Err(format!(
+                "Render size {}x{} must be in range 1..={}",
+                width, height, max_size
+            ));
+        }
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Capture Texture"),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let padded_row = (width * 4).next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Capture Readback Buffer"),
+            size: padded_row as u64 * height as u64,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        Ok(Self {
+            texture,
+            view,
+            buffer,
+            padded_row,
+        })
+    }
+
+    /// Reads back the rendered image as RGBA8 pixels in rows from the top.
+    pub async fn read(&self, device: &wgpu::Device, queue: &wgpu::Queue) -> Result<Vec<u8>, String> {
+        let size = self.texture.size();
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Capture Readback Encoder"),
+        });
+        encoder.copy_texture_to_buffer(
+            self.texture.as_image_copy(),
+            wgpu::TexelCopyBufferInfo {
+                buffer: &self.buffer,
+                layout: wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(self.padded_row),
+                    rows_per_image: Some(size.height),
+                },
+            },
+            size,
+        );
+        queue.submit(std::iter::once(encoder.finish()));
+
+        let slice = self.buffer.slice(..);
+        let (sender, receiver) = flume::bounded(1);
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        let _ = device.poll(wgpu::PollType::Wait {
+            submission_index: None,
+            timeout: None,
+        });
+        match receiver.recv_async().await {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => return Err(format!("Failed to read the captured image: {}", e)),
+            Err(e) => return Err(format!("Failed to read the captured image: {}", e)),
+        }
+
+        let swap = bgra(self.texture.format()) == Some(true);
+        let row = size.width as usize * 4;
+        let mut pixels = Vec::with_capacity(row * size.height as usize);
+        {
+            let data = slice.get_mapped_range();
+            for padded in data.chunks_exact(self.padded_row as usize) {
+                for pixel in padded[..row].chunks_exact(4) {
+                    let [r, g, b, a] = [pixel[0], pixel[1], pixel[2], pixel[3]];
+                    pixels.extend_from_slice(&if swap { [b, g, r, a] } else { [r, g, b, a] });
+                }
+            }
+        }
+        self.buffer.unmap();
+        Ok(pixels)
+    }
+}
+
+/// `Some(true)` for BGRA formats, `Some(false)` for RGBA ones, `None` for others.
+fn bgra(format: wgpu::TextureFormat) -> Option<bool> {
+    match format {
+        wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb => Some(true),
+        wgpu::TextureFormat::Rgba8Unorm | wgpu::TextureFormat::Rgba8UnormSrgb => Some(false),
+        _ => None,
+    }
+}
+
+fn to_linear(value: u8) -> f32 {
+    let c = value as f32 / 255.0;
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn to_srgb(c: f32) -> u8 {
+    let c = if c <= 0.0031308 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    };
+    (c.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+/// Averages blocks of `samples` × `samples` RGBA8 pixels with premultiplied alpha in linear color space.
+/// `width` and `height` are the size of the downsampled image. Color channels of the result are premultiplied
+/// by alpha after the sRGB encoding, so opaque pixels keep their colors.
+pub fn downsample(pixels: &[u8], width: u32, height: u32, samples: u32) -> Vec<u8> {
+    let (width, height, samples) = (width as usize, height as usize, samples as usize);
+    let source_row = width * samples * 4;
+    let lut: Vec<f32> = (0..=255).map(to_linear).collect();
+    let count = (samples * samples) as f32;
+    let mut result = Vec::with_capacity(width * height * 4);
+    for y in 0..height {
+        for x in 0..width {
+            let mut sum = [0.0; 4];
+            for sy in 0..samples {
+                let start = (y * samples + sy) * source_row + x * samples * 4;
+                for pixel in pixels[start..start + samples * 4].chunks_exact(4) {
+                    for c in 0..3 {
+                        sum[c] += lut[pixel[c] as usize];
+                    }
+                    sum[3] += pixel[3] as f32 / 255.0;
+                }
+            }
+            let alpha = sum[3] / count;
+            let alpha_byte = (alpha * 255.0).round() as u8;
+            if alpha_byte == 0 {
+                result.extend_from_slice(&[0; 4]);
+                continue;
+            }
+            // Straight color is encoded and then premultiplied again
+            let premultiply = |c: f32| (to_srgb(c / count / alpha) as f32 * alpha).round() as u8;
+            result.extend_from_slice(&[
+                premultiply(sum[0]),
+                premultiply(sum[1]),
+                premultiply(sum[2]),
+                alpha_byte,
+            ]);
+        }
+    }
+    result
+}
+
+/// PNG file with the RGBA8 pixels in rows from the top.
+pub fn encode_png(pixels: &[u8], width: u32, height: u32) -> Result<Vec<u8>, String> {
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&width.to_be_bytes());
+    header.extend_from_slice(&height.to_be_bytes());
+    // 8 bits per channel, RGBA, deflate, adaptive filtering, no interlacing
+    header.extend_from_slice(&[8, 6, 0, 0, 0]);
+
+    // Every row starts with the filter type, 0 – none
+    let row = width as usize * 4;
+    let mut encoder = ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+    for line in pixels.chunks_exact(row) {
+        encoder
+            .write_all(&[0])
+            .and_then(|_| encoder.write_all(line))
+            .map_err(|e| format!("Failed to encode PNG: {}", e))?;
+    }
+    let data = encoder.finish().map_err(|e| format!("Failed to encode PNG: {}", e))?;
+
+    let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+    for (kind, content) in [(b"IHDR", header.as_slice()), (b"IDAT", &data), (b"IEND", &[])] {
+        png.extend_from_slice(&(content.len() as u32).to_be_bytes());
+        png.extend_from_slice(kind);
+        png.extend_from_slice(content);
+        let mut crc = Crc::new();
+        crc.update(kind);
+        crc.update(content);
+        png.extend_from_slice(&crc.sum().to_be_bytes());
+    }
+    Ok(png)
+}
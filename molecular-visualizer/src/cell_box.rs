@@ -0,0 +1,79 @@
+use shared_lib::cell::UnitCell;
+use wgpu::util::DeviceExt;
+
+use super::config::CellBox as CellBoxStyle;
+use super::core::Vec3;
+use super::core::mesh::InstanceData;
+use super::measurement::segment_instance;
+
+/// Edges of the periodic cell of the structure drawn as lines.
+pub struct CellBox {
+    pub instance_buffer: Option<wgpu::Buffer>,
+    pub num_instances: u32,
+    dirty: bool,
+}
+
+impl CellBox {
+    pub fn new() -> Self {
+        Self {
+            instance_buffer: None,
+            num_instances: 0,
+            dirty: true,
+        }
+    }
+
+    /// Edges have to be rebuilt, e.g. after the cell or the style has changed.
+    pub fn invalidate(&mut self) {
+        self.dirty = true;
+    }
+
+    /// Rebuilds the edges if the box was invalidated since the last call. `origin` is subtracted from the corners
+    /// the same way as from the atoms of the molecule.
+    pub fn update(
+        &mut self,
+        device: &wgpu::Device,
+        cell: Option<&UnitCell>,
+        origin: [f64; 3],
+        ray_casting: bool,
+        style: &CellBoxStyle,
+    ) {
+        if !self.dirty {
+            return;
+        }
+        self.dirty = false;
+
+        let mut instances: Vec<InstanceData> = Vec::new();
+        if let Some(cell) = cell {
+            let corner = |i: usize| {
+                let fractional = [i & 1, (i >> 1) & 1, (i >> 2) & 1].map(|bit| bit as f64);
+                let [x, y, z] = cell.to_cartesian(fractional);
+                Vec3::new(x - origin[0], y - origin[1], z - origin[2])
+            };
+            // Corners are numbered by bits of their fractional coordinates, edges join corners differing in one bit
+            for i in 0..8 {
+                for bit in [1, 2, 4] {
+                    if i & bit == 0 {
+                        instances.push(segment_instance(
+                            corner(i),
+                            corner(i | bit),
+                            ray_casting,
+                            style.thickness,
+                            style.color,
+                        ));
+                    }
+                }
+            }
+        }
+
+        self.num_instances = instances.len() as u32;
+        self.instance_buffer = if instances.is_empty() {
+            None
+        } else {
+            Some(device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Cell Box Instance Buffer"),
+                contents: bytemuck::cast_slice(&instances),
+                usage: wgpu::BufferUsages::VERTEX,
+            }))
+        };
+    }
+}
@@ -0,0 +1,133 @@
+use shared_lib::cell::UnitCell;
+use shared_lib::contacts::{self, Clash};
+use shared_lib::types::AtomicCoordinates;
+use wgpu::util::DeviceExt;
+
+use super::core::mesh::InstanceData;
+use super::molecule::Molecule;
+use super::types::Color;
+
+// Halos are transparent spheres this much larger than their atoms
+const HALO_SCALE: f32 = 1.6;
+const HALO_COLOR: Color = Color {
+    r: 1.0,
+    g: 0.5,
+    b: 0.0,
+    a: 0.45,
+};
+
+/// Warning halos around the atoms closer to another atom than a fraction of the sum of their covalent radii,
+/// i.e. atoms overlapping each other. The pairs are found again whenever the molecule changes.
+pub struct ClashHalos {
+    fraction: Option<f64>,
+    clashes: Vec<Clash>,
+    pub instance_buffer: Option<wgpu::Buffer>,
+    pub num_instances: u32,
+    // Revision of the molecule the clashes were found for, None if they have to be found again
+    revision: Option<u64>,
+    // Clashes were found again since the halos were built
+    dirty: bool,
+}
+
+impl ClashHalos {
+    pub fn new() -> Self {
+        Self {
+            fraction: Some(contacts::DEFAULT_COVALENT_CLASH_FRACTION),
+            clashes: Vec::new(),
+            instance_buffer: None,
+            num_instances: 0,
+            revision: None,
+            dirty: true,
+        }
+    }
+
+    /// Halos have to be rebuilt, e.g. for a new molecule or after the style has changed.
+    pub fn invalidate(&mut self) {
+        self.revision = None;
+    }
+
+    pub fn fraction(&self) -> Option<f64> {
+        self.fraction
+    }
+
+    /// Atoms closer than `fraction` of the sum of their covalent radii get halos, `None` turns them off.
+    pub fn set_fraction(&mut self, fraction: Option<f64>) -> Result<(), String> {
+        if let Some(fraction) = fraction.filter(|f| !(f.is_finite() && *f > 0.0 && *f <= 1.0)) {
+            return Err(format!("Clash fraction must be in range (0, 1], got {}", fraction));
+        }
+        self.fraction = fraction;
+        self.invalidate();
+        Ok(())
+    }
+
+    /// Clashes of the current positions of the molecule with zero-based atom indices, found again if the molecule
+    /// changed since the last call.
+    pub fn clashes(&mut self, molecule: &Molecule, cell: Option<&UnitCell>) -> &[Clash] {
+        if self.revision != Some(molecule.revision()) {
+            self.revision = Some(molecule.revision());
+            self.clashes = find_clashes(molecule, cell, self.fraction);
+            self.dirty = true;
+        }
+        &self.clashes
+    }
+
+    /// Rebuilds the halos if the clashes changed since the last call. Hidden atoms clash but get no halos.
+    pub fn update(&mut self, device: &wgpu::Device, molecule: &Molecule, cell: Option<&UnitCell>) {
+        self.clashes(molecule, cell);
+        if !self.dirty {
+            return;
+        }
+        self.dirty = false;
+
+        let atoms = molecule.atoms();
+        let mut clashing = vec![false; atoms.len()];
+        for clash in &self.clashes {
+            clashing[clash.atom_index_1] = true;
+            clashing[clash.atom_index_2] = true;
+        }
+        let ray_casting = molecule.is_ray_casting();
+        let instances: Vec<InstanceData> = atoms
+            .iter()
+            .zip(&clashing)
+            .filter(|(atom, clashing)| **clashing && atom.visible)
+            .map(|(atom, _)| {
+                let mut instance = atom.get_instance_data(true, ray_casting);
+                instance.scale = [atom.radius * HALO_SCALE; 2];
+                instance.color = HALO_COLOR.to_rgba8();
+                instance.picking_id = 0;
+                instance
+            })
+            .collect();
+
+        self.num_instances = instances.len() as u32;
+        self.instance_buffer = if instances.is_empty() {
+            None
+        } else {
+            Some(device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Clash Halo Instance Buffer"),
+                contents: bytemuck::cast_slice(&instances),
+                usage: wgpu::BufferUsages::VERTEX,
+            }))
+        };
+    }
+}
+
+// Clashes of the positions of the atoms relative to the origin of the molecule, the same as of the structure
+fn find_clashes(molecule: &Molecule, cell: Option<&UnitCell>, fraction: Option<f64>) -> Vec<Clash> {
+    let Some(fraction) = fraction else {
+        return Vec::new();
+    };
+    let atoms = molecule.atoms();
+    let data = AtomicCoordinates {
+        atomic_num: atoms.iter().map(|atom| atom.number).collect(),
+        x: atoms.iter().map(|atom| atom.position.x as f64).collect(),
+        y: atoms.iter().map(|atom| atom.position.y as f64).collect(),
+        z: atoms.iter().map(|atom| atom.position.z as f64).collect(),
+        connectivity: None,
+        charges: None,
+        chains: None,
+        cell: cell.copied(),
+        isotopes: None,
+    };
+    contacts::find_covalent_clashes(&data, fraction)
+}
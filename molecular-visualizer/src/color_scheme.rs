@@ -0,0 +1,323 @@
+use shared_lib::colormap::{Colormap, Palette, relative_luminance};
+use shared_lib::periodic_table::get_atomic_mass;
+use shared_lib::types::AtomicCoordinates;
+
+use super::config::Style;
+use super::types::Color;
+
+/// Distinct colors of chains, repeated when there are more chains.
+const CHAIN_COLORS: [[f32; 3]; 10] = [
+    [0.12, 0.47, 0.71],
+    [1.0, 0.5, 0.05],
+    [0.17, 0.63, 0.17],
+    [0.84, 0.15, 0.16],
+    [0.58, 0.4, 0.74],
+    [0.55, 0.34, 0.29],
+    [0.89, 0.47, 0.76],
+    [0.5, 0.5, 0.5],
+    [0.74, 0.74, 0.13],
+    [0.09, 0.75, 0.81],
+];
+
+/// Okabe-Ito colors of chains in the colorblind-safe palette.
+const COLORBLIND_SAFE_CHAIN_COLORS: [[f32; 3]; 7] = [
+    [0.0, 0.447, 0.698],
+    [0.902, 0.624, 0.0],
+    [0.0, 0.620, 0.451],
+    [0.835, 0.369, 0.0],
+    [0.337, 0.706, 0.914],
+    [0.800, 0.475, 0.655],
+    [0.941, 0.894, 0.259],
+];
+
+/// Gray levels of chains in the grayscale palette, neighbouring chains differ the most.
+const GRAYSCALE_CHAIN_LEVELS: [f32; 4] = [0.25, 0.8, 0.5, 0.95];
+
+// Smallest luminance difference from the background of atoms in the colorblind-safe and grayscale palettes
+const MIN_ATOM_CONTRAST: f32 = 0.15;
+// Smallest luminance difference of label text from the background
+const MIN_LABEL_CONTRAST: f32 = 0.4;
+
+/// How atoms (and bonds colored as their atoms) get their colors.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ColorScheme {
+    // Colors of elements from the atoms of the style
+    Jmol,
+    // Classic colors of space-filling models as used by RasMol
+    Cpk,
+    // Okabe-Ito colors, distinguishable with any type of color vision deficiency
+    ColorblindSafe,
+    // Atomic mass mapped to the viridis colormap, from the lightest to the heaviest atom of the structure
+    AtomicMass,
+    // Charge mapped to the coolwarm colormap symmetrically around zero, negative charges are blue
+    Charge,
+    // Every chain gets its own color
+    Chain,
+    // Scalar bound to every atom, e.g. along a trajectory, mapped to the sequential colormap over its range.
+    // Atoms keep the colors of their elements until values are bound
+    Property,
+}
+
+impl ColorScheme {
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        match name.to_ascii_lowercase().replace(['-', ' '], "_").as_str() {
+            "jmol" | "element" => Ok(ColorScheme::Jmol),
+            "cpk" | "rasmol" => Ok(ColorScheme::Cpk),
+            "colorblind_safe" | "colorblind" | "okabe_ito" => Ok(ColorScheme::ColorblindSafe),
+            "atomic_mass" | "mass" => Ok(ColorScheme::AtomicMass),
+            "charge" => Ok(ColorScheme::Charge),
+            "chain" => Ok(ColorScheme::Chain),
+            "property" | "frame_property" => Ok(ColorScheme::Property),
+            _ => Err(format!("Unknown color scheme: {}", name)),
+        }
+    }
+
+    /// Colors of atoms with the atomic numbers in the palette of the style, `None` for elements missing from the style.
+    /// In the colorblind-safe and grayscale palettes atoms too close to the background in luminance are lightened
+    /// or darkened, bonds take the colors of their atoms and follow them.
+    pub fn atom_colors(
+        self,
+        style: &Style,
+        atomic_num: &[i32],
+        properties: &AtomProperties,
+    ) -> Result<Vec<Option<Color>>, String> {
+        let element_color = |number: i32| style.atom(number).map(|atom| atom.color);
+        // Colors of real elements are replaced by the palette, dummy atoms keep colors of the style
+        let palette_color = |number: i32, color: Option<Color>| match style.palette {
+            Palette::ColorblindSafe if number > 0 => Some(colorblind_safe_color(number)),
+            Palette::Grayscale if number > 0 => Some(grayscale_color(number)),
+            _ => color,
+        };
+        let colors: Vec<Option<Color>> = match self {
+            ColorScheme::Jmol => atomic_num
+                .iter()
+                .map(|&number| palette_color(number, element_color(number)))
+                .collect(),
+            ColorScheme::Cpk => atomic_num
+                .iter()
+                .map(|&number| {
+                    if number > 0 {
+                        palette_color(number, Some(cpk_color(number)))
+                    } else {
+                        element_color(number)
+                    }
+                })
+                .collect(),
+            ColorScheme::ColorblindSafe => atomic_num
+                .iter()
+                .map(|&number| {
+                    if number > 0 {
+                        Some(colorblind_safe_color(number))
+                    } else {
+                        element_color(number)
+                    }
+                })
+                .collect(),
+            ColorScheme::AtomicMass => {
+                let masses: Vec<Option<f64>> = atomic_num.iter().map(|&number| get_atomic_mass(number)).collect();
+                let (min, max) = masses
+                    .iter()
+                    .flatten()
+                    .fold((f64::MAX, f64::MIN), |(min, max), &mass| (min.min(mass), max.max(mass)));
+                let span = if max > min { max - min } else { 1.0 };
+                atomic_num
+                    .iter()
+                    .zip(&masses)
+                    .map(|(&number, mass)| match mass {
+                        Some(mass) => Some(colormap_color(style.palette.sequential(), (mass - min) / span)),
+                        None => element_color(number),
+                    })
+                    .collect()
+            }
+            ColorScheme::Charge => {
+                let charges = properties
+                    .charges
+                    .as_ref()
+                    .ok_or_else(|| "The structure has no charges".to_string())?;
+                let limit = charges.iter().fold(0.0_f64, |limit, &charge| limit.max(charge.abs()));
+                let limit = if limit > 0.0 { limit } else { 1.0 };
+                charges
+                    .iter()
+                    .map(|&charge| Some(colormap_color(style.palette.diverging(), 0.5 + 0.5 * charge / limit)))
+                    .collect()
+            }
+            ColorScheme::Chain => {
+                let chains = properties
+                    .chains
+                    .as_ref()
+                    .ok_or_else(|| "The structure has no chains".to_string())?;
+                chains
+                    .iter()
+                    .map(|&chain| Some(chain_color(style.palette, chain)))
+                    .collect()
+            }
+            ColorScheme::Property => match &properties.values {
+                Some((values, [min, max])) => {
+                    let span = if max > min { max - min } else { 1.0 };
+                    values
+                        .iter()
+                        .map(|value| Some(colormap_color(style.palette.sequential(), (value - min) / span)))
+                        .collect()
+                }
+                None => atomic_num
+                    .iter()
+                    .map(|&number| palette_color(number, element_color(number)))
+                    .collect(),
+            },
+        };
+        if style.palette == Palette::Standard {
+            return Ok(colors);
+        }
+        Ok(colors
+            .into_iter()
+            .map(|color| color.map(|color| contrasting(color, style.background_color, MIN_ATOM_CONTRAST)))
+            .collect())
+    }
+}
+
+/// Per-atom data of the structure for property-based color schemes.
+pub struct AtomProperties {
+    pub charges: Option<Vec<f64>>,
+    // Index of the chain of every atom, chains are numbered in order of appearance
+    pub chains: Option<Vec<usize>>,
+    // Scalar of every atom for the property scheme with the range mapped onto the colormap
+    pub values: Option<(Vec<f64>, [f64; 2])>,
+}
+
+impl AtomProperties {
+    pub fn new(data: &AtomicCoordinates) -> Self {
+        let num_atoms = data.atomic_num.len();
+        let charges = data.charges.clone().filter(|charges| charges.len() == num_atoms);
+        let chains = data
+            .chains
+            .as_ref()
+            .filter(|chains| chains.len() == num_atoms)
+            .map(|chains| {
+                let mut ids: Vec<&str> = Vec::new();
+                chains
+                    .iter()
+                    .map(|chain| match ids.iter().position(|id| id == chain) {
+                        Some(index) => index,
+                        None => {
+                            ids.push(chain);
+                            ids.len() - 1
+                        }
+                    })
+                    .collect()
+            });
+        Self {
+            charges,
+            chains,
+            values: None,
+        }
+    }
+}
+
+fn luminance(color: Color) -> f32 {
+    relative_luminance([color.r, color.g, color.b])
+}
+
+/// The color lightened or darkened until its luminance differs from the background by at least `min_contrast`.
+pub fn contrasting(color: Color, background: Color, min_contrast: f32) -> Color {
+    let (l, background_l) = (luminance(color), luminance(background));
+    if (l - background_l).abs() >= min_contrast {
+        return color;
+    }
+    // Move away from the background towards the end of the luminance range with more room
+    let (target, end) = if background_l < 0.5 {
+        ((background_l + min_contrast).min(1.0), 1.0)
+    } else {
+        ((background_l - min_contrast).max(0.0), 0.0)
+    };
+    let t = if (end - l).abs() > 0.0 {
+        ((target - l) / (end - l)).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    let mix = |c: f32| c + (end - c) * t;
+    Color::new(mix(color.r), mix(color.g), mix(color.b), color.a)
+}
+
+/// Label text and outline colors, replaced by black or white if the text is hard to read on the background.
+pub fn contrasting_label_colors(color: Color, outline_color: Color, background: Color) -> (Color, Color) {
+    if (luminance(color) - luminance(background)).abs() >= MIN_LABEL_CONTRAST {
+        return (color, outline_color);
+    }
+    let black = Color::new(0.0, 0.0, 0.0, color.a);
+    let white = Color::new(1.0, 1.0, 1.0, color.a);
+    if luminance(background) < 0.5 {
+        (white, black)
+    } else {
+        (black, white)
+    }
+}
+
+fn chain_color(palette: Palette, chain: usize) -> Color {
+    let [r, g, b] = match palette {
+        Palette::Standard => CHAIN_COLORS[chain % CHAIN_COLORS.len()],
+        Palette::ColorblindSafe => COLORBLIND_SAFE_CHAIN_COLORS[chain % COLORBLIND_SAFE_CHAIN_COLORS.len()],
+        Palette::Grayscale => [GRAYSCALE_CHAIN_LEVELS[chain % GRAYSCALE_CHAIN_LEVELS.len()]; 3],
+    };
+    Color::new(r, g, b, 1.0)
+}
+
+fn colormap_color(colormap: Colormap, t: f64) -> Color {
+    let [r, g, b] = colormap.sample(t);
+    Color::new(r, g, b, 1.0)
+}
+
+/// RasMol CPK colors, elements without their own color are deep pink.
+fn cpk_color(atomic_number: i32) -> Color {
+    let [r, g, b] = match atomic_number {
+        1 => [255, 255, 255],
+        2 => [255, 192, 203],
+        3 => [178, 34, 34],
+        5 | 17 => [0, 255, 0],
+        6 => [200, 200, 200],
+        7 => [143, 143, 255],
+        8 => [240, 0, 0],
+        9 | 14 | 79 => [218, 165, 32],
+        11 => [0, 0, 255],
+        12 => [34, 139, 34],
+        13 | 20 | 22 | 24 | 25 | 47 => [128, 128, 144],
+        15 | 26 | 56 => [255, 165, 0],
+        16 => [255, 200, 50],
+        28 | 29 | 30 | 35 => [165, 42, 42],
+        53 => [160, 32, 240],
+        _ => [255, 20, 147],
+    };
+    Color::new(r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0, 1.0)
+}
+
+/// Okabe-Ito palette: organic elements and halogens get their own colors, metals share one.
+fn colorblind_safe_color(atomic_number: i32) -> Color {
+    let [r, g, b] = match atomic_number {
+        1 => [255, 255, 255],
+        6 => [140, 140, 140],
+        7 => [0, 114, 178],
+        8 => [213, 94, 0],
+        9 | 17 | 35 | 53 => [0, 158, 115],
+        15 => [230, 159, 0],
+        16 | 34 => [240, 228, 66],
+        5 | 14 | 32 | 33 | 51 | 52 => [86, 180, 233],
+        2 | 10 | 18 | 36 | 54 | 86 => [200, 200, 200],
+        _ => [204, 121, 167],
+    };
+    Color::new(r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0, 1.0)
+}
+
+/// Gray levels of common elements spread over the whole range, so that they stay apart in black-and-white print.
+/// Other elements get the luminance of their CPK color.
+fn grayscale_color(atomic_number: i32) -> Color {
+    let level = match atomic_number {
+        1 => 1.0,
+        6 => 0.3,
+        7 => 0.5,
+        8 => 0.75,
+        9 | 17 | 35 | 53 => 0.6,
+        15 => 0.4,
+        16 => 0.88,
+        _ => luminance(cpk_color(atomic_number)),
+    };
+    Color::new(level, level, level, 1.0)
+}
@@ -1,3 +1,11 @@
+use shared_lib::colormap::Palette;
+use shared_lib::comparison::Tolerances;
+use shared_lib::connectivity::DEFAULT_BOND_TOLERANCE;
+use shared_lib::periodic_table::{DUMMY_ATOM, GHOST_ATOM, POINT_CHARGE, RadiusSet, is_pseudo_element};
+use shared_lib::types::AtomicCoordinates;
+
+use super::color_scheme::{ColorScheme, contrasting_label_colors};
+use super::presets::StylePresets;
 use super::types::Color;
 use std::collections::HashMap;
 
@@ -6,6 +14,20 @@ pub struct Atom {
     pub color: Color,
 }
 
+/// Radii of a set of the periodic table scaled, e.g. a quarter of van der Waals radii.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ScaledRadii {
+    pub set: RadiusSet,
+    pub scale: f32,
+}
+
+impl ScaledRadii {
+    /// Scaled radius of the element in Angstroms, None if the set has no value for it.
+    pub fn radius(&self, atomic_number: i32) -> Option<f32> {
+        self.set.radius(atomic_number).map(|radius| radius as f32 * self.scale)
+    }
+}
+
 pub struct SelectedAtom {
     pub color: Color,
     pub scale_factor: f32,
@@ -20,13 +42,156 @@ pub struct Bond {
     pub thickness: f32,
     pub color_mode: BondColorMode,
     pub color: Color,
+    // Distance between axes of neighbouring cylinders of double and triple bonds
+    pub multiple_bond_spacing: f32,
+    // Thickness of cylinders of double and triple bonds relative to `thickness`
+    pub multiple_bond_thickness_factor: f32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Representation {
+    // Atoms with radii of the style connected by bonds
+    BallAndStick,
+    // Atoms with van der Waals radii, no bonds
+    Spacefill,
+    // Bonds only, atoms are joints as thick as the bonds
+    Licorice,
+    // Thin bonds only
+    Wireframe,
+}
+
+impl Representation {
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        match name.to_ascii_lowercase().replace(['-', ' '], "_").as_str() {
+            "ball_and_stick" => Ok(Representation::BallAndStick),
+            "spacefill" | "vdw" => Ok(Representation::Spacefill),
+            "licorice" | "sticks" => Ok(Representation::Licorice),
+            "wireframe" | "lines" => Ok(Representation::Wireframe),
+            _ => Err(format!("Unknown representation: {}", name)),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Representation::BallAndStick => "ball_and_stick",
+            Representation::Spacefill => "spacefill",
+            Representation::Licorice => "licorice",
+            Representation::Wireframe => "wireframe",
+        }
+    }
+}
+
+pub struct Label {
+    // Height of the glyphs in Angstroms
+    pub size: f32,
+    pub color: Color,
+    pub outline_color: Color,
+}
+
+pub struct Measurement {
+    // Radius of the lines and arcs between measured atoms in Angstroms
+    pub thickness: f32,
+    pub color: Color,
+    // Translucent disks of fitted planes
+    pub plane_color: Color,
+}
+
+pub struct CellBox {
+    // Radius of the edges of the periodic cell in Angstroms
+    pub thickness: f32,
+    pub color: Color,
+}
+
+pub struct Vibration {
+    // Radius of the shafts of displacement arrows in Angstroms
+    pub arrow_thickness: f32,
+    // Length in Angstroms of the arrow of the atom with the largest displacement of the mode
+    pub arrow_length: f32,
+    pub arrow_color: Color,
+}
+
+pub struct Geometry {
+    // Draw atoms and bonds as ray casted impostors on cubes, otherwise as triangle meshes
+    pub ray_casting: bool,
+    // Number of subdivisions of the icosahedron used for atom meshes
+    pub sphere_subdivisions: u32,
+    // Apparent radius of atoms in pixels below which atom meshes have one subdivision less
+    pub sphere_medium_radius_pixels: f32,
+    // Apparent radius of atoms in pixels below which atom meshes have two subdivisions less
+    pub sphere_coarse_radius_pixels: f32,
+    // Number of side faces of bond meshes
+    pub cylinder_segments: u32,
+}
+
+// Depth cueing: objects farther from the camera blend toward the background
+pub struct Fog {
+    pub enabled: bool,
+    // Distances from the front of the molecule where the fog begins and where objects disappear in it,
+    // as fractions of the depth of the molecule (0 – the front, 1 – the back of its bounding sphere)
+    pub start: f32,
+    pub end: f32,
+}
+
+impl Fog {
+    pub fn set_range(&mut self, start: f32, end: f32) -> Result<(), String> {
+        if !(start.is_finite() && end.is_finite()) || start < 0.0 || end <= start {
+            return Err(format!("Invalid fog range from {} to {}", start, end));
+        }
+        self.start = start;
+        self.end = end;
+        Ok(())
+    }
+}
+
+pub struct Effects {
+    // Blur objects away from the selected atoms (the center of the molecule if nothing is selected)
+    pub depth_of_field: bool,
+    // Distance in Angstroms from the focus that stays sharp
+    pub focus_range: f32,
+    // Blur radius in pixels of the most defocused objects
+    pub max_blur_pixels: f32,
+    // Cartoon-style dark outline along silhouettes
+    pub outline: bool,
+    pub outline_color: Color,
+    pub outline_thickness_pixels: f32,
+    // Depth step in Angstroms between neighbouring pixels that is drawn as an edge
+    pub outline_depth_threshold: f32,
+    // Fast approximate anti-aliasing (FXAA) of jagged edges, also cheap enough for interactive use
+    pub antialiasing: bool,
+}
+
+impl Effects {
+    pub fn is_enabled(&self) -> bool {
+        self.depth_of_field || self.outline || self.antialiasing
+    }
 }
 
 pub struct Style {
     pub background_color: Color,
+    // Cleared to transparent instead of the background color, e.g. for compositing figures
+    pub transparent_background: bool,
     pub atoms: HashMap<i32, Atom>,
+    // Ball-and-stick radii of atoms from a radius set instead of the display radii above, elements missing from
+    // the set keep their display radii
+    pub radius_set: Option<ScaledRadii>,
     pub selected_atom: SelectedAtom,
     pub bond: Bond,
+    pub geometry: Geometry,
+    pub label: Label,
+    pub measurement: Measurement,
+    pub vibration: Vibration,
+    pub cell_box: CellBox,
+    pub fog: Fog,
+    // Post-processing for presentation renders, off for interactive use
+    pub effects: Effects,
+    pub representation: Representation,
+    pub color_scheme: ColorScheme,
+    // Colors of elements, chains and property colormaps of the color scheme
+    pub palette: Palette,
+    // Radius of bonds and atoms in the licorice representation
+    pub licorice_thickness: f32,
+    // Radius of bonds and atoms in the wireframe representation
+    pub wireframe_thickness: f32,
     pub geom_bond_tolerance: f64,
 }
 
@@ -35,14 +200,21 @@ impl Style {
         let mut atoms = HashMap::new();
 
         atoms.insert(
-            -2,
+            GHOST_ATOM,
+            Atom {
+                radius: 0.15,
+                color: Color::new(0.75, 0.75, 0.75, 1.0),
+            },
+        );
+        atoms.insert(
+            POINT_CHARGE,
             Atom {
                 radius: 0.25,
                 color: Color::new(0.73, 0.58, 0.31, 1.0),
             },
         );
         atoms.insert(
-            -1,
+            DUMMY_ATOM,
             Atom {
                 radius: 0.15,
                 color: Color::new(0.0, 0.98, 1.0, 1.0),
@@ -877,24 +1049,197 @@ impl Style {
 
         Self {
             background_color: Color::new(0.133, 0.133, 0.133, 1.0),
+            transparent_background: false,
             atoms,
+            radius_set: None,
             selected_atom: SelectedAtom {color: Color::new(0.58, 1.0, 1.0, 0.3), scale_factor: 1.4},
             bond: Bond {
                 thickness: 0.1,
                 color_mode: BondColorMode::AtomColor,
                 color: Color::new(0.5, 0.5, 0.5, 1.0),
+                multiple_bond_spacing: 0.18,
+                multiple_bond_thickness_factor: 0.5,
+            },
+            geometry: Geometry {
+                ray_casting: true,
+                sphere_subdivisions: 3,
+                sphere_medium_radius_pixels: 12.0,
+                sphere_coarse_radius_pixels: 4.0,
+                cylinder_segments: 16,
+            },
+            label: Label {
+                size: 0.25,
+                color: Color::new(1.0, 1.0, 1.0, 1.0),
+                outline_color: Color::new(0.0, 0.0, 0.0, 1.0),
             },
-            geom_bond_tolerance: 0.15,
+            measurement: Measurement {
+                thickness: 0.02,
+                color: Color::new(1.0, 0.85, 0.2, 1.0),
+                plane_color: Color::new(0.4, 0.7, 1.0, 0.35),
+            },
+            vibration: Vibration {
+                arrow_thickness: 0.04,
+                arrow_length: 1.2,
+                arrow_color: Color::new(0.3, 0.85, 0.4, 1.0),
+            },
+            cell_box: CellBox {
+                thickness: 0.015,
+                color: Color::new(0.75, 0.75, 0.75, 1.0),
+            },
+            fog: Fog {
+                enabled: false,
+                start: 0.3,
+                end: 1.0,
+            },
+            effects: Effects {
+                depth_of_field: false,
+                focus_range: 1.0,
+                max_blur_pixels: 6.0,
+                outline: false,
+                outline_color: Color::new(0.0, 0.0, 0.0, 1.0),
+                outline_thickness_pixels: 1.5,
+                outline_depth_threshold: 0.5,
+                antialiasing: false,
+            },
+            representation: Representation::BallAndStick,
+            color_scheme: ColorScheme::Jmol,
+            palette: Palette::Standard,
+            licorice_thickness: 0.15,
+            wireframe_thickness: 0.025,
+            geom_bond_tolerance: DEFAULT_BOND_TOLERANCE,
+        }
+    }
+
+    /// Style of atoms of the element, pseudo-elements without their own style are drawn as dummy atoms.
+    pub fn atom(&self, number: i32) -> Option<&Atom> {
+        self.atoms.get(&number).or_else(|| {
+            if is_pseudo_element(number) {
+                self.atoms.get(&DUMMY_ATOM)
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Label style with black or white text and outline if the label colors are hard to read on the background.
+    pub fn contrasting_label(&self) -> Label {
+        let (color, outline_color) =
+            contrasting_label_colors(self.label.color, self.label.outline_color, self.background_color);
+        Label {
+            size: self.label.size,
+            color,
+            outline_color,
+        }
+    }
+
+    /// Color the frame is cleared to, transparent black with `transparent_background`.
+    pub fn clear_color(&self) -> wgpu::Color {
+        if self.transparent_background {
+            return wgpu::Color::TRANSPARENT;
+        }
+        wgpu::Color {
+            r: self.background_color.r as f64,
+            g: self.background_color.g as f64,
+            b: self.background_color.b as f64,
+            a: 1.0,
+        }
+    }
+}
+
+pub struct Lod {
+    // Molecules with fewer atoms are always rendered in full detail
+    pub min_atoms: usize,
+    // Apparent radius of atoms in pixels below which bonds are drawn as low-poly cylinders
+    pub simplified_radius_pixels: f32,
+    // Apparent radius of atoms in pixels below which atoms are drawn as point sprites and bonds are hidden
+    pub points_radius_pixels: f32,
+    // Number of side faces of simplified bonds
+    pub simplified_cylinder_segments: u32,
+    // Molecules with at least this many atoms are culled on the GPU and drawn indirectly, if compute shaders are available
+    pub gpu_culling_min_atoms: usize,
+    // Otherwise molecules with at least this many atoms are culled on the CPU by chunks of instances
+    pub cpu_culling_min_atoms: usize,
+}
+
+impl Lod {
+    pub fn new() -> Self {
+        Self {
+            min_atoms: 100_000,
+            simplified_radius_pixels: 6.0,
+            points_radius_pixels: 2.0,
+            simplified_cylinder_segments: 6,
+            gpu_culling_min_atoms: 100_000,
+            cpu_culling_min_atoms: 20_000,
         }
     }
 }
 
+/// Representation of a structure opened without one chosen by the host, so that large files open responsively.
+pub struct ViewDefaults {
+    pub enabled: bool,
+    // Structures with fewer atoms are shown as balls and sticks, unless they have residues
+    pub ball_and_stick_max_atoms: usize,
+    // Otherwise structures with fewer atoms are shown as licorice, larger ones as wireframe
+    // (drawn as points when zoomed out, see `Lod`)
+    pub licorice_max_atoms: usize,
+}
+
+impl ViewDefaults {
+    pub fn new() -> Self {
+        Self {
+            enabled: true,
+            ball_and_stick_max_atoms: 5_000,
+            licorice_max_atoms: 50_000,
+        }
+    }
+
+    /// Representation by the number of atoms and the presence of residue data (chains), `None` if disabled.
+    pub fn representation(&self, data: &AtomicCoordinates) -> Option<Representation> {
+        if !self.enabled {
+            return None;
+        }
+        let num_atoms = data.atomic_num.len();
+        // Biomolecules read better as sticks even when small
+        let has_residues = data.chains.is_some();
+        Some(if num_atoms < self.ball_and_stick_max_atoms && !has_residues {
+            Representation::BallAndStick
+        } else if num_atoms < self.licorice_max_atoms {
+            Representation::Licorice
+        } else {
+            Representation::Wireframe
+        })
+    }
+}
+
 pub struct Config {
     pub style: Style,
+    pub lod: Lod,
+    pub presets: StylePresets,
+    /// Tolerances of symmetry detection and of atoms highlighted as moved.
+    pub tolerances: Tolerances,
+    /// Loaded structures are first shown along their principal axes instead of as in the file.
+    pub auto_orientation: bool,
+    /// Dummy atoms, point charges and ghost atoms of loaded structures are shown.
+    pub show_pseudo_atoms: bool,
+    pub view_defaults: ViewDefaults,
 }
 
 impl Config {
     pub fn new() -> Self {
-        Self { style: Style::new() }
+        Self {
+            style: Style::new(),
+            lod: Lod::new(),
+            presets: StylePresets::new(),
+            tolerances: Tolerances::default(),
+            auto_orientation: true,
+            show_pseudo_atoms: true,
+            view_defaults: ViewDefaults::new(),
+        }
+    }
+
+    /// Applies the built-in or registered style preset to the style.
+    pub fn apply_preset(&mut self, name: &str) -> Result<(), String> {
+        self.presets.get(name)?.apply(&mut self.style);
+        Ok(())
     }
 }
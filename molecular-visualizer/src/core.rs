@@ -1,14 +1,20 @@
+pub mod arcball;
 pub mod camera;
-pub mod math;
 pub mod mesh;
 pub mod mesh_objects;
+pub mod node;
 pub mod projection;
 pub mod transform;
 
-pub use camera::Camera;
+// Vectors, matrices and quaternions are shared with the analysis code
+pub use shared_lib::math;
+
+pub use arcball::Arcball;
+pub use camera::{Camera, CameraFlight, CameraLimits};
 pub use math::matrix::Mat4;
 pub use math::quaternion::Quaternion;
 pub use math::vector::Vec3;
 pub use mesh::Mesh;
+pub use node::Node;
 pub use projection::{ProjectionManager, ProjectionMode};
 pub use transform::Transform;
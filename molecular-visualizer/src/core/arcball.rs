@@ -0,0 +1,120 @@
+use super::math::{Quaternion, Vec3};
+
+// Angular speed in degrees per millisecond below which the inertial rotation stops
+const MIN_SPEED: f32 = 0.002;
+// Rotation does not continue if the cursor stood still for longer than this before release
+const MAX_RELEASE_DELAY_MS: f64 = 50.0;
+// Longest step of the inertial rotation, e.g. when frames were not drawn while the page was hidden
+const MAX_STEP_MS: f64 = 100.0;
+
+/// Maps drags on the screen to rotations about the center of the view, the point under the cursor follows it
+/// as if the molecule were inside of a ball. With inertia the rotation continues after release and slows down.
+pub struct Arcball {
+    // Fraction of the angular speed lost per second of the inertial rotation, `None` disables inertia
+    pub damping: Option<f32>,
+    width: f32,
+    height: f32,
+    // Point of the last drag on the ball and the time of it in milliseconds
+    last_point: Option<Vec3<f32>>,
+    last_time: f64,
+    // Rotation axis scaled by the angular speed in degrees per millisecond
+    velocity: Vec3<f32>,
+    spinning: bool,
+}
+
+impl Arcball {
+    pub fn new() -> Self {
+        Self {
+            damping: Some(0.9),
+            width: 1.0,
+            height: 1.0,
+            last_point: None,
+            last_time: 0.0,
+            velocity: Vec3::zero(),
+            spinning: false,
+        }
+    }
+
+    /// Point on the ball under the pixel. Outside of the ball the sphere continues with a hyperbolic sheet,
+    /// so that rotation stays smooth at the edge.
+    fn project(&self, x: f32, y: f32) -> Vec3<f32> {
+        let size = self.width.min(self.height);
+        let nx = (2.0 * x - self.width) / size;
+        let ny = (self.height - 2.0 * y) / size;
+        let r2 = nx * nx + ny * ny;
+        let z = if r2 <= 0.5 { (1.0 - r2).sqrt() } else { 0.5 / r2.sqrt() };
+        Vec3::new(nx, ny, z).normalized()
+    }
+
+    /// Starts a drag at the pixel of the viewport, an inertial rotation is stopped.
+    pub fn begin(&mut self, x: f32, y: f32, width: u32, height: u32, time: f64) {
+        self.width = width.max(1) as f32;
+        self.height = height.max(1) as f32;
+        self.last_point = Some(self.project(x, y));
+        self.last_time = time;
+        self.velocity = Vec3::zero();
+        self.spinning = false;
+    }
+
+    /// Rotation in view space from the last point of the drag to the pixel, `None` if the drag was not started
+    /// or the cursor has not moved far enough.
+    pub fn drag(&mut self, x: f32, y: f32, time: f64) -> Option<Quaternion<f32>> {
+        let from = self.last_point?;
+        let to = self.project(x, y);
+        let axis = Vec3::cross_product(from, to);
+        let sin = axis.length();
+        if sin < 1e-6 {
+            return None;
+        }
+        let angle = sin.atan2(Vec3::dot_product(from, to)).to_degrees();
+
+        // Smoothed, so that the speed at release does not depend on the last mouse event only
+        let elapsed = (time - self.last_time) as f32;
+        if elapsed > 0.0 {
+            let velocity = axis * (angle / sin / elapsed);
+            self.velocity = (self.velocity + velocity) * 0.5;
+        }
+        self.last_point = Some(to);
+        self.last_time = time;
+        Some(Quaternion::from_axis_and_angle(axis, angle))
+    }
+
+    /// Ends the drag, returns `true` if the rotation continues with inertia.
+    pub fn end(&mut self, time: f64) -> bool {
+        if self.last_point.take().is_none() {
+            return false;
+        }
+        self.spinning = self.damping.is_some_and(|damping| damping < 1.0)
+            && time - self.last_time <= MAX_RELEASE_DELAY_MS
+            && self.velocity.length() >= MIN_SPEED;
+        self.last_time = time;
+        self.spinning
+    }
+
+    pub fn is_spinning(&self) -> bool {
+        self.spinning
+    }
+
+    pub fn stop(&mut self) {
+        self.last_point = None;
+        self.spinning = false;
+    }
+
+    /// Rotation of the inertial motion since the last step, `None` once it has stopped.
+    pub fn step(&mut self, time: f64) -> Option<Quaternion<f32>> {
+        let Some(damping) = self.damping.filter(|_| self.spinning) else {
+            self.spinning = false;
+            return None;
+        };
+        let elapsed = (time - self.last_time).clamp(0.0, MAX_STEP_MS);
+        self.last_time = time;
+        let speed = self.velocity.length();
+        let rotation = Quaternion::from_axis_and_angle(self.velocity, speed * elapsed as f32);
+
+        self.velocity *= (1.0 - damping).powf(elapsed as f32 / 1000.0);
+        if self.velocity.length() < MIN_SPEED {
+            self.spinning = false;
+        }
+        Some(rotation)
+    }
+}
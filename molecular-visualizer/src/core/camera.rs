@@ -1,5 +1,50 @@
 use super::{Mat4, Vec3};
 
+/// Limits of the camera navigation.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CameraLimits {
+    /// Closest and farthest distance to the target in Angstroms.
+    pub min_distance: f32,
+    pub max_distance: f32,
+    /// Largest angle in degrees of the camera above or below the target, less than 90 so the view never looks
+    /// along the up vector.
+    pub max_pitch: f32,
+}
+
+impl Default for CameraLimits {
+    fn default() -> Self {
+        Self {
+            min_distance: 0.5,
+            max_distance: 10000.0,
+            max_pitch: 89.0,
+        }
+    }
+}
+
+impl CameraLimits {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(self.min_distance > 0.0 && self.min_distance <= self.max_distance && self.max_distance.is_finite()) {
+            return Err(format!(
+                "Invalid camera distance limits: {} to {}",
+                self.min_distance, self.max_distance
+            ));
+        }
+        if !(self.max_pitch > 0.0 && self.max_pitch < 90.0) {
+            return Err(format!(
+                "Pitch limit must be between 0 and 90 degrees: {}",
+                self.max_pitch
+            ));
+        }
+        Ok(())
+    }
+}
+
+// Rotation of the vector about the unit axis by the angle in degrees (Rodrigues' formula)
+fn rotate(v: Vec3<f32>, axis: Vec3<f32>, angle: f32) -> Vec3<f32> {
+    let (sin, cos) = angle.to_radians().sin_cos();
+    v * cos + Vec3::cross_product(axis, v) * sin + axis * (Vec3::dot_product(axis, v) * (1.0 - cos))
+}
+
 pub struct Camera {
     // Camera for managing 3D camera position, orientation and view matrix.
     // The camera uses a look-at approach where it maintains:
@@ -9,6 +54,7 @@ pub struct Camera {
     position: Vec3<f32>,
     target: Vec3<f32>,
     up_vector: Vec3<f32>,
+    limits: CameraLimits,
     matrix: Mat4<f32>,
     dirty: bool,
 }
@@ -19,6 +65,7 @@ impl Camera {
             position: Vec3::new(0.0, 0.0, 1.0),
             target: Vec3::new(0.0, 0.0, 0.0),
             up_vector: Vec3::new(0.0, 1.0, 0.0),
+            limits: CameraLimits::default(),
             matrix: Mat4::new(),
             dirty: true,
         }
@@ -52,4 +99,138 @@ impl Camera {
         self.up_vector = Vec3::new(0.0, 1.0, 0.0);
         self.dirty = true;
     }
+
+    // Only the render tests look at where the camera is
+    #[cfg(test)]
+    pub fn position(&self) -> Vec3<f32> {
+        self.position
+    }
+
+    #[cfg(test)]
+    pub fn target(&self) -> Vec3<f32> {
+        self.target
+    }
+
+    pub fn distance(&self) -> f32 {
+        (self.position - self.target).length()
+    }
+
+    pub fn limits(&self) -> CameraLimits {
+        self.limits
+    }
+
+    /// Sets the limits and moves the camera within them.
+    pub fn set_limits(&mut self, limits: CameraLimits) -> Result<(), String> {
+        limits.validate()?;
+        self.limits = limits;
+        self.orbit(0.0, 0.0);
+        self.dolly(1.0);
+        Ok(())
+    }
+
+    /// Turns the camera around the target by `yaw` degrees about the up vector and raises it by `pitch` degrees,
+    /// the elevation above the target is clamped to the pitch limit.
+    pub fn orbit(&mut self, yaw: f32, pitch: f32) {
+        let up = self.up_vector.normalized();
+        let offset = rotate(self.position - self.target, up, yaw);
+        let distance = offset.length();
+        if distance == 0.0 {
+            return;
+        }
+        let elevation = (Vec3::dot_product(offset, up) / distance)
+            .clamp(-1.0, 1.0)
+            .asin()
+            .to_degrees();
+        let new_elevation = (elevation + pitch).clamp(-self.limits.max_pitch, self.limits.max_pitch);
+        let axis = Vec3::cross_product(offset, up);
+        let offset = if axis.length() > 0.0 {
+            rotate(offset, axis.normalized(), new_elevation - elevation)
+        } else {
+            offset
+        };
+        self.position = self.target + offset;
+        self.dirty = true;
+    }
+
+    /// Moves the camera and the target together by `dx` along the right and `dy` along the up direction
+    /// of the view, in Angstroms.
+    pub fn pan(&mut self, dx: f32, dy: f32) {
+        let forward = (self.target - self.position).normalized();
+        let right = Vec3::cross_product(forward, self.up_vector).normalized();
+        let up = Vec3::cross_product(right, forward);
+        let shift = right * dx + up * dy;
+        self.position += shift;
+        self.target += shift;
+        self.dirty = true;
+    }
+
+    /// Points the camera at the target from the distance within the distance limits, keeping the direction
+    /// of the view.
+    pub fn look_at(&mut self, target: Vec3<f32>, distance: f32) {
+        let offset = self.position - self.target;
+        let direction = if offset.length() > 0.0 {
+            offset.normalized()
+        } else {
+            Vec3::new(0.0, 0.0, 1.0)
+        };
+        let distance = distance.clamp(self.limits.min_distance, self.limits.max_distance);
+        self.target = target;
+        self.position = target + direction * distance;
+        self.dirty = true;
+    }
+
+    /// Moves the camera towards the target, dividing the distance by `factor` within the distance limits.
+    /// Returns the ratio of the new distance to the old one.
+    pub fn dolly(&mut self, factor: f32) -> f32 {
+        let offset = self.position - self.target;
+        let distance = offset.length();
+        if distance == 0.0 || factor <= 0.0 {
+            return 1.0;
+        }
+        let new_distance = (distance / factor).clamp(self.limits.min_distance, self.limits.max_distance);
+        self.position = self.target + offset * (new_distance / distance);
+        self.dirty = true;
+        new_distance / distance
+    }
+}
+
+/// Flight of the camera to a new target and distance, eased in and out over `duration` milliseconds.
+pub struct CameraFlight {
+    from_target: Vec3<f32>,
+    from_distance: f32,
+    to_target: Vec3<f32>,
+    to_distance: f32,
+    duration: f64,
+    // Time of the first step, the flight starts on the first animation frame
+    start: Option<f64>,
+}
+
+impl CameraFlight {
+    pub fn new(camera: &Camera, target: Vec3<f32>, distance: f32, duration: f64) -> Self {
+        Self {
+            from_target: camera.target,
+            from_distance: camera.distance(),
+            to_target: target,
+            to_distance: distance,
+            duration,
+            start: None,
+        }
+    }
+
+    /// Moves the camera to where it is at the time in milliseconds, returns `false` once it has arrived.
+    pub fn step(&mut self, camera: &mut Camera, time: f64) -> bool {
+        let start = *self.start.get_or_insert(time);
+        let t = if self.duration > 0.0 {
+            ((time - start) / self.duration).clamp(0.0, 1.0) as f32
+        } else {
+            1.0
+        };
+        // Smoothstep: the camera starts and stops without a jerk
+        let s = t * t * (3.0 - 2.0 * t);
+        camera.look_at(
+            self.from_target + (self.to_target - self.from_target) * s,
+            self.from_distance + (self.to_distance - self.from_distance) * s,
+        );
+        t < 1.0
+    }
 }
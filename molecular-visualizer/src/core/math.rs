@@ -1,7 +0,0 @@
-pub mod matrix;
-pub mod quaternion;
-pub mod vector;
-
-pub use matrix::Mat4;
-pub use quaternion::Quaternion;
-pub use vector::Vec3;
@@ -1,4 +1,3 @@
-use super::super::types::Color;
 use bytemuck::{Pod, Zeroable};
 
 #[repr(C)]
@@ -29,14 +28,26 @@ impl Vertex {
     }
 }
 
+/// Bit of `InstanceData::flags` enabling Blinn-Phong lighting, flat color otherwise.
+pub const LIGHTING_FLAG: u32 = 1 << 2;
+/// Bit of `InstanceData::flags` hiding the instance, toggled in place without rebuilding instance buffers.
+pub const HIDDEN_FLAG: u32 = 1 << 3;
+
+/// Per-instance data, the model matrix is rebuilt in the vertex shader as translation * rotation * scale.
 #[repr(C)]
 #[derive(Copy, Clone, Debug, Pod, Zeroable)]
 pub struct InstanceData {
-    pub model_matrix: [[f32; 4]; 4],
-    pub color: Color,
-    pub picking_color: Color,
-    pub lighting_model: u32,
-    pub ray_casting_type: u32,
+    /// Unit quaternion as (x, y, z, w).
+    pub rotation: [f32; 4],
+    pub position: [f32; 3],
+    /// Scale along X and Y, scale along Z.
+    pub scale: [f32; 2],
+    /// RGBA, 8 bits per channel.
+    pub color: [u8; 4],
+    /// Encoded into the picking color by the shader, 0 means nothing.
+    pub picking_id: u32,
+    /// Bits 0-1: ray casting type (0 = mesh, 1 = sphere, 2 = cylinder), bit 2: `LIGHTING_FLAG`, bit 3: `HIDDEN_FLAG`.
+    pub flags: u32,
 }
 
 impl InstanceData {
@@ -45,52 +56,40 @@ impl InstanceData {
             array_stride: std::mem::size_of::<InstanceData>() as wgpu::BufferAddress,
             step_mode: wgpu::VertexStepMode::Instance,
             attributes: &[
-                // Model matrix row 0
+                // Rotation
                 wgpu::VertexAttribute {
                     offset: 0,
                     shader_location: 2,
                     format: wgpu::VertexFormat::Float32x4,
                 },
-                // Model matrix row 1
+                // Position
                 wgpu::VertexAttribute {
-                    offset: std::mem::size_of::<[f32; 4]>() as wgpu::BufferAddress,
+                    offset: std::mem::size_of::<[f32; 4]>() as wgpu::BufferAddress, // offset 16
                     shader_location: 3,
-                    format: wgpu::VertexFormat::Float32x4,
+                    format: wgpu::VertexFormat::Float32x3,
                 },
-                // Model matrix row 2
+                // Scale
                 wgpu::VertexAttribute {
-                    offset: (std::mem::size_of::<[f32; 4]>() * 2) as wgpu::BufferAddress,
+                    offset: std::mem::size_of::<[f32; 7]>() as wgpu::BufferAddress, // offset 28
                     shader_location: 4,
-                    format: wgpu::VertexFormat::Float32x4,
+                    format: wgpu::VertexFormat::Float32x2,
                 },
-                // Model matrix row 3
+                // Color
                 wgpu::VertexAttribute {
-                    offset: (std::mem::size_of::<[f32; 4]>() * 3) as wgpu::BufferAddress,
+                    offset: std::mem::size_of::<[f32; 9]>() as wgpu::BufferAddress, // offset 36
                     shader_location: 5,
-                    format: wgpu::VertexFormat::Float32x4,
+                    format: wgpu::VertexFormat::Unorm8x4,
                 },
-                // Color
+                // Picking ID
                 wgpu::VertexAttribute {
-                    offset: (std::mem::size_of::<[f32; 4]>() * 4) as wgpu::BufferAddress, // offset 64
+                    offset: std::mem::size_of::<[f32; 10]>() as wgpu::BufferAddress, // offset 40
                     shader_location: 6,
-                    format: wgpu::VertexFormat::Float32x4,
-                },
-                // Picking color
-                wgpu::VertexAttribute {
-                    offset: (std::mem::size_of::<[f32; 4]>() * 5) as wgpu::BufferAddress, // offset 80
-                    shader_location: 7,
-                    format: wgpu::VertexFormat::Float32x4,
-                },
-                // Lighting model
-                wgpu::VertexAttribute {
-                    offset: (std::mem::size_of::<[f32; 4]>() * 6) as wgpu::BufferAddress, // offset 96
-                    shader_location: 8,
                     format: wgpu::VertexFormat::Uint32,
                 },
-                // Ray Casting Type
+                // Flags
                 wgpu::VertexAttribute {
-                    offset: (std::mem::size_of::<[f32; 4]>() * 6 + std::mem::size_of::<u32>()) as wgpu::BufferAddress, // offset 100
-                    shader_location: 9,
+                    offset: std::mem::size_of::<[f32; 11]>() as wgpu::BufferAddress, // offset 44
+                    shader_location: 7,
                     format: wgpu::VertexFormat::Uint32,
                 },
             ],
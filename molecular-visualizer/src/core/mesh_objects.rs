@@ -1 +1,5 @@
+pub mod cone;
 pub mod cube;
+pub mod cylinder;
+pub mod rect;
+pub mod sphere;
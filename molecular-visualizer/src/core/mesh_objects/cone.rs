@@ -0,0 +1,62 @@
+use super::super::mesh::{Mesh, Vertex};
+
+pub fn create(radius: f32, length: f32, segments: u32) -> Mesh {
+    // Generate cone centered at origin with its axis along Z, the apex points to +Z.
+    let segments = segments.max(3) as u16;
+    let half_length = length / 2.0;
+
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+
+    let directions: Vec<(f32, f32)> = (0..segments)
+        .map(|i| {
+            let angle = 2.0 * std::f32::consts::PI * i as f32 / segments as f32;
+            (angle.cos(), angle.sin())
+        })
+        .collect();
+
+    // SIDE: pairs of base and apex vertices with normals perpendicular to the slant
+    let slant = (length * length + radius * radius).sqrt();
+    for &(x, y) in &directions {
+        let normal = [x * length / slant, y * length / slant, radius / slant];
+        vertices.push(Vertex {
+            position: [x * radius, y * radius, -half_length],
+            normal,
+        });
+        vertices.push(Vertex {
+            position: [0.0, 0.0, half_length],
+            normal,
+        });
+    }
+    for i in 0..segments {
+        let base = 2 * i;
+        let next_base = 2 * ((i + 1) % segments);
+        indices.extend_from_slice(&[base, next_base, base + 1]);
+    }
+
+    // BASE: triangle fan around the center vertex
+    let center = vertices.len() as u16;
+    vertices.push(Vertex {
+        position: [0.0, 0.0, -half_length],
+        normal: [0.0, 0.0, -1.0],
+    });
+    for &(x, y) in &directions {
+        vertices.push(Vertex {
+            position: [x * radius, y * radius, -half_length],
+            normal: [0.0, 0.0, -1.0],
+        });
+    }
+    for i in 0..segments {
+        let (a, b) = (center + 1 + i, center + 1 + (i + 1) % segments);
+        // Counter-clockwise when looking at the base from outside
+        indices.extend_from_slice(&[center, b, a]);
+    }
+
+    let num_indices = indices.len() as u32;
+
+    Mesh {
+        vertices,
+        indices,
+        num_indices,
+    }
+}
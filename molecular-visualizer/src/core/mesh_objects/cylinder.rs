@@ -0,0 +1,73 @@
+use super::super::mesh::{Mesh, Vertex};
+
+pub fn create(radius: f32, length: f32, segments: u32) -> Mesh {
+    // Generate capped cylinder centered at origin with its axis along Z.
+    let segments = segments.max(3) as u16;
+    let half_length = length / 2.0;
+
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+
+    let directions: Vec<(f32, f32)> = (0..segments)
+        .map(|i| {
+            let angle = 2.0 * std::f32::consts::PI * i as f32 / segments as f32;
+            (angle.cos(), angle.sin())
+        })
+        .collect();
+
+    // SIDE: pairs of bottom and top vertices with radial normals
+    for &(x, y) in &directions {
+        vertices.push(Vertex {
+            position: [x * radius, y * radius, -half_length],
+            normal: [x, y, 0.0],
+        });
+        vertices.push(Vertex {
+            position: [x * radius, y * radius, half_length],
+            normal: [x, y, 0.0],
+        });
+    }
+    for i in 0..segments {
+        let bottom = 2 * i;
+        let next_bottom = 2 * ((i + 1) % segments);
+        indices.extend_from_slice(&[
+            bottom,
+            next_bottom,
+            bottom + 1,
+            bottom + 1,
+            next_bottom,
+            next_bottom + 1,
+        ]);
+    }
+
+    // CAPS: triangle fans around center vertices
+    for (z, normal_z) in [(half_length, 1.0), (-half_length, -1.0)] {
+        let center = vertices.len() as u16;
+        vertices.push(Vertex {
+            position: [0.0, 0.0, z],
+            normal: [0.0, 0.0, normal_z],
+        });
+        for &(x, y) in &directions {
+            vertices.push(Vertex {
+                position: [x * radius, y * radius, z],
+                normal: [0.0, 0.0, normal_z],
+            });
+        }
+        for i in 0..segments {
+            let (a, b) = (center + 1 + i, center + 1 + (i + 1) % segments);
+            // Counter-clockwise when looking at the cap from outside
+            if normal_z > 0.0 {
+                indices.extend_from_slice(&[center, a, b]);
+            } else {
+                indices.extend_from_slice(&[center, b, a]);
+            }
+        }
+    }
+
+    let num_indices = indices.len() as u32;
+
+    Mesh {
+        vertices,
+        indices,
+        num_indices,
+    }
+}
@@ -0,0 +1,35 @@
+use super::super::mesh::{Mesh, Vertex};
+
+pub fn create(width: f32, height: f32) -> Mesh {
+    // Generate rectangle in the XY plane centered at origin, facing Z+.
+    let half_width = width / 2.0;
+    let half_height = height / 2.0;
+    let normal = [0.0, 0.0, 1.0];
+
+    let vertices = vec![
+        Vertex {
+            position: [-half_width, -half_height, 0.0],
+            normal,
+        }, // 0: left bottom
+        Vertex {
+            position: [half_width, -half_height, 0.0],
+            normal,
+        }, // 1: right bottom
+        Vertex {
+            position: [half_width, half_height, 0.0],
+            normal,
+        }, // 2: right top
+        Vertex {
+            position: [-half_width, half_height, 0.0],
+            normal,
+        }, // 3: left top
+    ];
+    let indices = vec![0, 1, 2, 0, 2, 3];
+    let num_indices = indices.len() as u32;
+
+    Mesh {
+        vertices,
+        indices,
+        num_indices,
+    }
+}
@@ -0,0 +1,99 @@
+use std::collections::HashMap;
+
+use super::super::mesh::{Mesh, Vertex};
+
+// Level 6 has 40962 vertices, the next level would overflow u16 indices
+pub const MAX_SUBDIVISIONS: u32 = 6;
+
+pub fn create(radius: f32, subdivisions: u32) -> Mesh {
+    // Generate icosphere centered at origin: each subdivision splits every triangle into four
+    // and pushes the new vertices onto the unit sphere.
+    let t = (1.0 + 5.0f32.sqrt()) / 2.0;
+    let mut points: Vec<[f32; 3]> = [
+        [-1.0, t, 0.0],
+        [1.0, t, 0.0],
+        [-1.0, -t, 0.0],
+        [1.0, -t, 0.0],
+        [0.0, -1.0, t],
+        [0.0, 1.0, t],
+        [0.0, -1.0, -t],
+        [0.0, 1.0, -t],
+        [t, 0.0, -1.0],
+        [t, 0.0, 1.0],
+        [-t, 0.0, -1.0],
+        [-t, 0.0, 1.0],
+    ]
+    .iter()
+    .map(|&p| normalize(p))
+    .collect();
+
+    let mut triangles: Vec<[u16; 3]> = vec![
+        [0, 11, 5],
+        [0, 5, 1],
+        [0, 1, 7],
+        [0, 7, 10],
+        [0, 10, 11],
+        [1, 5, 9],
+        [5, 11, 4],
+        [11, 10, 2],
+        [10, 7, 6],
+        [7, 1, 8],
+        [3, 9, 4],
+        [3, 4, 2],
+        [3, 2, 6],
+        [3, 6, 8],
+        [3, 8, 9],
+        [4, 9, 5],
+        [2, 4, 11],
+        [6, 2, 10],
+        [8, 6, 7],
+        [9, 8, 1],
+    ];
+
+    for _ in 0..subdivisions.min(MAX_SUBDIVISIONS) {
+        // Shared edges get a single midpoint vertex
+        let mut midpoints: HashMap<(u16, u16), u16> = HashMap::new();
+        let mut midpoint = |a: u16, b: u16| -> u16 {
+            *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                let (pa, pb) = (points[a as usize], points[b as usize]);
+                points.push(normalize([
+                    (pa[0] + pb[0]) / 2.0,
+                    (pa[1] + pb[1]) / 2.0,
+                    (pa[2] + pb[2]) / 2.0,
+                ]));
+                (points.len() - 1) as u16
+            })
+        };
+
+        triangles = triangles
+            .iter()
+            .flat_map(|&[a, b, c]| {
+                let ab = midpoint(a, b);
+                let bc = midpoint(b, c);
+                let ca = midpoint(c, a);
+                [[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]
+            })
+            .collect();
+    }
+
+    let vertices = points
+        .iter()
+        .map(|&p| Vertex {
+            position: [p[0] * radius, p[1] * radius, p[2] * radius],
+            normal: p,
+        })
+        .collect();
+    let indices: Vec<u16> = triangles.into_iter().flatten().collect();
+    let num_indices = indices.len() as u32;
+
+    Mesh {
+        vertices,
+        indices,
+        num_indices,
+    }
+}
+
+fn normalize(p: [f32; 3]) -> [f32; 3] {
+    let length = (p[0] * p[0] + p[1] * p[1] + p[2] * p[2]).sqrt();
+    [p[0] / length, p[1] / length, p[2] / length]
+}
@@ -0,0 +1,57 @@
+use super::math::Mat4;
+use super::transform::Transform;
+
+/// Object of the scene graph. The transform of a node is relative to its parent, hidden nodes hide their
+/// children. The id identifies the node, e.g. in picking.
+pub struct Node {
+    pub id: u32,
+    pub transform: Transform,
+    pub visible: bool,
+    pub children: Vec<Node>,
+}
+
+impl Node {
+    pub fn new(id: u32) -> Self {
+        Self {
+            id,
+            transform: Transform::new(),
+            visible: true,
+            children: Vec::new(),
+        }
+    }
+
+    pub fn with_child(mut self, child: Node) -> Self {
+        self.children.push(child);
+        self
+    }
+
+    /// The node or its descendant with the id, depth first.
+    pub fn find_mut(&mut self, id: u32) -> Option<&mut Node> {
+        if self.id == id {
+            return Some(self);
+        }
+        self.children.iter_mut().find_map(|child| child.find_mut(id))
+    }
+
+    /// Removes the descendant with the id together with its children.
+    pub fn remove(&mut self, id: u32) -> Option<Node> {
+        if let Some(i) = self.children.iter().position(|child| child.id == id) {
+            return Some(self.children.remove(i));
+        }
+        self.children.iter_mut().find_map(|child| child.remove(id))
+    }
+
+    /// World matrix of the node with the id, `None` if it is hidden or not in the subtree.
+    pub fn world_matrix(&mut self, parent: &Mat4<f32>, id: u32) -> Option<Mat4<f32>> {
+        if !self.visible {
+            return None;
+        }
+        let world = *parent * *self.transform.get_matrix();
+        if self.id == id {
+            return Some(world);
+        }
+        self.children
+            .iter_mut()
+            .find_map(|child| child.world_matrix(&world, id))
+    }
+}
@@ -1,5 +1,8 @@
 use super::Mat4;
 
+/// Field of view of the perspective projection in degrees.
+pub const DEFAULT_FOV: f32 = 45.0;
+
 pub struct OrthographicProjection {
     width: u32,
     height: u32,
@@ -56,6 +59,10 @@ impl OrthographicProjection {
         self.update_matrix();
     }
 
+    pub fn get_view_bounds(&self) -> f32 {
+        self.view_bounds
+    }
+
     pub fn set_view_bounds(&mut self, value: f32) {
         self.view_bounds = value;
         self.update_matrix();
@@ -122,14 +129,36 @@ impl PerspectiveProjection {
     pub fn get_fov(&self) -> f32 {
         self.fov
     }
+
+    pub fn set_fov(&mut self, fov: f32) {
+        self.fov = fov;
+        self.update_matrix();
+    }
 }
 
-#[derive(PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum ProjectionMode {
     Orthographic,
     Perspective,
 }
 
+impl ProjectionMode {
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        match name.to_ascii_lowercase().as_str() {
+            "orthographic" | "ortho" => Ok(ProjectionMode::Orthographic),
+            "perspective" => Ok(ProjectionMode::Perspective),
+            _ => Err(format!("Unknown projection mode: {}", name)),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ProjectionMode::Orthographic => "orthographic",
+            ProjectionMode::Perspective => "perspective",
+        }
+    }
+}
+
 pub struct ProjectionManager {
     pub mode: ProjectionMode,
     pub orthographic_projection: OrthographicProjection,
@@ -141,7 +170,7 @@ impl ProjectionManager {
         Self {
             mode,
             orthographic_projection: OrthographicProjection::new(width, height, 10.0, 10.0),
-            perspective_projection: PerspectiveProjection::new(45.0, width, height, 0.1, 1000.0),
+            perspective_projection: PerspectiveProjection::new(DEFAULT_FOV, width, height, 0.1, 1000.0),
         }
     }
 
@@ -158,10 +187,15 @@ impl ProjectionManager {
         self.perspective_projection.set_viewport(width, height);
     }
 
-    pub fn get_fov(self) -> f32 {
+    pub fn get_fov(&self) -> f32 {
         self.perspective_projection.fov
     }
 
+    /// Sets the field of view of the perspective projection in degrees.
+    pub fn set_fov(&mut self, fov: f32) {
+        self.perspective_projection.set_fov(fov);
+    }
+
     pub fn set_near_far_plane(&mut self, near_plane: f32, far_plane: f32) {
         self.perspective_projection.set_near_far_plane(near_plane, far_plane);
     }
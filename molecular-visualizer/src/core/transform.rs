@@ -84,6 +84,12 @@ impl Transform {
         self.dirty = true
     }
 
+    /// Applies the rotation in view space after the current one, e.g. of an arcball drag.
+    pub fn rotate_by(&mut self, rotation: Quaternion<f32>) {
+        self.rotation = rotation * self.rotation;
+        self.dirty = true;
+    }
+
     pub fn set_rotation(&mut self, rotation: Quaternion<f32>) {
         self.rotation = rotation;
         self.dirty = true;
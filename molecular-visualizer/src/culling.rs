@@ -0,0 +1,421 @@
+use std::ops::Range;
+
+use bytemuck::{Pod, Zeroable};
+use wgpu::util::{DeviceExt, DrawIndexedIndirectArgs};
+
+use super::core::Mat4;
+use super::core::mesh::InstanceData;
+use super::molecule::Molecule;
+use super::renderer::shader_source;
+
+const WORKGROUP_SIZE: u32 = 64;
+const MAX_LAYERS: usize = 4;
+const INSTANCE_SIZE: wgpu::BufferAddress = std::mem::size_of::<InstanceData>() as wgpu::BufferAddress;
+const DRAW_ARGS_SIZE: wgpu::BufferAddress = std::mem::size_of::<DrawIndexedIndirectArgs>() as wgpu::BufferAddress;
+// Consecutive instances tested against the view frustum as one chunk by the CPU culling
+pub const CHUNK_SIZE: usize = 256;
+// Most draws per layer of the CPU culling, the nearest visible ranges are merged above it
+pub const MAX_DRAWS: usize = 64;
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+struct LayerParams {
+    first: u32,
+    count: u32,
+    draw: u32,
+    padding: u32,
+}
+
+/// Instances drawn with one mesh.
+pub struct CullingLayer<'a> {
+    pub instances: &'a wgpu::Buffer,
+    pub count: u32,
+    /// Number of indices of the mesh.
+    pub index_count: u32,
+}
+
+struct LayerBinding {
+    instances: wgpu::Buffer,
+    params: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+}
+
+/// GPU-driven path for large scenes. A compute pass drops hidden instances and instances outside
+/// of the view frustum, and appends the rest of every layer to one buffer together with the
+/// arguments of indirect draws, so the CPU neither touches instances nor issues a draw per layer.
+pub struct Culling {
+    pipeline: wgpu::ComputePipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
+    visible_buffer: wgpu::Buffer,
+    draws_buffer: wgpu::Buffer,
+    capacity: u32, // instances
+    bindings: Vec<Option<LayerBinding>>,
+    firsts: Vec<u32>,
+    // Indirect draws may start at a non-zero instance, so layers sharing a mesh are drawn with one multi-draw
+    multi_draw: bool,
+}
+
+impl Culling {
+    /// Compute shaders and storage buffers are missing e.g. on WebGL2.
+    pub fn is_supported(device: &wgpu::Device) -> bool {
+        let limits = device.limits();
+        limits.max_compute_workgroups_per_dimension > 0 && limits.max_storage_buffers_per_shader_stage >= 3
+    }
+
+    pub fn new(device: &wgpu::Device, uniforms_layout: &wgpu::BindGroupLayout) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Culling Shader"),
+            source: shader_source("culling.wgsl", include_str!("shaders/culling.wgsl")),
+        });
+
+        let storage = |binding: u32, read_only: bool| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Culling Bind Group Layout"),
+            entries: &[
+                storage(0, true),
+                storage(1, false),
+                storage(2, false),
+                wgpu::BindGroupLayoutEntry {
+                    binding: 3,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Culling Pipeline Layout"),
+            bind_group_layouts: &[uniforms_layout, &bind_group_layout],
+            immediate_size: 0,
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Culling Pipeline"),
+            layout: Some(&pipeline_layout),
+            module: &shader,
+            entry_point: Some("cs_main"),
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+            cache: None,
+        });
+
+        let draws_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Culling Draws Buffer"),
+            size: DRAW_ARGS_SIZE * MAX_LAYERS as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::INDIRECT | wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        Self {
+            pipeline,
+            bind_group_layout,
+            visible_buffer: Self::create_visible_buffer(device, 1),
+            draws_buffer,
+            capacity: 1,
+            bindings: Vec::new(),
+            firsts: Vec::new(),
+            multi_draw: device.features().contains(wgpu::Features::INDIRECT_FIRST_INSTANCE),
+        }
+    }
+
+    fn create_visible_buffer(device: &wgpu::Device, capacity: u32) -> wgpu::Buffer {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Culling Visible Instances Buffer"),
+            size: capacity as wgpu::BufferAddress * INSTANCE_SIZE,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::STORAGE,
+            mapped_at_creation: false,
+        })
+    }
+
+    /// Records the culling pass of the layers into the encoder. The uniforms must be already written,
+    /// draws with `draw` take layers by their index in `layers`.
+    pub fn cull(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        uniforms_bind_group: &wgpu::BindGroup,
+        layers: &[CullingLayer],
+    ) {
+        assert!(
+            layers.len() <= MAX_LAYERS,
+            "At most {} layers can be culled",
+            MAX_LAYERS
+        );
+
+        let total: u32 = layers.iter().map(|layer| layer.count).sum();
+        if total > self.capacity {
+            self.capacity = total;
+            self.visible_buffer = Self::create_visible_buffer(device, total);
+            self.bindings.clear();
+        }
+        self.bindings.resize_with(layers.len(), || None);
+
+        self.firsts.clear();
+        let mut draws = Vec::with_capacity(layers.len());
+        let mut first = 0;
+        for (i, layer) in layers.iter().enumerate() {
+            self.firsts.push(first);
+            draws.push(DrawIndexedIndirectArgs {
+                index_count: layer.index_count,
+                instance_count: 0, // incremented by the culling pass
+                first_index: 0,
+                base_vertex: 0,
+                first_instance: if self.multi_draw { first } else { 0 },
+            });
+
+            if layer.count == 0 {
+                self.bindings[i] = None;
+                continue;
+            }
+            if self.bindings[i]
+                .as_ref()
+                .is_none_or(|binding| binding.instances != *layer.instances)
+            {
+                self.bindings[i] = Some(self.create_binding(device, layer.instances));
+            }
+            let params = LayerParams {
+                first,
+                count: layer.count,
+                draw: i as u32,
+                padding: 0,
+            };
+            queue.write_buffer(
+                &self.bindings[i].as_ref().unwrap().params,
+                0,
+                bytemuck::bytes_of(&params),
+            );
+            first += layer.count;
+        }
+        let args: Vec<u8> = draws.iter().flat_map(|draw| draw.as_bytes().to_vec()).collect();
+        queue.write_buffer(&self.draws_buffer, 0, &args);
+
+        let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("Culling Pass"),
+            timestamp_writes: None,
+        });
+        pass.set_pipeline(&self.pipeline);
+        pass.set_bind_group(0, uniforms_bind_group, &[]);
+        let max_groups = device.limits().max_compute_workgroups_per_dimension;
+        for (layer, binding) in layers.iter().zip(&self.bindings) {
+            if let Some(binding) = binding {
+                let groups = layer.count.div_ceil(WORKGROUP_SIZE);
+                let groups_x = groups.min(max_groups);
+                pass.set_bind_group(1, &binding.bind_group, &[]);
+                pass.dispatch_workgroups(groups_x, groups.div_ceil(groups_x), 1);
+            }
+        }
+    }
+
+    fn create_binding(&self, device: &wgpu::Device, instances: &wgpu::Buffer) -> LayerBinding {
+        let params = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Culling Layer Buffer"),
+            contents: bytemuck::bytes_of(&LayerParams::zeroed()),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Culling Bind Group"),
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: instances.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: self.visible_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: self.draws_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: params.as_entire_binding(),
+                },
+            ],
+        });
+        LayerBinding {
+            instances: instances.clone(),
+            params,
+            bind_group,
+        }
+    }
+
+    /// Draws the visible instances of consecutive layers that share the mesh bound to the render pass.
+    pub fn draw(&self, render_pass: &mut wgpu::RenderPass, layers: Range<usize>) {
+        if self.multi_draw {
+            render_pass.set_vertex_buffer(1, self.visible_buffer.slice(..));
+            render_pass.multi_draw_indexed_indirect(
+                &self.draws_buffer,
+                layers.start as wgpu::BufferAddress * DRAW_ARGS_SIZE,
+                layers.len() as u32,
+            );
+            return;
+        }
+
+        for layer in layers {
+            if self.bindings[layer].is_none() {
+                continue;
+            }
+            let offset = self.firsts[layer] as wgpu::BufferAddress * INSTANCE_SIZE;
+            render_pass.set_vertex_buffer(1, self.visible_buffer.slice(offset..));
+            render_pass.draw_indexed_indirect(&self.draws_buffer, layer as wgpu::BufferAddress * DRAW_ARGS_SIZE);
+        }
+    }
+}
+
+/// Bounding sphere of a chunk of instances.
+#[derive(Clone, Copy)]
+struct Bounds {
+    center: [f32; 3],
+    radius: f32,
+}
+
+/// CPU path for large scenes where compute shaders are missing (e.g. WebGL2). Instances are split into chunks
+/// of consecutive ones, and chunks outside of the view frustum are skipped by drawing ranges of the instance
+/// buffers. Atoms of a file mostly come along chains and residues or rows of a lattice, so consecutive instances
+/// are close to each other and the bounds of chunks are tight without reordering the instances.
+pub struct ChunkCulling {
+    atoms: Vec<Bounds>,
+    bonds: Vec<Bounds>,
+    num_atoms: u32,
+    num_bonds: u32,
+    // Revision of the molecule the chunks were made of
+    revision: Option<u64>,
+}
+
+impl ChunkCulling {
+    pub fn new() -> Self {
+        Self {
+            atoms: Vec::new(),
+            bonds: Vec::new(),
+            num_atoms: 0,
+            num_bonds: 0,
+            revision: None,
+        }
+    }
+
+    /// Chunks of the instances of atoms and bonds.
+    pub fn from_instances(atoms: &[InstanceData], bonds: &[InstanceData]) -> Self {
+        Self {
+            atoms: atoms.chunks(CHUNK_SIZE).map(chunk_bounds).collect(),
+            bonds: bonds.chunks(CHUNK_SIZE).map(chunk_bounds).collect(),
+            num_atoms: atoms.len() as u32,
+            num_bonds: bonds.len() as u32,
+            revision: None,
+        }
+    }
+
+    /// Rebuilds the chunks if the molecule has changed since the last call.
+    pub fn update(&mut self, molecule: &Molecule) {
+        if self.revision == Some(molecule.revision()) {
+            return;
+        }
+        let (atoms, bonds) = molecule.plain_instances();
+        *self = Self {
+            revision: Some(molecule.revision()),
+            ..Self::from_instances(&atoms, &bonds)
+        };
+    }
+
+    /// Ranges of instances of atoms and of bonds to draw, those of chunks at least partly inside the view frustum
+    /// of the transform from the molecule to clip space.
+    pub fn visible(&self, final_matrix: &Mat4<f32>) -> (Vec<Range<u32>>, Vec<Range<u32>>) {
+        let planes = frustum_planes(final_matrix);
+        (
+            visible_ranges(&self.atoms, self.num_atoms, &planes),
+            visible_ranges(&self.bonds, self.num_bonds, &planes),
+        )
+    }
+}
+
+fn chunk_bounds(instances: &[InstanceData]) -> Bounds {
+    let mut min = [f32::INFINITY; 3];
+    let mut max = [f32::NEG_INFINITY; 3];
+    for instance in instances {
+        for a in 0..3 {
+            min[a] = min[a].min(instance.position[a]);
+            max[a] = max[a].max(instance.position[a]);
+        }
+    }
+    let center: [f32; 3] = std::array::from_fn(|a| 0.5 * (min[a] + max[a]));
+    // Meshes fit into the unit cube scaled by the instance, whatever the rotation is, as in the culling shader
+    let radius = instances
+        .iter()
+        .map(|instance| {
+            let [xy, z] = instance.scale;
+            let distance_sq: f32 = (0..3).map(|a| (instance.position[a] - center[a]).powi(2)).sum();
+            distance_sq.sqrt() + (2.0 * xy * xy + z * z).sqrt()
+        })
+        .fold(0.0, f32::max);
+    Bounds { center, radius }
+}
+
+/// Planes (a, b, c, d) of the view frustum with a·x + b·y + c·z + d ≥ 0 inside, extracted from the transform
+/// to clip space with the depth range [0, 1] (Gribb & Hartmann).
+fn frustum_planes(m: &Mat4<f32>) -> [[f32; 4]; 6] {
+    let row = |i: usize| [m.data[i], m.data[4 + i], m.data[8 + i], m.data[12 + i]];
+    let (x, y, z, w) = (row(0), row(1), row(2), row(3));
+    let add = |u: [f32; 4], v: [f32; 4]| std::array::from_fn(|k| u[k] + v[k]);
+    let sub = |u: [f32; 4], v: [f32; 4]| std::array::from_fn(|k| u[k] - v[k]);
+    [add(w, x), sub(w, x), add(w, y), sub(w, y), z, sub(w, z)]
+}
+
+fn is_in_frustum(bounds: &Bounds, planes: &[[f32; 4]; 6]) -> bool {
+    planes.iter().all(|plane| {
+        let [a, b, c, d] = *plane;
+        let [x, y, z] = bounds.center;
+        a * x + b * y + c * z + d >= -bounds.radius * (a * a + b * b + c * c).sqrt()
+    })
+}
+
+/// Ranges of the `count` instances of the visible chunks, adjacent chunks are drawn together and the ranges
+/// separated by the shortest gaps are merged so that there are at most `MAX_DRAWS` of them.
+fn visible_ranges(chunks: &[Bounds], count: u32, planes: &[[f32; 4]; 6]) -> Vec<Range<u32>> {
+    let mut ranges: Vec<Range<u32>> = Vec::new();
+    for (k, bounds) in chunks.iter().enumerate() {
+        if !is_in_frustum(bounds, planes) {
+            continue;
+        }
+        let start = (k * CHUNK_SIZE) as u32;
+        let end = (start + CHUNK_SIZE as u32).min(count);
+        match ranges.last_mut() {
+            Some(last) if last.end == start => last.end = end,
+            _ => ranges.push(start..end),
+        }
+    }
+    if ranges.len() > MAX_DRAWS {
+        let mut gaps: Vec<(u32, usize)> = ranges
+            .windows(2)
+            .map(|pair| pair[1].start - pair[0].end)
+            .zip(1..)
+            .collect();
+        gaps.sort_unstable();
+        let mut merged = vec![false; ranges.len()];
+        gaps[..ranges.len() - MAX_DRAWS]
+            .iter()
+            .for_each(|&(_, k)| merged[k] = true);
+        let mut result: Vec<Range<u32>> = Vec::with_capacity(MAX_DRAWS);
+        for (range, merged) in ranges.into_iter().zip(merged) {
+            match result.last_mut() {
+                Some(last) if merged => last.end = range.end,
+                _ => result.push(range),
+            }
+        }
+        ranges = result;
+    }
+    ranges
+}
@@ -0,0 +1,81 @@
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::Path;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::SystemTime;
+
+/// Debug visualizations replacing the shading of atoms and bonds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DebugView {
+    /// Normals as seen by the lighting, mapped from [-1, 1] to [0, 1].
+    Normals,
+    /// Distance from the camera, nearer is brighter.
+    Depth,
+    /// Unrelated colors of neighboring picking IDs, unpickable objects are black.
+    PickingIds,
+    /// Every shaded fragment adds up, including the occluded ones.
+    Overdraw,
+}
+
+impl DebugView {
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        match name.to_ascii_lowercase().as_str() {
+            "normals" => Ok(DebugView::Normals),
+            "depth" => Ok(DebugView::Depth),
+            "picking_ids" | "ids" => Ok(DebugView::PickingIds),
+            "overdraw" => Ok(DebugView::Overdraw),
+            _ => Err(format!("Unknown debug view: {}", name)),
+        }
+    }
+
+    /// Value of `render_mode` in the uniforms of main.wgsl.
+    pub fn render_mode(self) -> u32 {
+        match self {
+            DebugView::Normals => 2,
+            DebugView::Depth => 3,
+            DebugView::PickingIds => 4,
+            DebugView::Overdraw => 5,
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+const SHADERS_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src/shaders");
+
+/// Reads the shader from the source tree, so that reloaded pipelines pick up edits without rebuilding.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn read_shader(name: &str) -> Option<String> {
+    std::fs::read_to_string(Path::new(SHADERS_DIR).join(name)).ok()
+}
+
+/// Notices edits of the shaders in the source tree by modification times of the files.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct ShaderWatcher {
+    last_modified: Option<SystemTime>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl ShaderWatcher {
+    pub fn new() -> Self {
+        Self {
+            last_modified: last_modified(),
+        }
+    }
+
+    /// True once for every batch of edits made since the previous call.
+    pub fn poll(&mut self) -> bool {
+        let modified = last_modified();
+        if modified == self.last_modified {
+            return false;
+        }
+        self.last_modified = modified;
+        true
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn last_modified() -> Option<SystemTime> {
+    std::fs::read_dir(SHADERS_DIR)
+        .ok()?
+        .filter_map(|entry| entry.ok()?.metadata().ok()?.modified().ok())
+        .max()
+}
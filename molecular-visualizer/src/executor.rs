@@ -0,0 +1,144 @@
+use std::ops::Range;
+
+use shared_lib::cancellation::{CANCELLED_MESSAGE, CancellationToken};
+use shared_lib::profiling;
+use wasm_bindgen::prelude::*;
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen_futures::JsFuture;
+
+// Number of items processed between two yields to the browser event loop.
+pub const DEFAULT_CHUNK_SIZE: usize = 4096;
+// Fewest items worth a thread of `map_parallel`, smaller parts cost more to start than to process
+#[cfg(all(feature = "parallel", not(target_arch = "wasm32")))]
+const MIN_ITEMS_PER_THREAD: usize = 1024;
+
+pub struct Progress {
+    callback: Option<js_sys::Function>,
+}
+
+impl Progress {
+    pub fn new(callback: Option<js_sys::Function>) -> Self {
+        Self { callback }
+    }
+
+    /// Calls the host callback as `callback(stage, done, total)`.
+    pub fn report(&self, stage: &str, done: usize, total: usize) {
+        if let Some(callback) = &self.callback {
+            let _ = callback.call3(
+                &JsValue::NULL,
+                &JsValue::from_str(stage),
+                &JsValue::from_f64(done as f64),
+                &JsValue::from_f64(total as f64),
+            );
+        }
+    }
+}
+
+/// Gives control back to the browser so pending events and frames can be processed.
+/// Outside of a window context (e.g. in a web worker) the future resolves on the next microtask.
+#[cfg(target_arch = "wasm32")]
+pub async fn yield_now() {
+    let promise = js_sys::Promise::new(&mut |resolve, _| match web_sys::window() {
+        Some(window) => {
+            let _ = window.set_timeout_with_callback(&resolve);
+        }
+        None => {
+            let _ = resolve.call0(&JsValue::NULL);
+        }
+    });
+    let _ = JsFuture::from(promise).await;
+}
+
+/// Native builds (headless render tests) have no event loop to yield to.
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn yield_now() {}
+
+/// Wraps an optional host callback `is_cancelled() -> bool` into a cancellation token.
+pub fn cancellation_token(is_cancelled: Option<js_sys::Function>) -> CancellationToken {
+    match is_cancelled {
+        Some(callback) => CancellationToken::new(move || {
+            callback
+                .call0(&JsValue::NULL)
+                .map(|value| value.is_truthy())
+                .unwrap_or(false)
+        }),
+        None => CancellationToken::none(),
+    }
+}
+
+/// Splits `0..total` into chunks of `chunk_size` items, calls `f` for every chunk
+/// and yields to the event loop between chunks. The host is asked for cancellation before every chunk.
+/// The first error of `f` is returned without processing the remaining chunks.
+/// The stage is also the name of its profiling span.
+pub async fn run_chunked<F>(
+    total: usize,
+    chunk_size: usize,
+    stage: &'static str,
+    progress: &Progress,
+    cancel: &CancellationToken,
+    mut f: F,
+) -> Result<(), String>
+where
+    F: FnMut(Range<usize>) -> Result<(), String>,
+{
+    let _span = profiling::span(stage);
+    let chunk_size = chunk_size.max(1);
+    let mut start = 0;
+
+    progress.report(stage, 0, total);
+    while start < total {
+        if cancel.is_cancelled() {
+            return Err(CANCELLED_MESSAGE.to_string());
+        }
+
+        let end = (start + chunk_size).min(total);
+        f(start..end)?;
+        progress.report(stage, end, total);
+        start = end;
+
+        if start < total {
+            yield_now().await;
+        }
+    }
+    Ok(())
+}
+
+/// Splits `range` into consecutive parts, calls `f` for every part and returns the results in the order of
+/// the parts, so concatenating them gives the same output as one call for the whole range. Only the native render
+/// tests with the `parallel` feature process the parts on scoped threads. The WebAssembly plugin always has a single
+/// part, the page stays responsive through `run_chunked` alone.
+#[cfg(all(feature = "parallel", not(target_arch = "wasm32")))]
+pub fn map_parallel<T, F>(range: Range<usize>, f: F) -> Vec<T>
+where
+    T: Send,
+    F: Fn(Range<usize>) -> T + Sync,
+{
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let parts = (range.len() / MIN_ITEMS_PER_THREAD).clamp(1, threads);
+    if parts == 1 {
+        return vec![f(range)];
+    }
+    let part_size = range.len().div_ceil(parts);
+    let f = &f;
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = range
+            .clone()
+            .step_by(part_size)
+            .map(|start| scope.spawn(move || f(start..(start + part_size).min(range.end))))
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap_or_else(|panic| std::panic::resume_unwind(panic)))
+            .collect()
+    })
+}
+
+/// Sequential fallback of `map_parallel`: the module is built without threads for the single thread of the page,
+/// with or without the `parallel` feature.
+#[cfg(not(all(feature = "parallel", not(target_arch = "wasm32"))))]
+pub fn map_parallel<T, F>(range: Range<usize>, f: F) -> Vec<T>
+where
+    F: Fn(Range<usize>) -> T,
+{
+    vec![f(range)]
+}
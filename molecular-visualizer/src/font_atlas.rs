@@ -0,0 +1,229 @@
+// 5×7 bitmap glyphs of printable ASCII (32..=126) and EXTRA_CHARS, one byte per column, bit 0 is the top row
+const GLYPHS: [[u8; 5]; 97] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00], // ' '
+    [0x00, 0x00, 0x5F, 0x00, 0x00], // !
+    [0x00, 0x07, 0x00, 0x07, 0x00], // "
+    [0x14, 0x7F, 0x14, 0x7F, 0x14], // #
+    [0x24, 0x2A, 0x7F, 0x2A, 0x12], // $
+    [0x23, 0x13, 0x08, 0x64, 0x62], // %
+    [0x36, 0x49, 0x55, 0x22, 0x50], // &
+    [0x00, 0x05, 0x03, 0x00, 0x00], // '
+    [0x00, 0x1C, 0x22, 0x41, 0x00], // (
+    [0x00, 0x41, 0x22, 0x1C, 0x00], // )
+    [0x08, 0x2A, 0x1C, 0x2A, 0x08], // *
+    [0x08, 0x08, 0x3E, 0x08, 0x08], // +
+    [0x00, 0x50, 0x30, 0x00, 0x00], // ,
+    [0x08, 0x08, 0x08, 0x08, 0x08], // -
+    [0x00, 0x60, 0x60, 0x00, 0x00], // .
+    [0x20, 0x10, 0x08, 0x04, 0x02], // /
+    [0x3E, 0x51, 0x49, 0x45, 0x3E], // 0
+    [0x00, 0x42, 0x7F, 0x40, 0x00], // 1
+    [0x42, 0x61, 0x51, 0x49, 0x46], // 2
+    [0x21, 0x41, 0x45, 0x4B, 0x31], // 3
+    [0x18, 0x14, 0x12, 0x7F, 0x10], // 4
+    [0x27, 0x45, 0x45, 0x45, 0x39], // 5
+    [0x3C, 0x4A, 0x49, 0x49, 0x30], // 6
+    [0x01, 0x71, 0x09, 0x05, 0x03], // 7
+    [0x36, 0x49, 0x49, 0x49, 0x36], // 8
+    [0x06, 0x49, 0x49, 0x29, 0x1E], // 9
+    [0x00, 0x36, 0x36, 0x00, 0x00], // :
+    [0x00, 0x56, 0x36, 0x00, 0x00], // ;
+    [0x08, 0x14, 0x22, 0x41, 0x00], // <
+    [0x14, 0x14, 0x14, 0x14, 0x14], // =
+    [0x00, 0x41, 0x22, 0x14, 0x08], // >
+    [0x02, 0x01, 0x51, 0x09, 0x06], // ?
+    [0x32, 0x49, 0x79, 0x41, 0x3E], // @
+    [0x7E, 0x11, 0x11, 0x11, 0x7E], // A
+    [0x7F, 0x49, 0x49, 0x49, 0x36], // B
+    [0x3E, 0x41, 0x41, 0x41, 0x22], // C
+    [0x7F, 0x41, 0x41, 0x22, 0x1C], // D
+    [0x7F, 0x49, 0x49, 0x49, 0x41], // E
+    [0x7F, 0x09, 0x09, 0x09, 0x01], // F
+    [0x3E, 0x41, 0x49, 0x49, 0x7A], // G
+    [0x7F, 0x08, 0x08, 0x08, 0x7F], // H
+    [0x00, 0x41, 0x7F, 0x41, 0x00], // I
+    [0x20, 0x40, 0x41, 0x3F, 0x01], // J
+    [0x7F, 0x08, 0x14, 0x22, 0x41], // K
+    [0x7F, 0x40, 0x40, 0x40, 0x40], // L
+    [0x7F, 0x02, 0x0C, 0x02, 0x7F], // M
+    [0x7F, 0x04, 0x08, 0x10, 0x7F], // N
+    [0x3E, 0x41, 0x41, 0x41, 0x3E], // O
+    [0x7F, 0x09, 0x09, 0x09, 0x06], // P
+    [0x3E, 0x41, 0x51, 0x21, 0x5E], // Q
+    [0x7F, 0x09, 0x19, 0x29, 0x46], // R
+    [0x46, 0x49, 0x49, 0x49, 0x31], // S
+    [0x01, 0x01, 0x7F, 0x01, 0x01], // T
+    [0x3F, 0x40, 0x40, 0x40, 0x3F], // U
+    [0x1F, 0x20, 0x40, 0x20, 0x1F], // V
+    [0x3F, 0x40, 0x38, 0x40, 0x3F], // W
+    [0x63, 0x14, 0x08, 0x14, 0x63], // X
+    [0x07, 0x08, 0x70, 0x08, 0x07], // Y
+    [0x61, 0x51, 0x49, 0x45, 0x43], // Z
+    [0x00, 0x7F, 0x41, 0x41, 0x00], // [
+    [0x02, 0x04, 0x08, 0x10, 0x20], // \
+    [0x00, 0x41, 0x41, 0x7F, 0x00], // ]
+    [0x04, 0x02, 0x01, 0x02, 0x04], // ^
+    [0x40, 0x40, 0x40, 0x40, 0x40], // _
+    [0x00, 0x01, 0x02, 0x04, 0x00], // `
+    [0x20, 0x54, 0x54, 0x54, 0x78], // a
+    [0x7F, 0x48, 0x44, 0x44, 0x38], // b
+    [0x38, 0x44, 0x44, 0x44, 0x20], // c
+    [0x38, 0x44, 0x44, 0x48, 0x7F], // d
+    [0x38, 0x54, 0x54, 0x54, 0x18], // e
+    [0x08, 0x7E, 0x09, 0x01, 0x02], // f
+    [0x0C, 0x52, 0x52, 0x52, 0x3E], // g
+    [0x7F, 0x08, 0x04, 0x04, 0x78], // h
+    [0x00, 0x44, 0x7D, 0x40, 0x00], // i
+    [0x20, 0x40, 0x44, 0x3D, 0x00], // j
+    [0x7F, 0x10, 0x28, 0x44, 0x00], // k
+    [0x00, 0x41, 0x7F, 0x40, 0x00], // l
+    [0x7C, 0x04, 0x18, 0x04, 0x78], // m
+    [0x7C, 0x08, 0x04, 0x04, 0x78], // n
+    [0x38, 0x44, 0x44, 0x44, 0x38], // o
+    [0x7C, 0x14, 0x14, 0x14, 0x08], // p
+    [0x08, 0x14, 0x14, 0x18, 0x7C], // q
+    [0x7C, 0x08, 0x04, 0x04, 0x08], // r
+    [0x48, 0x54, 0x54, 0x54, 0x20], // s
+    [0x04, 0x3F, 0x44, 0x40, 0x20], // t
+    [0x3C, 0x40, 0x40, 0x20, 0x7C], // u
+    [0x1C, 0x20, 0x40, 0x20, 0x1C], // v
+    [0x3C, 0x40, 0x30, 0x40, 0x3C], // w
+    [0x44, 0x28, 0x10, 0x28, 0x44], // x
+    [0x0C, 0x50, 0x50, 0x50, 0x3C], // y
+    [0x44, 0x64, 0x54, 0x4C, 0x44], // z
+    [0x00, 0x08, 0x36, 0x41, 0x00], // {
+    [0x00, 0x00, 0x7F, 0x00, 0x00], // |
+    [0x00, 0x41, 0x36, 0x08, 0x00], // }
+    [0x08, 0x04, 0x08, 0x10, 0x08], // ~
+    // Characters outside of ASCII, in the order of EXTRA_CHARS
+    [0x00, 0x06, 0x09, 0x09, 0x06], // °
+    [0x78, 0x16, 0x15, 0x16, 0x78], // Å
+];
+
+const FIRST_CHAR: u32 = 32;
+const LAST_CHAR: u32 = 126;
+// Units of measurements
+const EXTRA_CHARS: [char; 2] = ['°', 'Å'];
+pub const GLYPH_WIDTH: u32 = 5;
+pub const GLYPH_HEIGHT: u32 = 7;
+// Empty texels around a glyph: one for the outline and one so linear filtering does not bleed between cells
+pub const GLYPH_PADDING: u32 = 2;
+pub const CELL_WIDTH: u32 = GLYPH_WIDTH + 2 * GLYPH_PADDING;
+pub const CELL_HEIGHT: u32 = GLYPH_HEIGHT + 2 * GLYPH_PADDING;
+const COLUMNS: u32 = 16;
+const ROWS: u32 = (GLYPHS.len() as u32).div_ceil(COLUMNS);
+const ATLAS_WIDTH: u32 = COLUMNS * CELL_WIDTH;
+const ATLAS_HEIGHT: u32 = ROWS * CELL_HEIGHT;
+
+/// Texture with the glyphs of the built-in bitmap font.
+/// The red channel is the glyph, the green channel is the glyph dilated by one texel for the outline.
+pub struct FontAtlas {
+    pub bind_group: wgpu::BindGroup,
+}
+
+impl FontAtlas {
+    pub fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        layout: &wgpu::BindGroupLayout,
+        sampler: &wgpu::Sampler,
+    ) -> Self {
+        let size = wgpu::Extent3d {
+            width: ATLAS_WIDTH,
+            height: ATLAS_HEIGHT,
+            depth_or_array_layers: 1,
+        };
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Font Atlas Texture"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rg8Unorm,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        queue.write_texture(
+            wgpu::TexelCopyTextureInfo {
+                texture: &texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            &Self::texels(),
+            wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(2 * ATLAS_WIDTH),
+                rows_per_image: Some(ATLAS_HEIGHT),
+            },
+            size,
+        );
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Font Atlas Bind Group"),
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(sampler),
+                },
+            ],
+        });
+
+        Self { bind_group }
+    }
+
+    fn texels() -> Vec<u8> {
+        let width = ATLAS_WIDTH as usize;
+        let is_set = |glyph: &[u8; 5], x: i32, y: i32| {
+            (0..GLYPH_WIDTH as i32).contains(&x)
+                && (0..GLYPH_HEIGHT as i32).contains(&y)
+                && glyph[x as usize] & (1 << y) != 0
+        };
+
+        let mut texels = vec![0u8; width * ATLAS_HEIGHT as usize * 2];
+        for (i, glyph) in GLYPHS.iter().enumerate() {
+            let (cell_x, cell_y) = Self::cell(i as u32);
+            for y in 0..CELL_HEIGHT as i32 {
+                for x in 0..CELL_WIDTH as i32 {
+                    let (gx, gy) = (x - GLYPH_PADDING as i32, y - GLYPH_PADDING as i32);
+                    let outline = (-1..=1).any(|dy| (-1..=1).any(|dx| is_set(glyph, gx + dx, gy + dy)));
+                    let offset = ((cell_y + y as u32) as usize * width + (cell_x + x as u32) as usize) * 2;
+                    texels[offset] = if is_set(glyph, gx, gy) { 255 } else { 0 };
+                    texels[offset + 1] = if outline { 255 } else { 0 };
+                }
+            }
+        }
+        texels
+    }
+
+    // Top left texel of the cell of the glyph
+    fn cell(index: u32) -> (u32, u32) {
+        ((index % COLUMNS) * CELL_WIDTH, (index / COLUMNS) * CELL_HEIGHT)
+    }
+
+    /// UV rectangle (u_min, v_min, u_max, v_max) of the cell of the character, including the padding.
+    /// Characters missing from the font are drawn as '?'.
+    pub fn uv(ch: char) -> [f32; 4] {
+        let code = ch as u32;
+        let index = if (FIRST_CHAR..=LAST_CHAR).contains(&code) {
+            code - FIRST_CHAR
+        } else if let Some(i) = EXTRA_CHARS.iter().position(|&extra| extra == ch) {
+            LAST_CHAR - FIRST_CHAR + 1 + i as u32
+        } else {
+            '?' as u32 - FIRST_CHAR
+        };
+        let (x, y) = Self::cell(index);
+        [
+            x as f32 / ATLAS_WIDTH as f32,
+            y as f32 / ATLAS_HEIGHT as f32,
+            (x + CELL_WIDTH) as f32 / ATLAS_WIDTH as f32,
+            (y + CELL_HEIGHT) as f32 / ATLAS_HEIGHT as f32,
+        ]
+    }
+}
@@ -0,0 +1,215 @@
+use shared_lib::cancellation::CancellationToken;
+use shared_lib::types::AtomicCoordinates;
+
+use super::config::Config;
+use super::core::Vec3;
+use super::executor::Progress;
+use super::renderer::Renderer;
+use super::scene::{Scene, Viewport};
+
+struct GridCell {
+    scene: Scene,
+    label: String,
+}
+
+/// Small multiples: structures drawn side by side in one render target, e.g. an ensemble of conformers.
+/// Cells share the pipelines and render targets, every cell has its own viewport and camera.
+/// One cell can be expanded to the whole target.
+pub struct MoleculeGrid {
+    renderer: Renderer,
+    cells: Vec<GridCell>,
+    expanded: Option<usize>,
+    width: u32,
+    height: u32,
+}
+
+impl MoleculeGrid {
+    pub fn new(device: &wgpu::Device, surface_config: &wgpu::SurfaceConfiguration) -> Self {
+        Self {
+            renderer: Renderer::new(device, surface_config),
+            cells: Vec::new(),
+            expanded: None,
+            width: surface_config.width,
+            height: surface_config.height,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    /// Adds a cell with the structure, cells are filled row by row.
+    pub async fn add_structure(
+        &mut self,
+        device: &wgpu::Device,
+        config: &Config,
+        data: &AtomicCoordinates,
+        label: &str,
+        progress: &Progress,
+        cancel: &CancellationToken,
+    ) -> Result<(), String> {
+        let mut scene = Scene::with_renderer(device, self.renderer.clone(), config);
+        scene
+            .load_atomic_coordinates(device, config, data, progress, cancel)
+            .await?;
+        self.cells.push(GridCell {
+            scene,
+            label: label.to_string(),
+        });
+        self.update_viewports();
+        Ok(())
+    }
+
+    pub fn resize(&mut self, device: &wgpu::Device, surface_config: &wgpu::SurfaceConfiguration) {
+        self.renderer.resize(device, surface_config);
+        for cell in &mut self.cells {
+            cell.scene.renderer = self.renderer.clone();
+        }
+        self.width = surface_config.width;
+        self.height = surface_config.height;
+        self.update_viewports();
+    }
+
+    /// Numbers of columns and rows giving the most square cells for the aspect ratio of the target.
+    fn layout(&self) -> (u32, u32) {
+        let n = self.cells.len().max(1) as u32;
+        let aspect = self.width as f32 / self.height.max(1) as f32;
+        let columns = ((n as f32 * aspect).sqrt().round() as u32).clamp(1, n);
+        (columns, n.div_ceil(columns))
+    }
+
+    /// Viewport of the cell, `None` while another cell is expanded.
+    pub fn cell_viewport(&self, index: usize) -> Option<Viewport> {
+        if index >= self.cells.len() {
+            return None;
+        }
+        match self.expanded {
+            Some(expanded) if expanded == index => {
+                return Some(Viewport {
+                    x: 0,
+                    y: 0,
+                    width: self.width,
+                    height: self.height,
+                });
+            }
+            Some(_) => return None,
+            None => {}
+        }
+
+        let (columns, rows) = self.layout();
+        let (column, row) = (index as u32 % columns, index as u32 / columns);
+        let (x, y) = (column * self.width / columns, row * self.height / rows);
+        Some(Viewport {
+            x,
+            y,
+            width: (column + 1) * self.width / columns - x,
+            height: (row + 1) * self.height / rows - y,
+        })
+    }
+
+    /// Cell under the pixel (x, y).
+    pub fn cell_at(&self, x: u32, y: u32) -> Option<usize> {
+        (0..self.cells.len()).find(|&i| self.cell_viewport(i).is_some_and(|viewport| viewport.contains(x, y)))
+    }
+
+    pub fn label(&self, index: usize) -> Option<&str> {
+        self.cells.get(index).map(|cell| cell.label.as_str())
+    }
+
+    pub fn expanded(&self) -> Option<usize> {
+        self.expanded
+    }
+
+    /// Expands the cell to the whole target, `None` shows all cells again.
+    pub fn set_expanded(&mut self, index: Option<usize>) -> Result<(), String> {
+        if let Some(index) = index
+            && index >= self.cells.len()
+        {
+            return Err(format!(
+                "Cell index {} is out of range, the grid has {} cells",
+                index,
+                self.cells.len()
+            ));
+        }
+        self.expanded = index;
+        self.update_viewports();
+        Ok(())
+    }
+
+    /// Click-to-expand: a click on a cell expands it, a click on the expanded cell shows all cells again.
+    /// Returns false if there is no cell under the pixel (x, y).
+    pub fn toggle_expanded_at(&mut self, x: u32, y: u32) -> bool {
+        let Some(index) = self.cell_at(x, y) else {
+            return false;
+        };
+        self.expanded = match self.expanded {
+            Some(_) => None,
+            None => Some(index),
+        };
+        self.update_viewports();
+        true
+    }
+
+    /// Rotates all structures together, so that they are compared from the same side.
+    pub fn rotate(&mut self, pitch: f32, yaw: f32, roll: f32) {
+        for cell in &mut self.cells {
+            cell.scene.transform.rotate(pitch, yaw, roll);
+        }
+    }
+
+    pub fn scale(&mut self, factor: f32) {
+        for cell in &mut self.cells {
+            cell.scene.transform.scale(Vec3::new(factor, factor, factor));
+        }
+    }
+
+    fn update_viewports(&mut self) {
+        for i in 0..self.cells.len() {
+            if let Some(viewport) = self.cell_viewport(i) {
+                self.cells[i]
+                    .scene
+                    .projection_manager
+                    .set_viewport(viewport.width, viewport.height);
+            }
+        }
+    }
+
+    pub fn render_to_view(
+        &mut self,
+        view: &wgpu::TextureView,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        config: &Config,
+    ) {
+        // Gaps of the last row keep the background
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Grid Clear Encoder"),
+        });
+        encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Grid Clear Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                depth_slice: None,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(config.style.clear_color()),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+            multiview_mask: None,
+        });
+        queue.submit(std::iter::once(encoder.finish()));
+
+        // Every cell is submitted separately, as cells share the uniform buffer
+        for i in 0..self.cells.len() {
+            if let Some(viewport) = self.cell_viewport(i) {
+                self.cells[i]
+                    .scene
+                    .render_to_viewport(view, device, queue, config, 0, Some(viewport));
+            }
+        }
+    }
+}
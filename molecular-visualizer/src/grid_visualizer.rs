@@ -0,0 +1,198 @@
+use shared_lib::cancellation::CancellationToken;
+use shared_lib::types::AtomicCoordinates;
+use wasm_bindgen::prelude::*;
+use web_sys::HtmlCanvasElement;
+
+use super::config::Config;
+use super::executor::Progress;
+use super::grid::MoleculeGrid;
+use super::visualizer::CanvasGpu;
+
+/// Label and place of a grid cell in canvas pixels, for captions drawn by the host.
+#[wasm_bindgen]
+pub struct GridCellInfo {
+    label: String,
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+}
+
+#[wasm_bindgen]
+impl GridCellInfo {
+    #[wasm_bindgen(getter)]
+    pub fn label(&self) -> String {
+        self.label.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn x(&self) -> u32 {
+        self.x
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn y(&self) -> u32 {
+        self.y
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+}
+
+/// Many structures, e.g. a conformer ensemble, drawn as a grid in one canvas.
+#[wasm_bindgen]
+pub struct MoleculeGridVisualizer {
+    surface: wgpu::Surface<'static>,
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    config: wgpu::SurfaceConfiguration,
+    grid: MoleculeGrid,
+    visualizer_config: Config,
+}
+
+#[wasm_bindgen]
+impl MoleculeGridVisualizer {
+    /// Use as: `const grid = await MoleculeGridVisualizer.create(canvas);`, then add structures with `add_structure`.
+    pub async fn create(canvas: HtmlCanvasElement) -> Result<MoleculeGridVisualizer, JsValue> {
+        let CanvasGpu {
+            surface,
+            device,
+            queue,
+            config,
+            ..
+        } = CanvasGpu::new(canvas).await?;
+        let grid = MoleculeGrid::new(&device, &config);
+
+        Ok(MoleculeGridVisualizer {
+            surface,
+            device,
+            queue,
+            config,
+            grid,
+            visualizer_config: Config::new(),
+        })
+    }
+
+    /// Adds a cell with the structure (same data as for `MolecularVisualizer.create`) and its caption.
+    pub async fn add_structure(&mut self, data: Vec<u8>, label: String) -> Result<(), JsValue> {
+        let node_data: AtomicCoordinates = serde_json::from_slice(&data)
+            .map_err(|e| JsValue::from_str(&format!("Failed to deserialize data: {e}")))?;
+        self.grid
+            .add_structure(
+                &self.device,
+                &self.visualizer_config,
+                &node_data,
+                &label,
+                &Progress::new(None),
+                &CancellationToken::none(),
+            )
+            .await
+            .map_err(|e| JsValue::from_str(&e))?;
+        self.draw();
+        Ok(())
+    }
+
+    #[wasm_bindgen]
+    pub fn resize(&mut self, width: u32, height: u32) {
+        if width > 0 && height > 0 {
+            self.config.width = width;
+            self.config.height = height;
+            self.surface.configure(&self.device, &self.config);
+            self.grid.resize(&self.device, &self.config);
+            self.draw();
+        }
+    }
+
+    /// Rotates all structures together.
+    #[wasm_bindgen]
+    pub fn rotate_scene(&mut self, pitch: f32, yaw: f32, roll: f32) {
+        if pitch == 0.0 && yaw == 0.0 && roll == 0.0 {
+            return;
+        }
+        self.grid.rotate(pitch, yaw, roll);
+        self.draw();
+    }
+
+    #[wasm_bindgen]
+    pub fn scale_scene(&mut self, factor: f32) {
+        if factor == 1.0 || factor == 0.0 {
+            return;
+        }
+        self.grid.scale(factor);
+        self.draw();
+    }
+
+    /// Visible cells, all of them or only the expanded one.
+    #[wasm_bindgen]
+    pub fn cells(&self) -> Vec<GridCellInfo> {
+        (0..self.grid.len())
+            .filter_map(|i| {
+                let viewport = self.grid.cell_viewport(i)?;
+                Some(GridCellInfo {
+                    label: self.grid.label(i)?.to_string(),
+                    x: viewport.x,
+                    y: viewport.y,
+                    width: viewport.width,
+                    height: viewport.height,
+                })
+            })
+            .collect()
+    }
+
+    /// Index (starts from 0) of the cell under the canvas pixel (x, y).
+    #[wasm_bindgen]
+    pub fn cell_at(&self, x: u32, y: u32) -> Option<usize> {
+        self.grid.cell_at(x, y)
+    }
+
+    /// Click-to-expand: expands the cell under the canvas pixel (x, y), or shows all cells if one is expanded.
+    /// Returns true if the layout has changed.
+    #[wasm_bindgen]
+    pub fn click(&mut self, x: u32, y: u32) -> bool {
+        if !self.grid.toggle_expanded_at(x, y) {
+            return false;
+        }
+        self.draw();
+        true
+    }
+
+    /// Index of the expanded cell, if any.
+    #[wasm_bindgen]
+    pub fn expanded(&self) -> Option<usize> {
+        self.grid.expanded()
+    }
+
+    /// Expands the cell (index starts from 0) to the whole canvas, no index shows all cells.
+    #[wasm_bindgen]
+    pub fn set_expanded(&mut self, index: Option<usize>) -> Result<(), JsValue> {
+        self.grid.set_expanded(index).map_err(|e| JsValue::from_str(&e))?;
+        self.draw();
+        Ok(())
+    }
+
+    #[wasm_bindgen]
+    pub fn render(&mut self) -> Result<(), JsValue> {
+        self.draw();
+        Ok(())
+    }
+
+    fn draw(&mut self) {
+        let surface_texture = match self.surface.get_current_texture() {
+            Ok(surface_texture) => surface_texture,
+            Err(_) => return,
+        };
+        let view = surface_texture
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+        self.grid
+            .render_to_view(&view, &self.device, &self.queue, &self.visualizer_config);
+        surface_texture.present();
+    }
+}
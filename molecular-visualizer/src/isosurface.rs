@@ -0,0 +1,171 @@
+use shared_lib::cancellation::CancellationToken;
+use shared_lib::isosurface::{Extraction, IsoMesh};
+use shared_lib::types::VolumeCube;
+use wgpu::util::DeviceExt;
+
+use super::core::mesh::{InstanceData, LIGHTING_FLAG, Vertex};
+use super::executor::{Progress, run_chunked};
+use super::types::Color;
+
+// Number of grid cells polygonised between two yields to the browser event loop.
+const CELLS_PER_CHUNK: usize = 65536;
+
+#[derive(Clone, Copy, Debug)]
+pub struct IsosurfaceSettings {
+    /// Lobes are drawn at `+|isovalue|` and `-|isovalue|`.
+    pub isovalue: f64,
+    pub positive_color: Color,
+    pub negative_color: Color,
+    /// Lobes with an opacity below 1 are drawn with the transparent objects.
+    pub opacity: f32,
+}
+
+impl Default for IsosurfaceSettings {
+    fn default() -> Self {
+        Self {
+            isovalue: 0.05,
+            positive_color: Color {
+                r: 0.2,
+                g: 0.4,
+                b: 1.0,
+                a: 1.0,
+            },
+            negative_color: Color {
+                r: 1.0,
+                g: 0.25,
+                b: 0.2,
+                a: 1.0,
+            },
+            opacity: 1.0,
+        }
+    }
+}
+
+impl IsosurfaceSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(self.isovalue.is_finite() && self.isovalue != 0.0) {
+            return Err(format!("Isovalue must be a nonzero number, got {}", self.isovalue));
+        }
+        if !(0.0..=1.0).contains(&self.opacity) {
+            return Err(format!("Opacity must be in range [0, 1], got {}", self.opacity));
+        }
+        Ok(())
+    }
+
+    pub fn is_transparent(&self) -> bool {
+        self.opacity < 1.0
+    }
+}
+
+/// Triangles of one lobe with a single instance placing them at the origin of the molecule.
+pub struct Lobe {
+    pub vertex_buffer: wgpu::Buffer,
+    pub index_buffer: wgpu::Buffer,
+    pub instance_buffer: wgpu::Buffer,
+    pub num_indices: u32,
+}
+
+impl Lobe {
+    // None for a lobe without triangles
+    fn new(device: &wgpu::Device, mesh: &IsoMesh, origin: [f64; 3], color: Color, opacity: f32) -> Option<Self> {
+        if mesh.indices.is_empty() {
+            return None;
+        }
+        // Vertices are relative to the origin of the molecule, like atom positions
+        let vertices: Vec<Vertex> = mesh
+            .positions
+            .iter()
+            .zip(&mesh.normals)
+            .map(|(position, normal)| Vertex {
+                position: std::array::from_fn(|c| (position[c] as f64 - origin[c]) as f32),
+                normal: *normal,
+            })
+            .collect();
+        let instance = InstanceData {
+            rotation: [0.0, 0.0, 0.0, 1.0],
+            position: [0.0; 3],
+            scale: [1.0, 1.0],
+            color: Color { a: opacity, ..color }.to_rgba8(),
+            picking_id: 0,
+            flags: LIGHTING_FLAG,
+        };
+        Some(Self {
+            vertex_buffer: device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Isosurface Vertex Buffer"),
+                contents: bytemuck::cast_slice(&vertices),
+                usage: wgpu::BufferUsages::VERTEX,
+            }),
+            index_buffer: device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Isosurface Index Buffer"),
+                contents: bytemuck::cast_slice(&mesh.indices),
+                usage: wgpu::BufferUsages::INDEX,
+            }),
+            instance_buffer: device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Isosurface Instance Buffer"),
+                contents: bytemuck::bytes_of(&instance),
+                usage: wgpu::BufferUsages::VERTEX,
+            }),
+            num_indices: mesh.indices.len() as u32,
+        })
+    }
+}
+
+/// Positive and negative lobes of a volume cube, e.g. of an orbital. A density has no negative lobe.
+pub struct Isosurface {
+    pub lobes: Vec<Lobe>,
+    pub transparent: bool,
+}
+
+impl Isosurface {
+    pub async fn new(
+        device: &wgpu::Device,
+        cube: &VolumeCube,
+        origin: [f64; 3],
+        settings: &IsosurfaceSettings,
+        progress: &Progress,
+        cancel: &CancellationToken,
+    ) -> Result<Self, String> {
+        settings.validate()?;
+        let positive = extract(cube, settings.isovalue.abs(), progress, cancel).await?;
+        let negative = extract(cube, -settings.isovalue.abs(), progress, cancel).await?;
+        let lobes = [
+            (&positive, settings.positive_color),
+            (&negative, settings.negative_color),
+        ]
+        .into_iter()
+        .filter_map(|(mesh, color)| Lobe::new(device, mesh, origin, color, settings.opacity))
+        .collect();
+        Ok(Self {
+            lobes,
+            transparent: settings.is_transparent(),
+        })
+    }
+
+    pub fn num_triangles(&self) -> u32 {
+        self.lobes.iter().map(|lobe| lobe.num_indices / 3).sum()
+    }
+}
+
+/// Extracts the isosurface in chunks of whole slabs of the grid, so large cubes do not block the page.
+async fn extract(
+    cube: &VolumeCube,
+    isovalue: f64,
+    progress: &Progress,
+    cancel: &CancellationToken,
+) -> Result<IsoMesh, String> {
+    let mut extraction = Extraction::new(cube, isovalue)?;
+    let slabs_per_chunk = CELLS_PER_CHUNK / extraction.slab_cells().max(1);
+    run_chunked(
+        extraction.num_slabs(),
+        slabs_per_chunk,
+        "isosurface",
+        progress,
+        cancel,
+        |slabs| {
+            extraction.extract_slabs(slabs);
+            Ok(())
+        },
+    )
+    .await?;
+    Ok(extraction.finish())
+}
@@ -0,0 +1,391 @@
+use std::collections::{HashMap, HashSet};
+
+use bytemuck::{Pod, Zeroable};
+use shared_lib::connectivity::Bond;
+use wgpu::util::DeviceExt;
+
+use super::atom::Atom;
+use super::config::Label;
+use super::core::{Mat4, Vec3};
+use super::font_atlas::{CELL_HEIGHT, CELL_WIDTH, FontAtlas, GLYPH_HEIGHT, GLYPH_PADDING, GLYPH_WIDTH};
+use super::utils::PickedObject;
+
+// Characters of a typical bond length, e.g. "1.09", labels of bonds are placed at least that wide apart
+const BOND_LENGTH_CHARS: usize = 4;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LabelKind {
+    // Element symbol of the atom
+    Symbol,
+    // Index of the atom, starts from 1
+    Index,
+    // Text set by the host for individual atoms
+    Custom,
+}
+
+impl LabelKind {
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        match name.to_ascii_lowercase().as_str() {
+            "symbol" | "element" => Ok(LabelKind::Symbol),
+            "index" => Ok(LabelKind::Index),
+            "custom" => Ok(LabelKind::Custom),
+            _ => Err(format!("Unknown label type: {}", name)),
+        }
+    }
+}
+
+/// Bonds labeled with their lengths.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BondLabels {
+    None,
+    All,
+    // Only bonds between selected atoms
+    Selected,
+}
+
+impl BondLabels {
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        match name.to_ascii_lowercase().as_str() {
+            "none" | "off" => Ok(BondLabels::None),
+            "all" => Ok(BondLabels::All),
+            "selected" | "selection" => Ok(BondLabels::Selected),
+            _ => Err(format!("Unknown bond labels: {}", name)),
+        }
+    }
+}
+
+/// One character of a label, drawn as a quad facing the camera.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+pub struct GlyphInstance {
+    /// Center of the atom in molecule space.
+    pub anchor: [f32; 3],
+    /// The label is moved towards the camera by this distance so it is not hidden inside the atom.
+    pub lift: f32,
+    /// Bottom left corner of the quad relative to the anchor in view space, in Angstroms.
+    pub offset: [f32; 2],
+    pub size: [f32; 2],
+    pub uv: [f32; 4],
+    pub color: [u8; 4],
+    pub outline_color: [u8; 4],
+}
+
+impl GlyphInstance {
+    pub fn desc() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<GlyphInstance>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &[
+                // Anchor and lift
+                wgpu::VertexAttribute {
+                    offset: 0,
+                    shader_location: 0,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+                // Offset and size
+                wgpu::VertexAttribute {
+                    offset: std::mem::size_of::<[f32; 4]>() as wgpu::BufferAddress,
+                    shader_location: 1,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+                // UV rectangle
+                wgpu::VertexAttribute {
+                    offset: (std::mem::size_of::<[f32; 4]>() * 2) as wgpu::BufferAddress,
+                    shader_location: 2,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+                // Color
+                wgpu::VertexAttribute {
+                    offset: (std::mem::size_of::<[f32; 4]>() * 3) as wgpu::BufferAddress,
+                    shader_location: 3,
+                    format: wgpu::VertexFormat::Unorm8x4,
+                },
+                // Outline color
+                wgpu::VertexAttribute {
+                    offset: (std::mem::size_of::<[f32; 4]>() * 3 + 4) as wgpu::BufferAddress,
+                    shader_location: 4,
+                    format: wgpu::VertexFormat::Unorm8x4,
+                },
+            ],
+        }
+    }
+}
+
+/// Text anchored to a point of molecule space instead of an atom, e.g. the value of a measurement.
+pub struct AnchoredText {
+    pub position: Vec3<f32>,
+    /// Distance towards the camera, so the text is not hidden inside nearby geometry.
+    pub lift: f32,
+    pub text: String,
+}
+
+/// Rectangle of the label of an atom for picking, placed on the screen the same way as its glyphs in labels.wgsl.
+struct Line {
+    anchor: Vec3<f32>,
+    lift: f32,
+    /// Bottom left and top right corners relative to the anchor in view space, in Angstroms.
+    min: [f32; 2],
+    max: [f32; 2],
+    /// Zero-based index of the atom.
+    atom: usize,
+}
+
+/// Texts drawn next to atoms. Each label type is toggled separately,
+/// visible types of one atom are joined into a single line.
+pub struct Labels {
+    symbols: bool,
+    indices: bool,
+    custom: bool,
+    custom_texts: HashMap<usize, String>, // atom (index starts from 1) -> text
+    measurement_texts: Vec<AnchoredText>,
+    bond_labels: BondLabels,
+    // Selected atoms the bond lengths were labeled for with `BondLabels::Selected`
+    labeled_selection: Vec<usize>,
+    lines: Vec<Line>,
+
+    pub instance_buffer: Option<wgpu::Buffer>,
+    pub num_glyphs: u32,
+    dirty: bool,
+}
+
+impl Labels {
+    pub fn new() -> Self {
+        Self {
+            symbols: false,
+            indices: false,
+            custom: true,
+            custom_texts: HashMap::new(),
+            measurement_texts: Vec::new(),
+            bond_labels: BondLabels::None,
+            labeled_selection: Vec::new(),
+            lines: Vec::new(),
+            instance_buffer: None,
+            num_glyphs: 0,
+            dirty: false,
+        }
+    }
+
+    pub fn set_visible(&mut self, kind: LabelKind, visible: bool) {
+        match kind {
+            LabelKind::Symbol => self.symbols = visible,
+            LabelKind::Index => self.indices = visible,
+            LabelKind::Custom => self.custom = visible,
+        }
+        self.dirty = true;
+    }
+
+    pub fn set_custom_text(&mut self, index: usize, text: &str) {
+        if text.is_empty() {
+            self.custom_texts.remove(&index);
+        } else {
+            self.custom_texts.insert(index, text.to_string());
+        }
+        self.dirty = true;
+    }
+
+    pub fn clear_custom_texts(&mut self) {
+        self.custom_texts.clear();
+        self.dirty = true;
+    }
+
+    /// Texts of measurements are always visible, whatever label types are toggled.
+    pub fn set_measurement_texts(&mut self, texts: Vec<AnchoredText>) {
+        self.measurement_texts = texts;
+        self.dirty = true;
+    }
+
+    /// Lengths of bonds are drawn at their middles, not pickable. Labels that would overlap others are left out.
+    pub fn set_bond_labels(&mut self, bond_labels: BondLabels) {
+        self.bond_labels = bond_labels;
+        self.dirty = true;
+    }
+
+    /// Glyphs have to be rebuilt, e.g. after atoms have moved or changed their radii.
+    pub fn invalidate(&mut self) {
+        self.dirty = true;
+    }
+
+    fn text(&self, index: usize, atom: &Atom) -> String {
+        let mut parts = Vec::new();
+        if self.symbols
+            && let Some(symbol) = atom.symbol()
+        {
+            parts.push(symbol);
+        }
+        if self.indices {
+            parts.push(index.to_string());
+        }
+        if self.custom
+            && let Some(text) = self.custom_texts.get(&index)
+        {
+            parts.push(text.clone());
+        }
+        parts.join(" ")
+    }
+
+    /// Rebuilds the glyph instances if labels, atoms or, for lengths of selected bonds, the selection (zero-based
+    /// atoms) have changed since the last call.
+    pub fn update(&mut self, device: &wgpu::Device, atoms: &[Atom], bonds: &[Bond], selected: &[usize], style: &Label) {
+        let selection_changed = self.bond_labels == BondLabels::Selected && self.labeled_selection != selected;
+        if !self.dirty && !selection_changed {
+            return;
+        }
+        self.dirty = false;
+        self.labeled_selection = selected.to_vec();
+
+        let mut glyphs = Vec::new();
+        let mut lines = Vec::new();
+        for (i, atom) in atoms.iter().enumerate().filter(|(_, atom)| atom.visible) {
+            // Highlighted atoms are 15% larger
+            let lift = atom.radius * 1.15;
+            if let Some((min, max)) = push_line(&mut glyphs, atom.position, lift, &self.text(i + 1, atom), style) {
+                lines.push(Line {
+                    anchor: atom.position,
+                    lift,
+                    min,
+                    max,
+                    atom: i,
+                });
+            }
+        }
+        self.push_bond_lengths(&mut glyphs, atoms, bonds, style);
+        // Values of measurements are not pickable, they cover the middle atom of angles
+        for text in &self.measurement_texts {
+            push_line(&mut glyphs, text.position, text.lift, &text.text, style);
+        }
+        self.lines = lines;
+
+        self.num_glyphs = glyphs.len() as u32;
+        self.instance_buffer = if glyphs.is_empty() {
+            None
+        } else {
+            Some(device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Label Glyphs Buffer"),
+                contents: bytemuck::cast_slice(&glyphs),
+                usage: wgpu::BufferUsages::VERTEX,
+            }))
+        };
+    }
+
+    // Lengths at the middles of the bonds between visible atoms, a label is left out if it is closer to a placed
+    // one than its width as it would overlap it when looked at from some side
+    fn push_bond_lengths(&self, glyphs: &mut Vec<GlyphInstance>, atoms: &[Atom], bonds: &[Bond], style: &Label) {
+        let selected: HashSet<usize> = match self.bond_labels {
+            BondLabels::None => return,
+            BondLabels::All => HashSet::new(),
+            BondLabels::Selected => self.labeled_selection.iter().copied().collect(),
+        };
+        let spacing = line_width(BOND_LENGTH_CHARS, style);
+        let cell_of = |p: Vec3<f32>| [p.x, p.y, p.z].map(|x| (x / spacing).floor() as i32);
+        let mut placed: HashMap<[i32; 3], Vec<Vec3<f32>>> = HashMap::new();
+        for bond in bonds {
+            let (atom_1, atom_2) = (&atoms[bond.atom_index_1], &atoms[bond.atom_index_2]);
+            // Bonds across the boundary of the periodic cell are drawn to images of the atoms
+            if bond.image != [0, 0, 0] || !atom_1.visible || !atom_2.visible {
+                continue;
+            }
+            if self.bond_labels == BondLabels::Selected
+                && !(selected.contains(&bond.atom_index_1) && selected.contains(&bond.atom_index_2))
+            {
+                continue;
+            }
+            let middle = (atom_1.position + atom_2.position) * 0.5;
+            let cell = cell_of(middle);
+            let crowded = (-1..=1).any(|x| {
+                (-1..=1).any(|y| {
+                    (-1..=1).any(|z| {
+                        placed
+                            .get(&[cell[0] + x, cell[1] + y, cell[2] + z])
+                            .is_some_and(|points| points.iter().any(|&p| (p - middle).length() < spacing))
+                    })
+                })
+            });
+            if crowded {
+                continue;
+            }
+            placed.entry(cell).or_default().push(middle);
+            // Lifted in front of the atoms, the middle is inside them in space-filling models
+            let lift = atom_1.radius.max(atom_2.radius) * 1.15;
+            let length = (atom_1.position - atom_2.position).length();
+            push_line(glyphs, middle, lift, &format!("{:.2}", length), style);
+        }
+    }
+
+    /// Label of an atom under the point of the screen in normalized device coordinates, the nearest to the camera
+    /// of overlapping ones. Labels are drawn over the molecule, so they are picked before anything else.
+    pub fn pick(
+        &self,
+        point: (f32, f32),
+        projection: &Mat4<f32>,
+        view: &Mat4<f32>,
+        scene: &Mat4<f32>,
+        is_perspective: bool,
+    ) -> Option<PickedObject> {
+        let scene_scale = Vec3::new(scene.data[0], scene.data[1], scene.data[2]).length();
+        let model_view = *view * *scene;
+        let mut nearest: Option<(f32, PickedObject)> = None;
+        for line in &self.lines {
+            let center = model_view.transform_point(line.anchor);
+            let towards_camera = if is_perspective {
+                -center.normalized()
+            } else {
+                Vec3::new(0.0, 0.0, 1.0)
+            };
+            let lifted = center + towards_camera * (line.lift * scene_scale);
+            let corner = |offset: [f32; 2]| {
+                projection.transform_point(lifted + Vec3::new(offset[0], offset[1], 0.0) * scene_scale)
+            };
+            let (min, max) = (corner(line.min), corner(line.max));
+            let inside = (min.x..=max.x).contains(&point.0) && (min.y..=max.y).contains(&point.1);
+            // The camera looks along -Z in view space
+            if inside && nearest.is_none_or(|(z, _)| lifted.z > z) {
+                nearest = Some((lifted.z, PickedObject::Label(line.atom)));
+            }
+        }
+        nearest.map(|(_, target)| target)
+    }
+}
+
+/// Appends glyphs of one line of text centered on the anchor, returns the bottom left and top right corners
+/// of the line relative to the anchor, None for an empty text.
+fn push_line(
+    glyphs: &mut Vec<GlyphInstance>,
+    anchor: Vec3<f32>,
+    lift: f32,
+    text: &str,
+    style: &Label,
+) -> Option<([f32; 2], [f32; 2])> {
+    let count = text.chars().count();
+    if count == 0 {
+        return None;
+    }
+
+    // `style.size` is the height of the glyph, quads also cover the padding of atlas cells
+    let texel = style.size / GLYPH_HEIGHT as f32;
+    let advance = (GLYPH_WIDTH + 1) as f32 * texel;
+    let size = [CELL_WIDTH as f32 * texel, CELL_HEIGHT as f32 * texel];
+    let (color, outline_color) = (style.color.to_rgba8(), style.outline_color.to_rgba8());
+
+    let width = line_width(count, style);
+    let left = -width / 2.0 - GLYPH_PADDING as f32 * texel;
+    let bottom = -style.size / 2.0 - GLYPH_PADDING as f32 * texel;
+    for (j, ch) in text.chars().enumerate() {
+        glyphs.push(GlyphInstance {
+            anchor: [anchor.x, anchor.y, anchor.z],
+            lift,
+            offset: [left + j as f32 * advance, bottom],
+            size,
+            uv: FontAtlas::uv(ch),
+            color,
+            outline_color,
+        });
+    }
+    let right = left + (count - 1) as f32 * advance + size[0];
+    Some(([left, bottom], [right, bottom + size[1]]))
+}
+
+/// Width in Angstroms of a line of `count` characters without the padding of the glyphs.
+fn line_width(count: usize, style: &Label) -> f32 {
+    let texel = style.size / GLYPH_HEIGHT as f32;
+    count as f32 * (GLYPH_WIDTH + 1) as f32 * texel - texel
+}
@@ -1,12 +1,49 @@
+// Only the wasm build exposes the visualizer, natively the crate is compiled for headless render tests
+#![cfg_attr(not(target_arch = "wasm32"), allow(dead_code))]
+
+mod animation;
 mod atom;
+mod autosave;
 mod bond;
-mod bonds;
+mod bvh;
+mod capture;
+mod cell_box;
+mod clash_halos;
+mod color_scheme;
 mod config;
 mod core;
+mod culling;
+#[cfg(feature = "debug-tools")]
+mod debug_tools;
+mod executor;
+mod font_atlas;
+mod grid;
+#[cfg(target_arch = "wasm32")]
+mod grid_visualizer;
+mod isosurface;
+mod labels;
+mod measurement;
 mod molecule;
+mod overlay;
+mod post_process;
+mod presets;
 mod renderer;
+mod report;
 mod scene;
+mod slice;
+mod structure_export;
+mod style_settings;
+mod supercell;
+#[cfg(target_arch = "wasm32")]
+mod thumbnail_renderer;
+mod thumbnails;
 mod types;
 mod utils;
 mod vertex_buffer;
+mod vibration;
+mod view;
+#[cfg(target_arch = "wasm32")]
 mod visualizer;
+
+#[cfg(test)]
+mod render_tests;
@@ -0,0 +1,554 @@
+use std::f64::consts::PI;
+
+use wasm_bindgen::prelude::*;
+use wgpu::util::DeviceExt;
+
+use super::atom::Atom;
+use super::bvh::{Bvh, Hit, Primitive, Ray, Shape, Target};
+use super::config::Measurement;
+use super::core::mesh::{InstanceData, LIGHTING_FLAG};
+use super::core::{Quaternion, Vec3};
+use super::labels::AnchoredText;
+use super::types::Color;
+use super::utils::PickedObject;
+
+// Number of straight pieces of angle arcs
+const ARC_SEGMENTS: usize = 16;
+// Radius of angle arcs relative to the shortest of the measured bonds
+const ARC_RADIUS_FACTOR: f64 = 0.6;
+// Labels are moved towards the camera by this distance, enough to clear bonds of the default thickness
+const LABEL_LIFT: f32 = 0.25;
+// Plane disks extend beyond the farthest fitted atom by this distance in Angstroms
+const PLANE_MARGIN: f64 = 0.5;
+// Half of the thickness of plane disks in Angstroms
+const PLANE_HALF_THICKNESS: f32 = 0.005;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MeasurementKind {
+    Distance,
+    Angle,
+    Dihedral,
+    // Between the normals of two planes, from 0 to 90 degrees
+    PlaneAngle,
+    // Between a bond and its projection onto a plane, from 0 to 90 degrees
+    PlaneBondAngle,
+}
+
+impl MeasurementKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            MeasurementKind::Distance => "distance",
+            MeasurementKind::Angle => "angle",
+            MeasurementKind::Dihedral => "dihedral",
+            MeasurementKind::PlaneAngle => "plane_angle",
+            MeasurementKind::PlaneBondAngle => "plane_bond_angle",
+        }
+    }
+}
+
+/// Distance (Angstroms), angle or dihedral (degrees) between 2, 3 or 4 atoms in the order of selection,
+/// or angle (degrees) between two planes or between a plane and a bond.
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+pub struct MeasurementInfo {
+    kind: MeasurementKind,
+    atoms: Vec<usize>,
+    planes: Vec<usize>,
+    value: f64,
+}
+
+#[wasm_bindgen]
+impl MeasurementInfo {
+    /// "distance", "angle", "dihedral", "plane_angle" or "plane_bond_angle".
+    #[wasm_bindgen(getter)]
+    pub fn kind(&self) -> String {
+        self.kind.name().to_string()
+    }
+
+    /// Measured atoms, indices start from 1. Atoms of the bond for "plane_bond_angle", none for "plane_angle".
+    #[wasm_bindgen(getter)]
+    pub fn atoms(&self) -> Vec<usize> {
+        self.atoms.clone()
+    }
+
+    /// Measured planes, indices start from 1.
+    #[wasm_bindgen(getter)]
+    pub fn planes(&self) -> Vec<usize> {
+        self.planes.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+
+    /// The value with its unit, as shown in the scene.
+    #[wasm_bindgen(getter)]
+    pub fn text(&self) -> String {
+        match self.kind {
+            MeasurementKind::Distance => format!("{:.3} Å", self.value),
+            _ => format!("{:.1}°", self.value),
+        }
+    }
+}
+
+impl MeasurementInfo {
+    /// Measures 2 to 4 atoms (zero-based indices), other numbers of atoms have no measurement.
+    pub fn new(atoms: &[Atom], indices: &[usize]) -> Option<Self> {
+        let p = positions(atoms, indices);
+        let (kind, value) = match p.len() {
+            2 => (MeasurementKind::Distance, (p[1] - p[0]).length()),
+            3 => (
+                MeasurementKind::Angle,
+                angle_between(p[0] - p[1], p[2] - p[1]).to_degrees(),
+            ),
+            4 => (MeasurementKind::Dihedral, dihedral(p[0], p[1], p[2], p[3]).to_degrees()),
+            _ => return None,
+        };
+        Some(Self {
+            kind,
+            atoms: indices.iter().map(|i| i + 1).collect(),
+            planes: Vec::new(),
+            value,
+        })
+    }
+}
+
+/// Least-squares plane through atoms, e.g. of an aromatic ring.
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+pub struct PlaneInfo {
+    atoms: Vec<usize>,
+    center: Vec3<f64>,
+    normal: Vec3<f64>,
+    // Distance from the center to the farthest fitted atom
+    size: f64,
+    rms_deviation: f64,
+}
+
+#[wasm_bindgen]
+impl PlaneInfo {
+    /// Fitted atoms, indices start from 1.
+    #[wasm_bindgen(getter)]
+    pub fn atoms(&self) -> Vec<usize> {
+        self.atoms.clone()
+    }
+
+    /// Unit normal (x, y, z).
+    #[wasm_bindgen(getter)]
+    pub fn normal(&self) -> Vec<f64> {
+        vec![self.normal.x, self.normal.y, self.normal.z]
+    }
+
+    /// Root mean square distance of the fitted atoms from the plane in Angstroms.
+    #[wasm_bindgen(getter)]
+    pub fn rms_deviation(&self) -> f64 {
+        self.rms_deviation
+    }
+}
+
+impl PlaneInfo {
+    /// Fits a plane through at least 3 atoms (zero-based indices) that are not on one line.
+    pub fn fit(atoms: &[Atom], indices: &[usize]) -> Result<Self, String> {
+        if indices.len() < 3 {
+            return Err(format!("A plane needs at least 3 atoms, got {}", indices.len()));
+        }
+        let p = positions(atoms, indices);
+        let center = p.iter().fold(Vec3::new(0.0, 0.0, 0.0), |sum, &v| sum + v) / p.len() as f64;
+
+        let mut covariance = [[0.0; 3]; 3];
+        for v in &p {
+            let d = [v.x - center.x, v.y - center.y, v.z - center.z];
+            for i in 0..3 {
+                for j in 0..3 {
+                    covariance[i][j] += d[i] * d[j];
+                }
+            }
+        }
+        // The normal is the direction of the least spread of the atoms
+        let normal = smallest_eigenvector(&covariance).ok_or_else(|| "The atoms are on one line".to_string())?;
+
+        let distances = p.iter().map(|&v| Vec3::dot_product(v - center, normal));
+        let rms_deviation = (distances.map(|d| d * d).sum::<f64>() / p.len() as f64).sqrt();
+        let size = p.iter().map(|&v| (v - center).length()).fold(0.0, f64::max);
+        Ok(Self {
+            atoms: indices.iter().map(|i| i + 1).collect(),
+            center,
+            normal,
+            size,
+            rms_deviation,
+        })
+    }
+}
+
+/// Unit eigenvector of the smallest eigenvalue of a symmetric 3×3 matrix, `None` if the eigenvalue is not unique.
+fn smallest_eigenvector(m: &[[f64; 3]; 3]) -> Option<Vec3<f64>> {
+    // Eigenvalues from the trigonometric solution of the characteristic equation
+    let off_diagonal = m[0][1] * m[0][1] + m[0][2] * m[0][2] + m[1][2] * m[1][2];
+    let q = (m[0][0] + m[1][1] + m[2][2]) / 3.0;
+    let p = (((m[0][0] - q).powi(2) + (m[1][1] - q).powi(2) + (m[2][2] - q).powi(2) + 2.0 * off_diagonal) / 6.0).sqrt();
+    if p == 0.0 {
+        return None;
+    }
+    let b = |i: usize, j: usize| (m[i][j] - if i == j { q } else { 0.0 }) / p;
+    let det = b(0, 0) * (b(1, 1) * b(2, 2) - b(1, 2) * b(2, 1)) - b(0, 1) * (b(1, 0) * b(2, 2) - b(1, 2) * b(2, 0))
+        + b(0, 2) * (b(1, 0) * b(2, 1) - b(1, 1) * b(2, 0));
+    let phi = (det / 2.0).clamp(-1.0, 1.0).acos() / 3.0;
+    let smallest = q + 2.0 * p * (phi + 2.0 * PI / 3.0).cos();
+
+    // The eigenvector is orthogonal to the rows of m - λI, the best conditioned cross product of them is used
+    let shifted = |i: usize, j: usize| m[i][j] - if i == j { smallest } else { 0.0 };
+    let rows = [0, 1, 2].map(|i| Vec3::new(shifted(i, 0), shifted(i, 1), shifted(i, 2)));
+    let candidate = [(0, 1), (0, 2), (1, 2)]
+        .map(|(i, j)| Vec3::cross_product(rows[i], rows[j]))
+        .into_iter()
+        .max_by(|a, b| a.length_squared().total_cmp(&b.length_squared()))?;
+    // Rows of a matrix of rank 1 (atoms on one line) have no defined cross product
+    if candidate.length_squared() <= 1e-12 * p.powi(4) {
+        return None;
+    }
+    Some(candidate.normalized())
+}
+
+pub fn positions(atoms: &[Atom], indices: &[usize]) -> Vec<Vec3<f64>> {
+    indices
+        .iter()
+        .map(|&i| {
+            let p = atoms[i].position;
+            Vec3::new(p.x as f64, p.y as f64, p.z as f64)
+        })
+        .collect()
+}
+
+/// Angle between the vectors in radians.
+pub fn angle_between(a: Vec3<f64>, b: Vec3<f64>) -> f64 {
+    let cos = Vec3::dot_product(a, b) / (a.length() * b.length());
+    cos.clamp(-1.0, 1.0).acos()
+}
+
+/// Signed torsion angle (IUPAC convention) in radians.
+pub fn dihedral(p0: Vec3<f64>, p1: Vec3<f64>, p2: Vec3<f64>, p3: Vec3<f64>) -> f64 {
+    let (b1, b2, b3) = (p1 - p0, p2 - p1, p3 - p2);
+    let n1 = Vec3::cross_product(b1, b2);
+    let n2 = Vec3::cross_product(b2, b3);
+    let y = b2.length() * Vec3::dot_product(b1, n2);
+    let x = Vec3::dot_product(n1, n2);
+    y.atan2(x)
+}
+
+/// Lines between the measured atoms, the arc of the angle and the label with the value.
+/// Fitted planes are drawn as translucent disks.
+pub struct MeasurementOverlay {
+    info: Option<MeasurementInfo>,
+    planes: Vec<PlaneInfo>,
+    pub instance_buffer: Option<wgpu::Buffer>,
+    pub num_instances: u32,
+    pub planes_instance_buffer: Option<wgpu::Buffer>,
+    pub num_plane_instances: u32,
+    // Lines, arcs and disks for CPU picking, built with the instances
+    bvh: Bvh,
+    dirty: bool,
+}
+
+impl MeasurementOverlay {
+    pub fn new() -> Self {
+        Self {
+            info: None,
+            planes: Vec::new(),
+            instance_buffer: None,
+            num_instances: 0,
+            planes_instance_buffer: None,
+            num_plane_instances: 0,
+            bvh: Bvh::new(Vec::new()),
+            dirty: false,
+        }
+    }
+
+    pub fn info(&self) -> Option<&MeasurementInfo> {
+        self.info.as_ref()
+    }
+
+    /// Plane with the index starting from 1.
+    pub fn plane(&self, index: usize) -> Result<&PlaneInfo, String> {
+        match index {
+            1.. if index <= self.planes.len() => Ok(&self.planes[index - 1]),
+            _ => Err(format!(
+                "Plane index {} is out of range 1..={}",
+                index,
+                self.planes.len()
+            )),
+        }
+    }
+
+    /// Fits a plane through the atoms (zero-based indices), returns its index starting from 1.
+    pub fn add_plane(&mut self, atoms: &[Atom], indices: &[usize]) -> Result<usize, String> {
+        self.planes.push(PlaneInfo::fit(atoms, indices)?);
+        self.dirty = true;
+        Ok(self.planes.len())
+    }
+
+    /// Removes all planes, returns whether a measurement of them was removed too.
+    pub fn clear_planes(&mut self) -> bool {
+        self.planes.clear();
+        self.dirty = true;
+        let measured = self.info.as_ref().is_some_and(|info| !info.planes.is_empty());
+        if measured {
+            self.info = None;
+        }
+        measured
+    }
+
+    /// Measures the angle between two planes (indices start from 1), returns the label of the value.
+    pub fn measure_planes(&mut self, plane_1: usize, plane_2: usize) -> Result<AnchoredText, String> {
+        let (a, b) = (self.plane(plane_1)?, self.plane(plane_2)?);
+        let cos = Vec3::dot_product(a.normal, b.normal).abs();
+        let position = (a.center + b.center) / 2.0;
+        self.set_info(
+            MeasurementInfo {
+                kind: MeasurementKind::PlaneAngle,
+                atoms: Vec::new(),
+                planes: vec![plane_1, plane_2],
+                value: cos.clamp(0.0, 1.0).acos().to_degrees(),
+            },
+            position,
+        )
+    }
+
+    /// Measures the angle between the plane (index starts from 1) and the bond between two atoms
+    /// (zero-based indices), returns the label of the value.
+    pub fn measure_plane_bond(
+        &mut self,
+        atoms: &[Atom],
+        plane: usize,
+        atom_1: usize,
+        atom_2: usize,
+    ) -> Result<AnchoredText, String> {
+        let normal = self.plane(plane)?.normal;
+        let p = positions(atoms, &[atom_1, atom_2]);
+        let bond = p[1] - p[0];
+        if bond.length() == 0.0 {
+            return Err("The atoms of the bond are at the same position".to_string());
+        }
+        let sin = Vec3::dot_product(bond.normalized(), normal).abs();
+        self.set_info(
+            MeasurementInfo {
+                kind: MeasurementKind::PlaneBondAngle,
+                atoms: vec![atom_1 + 1, atom_2 + 1],
+                planes: vec![plane],
+                value: sin.clamp(0.0, 1.0).asin().to_degrees(),
+            },
+            (p[0] + p[1]) / 2.0,
+        )
+    }
+
+    fn set_info(&mut self, info: MeasurementInfo, position: Vec3<f64>) -> Result<AnchoredText, String> {
+        let text = info.text();
+        self.info = Some(info);
+        self.dirty = true;
+        Ok(AnchoredText {
+            position: Vec3::new(position.x as f32, position.y as f32, position.z as f32),
+            lift: LABEL_LIFT,
+            text,
+        })
+    }
+
+    /// Measures the selected atoms (zero-based, in the order of selection), returns the label of the value.
+    pub fn measure(&mut self, atoms: &[Atom], selected: &[usize]) -> Option<AnchoredText> {
+        self.info = MeasurementInfo::new(atoms, selected);
+        self.dirty = true;
+
+        let info = self.info.as_ref()?;
+        let p = positions(atoms, selected);
+        let position = match info.kind {
+            MeasurementKind::Distance => (p[0] + p[1]) / 2.0,
+            // Beyond the arc on the bisector of the angle
+            MeasurementKind::Angle => {
+                let bisector = ((p[0] - p[1]).normalized() + (p[2] - p[1]).normalized()).normalized();
+                p[1] + bisector * (arc_radius(&p) * 1.3)
+            }
+            MeasurementKind::Dihedral => (p[1] + p[2]) / 2.0,
+            MeasurementKind::PlaneAngle | MeasurementKind::PlaneBondAngle => return None,
+        };
+        Some(AnchoredText {
+            position: Vec3::new(position.x as f32, position.y as f32, position.z as f32),
+            lift: LABEL_LIFT,
+            text: info.text(),
+        })
+    }
+
+    /// Lines and arcs have to be rebuilt, e.g. after switching between ray casting and meshes.
+    pub fn invalidate(&mut self) {
+        self.dirty = true;
+    }
+
+    /// Rebuilds the instances of lines and arcs if the measurement has changed since the last call.
+    pub fn update(&mut self, device: &wgpu::Device, atoms: &[Atom], ray_casting: bool, style: &Measurement) {
+        if !self.dirty {
+            return;
+        }
+        self.dirty = false;
+
+        let mut segments = Vec::new();
+        if let Some(info) = &self.info {
+            let indices: Vec<usize> = info.atoms.iter().map(|i| i - 1).collect();
+            let p = positions(atoms, &indices);
+            for pair in p.windows(2) {
+                segments.push((pair[0], pair[1]));
+            }
+            match info.kind {
+                MeasurementKind::Distance | MeasurementKind::PlaneBondAngle => {}
+                // Between the centers of the planes
+                MeasurementKind::PlaneAngle => {
+                    let centers: Vec<Vec3<f64>> = info.planes.iter().map(|&i| self.planes[i - 1].center).collect();
+                    segments.push((centers[0], centers[1]));
+                }
+                MeasurementKind::Angle => push_arc(&mut segments, p[1], p[0] - p[1], p[2] - p[1], arc_radius(&p)),
+                // Between the outer bonds projected onto the plane perpendicular to the central one
+                MeasurementKind::Dihedral => {
+                    let axis = (p[2] - p[1]).normalized();
+                    let project = |v: Vec3<f64>| v - axis * Vec3::dot_product(v, axis);
+                    let center = (p[1] + p[2]) / 2.0;
+                    push_arc(
+                        &mut segments,
+                        center,
+                        project(p[0] - p[1]),
+                        project(p[3] - p[2]),
+                        arc_radius(&p),
+                    );
+                }
+            }
+        }
+
+        let picking_id = PickedObject::Measurement.picking_id();
+        let instances: Vec<InstanceData> = segments
+            .iter()
+            .map(|&(start, end)| InstanceData {
+                picking_id,
+                ..segment_instance(start, end, ray_casting, style.thickness, style.color)
+            })
+            .collect();
+        self.num_instances = instances.len() as u32;
+        self.instance_buffer = if instances.is_empty() {
+            None
+        } else {
+            Some(device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Measurement Instance Buffer"),
+                contents: bytemuck::cast_slice(&instances),
+                usage: wgpu::BufferUsages::VERTEX,
+            }))
+        };
+
+        let disks: Vec<InstanceData> = self
+            .planes
+            .iter()
+            .enumerate()
+            .map(|(i, plane)| plane_instance(i, plane, style))
+            .collect();
+        self.num_plane_instances = disks.len() as u32;
+        self.planes_instance_buffer = if disks.is_empty() {
+            None
+        } else {
+            Some(device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Measurement Planes Instance Buffer"),
+                contents: bytemuck::cast_slice(&disks),
+                usage: wgpu::BufferUsages::VERTEX,
+            }))
+        };
+
+        let to_f32 = |v: Vec3<f64>| Vec3::new(v.x as f32, v.y as f32, v.z as f32);
+        let lines = segments.iter().map(|&(start, end)| Primitive {
+            shape: Shape::Cylinder {
+                center: to_f32((start + end) / 2.0),
+                axis: to_f32(end - start).normalized(),
+                half_length: (end - start).length() as f32 / 2.0,
+                radius: style.thickness,
+            },
+            target: Target::Measurement,
+        });
+        let disks = self.planes.iter().enumerate().map(|(i, plane)| Primitive {
+            shape: Shape::Disk {
+                center: to_f32(plane.center),
+                normal: to_f32(plane.normal),
+                radius: (plane.size + PLANE_MARGIN) as f32,
+            },
+            target: Target::Plane(i),
+        });
+        self.bvh = Bvh::new(lines.chain(disks).collect());
+    }
+
+    /// CPU alternative to the picking pass: the nearest line, arc or plane disk hit by the ray in molecule space.
+    /// The overlay is picked as it was at the last update.
+    pub fn pick(&self, ray: &Ray) -> Option<Hit> {
+        self.bvh.intersect(ray)
+    }
+}
+
+fn arc_radius(p: &[Vec3<f64>]) -> f64 {
+    let shortest = p
+        .windows(2)
+        .map(|pair| (pair[1] - pair[0]).length())
+        .fold(f64::MAX, f64::min);
+    shortest * ARC_RADIUS_FACTOR
+}
+
+/// Arc around the center from direction `from` to direction `to`, nothing if they are (anti)parallel.
+fn push_arc(
+    segments: &mut Vec<(Vec3<f64>, Vec3<f64>)>,
+    center: Vec3<f64>,
+    from: Vec3<f64>,
+    to: Vec3<f64>,
+    radius: f64,
+) {
+    let angle = angle_between(from, to);
+    if !angle.is_finite() || angle < 1e-3 || PI - angle < 1e-3 {
+        return;
+    }
+
+    // Orthonormal basis of the plane of the arc
+    let u = from.normalized();
+    let w = (to - u * Vec3::dot_product(to, u)).normalized();
+    let point = |t: f64| center + (u * (angle * t).cos() + w * (angle * t).sin()) * radius;
+    for i in 0..ARC_SEGMENTS {
+        let (t0, t1) = (i as f64 / ARC_SEGMENTS as f64, (i + 1) as f64 / ARC_SEGMENTS as f64);
+        segments.push((point(t0), point(t1)));
+    }
+}
+
+/// Unlit cylinder from start to end, with the same instance layout as bonds.
+pub fn segment_instance(
+    start: Vec3<f64>,
+    end: Vec3<f64>,
+    ray_casting: bool,
+    thickness: f32,
+    color: Color,
+) -> InstanceData {
+    let to_f32 = |v: Vec3<f64>| Vec3::new(v.x as f32, v.y as f32, v.z as f32);
+    let (start, end) = (to_f32(start), to_f32(end));
+    let center = (start + end) / 2.0;
+    let rotation = Quaternion::rotation_to(Vec3::new(0.0, 0.0, 1.0), (end - start).normalized());
+
+    InstanceData {
+        rotation: [rotation.x, rotation.y, rotation.z, rotation.w],
+        position: [center.x, center.y, center.z],
+        scale: [thickness, (end - start).length() / 2.0],
+        color: color.to_rgba8(),
+        picking_id: 0,
+        flags: if ray_casting { 2 } else { 0 },
+    }
+}
+
+/// Flat lit cylinder mesh (never ray casted) covering the fitted atoms of the plane.
+fn plane_instance(index: usize, plane: &PlaneInfo, style: &Measurement) -> InstanceData {
+    let to_f32 = |v: Vec3<f64>| Vec3::new(v.x as f32, v.y as f32, v.z as f32);
+    let rotation = Quaternion::rotation_to(Vec3::new(0.0, 0.0, 1.0), to_f32(plane.normal));
+    let center = to_f32(plane.center);
+
+    InstanceData {
+        rotation: [rotation.x, rotation.y, rotation.z, rotation.w],
+        position: [center.x, center.y, center.z],
+        scale: [(plane.size + PLANE_MARGIN) as f32, PLANE_HALF_THICKNESS],
+        color: style.plane_color.to_rgba8(),
+        picking_id: PickedObject::Plane(index).picking_id(),
+        flags: LIGHTING_FLAG,
+    }
+}
@@ -1,34 +1,85 @@
-use std::collections::HashSet;
+use std::collections::BTreeMap;
 
-use shared_lib::periodic_table::get_element_by_number;
-use shared_lib::types::AtomicCoordinates;
+use bytemuck::Zeroable;
+use shared_lib::cancellation::CancellationToken;
+use shared_lib::cell::UnitCell;
+use shared_lib::connectivity;
+use shared_lib::neighbors::NeighborGrid;
+use shared_lib::periodic_table::{get_isotope_mass, get_vdw_radius, is_pseudo_element};
+use shared_lib::types::{AtomicCoordinates, Connection};
 use wgpu::util::DeviceExt;
 
-use super::atom::{Atom, AtomInfo};
+use super::atom::{Atom, AtomInfo, AtomStyle};
 use super::bond::Bond;
-use super::bonds;
-use super::config::Config;
+use super::bvh::{Bvh, Hit, Primitive, Ray, Shape, Target};
+use super::color_scheme::{AtomProperties, ColorScheme};
+use super::config::{Config, Representation, Style};
 use super::core::mesh::InstanceData;
-use super::core::{Mat4, Vec3};
-use super::types::Color;
-use super::utils::id_to_color;
+use super::core::{Quaternion, Vec3};
+use super::executor::{DEFAULT_CHUNK_SIZE, Progress, map_parallel, run_chunked};
+use super::types::{Color, Dimming};
 
 pub struct Molecule {
     atoms: Vec<Atom>,
     bonds: Vec<Bond>,
+    // Topology of the bonds, the cylinders in `bonds` are rebuilt from it when the representation changes
+    bonds_list: Vec<connectivity::Bond>,
+    neighbors: Vec<Vec<usize>>,
+    // Charges and chains for the color schemes
+    properties: AtomProperties,
+    // Periodic cell, bonds through its boundaries are drawn as halves towards the images of the atoms
+    cell: Option<UnitCell>,
+    // Radii and colors of single atoms (zero-based) overriding the style, kept when the style changes
+    atom_styles: BTreeMap<usize, AtomStyle>,
 
     pub radius: f32,
-    pub transform: Mat4<f32>,
+    // Mean radius of atoms, used to estimate their size on screen
+    pub atom_radius: f32,
+    // Center of the molecule in double precision. Atom positions are stored relative to it,
+    // so f32 instance matrices stay precise for structures far from the coordinate origin.
+    pub origin: [f64; 3],
     pub atoms_instance_buffer: wgpu::Buffer,
     pub atom_selections_instance_buffer: wgpu::Buffer,
     pub bonds_instance_buffer: wgpu::Buffer,
+    // Atoms and bonds are ray casted impostors on cubes, otherwise sphere and cylinder meshes
+    ray_casting: bool,
 
     highlighted_atom: usize, // atom (index starts from 1) under cursor, 0 = no atoms under cursor
-    selected_atoms: HashSet<usize>,
+    selected_atoms: Vec<usize>, // zero-based, in the order of selection
+    bvh: Option<Bvh>,        // built on the first CPU picking request
+    // Highlighting and selection changes not yet written to the instance buffers
+    dirty: DirtyInstances,
+    // Slots of the selection instance buffer, it grows like a vector as atoms are selected
+    selections_capacity: usize,
+    // Incremented when positions, sizes, colors or visibility of the instances change, not on highlighting
+    // and selection, so that copies of the instances know when to follow
+    revision: u64,
+}
+
+/// Instances changed by highlighting and selection since they were written to the GPU. Only these are written
+/// before the next frame, at their offsets in the buffers, instead of rebuilding complete buffers.
+#[derive(Default)]
+struct DirtyInstances {
+    // Zero-based atoms whose instances changed
+    atoms: Vec<usize>,
+    // First slot of the selection instances to write, the following slots may have shifted
+    selections_from: Option<usize>,
+}
+
+impl DirtyInstances {
+    fn selections_from(&mut self, slot: usize) {
+        self.selections_from = Some(self.selections_from.map_or(slot, |from| from.min(slot)));
+    }
 }
 
 impl Molecule {
-    pub fn new(device: &wgpu::Device, config: &Config, atomic_coordinates: &AtomicCoordinates) -> Result<Self, String> {
+    pub async fn new(
+        device: &wgpu::Device,
+        config: &Config,
+        atomic_coordinates: &AtomicCoordinates,
+        progress: &Progress,
+        cancel: &CancellationToken,
+    ) -> Result<Self, String> {
         let mut radius: f32 = 0.0;
         let num_atoms = atomic_coordinates.atomic_num.len();
 
@@ -36,98 +87,149 @@ impl Molecule {
         let y = atomic_coordinates.y.iter().sum::<f64>();
         let z = atomic_coordinates.z.iter().sum::<f64>();
 
-        let center = Vec3::new(
-            x as f32 / num_atoms as f32,
-            y as f32 / num_atoms as f32,
-            z as f32 / num_atoms as f32,
-        );
+        // A structure without atoms, e.g. under a volume cube opened on its own, stays at the coordinate origin
+        let n = num_atoms.max(1) as f64;
+        let origin = [x / n, y / n, z / n];
 
-        let mut transform = Mat4::new();
-        transform.translate(-center);
-
-        let mut atoms = Vec::new();
-        let atoms_style = &config.style.atoms;
-        for i in 0..num_atoms {
-            let atom = atoms_style.get(&atomic_coordinates.atomic_num[i]).ok_or(format!(
-                "Atom not found for atomic number: {}",
-                atomic_coordinates.atomic_num[i]
-            ))?;
-
-            let position = Vec3::new(
-                atomic_coordinates.x[i] as f32,
-                atomic_coordinates.y[i] as f32,
-                atomic_coordinates.z[i] as f32,
-            );
+        let mass_numbers = atomic_coordinates.mass_numbers()?;
+        let properties = AtomProperties::new(atomic_coordinates);
+        let colors =
+            config
+                .style
+                .color_scheme
+                .atom_colors(&config.style, &atomic_coordinates.atomic_num, &properties)?;
 
-            radius = radius.max((position - center).length_squared() + atom.radius);
-
-            atoms.push(Atom::new(
-                atomic_coordinates.atomic_num[i],
-                position,
-                atom.radius,
-                atom.color,
-                id_to_color(i + 1),
-                config.style.selected_atom.color,
-                config.style.selected_atom.scale_factor,
-            ));
-        }
-
-        let bond_thickness = config.style.bond.thickness;
-        let mut bonds = Vec::new();
-        let bonds_list = bonds::build(atomic_coordinates, config.style.geom_bond_tolerance);
-        for bond in bonds_list {
-            let atom_1 = &atoms[bond.atom_index_1];
-            let atom_2 = &atoms[bond.atom_index_2];
-
-            let computed_bonds = get_bonds(
-                atom_1.position,
-                atom_1.radius,
-                atom_1.color,
-                atom_2.position,
-                atom_2.radius,
-                atom_2.color,
-            );
+        let mut atoms = Vec::with_capacity(num_atoms);
+        run_chunked(num_atoms, DEFAULT_CHUNK_SIZE, "atoms", progress, cancel, |range| {
+            let parts = map_parallel(range, |part| build_atoms(config, atomic_coordinates, &mass_numbers, origin, &colors, part));
+            for part in parts {
+                let (part_atoms, part_radius) = part?;
+                atoms.extend(part_atoms);
+                radius = radius.max(part_radius);
+            }
+            Ok(())
+        })
+        .await?;
 
-            for b in computed_bonds {
-                bonds.push(Bond::new(b.0, b.1, bond_thickness, b.2, b.3));
+        let bonds_list = match &atomic_coordinates.connectivity {
+            Some(connections) => connectivity::from_connectivity(num_atoms, connections),
+            None => {
+                let search = connectivity::prepare(
+                    atomic_coordinates,
+                    config.style.geom_bond_tolerance,
+                    atomic_coordinates.cell.as_ref(),
+                );
+                let mut bonds_list = Vec::new();
+                run_chunked(
+                    search.num_atoms(),
+                    DEFAULT_CHUNK_SIZE,
+                    "bonds",
+                    progress,
+                    cancel,
+                    |range| {
+                        let parts = map_parallel(range, |part| {
+                            let mut bonds = Vec::new();
+                            connectivity::search_range(&search, part, &mut bonds);
+                            bonds
+                        });
+                        parts.into_iter().for_each(|part| bonds_list.extend(part));
+                        Ok(())
+                    },
+                )
+                .await?;
+                bonds_list
             }
-        }
+        };
+        let neighbors = connectivity::neighbors(num_atoms, &bonds_list);
 
+        let mut bonds = Vec::with_capacity(bonds_list.len() * 2);
+        run_chunked(
+            bonds_list.len(),
+            DEFAULT_CHUNK_SIZE,
+            "bond geometry",
+            progress,
+            cancel,
+            |range| {
+                let cell = atomic_coordinates.cell.as_ref();
+                let parts = map_parallel(range, |part| {
+                    let mut cylinders = Vec::new();
+                    let first = part.start;
+                    bond_cylinders(&atoms, &bonds_list[part], first, &neighbors, cell, &config.style, &mut cylinders);
+                    cylinders
+                });
+                parts.into_iter().for_each(|part| bonds.extend(part));
+                Ok(())
+            },
+        )
+        .await?;
+
+        let atom_radius = mean_radius(&atoms);
+        let ray_casting = config.style.geometry.ray_casting;
         let (atoms_instance_buffer, atom_selections_instance_buffer) =
-            Self::create_atoms_instance_buffers(&atoms, device);
+            Self::create_atoms_instance_buffers(&atoms, &[], ray_casting, device);
 
         Ok(Self {
             atoms_instance_buffer: atoms_instance_buffer,
-            bonds_instance_buffer: Self::create_bonds_instance_buffer(&bonds, device),
+            bonds_instance_buffer: Self::create_bonds_instance_buffer(&bonds, ray_casting, device),
             atom_selections_instance_buffer: atom_selections_instance_buffer,
             atoms,
             bonds,
+            bonds_list,
+            neighbors,
+            properties,
+            cell: atomic_coordinates.cell,
+            atom_styles: BTreeMap::new(),
             radius: radius.sqrt(),
-            transform,
+            atom_radius,
+            origin,
+            ray_casting,
             highlighted_atom: 0,
-            selected_atoms: HashSet::new(),
+            selected_atoms: Vec::new(),
+            bvh: None,
+            dirty: DirtyInstances::default(),
+            selections_capacity: 0,
+            revision: 0,
         })
     }
 
     fn create_instance_buffer(data: &Vec<InstanceData>, device: &wgpu::Device) -> wgpu::Buffer {
+        // Flags are updated in place, the culling compute pass reads instances as a storage buffer
+        let mut usage = wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST;
+        if device.limits().max_storage_buffers_per_shader_stage > 0 {
+            usage |= wgpu::BufferUsages::STORAGE;
+        }
         device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Instance Buffer"),
             contents: bytemuck::cast_slice(&data),
-            usage: wgpu::BufferUsages::VERTEX,
+            usage,
         })
     }
 
-    fn create_atoms_instance_buffers(atoms: &Vec<Atom>, device: &wgpu::Device) -> (wgpu::Buffer, wgpu::Buffer) {
-        let mut atoms_data: Vec<InstanceData> = Vec::new();
-        let mut spheres_data: Vec<InstanceData> = Vec::new();
-        for atom in atoms {
-            if atom.visible {
-                atoms_data.push(atom.get_instance_data(false));
-                if atom.selected {
-                    spheres_data.push(atom.get_instance_data(true));
-                }
-            }
-        }
+    fn write_instance_flags(queue: &wgpu::Queue, buffer: &wgpu::Buffer, slot: usize, flags: u32) {
+        let offset = slot * std::mem::size_of::<InstanceData>() + std::mem::offset_of!(InstanceData, flags);
+        queue.write_buffer(buffer, offset as wgpu::BufferAddress, &flags.to_le_bytes());
+    }
+
+    /// Instance buffers of the atoms and of the selection spheres of `selected` atoms in the order of selection.
+    fn create_atoms_instance_buffers(
+        atoms: &[Atom],
+        selected: &[usize],
+        ray_casting: bool,
+        device: &wgpu::Device,
+    ) -> (wgpu::Buffer, wgpu::Buffer) {
+        let mut atoms_data: Vec<InstanceData> = Vec::with_capacity(atoms.len());
+        // Hidden atoms keep their slots, so instance indices match atom indices
+        let parts = map_parallel(0..atoms.len(), |part| {
+            atoms[part]
+                .iter()
+                .map(|atom| atom.get_instance_data(false, ray_casting))
+                .collect::<Vec<InstanceData>>()
+        });
+        parts.into_iter().for_each(|part| atoms_data.extend(part));
+        let spheres_data: Vec<InstanceData> = selected
+            .iter()
+            .map(|&i| atoms[i].get_instance_data(true, ray_casting))
+            .collect();
 
         (
             Self::create_instance_buffer(&atoms_data, device),
@@ -135,17 +237,456 @@ impl Molecule {
         )
     }
 
-    fn create_bonds_instance_buffer(bonds: &Vec<Bond>, device: &wgpu::Device) -> wgpu::Buffer {
+    fn create_bonds_instance_buffer(bonds: &Vec<Bond>, ray_casting: bool, device: &wgpu::Device) -> wgpu::Buffer {
         Self::create_instance_buffer(
-            &bonds
-                .iter()
-                .filter(|item| item.visible)
-                .map(|item| item.get_instance_data())
-                .collect(),
+            &bonds.iter().map(|item| item.get_instance_data(ray_casting)).collect(),
             device,
         )
     }
 
+    pub fn is_ray_casting(&self) -> bool {
+        self.ray_casting
+    }
+
+    pub fn set_ray_casting(&mut self, ray_casting: bool, device: &wgpu::Device) {
+        if self.ray_casting == ray_casting {
+            return;
+        }
+        self.ray_casting = ray_casting;
+        self.rebuild_atom_instances(device);
+        self.bonds_instance_buffer = Self::create_bonds_instance_buffer(&self.bonds, ray_casting, device);
+        self.revision += 1;
+    }
+
+    /// Applies the representation of the style to the atoms and rebuilds the bond cylinders.
+    pub fn set_representation(&mut self, style: &Style, device: &wgpu::Device) {
+        for atom in &mut self.atoms {
+            let radius = style.atom(atom.number).map_or(atom.radius, |a| a.radius);
+            atom.radius = representation_radius(style, atom.number, radius);
+        }
+        self.apply_atom_styles();
+        self.atom_radius = mean_radius(&self.atoms);
+
+        self.bonds.clear();
+        bond_cylinders(
+            &self.atoms,
+            &self.bonds_list,
+            0,
+            &self.neighbors,
+            self.cell.as_ref(),
+            style,
+            &mut self.bonds,
+        );
+        self.bvh = None;
+
+        self.rebuild_atom_instances(device);
+        self.bonds_instance_buffer = Self::create_bonds_instance_buffer(&self.bonds, self.ray_casting, device);
+        self.revision += 1;
+    }
+
+    /// Searches bonds again with the tolerance of the style. Bonds given by the structure are kept.
+    pub fn set_bond_tolerance(&mut self, data: &AtomicCoordinates, style: &Style, device: &wgpu::Device) {
+        if data.connectivity.is_some() {
+            return;
+        }
+        let search = connectivity::prepare(data, style.geom_bond_tolerance, self.cell.as_ref());
+        self.bonds_list.clear();
+        connectivity::search_range(&search, 0..search.num_atoms(), &mut self.bonds_list);
+        self.neighbors = connectivity::neighbors(self.atoms.len(), &self.bonds_list);
+
+        self.bonds.clear();
+        bond_cylinders(
+            &self.atoms,
+            &self.bonds_list,
+            0,
+            &self.neighbors,
+            self.cell.as_ref(),
+            style,
+            &mut self.bonds,
+        );
+        self.bvh = None;
+        self.bonds_instance_buffer = Self::create_bonds_instance_buffer(&self.bonds, self.ray_casting, device);
+        self.revision += 1;
+    }
+
+    /// Moves the atoms, e.g. to a frame of an animation. Bonds keep the topology of the structure.
+    pub fn set_positions(&mut self, positions: &[Vec3<f32>], style: &Style, device: &wgpu::Device) {
+        for (atom, &position) in self.atoms.iter_mut().zip(positions) {
+            atom.position = position;
+        }
+
+        self.bonds.clear();
+        bond_cylinders(
+            &self.atoms,
+            &self.bonds_list,
+            0,
+            &self.neighbors,
+            self.cell.as_ref(),
+            style,
+            &mut self.bonds,
+        );
+        self.bvh = None;
+
+        self.rebuild_atom_instances(device);
+        self.bonds_instance_buffer = Self::create_bonds_instance_buffer(&self.bonds, self.ray_casting, device);
+        self.revision += 1;
+    }
+
+    /// Replaces the periodic cell, bonds through its boundaries keep their images and are redrawn towards them.
+    pub fn set_cell(&mut self, cell: UnitCell, style: &Style, device: &wgpu::Device) {
+        self.cell = Some(cell);
+        if self.bonds_list.iter().all(|bond| bond.image == [0, 0, 0]) {
+            return;
+        }
+        self.bonds.clear();
+        bond_cylinders(
+            &self.atoms,
+            &self.bonds_list,
+            0,
+            &self.neighbors,
+            self.cell.as_ref(),
+            style,
+            &mut self.bonds,
+        );
+        self.bvh = None;
+        self.bonds_instance_buffer = Self::create_bonds_instance_buffer(&self.bonds, self.ray_casting, device);
+        self.revision += 1;
+    }
+
+    /// Atoms (zero-based) on the side of `atom_2` of the bond between `atom_1` and `atom_2`.
+    /// Bonds in rings do not split the molecule in two parts.
+    pub fn bond_fragment(&self, atom_1: usize, atom_2: usize) -> Result<Vec<usize>, String> {
+        if !self.neighbors[atom_1].contains(&atom_2) {
+            return Err(format!("Atoms {} and {} are not bonded", atom_1 + 1, atom_2 + 1));
+        }
+        let mut visited = vec![false; self.atoms.len()];
+        visited[atom_1] = true;
+        visited[atom_2] = true;
+        let mut fragment = vec![atom_2];
+        let mut stack = vec![atom_2];
+        while let Some(i) = stack.pop() {
+            for &j in &self.neighbors[i] {
+                if j == atom_1 && i != atom_2 {
+                    return Err(format!(
+                        "The bond between atoms {} and {} is in a ring",
+                        atom_1 + 1,
+                        atom_2 + 1
+                    ));
+                }
+                if !visited[j] {
+                    visited[j] = true;
+                    fragment.push(j);
+                    stack.push(j);
+                }
+            }
+        }
+        Ok(fragment)
+    }
+
+    /// Rotates the side of `atom_2` of the bond (zero-based atoms) about the bond by the angle in degrees,
+    /// clockwise when looking from `atom_1` to `atom_2`.
+    pub fn rotate_bond(
+        &mut self,
+        atom_1: usize,
+        atom_2: usize,
+        angle: f32,
+        style: &Style,
+        device: &wgpu::Device,
+    ) -> Result<(), String> {
+        let fragment = self.bond_fragment(atom_1, atom_2)?;
+        let center = self.atoms[atom_1].position;
+        let rotation =
+            Quaternion::from_axis_and_angle(self.atoms[atom_2].position - center, angle).to_rotation_matrix();
+        let mut positions: Vec<Vec3<f32>> = self.atoms.iter().map(|atom| atom.position).collect();
+        for i in fragment {
+            positions[i] = center + rotation.transform_point(positions[i] - center);
+        }
+        self.set_positions(&positions, style, device);
+        Ok(())
+    }
+
+    /// Recolors atoms and bonds with the color scheme of the style.
+    pub fn set_color_scheme(&mut self, style: &Style, device: &wgpu::Device) -> Result<(), String> {
+        let numbers: Vec<i32> = self.atoms.iter().map(|atom| atom.number).collect();
+        let colors = style.color_scheme.atom_colors(style, &numbers, &self.properties)?;
+        for (atom, color) in self.atoms.iter_mut().zip(colors) {
+            if let Some(color) = color {
+                atom.color = color;
+            }
+        }
+        self.apply_atom_styles();
+
+        self.bonds.clear();
+        bond_cylinders(
+            &self.atoms,
+            &self.bonds_list,
+            0,
+            &self.neighbors,
+            self.cell.as_ref(),
+            style,
+            &mut self.bonds,
+        );
+
+        self.rebuild_atom_instances(device);
+        self.bonds_instance_buffer = Self::create_bonds_instance_buffer(&self.bonds, self.ray_casting, device);
+        self.revision += 1;
+        Ok(())
+    }
+
+    /// Binds the scalar of every atom to the property color scheme with the range mapped onto the colormap, `None`
+    /// unbinds it. Only colors of the atoms change, instances are rebuilt by the following `set_positions`.
+    pub fn set_property_values(&mut self, values: Option<(Vec<f64>, [f64; 2])>, style: &Style) {
+        self.properties.values = values;
+        if style.color_scheme != ColorScheme::Property {
+            return;
+        }
+        let numbers: Vec<i32> = self.atoms.iter().map(|atom| atom.number).collect();
+        if let Ok(colors) = style.color_scheme.atom_colors(style, &numbers, &self.properties) {
+            for (atom, color) in self.atoms.iter_mut().zip(colors) {
+                if let Some(color) = color {
+                    atom.color = color;
+                }
+            }
+            self.apply_atom_styles();
+        }
+    }
+
+    /// Sets the radius and the color overriding the style for the atoms (zero-based), an empty style removes
+    /// the override. Atoms without an override take the radius and the color of the style again.
+    pub fn set_atom_style(
+        &mut self,
+        indices: &[usize],
+        atom_style: AtomStyle,
+        style: &Style,
+        device: &wgpu::Device,
+    ) -> Result<(), String> {
+        for &i in indices {
+            if atom_style.is_empty() {
+                self.atom_styles.remove(&i);
+            } else {
+                self.atom_styles.insert(i, atom_style);
+            }
+        }
+        let numbers: Vec<i32> = self.atoms.iter().map(|atom| atom.number).collect();
+        let colors = style.color_scheme.atom_colors(style, &numbers, &self.properties)?;
+        for (atom, color) in self.atoms.iter_mut().zip(colors) {
+            if let Some(color) = color {
+                atom.color = color;
+            }
+            let radius = style.atom(atom.number).map_or(atom.radius, |a| a.radius);
+            atom.radius = representation_radius(style, atom.number, radius);
+        }
+        self.apply_atom_styles();
+        self.atom_radius = mean_radius(&self.atoms);
+
+        self.bonds.clear();
+        bond_cylinders(
+            &self.atoms,
+            &self.bonds_list,
+            0,
+            &self.neighbors,
+            self.cell.as_ref(),
+            style,
+            &mut self.bonds,
+        );
+        self.bvh = None;
+
+        self.rebuild_atom_instances(device);
+        self.bonds_instance_buffer = Self::create_bonds_instance_buffer(&self.bonds, self.ray_casting, device);
+        self.revision += 1;
+        Ok(())
+    }
+
+    /// Radii and colors overriding the style by atom (zero-based), in the order of atoms.
+    pub fn atom_styles(&self) -> &BTreeMap<usize, AtomStyle> {
+        &self.atom_styles
+    }
+
+    fn apply_atom_styles(&mut self) {
+        for (&i, atom_style) in &self.atom_styles {
+            let atom = &mut self.atoms[i];
+            if let Some(radius) = atom_style.radius {
+                atom.radius = radius;
+            }
+            if let Some(color) = atom_style.color {
+                atom.color = color;
+            }
+        }
+    }
+
+    /// Shows or hides the atom (index starts from 1) together with its bonds.
+    /// Only flags of the affected instances are written, instance buffers are not rebuilt.
+    pub fn set_atom_visible(&mut self, index: usize, visible: bool, queue: &wgpu::Queue) -> bool {
+        if index == 0 || index > self.atoms.len() {
+            return false;
+        }
+        self.set_atoms_visible(&[index - 1], visible, queue)
+    }
+
+    /// Labels the atom (index starts from 1) with the mass number of an isotope, 0 for the natural composition.
+    /// Fails if the atom has no such isotope.
+    pub fn set_mass_number(&mut self, index: usize, mass_number: u32) -> Result<(), String> {
+        let atom = index
+            .checked_sub(1)
+            .and_then(|i| self.atoms.get_mut(i))
+            .ok_or_else(|| format!("Invalid atom index: {}", index))?;
+        if mass_number != 0 && get_isotope_mass(atom.number, mass_number).is_none() {
+            return Err(format!("Atom {} has no isotope {}", index, mass_number));
+        }
+        atom.mass_number = mass_number;
+        Ok(())
+    }
+
+    /// Shows or hides the atoms (zero-based) together with their bonds, true if any atom has changed.
+    pub fn set_atoms_visible(&mut self, indices: &[usize], visible: bool, queue: &wgpu::Queue) -> bool {
+        let mut changed = vec![false; self.atoms.len()];
+        for &i in indices {
+            if i >= self.atoms.len() || self.atoms[i].visible == visible {
+                continue;
+            }
+            self.atoms[i].visible = visible;
+            changed[i] = true;
+
+            let flags = self.atoms[i].get_instance_data(false, self.ray_casting).flags;
+            Self::write_instance_flags(queue, &self.atoms_instance_buffer, i, flags);
+            // The selection buffer may not have the slot yet if the atom has just been selected
+            if let Some(slot) = self.selection_slot(i) {
+                self.dirty.selections_from(slot);
+            }
+        }
+        if !changed.contains(&true) {
+            return false;
+        }
+
+        for (slot, bond) in self.bonds.iter_mut().enumerate() {
+            if changed[bond.atoms.0] || changed[bond.atoms.1] {
+                bond.visible = self.atoms[bond.atoms.0].visible && self.atoms[bond.atoms.1].visible;
+                let flags = bond.get_instance_data(self.ray_casting).flags;
+                Self::write_instance_flags(queue, &self.bonds_instance_buffer, slot, flags);
+            }
+        }
+
+        self.bvh = None;
+        self.revision += 1;
+        true
+    }
+
+    /// Dims the atoms (zero-based) not in `focused` together with their bonds, `None` restores all of them.
+    pub fn set_focused_atoms(&mut self, focused: Option<&[usize]>, dimming: Dimming, queue: &wgpu::Queue) {
+        let mut dimmed = vec![focused.map(|_| dimming); self.atoms.len()];
+        for &i in focused.unwrap_or(&[]) {
+            if let Some(dimmed) = dimmed.get_mut(i) {
+                *dimmed = None;
+            }
+        }
+        if self.atoms.iter().zip(&dimmed).all(|(atom, &dimmed)| atom.dimmed == dimmed) {
+            return;
+        }
+        for (atom, dimmed) in self.atoms.iter_mut().zip(dimmed) {
+            atom.dimmed = dimmed;
+        }
+        for bond in &mut self.bonds {
+            bond.dimmed = self.atoms[bond.atoms.0].dimmed.or(self.atoms[bond.atoms.1].dimmed);
+        }
+
+        // Colors of every instance may change, the buffers keep their sizes
+        let atoms: Vec<InstanceData> = self
+            .atoms
+            .iter()
+            .map(|atom| atom.get_instance_data(false, self.ray_casting))
+            .collect();
+        queue.write_buffer(&self.atoms_instance_buffer, 0, bytemuck::cast_slice(&atoms));
+        let bonds: Vec<InstanceData> = self
+            .bonds
+            .iter()
+            .map(|bond| bond.get_instance_data(self.ray_casting))
+            .collect();
+        if !bonds.is_empty() {
+            queue.write_buffer(&self.bonds_instance_buffer, 0, bytemuck::cast_slice(&bonds));
+        }
+        self.revision += 1;
+    }
+
+    /// Atoms (zero-based) with centers closer than `cutoff` in Angstroms to any of the atoms, including them.
+    pub fn atoms_near(&self, atoms: &[usize], cutoff: f32) -> Vec<usize> {
+        let position = |atom: &Atom| [atom.position.x, atom.position.y, atom.position.z].map(f64::from);
+        if cutoff <= 0.0 {
+            return atoms.to_vec();
+        }
+        let grid = NeighborGrid::new(self.atoms.iter().map(position).collect(), cutoff as f64, None);
+        let mut near = vec![false; self.atoms.len()];
+        for &i in atoms {
+            near[i] = true;
+            grid.for_each_neighbor(position(&self.atoms[i]), |j, _, _| near[j] = true);
+        }
+        (0..self.atoms.len()).filter(|&i| near[i]).collect()
+    }
+
+    /// Draws the atoms (zero-based) in the warning color of clashes and the others in their own colors.
+    pub fn set_clashing_atoms(&mut self, clashing: &[usize]) {
+        let mut flags = vec![false; self.atoms.len()];
+        for &i in clashing {
+            flags[i] = true;
+        }
+        for (i, flag) in flags.into_iter().enumerate() {
+            if self.atoms[i].clashing != flag {
+                self.atoms[i].clashing = flag;
+                self.mark_atom_dirty(i);
+            }
+        }
+    }
+
+    /// Radius of the visible atoms around the center of the molecule, measured the same way as `radius`.
+    pub fn visible_radius(&self) -> f32 {
+        self.atoms
+            .iter()
+            .filter(|atom| atom.visible)
+            .map(|atom| atom.position.length_squared() + atom.radius)
+            .fold(0.0, f32::max)
+            .sqrt()
+    }
+
+    pub fn atoms(&self) -> &[Atom] {
+        &self.atoms
+    }
+
+    /// Bonds between the atoms (zero-based indices), given by the file or perceived from distances.
+    pub fn bonds(&self) -> &[connectivity::Bond] {
+        &self.bonds_list
+    }
+
+    /// Zero-based indices of the selected atoms in the order of selection.
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Instances of the atoms and of the bond cylinders without highlighting and not pickable,
+    /// e.g. for copies of the structure.
+    pub fn plain_instances(&self) -> (Vec<InstanceData>, Vec<InstanceData>) {
+        let atoms = self
+            .atoms
+            .iter()
+            .map(|atom| InstanceData {
+                scale: [atom.radius, atom.radius],
+                picking_id: 0,
+                ..atom.get_instance_data(false, self.ray_casting)
+            })
+            .collect();
+        let bonds = self
+            .bonds
+            .iter()
+            .map(|bond| InstanceData {
+                picking_id: 0,
+                ..bond.get_instance_data(self.ray_casting)
+            })
+            .collect();
+        (atoms, bonds)
+    }
+
+    pub fn selected_atoms(&self) -> &[usize] {
+        &self.selected_atoms
+    }
+
     pub fn atoms_instance_count(&self) -> usize {
         self.atoms.len()
     }
@@ -158,15 +699,49 @@ impl Molecule {
         self.bonds.len()
     }
 
+    pub fn atom_info(&self, index: usize) -> Option<AtomInfo> {
+        let atom = self.atoms.get(index.checked_sub(1)?)?;
+        Some(AtomInfo::new(atom.symbol()?, index))
+    }
+
+    /// CPU alternative to the picking pass: the nearest atom or bond hit by the ray in molecule space.
+    pub fn pick(&mut self, ray: &Ray) -> Option<Hit> {
+        let atoms = &self.atoms;
+        let bonds = &self.bonds;
+        let bvh = self.bvh.get_or_insert_with(|| {
+            let spheres = atoms
+                .iter()
+                .enumerate()
+                .filter(|(_, atom)| atom.visible)
+                .map(|(i, atom)| Primitive {
+                    shape: Shape::Sphere {
+                        center: atom.position,
+                        radius: atom.radius,
+                    },
+                    target: Target::Atom(i),
+                });
+            let cylinders = bonds.iter().filter(|bond| bond.visible).map(|bond| Primitive {
+                shape: Shape::Cylinder {
+                    center: bond.position,
+                    axis: bond.direction.normalized(),
+                    half_length: bond.lenght,
+                    radius: bond.thickness,
+                },
+                target: Target::Bond(bond.index),
+            });
+            Bvh::new(spheres.chain(cylinders).collect())
+        });
+        bvh.intersect(ray)
+    }
+
     /// Returns (atom_info, needs_render)
-    pub fn highlight_atom(&mut self, index: usize, device: &wgpu::Device) -> (Option<AtomInfo>, bool) {
+    pub fn highlight_atom(&mut self, index: usize) -> (Option<AtomInfo>, bool) {
         if index == 0 || index > self.atoms.len() {
             // No atom under cursor - clear highlight if any
             if self.highlighted_atom > 0 {
                 self.atoms[self.highlighted_atom - 1].highlighted = false;
+                self.mark_atom_dirty(self.highlighted_atom - 1);
                 self.highlighted_atom = 0;
-                (self.atoms_instance_buffer, self.atom_selections_instance_buffer) =
-                    Self::create_atoms_instance_buffers(&self.atoms, device);
                 return (None, true);
             }
             return (None, false);
@@ -174,48 +749,340 @@ impl Molecule {
 
         // Same atom already highlighted - return info without updating buffer
         if self.highlighted_atom == index {
-            let element = match get_element_by_number(self.atoms[index - 1].number) {
-                Some(e) => e,
+            let symbol = match self.atoms[index - 1].symbol() {
+                Some(symbol) => symbol,
                 None => return (None, false),
             };
-            return (Some(AtomInfo::new(element.symbol.to_string(), index)), false);
+            return (Some(AtomInfo::new(symbol, index)), false);
         }
 
-        let element = match get_element_by_number(self.atoms[index - 1].number) {
-            Some(e) => e,
+        let symbol = match self.atoms[index - 1].symbol() {
+            Some(symbol) => symbol,
             None => return (None, false),
         };
 
         // Reset previous highlighted atom
         if self.highlighted_atom > 0 {
             self.atoms[self.highlighted_atom - 1].highlighted = false;
+            self.mark_atom_dirty(self.highlighted_atom - 1);
         }
 
         // Set new highlighted atom
         self.atoms[index - 1].highlighted = true;
+        self.mark_atom_dirty(index - 1);
         self.highlighted_atom = index;
-        (self.atoms_instance_buffer, self.atom_selections_instance_buffer) =
-            Self::create_atoms_instance_buffers(&self.atoms, device);
-        (Some(AtomInfo::new(element.symbol.to_string(), index)), true)
+        (Some(AtomInfo::new(symbol, index)), true)
     }
 
-    pub fn toggle_atom_selection(&mut self, index: usize, device: &wgpu::Device) -> bool {
+    pub fn toggle_atom_selection(&mut self, index: usize) -> bool {
         if index == 0 || index > self.atoms.len() {
             // No atom under cursor - clear highlight if any
             return false;
         }
 
-        if self.atoms[index - 1].selected {
-            self.selected_atoms.remove(&(index - 1));
+        let i = index - 1;
+        if self.atoms[i].selected {
+            let slot = self.selection_slot(i).unwrap();
+            self.selected_atoms.remove(slot);
+            self.dirty.selections_from(slot);
         } else {
-            self.selected_atoms.insert(index - 1);
+            self.dirty.selections_from(self.selected_atoms.len());
+            self.selected_atoms.push(i);
         }
 
-        self.atoms[index - 1].toggle_selection();
-        (self.atoms_instance_buffer, self.atom_selections_instance_buffer) =
-            Self::create_atoms_instance_buffers(&self.atoms, device);
+        self.atoms[i].toggle_selection();
         true
     }
+
+    /// Adds the atoms (zero-based indices) to the selection in the given order, returns false if all were selected.
+    pub fn select_atoms(&mut self, indices: &[usize]) -> bool {
+        let mut changed = false;
+        for &i in indices {
+            if !self.atoms[i].selected {
+                self.atoms[i].toggle_selection();
+                self.dirty.selections_from(self.selected_atoms.len());
+                self.selected_atoms.push(i);
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    /// Returns false if no atoms were selected.
+    pub fn clear_selection(&mut self) -> bool {
+        if self.selected_atoms.is_empty() {
+            return false;
+        }
+        for i in std::mem::take(&mut self.selected_atoms) {
+            self.atoms[i].toggle_selection();
+        }
+        // No selection instances are drawn, so none are written
+        self.dirty.selections_from = None;
+        true
+    }
+
+    /// Slot of the selected atom (zero-based) in the selection instance buffer.
+    fn selection_slot(&self, i: usize) -> Option<usize> {
+        self.selected_atoms.iter().position(|&j| j == i)
+    }
+
+    /// Marks the instances of the atom (zero-based) and of its selection sphere to be written.
+    fn mark_atom_dirty(&mut self, i: usize) {
+        self.dirty.atoms.push(i);
+        if let Some(slot) = self.selection_slot(i) {
+            self.dirty.selections_from(slot);
+        }
+    }
+
+    /// Recreates the instance buffers of the atoms, e.g. after their geometry has changed.
+    fn rebuild_atom_instances(&mut self, device: &wgpu::Device) {
+        (self.atoms_instance_buffer, self.atom_selections_instance_buffer) =
+            Self::create_atoms_instance_buffers(&self.atoms, &self.selected_atoms, self.ray_casting, device);
+        self.selections_capacity = self.selected_atoms.len();
+        self.dirty = DirtyInstances::default();
+    }
+
+    /// Writes the instances changed by highlighting and selection to the buffers, consecutive atoms in one
+    /// write. The selection buffer is only recreated when more atoms are selected than it has slots for.
+    pub fn write_dirty_instances(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        let mut atoms = std::mem::take(&mut self.dirty.atoms);
+        atoms.sort_unstable();
+        atoms.dedup();
+        let instance_size = std::mem::size_of::<InstanceData>();
+        for run in atoms.chunk_by(|a, b| b - a == 1) {
+            let data: Vec<InstanceData> = run
+                .iter()
+                .map(|&i| self.atoms[i].get_instance_data(false, self.ray_casting))
+                .collect();
+            let offset = run[0] * instance_size;
+            queue.write_buffer(&self.atoms_instance_buffer, offset as wgpu::BufferAddress, bytemuck::cast_slice(&data));
+        }
+
+        let Some(from) = self.dirty.selections_from.take() else {
+            return;
+        };
+        let count = self.selected_atoms.len();
+        if count > self.selections_capacity {
+            // Grows geometrically, so selecting atoms one by one recreates the buffer a few times only
+            let capacity = count.max(2 * self.selections_capacity);
+            let mut data: Vec<InstanceData> = self.selection_instances(0..count);
+            data.resize(capacity, InstanceData::zeroed());
+            self.atom_selections_instance_buffer = Self::create_instance_buffer(&data, device);
+            self.selections_capacity = capacity;
+        } else if from < count {
+            let data = self.selection_instances(from..count);
+            let offset = from * instance_size;
+            queue.write_buffer(
+                &self.atom_selections_instance_buffer,
+                offset as wgpu::BufferAddress,
+                bytemuck::cast_slice(&data),
+            );
+        }
+    }
+
+    fn selection_instances(&self, slots: std::ops::Range<usize>) -> Vec<InstanceData> {
+        self.selected_atoms[slots]
+            .iter()
+            .map(|&i| self.atoms[i].get_instance_data(true, self.ray_casting))
+            .collect()
+    }
+}
+
+/// Atoms of the range with the largest squared distance plus radius of them from the origin. Fails at the first
+/// unknown atomic number.
+fn build_atoms(
+    config: &Config,
+    atomic_coordinates: &AtomicCoordinates,
+    mass_numbers: &[u32],
+    origin: [f64; 3],
+    colors: &[Option<Color>],
+    range: std::ops::Range<usize>,
+) -> Result<(Vec<Atom>, f32), String> {
+    let mut atoms = Vec::with_capacity(range.len());
+    let mut radius: f32 = 0.0;
+    for i in range {
+        let atom = config.style.atom(atomic_coordinates.atomic_num[i]).ok_or_else(|| {
+            format!("Atom not found for atomic number: {}", atomic_coordinates.atomic_num[i])
+        })?;
+
+        // Subtract the origin before narrowing to f32
+        let position = Vec3::new(
+            (atomic_coordinates.x[i] - origin[0]) as f32,
+            (atomic_coordinates.y[i] - origin[1]) as f32,
+            (atomic_coordinates.z[i] - origin[2]) as f32,
+        );
+
+        let atom_radius = representation_radius(&config.style, atomic_coordinates.atomic_num[i], atom.radius);
+        radius = radius.max(position.length_squared() + atom_radius);
+
+        let mut atom = Atom::new(
+            atomic_coordinates.atomic_num[i],
+            position,
+            atom_radius,
+            colors[i].unwrap_or(atom.color),
+            (i + 1) as u32,
+            config.style.selected_atom.color,
+            config.style.selected_atom.scale_factor,
+        );
+        atom.mass_number = mass_numbers[i];
+        atom.visible = config.show_pseudo_atoms || !is_pseudo_element(atom.number);
+        atoms.push(atom);
+    }
+    Ok((atoms, radius))
+}
+
+/// Radius of an atom in the representation of the style, `radius` is the ball-and-stick one.
+fn representation_radius(style: &Style, atomic_number: i32, radius: f32) -> f32 {
+    match style.representation {
+        Representation::BallAndStick => style
+            .radius_set
+            .and_then(|radii| radii.radius(atomic_number))
+            .unwrap_or(radius),
+        Representation::Spacefill => get_vdw_radius(atomic_number).map_or(radius, |r| r as f32),
+        Representation::Licorice => style.licorice_thickness,
+        Representation::Wireframe => style.wireframe_thickness,
+    }
+}
+
+fn mean_radius(atoms: &[Atom]) -> f32 {
+    atoms.iter().map(|atom| atom.radius).sum::<f32>() / atoms.len().max(1) as f32
+}
+
+/// Appends cylinders of the bonds in the representation of the style to `result`.
+/// `first` is the index of the first bond of `bonds_list` in the whole list of bonds, the cylinders are picked by it.
+fn bond_cylinders(
+    atoms: &[Atom],
+    bonds_list: &[connectivity::Bond],
+    first: usize,
+    neighbors: &[Vec<usize>],
+    cell: Option<&UnitCell>,
+    style: &Style,
+    result: &mut Vec<Bond>,
+) {
+    let thickness = match style.representation {
+        Representation::BallAndStick => style.bond.thickness,
+        // Atoms of a space-filling model hide bonds anyway
+        Representation::Spacefill => return,
+        Representation::Licorice => style.licorice_thickness,
+        Representation::Wireframe => style.wireframe_thickness,
+    };
+
+    for (index, bond) in bonds_list.iter().enumerate() {
+        let atom_1 = &atoms[bond.atom_index_1];
+        let atom_2 = &atoms[bond.atom_index_2];
+
+        let offsets = match style.representation {
+            // Thick sticks of multiple bonds would merge into one
+            Representation::Licorice => &[0.0],
+            _ => multiple_bond_offsets(bond.order),
+        };
+        let (side, thickness) = if offsets.len() > 1 {
+            (
+                bond_side(atoms, neighbors, bond.atom_index_1, bond.atom_index_2),
+                thickness * style.bond.multiple_bond_thickness_factor,
+            )
+        } else {
+            (Vec3::zero(), thickness)
+        };
+
+        // A bond through the boundary of the cell is drawn as the halves of both atoms towards the image
+        // of the other atom
+        let translation = match cell {
+            Some(cell) if bond.image != [0, 0, 0] => {
+                let [x, y, z] = cell.to_cartesian(bond.image.map(|n| n as f64));
+                Some(Vec3::new(x as f32, y as f32, z as f32))
+            }
+            _ => None,
+        };
+
+        for offset in offsets {
+            let shift = side * (offset * style.bond.multiple_bond_spacing);
+            let computed_bonds = match translation {
+                Some(translation) => {
+                    let mut halves = get_bonds(
+                        atom_1.position + shift,
+                        atom_1.radius,
+                        atom_1.color,
+                        atom_2.position + translation + shift,
+                        atom_2.radius,
+                        atom_2.color,
+                    );
+                    halves.truncate(1);
+                    let towards_1 = get_bonds(
+                        atom_1.position - translation + shift,
+                        atom_1.radius,
+                        atom_1.color,
+                        atom_2.position + shift,
+                        atom_2.radius,
+                        atom_2.color,
+                    );
+                    halves.extend(towards_1.into_iter().skip(1));
+                    halves
+                }
+                None => get_bonds(
+                    atom_1.position + shift,
+                    atom_1.radius,
+                    atom_1.color,
+                    atom_2.position + shift,
+                    atom_2.radius,
+                    atom_2.color,
+                ),
+            };
+
+            for b in computed_bonds {
+                let mut cylinder = Bond::new(
+                    first + index,
+                    (bond.atom_index_1, bond.atom_index_2),
+                    b.0,
+                    b.1,
+                    thickness,
+                    b.2,
+                    b.3,
+                );
+                cylinder.visible = atom_1.visible && atom_2.visible;
+                cylinder.dimmed = atom_1.dimmed.or(atom_2.dimmed);
+                result.push(cylinder);
+            }
+        }
+    }
+}
+
+/// Offsets of the cylinders of a bond in units of the multiple bond spacing.
+/// Aromatic bonds are drawn as double ones.
+fn multiple_bond_offsets(order: u8) -> &'static [f32] {
+    match order {
+        2 | Connection::AROMATIC => &[-0.5, 0.5],
+        3 => &[-1.0, 0.0, 1.0],
+        _ => &[0.0],
+    }
+}
+
+/// Unit vector perpendicular to the bond along which cylinders of a multiple bond are shifted.
+/// It lies in the plane of the bond and a neighbouring atom, so double bonds of rings stay flat.
+fn bond_side(atoms: &[Atom], neighbors: &[Vec<usize>], index_1: usize, index_2: usize) -> Vec3<f32> {
+    let direction = (atoms[index_2].position - atoms[index_1].position).normalized();
+
+    let in_plane = neighbors[index_1]
+        .iter()
+        .filter(|&&k| k != index_2)
+        .map(|&k| atoms[k].position - atoms[index_1].position)
+        .chain(
+            neighbors[index_2]
+                .iter()
+                .filter(|&&k| k != index_1)
+                .map(|&k| atoms[k].position - atoms[index_2].position),
+        )
+        .map(|v| (v - direction * Vec3::dot_product(v, direction)).normalized())
+        .find(|v| v.length() > 0.0);
+
+    in_plane.unwrap_or_else(|| {
+        let helper = if direction.x.abs() < 0.9 {
+            Vec3::new(1.0, 0.0, 0.0)
+        } else {
+            Vec3::new(0.0, 1.0, 0.0)
+        };
+        Vec3::cross_product(direction, helper).normalized()
+    })
 }
 
 fn get_bonds(
@@ -0,0 +1,54 @@
+use bytemuck::Zeroable;
+use shared_lib::types::AtomicCoordinates;
+use wgpu::util::DeviceExt;
+
+use super::molecule::Molecule;
+use super::renderer::Uniforms;
+
+/// Structure shown together with the molecule of the scene, e.g. another conformer to compare it with.
+/// It is drawn with the transform of its own node of the scene graph and is not pickable or editable.
+pub struct Overlay {
+    /// Id of the node of the structure in the scene graph.
+    pub node: u32,
+    pub molecule: Molecule,
+    // Structure the molecule was built from, bonds are rebuilt from it when the bond tolerance changes
+    pub data: AtomicCoordinates,
+    uniform_buffer: wgpu::Buffer,
+    pub bind_group: wgpu::BindGroup,
+}
+
+impl Overlay {
+    pub fn new(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        node: u32,
+        molecule: Molecule,
+        data: AtomicCoordinates,
+    ) -> Self {
+        // Uniforms of the main render with the scene transform of the node
+        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Overlay Uniform Buffer"),
+            contents: bytemuck::bytes_of(&Uniforms::zeroed()),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Overlay Bind Group"),
+            layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform_buffer.as_entire_binding(),
+            }],
+        });
+        Self {
+            node,
+            molecule,
+            data,
+            uniform_buffer,
+            bind_group,
+        }
+    }
+
+    pub fn write_uniforms(&self, queue: &wgpu::Queue, uniforms: &Uniforms) {
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::bytes_of(uniforms));
+    }
+}
@@ -0,0 +1,260 @@
+use bytemuck::{Pod, Zeroable};
+use wgpu::util::DeviceExt;
+
+use super::config::Effects;
+use super::core::Mat4;
+use super::renderer::{USAGE_BINDING, shader_source};
+use super::scene::Viewport;
+
+const DEPTH_OF_FIELD_FLAG: u32 = 1;
+const OUTLINE_FLAG: u32 = 2;
+const ANTIALIASING_FLAG: u32 = 4;
+
+/// Uniforms of post.wgsl.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+pub struct PostProcessParams {
+    inverse_projection: [f32; 16],
+    outline_color: [f32; 4],
+    viewport: [f32; 4],
+    focus_depth: f32,
+    focus_range: f32,
+    max_blur: f32,
+    outline_thickness: f32,
+    outline_threshold: f32,
+    flags: u32,
+    padding: [u32; 2],
+}
+
+impl PostProcessParams {
+    /// `focus_depth` is the distance from the camera in view space, `scale` converts Angstroms of the molecule
+    /// to view space.
+    pub fn new(effects: &Effects, projection: &Mat4<f32>, focus_depth: f32, scale: f32, viewport: Viewport) -> Self {
+        let color = effects.outline_color;
+        let mut flags = 0;
+        if effects.depth_of_field {
+            flags |= DEPTH_OF_FIELD_FLAG;
+        }
+        if effects.outline {
+            flags |= OUTLINE_FLAG;
+        }
+        if effects.antialiasing {
+            flags |= ANTIALIASING_FLAG;
+        }
+        Self {
+            inverse_projection: projection.inverted().unwrap_or_default().data,
+            outline_color: [color.r, color.g, color.b, color.a],
+            viewport: [
+                viewport.x as f32,
+                viewport.y as f32,
+                viewport.width as f32,
+                viewport.height as f32,
+            ],
+            focus_depth,
+            focus_range: effects.focus_range * scale,
+            max_blur: effects.max_blur_pixels,
+            outline_thickness: effects.outline_thickness_pixels,
+            outline_threshold: effects.outline_depth_threshold * scale,
+            flags,
+            padding: [0; 2],
+        }
+    }
+}
+
+/// Effects for presentation renders applied to the finished frame. The scene is drawn into
+/// an intermediate texture, then the post-processing pass reads it together with the depth buffer.
+pub struct PostProcess {
+    pipeline: wgpu::RenderPipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
+    params_buffer: wgpu::Buffer,
+    format: wgpu::TextureFormat,
+    pub color_texture_view: wgpu::TextureView,
+    width: u32,
+    height: u32,
+}
+
+impl PostProcess {
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat, width: u32, height: u32) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Post-processing Shader"),
+            source: shader_source("post.wgsl", include_str!("shaders/post.wgsl")),
+        });
+
+        // Color and depth are read with textureLoad
+        let texture = |binding: u32| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        };
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Post-processing Bind Group Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                texture(1),
+                texture(2),
+            ],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Post-processing Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            immediate_size: 0,
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Post-processing Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                buffers: &[],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview_mask: None,
+            cache: None,
+        });
+
+        let params_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Post-processing Params Buffer"),
+            contents: bytemuck::bytes_of(&PostProcessParams::zeroed()),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        Self {
+            pipeline,
+            bind_group_layout,
+            params_buffer,
+            format,
+            color_texture_view: Self::create_color_texture(device, format, width, height),
+            width,
+            height,
+        }
+    }
+
+    fn create_color_texture(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        width: u32,
+        height: u32,
+    ) -> wgpu::TextureView {
+        device
+            .create_texture(&wgpu::TextureDescriptor {
+                label: Some("Post-processing Color Texture"),
+                size: wgpu::Extent3d {
+                    width,
+                    height,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format,
+                usage: USAGE_BINDING,
+                view_formats: &[],
+            })
+            .create_view(&wgpu::TextureViewDescriptor::default())
+    }
+
+    /// Recreates the intermediate texture if the size of the render target has changed.
+    pub fn resize(&mut self, device: &wgpu::Device, width: u32, height: u32) {
+        if (self.width, self.height) != (width, height) {
+            self.color_texture_view = Self::create_color_texture(device, self.format, width, height);
+            self.width = width;
+            self.height = height;
+        }
+    }
+
+    /// Records the pass drawing the intermediate texture with the effects into the viewport of `view`.
+    pub fn draw(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        depth_view: &wgpu::TextureView,
+        params: &PostProcessParams,
+    ) {
+        queue.write_buffer(&self.params_buffer, 0, bytemuck::bytes_of(params));
+        // The depth view is recreated when the renderer is resized, so the bind group is not kept
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Post-processing Bind Group"),
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: self.params_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&self.color_texture_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::TextureView(depth_view),
+                },
+            ],
+        });
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Post-processing Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                depth_slice: None,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+            multiview_mask: None,
+        });
+
+        let [x, y, width, height] = params.viewport;
+        render_pass.set_viewport(x, y, width, height, 0.0, 1.0);
+        render_pass.set_scissor_rect(x as u32, y as u32, width as u32, height as u32);
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &bind_group, &[]);
+        render_pass.draw(0..6, 0..1);
+    }
+}
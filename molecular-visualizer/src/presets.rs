@@ -0,0 +1,202 @@
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+use shared_lib::colormap::Palette;
+
+use super::color_scheme::ColorScheme;
+use super::config::{Representation, Style};
+use super::types::Color;
+
+pub const BUILT_IN_PRESETS: [&str; 6] = [
+    "default",
+    "publication",
+    "presentation",
+    "dark",
+    "colorblind_safe",
+    "grayscale_print",
+];
+
+/// Named set of style settings. Only the settings given by the preset are changed when it is applied.
+#[derive(Clone, Debug, Default)]
+pub struct StylePreset {
+    pub background_color: Option<Color>,
+    pub color_scheme: Option<ColorScheme>,
+    pub palette: Option<Palette>,
+    pub representation: Option<Representation>,
+    pub bond_thickness: Option<f32>,
+    pub label_color: Option<Color>,
+    pub label_outline_color: Option<Color>,
+    pub outline: Option<bool>,
+    pub depth_of_field: Option<bool>,
+}
+
+// Preset as given by the host, names are parsed the same way as in the rest of the API
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PresetJson {
+    background_color: Option<[f32; 3]>,
+    color_scheme: Option<String>,
+    palette: Option<String>,
+    representation: Option<String>,
+    bond_thickness: Option<f32>,
+    label_color: Option<[f32; 3]>,
+    label_outline_color: Option<[f32; 3]>,
+    outline: Option<bool>,
+    depth_of_field: Option<bool>,
+}
+
+fn rgb(r: f32, g: f32, b: f32) -> Option<Color> {
+    Some(Color::new(r, g, b, 1.0))
+}
+
+impl StylePreset {
+    /// Preset from JSON, e.g. `{"background_color": [1, 1, 1], "color_scheme": "cpk", "outline": true}`.
+    /// Colors are RGB in range [0, 1].
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let preset: PresetJson =
+            serde_json::from_str(json).map_err(|e| format!("Failed to parse style preset: {}", e))?;
+        let color = |rgb: Option<[f32; 3]>| rgb.map(|[r, g, b]| Color::new(r, g, b, 1.0));
+        Ok(Self {
+            background_color: color(preset.background_color),
+            color_scheme: preset.color_scheme.as_deref().map(ColorScheme::from_name).transpose()?,
+            palette: preset.palette.as_deref().map(Palette::from_name).transpose()?,
+            representation: preset
+                .representation
+                .as_deref()
+                .map(Representation::from_name)
+                .transpose()?,
+            bond_thickness: preset.bond_thickness,
+            label_color: color(preset.label_color),
+            label_outline_color: color(preset.label_outline_color),
+            outline: preset.outline,
+            depth_of_field: preset.depth_of_field,
+        })
+    }
+
+    /// Built-in presets set all the settings, so that they switch the look completely.
+    pub fn built_in(name: &str) -> Option<Self> {
+        let default = Self {
+            background_color: rgb(0.133, 0.133, 0.133),
+            color_scheme: Some(ColorScheme::Jmol),
+            palette: Some(Palette::Standard),
+            representation: Some(Representation::BallAndStick),
+            bond_thickness: Some(0.1),
+            label_color: rgb(1.0, 1.0, 1.0),
+            label_outline_color: rgb(0.0, 0.0, 0.0),
+            outline: Some(false),
+            depth_of_field: Some(false),
+        };
+        match name {
+            "default" => Some(default),
+            // Print-friendly: white paper, dark outlines separate atoms of similar colors
+            "publication" => Some(Self {
+                background_color: rgb(1.0, 1.0, 1.0),
+                label_color: rgb(0.0, 0.0, 0.0),
+                label_outline_color: rgb(1.0, 1.0, 1.0),
+                outline: Some(true),
+                ..default
+            }),
+            // Projectors: deep blue background, thicker bonds and depth cues
+            "presentation" => Some(Self {
+                background_color: rgb(0.05, 0.08, 0.16),
+                bond_thickness: Some(0.14),
+                depth_of_field: Some(true),
+                ..default
+            }),
+            "dark" => Some(Self {
+                background_color: rgb(0.0, 0.0, 0.0),
+                ..default
+            }),
+            // Outlines help where colors alone are not enough
+            "colorblind_safe" => Some(Self {
+                color_scheme: Some(ColorScheme::ColorblindSafe),
+                palette: Some(Palette::ColorblindSafe),
+                outline: Some(true),
+                ..default
+            }),
+            // Black-and-white print: gray levels on white paper with outlines
+            "grayscale_print" => Some(Self {
+                background_color: rgb(1.0, 1.0, 1.0),
+                palette: Some(Palette::Grayscale),
+                label_color: rgb(0.0, 0.0, 0.0),
+                label_outline_color: rgb(1.0, 1.0, 1.0),
+                outline: Some(true),
+                ..default
+            }),
+            _ => None,
+        }
+    }
+
+    pub fn apply(&self, style: &mut Style) {
+        if let Some(color) = self.background_color {
+            style.background_color = color;
+        }
+        if let Some(color_scheme) = self.color_scheme {
+            style.color_scheme = color_scheme;
+        }
+        if let Some(palette) = self.palette {
+            style.palette = palette;
+        }
+        if let Some(representation) = self.representation {
+            style.representation = representation;
+        }
+        if let Some(thickness) = self.bond_thickness {
+            style.bond.thickness = thickness;
+        }
+        if let Some(color) = self.label_color {
+            style.label.color = color;
+        }
+        if let Some(color) = self.label_outline_color {
+            style.label.outline_color = color;
+        }
+        if let Some(outline) = self.outline {
+            style.effects.outline = outline;
+        }
+        if let Some(depth_of_field) = self.depth_of_field {
+            style.effects.depth_of_field = depth_of_field;
+        }
+    }
+}
+
+/// Built-in presets and presets registered by the host.
+pub struct StylePresets {
+    custom: BTreeMap<String, StylePreset>,
+}
+
+impl StylePresets {
+    pub fn new() -> Self {
+        Self {
+            custom: BTreeMap::new(),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Result<StylePreset, String> {
+        let name = normalized(name);
+        StylePreset::built_in(&name)
+            .or_else(|| self.custom.get(&name).cloned())
+            .ok_or_else(|| format!("Unknown style preset: {}", name))
+    }
+
+    /// Adds or replaces a custom preset, names of built-in presets are reserved.
+    pub fn register(&mut self, name: &str, preset: StylePreset) -> Result<(), String> {
+        let name = normalized(name);
+        if BUILT_IN_PRESETS.contains(&name.as_str()) {
+            return Err(format!("Style preset {} is built in and can not be replaced", name));
+        }
+        self.custom.insert(name, preset);
+        Ok(())
+    }
+
+    /// Names of the built-in presets followed by the custom ones.
+    pub fn names(&self) -> Vec<String> {
+        BUILT_IN_PRESETS
+            .iter()
+            .map(|name| name.to_string())
+            .chain(self.custom.keys().cloned())
+            .collect()
+    }
+}
+
+fn normalized(name: &str) -> String {
+    name.to_ascii_lowercase().replace(['-', ' '], "_")
+}
@@ -0,0 +1,2693 @@
+//! Headless visual regression tests.
+//!
+//! Reference scenes are rendered offscreen and compared with golden images in `tests/golden`.
+//! Run with `UPDATE_GOLDEN=1` to (re)create the golden images after an intended visual change.
+//! The tests are skipped if the machine has no GPU adapter (not even a software one like llvmpipe).
+
+use std::future::Future;
+use std::io::Read;
+use std::path::PathBuf;
+
+use flate2::read::ZlibDecoder;
+use shared_lib::cancellation::CancellationToken;
+use shared_lib::cell::UnitCell;
+use shared_lib::colormap::{ColorVisionDeficiency, Colormap, Palette, relative_luminance};
+use shared_lib::comparison::Tolerances;
+use shared_lib::connectivity;
+use shared_lib::contacts;
+use shared_lib::editing;
+use shared_lib::inertia;
+use shared_lib::periodic_table::{GHOST_ATOM, POINT_CHARGE, RadiusSet};
+use shared_lib::templates;
+use shared_lib::types::{AtomicCoordinates, Connection, VibrationalModes, VolumeCube};
+
+use super::atom::AtomStyle;
+use super::autosave::{Autosave, Edit};
+use super::color_scheme::ColorScheme;
+use super::config::{Config, Representation, ScaledRadii, ViewDefaults};
+use super::core::mesh::InstanceData;
+use super::core::{CameraLimits, Mat4, ProjectionMode, Vec3};
+use super::culling::{CHUNK_SIZE, ChunkCulling, MAX_DRAWS};
+#[cfg(feature = "debug-tools")]
+use super::debug_tools::DebugView;
+use super::executor::{DEFAULT_CHUNK_SIZE, Progress};
+use super::grid::MoleculeGrid;
+use super::isosurface::IsosurfaceSettings;
+use super::labels::{BondLabels, LabelKind};
+use super::molecule::Molecule;
+use super::presets::StylePreset;
+use super::report::{Report, ReportFormat};
+use super::scene::{FocusMode, MOLECULE_NODE, ROOT_NODE, Scene};
+use super::slice::SliceSettings;
+use super::structure_export;
+use super::style_settings::StyleSettings;
+use super::thumbnails::{Thumbnails, structure_hash};
+use super::types::{Color, Dimming};
+use super::utils::{PickedObject, color_to_id, id_to_color};
+use super::view::{self, AUTO_ORIENTATION_TILT};
+
+const WIDTH: u32 = 128;
+const HEIGHT: u32 = 128;
+const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+// Pixels differing by more than this (YIQ distance normalized to [0, 1]) are counted as changed
+const PIXEL_THRESHOLD: f64 = 0.1;
+// Share of changed pixels tolerated to absorb rasterization differences between drivers
+const MAX_CHANGED_PIXELS: f64 = 0.005;
+
+/// Polls a future to completion. wgpu futures resolve once the device is polled, so busy polling is enough.
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = std::pin::pin!(future);
+    let mut context = std::task::Context::from_waker(std::task::Waker::noop());
+    loop {
+        if let std::task::Poll::Ready(output) = future.as_mut().poll(&mut context) {
+            return output;
+        }
+    }
+}
+
+struct Gpu {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+}
+
+impl Gpu {
+    fn new() -> Option<Self> {
+        Self::request(wgpu::Limits::downlevel_webgl2_defaults(), wgpu::Features::empty())
+    }
+
+    /// Device with compute shaders for the GPU-driven path, `None` if the adapter has none.
+    fn compute(features: wgpu::Features) -> Option<Self> {
+        Self::request(wgpu::Limits::downlevel_defaults(), features)
+    }
+
+    fn request(limits: wgpu::Limits, features: wgpu::Features) -> Option<Self> {
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor::default());
+        let adapter = block_on(instance.request_adapter(&wgpu::RequestAdapterOptions::default())).ok()?;
+        let (device, queue) = block_on(adapter.request_device(&wgpu::DeviceDescriptor {
+            label: Some("Render Test Device"),
+            required_features: features,
+            required_limits: limits.using_resolution(adapter.limits()),
+            memory_hints: wgpu::MemoryHints::default(),
+            experimental_features: wgpu::ExperimentalFeatures::default(),
+            trace: wgpu::Trace::Off,
+        }))
+        .ok()?;
+        Some(Self { device, queue })
+    }
+}
+
+fn surface_config() -> wgpu::SurfaceConfiguration {
+    wgpu::SurfaceConfiguration {
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+        format: FORMAT,
+        width: WIDTH,
+        height: HEIGHT,
+        present_mode: wgpu::PresentMode::Fifo,
+        alpha_mode: wgpu::CompositeAlphaMode::Opaque,
+        view_formats: vec![],
+        desired_maximum_frame_latency: 2,
+    }
+}
+
+/// Carbon dioxide with explicit double bonds, the carbon atom is in the center of the image.
+fn carbon_dioxide() -> AtomicCoordinates {
+    AtomicCoordinates {
+        atomic_num: vec![8, 6, 8],
+        x: vec![-1.16, 0.0, 1.16],
+        y: vec![0.0, 0.0, 0.0],
+        z: vec![0.0, 0.0, 0.0],
+        connectivity: Some(vec![
+            Connection {
+                atom_1: 0,
+                atom_2: 1,
+                order: 2,
+            },
+            Connection {
+                atom_1: 1,
+                atom_2: 2,
+                order: 2,
+            },
+        ]),
+        charges: None,
+        chains: None,
+        cell: None,
+        isotopes: None,
+    }
+}
+
+fn water() -> AtomicCoordinates {
+    AtomicCoordinates {
+        atomic_num: vec![8, 1, 1],
+        x: vec![0.0, 0.757, -0.757],
+        y: vec![0.0, 0.586, 0.586],
+        z: vec![0.0, 0.0, 0.0],
+        connectivity: None,
+        charges: None,
+        chains: None,
+        cell: None,
+        isotopes: None,
+    }
+}
+
+/// Single iron atom at the origin.
+fn iron() -> AtomicCoordinates {
+    AtomicCoordinates {
+        atomic_num: vec![26],
+        x: vec![0.0],
+        y: vec![0.0],
+        z: vec![0.0],
+        connectivity: None,
+        charges: None,
+        chains: None,
+        cell: None,
+        isotopes: None,
+    }
+}
+
+/// Hydrogen peroxide with the H-O-O-H dihedral of 120 degrees, the O-O bond is along the Y axis.
+fn hydrogen_peroxide() -> AtomicCoordinates {
+    AtomicCoordinates {
+        atomic_num: vec![1, 8, 8, 1],
+        x: vec![0.9, 0.0, 0.0, -0.45],
+        y: vec![1.0, 0.725, -0.725, -1.0],
+        z: vec![0.0, 0.0, 0.0, 0.779],
+        connectivity: None,
+        charges: None,
+        chains: None,
+        cell: None,
+        isotopes: None,
+    }
+}
+
+/// Gaussian centered at the origin on a 21×21×21 grid with 0.2 Angstrom steps.
+fn gaussian_cube() -> VolumeCube {
+    sampled_cube(|[x, y, z]| (-(x * x + y * y + z * z)).exp())
+}
+
+/// p orbital along X with the positive lobe at positive X on the grid of `gaussian_cube`.
+fn p_orbital_cube() -> VolumeCube {
+    sampled_cube(|[x, y, z]| x * (-(x * x + y * y + z * z)).exp())
+}
+
+fn sampled_cube(f: impl Fn([f64; 3]) -> f64) -> VolumeCube {
+    let n = 21;
+    let step = 0.2;
+    let start = -step * (n - 1) as f64 / 2.0;
+    let mut cube_data = Vec::with_capacity(n * n * n);
+    for i in 0..n {
+        for j in 0..n {
+            for k in 0..n {
+                cube_data.push(f([i, j, k].map(|index| start + step * index as f64)));
+            }
+        }
+    }
+    VolumeCube {
+        comment1: String::new(),
+        comment2: String::new(),
+        box_origin: vec![start; 3],
+        // Negative numbers of voxels mean Angstroms
+        steps_number: vec![-(n as i32); 3],
+        steps_size: vec![vec![step, 0.0, 0.0], vec![0.0, step, 0.0], vec![0.0, 0.0, step]],
+        cube_data,
+        dataset_id: None,
+        deferred: None,
+    }
+}
+
+fn load_volume_cube(scene: &mut Scene, gpu: &Gpu, cube: VolumeCube) -> Result<(), String> {
+    block_on(scene.load_volume_cube(
+        &gpu.device,
+        &gpu.queue,
+        cube,
+        &Progress::new(None),
+        &CancellationToken::none(),
+    ))
+}
+
+fn set_isosurface(scene: &mut Scene, gpu: &Gpu, settings: IsosurfaceSettings, visible: bool) -> Result<(), String> {
+    block_on(scene.set_isosurface(
+        &gpu.device,
+        settings,
+        visible,
+        &Progress::new(None),
+        &CancellationToken::none(),
+    ))
+}
+
+/// Loads the molecule into a new scene, lets `setup` adjust it and renders it into RGBA8 pixels.
+fn render<F>(gpu: &Gpu, config: &Config, data: &AtomicCoordinates, setup: F) -> Vec<u8>
+where
+    F: FnOnce(&mut Scene, &Gpu),
+{
+    let mut scene = Scene::new(&gpu.device, &surface_config(), config);
+    scene.projection_manager.set_viewport(WIDTH, HEIGHT);
+    scene.cpu_picking = true;
+    block_on(scene.load_atomic_coordinates(
+        &gpu.device,
+        config,
+        data,
+        &Progress::new(None),
+        &CancellationToken::none(),
+    ))
+    .unwrap();
+    setup(&mut scene, gpu);
+
+    render_offscreen(gpu, |view| {
+        scene.render_to_view(view, &gpu.device, &gpu.queue, config, 0)
+    })
+}
+
+/// Creates the render target, lets `draw` render into it and reads back RGBA8 pixels.
+fn render_offscreen<F>(gpu: &Gpu, draw: F) -> Vec<u8>
+where
+    F: FnOnce(&wgpu::TextureView),
+{
+    let texture = gpu.device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Render Test Texture"),
+        size: wgpu::Extent3d {
+            width: WIDTH,
+            height: HEIGHT,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: FORMAT,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+        view_formats: &[],
+    });
+    draw(&texture.create_view(&wgpu::TextureViewDescriptor::default()));
+
+    // WIDTH * 4 is a multiple of COPY_BYTES_PER_ROW_ALIGNMENT, so rows need no padding
+    let buffer = gpu.device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Render Test Readback Buffer"),
+        size: (WIDTH * HEIGHT * 4) as u64,
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+    let mut encoder = gpu.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("Render Test Readback Encoder"),
+    });
+    encoder.copy_texture_to_buffer(
+        texture.as_image_copy(),
+        wgpu::TexelCopyBufferInfo {
+            buffer: &buffer,
+            layout: wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(WIDTH * 4),
+                rows_per_image: Some(HEIGHT),
+            },
+        },
+        texture.size(),
+    );
+    gpu.queue.submit(std::iter::once(encoder.finish()));
+
+    let slice = buffer.slice(..);
+    slice.map_async(wgpu::MapMode::Read, |result| result.unwrap());
+    gpu.device
+        .poll(wgpu::PollType::Wait {
+            submission_index: None,
+            timeout: None,
+        })
+        .unwrap();
+    let pixels = slice.get_mapped_range().to_vec();
+    buffer.unmap();
+    pixels
+}
+
+fn golden_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("golden")
+        .join(format!("{name}.ppm"))
+}
+
+/// Binary PPM (P6), the alpha channel is dropped.
+fn write_ppm(path: &PathBuf, rgba: &[u8]) {
+    let mut data = format!("P6\n{WIDTH} {HEIGHT}\n255\n").into_bytes();
+    for pixel in rgba.chunks_exact(4) {
+        data.extend_from_slice(&pixel[..3]);
+    }
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, data).unwrap();
+}
+
+fn read_ppm(path: &PathBuf) -> Result<Vec<[u8; 3]>, String> {
+    let data = std::fs::read(path).map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+    let header = format!("P6\n{WIDTH} {HEIGHT}\n255\n").into_bytes();
+    if !data.starts_with(&header) || data.len() != header.len() + (WIDTH * HEIGHT * 3) as usize {
+        return Err(format!("{} is not a {WIDTH}×{HEIGHT} binary PPM image", path.display()));
+    }
+    Ok(data[header.len()..]
+        .chunks_exact(3)
+        .map(|pixel| [pixel[0], pixel[1], pixel[2]])
+        .collect())
+}
+
+/// Perceptual difference of two sRGB colors: weighted distance in YIQ space normalized to [0, 1],
+/// so changes of brightness weigh more than changes of hue as they do for the eye.
+fn color_difference(a: [u8; 3], b: [u8; 3]) -> f64 {
+    let yiq = |c: [u8; 3]| {
+        let [r, g, b] = c.map(|v| v as f64 / 255.0);
+        [
+            0.29889531 * r + 0.58662247 * g + 0.11448223 * b,
+            0.59597799 * r - 0.27417610 * g - 0.32180189 * b,
+            0.21147017 * r - 0.52261711 * g + 0.31114694 * b,
+        ]
+    };
+    let ([y1, i1, q1], [y2, i2, q2]) = (yiq(a), yiq(b));
+    let delta = 0.5053 * (y1 - y2).powi(2) + 0.299 * (i1 - i2).powi(2) + 0.1957 * (q1 - q2).powi(2);
+    // 0.5053 is the largest possible delta (black vs white)
+    (delta / 0.5053).sqrt()
+}
+
+fn assert_matches_golden(name: &str, rgba: &[u8]) {
+    let path = golden_path(name);
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        write_ppm(&path, rgba);
+        return;
+    }
+
+    let golden = read_ppm(&path).unwrap_or_else(|e| panic!("{e}. Run the tests with UPDATE_GOLDEN=1 to create it."));
+    let changed = rgba
+        .chunks_exact(4)
+        .zip(&golden)
+        .filter(|(pixel, expected)| color_difference([pixel[0], pixel[1], pixel[2]], **expected) > PIXEL_THRESHOLD)
+        .count();
+    let share = changed as f64 / (WIDTH * HEIGHT) as f64;
+    if share > MAX_CHANGED_PIXELS {
+        let actual = std::env::temp_dir().join(format!("{name}.actual.ppm"));
+        write_ppm(&actual, rgba);
+        panic!(
+            "{name}: {changed} pixels ({:.2}%) differ from {}, the rendered image is saved to {}",
+            share * 100.0,
+            path.display(),
+            actual.display()
+        );
+    }
+}
+
+fn run<F>(name: &str, config: &Config, data: &AtomicCoordinates, setup: F)
+where
+    F: FnOnce(&mut Scene, &Gpu),
+{
+    let Some(gpu) = Gpu::new() else {
+        eprintln!("{name}: skipped, no GPU adapter available");
+        return;
+    };
+    let rgba = render(&gpu, config, data, setup);
+    assert_matches_golden(name, &rgba);
+}
+
+/// Renders the scene with the GPU-driven path, both with one multi-draw call and with a draw per layer,
+/// the images must match the golden image of the direct path.
+fn run_gpu_driven<F>(name: &str, data: &AtomicCoordinates, setup: F)
+where
+    F: Fn(&mut Scene, &Gpu),
+{
+    let mut config = Config::new();
+    config.lod.gpu_culling_min_atoms = 0;
+    for features in [wgpu::Features::INDIRECT_FIRST_INSTANCE, wgpu::Features::empty()] {
+        let Some(gpu) = Gpu::compute(features) else {
+            eprintln!("{name}: skipped, no GPU adapter with compute shaders and {features:?} available");
+            continue;
+        };
+        let rgba = render(&gpu, &config, data, &setup);
+        assert_matches_golden(name, &rgba);
+    }
+}
+
+#[test]
+fn small_molecule() {
+    run("small_molecule", &Config::new(), &water(), |scene, _| {
+        scene.transform.rotate(20.0, 30.0, 0.0);
+    });
+}
+
+#[test]
+fn multiple_bonds() {
+    run("multiple_bonds", &Config::new(), &carbon_dioxide(), |scene, _| {
+        scene.transform.rotate(0.0, 0.0, 30.0);
+    });
+}
+
+#[test]
+fn transparent_selection() {
+    run(
+        "transparent_selection",
+        &Config::new(),
+        &carbon_dioxide(),
+        |scene, gpu| {
+            let selected = block_on(scene.toggle_atom_selection(WIDTH / 2, HEIGHT / 2, &gpu.device, &gpu.queue));
+            assert!(selected, "No atom in the center of the image");
+        },
+    );
+}
+
+/// Selection and highlighting changes written between frames into the existing instance buffers, with the
+/// selection buffer growing and slots shifting, give the same image as the final state built at once.
+#[test]
+fn incremental_selection_updates() {
+    let Some(gpu) = Gpu::new() else {
+        eprintln!("incremental_selection_updates: skipped, no GPU adapter available");
+        return;
+    };
+    let config = Config::new();
+    let (center_x, center_y) = (WIDTH / 2, HEIGHT / 2);
+    let frame = |scene: &mut Scene| {
+        render_offscreen(&gpu, |view| {
+            scene.render_to_view(view, &gpu.device, &gpu.queue, &config, 0)
+        })
+    };
+
+    let incremental = render(&gpu, &config, &carbon_dioxide(), |scene, gpu| {
+        assert!(scene.select_atoms(&[1]).unwrap());
+        frame(scene);
+        assert!(scene.select_atoms(&[2, 3]).unwrap());
+        frame(scene);
+        // Deselects the carbon atom in the middle of the selection and highlights it
+        assert!(block_on(scene.toggle_atom_selection(
+            center_x,
+            center_y,
+            &gpu.device,
+            &gpu.queue
+        )));
+        let (info, _) = block_on(scene.new_cursor_position(center_x, center_y, &gpu.device, &gpu.queue));
+        assert_eq!(info.map(|info| info.tag()), Some(2));
+    });
+    let at_once = render(&gpu, &config, &carbon_dioxide(), |scene, gpu| {
+        assert!(scene.select_atoms(&[1, 3]).unwrap());
+        block_on(scene.new_cursor_position(center_x, center_y, &gpu.device, &gpu.queue));
+    });
+    assert!(
+        incremental == at_once,
+        "Incremental updates differ from the buffers built at once"
+    );
+}
+
+/// Object classes are kept in the high byte of picking IDs, atom IDs are the same as without classes.
+#[test]
+fn picking_ids() {
+    let objects = [
+        PickedObject::Atom(0),
+        PickedObject::Atom(41),
+        PickedObject::Bond(0),
+        PickedObject::Bond(0xff_fffe),
+        PickedObject::Label(7),
+        PickedObject::Measurement,
+        PickedObject::Plane(1),
+    ];
+    for object in objects {
+        let id = object.picking_id();
+        assert_eq!(PickedObject::from_picking_id(id), Some(object));
+        let [r, g, b, a] = id_to_color(id);
+        assert_eq!(color_to_id(r, g, b, a), id);
+    }
+    assert_eq!(PickedObject::Atom(41).picking_id(), 42);
+    // Indices beyond 24 bits are not pickable
+    assert_eq!(PickedObject::Bond(0xff_ffff).picking_id(), 0);
+    assert_eq!(PickedObject::from_picking_id(0), None);
+    assert_eq!(PickedObject::from_picking_id(5 << 24 | 1), None);
+}
+
+/// Objects under every 8th pixel along both axes found by CPU ray casting and read back from the picking texture.
+fn pick_grid(scene: &mut Scene, gpu: &Gpu) -> Vec<(Option<PickedObject>, Option<PickedObject>)> {
+    let mut objects = Vec::new();
+    for y in (4..HEIGHT).step_by(8) {
+        for x in (4..WIDTH).step_by(8) {
+            scene.cpu_picking = true;
+            let cpu = block_on(scene.pick_object(x, y, &gpu.device, &gpu.queue));
+            scene.cpu_picking = false;
+            let gpu = block_on(scene.pick_object(x, y, &gpu.device, &gpu.queue));
+            objects.push((cpu, gpu));
+        }
+    }
+    objects
+}
+
+/// Both picking paths find the same objects, apart from a few pixels on the edges.
+fn assert_picking_paths_agree(objects: &[(Option<PickedObject>, Option<PickedObject>)]) {
+    let picked = objects
+        .iter()
+        .filter(|(cpu, gpu)| cpu.is_some() || gpu.is_some())
+        .count();
+    let differ = objects.iter().filter(|(cpu, gpu)| cpu != gpu).count();
+    assert!(picked > 0, "Nothing picked");
+    assert!(
+        differ as f64 <= 0.05 * picked as f64,
+        "CPU and GPU picking differ in {differ} of {picked} pixels"
+    );
+}
+
+#[test]
+fn bond_picking() {
+    let Some(gpu) = Gpu::new() else {
+        eprintln!("bond_picking: skipped, no GPU adapter available");
+        return;
+    };
+    let config = Config::new();
+    render(&gpu, &config, &water(), |scene, gpu| {
+        render_offscreen(gpu, |view| {
+            scene.render_to_view(view, &gpu.device, &gpu.queue, &config, 0)
+        });
+        let objects = pick_grid(scene, gpu);
+        assert_picking_paths_agree(&objects);
+
+        let bonds: Vec<PickedObject> = objects
+            .iter()
+            .filter_map(|&(_, gpu)| gpu)
+            .filter(|object| matches!(object, PickedObject::Bond(_)))
+            .collect();
+        assert!(!bonds.is_empty(), "No bond picked");
+        for bond in bonds {
+            let mut atoms = scene.pick_info(bond).unwrap().atoms();
+            atoms.sort();
+            assert!(atoms == [1, 2] || atoms == [1, 3], "{atoms:?}");
+        }
+    });
+}
+
+/// Lines of the measurement, its label, labels of atoms and disks of planes are picked along with atoms and bonds.
+#[test]
+fn measurement_and_label_picking() {
+    let Some(gpu) = Gpu::new() else {
+        eprintln!("measurement_and_label_picking: skipped, no GPU adapter available");
+        return;
+    };
+    let config = Config::new();
+    render(&gpu, &config, &water(), |scene, gpu| {
+        scene.select_atoms(&[2, 1, 3]).unwrap();
+        scene.add_plane(&[1, 2, 3]).unwrap();
+        scene.set_labels_visible(LabelKind::Symbol, true);
+        render_offscreen(gpu, |view| {
+            scene.render_to_view(view, &gpu.device, &gpu.queue, &config, 0)
+        });
+        let objects = pick_grid(scene, gpu);
+        assert_picking_paths_agree(&objects);
+
+        let found = |kind: &str| {
+            objects
+                .iter()
+                .any(|(_, gpu)| gpu.is_some_and(|object| object.kind() == kind))
+        };
+        for kind in ["atom", "bond", "label", "measurement", "plane"] {
+            assert!(found(kind), "No {kind} picked");
+        }
+        let plane = scene.pick_info(PickedObject::Plane(0)).unwrap();
+        assert_eq!(
+            (plane.kind(), plane.index(), plane.atoms()),
+            ("plane".to_string(), 1, vec![1, 2, 3])
+        );
+        assert_eq!(scene.pick_info(PickedObject::Measurement).unwrap().atoms(), [2, 1, 3]);
+        assert_eq!(scene.pick_info(PickedObject::Plane(1)).map(|info| info.kind()), None);
+    });
+}
+
+#[test]
+fn triangle_meshes() {
+    let mut config = Config::new();
+    config.style.geometry.ray_casting = false;
+    run("triangle_meshes", &config, &water(), |scene, _| {
+        scene.transform.rotate(20.0, 30.0, 0.0);
+    });
+}
+
+#[test]
+fn coarse_sphere_meshes() {
+    let mut config = Config::new();
+    config.style.geometry.ray_casting = false;
+    config.style.geometry.sphere_coarse_radius_pixels = f32::MAX;
+    run("coarse_sphere_meshes", &config, &water(), |scene, _| {
+        scene.transform.rotate(20.0, 30.0, 0.0);
+    });
+}
+
+#[test]
+fn volume_slice() {
+    run("volume_slice", &Config::new(), &water(), |scene, gpu| {
+        load_volume_cube(scene, gpu, gaussian_cube()).unwrap();
+        let settings = SliceSettings {
+            normal: Vec3::new(0.0, 0.0, 1.0),
+            colormap: Colormap::Viridis,
+            ..SliceSettings::default()
+        };
+        scene.set_slice(&gpu.device, &gpu.queue, settings, true).unwrap();
+    });
+}
+
+/// Lobes of a p orbital around water, opaque and translucent. They are built once a cube is loaded.
+#[test]
+fn isosurface() {
+    let settings = IsosurfaceSettings {
+        isovalue: 0.3,
+        ..IsosurfaceSettings::default()
+    };
+    run("isosurface", &Config::new(), &water(), |scene, gpu| {
+        set_isosurface(scene, gpu, settings, true).unwrap();
+        assert!(scene.isosurface().is_none());
+        load_volume_cube(scene, gpu, p_orbital_cube()).unwrap();
+        let lobes = &scene.isosurface().unwrap().lobes;
+        assert_eq!(lobes.len(), 2);
+        assert_eq!(lobes[0].num_indices, lobes[1].num_indices);
+        let invalid = IsosurfaceSettings {
+            isovalue: 0.0,
+            ..settings
+        };
+        assert!(set_isosurface(scene, gpu, invalid, true).is_err());
+        scene.transform.rotate(20.0, 30.0, 0.0);
+    });
+    run("translucent_isosurface", &Config::new(), &water(), |scene, gpu| {
+        load_volume_cube(scene, gpu, p_orbital_cube()).unwrap();
+        let translucent = IsosurfaceSettings {
+            opacity: 0.5,
+            ..settings
+        };
+        set_isosurface(scene, gpu, translucent, true).unwrap();
+        scene.transform.rotate(20.0, 30.0, 0.0);
+    });
+    // A density has no negative lobe
+    let Some(gpu) = Gpu::new() else {
+        return;
+    };
+    render(&gpu, &Config::new(), &water(), |scene, gpu| {
+        load_volume_cube(scene, gpu, gaussian_cube()).unwrap();
+        set_isosurface(scene, gpu, settings, true).unwrap();
+        assert_eq!(scene.isosurface().unwrap().lobes.len(), 1);
+        set_isosurface(scene, gpu, settings, false).unwrap();
+        assert!(scene.isosurface().is_none());
+    });
+}
+
+/// Lobes of a cube opened without its structure, the empty structure is centered on the box of the cube.
+#[test]
+fn isosurface_without_atoms() {
+    let empty = AtomicCoordinates {
+        atomic_num: vec![],
+        x: vec![],
+        y: vec![],
+        z: vec![],
+        connectivity: None,
+        charges: None,
+        chains: None,
+        cell: None,
+        isotopes: None,
+    };
+    let settings = IsosurfaceSettings {
+        isovalue: 0.3,
+        ..IsosurfaceSettings::default()
+    };
+    run("isosurface_without_atoms", &Config::new(), &empty, |scene, gpu| {
+        assert!(scene.volume_bounds().is_none());
+        load_volume_cube(scene, gpu, p_orbital_cube()).unwrap();
+        let (min, max) = scene.volume_bounds().unwrap();
+        assert!((0..3).all(|c| min[c] < max[c]));
+        set_isosurface(scene, gpu, settings, true).unwrap();
+        scene.transform.rotate(20.0, 30.0, 0.0);
+    });
+}
+
+/// An unknown element fails the build in its chunk, the remaining chunks are not processed.
+#[test]
+fn unknown_element_stops_build() {
+    let Some(gpu) = Gpu::new() else {
+        return;
+    };
+    let num_atoms = 2 * DEFAULT_CHUNK_SIZE;
+    let mut data = AtomicCoordinates {
+        atomic_num: vec![6; num_atoms],
+        x: (0..num_atoms).map(|i| 2.0 * i as f64).collect(),
+        y: vec![0.0; num_atoms],
+        z: vec![0.0; num_atoms],
+        connectivity: None,
+        charges: None,
+        chains: None,
+        cell: None,
+        isotopes: None,
+    };
+    data.atomic_num[1] = 999;
+    let chunks = std::rc::Rc::new(std::cell::Cell::new(0));
+    let cancel = CancellationToken::new({
+        let chunks = chunks.clone();
+        move || {
+            chunks.set(chunks.get() + 1);
+            false
+        }
+    });
+    let result = block_on(Molecule::new(
+        &gpu.device,
+        &Config::new(),
+        &data,
+        &Progress::new(None),
+        &cancel,
+    ));
+    assert_eq!(result.err().unwrap(), "Atom not found for atomic number: 999");
+    assert_eq!(chunks.get(), 1);
+}
+
+#[test]
+fn hidden_atom() {
+    run("hidden_atom", &Config::new(), &water(), |scene, gpu| {
+        scene.set_atom_visible(&gpu.queue, 2, false).unwrap();
+        scene.transform.rotate(20.0, 30.0, 0.0);
+    });
+}
+
+/// Hiding the hydrogen atoms of water by element leaves the oxygen atom without bonds.
+#[test]
+fn element_visibility() {
+    run("element_visibility", &Config::new(), &water(), |scene, gpu| {
+        assert_eq!(scene.element_counts(), vec![(1, 2, 2), (8, 1, 1)]);
+        assert!(scene.set_element_visible(&gpu.queue, 6, false).is_err());
+        scene.set_element_visible(&gpu.queue, 1, false).unwrap();
+        scene.set_element_visible(&gpu.queue, 8, false).unwrap();
+        scene.set_element_visible(&gpu.queue, 8, true).unwrap();
+        assert_eq!(scene.element_counts(), vec![(1, 2, 0), (8, 1, 1)]);
+        scene.transform.rotate(20.0, 30.0, 0.0);
+    });
+}
+
+/// Water with a ghost atom, a point charge and a dummy atom of an atomic number without a style: all of them are
+/// drawn, none of them is bonded and they are hidden together at load or afterwards.
+#[test]
+fn pseudo_atoms() {
+    let Some(gpu) = Gpu::new() else {
+        eprintln!("pseudo_atoms: skipped, no GPU adapter available");
+        return;
+    };
+    let embedded = || {
+        let mut data = water();
+        for (number, x) in [(GHOST_ATOM, 0.8), (POINT_CHARGE, -3.0), (0, 3.0)] {
+            data.atomic_num.push(number);
+            data.x.push(x);
+            data.y.push(0.0);
+            data.z.push(0.0);
+        }
+        data
+    };
+    let mut config = Config::new();
+    render(&gpu, &config, &embedded(), |scene, gpu| {
+        assert_eq!(
+            scene.element_counts(),
+            vec![
+                (GHOST_ATOM, 1, 1),
+                (POINT_CHARGE, 1, 1),
+                (0, 1, 1),
+                (1, 2, 2),
+                (8, 1, 1)
+            ]
+        );
+        assert_eq!(scene.atom_info(4).unwrap().symbol(), "Bq");
+        assert_eq!(scene.atom_info(6).unwrap().symbol(), "X");
+        // The ghost atom is within a bond length of the oxygen atom
+        assert_eq!(scene.bonds().len(), 2);
+        scene.set_pseudo_atoms_visible(&gpu.queue, false);
+        let visible: Vec<usize> = scene.element_counts().iter().map(|&(_, _, visible)| visible).collect();
+        assert_eq!(visible, vec![0, 0, 0, 2, 1]);
+    });
+
+    config.show_pseudo_atoms = false;
+    render(&gpu, &config, &embedded(), |scene, gpu| {
+        let visible: Vec<usize> = scene.element_counts().iter().map(|&(_, _, visible)| visible).collect();
+        assert_eq!(visible, vec![0, 0, 0, 2, 1]);
+        scene.set_pseudo_atoms_visible(&gpu.queue, true);
+        assert!(
+            scene
+                .element_counts()
+                .iter()
+                .all(|&(_, count, visible)| count == visible)
+        );
+    });
+}
+
+/// The oxygen atom of water enlarged and colored green and a hydrogen atom colored red keep their overrides when
+/// the representation changes to licorice, bonds take the colors of their atoms.
+#[test]
+fn atom_style_overrides() {
+    run("atom_style_overrides", &Config::new(), &water(), |scene, gpu| {
+        let config = representation_config(Representation::Licorice);
+        let green = AtomStyle {
+            radius: Some(0.6),
+            color: Some(Color::new(0.1, 0.8, 0.2, 1.0)),
+        };
+        let red = AtomStyle {
+            radius: None,
+            color: Some(Color::new(0.9, 0.1, 0.1, 1.0)),
+        };
+        assert!(scene.set_atom_style(&gpu.device, &config, &[4], green).is_err());
+        let negative = AtomStyle {
+            radius: Some(-1.0),
+            color: None,
+        };
+        assert!(scene.set_atom_style(&gpu.device, &config, &[1], negative).is_err());
+        scene.set_atom_style(&gpu.device, &config, &[1], green).unwrap();
+        scene.set_atom_style(&gpu.device, &config, &[2, 3], red).unwrap();
+        scene
+            .set_atom_style(&gpu.device, &config, &[3], AtomStyle::default())
+            .unwrap();
+        let styles = scene.atom_styles();
+        assert_eq!(styles.iter().map(|&(i, _)| i).collect::<Vec<_>>(), vec![0, 1]);
+        assert_eq!(styles[0].1.radius, Some(0.6));
+        assert_eq!(styles[1].1.radius, None);
+        scene.set_representation(&gpu.device, &config);
+        scene.transform.rotate(20.0, 30.0, 0.0);
+    });
+}
+
+/// The length of an O-H bond of water is drawn at its middle, the label of the other bond would overlap it and
+/// is left out.
+#[test]
+fn bond_length_labels() {
+    assert_eq!(BondLabels::from_name("Selection"), Ok(BondLabels::Selected));
+    assert!(BondLabels::from_name("angles").is_err());
+    run("bond_length_labels", &Config::new(), &water(), |scene, _| {
+        scene.set_bond_labels(BondLabels::All);
+    });
+}
+
+#[test]
+fn zoomed_in() {
+    // The oxygen atoms are partially or fully outside of the image
+    run("zoomed_in", &Config::new(), &carbon_dioxide(), |scene, _| {
+        scene.transform.rotate(0.0, 0.0, 30.0);
+        scene.transform.scale(Vec3::new(2.5, 2.5, 2.5));
+    });
+}
+
+#[test]
+fn orthographic_projection() {
+    run("orthographic_projection", &Config::new(), &water(), |scene, _| {
+        scene.set_projection_mode(ProjectionMode::Orthographic);
+        scene.transform.rotate(20.0, 30.0, 0.0);
+    });
+}
+
+/// Resetting after zooming and changing the field of view gives the initial picture.
+#[test]
+fn reset_view() {
+    run("small_molecule", &Config::new(), &water(), |scene, _| {
+        assert!(scene.set_fov(5.0).is_err());
+        scene.set_fov(30.0).unwrap();
+        scene.transform.rotate(40.0, -10.0, 15.0);
+        scene.transform.scale(Vec3::new(2.0, 2.0, 2.0));
+        scene.set_fov(45.0).unwrap();
+        scene.reset_view();
+        scene.transform.rotate(20.0, 30.0, 0.0);
+    });
+}
+
+/// With the hydrogen atoms hidden the oxygen atoms fill the image.
+#[test]
+fn zoom_to_fit() {
+    run("zoom_to_fit", &Config::new(), &hydrogen_peroxide(), |scene, gpu| {
+        scene.set_atom_visible(&gpu.queue, 1, false).unwrap();
+        scene.set_atom_visible(&gpu.queue, 4, false).unwrap();
+        scene.transform.rotate(0.0, 0.0, 60.0);
+        scene.zoom_to_fit();
+    });
+}
+
+/// Rotating the O-O bond of hydrogen peroxide turns the molecule into the planar trans conformer.
+#[test]
+fn torsion_driver() {
+    let bond = |atom_1, atom_2| Connection {
+        atom_1,
+        atom_2,
+        order: 1,
+    };
+    let mut data = hydrogen_peroxide();
+    data.connectivity = Some(vec![bond(0, 1), bond(1, 2), bond(2, 3)]);
+    run("torsion_driver", &Config::new(), &data, |scene, gpu| {
+        let config = Config::new();
+        assert!(scene.rotate_bond(&gpu.device, &config, 1, 3, 10.0).is_err());
+        scene.select_atoms(&[1, 2, 3, 4]).unwrap();
+        scene.rotate_bond(&gpu.device, &config, 2, 3, 30.0).unwrap();
+        let dihedral = scene.measurement().unwrap().value();
+        assert!((dihedral - 150.0).abs() < 0.1, "{}", dihedral);
+
+        scene.set_dihedral(&gpu.device, &config, [1, 2, 3, 4], -170.0).unwrap();
+        let dihedral = scene.measurement().unwrap().value();
+        assert!((dihedral + 170.0).abs() < 0.1, "{}", dihedral);
+        scene.set_dihedral(&gpu.device, &config, [1, 2, 3, 4], 180.0).unwrap();
+        // The O-O bond and the hydrogen atom of the first oxygen stay in place
+        let coordinates = scene.atom_coordinates();
+        assert!((coordinates[0][0] - 0.9).abs() < 1e-5 && (coordinates[2][1] + 0.725).abs() < 1e-5);
+        scene.clear_selection();
+        scene.transform.rotate(20.0, 30.0, 0.0);
+    });
+
+    let mut ring = water();
+    ring.connectivity = Some(vec![bond(0, 1), bond(1, 2), bond(2, 0)]);
+    if let Some(gpu) = Gpu::new() {
+        render(&gpu, &Config::new(), &ring, |scene, gpu| {
+            let error = scene.rotate_bond(&gpu.device, &Config::new(), 1, 2, 10.0).unwrap_err();
+            assert!(error.contains("ring"), "{}", error);
+        });
+    }
+}
+
+/// Replacing a hydrogen atom of water by a methyl group gives methanol in the same view.
+#[test]
+fn template_attachment() {
+    run("template_attachment", &Config::new(), &water(), |scene, gpu| {
+        let config = Config::new();
+        let view = format!("{:?}", scene.view_state());
+        let methanol = templates::attach(&water(), 1, templates::find_template("methyl").unwrap()).unwrap();
+        block_on(scene.replace_atomic_coordinates(&gpu.device, &config, &methanol)).unwrap();
+        assert_eq!(scene.bonds().len(), 5);
+        let coordinates = scene.atom_coordinates();
+        let length = (0..3)
+            .map(|a| (coordinates[2][a] - coordinates[0][a]).powi(2))
+            .sum::<f64>()
+            .sqrt();
+        assert!((length - 1.38).abs() < 0.01, "{}", length);
+        assert_eq!(format!("{:?}", scene.view_state()), view);
+    });
+}
+
+/// A click on the middle of the canvas adds an atom at the center of the structure in the builder mode.
+#[test]
+fn builder_edits() {
+    let Some(gpu) = Gpu::new() else {
+        eprintln!("builder_edits: skipped, no GPU adapter available");
+        return;
+    };
+    let config = Config::new();
+    render(&gpu, &config, &water(), |scene, gpu| {
+        let view = format!("{:?}", scene.view_state());
+        let center = scene.screen_point(WIDTH as f32 / 2.0, HEIGHT as f32 / 2.0).unwrap();
+        let centroid = [0.0, 0.586 * 2.0 / 3.0, 0.0];
+        let distance = |a: [f64; 3], b: [f64; 3]| (0..3).map(|k| (a[k] - b[k]).powi(2)).sum::<f64>().sqrt();
+        assert!(distance(center, centroid) < 0.05, "{center:?}");
+        let aside = scene.screen_point(WIDTH as f32, HEIGHT as f32 / 2.0).unwrap();
+        assert!(distance(aside, centroid) > 1.0, "{aside:?}");
+
+        let data = editing::add_atom(&water(), 6, aside).unwrap();
+        block_on(scene.replace_atomic_coordinates(&gpu.device, &config, &data)).unwrap();
+        assert!(distance(scene.atom_coordinates()[3], aside) < 1e-5);
+        assert_eq!(format!("{:?}", scene.view_state()), view);
+    });
+}
+
+/// Moving a hydrogen atom of water in the symmetry-constrained mode moves the other one as its mirror image,
+/// the oxygen atom on the rotation axis moves only along it.
+#[test]
+fn symmetry_editing() {
+    run("symmetry_editing", &Config::new(), &water(), |scene, gpu| {
+        let config = Config::new();
+        assert_eq!(scene.detect_symmetry(0.05).unwrap(), "C2v");
+        assert_eq!(scene.symmetry().unwrap().equivalent_atoms(1), vec![1, 2]);
+        scene
+            .move_atom(&gpu.device, &config, 2, Vec3::new(0.2, -0.1, 0.3))
+            .unwrap();
+        scene
+            .move_atom(&gpu.device, &config, 1, Vec3::new(0.3, -0.2, 0.1))
+            .unwrap();
+
+        let coordinates = scene.atom_coordinates();
+        let close = |a: [f64; 3], b: [f64; 3]| a.iter().zip(b).all(|(x, y)| (x - y).abs() < 1e-5);
+        assert!(close(coordinates[0], [0.0, -0.2, 0.0]), "{:?}", coordinates[0]);
+        assert!(close(coordinates[1], [0.957, 0.486, 0.0]), "{:?}", coordinates[1]);
+        assert!(close(coordinates[2], [-0.957, 0.486, 0.0]), "{:?}", coordinates[2]);
+        assert_eq!(scene.detect_symmetry(0.05).unwrap(), "C2v");
+
+        scene.clear_symmetry();
+        scene
+            .move_atom(&gpu.device, &config, 3, Vec3::new(0.0, 0.3, 0.0))
+            .unwrap();
+        assert_eq!(scene.detect_symmetry(0.05).unwrap(), "Cs");
+        scene.transform.rotate(20.0, 30.0, 0.0);
+    });
+}
+
+/// Halfway between the frames the stretched O-H bond has the mean length, the bond is kept.
+#[test]
+fn animation_frames() {
+    run("animation_frames", &Config::new(), &water(), |scene, gpu| {
+        let config = Config::new();
+        let mut stretched = water();
+        stretched.x[1] *= 1.5;
+        stretched.y[1] *= 1.5;
+        let mut wrong_atoms = water();
+        wrong_atoms.atomic_num[2] = 9;
+        assert!(scene.set_frames(&gpu.device, &config, &[water(), wrong_atoms]).is_err());
+        scene.set_frames(&gpu.device, &config, &[water(), stretched]).unwrap();
+        scene.select_atoms(&[1, 2]).unwrap();
+        let frames = scene.frame_coordinates();
+        assert_eq!(frames.len(), 2);
+        assert!((frames[1][1][0] - 1.1355).abs() < 1e-6 && (frames[1][1][1] - 0.879).abs() < 1e-6);
+
+        let animation = scene.animation_mut().unwrap();
+        assert!(animation.set_frame_rate(0.0).is_err());
+        animation.set_frame_rate(10.0).unwrap();
+        animation.play();
+        assert!(!scene.advance_animation(&gpu.device, &config, 1000.0));
+        assert!(scene.advance_animation(&gpu.device, &config, 1050.0));
+        assert!((scene.animation().unwrap().frame() - 0.5).abs() < 1e-9);
+        let distance = scene.measurement().unwrap().value();
+        assert!(
+            (distance - 1.5 * 0.957).abs() > 0.1 && (distance - 1.25 * 0.957).abs() < 1e-3,
+            "{}",
+            distance
+        );
+
+        // Steps wrap around to the first frame
+        let animation = scene.animation_mut().unwrap();
+        animation.step(1);
+        assert!(!animation.is_playing());
+        assert_eq!(animation.frame(), 1.0);
+        animation.step(1);
+        assert_eq!(animation.frame(), 0.0);
+        animation.step(-1);
+        assert_eq!(animation.frame(), 1.0);
+        animation.set_frame(0).unwrap();
+        animation.play();
+        scene.advance_animation(&gpu.device, &config, 2000.0);
+        scene.advance_animation(&gpu.device, &config, 2050.0);
+        scene.transform.rotate(20.0, 30.0, 0.0);
+    });
+}
+
+/// Halfway between the frames the values of the atoms are interpolated: oxygen is in the middle of the colormap,
+/// the hydrogens are at its ends.
+#[test]
+fn frame_property_colors() {
+    let mut config = Config::new();
+    config.style.color_scheme = ColorScheme::Property;
+    run("frame_property_colors", &config, &water(), |scene, gpu| {
+        let properties = || Some(vec![vec![0.0, 0.0, 1.0], vec![1.0, 0.0, 2.0]]);
+        assert!(scene.set_frame_properties(&gpu.device, &config, properties()).is_err());
+        scene.set_frames(&gpu.device, &config, &[water(), water()]).unwrap();
+        assert!(
+            scene
+                .set_frame_properties(&gpu.device, &config, Some(vec![vec![0.0; 3]]))
+                .is_err()
+        );
+        assert!(
+            scene
+                .set_frame_properties(&gpu.device, &config, Some(vec![vec![0.0; 3], vec![0.0; 2]]))
+                .is_err()
+        );
+        let nan = Some(vec![vec![0.0; 3], vec![0.0, f64::NAN, 0.0]]);
+        assert!(scene.set_frame_properties(&gpu.device, &config, nan).is_err());
+        scene.set_frame_properties(&gpu.device, &config, properties()).unwrap();
+
+        let animation = scene.animation_mut().unwrap();
+        animation.play();
+        scene.advance_animation(&gpu.device, &config, 1000.0);
+        scene.advance_animation(&gpu.device, &config, 1050.0);
+        let (values, range) = scene.animation().unwrap().property_values().unwrap();
+        assert_eq!(values, vec![0.5, 0.0, 1.5]);
+        assert_eq!(range, [0.0, 2.0]);
+        scene.transform.rotate(20.0, 30.0, 0.0);
+    });
+}
+
+/// Frames are written as consecutive structures by formats holding sequences, the others take one structure.
+#[test]
+fn structure_export() {
+    let ion = || AtomicCoordinates {
+        charges: Some(vec![-0.4, 0.7, 0.7]),
+        ..water()
+    };
+    let mut stretched = ion();
+    stretched.x[1] *= 1.5;
+    let frames = vec![("Frame 1".to_string(), ion()), ("Frame 2".to_string(), stretched)];
+
+    let xyz = structure_export::export_structures(&frames, "XYZ", Some(r#"{"xyz": {"precision": 4}}"#)).unwrap();
+    let lines: Vec<&str> = xyz.lines().collect();
+    assert_eq!(lines.len(), 10);
+    assert_eq!(lines[1], "Frame 1");
+    assert_eq!(lines[6], "Frame 2");
+    assert!(lines[8].starts_with("H") && lines[8].contains("1.1355"), "{}", lines[8]);
+    assert_eq!(
+        structure_export::export_structures(&frames, "pdb", None)
+            .unwrap()
+            .matches("ENDMDL")
+            .count(),
+        2
+    );
+
+    assert!(structure_export::export_structures(&frames, "gaussian", None).is_err());
+    assert!(structure_export::export_structures(&frames[..0], "xyz", None).is_err());
+    assert!(structure_export::export_structures(&frames, "cif", None).is_err());
+    assert!(structure_export::export_structures(&frames, "xyz", Some("{")).is_err());
+    // The total charge is the rounded sum of the atomic charges
+    let gaussian = structure_export::export_structures(&frames[..1], "gaussian", None).unwrap();
+    assert!(gaussian.contains("\n1 2\n"), "{}", gaussian);
+
+    let formats = structure_export::formats();
+    let multi_frame: Vec<&str> = formats.iter().filter(|f| f.multi_frame).map(|f| f.name).collect();
+    assert_eq!(multi_frame, vec!["xyz", "pdb"]);
+}
+
+/// Heavy water read with labelled hydrogens, the labels are edited and reported back with the symbols.
+#[test]
+fn isotope_labels() {
+    let Some(gpu) = Gpu::new() else {
+        eprintln!("isotope_labels: skipped, no GPU adapter available");
+        return;
+    };
+    let config = Config::new();
+    let heavy_water = AtomicCoordinates {
+        isotopes: Some(vec![0, 2, 0]),
+        ..water()
+    };
+    render(&gpu, &config, &heavy_water, |scene, _| {
+        assert_eq!(scene.mass_numbers(), vec![0, 2, 0]);
+        assert_eq!(scene.atom_info(2).unwrap().symbol(), "D");
+        scene.set_mass_number(3, 3).unwrap();
+        scene.set_mass_number(1, 18).unwrap();
+        assert_eq!(scene.atom_info(1).unwrap().symbol(), "18O");
+        assert_eq!(scene.atom_info(3).unwrap().symbol(), "T");
+        assert!(scene.set_mass_number(1, 7).is_err());
+        assert!(scene.set_mass_number(4, 2).is_err());
+        scene.set_mass_number(2, 0).unwrap();
+        assert_eq!(scene.mass_numbers(), vec![18, 0, 3]);
+    });
+}
+
+/// Two atoms in a cubic cell, the cell is made monoclinic and longer with the atoms following it.
+#[test]
+fn cell_editing() {
+    let data = AtomicCoordinates {
+        atomic_num: vec![11, 17],
+        x: vec![0.0, 1.41],
+        y: vec![0.0, 1.41],
+        z: vec![0.0, 1.41],
+        connectivity: None,
+        charges: None,
+        chains: None,
+        cell: Some(UnitCell::from_parameters([2.82, 2.82, 2.82, 90.0, 90.0, 90.0]).unwrap()),
+        isotopes: None,
+    };
+    let close = |a: [f64; 3], b: [f64; 3]| (0..3).all(|i| (a[i] - b[i]).abs() < 1e-4);
+    run("cell_editing", &Config::new(), &data, |scene, gpu| {
+        let config = Config::new();
+        let [a, b, c, alpha, beta, gamma] = scene.cell().unwrap().parameters();
+        assert!((a - 2.82).abs() < 1e-9 && b == a && c == a);
+        assert!([alpha, beta, gamma].iter().all(|angle| (angle - 90.0).abs() < 1e-9));
+        assert!(UnitCell::from_parameters([1.0, 1.0, 1.0, 10.0, 10.0, 170.0]).is_err());
+        assert!(UnitCell::new([[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, -1.0]]).is_err());
+
+        // Cartesian positions are kept
+        let cell = UnitCell::from_parameters([3.0, 3.0, 3.0, 90.0, 90.0, 90.0]).unwrap();
+        scene.set_cell(&gpu.device, &config, cell, false).unwrap();
+        assert!(close(scene.atom_coordinates()[1], [1.41, 1.41, 1.41]));
+
+        // Fractional coordinates are kept
+        let cell = UnitCell::from_parameters([3.0, 3.0, 4.0, 90.0, 105.0, 90.0]).unwrap();
+        scene.set_cell(&gpu.device, &config, cell, true).unwrap();
+        let coordinates = scene.atom_coordinates();
+        assert!(close(coordinates[0], [0.0; 3]), "{:?}", coordinates[0]);
+        assert!(close(cell.to_fractional(coordinates[1]), [0.47; 3]));
+        scene.transform.rotate(20.0, 30.0, 0.0);
+        scene.transform.scale(Vec3::new(0.4, 0.4, 0.4));
+    });
+
+    if let Some(gpu) = Gpu::new() {
+        render(&gpu, &Config::new(), &water(), |scene, gpu| {
+            let cell = UnitCell::from_parameters([3.0, 3.0, 3.0, 90.0, 90.0, 90.0]).unwrap();
+            assert!(scene.set_cell(&gpu.device, &Config::new(), cell, true).is_err());
+            scene.set_cell(&gpu.device, &Config::new(), cell, false).unwrap();
+        });
+    }
+}
+
+/// The ion pair of the cell_editing structure replicated into 3 × 2 × 1 cells, the copies hide the hidden atom too.
+#[test]
+fn supercell() {
+    let data = AtomicCoordinates {
+        atomic_num: vec![11, 17],
+        x: vec![0.0, 1.41],
+        y: vec![0.0, 1.41],
+        z: vec![0.0, 1.41],
+        connectivity: None,
+        charges: None,
+        chains: None,
+        cell: Some(UnitCell::from_parameters([2.82, 2.82, 2.82, 90.0, 90.0, 90.0]).unwrap()),
+        isotopes: None,
+    };
+    run("supercell", &Config::new(), &data, |scene, gpu| {
+        assert_eq!(scene.supercell(), [1, 1, 1]);
+        assert!(scene.set_supercell([0, 1, 1]).is_err());
+        assert!(scene.set_supercell([11, 1, 1]).is_err());
+        scene.set_supercell([3, 2, 1]).unwrap();
+        assert_eq!(scene.supercell(), [3, 2, 1]);
+        scene.set_atom_visible(&gpu.queue, 2, false).unwrap();
+        scene.transform.rotate(20.0, 30.0, 0.0);
+        scene.transform.scale(Vec3::new(0.45, 0.45, 0.45));
+    });
+
+    if let Some(gpu) = Gpu::new() {
+        render(&gpu, &Config::new(), &water(), |scene, _| {
+            assert!(scene.set_supercell([2, 2, 2]).is_err());
+            scene.set_supercell([1, 1, 1]).unwrap();
+        });
+    }
+}
+
+/// Infinite carbon chain along a: the second bond of the atoms goes through the boundary of the cell
+/// and is drawn as two halves ending at the opposite faces.
+#[test]
+fn periodic_bonds() {
+    let data = AtomicCoordinates {
+        atomic_num: vec![6, 6],
+        x: vec![0.6, 1.85],
+        y: vec![3.0, 3.0],
+        z: vec![3.0, 3.0],
+        connectivity: None,
+        charges: None,
+        chains: None,
+        cell: Some(UnitCell::from_parameters([2.5, 6.0, 6.0, 90.0, 90.0, 90.0]).unwrap()),
+        isotopes: None,
+    };
+    let tolerance = Config::new().style.geom_bond_tolerance;
+    let mut bonds = Vec::new();
+    let search = connectivity::prepare(&data, tolerance, data.cell.as_ref());
+    connectivity::search_range(&search, 0..search.num_atoms(), &mut bonds);
+    let mut images: Vec<[i32; 3]> = bonds.iter().map(|bond| bond.image).collect();
+    images.sort();
+    assert_eq!(images, [[0, 0, 0], [1, 0, 0]]);
+    assert!(
+        bonds
+            .iter()
+            .all(|bond| (bond.atom_index_1, bond.atom_index_2) == (1, 0))
+    );
+    let markdown = Report::new(&data, &bonds, &Tolerances::default()).render(ReportFormat::Markdown);
+    assert_eq!(markdown.matches("| C1–C2 | 1.250 |").count(), 2, "{}", markdown);
+
+    run("periodic_bonds", &Config::new(), &data, |scene, _| {
+        assert_eq!(scene.bonds().len(), 2);
+        scene.transform.rotate(20.0, 30.0, 0.0);
+        scene.transform.scale(Vec3::new(0.7, 0.7, 0.7));
+    });
+
+    // Without the cell only the bond inside it is found
+    let molecule = AtomicCoordinates { cell: None, ..data };
+    let search = connectivity::prepare(&molecule, tolerance, None);
+    let mut bonds = Vec::new();
+    connectivity::search_range(&search, 0..search.num_atoms(), &mut bonds);
+    assert_eq!(bonds.len(), 1);
+
+    // A single atom of a narrow cell is bonded to its images on both sides by the same bond
+    let chain = AtomicCoordinates {
+        atomic_num: vec![6],
+        x: vec![0.0],
+        y: vec![0.0],
+        z: vec![0.0],
+        connectivity: None,
+        charges: None,
+        chains: None,
+        cell: Some(UnitCell::from_parameters([1.3, 6.0, 6.0, 90.0, 90.0, 90.0]).unwrap()),
+        isotopes: None,
+    };
+    let search = connectivity::prepare(&chain, tolerance, chain.cell.as_ref());
+    let mut bonds = Vec::new();
+    connectivity::search_range(&search, 0..search.num_atoms(), &mut bonds);
+    assert_eq!(
+        bonds.iter().map(|bond| bond.image.map(i32::abs)).collect::<Vec<_>>(),
+        [[1, 0, 0]]
+    );
+}
+
+/// RGBA8 pixels of a PNG file written by the capture.
+fn png_pixels(png: &[u8]) -> Vec<u8> {
+    // Signature, IHDR with the size, 8-bit RGBA, then a single IDAT chunk
+    assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+    assert_eq!(&png[12..16], b"IHDR");
+    assert_eq!(&png[16..24], [WIDTH.to_be_bytes(), HEIGHT.to_be_bytes()].concat());
+    assert_eq!(&png[24..26], [8, 6]);
+    assert_eq!(&png[37..41], b"IDAT");
+    let length = u32::from_be_bytes(png[33..37].try_into().unwrap()) as usize;
+    let mut data = Vec::new();
+    ZlibDecoder::new(&png[41..41 + length]).read_to_end(&mut data).unwrap();
+    let row = WIDTH as usize * 4 + 1;
+    assert_eq!(data.len(), row * HEIGHT as usize);
+    let rgba: Vec<u8> = data.chunks_exact(row).flat_map(|line| line[1..].to_vec()).collect();
+    rgba
+}
+
+/// Thumbnails are rendered once per structure and size, and the least recently used ones are dropped from the cache.
+#[test]
+fn thumbnail_cache() {
+    let Some(gpu) = Gpu::new() else {
+        return;
+    };
+    let mut thumbnails = Thumbnails::new(&gpu.device, FORMAT);
+    let data = water();
+    let hash = structure_hash(&data);
+    assert!(thumbnails.cached(hash, WIDTH).is_none());
+    let png = block_on(thumbnails.thumbnail(&gpu.device, &gpu.queue, &data, WIDTH)).unwrap();
+    assert_matches_golden("thumbnail", &png_pixels(&png));
+    assert_eq!(thumbnails.cached(hash, WIDTH), Some(png.as_slice()));
+    assert!(thumbnails.cached(hash, WIDTH / 2).is_none());
+    assert!(block_on(thumbnails.thumbnail(&gpu.device, &gpu.queue, &data, 0)).is_err());
+
+    let mut moved = water();
+    assert_eq!(structure_hash(&moved), hash);
+    moved.x[0] += 0.01;
+    assert_ne!(structure_hash(&moved), hash);
+
+    thumbnails.set_capacity(1);
+    let co2 = carbon_dioxide();
+    block_on(thumbnails.thumbnail(&gpu.device, &gpu.queue, &co2, WIDTH)).unwrap();
+    assert!(thumbnails.cached(hash, WIDTH).is_none());
+    assert!(thumbnails.cached(structure_hash(&co2), WIDTH).is_some());
+    thumbnails.clear();
+    assert!(thumbnails.cached(structure_hash(&co2), WIDTH).is_none());
+}
+
+/// Snapshots of edits are restored onto the structure they were taken from, with the history of edits.
+#[test]
+fn autosave_snapshot() {
+    let mut autosave = Autosave::new(&water());
+    assert_eq!(autosave.key().len(), 16);
+    assert!(!autosave.is_dirty() && !autosave.has_edits());
+    for _ in 0..3 {
+        autosave.record("Move atom 1".to_string());
+    }
+    autosave.record("Rotate bond 1-2".to_string());
+    assert!(autosave.is_dirty());
+    let history = [("Move atom 1", 3), ("Rotate bond 1-2", 1)].map(|(description, count)| Edit {
+        description: description.to_string(),
+        count,
+    });
+    assert_eq!(autosave.edits(), history);
+
+    let mut edited = water();
+    edited.x[0] += 0.5;
+    let snapshot = autosave.snapshot(edited, 1.7e12).unwrap();
+    assert!(!autosave.is_dirty() && autosave.has_edits());
+
+    let mut reopened = Autosave::new(&water());
+    assert_eq!(reopened.key(), autosave.key());
+    let restored = reopened.restore(&snapshot).unwrap();
+    assert_eq!(restored.x[0], water().x[0] + 0.5);
+    assert_eq!(reopened.edits(), history);
+    assert!(!reopened.is_dirty());
+    assert!(Autosave::new(&carbon_dioxide()).restore(&snapshot).is_err());
+    assert!(reopened.restore("{}").is_err());
+
+    reopened.reset(&restored);
+    assert_ne!(reopened.key(), autosave.key());
+    assert!(!reopened.has_edits());
+}
+
+/// Summary of water with its perceived bonds in both formats, frequencies and the image are set by the caller.
+#[test]
+fn structure_report() {
+    let data = water();
+    let search = connectivity::prepare(&data, Config::new().style.geom_bond_tolerance, None);
+    let mut bonds = Vec::new();
+    connectivity::search_range(&search, 0..search.num_atoms(), &mut bonds);
+    let mut report = Report::new(&data, &bonds, &Tolerances::default());
+    report.frequencies = vec![-50.0, 1595.0];
+    report.image = Some(vec![0, 1, 2, 3]);
+
+    let markdown = report.render(ReportFormat::from_name("md").unwrap());
+    for line in [
+        "# H2O",
+        "| Formula | H2O |",
+        "| Molecular weight | 18.015 g/mol |",
+        "| Point group | C2v |",
+        "| O1–H2 | 0.957 |",
+        "| H2–O1–H3 | 104.5 |",
+        "| Mode | Wavenumber (cm⁻¹) |",
+        "| 1 | 50.0i |",
+        "![H2O](data:image/png;base64,AAECAw==)",
+    ] {
+        assert!(markdown.lines().any(|l| l == line), "{}\n{}", line, markdown);
+    }
+
+    let constants = inertia::rotational_constants(&data).unwrap().map(Option::unwrap);
+    for (constant, expected) in constants.iter().zip([822.2, 437.5, 285.6]) {
+        assert!((constant - expected).abs() < 0.1, "{:?}", constants);
+    }
+    let linear = inertia::rotational_constants(&carbon_dioxide()).unwrap();
+    assert!(
+        linear[0].is_none() && (linear[1].unwrap() - 11.7).abs() < 0.1,
+        "{:?}",
+        linear
+    );
+
+    report.title = "H2O <optimized>".to_string();
+    let html = report.render(ReportFormat::Html);
+    assert!(html.contains("<h1>H2O &lt;optimized&gt;</h1>"));
+    assert!(html.contains("<tr><th>Point group</th><td>C2v</td></tr>"));
+    assert!(html.contains("<tr><th>Bond</th><th>Length (Å)</th></tr>"));
+    assert!(html.contains("<img src=\"data:image/png;base64,AAECAw==\""));
+    assert!(ReportFormat::from_name("pdf").is_err());
+}
+
+/// Supersampled capture of the rotated water molecule decoded from the PNG file, the view on the screen is kept.
+#[test]
+fn image_capture() {
+    let Some(gpu) = Gpu::new() else {
+        return;
+    };
+    let config = Config::new();
+    let mut png = Vec::new();
+    let rotate = |scene: &mut Scene| scene.transform.rotate(20.0, 30.0, 0.0);
+    let pixels = render(&gpu, &config, &water(), |scene, gpu| {
+        rotate(scene);
+        let capture = |scene: &mut Scene, supersampling| {
+            block_on(scene.capture_image(
+                &gpu.device,
+                &gpu.queue,
+                &config,
+                &surface_config(),
+                WIDTH,
+                HEIGHT,
+                supersampling,
+            ))
+        };
+        assert!(capture(scene, 0).is_err());
+        png = capture(scene, 3).unwrap();
+    });
+    assert_eq!(pixels, render(&gpu, &config, &water(), |scene, _| rotate(scene)));
+
+    let rgba = png_pixels(&png);
+    assert!(rgba.chunks_exact(4).all(|pixel| pixel[3] == 255));
+    assert_matches_golden("image_capture", &rgba);
+}
+
+/// Symmetric stretch of water at the largest displacement, the stretched bonds are kept and the arrows point
+/// along them.
+#[test]
+fn vibration_mode() {
+    run("vibration_mode", &Config::new(), &water(), |scene, gpu| {
+        let config = Config::new();
+        let stretch = vec![[0.0, -0.077, 0.0], [0.791, 0.612, 0.0], [-0.791, 0.612, 0.0]];
+        let bend = vec![[0.0, 0.07, 0.0], [-0.612, -0.55, 0.0], [0.612, -0.55, 0.0]];
+        let modes = VibrationalModes {
+            frequencies: vec![3657.0, 1595.0],
+            displacements: vec![stretch.clone(), bend],
+            atomic_coordinates: None,
+        };
+        let wrong_atoms = VibrationalModes {
+            frequencies: vec![3657.0],
+            displacements: vec![stretch[..2].to_vec()],
+            atomic_coordinates: None,
+        };
+        assert!(scene.set_vibrational_modes(&gpu.device, &config, &wrong_atoms).is_err());
+        scene.set_vibrational_modes(&gpu.device, &config, &modes).unwrap();
+        scene.select_atoms(&[1, 2]).unwrap();
+
+        // A quarter of the oscillation at 1 Hz reaches the largest displacement of 0.3 Angstroms
+        let vibration = scene.vibration_mut().unwrap();
+        assert!(vibration.set_amplitude(0.0).is_err());
+        assert!(vibration.set_mode(2).is_err());
+        vibration.play();
+        assert!(!scene.advance_vibration(&gpu.device, &config, 1000.0));
+        assert!(scene.advance_vibration(&gpu.device, &config, 1050.0));
+        assert!(scene.advance_vibration(&gpu.device, &config, 1150.0));
+        assert!(scene.advance_vibration(&gpu.device, &config, 1250.0));
+        let stretched = 0.957 + 0.3 + 0.3 * 0.077 * 0.612;
+        let distance = scene.measurement().unwrap().value();
+        assert!((distance - stretched).abs() < 2e-3, "{}", distance);
+
+        // The atoms return to the equilibrium
+        scene.vibration_mut().unwrap().stop();
+        scene.update_vibration(&gpu.device, &config);
+        assert!((scene.measurement().unwrap().value() - 0.957).abs() < 1e-3);
+
+        let vibration = scene.vibration_mut().unwrap();
+        vibration.set_arrows_visible(true);
+        vibration.set_phase(0.25);
+        scene.update_vibration(&gpu.device, &config);
+        scene.clear_selection();
+        scene.transform.scale(Vec3::new(0.5, 0.5, 0.5));
+        scene.transform.rotate(20.0, 30.0, 0.0);
+    });
+}
+
+/// Navigation of the camera is undone by the opposite moves, panning keeps the target under the cursor
+/// in both projections and the limits clamp the distance and the pitch.
+#[test]
+fn camera_navigation() {
+    run("small_molecule", &Config::new(), &water(), |scene, _| {
+        scene.transform.rotate(20.0, 30.0, 0.0);
+        let distance = scene.camera.distance();
+        let screen = |scene: &mut Scene, point: Vec3<f32>| {
+            let matrix = *scene.projection_manager.get_matrix() * *scene.camera.get_matrix();
+            let ndc = matrix.transform_point(point);
+            ((ndc.x + 1.0) / 2.0 * WIDTH as f32, (1.0 - ndc.y) / 2.0 * HEIGHT as f32)
+        };
+
+        for mode in [ProjectionMode::Orthographic, ProjectionMode::Perspective] {
+            scene.set_projection_mode(mode);
+            let target = scene.camera.target();
+            let (x, y) = screen(scene, target);
+            scene.pan_camera(16.0, -8.0, WIDTH, HEIGHT);
+            let (panned_x, panned_y) = screen(scene, target);
+            assert!(
+                (panned_x - x - 16.0).abs() < 1e-3 && (panned_y - y + 8.0).abs() < 1e-3,
+                "{:?}",
+                mode
+            );
+            scene.pan_camera(-16.0, 8.0, WIDTH, HEIGHT);
+        }
+
+        scene.orbit_camera(30.0, 20.0);
+        assert!((scene.camera.distance() - distance).abs() < 1e-3);
+        scene.orbit_camera(0.0, -20.0);
+        scene.orbit_camera(-30.0, 0.0);
+        scene.dolly_camera(2.0);
+        assert!((scene.camera.distance() - distance / 2.0).abs() < 1e-3);
+        scene.dolly_camera(0.5);
+
+        let mut limits = scene.camera.limits();
+        limits.max_pitch = 90.0;
+        assert!(scene.set_camera_limits(limits).is_err());
+        limits.max_pitch = 60.0;
+        limits.max_distance = distance / 2.0;
+        scene.set_camera_limits(limits).unwrap();
+        assert!((scene.camera.distance() - distance / 2.0).abs() < 1e-3);
+        scene.orbit_camera(0.0, 100.0);
+        let offset = scene.camera.position() - scene.camera.target();
+        assert!(
+            (offset.y / offset.length() - 60.0_f32.to_radians().sin()).abs() < 1e-4,
+            "{:?}",
+            offset
+        );
+        scene.orbit_camera(0.0, -60.0);
+
+        scene.set_camera_limits(CameraLimits::default()).unwrap();
+        scene.dolly_camera(0.5);
+        assert!((scene.camera.distance() - distance).abs() < 1e-3);
+    });
+}
+
+/// The camera flies to a hydrogen of the rotated water, which ends up in the center of the view with
+/// the other atoms dimmed.
+#[test]
+fn focus_on_atoms() {
+    run("focus_on_atoms", &Config::new(), &water(), |scene, gpu| {
+        scene.transform.rotate(20.0, 30.0, 0.0);
+        let distance = scene.camera.distance();
+        assert!(scene.focus_on(&gpu.queue, &[], true).is_err());
+        assert!(scene.focus_on(&gpu.queue, &[4], true).is_err());
+
+        scene.focus_on(&gpu.queue, &[2], true).unwrap();
+        assert!(scene.is_camera_flying());
+        assert!(scene.step_camera_flight(1000.0));
+        assert_eq!(scene.camera.distance(), distance);
+        scene.step_camera_flight(1250.0);
+        let halfway = scene.camera.distance();
+        assert!(scene.is_camera_flying());
+        scene.step_camera_flight(2000.0);
+        assert!(!scene.is_camera_flying() && !scene.step_camera_flight(2100.0));
+        // Halfway in time is halfway in distance with the symmetric easing
+        let end = scene.camera.distance();
+        assert!((halfway - (distance + end) / 2.0).abs() < 1e-3 && end != distance);
+
+        // The hydrogen is picked in the center, selected and deselected again
+        for _ in 0..2 {
+            block_on(scene.toggle_atom_selection(WIDTH / 2, HEIGHT / 2, &gpu.device, &gpu.queue));
+        }
+        assert_eq!(scene.picked_object(), Some(PickedObject::Atom(1)));
+    });
+}
+
+/// In the focus mode the selected corner atom of a lattice and its three neighbors, one of them nitrogen, keep
+/// their colors, the rest of the lattice is desaturated.
+#[test]
+fn focus_mode() {
+    run("focus_mode", &Config::new(), &lattice(4), |scene, gpu| {
+        let desaturate = FocusMode {
+            cutoff: 1.6,
+            dimming: Dimming::from_name("Desaturate").unwrap(),
+        };
+        assert!(Dimming::from_name("blur").is_err());
+        let invalid = FocusMode {
+            cutoff: -1.0,
+            ..desaturate
+        };
+        assert!(scene.set_focus_mode(&gpu.queue, Some(invalid)).is_err());
+        scene.set_focus_mode(&gpu.queue, Some(desaturate)).unwrap();
+        scene.select_atoms(&[64]).unwrap();
+        scene.transform.rotate(20.0, 30.0, 0.0);
+    });
+}
+
+/// A neon atom moved next to water is drawn in the warning color together with the oxygen atom, moving it
+/// back or turning the check off clears the highlighting.
+#[test]
+fn clash_highlight() {
+    let config = Config::new();
+    let mut data = water();
+    data.atomic_num.push(10);
+    data.x.push(0.0);
+    data.y.push(-4.0);
+    data.z.push(0.0);
+    run("clash_highlight", &config, &data, |scene, gpu| {
+        assert!(scene.set_clash_check(Some(1.5)).is_err());
+        let (there, back) = (Vec3::new(0.0, 2.0, 0.0), Vec3::new(0.0, -2.0, 0.0));
+        scene.move_atom(&gpu.device, &config, 4, there).unwrap();
+        let clashes = scene.clashes();
+        assert_eq!(clashes.len(), 1);
+        assert_eq!((clashes[0].atom_index_1, clashes[0].atom_index_2), (0, 3));
+        assert!((clashes[0].distance - 2.0).abs() < 1e-5);
+        scene.move_atom(&gpu.device, &config, 4, back).unwrap();
+        assert!(scene.clashes().is_empty());
+
+        scene.set_clash_check(None).unwrap();
+        scene.move_atom(&gpu.device, &config, 4, there).unwrap();
+        assert!(scene.clashes().is_empty());
+        scene.set_clash_check(Some(contacts::DEFAULT_CLASH_FRACTION)).unwrap();
+        assert_eq!(scene.clashes().len(), 1);
+    });
+}
+
+/// A carbon atom overlapping a hydrogen atom of water gets a warning halo together with it and is listed in the
+/// report, bonds of water are not clashes and turning the check off drops the halos.
+#[test]
+fn covalent_clash_halos() {
+    let config = Config::new();
+    let mut data = water();
+    data.atomic_num.push(6);
+    data.x.push(1.0);
+    data.y.push(0.9);
+    data.z.push(0.0);
+    let clashes = contacts::find_covalent_clashes(&data, contacts::DEFAULT_COVALENT_CLASH_FRACTION);
+    assert_eq!(clashes.len(), 1);
+    assert_eq!((clashes[0].atom_index_1, clashes[0].atom_index_2), (1, 3));
+    // Bonds of water are about as long as the sums of the covalent radii
+    assert!(contacts::find_covalent_clashes(&water(), 1.0).is_empty());
+
+    let bonds = connectivity::find_bonds(&data, 0.15);
+    let mut report = Report::new(&data, &bonds, &Tolerances::default());
+    report.add_clashes(&data, &clashes);
+    let markdown = report.render(ReportFormat::Markdown);
+    assert!(markdown.contains("## Clashes"), "{}", markdown);
+    assert!(
+        markdown.contains("| H2···C4 | 0.397 (37% of covalent radii) |"),
+        "{}",
+        markdown
+    );
+
+    run("covalent_clash_halos", &config, &data, |scene, gpu| {
+        assert!(scene.set_covalent_clash_check(Some(0.0)).is_err());
+        assert_eq!(
+            scene.covalent_clash_check(),
+            Some(contacts::DEFAULT_COVALENT_CLASH_FRACTION)
+        );
+        assert_eq!(scene.covalent_clashes().len(), 1);
+        let away = Vec3::new(3.0, 0.0, 0.0);
+        scene.move_atom(&gpu.device, &config, 4, away).unwrap();
+        assert!(scene.covalent_clashes().is_empty());
+        scene.move_atom(&gpu.device, &config, 4, -away).unwrap();
+        scene.set_covalent_clash_check(None).unwrap();
+        assert!(scene.covalent_clashes().is_empty());
+        scene
+            .set_covalent_clash_check(Some(contacts::DEFAULT_COVALENT_CLASH_FRACTION))
+            .unwrap();
+        assert_eq!(scene.covalent_clashes().len(), 1);
+    });
+}
+
+/// Carbon dioxide along the Z axis of the file is turned horizontal with the default tilt, the rotation is
+/// kept by resetting the view and dropped by turning the orientation off.
+#[test]
+fn auto_orientation() {
+    let mut data = carbon_dioxide();
+    std::mem::swap(&mut data.x, &mut data.z);
+    run("auto_orientation", &Config::new(), &data, |scene, _| {
+        let positions = |scene: &Scene| -> Vec<Vec3<f32>> {
+            let coordinates = scene.atom_coordinates();
+            coordinates
+                .iter()
+                .map(|p| Vec3::new(p[0] as f32, p[1] as f32, p[2] as f32))
+                .collect()
+        };
+        let screen = |scene: &Scene| -> Vec<Vec3<f32>> {
+            let matrix = scene.transform.rotation.to_rotation_matrix();
+            positions(scene)
+                .into_iter()
+                .map(|p| matrix.transform_point(p))
+                .collect()
+        };
+        scene.orient(false);
+        let file = screen(scene);
+        assert!((file[0].x - file[2].x).abs() < 1e-4 && (file[0].y - file[2].y).abs() < 1e-4);
+
+        scene.orient(true);
+        let oriented = screen(scene);
+        let d = oriented[2] - oriented[0];
+        let [_, yaw] = AUTO_ORIENTATION_TILT;
+        assert!((d.x.abs() - 2.32 * yaw.to_radians().cos()).abs() < 1e-3 && d.y.abs() < 1e-4);
+
+        scene.transform.rotate(40.0, 0.0, 10.0);
+        scene.reset_view();
+        assert!(
+            scene
+                .transform
+                .rotation
+                .approx_eq(view::principal_orientation(&positions(scene)))
+        );
+    });
+}
+
+/// Translations of the root and the molecule node add up, the oxygen atom is moved into the center. Hidden nodes
+/// hide the molecule from picking.
+#[test]
+fn scene_graph_nodes() {
+    run("scene_graph_nodes", &Config::new(), &carbon_dioxide(), |scene, _| {
+        let center = |scene: &mut Scene| scene.object_at(WIDTH / 2, HEIGHT / 2);
+        assert_eq!(center(scene), Some(PickedObject::Atom(1)));
+        assert!(scene.node_mut(2).is_none());
+
+        scene
+            .node_mut(ROOT_NODE)
+            .unwrap()
+            .transform
+            .set_position(Vec3::new(0.5, 0.0, 0.0));
+        let molecule = scene.node_mut(MOLECULE_NODE).unwrap();
+        molecule.transform.set_position(Vec3::new(0.66, 0.0, 0.0));
+        assert_eq!(center(scene), Some(PickedObject::Atom(0)));
+
+        for id in [ROOT_NODE, MOLECULE_NODE] {
+            scene.node_mut(id).unwrap().visible = false;
+            assert_eq!(center(scene), None);
+            scene.node_mut(id).unwrap().visible = true;
+        }
+        assert_eq!(center(scene), Some(PickedObject::Atom(0)));
+    });
+}
+
+/// A second water molecule is drawn next to the first one where its coordinates put it, structures are removed
+/// and centered by the ids of their nodes.
+#[test]
+fn multiple_structures() {
+    let mut next = water();
+    next.x.iter_mut().for_each(|x| *x += 2.0);
+    run("multiple_structures", &Config::new(), &water(), |scene, gpu| {
+        let id = block_on(scene.add_structure(&gpu.device, &Config::new(), next)).unwrap();
+        let other = block_on(scene.add_structure(&gpu.device, &Config::new(), carbon_dioxide())).unwrap();
+        assert_eq!(scene.structures(), vec![id, other]);
+        let offset = scene.node_mut(id).unwrap().transform.position;
+        assert!((offset.x - 2.0).abs() < 1e-5 && offset.y.abs() < 1e-5 && offset.z == 0.0);
+
+        assert!(scene.remove_structure(MOLECULE_NODE).is_err());
+        assert!(scene.center_structure(other + 1).is_err());
+        scene.node_mut(other).unwrap().visible = false;
+        scene.remove_structure(other).unwrap();
+        assert!(scene.remove_structure(other).is_err() && scene.node_mut(other).is_none());
+        assert_eq!(scene.structures(), vec![id]);
+
+        scene.center_structure(id).unwrap();
+        assert_eq!(scene.node_mut(id).unwrap().transform.position.x, 0.0);
+        scene.node_mut(id).unwrap().transform.set_position(offset);
+        scene.transform.set_scale(Vec3::new(0.5, 0.5, 0.5));
+    });
+}
+
+/// Water turned by 90° and moved is superposed onto the molecule, then put next to it to show that it is no longer
+/// turned.
+#[test]
+fn structure_alignment() {
+    let mut mobile = water();
+    (mobile.x, mobile.y) = (
+        mobile.y.iter().map(|y| 2.0 - y).collect(),
+        mobile.x.iter().map(|x| x + 0.5).collect(),
+    );
+    mobile.z = vec![1.0; 3];
+    let centroid = |data: &AtomicCoordinates| -> [f64; 3] {
+        [&data.x, &data.y, &data.z].map(|values| values.iter().sum::<f64>() / 3.0)
+    };
+    let (mobile_center, target_center) = (centroid(&mobile), centroid(&water()));
+    let mobile_positions: Vec<[f64; 3]> = (0..3).map(|i| [mobile.x[i], mobile.y[i], mobile.z[i]]).collect();
+    let target = water();
+
+    run("structure_alignment", &Config::new(), &water(), |scene, gpu| {
+        let id = block_on(scene.add_structure(&gpu.device, &Config::new(), mobile)).unwrap();
+        let tolerances = Tolerances::default();
+        assert!(scene.align_structure(id, id, None, &tolerances).is_err());
+        assert!(scene.align_structure(id, id + 1, None, &tolerances).is_err());
+        assert!(
+            scene
+                .align_structure(id, MOLECULE_NODE, Some(&[(0, 0), (3, 1)]), &tolerances)
+                .is_err()
+        );
+        assert!(
+            scene
+                .align_structure(id, MOLECULE_NODE, Some(&[]), &tolerances)
+                .is_err()
+        );
+
+        let alignment = scene.align_structure(id, MOLECULE_NODE, None, &tolerances).unwrap();
+        assert!(alignment.rmsd < 1e-6 && alignment.is_match(&tolerances));
+        // Atoms of the structure land on the atoms of the molecule in the space of the scene
+        let matrix = *scene.node_mut(id).unwrap().transform.get_matrix();
+        for (i, p) in mobile_positions.iter().enumerate() {
+            let local = Vec3::new(
+                (p[0] - mobile_center[0]) as f32,
+                (p[1] - mobile_center[1]) as f32,
+                (p[2] - mobile_center[2]) as f32,
+            );
+            let world = matrix.transform_point(local);
+            let expected = Vec3::new(
+                (target.x[i] - target_center[0]) as f32,
+                (target.y[i] - target_center[1]) as f32,
+                (target.z[i] - target_center[2]) as f32,
+            );
+            assert!(
+                (world - expected).length() < 1e-4,
+                "atom {i}: {world:?} instead of {expected:?}"
+            );
+        }
+
+        // Aligned by the oxygen atoms only, the structure is just moved onto the molecule
+        let alignment = scene
+            .align_structure(id, MOLECULE_NODE, Some(&[(0, 0)]), &tolerances)
+            .unwrap();
+        assert!(alignment.rmsd < 1e-9);
+        scene.align_structure(id, MOLECULE_NODE, None, &tolerances).unwrap();
+        let node = scene.node_mut(id).unwrap();
+        node.transform
+            .set_position(node.transform.position + Vec3::new(2.0, 0.0, 0.0));
+        scene.transform.set_scale(Vec3::new(0.5, 0.5, 0.5));
+    });
+}
+
+/// Dragging from the center by a quarter of the image turns the molecule by 30° about the vertical axis,
+/// after a quick release it keeps spinning until it slows down.
+#[test]
+fn arcball_rotation() {
+    run("arcball_rotation", &Config::new(), &water(), |scene, _| {
+        let rotation_of = |scene: &Scene| scene.view_state().rotation();
+        scene.begin_rotation(64.0, 64.0, 128, 128, 0.0);
+        assert!(scene.drag_rotation(96.0, 64.0, 16.0));
+        let half_angle = 15.0_f32.to_radians();
+        let rotation = rotation_of(scene);
+        let expected = [half_angle.cos(), 0.0, half_angle.sin(), 0.0];
+        assert!(
+            rotation.iter().zip(expected).all(|(a, b)| (a - b).abs() < 1e-4),
+            "{:?}",
+            rotation
+        );
+
+        assert!(scene.end_rotation(20.0));
+        assert!(scene.step_inertia(36.0));
+        assert!(rotation_of(scene)[2] > half_angle.sin());
+        // Without damping the molecule spins until the next drag
+        scene.set_inertia(Some(0.0)).unwrap();
+        assert!(scene.step_inertia(10_000.0) && scene.is_spinning());
+
+        // A release after the cursor stood still does not spin
+        scene.begin_rotation(64.0, 64.0, 128, 128, 20_000.0);
+        assert!(!scene.is_spinning());
+        assert!(scene.drag_rotation(64.0, 40.0, 20_016.0));
+        assert!(!scene.end_rotation(20_200.0));
+        assert!(scene.set_inertia(Some(1.5)).is_err());
+        scene.set_inertia(None).unwrap();
+        assert!(!scene.step_inertia(20_216.0));
+    });
+}
+
+#[test]
+fn gpu_driven_small_molecule() {
+    run_gpu_driven("small_molecule", &water(), |scene, _| {
+        scene.transform.rotate(20.0, 30.0, 0.0);
+    });
+}
+
+#[test]
+fn gpu_driven_hidden_atom() {
+    run_gpu_driven("hidden_atom", &water(), |scene, gpu| {
+        scene.set_atom_visible(&gpu.queue, 2, false).unwrap();
+        scene.transform.rotate(20.0, 30.0, 0.0);
+    });
+}
+
+#[test]
+fn gpu_driven_frustum_culling() {
+    run_gpu_driven("zoomed_in", &carbon_dioxide(), |scene, _| {
+        scene.transform.rotate(0.0, 0.0, 30.0);
+        scene.transform.scale(Vec3::new(2.5, 2.5, 2.5));
+    });
+}
+
+#[test]
+fn lod_simplified_bonds() {
+    let mut config = Config::new();
+    config.lod.min_atoms = 0;
+    config.lod.simplified_radius_pixels = f32::MAX;
+    config.lod.points_radius_pixels = 0.0;
+    run("lod_simplified_bonds", &config, &water(), |scene, _| {
+        scene.transform.rotate(20.0, 30.0, 0.0);
+    });
+}
+
+#[test]
+fn lod_point_sprites() {
+    let mut config = Config::new();
+    config.lod.min_atoms = 0;
+    config.lod.points_radius_pixels = f32::MAX;
+    run("lod_point_sprites", &config, &water(), |scene, _| {
+        scene.transform.rotate(20.0, 30.0, 0.0);
+    });
+}
+
+#[test]
+fn atom_labels() {
+    let mut config = Config::new();
+    config.style.label.size = 0.12;
+    run("atom_labels", &config, &water(), |scene, _| {
+        scene.set_labels_visible(LabelKind::Symbol, true);
+        scene.set_labels_visible(LabelKind::Index, true);
+        scene.set_atom_label(1, "q=-0.8").unwrap();
+    });
+}
+
+#[test]
+fn angle_measurement() {
+    let mut config = Config::new();
+    config.style.label.size = 0.12;
+    run("angle_measurement", &config, &water(), |scene, _| {
+        assert!(scene.select_atoms(&[2, 1, 3]).unwrap());
+        let measurement = scene.measurement().unwrap();
+        assert_eq!(measurement.kind(), "angle");
+        assert!((measurement.value() - 104.5).abs() < 0.1, "{}", measurement.value());
+    });
+}
+
+#[test]
+fn dihedral_measurement() {
+    let mut config = Config::new();
+    config.style.label.size = 0.12;
+    run("dihedral_measurement", &config, &hydrogen_peroxide(), |scene, _| {
+        assert!(scene.select_atoms(&[1, 2, 3, 4]).unwrap());
+        let measurement = scene.measurement().unwrap();
+        assert_eq!(measurement.kind(), "dihedral");
+        assert!(
+            (measurement.value().abs() - 120.0).abs() < 0.1,
+            "{}",
+            measurement.value()
+        );
+        scene.transform.rotate(30.0, 20.0, 0.0);
+    });
+}
+
+/// Two squares of carbon atoms, the second one is turned by 60° around the X axis.
+fn twisted_squares() -> AtomicCoordinates {
+    AtomicCoordinates {
+        atomic_num: vec![6; 8],
+        x: vec![-2.5, -2.5, -1.1, -1.1, 1.1, 1.1, 2.5, 2.5],
+        y: vec![0.7, -0.7, -0.7, 0.7, 0.35, -0.35, -0.35, 0.35],
+        z: vec![0.0, 0.0, 0.0, 0.0, 0.606, -0.606, -0.606, 0.606],
+        connectivity: None,
+        charges: None,
+        chains: None,
+        cell: None,
+        isotopes: None,
+    }
+}
+
+#[test]
+fn plane_angle_measurement() {
+    let mut config = Config::new();
+    config.style.label.size = 0.12;
+    run("plane_angle_measurement", &config, &twisted_squares(), |scene, _| {
+        assert!(scene.add_plane(&[1, 2]).is_err());
+        assert_eq!(scene.add_plane(&[1, 2, 3, 4]).unwrap(), 1);
+        assert_eq!(scene.add_plane(&[5, 6, 7, 8]).unwrap(), 2);
+        assert!(scene.plane(1).unwrap().rms_deviation() < 1e-3);
+
+        scene.measure_plane_bond(1, 5, 6).unwrap();
+        let measurement = scene.measurement().unwrap();
+        assert_eq!(measurement.kind(), "plane_bond_angle");
+        assert!((measurement.value() - 60.0).abs() < 0.1, "{}", measurement.value());
+
+        scene.measure_planes(1, 2).unwrap();
+        let measurement = scene.measurement().unwrap();
+        assert_eq!(measurement.kind(), "plane_angle");
+        assert!((measurement.value() - 60.0).abs() < 0.1, "{}", measurement.value());
+        assert_eq!(measurement.planes(), vec![1, 2]);
+        assert!(scene.measure_planes(1, 3).is_err());
+        scene.transform.rotate(-30.0, 15.0, 0.0);
+    });
+}
+
+#[test]
+fn rectangle_selection() {
+    run("rectangle_selection", &Config::new(), &carbon_dioxide(), |scene, _| {
+        // The right half of the image replaces the selection, the left half is added to it
+        assert!(scene.select_atoms(&[2]).unwrap());
+        assert!(scene.select_rect(WIDTH - 1, HEIGHT - 1, WIDTH / 2 + 4, 0, false));
+        assert_eq!(scene.selected_atoms(), vec![3]);
+        assert!(scene.select_rect(0, 0, WIDTH / 2 - 4, HEIGHT - 1, true));
+        assert_eq!(scene.selected_atoms(), vec![3, 1]);
+        assert!(!scene.select_rect(0, 0, WIDTH / 2 - 4, HEIGHT - 1, true));
+    });
+}
+
+/// Objects read back from a rectangle of the picking texture at once are the ones read pixel by pixel.
+#[test]
+fn picking_region() {
+    let Some(gpu) = Gpu::new() else {
+        eprintln!("picking_region: skipped, no GPU adapter available");
+        return;
+    };
+    render(&gpu, &Config::new(), &water(), |scene, gpu| {
+        scene.cpu_picking = false;
+        // Renders the picking texture
+        block_on(scene.pick_object(0, 0, &gpu.device, &gpu.queue));
+
+        let all = block_on(scene.read_picking_region(0, 0, WIDTH, HEIGHT, &gpu.device, &gpu.queue));
+        for i in 0..3 {
+            assert!(all.contains(&PickedObject::Atom(i)), "Atom {i} not found");
+        }
+        assert!(all.iter().any(|object| matches!(object, PickedObject::Bond(_))));
+        // Clipped to the texture
+        let clipped = block_on(scene.read_picking_region(0, 0, WIDTH * 2, HEIGHT * 2, &gpu.device, &gpu.queue));
+        assert_eq!(clipped, all);
+
+        let (x, y, width, height) = (WIDTH / 2 - 13, HEIGHT / 2 - 5, 21, 13);
+        let region = block_on(scene.read_picking_region(x, y, width, height, &gpu.device, &gpu.queue));
+        let mut pixels = std::collections::HashSet::new();
+        for j in y..y + height {
+            for i in x..x + width {
+                pixels.extend(block_on(scene.read_picking_pixel(i, j, &gpu.device, &gpu.queue)));
+            }
+        }
+        assert!(!region.is_empty());
+        assert_eq!(region, pixels);
+    });
+}
+
+/// Looking along the axis of carbon dioxide only the nearest oxygen atom is seen, the rectangle of
+/// the whole image selects all atoms, but only the nearest one is selected as visible.
+#[test]
+fn visible_rectangle_selection() {
+    let Some(gpu) = Gpu::new() else {
+        eprintln!("visible_rectangle_selection: skipped, no GPU adapter available");
+        return;
+    };
+    render(&gpu, &Config::new(), &carbon_dioxide(), |scene, gpu| {
+        scene.cpu_picking = false;
+        scene.transform.rotate(0.0, 90.0, 0.0);
+        assert!(scene.select_rect(0, 0, WIDTH - 1, HEIGHT - 1, false));
+        assert_eq!(scene.selected_atoms(), vec![1, 2, 3]);
+
+        let visible = |scene: &mut Scene| {
+            block_on(scene.select_visible_rect(0, 0, WIDTH - 1, HEIGHT - 1, false, &gpu.device, &gpu.queue))
+        };
+        assert!(visible(scene));
+        let selected = scene.selected_atoms();
+        assert!(selected == [1] || selected == [3], "{selected:?}");
+        assert!(!visible(scene));
+    });
+}
+
+/// The view of one scene applied to another one draws the same picture as rotating and zooming it directly.
+#[test]
+fn linked_view() {
+    run("zoomed_in", &Config::new(), &carbon_dioxide(), |scene, gpu| {
+        let mut linked = Scene::new(&gpu.device, &surface_config(), &Config::new());
+        linked.transform.rotate(0.0, 0.0, 30.0);
+        linked.transform.scale(Vec3::new(2.5, 2.5, 2.5));
+        scene.set_view_state(&linked.view_state());
+    });
+}
+
+/// Four structures in a 2×2 grid, then the third one expanded to the whole image by a click.
+#[test]
+fn conformer_grid() {
+    let Some(gpu) = Gpu::new() else {
+        eprintln!("conformer_grid: skipped, no GPU adapter available");
+        return;
+    };
+    let config = Config::new();
+    let mut grid = MoleculeGrid::new(&gpu.device, &surface_config());
+    let structures = [water(), carbon_dioxide(), hydrogen_peroxide(), water()];
+    for (i, data) in structures.iter().enumerate() {
+        block_on(grid.add_structure(
+            &gpu.device,
+            &config,
+            data,
+            &format!("Conformer {}", i + 1),
+            &Progress::new(None),
+            &CancellationToken::none(),
+        ))
+        .unwrap();
+    }
+    grid.rotate(20.0, 30.0, 0.0);
+
+    assert_eq!(grid.cell_at(WIDTH / 4, HEIGHT * 3 / 4), Some(2));
+    assert_eq!(grid.label(2), Some("Conformer 3"));
+    let rgba = render_offscreen(&gpu, |view| grid.render_to_view(view, &gpu.device, &gpu.queue, &config));
+    assert_matches_golden("conformer_grid", &rgba);
+
+    assert!(grid.toggle_expanded_at(WIDTH / 4, HEIGHT * 3 / 4));
+    assert_eq!(grid.expanded(), Some(2));
+    let rgba = render_offscreen(&gpu, |view| grid.render_to_view(view, &gpu.device, &gpu.queue, &config));
+    assert_matches_golden("conformer_grid_expanded", &rgba);
+
+    assert!(grid.toggle_expanded_at(WIDTH / 4, HEIGHT / 4));
+    assert_eq!(grid.expanded(), None);
+}
+
+fn representation_config(representation: Representation) -> Config {
+    let mut config = Config::new();
+    config.style.representation = representation;
+    config
+}
+
+#[test]
+fn spacefill_representation() {
+    let config = representation_config(Representation::Spacefill);
+    run("spacefill_representation", &config, &water(), |scene, _| {
+        scene.transform.rotate(20.0, 30.0, 0.0);
+    });
+}
+
+#[test]
+fn licorice_representation() {
+    let config = representation_config(Representation::Licorice);
+    run("licorice_representation", &config, &carbon_dioxide(), |scene, _| {
+        scene.transform.rotate(0.0, 0.0, 30.0);
+    });
+}
+
+#[test]
+fn wireframe_representation() {
+    let config = representation_config(Representation::Wireframe);
+    run("wireframe_representation", &config, &carbon_dioxide(), |scene, _| {
+        scene.transform.rotate(0.0, 0.0, 30.0);
+    });
+}
+
+/// The scheme is switched after loading, so atoms and bonds are recolored in place.
+#[test]
+fn cpk_color_scheme() {
+    let mut cpk = Config::new();
+    cpk.style.color_scheme = ColorScheme::Cpk;
+    run("cpk_color_scheme", &Config::new(), &carbon_dioxide(), |scene, gpu| {
+        scene.transform.rotate(0.0, 0.0, 30.0);
+        scene.set_color_scheme(&gpu.device, &cpk).unwrap();
+    });
+}
+
+/// Oxygen with the negative charge is blue, hydrogens are red.
+#[test]
+fn charge_color_scheme() {
+    let mut config = Config::new();
+    config.style.color_scheme = ColorScheme::Charge;
+    let data = AtomicCoordinates {
+        charges: Some(vec![-0.8, 0.4, 0.4]),
+        ..water()
+    };
+    run("charge_color_scheme", &config, &data, |scene, _| {
+        scene.transform.rotate(20.0, 30.0, 0.0);
+    });
+}
+
+#[test]
+fn outline_effect() {
+    let mut config = Config::new();
+    config.style.effects.outline = true;
+    run("outline_effect", &config, &hydrogen_peroxide(), |scene, _| {
+        scene.transform.rotate(20.0, 30.0, 0.0);
+    });
+}
+
+/// The camera focuses on the center of the molecule, the oxygen atoms turned to and away from it are blurred.
+#[test]
+fn depth_of_field_effect() {
+    let mut config = Config::new();
+    config.style.effects.depth_of_field = true;
+    config.style.effects.focus_range = 0.25;
+    run("depth_of_field_effect", &config, &carbon_dioxide(), |scene, _| {
+        scene.transform.rotate(0.0, 60.0, 30.0);
+    });
+}
+
+/// FXAA only changes pixels along the edges of the atoms, bonds and the translucent selection sphere.
+#[test]
+fn antialiasing_effect() {
+    let Some(gpu) = Gpu::new() else {
+        return;
+    };
+    let setup = |scene: &mut Scene, _: &Gpu| {
+        scene.transform.rotate(20.0, 30.0, 0.0);
+        scene.select_atoms(&[1]).unwrap();
+    };
+    let mut config = Config::new();
+    let aliased = render(&gpu, &config, &hydrogen_peroxide(), setup);
+    config.style.effects.antialiasing = true;
+    let smoothed = render(&gpu, &config, &hydrogen_peroxide(), setup);
+
+    let changed = aliased
+        .chunks_exact(4)
+        .zip(smoothed.chunks_exact(4))
+        .filter(|(a, b)| a != b)
+        .count();
+    assert!(changed > 0 && changed < (WIDTH * HEIGHT / 10) as usize, "{}", changed);
+    assert_matches_golden("antialiasing_effect", &smoothed);
+}
+
+/// Atoms at the back of the molecule blend toward the background, the front ones keep their colors.
+#[test]
+fn depth_fog() {
+    let Some(gpu) = Gpu::new() else {
+        return;
+    };
+    let setup = |scene: &mut Scene, _: &Gpu| {
+        scene.transform.rotate(0.0, 60.0, 0.0);
+    };
+    let mut config = Config::new();
+    let clear = render(&gpu, &config, &carbon_dioxide(), setup);
+    config.style.fog.enabled = true;
+    config.style.fog.set_range(0.2, 1.0).unwrap();
+    let foggy = render(&gpu, &config, &carbon_dioxide(), setup);
+
+    let background = &clear[..4];
+    let contrast = |rgba: &[u8]| {
+        rgba.chunks_exact(4)
+            .map(|p| (0..3).map(|c| p[c].abs_diff(background[c]) as u64).sum::<u64>())
+            .sum::<u64>()
+    };
+    assert_eq!(&foggy[..4], background);
+    assert!(
+        contrast(&foggy) < contrast(&clear),
+        "{} {}",
+        contrast(&foggy),
+        contrast(&clear)
+    );
+    assert!(config.style.fog.set_range(0.5, 0.5).is_err());
+    assert_matches_golden("depth_fog", &foggy);
+}
+
+#[test]
+fn publication_preset() {
+    let mut config = Config::new();
+    config.apply_preset("publication").unwrap();
+    run("publication_preset", &config, &carbon_dioxide(), |scene, _| {
+        scene.transform.rotate(0.0, 0.0, 30.0);
+    });
+}
+
+/// A preset applied after loading restyles the molecule in place.
+#[test]
+fn colorblind_safe_preset() {
+    let mut config = Config::new();
+    config.apply_preset("colorblind-safe").unwrap();
+    run(
+        "colorblind_safe_preset",
+        &Config::new(),
+        &hydrogen_peroxide(),
+        |scene, gpu| {
+            scene.transform.rotate(20.0, 30.0, 0.0);
+            scene.set_style(&gpu.device, &config).unwrap();
+        },
+    );
+}
+
+/// A registered preset with the settings of "publication" gives the same picture.
+#[test]
+fn custom_style_preset() {
+    let mut config = Config::new();
+    let json = r#"{"background_color": [1, 1, 1], "label_color": [0, 0, 0], "outline": true}"#;
+    assert!(
+        config
+            .presets
+            .register("Publication", StylePreset::from_json(json).unwrap())
+            .is_err()
+    );
+    config
+        .presets
+        .register("My paper", StylePreset::from_json(json).unwrap())
+        .unwrap();
+    config.apply_preset("my_paper").unwrap();
+    run("publication_preset", &config, &carbon_dioxide(), |scene, _| {
+        scene.transform.rotate(0.0, 0.0, 30.0);
+    });
+}
+
+#[test]
+fn style_settings() {
+    assert!(StyleSettings::from_json(r#"{"atom_radii": {"Xy": 0.3}}"#).is_err());
+    assert!(StyleSettings::from_json(r#"{"bond_radius": -0.1}"#).is_err());
+
+    let json = r#"{"background_color": [0.2, 0.3, 0.4], "atom_radii": {"O": 0.45, "1": 0.2}, "bond_radius": 0.15}"#;
+    let mut config = Config::new();
+    StyleSettings::from_json(json).unwrap().apply(&mut config.style);
+    run("style_settings", &config, &water(), |scene, _| {
+        scene.transform.rotate(20.0, 30.0, 0.0);
+    });
+}
+
+/// Ball-and-stick radii from a scaled radius set, elements missing from the set keep their display radii.
+#[test]
+fn radius_sets() {
+    let mut config = Config::new();
+    StyleSettings::from_json(r#"{"radius_set": "van der Waals", "radius_scale": 0.25}"#)
+        .unwrap()
+        .apply(&mut config.style);
+    let radii = config.style.radius_set.unwrap();
+    assert_eq!(radii.set, RadiusSet::VanDerWaals);
+    assert!((radii.radius(8).unwrap() - 0.38).abs() < 1e-6);
+    assert_eq!(radii.radius(POINT_CHARGE), None);
+
+    let ionic = ScaledRadii {
+        set: RadiusSet::from_name("ionic").unwrap(),
+        scale: 1.0,
+    };
+    assert_eq!(ionic.radius(11), Some(1.02));
+    assert_eq!(ionic.radius(1), None);
+    assert_eq!(RadiusSet::Covalent.radius(6), Some(0.75));
+    assert_eq!(RadiusSet::Covalent.radius(GHOST_ATOM), None);
+
+    StyleSettings::from_json(r#"{"radius_set": "display"}"#)
+        .unwrap()
+        .apply(&mut config.style);
+    assert_eq!(config.style.radius_set, None);
+    assert!(StyleSettings::from_json(r#"{"radius_set": "metallic"}"#).is_err());
+    assert!(StyleSettings::from_json(r#"{"radius_set": "vdw", "radius_scale": 0}"#).is_err());
+    assert!(StyleSettings::from_json(r#"{"radius_scale": 0.5}"#).is_err());
+}
+
+/// Structures opened without a representation from the host get one by their size, residues make small ones
+/// sticks.
+#[test]
+fn view_defaults() {
+    let mut defaults = ViewDefaults::new();
+    assert_eq!(defaults.representation(&water()), Some(Representation::BallAndStick));
+    assert_eq!(defaults.representation(&lattice(20)), Some(Representation::Licorice));
+    let mut protein = water();
+    protein.chains = Some(vec!["A".to_string(); 3]);
+    assert_eq!(defaults.representation(&protein), Some(Representation::Licorice));
+    defaults.licorice_max_atoms = 3;
+    assert_eq!(defaults.representation(&protein), Some(Representation::Wireframe));
+    defaults.enabled = false;
+    assert_eq!(defaults.representation(&water()), None);
+
+    let settings = StyleSettings::from_json(r#"{"representation": "sticks"}"#).unwrap();
+    assert_eq!(settings.representation, Some(Representation::Licorice));
+    assert!(StyleSettings::from_json(r#"{"representation": "cartoon"}"#).is_err());
+}
+
+/// With a negative tolerance the atoms are too far apart to be bonded, the bonds are removed after loading.
+#[test]
+fn bond_tolerance_update() {
+    let mut config = Config::new();
+    StyleSettings::from_json(r#"{"bond_tolerance": -0.5}"#)
+        .unwrap()
+        .apply(&mut config.style);
+    run(
+        "bond_tolerance_update",
+        &Config::new(),
+        &hydrogen_peroxide(),
+        |scene, gpu| {
+            scene.transform.rotate(20.0, 30.0, 0.0);
+            scene.set_bond_tolerance(&gpu.device, &config, &hydrogen_peroxide());
+            scene.set_style(&gpu.device, &config).unwrap();
+        },
+    );
+}
+
+/// Atoms and bonds of a structure large enough to be split between threads with the `parallel` feature come
+/// in the same order as from a single pass.
+/// Carbon and nitrogen atoms on a simple cubic lattice of `n` points along each axis, row by row.
+fn lattice(n: usize) -> AtomicCoordinates {
+    let mut data = AtomicCoordinates {
+        atomic_num: vec![],
+        x: vec![],
+        y: vec![],
+        z: vec![],
+        connectivity: None,
+        charges: None,
+        chains: None,
+        cell: None,
+        isotopes: None,
+    };
+    for i in 0..n * n * n {
+        data.atomic_num.push(if i % 3 == 0 { 7 } else { 6 });
+        data.x.push((i % n) as f64 * 1.5);
+        data.y.push((i / n % n) as f64 * 1.5);
+        data.z.push((i / n / n) as f64 * 1.5);
+    }
+    data
+}
+
+/// Zoomed into a corner of a large lattice, skipping the chunks of instances outside of the view must not change
+/// the image.
+#[test]
+fn cpu_chunk_culling() {
+    let Some(gpu) = Gpu::new() else {
+        eprintln!("cpu_chunk_culling: skipped, no GPU adapter available");
+        return;
+    };
+    let data = lattice(16);
+    let render_with = |min_atoms: usize| {
+        let mut config = Config::new();
+        config.lod.cpu_culling_min_atoms = min_atoms;
+        render(&gpu, &config, &data, |scene, _| {
+            scene.transform.rotate(20.0, 30.0, 0.0);
+            scene.transform.scale(Vec3::new(6.0, 6.0, 6.0));
+        })
+    };
+    let culled = render_with(0);
+    assert!(
+        culled.chunks(4).any(|pixel| pixel[..3] != culled[..3]),
+        "nothing is drawn"
+    );
+    assert!(culled == render_with(usize::MAX), "culling changed the image");
+}
+
+/// Only the chunks crossing the view frustum are drawn, and distant visible chunks are merged into a limited
+/// number of draws.
+#[test]
+fn cpu_chunk_culling_ranges() {
+    let atom = |x: f32| InstanceData {
+        rotation: [0.0, 0.0, 0.0, 1.0],
+        position: [x, 0.0, 0.5],
+        scale: [0.1, 0.1],
+        color: [255; 4],
+        picking_id: 0,
+        flags: 0,
+    };
+    // Clip space is the view frustum of the identity transform, two chunks of the line cross it
+    let line: Vec<InstanceData> = (0..8 * CHUNK_SIZE).map(|i| atom(i as f32 * 0.01 - 5.0)).collect();
+    let (atoms, bonds) = ChunkCulling::from_instances(&line, &[]).visible(&Mat4::new());
+    assert_eq!(atoms, vec![CHUNK_SIZE as u32..3 * CHUNK_SIZE as u32]);
+    assert!(bonds.is_empty());
+
+    // Every other chunk is visible
+    let num_chunks = 4 * MAX_DRAWS;
+    let striped: Vec<InstanceData> = (0..num_chunks * CHUNK_SIZE)
+        .map(|i| atom(if (i / CHUNK_SIZE).is_multiple_of(2) { 0.0 } else { 10.0 }))
+        .collect();
+    let (atoms, _) = ChunkCulling::from_instances(&striped, &[]).visible(&Mat4::new());
+    assert_eq!(atoms.len(), MAX_DRAWS);
+    assert_eq!(atoms[0].start, 0);
+    assert_eq!(atoms[MAX_DRAWS - 1].end, ((num_chunks - 1) * CHUNK_SIZE) as u32);
+    assert!(atoms.windows(2).all(|pair| pair[0].end < pair[1].start));
+}
+
+#[test]
+fn large_structure_build_order() {
+    let Some(gpu) = Gpu::new() else {
+        eprintln!("large_structure_build_order: skipped, no GPU adapter available");
+        return;
+    };
+    let n = 24;
+    let data = lattice(n);
+    let config = Config::new();
+    let mut scene = Scene::new(&gpu.device, &surface_config(), &config);
+    block_on(scene.load_atomic_coordinates(
+        &gpu.device,
+        &config,
+        &data,
+        &Progress::new(None),
+        &CancellationToken::none(),
+    ))
+    .unwrap();
+
+    let positions = scene.atom_coordinates();
+    assert_eq!(positions.len(), data.atomic_num.len());
+    for (i, position) in positions.iter().enumerate() {
+        let expected = [data.x[i], data.y[i], data.z[i]];
+        assert!((0..3).all(|a| (position[a] - expected[a]).abs() < 1e-4), "atom {}", i);
+    }
+    let pairs = |bonds: &[connectivity::Bond]| -> Vec<(usize, usize)> {
+        bonds
+            .iter()
+            .map(|bond| (bond.atom_index_1, bond.atom_index_2))
+            .collect()
+    };
+    let expected = connectivity::find_bonds(&data, config.style.geom_bond_tolerance);
+    assert_eq!(expected.len(), 3 * n * n * (n - 1));
+    assert_eq!(pairs(scene.bonds()), pairs(&expected));
+}
+
+/// Atoms are gray levels, the white hydrogen atoms and labels are darkened to stand out on the white background.
+#[test]
+fn grayscale_palette() {
+    let mut config = Config::new();
+    config.style.palette = Palette::Grayscale;
+    config.style.background_color.r = 1.0;
+    config.style.background_color.g = 1.0;
+    config.style.background_color.b = 1.0;
+    config.style.label.size = 0.15;
+    run("grayscale_palette", &config, &hydrogen_peroxide(), |scene, _| {
+        scene.transform.rotate(20.0, 30.0, 0.0);
+        scene.set_labels_visible(LabelKind::Symbol, true);
+    });
+}
+
+/// Colormaps of the colorblind-safe palette keep their order with every deficiency: luminance of the sequential
+/// one grows, ends of the diverging one stay apart.
+#[test]
+fn colorblind_safe_palette() {
+    let palette = Palette::ColorblindSafe;
+    for deficiency in ColorVisionDeficiency::ALL {
+        let seen = |colormap: Colormap, t: f64| relative_luminance(deficiency.simulate(colormap.sample(t)));
+        for i in 0..32 {
+            let (t0, t1) = (i as f64 / 32.0, (i + 1) as f64 / 32.0);
+            assert!(
+                seen(palette.sequential(), t1) > seen(palette.sequential(), t0),
+                "{deficiency:?}"
+            );
+        }
+        let (low, high) = (
+            deficiency.simulate(palette.diverging().sample(0.0)),
+            deficiency.simulate(palette.diverging().sample(1.0)),
+        );
+        let distance = (0..3).map(|c| (low[c] - high[c]).powi(2)).sum::<f32>().sqrt();
+        assert!(distance > 0.2, "{deficiency:?}: {distance}");
+    }
+
+    let mut config = Config::new();
+    config.style.color_scheme = ColorScheme::Charge;
+    config.style.palette = palette;
+    let data = AtomicCoordinates {
+        charges: Some(vec![-0.8, 0.4, 0.4]),
+        ..water()
+    };
+    run("colorblind_safe_palette", &config, &data, |scene, _| {
+        scene.transform.rotate(20.0, 30.0, 0.0);
+    });
+}
+
+#[cfg(feature = "debug-tools")]
+fn run_debug_view(name: &str, view: DebugView) {
+    run(name, &Config::new(), &carbon_dioxide(), |scene, _| {
+        scene.transform.rotate(0.0, 0.0, 30.0);
+        scene.set_debug_view(Some(view));
+    });
+}
+
+#[cfg(feature = "debug-tools")]
+#[test]
+fn debug_normals() {
+    run_debug_view("debug_normals", DebugView::Normals);
+}
+
+#[cfg(feature = "debug-tools")]
+#[test]
+fn debug_depth() {
+    run_debug_view("debug_depth", DebugView::Depth);
+}
+
+#[cfg(feature = "debug-tools")]
+#[test]
+fn debug_picking_ids() {
+    run_debug_view("debug_picking_ids", DebugView::PickingIds);
+}
+
+#[cfg(feature = "debug-tools")]
+#[test]
+fn debug_overdraw() {
+    run_debug_view("debug_overdraw", DebugView::Overdraw);
+}
+
+/// Shaders reloaded from the source tree draw the same picture as the embedded ones.
+#[cfg(feature = "debug-tools")]
+#[test]
+fn reloaded_shaders() {
+    run("small_molecule", &Config::new(), &water(), |scene, gpu| {
+        block_on(scene.reload_shaders(&gpu.device, &gpu.queue, &surface_config())).unwrap();
+        scene.transform.rotate(20.0, 30.0, 0.0);
+    });
+}
+
+/// Capture over a transparent background: the background stays fully transparent, the selection sphere composited
+/// by WBOIT and the outline keep partial alpha with premultiplied colors.
+#[test]
+fn transparent_background() {
+    let Some(gpu) = Gpu::new() else {
+        return;
+    };
+    let mut config = Config::new();
+    config.style.transparent_background = true;
+    let capture = |config: &Config| {
+        let mut png = Vec::new();
+        render(&gpu, config, &water(), |scene, gpu| {
+            scene.transform.rotate(20.0, 30.0, 0.0);
+            scene.select_atoms(&[1]).unwrap();
+            png = block_on(scene.capture_image(&gpu.device, &gpu.queue, config, &surface_config(), WIDTH, HEIGHT, 2))
+                .unwrap();
+        });
+        png_pixels(&png)
+    };
+    let premultiplied = |rgba: &[u8]| {
+        rgba.chunks_exact(4)
+            .all(|p| p[0] <= p[3] && p[1] <= p[3] && p[2] <= p[3])
+    };
+    let translucent = |rgba: &[u8]| rgba.chunks_exact(4).filter(|p| p[3] > 0 && p[3] < 255).count();
+
+    let rgba = capture(&config);
+    assert_eq!(&rgba[..4], [0; 4]);
+    assert!(rgba.chunks_exact(4).any(|pixel| pixel[3] == 255));
+    assert!(premultiplied(&rgba));
+    assert!(translucent(&rgba) > 100, "{}", translucent(&rgba));
+    assert_matches_golden("transparent_background", &rgba);
+
+    config.style.effects.outline = true;
+    let outlined = capture(&config);
+    assert_eq!(&outlined[..4], [0; 4]);
+    assert!(premultiplied(&outlined));
+}
+
+#[cfg(feature = "profiling")]
+#[test]
+fn profiling_spans() {
+    use shared_lib::profiling;
+
+    let Some(gpu) = Gpu::new() else {
+        eprintln!("profiling_spans: skipped, no GPU adapter available");
+        return;
+    };
+    profiling::take_records();
+    render(&gpu, &Config::new(), &water(), |_, _| {});
+    let spans = profiling::take_records();
+    let find = |name: &str| {
+        spans
+            .iter()
+            .find(|span| span.name == name)
+            .unwrap_or_else(|| panic!("No span {name}"))
+    };
+    assert_eq!(find("load structure").depth, 0);
+    assert_eq!(find("bonds").depth, 1);
+    assert_eq!(find("render").depth, 0);
+    assert_eq!(find("opaque pass").depth, 1);
+    // Nested spans end first and fit into the enclosing ones
+    let (outer, inner) = (find("render"), find("opaque pass"));
+    assert!(inner.start >= outer.start && inner.start + inner.duration <= outer.start + outer.duration);
+    assert!(spans.iter().all(|span| span.duration >= 0.0));
+
+    // Top-level spans go to the sink instead
+    let received = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+    let sink = received.clone();
+    profiling::set_sink(Some(Box::new(move |spans| sink.borrow_mut().push(spans))));
+    render(&gpu, &Config::new(), &water(), |_, _| {});
+    profiling::set_sink(None);
+    assert!(profiling::take_records().is_empty());
+    let received = received.borrow();
+    assert_eq!(received.len(), 2);
+    assert_eq!(received[0].last().unwrap().name, "load structure");
+    assert_eq!(received[1].last().unwrap().name, "render");
+}
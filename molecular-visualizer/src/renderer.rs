@@ -1,4 +1,12 @@
+use bytemuck::{Pod, Zeroable};
+
+use super::core::Mat4;
 use super::core::mesh::{InstanceData, Vertex};
+use super::culling::Culling;
+#[cfg(all(feature = "debug-tools", not(target_arch = "wasm32")))]
+use super::debug_tools;
+use super::labels::GlyphInstance;
+use super::slice::SliceVertex;
 use wgpu::util::DeviceExt;
 
 pub const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
@@ -10,11 +18,105 @@ pub const USAGE_BINDING: wgpu::TextureUsages =
 pub const USAGE_COPY_SRC: wgpu::TextureUsages =
     wgpu::TextureUsages::RENDER_ATTACHMENT.union(wgpu::TextureUsages::COPY_SRC);
 
+/// Uniforms of main.wgsl, the other shaders declare the same fields up to `lod_level`.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+pub struct Uniforms {
+    projection_transform: [f32; 16],
+    view_transform: [f32; 16],
+    scene_transform: [f32; 16],
+    final_transform: [f32; 16],
+    render_mode: u32,
+    is_perspective: u32,
+    lod_level: u32,
+    fog_enabled: u32,
+    fog_color: [f32; 4],
+    fog_start: f32,
+    fog_end: f32,
+    padding: [u32; 2],
+}
+
+// Offsets of the WGSL layout: the vec4 of the fog color is 16 byte aligned and so is the size of the struct
+const _: () = {
+    assert!(std::mem::offset_of!(Uniforms, render_mode) == 256);
+    assert!(std::mem::offset_of!(Uniforms, fog_color) == 272);
+    assert!(std::mem::offset_of!(Uniforms, fog_start) == 288);
+    assert!(std::mem::size_of::<Uniforms>() == 304);
+};
+
+impl Uniforms {
+    /// Without fog, see `with_fog`. `render_mode` is 0 for normal rendering, 1 for picking and 2-5 for debug views.
+    pub fn new(
+        projection: &Mat4<f32>,
+        view: &Mat4<f32>,
+        scene: &Mat4<f32>,
+        render_mode: u32,
+        is_perspective: bool,
+        lod_level: u32,
+    ) -> Self {
+        Self {
+            projection_transform: projection.data,
+            view_transform: view.data,
+            scene_transform: scene.data,
+            final_transform: (*projection * *view * *scene).data,
+            render_mode,
+            is_perspective: is_perspective as u32,
+            lod_level,
+            fog_enabled: 0,
+            fog_color: [0.0; 4],
+            fog_start: 0.0,
+            fog_end: 0.0,
+            padding: [0; 2],
+        }
+    }
+
+    /// The same uniforms for an object with its own scene transform, e.g. another structure of the scene.
+    pub fn with_scene(self, scene: &Mat4<f32>) -> Self {
+        let projection = Mat4::from_array(self.projection_transform);
+        let view = Mat4::from_array(self.view_transform);
+        Self {
+            scene_transform: scene.data,
+            final_transform: (projection * view * *scene).data,
+            ..self
+        }
+    }
+
+    /// Depth cueing towards `color` between the distances from the camera in view space.
+    pub fn with_fog(self, color: [f32; 4], start: f32, end: f32) -> Self {
+        Self {
+            fog_enabled: 1,
+            fog_color: color,
+            fog_start: start,
+            fog_end: end,
+            ..self
+        }
+    }
+}
+
+/// WGSL source of a shader from `src/shaders`. With debug tools, native builds read the file from the source tree
+/// so that `Scene::reload_shaders` picks up edits, the embedded source is the fallback.
+#[cfg_attr(
+    not(all(feature = "debug-tools", not(target_arch = "wasm32"))),
+    allow(unused_variables)
+)]
+pub fn shader_source(name: &str, embedded: &'static str) -> wgpu::ShaderSource<'static> {
+    #[cfg(all(feature = "debug-tools", not(target_arch = "wasm32")))]
+    if let Some(source) = debug_tools::read_shader(name) {
+        return wgpu::ShaderSource::Wgsl(source.into());
+    }
+    wgpu::ShaderSource::Wgsl(embedded.into())
+}
+
+/// Pipelines and render targets, clones share them.
+#[derive(Clone)]
 pub struct Renderer {
     pub pipeline: wgpu::RenderPipeline,
     pub picking_pipeline: wgpu::RenderPipeline,
+    #[cfg(feature = "debug-tools")]
+    pub overdraw_pipeline: wgpu::RenderPipeline,
     pub uniform_buffer: wgpu::Buffer,
     pub bind_group: wgpu::BindGroup,
+    pub bind_group_layout: wgpu::BindGroupLayout,
     pub depth_texture_view: wgpu::TextureView,
 
     pub picking_texture: wgpu::Texture,
@@ -29,6 +131,17 @@ pub struct Renderer {
     pub wboit_revealage_texture_view: wgpu::TextureView,
     pub wboit_bind_group: wgpu::BindGroup,
 
+    // Volume slice plane
+    pub slice_pipeline: wgpu::RenderPipeline,
+    pub slice_bind_group_layout: wgpu::BindGroupLayout,
+    pub slice_sampler: wgpu::Sampler,
+
+    // Atom labels
+    pub labels_pipeline: wgpu::RenderPipeline,
+    pub font_atlas_bind_group_layout: wgpu::BindGroupLayout,
+    pub font_sampler: wgpu::Sampler,
+
+    format: wgpu::TextureFormat,
     width: u32,
     height: u32,
 }
@@ -38,27 +151,34 @@ impl Renderer {
         // Create shader modules
         let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: Some("Main Shader"),
-            source: wgpu::ShaderSource::Wgsl(include_str!("shaders/main.wgsl").into()),
+            source: shader_source("main.wgsl", include_str!("shaders/main.wgsl")),
         });
 
         let wboit_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: Some("WBOIT Composite Shader"),
-            source: wgpu::ShaderSource::Wgsl(include_str!("shaders/wboit.wgsl").into()),
+            source: shader_source("wboit.wgsl", include_str!("shaders/wboit.wgsl")),
         });
 
-        // Create uniform buffer for 4 matrices (256 bytes) + 3 u32 flags (8 bytes) + padding (8 bytes)
+        // Create uniform buffer for 4 matrices (256 bytes) + 4 u32 flags (16 bytes) + fog color (16 bytes)
+        // + fog distances (8 bytes) + padding (8 bytes)
         let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Uniform Buffer"),
-            contents: &[0u8; 272],
+            contents: bytemuck::bytes_of(&Uniforms::zeroed()),
             usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
         });
 
+        // Uniforms are also read by the culling pass where compute shaders are available
+        let mut visibility = wgpu::ShaderStages::VERTEX_FRAGMENT;
+        if Culling::is_supported(device) {
+            visibility |= wgpu::ShaderStages::COMPUTE;
+        }
+
         // Create bind group layout
         let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
             label: Some("Bind Group Layout"),
             entries: &[wgpu::BindGroupLayoutEntry {
                 binding: 0,
-                visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                visibility,
                 ty: wgpu::BindingType::Buffer {
                     ty: wgpu::BufferBindingType::Uniform,
                     has_dynamic_offset: false,
@@ -104,6 +224,8 @@ impl Renderer {
             PICKING_FORMAT,
         );
         let transparent_pipeline = Self::create_transparent_pipeline(device, &pipeline_layout, &shader);
+        #[cfg(feature = "debug-tools")]
+        let overdraw_pipeline = Self::create_overdraw_pipeline(device, &pipeline_layout, &shader, config.format);
 
         // Create WBOIT textures
         let (_, depth_texture_view) =
@@ -140,13 +262,48 @@ impl Renderer {
             &wboit_revealage_texture_view,
         );
 
+        // Create volume slice pipeline
+        let slice_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Slice Shader"),
+            source: shader_source("slice.wgsl", include_str!("shaders/slice.wgsl")),
+        });
+        let (slice_pipeline, slice_bind_group_layout) =
+            Self::create_slice_pipeline(device, &bind_group_layout, &slice_shader, config.format);
+        let slice_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Slice Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        // Create atom labels pipeline
+        let labels_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Labels Shader"),
+            source: shader_source("labels.wgsl", include_str!("shaders/labels.wgsl")),
+        });
+        let (labels_pipeline, font_atlas_bind_group_layout) =
+            Self::create_labels_pipeline(device, &bind_group_layout, &labels_shader, config.format);
+        let font_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Font Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
         Self {
             pipeline,
             picking_pipeline,
+            #[cfg(feature = "debug-tools")]
+            overdraw_pipeline,
             transparent_pipeline,
             composite_pipeline,
             uniform_buffer,
             bind_group,
+            bind_group_layout,
             depth_texture_view,
             picking_texture,
             picking_texture_view,
@@ -155,6 +312,13 @@ impl Renderer {
             wboit_accumulation_texture_view,
             wboit_revealage_texture_view,
             wboit_bind_group,
+            slice_pipeline,
+            slice_bind_group_layout,
+            slice_sampler,
+            labels_pipeline,
+            font_atlas_bind_group_layout,
+            font_sampler,
+            format: config.format,
             width: config.width,
             height: config.height,
         }
@@ -205,6 +369,11 @@ impl Renderer {
         (self.width, self.height)
     }
 
+    /// Format of the render target.
+    pub fn format(&self) -> wgpu::TextureFormat {
+        self.format
+    }
+
     fn create_pipeline(
         device: &wgpu::Device,
         pipeline_layout: &wgpu::PipelineLayout,
@@ -295,6 +464,67 @@ impl Renderer {
         })
     }
 
+    /// Opaque geometry without depth test, every fragment adds its color to the target.
+    #[cfg(feature = "debug-tools")]
+    fn create_overdraw_pipeline(
+        device: &wgpu::Device,
+        pipeline_layout: &wgpu::PipelineLayout,
+        shader: &wgpu::ShaderModule,
+        fragment_format: wgpu::TextureFormat,
+    ) -> wgpu::RenderPipeline {
+        let additive = wgpu::BlendComponent {
+            src_factor: wgpu::BlendFactor::One,
+            dst_factor: wgpu::BlendFactor::One,
+            operation: wgpu::BlendOperation::Add,
+        };
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Overdraw Pipeline"),
+            layout: Some(pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: shader,
+                entry_point: Some("vs_main"),
+                buffers: &[Vertex::desc(), InstanceData::desc()],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: shader,
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: fragment_format,
+                    blend: Some(wgpu::BlendState {
+                        color: additive,
+                        alpha: additive,
+                    }),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: Some(wgpu::Face::Back),
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: DEPTH_FORMAT,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::Always,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview_mask: None,
+            cache: None,
+        })
+    }
+
     fn create_transparent_pipeline(
         device: &wgpu::Device,
         pipeline_layout: &wgpu::PipelineLayout,
@@ -482,4 +712,152 @@ impl Renderer {
             ],
         })
     }
+
+    // Layout of a filterable 2D texture (binding 0) and its sampler (binding 1)
+    fn create_texture_bind_group_layout(device: &wgpu::Device, label: &str) -> wgpu::BindGroupLayout {
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some(label),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        })
+    }
+
+    fn create_slice_pipeline(
+        device: &wgpu::Device,
+        uniforms_layout: &wgpu::BindGroupLayout,
+        shader: &wgpu::ShaderModule,
+        surface_format: wgpu::TextureFormat,
+    ) -> (wgpu::RenderPipeline, wgpu::BindGroupLayout) {
+        let bind_group_layout = Self::create_texture_bind_group_layout(device, "Slice Bind Group Layout");
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Slice Pipeline Layout"),
+            bind_group_layouts: &[uniforms_layout, &bind_group_layout],
+            immediate_size: 0,
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Slice Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: shader,
+                entry_point: Some("vs_main"),
+                buffers: &[SliceVertex::desc()],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: shader,
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: surface_format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None, // The plane is visible from both sides
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: DEPTH_FORMAT,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview_mask: None,
+            cache: None,
+        });
+
+        (pipeline, bind_group_layout)
+    }
+
+    fn create_labels_pipeline(
+        device: &wgpu::Device,
+        uniforms_layout: &wgpu::BindGroupLayout,
+        shader: &wgpu::ShaderModule,
+        surface_format: wgpu::TextureFormat,
+    ) -> (wgpu::RenderPipeline, wgpu::BindGroupLayout) {
+        let bind_group_layout = Self::create_texture_bind_group_layout(device, "Font Atlas Bind Group Layout");
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Labels Pipeline Layout"),
+            bind_group_layouts: &[uniforms_layout, &bind_group_layout],
+            immediate_size: 0,
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Labels Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: shader,
+                entry_point: Some("vs_main"),
+                buffers: &[GlyphInstance::desc()],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: shader,
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: surface_format,
+                    blend: None, // Glyphs are alpha tested
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: DEPTH_FORMAT,
+                // Quads of neighbouring glyphs overlap with their padding
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview_mask: None,
+            cache: None,
+        });
+
+        (pipeline, bind_group_layout)
+    }
 }
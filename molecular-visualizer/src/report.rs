@@ -0,0 +1,320 @@
+use shared_lib::comparison::Tolerances;
+use shared_lib::connectivity::Bond;
+use shared_lib::contacts::Clash;
+use shared_lib::inertia::rotational_constants;
+use shared_lib::mass_spectrum::formula;
+use shared_lib::periodic_table::element_symbol;
+use shared_lib::properties::atomic_masses;
+use shared_lib::symmetry::Symmetry;
+use shared_lib::types::AtomicCoordinates;
+
+use super::core::Vec3;
+use super::measurement::angle_between;
+
+/// Size in pixels of the image of the structure in the report.
+pub const DEFAULT_IMAGE_WIDTH: u32 = 800;
+pub const DEFAULT_IMAGE_HEIGHT: u32 = 600;
+// Longer lists of bonds, angles and frequencies are cut, the report is meant to be read
+const MAX_ROWS: usize = 50;
+// Speed of light in cm/ns converts GHz to cm⁻¹
+const SPEED_OF_LIGHT: f64 = 29.979_245_8;
+const BASE64_ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ReportFormat {
+    Markdown,
+    Html,
+}
+
+impl ReportFormat {
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        match name.to_ascii_lowercase().as_str() {
+            "markdown" | "md" => Ok(ReportFormat::Markdown),
+            "html" => Ok(ReportFormat::Html),
+            _ => Err(format!("Unknown report format: {}", name)),
+        }
+    }
+}
+
+/// Table of the report under a heading, rows are pairs of a name and a value.
+struct Section {
+    title: String,
+    header: [String; 2],
+    rows: Vec<[String; 2]>,
+    // Rows left out of a long table
+    omitted: usize,
+}
+
+impl Section {
+    fn new(title: &str, header: [&str; 2], rows: Vec<[String; 2]>) -> Self {
+        let omitted = rows.len().saturating_sub(MAX_ROWS);
+        let mut rows = rows;
+        rows.truncate(MAX_ROWS);
+        Self {
+            title: title.to_string(),
+            header: header.map(str::to_string),
+            rows,
+            omitted,
+        }
+    }
+}
+
+/// Printable summary of a structure: an image, the formula and the mass, symmetry, bond lengths and angles,
+/// rotational constants and vibrational frequencies, e.g. for a lab notebook or supporting information.
+pub struct Report {
+    pub title: String,
+    /// PNG image of the structure embedded into the report.
+    pub image: Option<Vec<u8>>,
+    /// Wavenumbers of the normal modes in cm⁻¹, imaginary frequencies are negative.
+    pub frequencies: Vec<f64>,
+    sections: Vec<Section>,
+}
+
+fn atom_label(data: &AtomicCoordinates, index: usize) -> String {
+    format!("{}{}", element_symbol(data.atomic_num[index]), index + 1)
+}
+
+fn optional_constant(value: Option<f64>, scale: f64, precision: usize) -> String {
+    value.map_or("—".to_string(), |value| format!("{:.*}", precision, value / scale))
+}
+
+impl Report {
+    /// Summary of the structure with the bonds given by zero-based atom indices. The title is the formula.
+    /// The point group is detected with the distance tolerance.
+    pub fn new(data: &AtomicCoordinates, bonds: &[Bond], tolerances: &Tolerances) -> Self {
+        let formula = formula(&data.atomic_num);
+        let num_atoms = data.atomic_num.len();
+        let positions: Vec<Vec3<f64>> = (0..num_atoms)
+            .map(|i| Vec3::new(data.x[i], data.y[i], data.z[i]))
+            .collect();
+        // Labelled atoms have the masses of their isotopes
+        let mass: f64 = atomic_masses(data).map_or(0.0, |masses| masses.iter().sum());
+
+        let mut summary = vec![
+            ["Formula".to_string(), formula.clone()],
+            ["Molecular weight".to_string(), format!("{:.3} g/mol", mass)],
+            ["Atoms".to_string(), num_atoms.to_string()],
+        ];
+        if let Some(cell) = &data.cell {
+            let [a, b, c, alpha, beta, gamma] = cell.parameters();
+            summary.push([
+                "Cell".to_string(),
+                format!(
+                    "a = {:.4} Å, b = {:.4} Å, c = {:.4} Å, α = {:.2}°, β = {:.2}°, γ = {:.2}°",
+                    a, b, c, alpha, beta, gamma
+                ),
+            ]);
+        } else {
+            // Point groups and rotation are only defined for finite structures
+            if let Ok(symmetry) = Symmetry::from_coordinates(data, tolerances.distance) {
+                summary.push(["Point group".to_string(), symmetry.point_group().to_string()]);
+            }
+            if let Ok(constants) = rotational_constants(data)
+                && constants.iter().any(Option::is_some)
+            {
+                for (unit, scale, precision) in [("GHz", 1.0, 5), ("cm⁻¹", SPEED_OF_LIGHT, 6)] {
+                    let [a, b, c] = constants.map(|value| optional_constant(value, scale, precision));
+                    summary.push([
+                        format!("Rotational constants A, B, C ({})", unit),
+                        format!("{}, {}, {}", a, b, c),
+                    ]);
+                }
+            }
+        }
+
+        // Bond vectors from the first atom, through the boundary of the cell to the image of the second one
+        let mut bond_vectors: Vec<(usize, usize, Vec3<f64>)> = bonds
+            .iter()
+            .map(|bond| {
+                let (i, j) = (bond.atom_index_1, bond.atom_index_2);
+                let [x, y, z] = match &data.cell {
+                    Some(cell) => cell.to_cartesian(bond.image.map(|n| n as f64)),
+                    None => [0.0; 3],
+                };
+                let vector = positions[j] + Vec3::new(x, y, z) - positions[i];
+                if i < j { (i, j, vector) } else { (j, i, -vector) }
+            })
+            .collect();
+        bond_vectors.sort_unstable_by_key(|&(i, j, _)| (i, j));
+        let bond_rows = bond_vectors
+            .iter()
+            .map(|&(i, j, vector)| {
+                [
+                    format!("{}–{}", atom_label(data, i), atom_label(data, j)),
+                    format!("{:.3}", vector.length()),
+                ]
+            })
+            .collect();
+
+        let mut neighbors: Vec<Vec<(usize, Vec3<f64>)>> = vec![Vec::new(); num_atoms];
+        for &(i, j, vector) in &bond_vectors {
+            neighbors[i].push((j, vector));
+            neighbors[j].push((i, -vector));
+        }
+        let mut angle_rows = Vec::new();
+        for (center, mut neighbors) in neighbors.into_iter().enumerate() {
+            neighbors.sort_by_key(|&(i, _)| i);
+            for (n, &(i, u)) in neighbors.iter().enumerate() {
+                for &(k, v) in &neighbors[n + 1..] {
+                    angle_rows.push([
+                        format!(
+                            "{}–{}–{}",
+                            atom_label(data, i),
+                            atom_label(data, center),
+                            atom_label(data, k)
+                        ),
+                        format!("{:.1}", angle_between(u, v).to_degrees()),
+                    ]);
+                }
+            }
+        }
+
+        Self {
+            title: formula,
+            image: None,
+            frequencies: Vec::new(),
+            sections: vec![
+                Section::new("Summary", ["Property", "Value"], summary),
+                Section::new("Bond lengths", ["Bond", "Length (Å)"], bond_rows),
+                Section::new("Bond angles", ["Atoms", "Angle (°)"], angle_rows),
+            ],
+        }
+    }
+
+    /// Lists the pairs of atoms of the structure (zero-based indices) closer than a fraction of the sum of their
+    /// covalent radii after the bond angles, nothing if there are none.
+    pub fn add_clashes(&mut self, data: &AtomicCoordinates, clashes: &[Clash]) {
+        let rows = clashes
+            .iter()
+            .map(|clash| {
+                [
+                    format!(
+                        "{}···{}",
+                        atom_label(data, clash.atom_index_1),
+                        atom_label(data, clash.atom_index_2)
+                    ),
+                    format!(
+                        "{:.3} ({:.0}% of covalent radii)",
+                        clash.distance,
+                        clash.fraction * 100.0
+                    ),
+                ]
+            })
+            .collect();
+        self.sections
+            .push(Section::new("Clashes", ["Atoms", "Distance (Å)"], rows));
+    }
+
+    fn frequency_section(&self) -> Option<Section> {
+        if self.frequencies.is_empty() {
+            return None;
+        }
+        let rows = self
+            .frequencies
+            .iter()
+            .enumerate()
+            .map(|(i, &frequency)| {
+                let value = if frequency < 0.0 {
+                    format!("{:.1}i", -frequency)
+                } else {
+                    format!("{:.1}", frequency)
+                };
+                [(i + 1).to_string(), value]
+            })
+            .collect();
+        Some(Section::new(
+            "Vibrational frequencies",
+            ["Mode", "Wavenumber (cm⁻¹)"],
+            rows,
+        ))
+    }
+
+    pub fn render(&self, format: ReportFormat) -> String {
+        match format {
+            ReportFormat::Markdown => self.to_markdown(),
+            ReportFormat::Html => self.to_html(),
+        }
+    }
+
+    fn to_markdown(&self) -> String {
+        let mut text = format!("# {}\n\n", self.title);
+        if let Some(png) = &self.image {
+            text += &format!("![{}](data:image/png;base64,{})\n\n", self.title, base64(png));
+        }
+        let frequencies = self.frequency_section();
+        for section in self.sections.iter().chain(&frequencies).filter(|s| !s.rows.is_empty()) {
+            let [name, value] = &section.header;
+            text += &format!("## {}\n\n| {} | {} |\n|---|---|\n", section.title, name, value);
+            for [name, value] in &section.rows {
+                text += &format!("| {} | {} |\n", name, value);
+            }
+            if section.omitted > 0 {
+                text += &format!("\n… and {} more\n", section.omitted);
+            }
+            text += "\n";
+        }
+        text
+    }
+
+    fn to_html(&self) -> String {
+        let title = escape_html(&self.title);
+        let mut html = format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{0}</title>\n</head>\n<body>\n<h1>{0}</h1>\n",
+            title
+        );
+        if let Some(png) = &self.image {
+            html += &format!(
+                "<img src=\"data:image/png;base64,{}\" alt=\"{}\">\n",
+                base64(png),
+                title
+            );
+        }
+        let frequencies = self.frequency_section();
+        for section in self.sections.iter().chain(&frequencies).filter(|s| !s.rows.is_empty()) {
+            let [name, value] = &section.header;
+            html += &format!(
+                "<h2>{}</h2>\n<table>\n<tr><th>{}</th><th>{}</th></tr>\n",
+                escape_html(&section.title),
+                escape_html(name),
+                escape_html(value)
+            );
+            for [name, value] in &section.rows {
+                html += &format!(
+                    "<tr><th>{}</th><td>{}</td></tr>\n",
+                    escape_html(name),
+                    escape_html(value)
+                );
+            }
+            html += "</table>\n";
+            if section.omitted > 0 {
+                html += &format!("<p>… and {} more</p>\n", section.omitted);
+            }
+        }
+        html += "</body>\n</html>\n";
+        html
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Standard Base64 with padding, used for images embedded as data URLs.
+fn base64(data: &[u8]) -> String {
+    let mut text = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bytes = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let triple = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                text.push(BASE64_ALPHABET[(triple >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                text.push('=');
+            }
+        }
+    }
+    text
+}
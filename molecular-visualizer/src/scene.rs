@@ -1,38 +1,355 @@
-use shared_lib::types::AtomicCoordinates;
+use std::collections::HashSet;
 
-use super::atom::AtomInfo;
-use super::config::Config;
-use super::core::{Camera, Mesh, ProjectionManager, ProjectionMode, Transform, Vec3, mesh_objects};
+use shared_lib::alignment::{self, Alignment};
+use shared_lib::cancellation::CancellationToken;
+use shared_lib::cell::UnitCell;
+use shared_lib::comparison::Tolerances;
+use shared_lib::connectivity::Bond;
+use shared_lib::contacts::{self, Clash, DEFAULT_CLASH_FRACTION};
+use shared_lib::periodic_table::is_pseudo_element;
+use shared_lib::profiling;
+use shared_lib::symmetry::Symmetry;
+use shared_lib::types::{AtomicCoordinates, VibrationalModes, VolumeCube};
+use shared_lib::volume::VolumeGrid;
+
+use super::animation::Animation;
+use super::atom::{AtomInfo, AtomStyle};
+use super::bvh::{Hit, Ray, Target};
+use super::capture::{CaptureTarget, MAX_SUPERSAMPLING, downsample, encode_png};
+use super::cell_box::CellBox;
+use super::clash_halos::ClashHalos;
+use super::config::{Config, Geometry, Lod};
+use super::core::projection::DEFAULT_FOV;
+use super::core::{
+    Arcball, Camera, CameraFlight, CameraLimits, Mat4, Mesh, Node, ProjectionManager, ProjectionMode, Quaternion,
+    Transform, Vec3, mesh_objects,
+};
+use super::culling::{ChunkCulling, Culling, CullingLayer};
+#[cfg(feature = "debug-tools")]
+use super::debug_tools::DebugView;
+#[cfg(all(feature = "debug-tools", not(target_arch = "wasm32")))]
+use super::debug_tools::ShaderWatcher;
+use super::executor::Progress;
+use super::font_atlas::FontAtlas;
+use super::isosurface::{Isosurface, IsosurfaceSettings};
+use super::labels::{BondLabels, LabelKind, Labels};
+use super::measurement::{MeasurementInfo, MeasurementOverlay, PlaneInfo, dihedral, positions};
 use super::molecule::Molecule;
-use super::renderer::Renderer;
-use super::utils::color_to_id;
+use super::overlay::Overlay;
+use super::post_process::{PostProcess, PostProcessParams};
+use super::renderer::{Renderer, Uniforms};
+use super::slice::{Slice, SliceSettings};
+use super::supercell::Supercell;
+use super::types::Dimming;
+use super::utils::{PickInfo, PickedObject, color_to_id};
 use super::vertex_buffer::VertexBuffer;
+use super::vibration::Vibration;
+use super::view::{self, ViewState};
+
+// A finer level of detail is restored only when atoms are this much larger than the threshold,
+// so zooming around a threshold does not make the picture flicker
+const LOD_HYSTERESIS: f32 = 1.25;
+
+// Range of the field of view in degrees accepted from the host
+const MIN_FOV: f32 = 10.0;
+const MAX_FOV: f32 = 120.0;
+// Duration in milliseconds of the camera flight to focused atoms
+const FOCUS_DURATION: f64 = 500.0;
+// Smallest radius in Angstroms of the view of focused atoms, so a single atom is shown with its neighbors
+const MIN_FOCUS_RADIUS: f32 = 2.0;
+
+/// Distance in Angstroms from the selected atoms within which atoms stay in focus by default, see `FocusMode`.
+pub const DEFAULT_FOCUS_CUTOFF: f32 = 5.0;
+
+/// Id of the root of the scene graph, its transform is applied after the rotation and zoom of the scene.
+pub const ROOT_NODE: u32 = 0;
+/// Id of the node of the molecule in the scene graph.
+pub const MOLECULE_NODE: u32 = 1;
+
+/// Focus on the selection: the selected atoms and the atoms closer than `cutoff` in Angstroms to them are drawn
+/// as they are, the others and their bonds are dimmed. Nothing is dimmed while no atoms are selected.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FocusMode {
+    pub cutoff: f32,
+    pub dimming: Dimming,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LodLevel {
+    /// Atoms and bonds as configured.
+    Full,
+    /// Bonds are drawn as low-poly cylinders.
+    Simplified,
+    /// Atoms are drawn as point sprites, bonds are hidden.
+    Points,
+}
+
+impl LodLevel {
+    fn shader_value(self) -> u32 {
+        match self {
+            LodLevel::Full => 0,
+            LodLevel::Simplified => 1,
+            LodLevel::Points => 2,
+        }
+    }
+}
+
+/// Chooses the level of detail of large molecules from the number of atoms and their size on screen.
+pub struct LodManager {
+    level: LodLevel,
+}
+
+impl LodManager {
+    pub fn new() -> Self {
+        Self { level: LodLevel::Full }
+    }
+
+    pub fn level(&self) -> LodLevel {
+        self.level
+    }
+
+    /// `radius_pixels` is the apparent radius of an average atom, it shrinks as the camera moves away.
+    pub fn update(&mut self, config: &Lod, num_atoms: usize, radius_pixels: f32) -> LodLevel {
+        if num_atoms < config.min_atoms {
+            self.level = LodLevel::Full;
+            return self.level;
+        }
+
+        let level_for = |margin: f32| {
+            if radius_pixels < config.points_radius_pixels * margin {
+                LodLevel::Points
+            } else if radius_pixels < config.simplified_radius_pixels * margin {
+                LodLevel::Simplified
+            } else {
+                LodLevel::Full
+            }
+        };
+
+        // Coarser levels apply at once, finer ones only with the hysteresis margin
+        let level = level_for(1.0);
+        self.level = if level >= self.level {
+            level
+        } else {
+            level_for(LOD_HYSTERESIS).min(self.level)
+        };
+        self.level
+    }
+}
+
+/// Index of the atom mesh for atoms of the apparent radius, 0 is the finest one.
+/// Finer meshes are restored with the same hysteresis as levels of detail.
+fn sphere_detail(config: &Geometry, radius_pixels: f32, current: usize) -> usize {
+    let detail_for = |margin: f32| {
+        if radius_pixels < config.sphere_coarse_radius_pixels * margin {
+            2
+        } else if radius_pixels < config.sphere_medium_radius_pixels * margin {
+            1
+        } else {
+            0
+        }
+    };
+
+    let detail = detail_for(1.0);
+    if detail >= current {
+        detail
+    } else {
+        detail_for(LOD_HYSTERESIS).min(current)
+    }
+}
+
+/// Part of the render target in pixels, (x, y) is the top left corner.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Viewport {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Viewport {
+    pub fn contains(&self, x: u32, y: u32) -> bool {
+        (self.x..self.x + self.width).contains(&x) && (self.y..self.y + self.height).contains(&y)
+    }
+
+    fn apply(&self, render_pass: &mut wgpu::RenderPass) {
+        render_pass.set_viewport(
+            self.x as f32,
+            self.y as f32,
+            self.width as f32,
+            self.height as f32,
+            0.0,
+            1.0,
+        );
+        render_pass.set_scissor_rect(self.x, self.y, self.width, self.height);
+    }
+}
+
+/// Distance from the camera to the selected atoms, or to the center of the molecule if nothing is selected.
+fn focus_depth(molecule: &Molecule, view_scene: &Mat4<f32>) -> f32 {
+    let selected = molecule.selected_atoms();
+    let depth = |position: Vec3<f32>| -view_scene.transform_point(position).z;
+    if selected.is_empty() {
+        return depth(Vec3::new(0.0, 0.0, 0.0));
+    }
+    let atoms = molecule.atoms();
+    selected.iter().map(|&i| depth(atoms[i].position)).sum::<f32>() / selected.len() as f32
+}
+
+/// Apparent radius in pixels of a sphere at the origin of scene space.
+fn radius_in_pixels(radius: f32, projection: &Mat4<f32>, view_scene: &Mat4<f32>, width: u32) -> f32 {
+    let center = view_scene.transform_point(Vec3::new(0.0, 0.0, 0.0));
+    let a = projection.transform_point(center);
+    let b = projection.transform_point(center + Vec3::new(radius, 0.0, 0.0));
+    (b.x - a.x).abs() * width as f32 / 2.0
+}
 
 pub struct Scene {
     pub projection_manager: ProjectionManager,
     pub transform: Transform,
+    // Objects placed in the rotated and zoomed scene, the molecule is the node `MOLECULE_NODE`
+    nodes: Node,
+    // Other structures of the scene, each of them with its own node
+    overlays: Vec<Overlay>,
+    next_node: u32,
+    pub camera: Camera,
     pub renderer: Renderer,
+    // Ray casting on the CPU instead of reading back the picking texture
+    pub cpu_picking: bool,
+    // Object under the cursor at the last hover or click
+    picked: Option<PickedObject>,
 
-    camera: Camera,
+    arcball: Arcball,
+    camera_flight: Option<CameraFlight>,
+    // Rotation restored by `reset_view`, see `orient`
+    initial_rotation: Quaternion<f32>,
     molecule: Option<Molecule>,
     cube_mesh: Mesh,
     cube_vb: VertexBuffer,
+    // Atom meshes from the finest to the coarsest, chosen by the apparent radius of atoms
+    sphere_meshes: [Mesh; 3],
+    sphere_vbs: [VertexBuffer; 3],
+    sphere_detail: usize,
+    cylinder_mesh: Mesh,
+    cylinder_vb: VertexBuffer,
+    cone_mesh: Mesh,
+    cone_vb: VertexBuffer,
+    rect_mesh: Mesh,
+    rect_vb: VertexBuffer,
+    simplified_cylinder_mesh: Mesh,
+    simplified_cylinder_vb: VertexBuffer,
+    lod: LodManager,
+
+    volume_cube: Option<VolumeCube>,
+    slice_settings: SliceSettings,
+    slice_visible: bool,
+    slice: Option<Slice>,
+    isosurface_settings: IsosurfaceSettings,
+    isosurface_visible: bool,
+    isosurface: Option<Isosurface>,
+
+    labels: Labels,
+    font_atlas: Option<FontAtlas>, // created when the first label is shown
+
+    culling: Option<Culling>, // created when the first large molecule is drawn
+    chunk_culling: ChunkCulling,
+
+    post_process: Option<PostProcess>, // created when effects are first enabled
+
+    measurement: MeasurementOverlay, // of the selected atoms
+
+    cell: Option<UnitCell>, // periodic cell of the structure
+    cell_box: CellBox,
+    supercell: Supercell, // copies of the structure in the neighboring cells
+
+    animation: Option<Animation>, // frames of the structure
+    symmetry: Option<Symmetry>,   // kept while atoms are moved in the symmetry-constrained editing mode
+    vibration: Option<Vibration>, // normal modes of the structure
+    clash_fraction: Option<f64>,  // edits highlight atoms closer than this fraction of their van der Waals radii
+    clashes: Vec<Clash>,          // found after the last edit
+    clash_halos: ClashHalos,      // around atoms overlapping within their covalent radii
+    focus_mode: Option<FocusMode>,
+    focus_dirty: bool, // the selection or the positions changed in the focus mode, dimming is updated on render
 
     picking_texture_dirty: bool,
+
+    #[cfg(feature = "debug-tools")]
+    debug_view: Option<DebugView>,
+    #[cfg(all(feature = "debug-tools", not(target_arch = "wasm32")))]
+    shader_watcher: ShaderWatcher,
 }
 
 impl Scene {
-    pub fn new(device: &wgpu::Device, surface_config: &wgpu::SurfaceConfiguration) -> Self {
+    pub fn new(device: &wgpu::Device, surface_config: &wgpu::SurfaceConfiguration, config: &Config) -> Self {
+        Self::with_renderer(device, Renderer::new(device, surface_config), config)
+    }
+
+    /// Scene drawn with pipelines and render targets shared with other scenes, e.g. cells of a grid.
+    pub fn with_renderer(device: &wgpu::Device, renderer: Renderer, config: &Config) -> Self {
         let cube_mesh = mesh_objects::cube::create(2.0);
+        let subdivisions = config.style.geometry.sphere_subdivisions;
+        let sphere_meshes = [0, 1, 2].map(|i| mesh_objects::sphere::create(1.0, subdivisions.saturating_sub(i)));
+        let cylinder_mesh = mesh_objects::cylinder::create(1.0, 2.0, config.style.geometry.cylinder_segments);
+        let cone_mesh = mesh_objects::cone::create(1.0, 2.0, config.style.geometry.cylinder_segments);
+        let rect_mesh = mesh_objects::rect::create(2.0, 2.0);
+        let simplified_cylinder_mesh =
+            mesh_objects::cylinder::create(1.0, 2.0, config.lod.simplified_cylinder_segments);
         Self {
             projection_manager: ProjectionManager::new(1, 1, ProjectionMode::Perspective),
             transform: Transform::new(),
-            renderer: Renderer::new(device, surface_config),
+            nodes: Node::new(ROOT_NODE).with_child(Node::new(MOLECULE_NODE)),
+            overlays: Vec::new(),
+            next_node: MOLECULE_NODE + 1,
+            renderer,
+            cpu_picking: false,
+            picked: None,
             camera: Camera::new(),
+            arcball: Arcball::new(),
+            camera_flight: None,
+            initial_rotation: Quaternion::new(1.0, 0.0, 0.0, 0.0),
             molecule: None,
             cube_vb: VertexBuffer::new(device, &cube_mesh),
             cube_mesh,
+            sphere_vbs: sphere_meshes.each_ref().map(|mesh| VertexBuffer::new(device, mesh)),
+            sphere_meshes,
+            sphere_detail: 0,
+            cylinder_vb: VertexBuffer::new(device, &cylinder_mesh),
+            cylinder_mesh,
+            cone_vb: VertexBuffer::new(device, &cone_mesh),
+            cone_mesh,
+            rect_vb: VertexBuffer::new(device, &rect_mesh),
+            rect_mesh,
+            simplified_cylinder_vb: VertexBuffer::new(device, &simplified_cylinder_mesh),
+            simplified_cylinder_mesh,
+            lod: LodManager::new(),
+            volume_cube: None,
+            slice_settings: SliceSettings::default(),
+            slice_visible: false,
+            slice: None,
+            isosurface_settings: IsosurfaceSettings::default(),
+            isosurface_visible: false,
+            isosurface: None,
+            labels: Labels::new(),
+            font_atlas: None,
+            culling: None,
+            chunk_culling: ChunkCulling::new(),
+            post_process: None,
+            measurement: MeasurementOverlay::new(),
+            cell: None,
+            cell_box: CellBox::new(),
+            supercell: Supercell::new(),
+            animation: None,
+            symmetry: None,
+            clash_fraction: Some(DEFAULT_CLASH_FRACTION),
+            clashes: Vec::new(),
+            clash_halos: ClashHalos::new(),
+            focus_mode: None,
+            focus_dirty: false,
+            vibration: None,
             picking_texture_dirty: true,
+            #[cfg(feature = "debug-tools")]
+            debug_view: None,
+            #[cfg(all(feature = "debug-tools", not(target_arch = "wasm32")))]
+            shader_watcher: ShaderWatcher::new(),
         }
     }
 
@@ -41,37 +358,1531 @@ impl Scene {
             .orthographic_projection
             .set_view_bounds(scene_size + scene_size * 0.10);
 
-        let fov_factor = self.projection_manager.perspective_projection.get_fov() / 45.0;
+        self.camera_flight = None;
+        self.camera.reset_to_default();
+        self.camera
+            .set_position(Vec3::new(0.0, 0.0, self.framing_distance(scene_size)));
+        self.update_far_plane(scene_size);
+    }
+
+    // Distance of the camera from a sphere of the radius filling the view: three radii away with the default
+    // field of view, narrower fields of view need the camera farther away
+    fn framing_distance(&self, radius: f32) -> f32 {
+        let fov = self.projection_manager.perspective_projection.get_fov();
+        let distance_factor = (DEFAULT_FOV / 2.0).to_radians().tan() / (fov / 2.0).to_radians().tan();
+        3.0 * radius * distance_factor
+    }
+
+    // The far plane stays behind the molecule however far the camera is moved away
+    fn update_far_plane(&mut self, scene_size: f32) {
+        let fov_factor = self.projection_manager.perspective_projection.get_fov() / DEFAULT_FOV;
+        let far_plane = (10.0 * scene_size / fov_factor).max(self.camera.distance() + 2.0 * scene_size);
+        self.projection_manager
+            .perspective_projection
+            .set_near_far_plane(0.1, far_plane);
+    }
+
+    /// Turns the camera around its target, see `Camera::orbit`.
+    pub fn orbit_camera(&mut self, yaw: f32, pitch: f32) {
+        self.camera.orbit(yaw, pitch);
+    }
+
+    /// Moves the camera in the view plane so that points at the depth of its target follow the cursor moved by
+    /// `dx`, `dy` pixels of the viewport (Y down).
+    pub fn pan_camera(&mut self, dx: f32, dy: f32, width: u32, height: u32) {
+        // Both projections fit their view bounds or field of view to the narrower side of the viewport
+        let size = match self.projection_manager.mode {
+            ProjectionMode::Orthographic => 2.0 * self.projection_manager.orthographic_projection.get_view_bounds(),
+            ProjectionMode::Perspective => {
+                let fov = self.projection_manager.perspective_projection.get_fov();
+                2.0 * self.camera.distance() * (fov / 2.0).to_radians().tan()
+            }
+        };
+        let scale = size / width.min(height).max(1) as f32;
+        self.camera_flight = None;
+        self.camera.pan(-dx * scale, dy * scale);
+    }
+
+    /// Moves the camera towards its target, `factor` above 1 zooms in, see `Camera::dolly`. The orthographic
+    /// projection zooms alike, though the distance does not change the picture of it.
+    pub fn dolly_camera(&mut self, factor: f32) {
+        self.camera_flight = None;
+        let ratio = self.camera.dolly(factor);
+        self.follow_camera_distance(ratio);
+    }
+
+    /// Sets the limits of the camera navigation and moves the camera within them.
+    pub fn set_camera_limits(&mut self, limits: CameraLimits) -> Result<(), String> {
+        let distance = self.camera.distance();
+        self.camera.set_limits(limits)?;
+        if distance > 0.0 {
+            self.follow_camera_distance(self.camera.distance() / distance);
+        }
+        Ok(())
+    }
+
+    /// Flies the camera to the bounding sphere of the atoms (indices start from 1), so that they fill the view
+    /// like the whole molecule does after loading, see `step_camera_flight`. With `dim` the other atoms and
+    /// their bonds are dimmed, otherwise all atoms are shown as they are.
+    pub fn focus_on(&mut self, queue: &wgpu::Queue, indices: &[usize], dim: bool) -> Result<(), String> {
+        if indices.is_empty() {
+            return Err("No atoms to focus on".to_string());
+        }
+        for &index in indices {
+            self.check_atom_index(index)?;
+        }
+        let atoms: Vec<usize> = indices.iter().map(|index| index - 1).collect();
+        let Some(matrix) = self.molecule_matrix() else {
+            return Ok(());
+        };
+        let scale = self.transform.scale.x;
+        let Some(molecule) = &mut self.molecule else {
+            return Ok(());
+        };
+
+        // Spheres of the atoms in world space around the center of their bounding box
+        let spheres: Vec<(Vec3<f32>, f32)> = atoms
+            .iter()
+            .map(|&i| {
+                let atom = &molecule.atoms()[i];
+                (matrix.transform_point(atom.position), atom.radius * scale)
+            })
+            .collect();
+        let (mut min, mut max) = (spheres[0].0, spheres[0].0);
+        for (p, _) in &spheres {
+            min = Vec3::new(min.x.min(p.x), min.y.min(p.y), min.z.min(p.z));
+            max = Vec3::new(max.x.max(p.x), max.y.max(p.y), max.z.max(p.z));
+        }
+        let center = (min + max) * 0.5;
+        let radius = spheres
+            .iter()
+            .map(|&(p, r)| (p - center).length() + r)
+            .fold(MIN_FOCUS_RADIUS * scale, f32::max);
+
+        molecule.set_focused_atoms(dim.then_some(&atoms), Dimming::Fade, queue);
+        self.camera_flight = Some(CameraFlight::new(
+            &self.camera,
+            center,
+            self.framing_distance(radius),
+            FOCUS_DURATION,
+        ));
+        Ok(())
+    }
+
+    /// Shows the atoms dimmed by `focus_on` as they are.
+    pub fn clear_focus(&mut self, queue: &wgpu::Queue) {
+        if let Some(molecule) = &mut self.molecule {
+            molecule.set_focused_atoms(None, Dimming::Fade, queue);
+        }
+    }
+
+    /// Turns on the focus mode on the selection or turns it off with `None`, which also restores atoms dimmed
+    /// by `focus_on`.
+    pub fn set_focus_mode(&mut self, queue: &wgpu::Queue, focus_mode: Option<FocusMode>) -> Result<(), String> {
+        if let Some(mode) = focus_mode
+            && (!mode.cutoff.is_finite() || mode.cutoff < 0.0)
+        {
+            return Err(format!("Invalid focus cutoff: {}", mode.cutoff));
+        }
+        self.focus_mode = focus_mode;
+        self.update_focus(queue);
+        Ok(())
+    }
+
+    // Dims the atoms out of focus of the focus mode
+    fn update_focus(&mut self, queue: &wgpu::Queue) {
+        self.focus_dirty = false;
+        let Some(molecule) = &mut self.molecule else {
+            return;
+        };
+        match self.focus_mode {
+            Some(mode) if !molecule.selected_atoms().is_empty() => {
+                let focused = molecule.atoms_near(molecule.selected_atoms(), mode.cutoff);
+                molecule.set_focused_atoms(Some(&focused), mode.dimming, queue);
+            }
+            _ => molecule.set_focused_atoms(None, Dimming::Fade, queue),
+        }
+    }
+
+    /// Moves the camera along its flight to focused atoms, `time` is in milliseconds. Returns `true` if
+    /// the camera was moved.
+    pub fn step_camera_flight(&mut self, time: f64) -> bool {
+        let Some(flight) = &mut self.camera_flight else {
+            return false;
+        };
+        let distance = self.camera.distance();
+        if !flight.step(&mut self.camera, time) {
+            self.camera_flight = None;
+        }
+        if distance > 0.0 {
+            self.follow_camera_distance(self.camera.distance() / distance);
+        }
+        true
+    }
+
+    pub fn is_camera_flying(&self) -> bool {
+        self.camera_flight.is_some()
+    }
+
+    // Projections follow the camera moved `ratio` times farther from its target
+    fn follow_camera_distance(&mut self, ratio: f32) {
+        let orthographic = &mut self.projection_manager.orthographic_projection;
+        orthographic.set_view_bounds(orthographic.get_view_bounds() * ratio);
+        if let Some(radius) = self.molecule.as_ref().map(|molecule| molecule.radius) {
+            self.update_far_plane(radius);
+        }
+    }
+
+    pub fn projection_mode(&self) -> ProjectionMode {
+        self.projection_manager.mode
+    }
+
+    pub fn set_projection_mode(&mut self, mode: ProjectionMode) {
+        self.projection_manager.mode = mode;
+    }
+
+    /// Field of view of the perspective projection in degrees.
+    pub fn fov(&self) -> f32 {
+        self.projection_manager.get_fov()
+    }
+
+    /// Sets the field of view in degrees and moves the camera so that the molecule keeps its size on screen.
+    pub fn set_fov(&mut self, fov: f32) -> Result<(), String> {
+        if !(MIN_FOV..=MAX_FOV).contains(&fov) {
+            return Err(format!(
+                "Field of view must be between {} and {} degrees: {}",
+                MIN_FOV, MAX_FOV, fov
+            ));
+        }
+        self.projection_manager.set_fov(fov);
+        if let Some(radius) = self.molecule.as_ref().map(|molecule| molecule.radius) {
+            self.setup_camera(radius);
+        }
+        Ok(())
+    }
+
+    /// Zooms so that the visible atoms fill the view, the rotation is kept.
+    pub fn zoom_to_fit(&mut self) {
+        let Some(molecule) = &self.molecule else {
+            return;
+        };
+        let visible_radius = molecule.visible_radius();
+        if visible_radius > 0.0 {
+            let scale = molecule.radius / visible_radius;
+            self.transform.set_scale(Vec3::new(scale, scale, scale));
+        }
+    }
+
+    /// Restores the initial rotation and zoom and the camera. The projection mode and field of view are kept.
+    pub fn reset_view(&mut self) {
+        self.arcball.stop();
+        self.transform = Transform::new();
+        self.transform.set_rotation(self.initial_rotation);
+        if let Some(radius) = self.molecule.as_ref().map(|molecule| molecule.radius) {
+            self.setup_camera(radius);
+        }
+    }
+
+    /// Sets the initial rotation of the molecule and restores the initial view: along the principal axes of the
+    /// molecule if `auto` (see `view::principal_orientation`), otherwise as in the loaded structure.
+    pub fn orient(&mut self, auto: bool) {
+        self.initial_rotation = match (&self.molecule, auto) {
+            (Some(molecule), true) => {
+                let positions: Vec<Vec3<f32>> = molecule.atoms().iter().map(|atom| atom.position).collect();
+                view::principal_orientation(&positions)
+            }
+            _ => Quaternion::new(1.0, 0.0, 0.0, 0.0),
+        };
+        self.reset_view();
+    }
+
+    /// Node of the scene graph with the id, e.g. `MOLECULE_NODE` to move or hide the molecule.
+    pub fn node_mut(&mut self, id: u32) -> Option<&mut Node> {
+        self.picking_texture_dirty = true;
+        self.nodes.find_mut(id)
+    }
+
+    /// Adds another structure to the scene, e.g. a conformer to overlay with the molecule, and returns the id of
+    /// its node. The structure keeps its position relative to the molecule as in the coordinates, see
+    /// `center_structure`. It is drawn with the style of the molecule but is not pickable or editable.
+    pub async fn add_structure(
+        &mut self,
+        device: &wgpu::Device,
+        config: &Config,
+        data: AtomicCoordinates,
+    ) -> Result<u32, String> {
+        if data.atomic_num.is_empty() {
+            return Err("The structure has no atoms".to_string());
+        }
+        let mut molecule =
+            Molecule::new(device, config, &data, &Progress::new(None), &CancellationToken::none()).await?;
+        if let Some(ray_casting) = self.molecule.as_ref().map(Molecule::is_ray_casting) {
+            molecule.set_ray_casting(ray_casting, device);
+        }
+        // Both molecules are centered on their own origins
+        let reference = self
+            .molecule
+            .as_ref()
+            .map_or(molecule.origin, |reference| reference.origin);
+        let offset: [f32; 3] = std::array::from_fn(|k| (molecule.origin[k] - reference[k]) as f32);
+
+        let id = self.next_node;
+        self.next_node += 1;
+        let mut node = Node::new(id);
+        node.transform.set_position(Vec3::new(offset[0], offset[1], offset[2]));
+        self.nodes.children.push(node);
+        self.overlays.push(Overlay::new(
+            device,
+            &self.renderer.bind_group_layout,
+            id,
+            molecule,
+            data,
+        ));
+        Ok(id)
+    }
+
+    /// Removes the structure added with `add_structure`, the molecule itself cannot be removed.
+    pub fn remove_structure(&mut self, id: u32) -> Result<(), String> {
+        let i = self.overlay_index(id)?;
+        self.overlays.remove(i);
+        self.nodes.remove(id);
+        Ok(())
+    }
+
+    /// Moves the structure (or the molecule with `MOLECULE_NODE`) so that its center is at the center of the
+    /// molecule as loaded, e.g. to compare conformers from different files.
+    pub fn center_structure(&mut self, id: u32) -> Result<(), String> {
+        if id != MOLECULE_NODE {
+            self.overlay_index(id)?;
+        }
+        if let Some(node) = self.node_mut(id) {
+            node.transform.set_position(Vec3::new(0.0, 0.0, 0.0));
+        }
+        Ok(())
+    }
+
+    /// Superposes the structure (or the molecule with `MOLECULE_NODE`) onto the `target` one by moving and
+    /// rotating its node, using the pairs of atoms (zero-based, the atom of `id` first) or all atoms paired in
+    /// order if `None`. The node takes the scale of the target node.
+    pub fn align_structure(
+        &mut self,
+        id: u32,
+        target: u32,
+        pairs: Option<&[(usize, usize)]>,
+        tolerances: &Tolerances,
+    ) -> Result<Alignment, String> {
+        if id == target {
+            return Err("A structure cannot be aligned onto itself".to_string());
+        }
+        let (mobile, mobile_origin) = self.structure_positions(id)?;
+        let (positions, target_origin) = self.structure_positions(target)?;
+        let alignment = alignment::align(&mobile, &positions, pairs, tolerances)?;
+
+        // Molecule space of the structure to the one of the target: both molecules are centered on their origins
+        let mut matrix = Mat4::new();
+        matrix.translate(target_origin.map(|x| -x).into());
+        let mut matrix = matrix * alignment.matrix();
+        matrix.translate(mobile_origin.into());
+        let matrix = Mat4::from_array(matrix.data.map(|x| x as f32));
+
+        let target_node = self
+            .nodes
+            .find_mut(target)
+            .ok_or_else(|| format!("Unknown structure: {}", target))?;
+        let (scale, rotation) = (target_node.transform.scale, target_node.transform.rotation);
+        let world = *target_node.transform.get_matrix() * matrix;
+        let r = alignment.rotation;
+        let rotation = rotation * Quaternion::new(r.w as f32, r.x as f32, r.y as f32, r.z as f32);
+        if let Some(node) = self.node_mut(id) {
+            node.transform
+                .set_position(world.transform_point(Vec3::new(0.0, 0.0, 0.0)));
+            node.transform.set_rotation(rotation);
+            node.transform.set_scale(scale);
+        }
+        Ok(alignment)
+    }
+
+    // Coordinates of the atoms of the structure with the id as loaded with the origin of its molecule
+    fn structure_positions(&self, id: u32) -> Result<(Vec<[f64; 3]>, [f64; 3]), String> {
+        if id == MOLECULE_NODE {
+            let molecule = self.molecule.as_ref().ok_or("No structure is loaded")?;
+            return Ok((self.atom_coordinates(), molecule.origin));
+        }
+        let overlay = &self.overlays[self.overlay_index(id)?];
+        let data = &overlay.data;
+        let positions = (0..data.atomic_num.len())
+            .map(|i| [data.x[i], data.y[i], data.z[i]])
+            .collect();
+        Ok((positions, overlay.molecule.origin))
+    }
+
+    /// Ids of the nodes of the structures added with `add_structure`.
+    pub fn structures(&self) -> Vec<u32> {
+        self.overlays.iter().map(|overlay| overlay.node).collect()
+    }
+
+    fn overlay_index(&self, id: u32) -> Result<usize, String> {
+        self.overlays
+            .iter()
+            .position(|overlay| overlay.node == id)
+            .ok_or_else(|| format!("Unknown structure: {}", id))
+    }
+
+    // Matrix from molecule space to world space: the rotation and zoom of the scene followed by the transforms
+    // of the nodes down to the molecule, `None` if the molecule is hidden
+    fn molecule_matrix(&mut self) -> Option<Mat4<f32>> {
+        let scene = *self.transform.get_matrix();
+        self.nodes.world_matrix(&scene, MOLECULE_NODE)
+    }
+
+    pub fn resize(&mut self, device: &wgpu::Device, config: &wgpu::SurfaceConfiguration) {
+        self.renderer.resize(device, config);
+    }
+
+    pub fn view_state(&self) -> ViewState {
+        ViewState::of(&self.transform)
+    }
+
+    pub fn set_view_state(&mut self, view: &ViewState) {
+        view.apply(&mut self.transform);
+    }
+
+    /// Starts rotating the molecule with the mouse at the pixel of the viewport, `time` is in milliseconds.
+    /// An inertial rotation is stopped.
+    pub fn begin_rotation(&mut self, x: f32, y: f32, width: u32, height: u32, time: f64) {
+        self.arcball.begin(x, y, width, height, time);
+    }
+
+    /// Rotates the molecule so that the point under the cursor follows it, returns `true` if it was rotated.
+    pub fn drag_rotation(&mut self, x: f32, y: f32, time: f64) -> bool {
+        match self.arcball.drag(x, y, time) {
+            Some(rotation) => {
+                self.transform.rotate_by(rotation);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Ends the rotation with the mouse, returns `true` if it continues with inertia.
+    pub fn end_rotation(&mut self, time: f64) -> bool {
+        self.arcball.end(time)
+    }
+
+    /// Continues the inertial rotation, returns `true` if the molecule was rotated.
+    pub fn step_inertia(&mut self, time: f64) -> bool {
+        match self.arcball.step(time) {
+            Some(rotation) => {
+                self.transform.rotate_by(rotation);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn is_spinning(&self) -> bool {
+        self.arcball.is_spinning()
+    }
+
+    /// Fraction of the angular speed lost per second after release, `None` disables inertia.
+    pub fn set_inertia(&mut self, damping: Option<f32>) -> Result<(), String> {
+        if let Some(damping) = damping.filter(|d| !(0.0..=1.0).contains(d)) {
+            return Err(format!("Damping must be in range [0, 1], got {}", damping));
+        }
+        self.arcball.damping = damping;
+        Ok(())
+    }
+
+    /// Replaces the shading of atoms and bonds with a debug visualization, `None` restores it.
+    #[cfg(feature = "debug-tools")]
+    pub fn set_debug_view(&mut self, view: Option<DebugView>) {
+        self.debug_view = view;
+    }
+
+    /// Recreates all pipelines from the shaders in the source tree. Invalid shaders are reported
+    /// and the current pipelines are kept.
+    #[cfg(all(feature = "debug-tools", not(target_arch = "wasm32")))]
+    pub async fn reload_shaders(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        surface_config: &wgpu::SurfaceConfiguration,
+    ) -> Result<(), String> {
+        let scope = device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let renderer = Renderer::new(device, surface_config);
+        let culling = Culling::is_supported(device).then(|| Culling::new(device, &renderer.bind_group_layout));
+        if let Some(error) = scope.pop().await {
+            return Err(format!("Failed to reload shaders: {error}"));
+        }
+
+        self.renderer = renderer;
+        // Bind groups created with layouts of the previous renderer
+        self.culling = culling.filter(|_| self.culling.is_some());
+        self.post_process = None;
+        self.font_atlas = None;
+        self.picking_texture_dirty = true;
+        self.update_slice(device, queue)
+    }
+
+    /// Reloads the shaders if any of them was edited since the previous call, returns whether they were reloaded.
+    #[cfg(all(feature = "debug-tools", not(target_arch = "wasm32")))]
+    pub async fn reload_changed_shaders(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        surface_config: &wgpu::SurfaceConfiguration,
+    ) -> Result<bool, String> {
+        if !self.shader_watcher.poll() {
+            return Ok(false);
+        }
+        self.reload_shaders(device, queue, surface_config).await?;
+        Ok(true)
+    }
+
+    fn opaque_pipeline(&self) -> &wgpu::RenderPipeline {
+        #[cfg(feature = "debug-tools")]
+        if self.debug_view == Some(DebugView::Overdraw) {
+            return &self.renderer.overdraw_pipeline;
+        }
+        &self.renderer.pipeline
+    }
+
+    fn clear_color(&self, config: &Config) -> wgpu::Color {
+        // Overdraw adds up from black
+        #[cfg(feature = "debug-tools")]
+        if self.debug_view == Some(DebugView::Overdraw) {
+            return wgpu::Color::BLACK;
+        }
+        config.style.clear_color()
+    }
+
+    pub async fn load_atomic_coordinates(
+        &mut self,
+        device: &wgpu::Device,
+        config: &Config,
+        data: &AtomicCoordinates,
+        progress: &Progress,
+        cancel: &CancellationToken,
+    ) -> Result<(), String> {
+        let _span = profiling::span("load structure");
+        match Molecule::new(device, config, data, progress, cancel).await {
+            Ok(molecule) => {
+                self.setup_camera(molecule.radius);
+                self.set_molecule(molecule, data);
+            }
+            Err(e) if cancel.is_cancelled() => return Err(e),
+            Err(_) => {}
+        }
+        Ok(())
+    }
+
+    /// Replaces the structure after atoms have been added or removed, e.g. by attaching a template, keeping
+    /// the view. Per-atom state (selection, labels, planes, symmetry, vibration) is cleared.
+    pub async fn replace_atomic_coordinates(
+        &mut self,
+        device: &wgpu::Device,
+        config: &Config,
+        data: &AtomicCoordinates,
+    ) -> Result<(), String> {
+        let molecule = Molecule::new(device, config, data, &Progress::new(None), &CancellationToken::none()).await?;
+        self.set_molecule(molecule, data);
+        Ok(())
+    }
+
+    fn set_molecule(&mut self, molecule: Molecule, data: &AtomicCoordinates) {
+        self.molecule = Some(molecule);
+        self.picked = None;
+        self.labels.clear_custom_texts();
+        self.measurement.clear_planes();
+        self.cell = data.cell;
+        self.cell_box.invalidate();
+        self.supercell = Supercell::new();
+        self.chunk_culling = ChunkCulling::new();
+        self.animation = None;
+        self.symmetry = None;
+        self.vibration = None;
+        self.clashes.clear();
+        self.clash_halos.invalidate();
+        self.measure_selection();
+    }
+
+    pub fn set_ray_casting(&mut self, device: &wgpu::Device, ray_casting: bool) {
+        for overlay in &mut self.overlays {
+            overlay.molecule.set_ray_casting(ray_casting, device);
+        }
+        if let Some(molecule) = self.molecule.as_mut() {
+            molecule.set_ray_casting(ray_casting, device);
+            self.measurement.invalidate();
+            self.cell_box.invalidate();
+            self.clash_halos.invalidate();
+            self.picking_texture_dirty = true;
+        }
+    }
+
+    pub fn set_representation(&mut self, device: &wgpu::Device, config: &Config) {
+        for overlay in &mut self.overlays {
+            overlay.molecule.set_representation(&config.style, device);
+        }
+        if let Some(molecule) = self.molecule.as_mut() {
+            molecule.set_representation(&config.style, device);
+            self.labels.invalidate();
+            self.picking_texture_dirty = true;
+        }
+    }
+
+    pub fn set_color_scheme(&mut self, device: &wgpu::Device, config: &Config) -> Result<(), String> {
+        for overlay in &mut self.overlays {
+            overlay.molecule.set_color_scheme(&config.style, device)?;
+        }
+        if let Some(molecule) = self.molecule.as_mut() {
+            molecule.set_color_scheme(&config.style, device)?;
+        }
+        Ok(())
+    }
+
+    /// Rebuilds bonds of the structure `data` of the molecule and of the other structures after the bond
+    /// tolerance has changed.
+    pub fn set_bond_tolerance(&mut self, device: &wgpu::Device, config: &Config, data: &AtomicCoordinates) {
+        for overlay in &mut self.overlays {
+            overlay
+                .molecule
+                .set_bond_tolerance(&overlay.data, &config.style, device);
+        }
+        if let Some(molecule) = self.molecule.as_mut() {
+            molecule.set_bond_tolerance(data, &config.style, device);
+            self.labels.invalidate();
+        }
+    }
+
+    /// Applies all settings of the style to the molecule, e.g. after a style preset.
+    pub fn set_style(&mut self, device: &wgpu::Device, config: &Config) -> Result<(), String> {
+        for overlay in &mut self.overlays {
+            overlay.molecule.set_color_scheme(&config.style, device)?;
+            overlay.molecule.set_representation(&config.style, device);
+        }
+        if let Some(molecule) = self.molecule.as_mut() {
+            molecule.set_color_scheme(&config.style, device)?;
+            molecule.set_representation(&config.style, device);
+            self.labels.invalidate();
+            self.measurement.invalidate();
+            self.cell_box.invalidate();
+            self.clash_halos.invalidate();
+            if let Some(vibration) = self.vibration.as_mut() {
+                vibration.invalidate();
+            }
+            self.picking_texture_dirty = true;
+        }
+        Ok(())
+    }
+
+    pub fn set_labels_visible(&mut self, kind: LabelKind, visible: bool) {
+        self.labels.set_visible(kind, visible);
+    }
+
+    /// Shows the lengths of all bonds, of the bonds between selected atoms or of none.
+    pub fn set_bond_labels(&mut self, bond_labels: BondLabels) {
+        self.labels.set_bond_labels(bond_labels);
+    }
+
+    /// Sets the custom label of the atom (index starts from 1), an empty text removes it.
+    pub fn set_atom_label(&mut self, index: usize, text: &str) -> Result<(), String> {
+        self.check_atom_index(index)?;
+        self.labels.set_custom_text(index, text);
+        Ok(())
+    }
+
+    /// Labels the atom (index starts from 1) with the mass number of an isotope, 0 for the natural composition.
+    pub fn set_mass_number(&mut self, index: usize, mass_number: u32) -> Result<(), String> {
+        self.check_atom_index(index)?;
+        if let Some(molecule) = &mut self.molecule {
+            molecule.set_mass_number(index, mass_number)?;
+            self.labels.invalidate();
+        }
+        Ok(())
+    }
+
+    /// Mass numbers of the atoms, 0 for the unlabelled ones.
+    pub fn mass_numbers(&self) -> Vec<u32> {
+        self.molecule.as_ref().map_or_else(Vec::new, |molecule| {
+            molecule.atoms().iter().map(|atom| atom.mass_number).collect()
+        })
+    }
+
+    /// Shows or hides the atom (index starts from 1) together with its bonds.
+    pub fn set_atom_visible(&mut self, queue: &wgpu::Queue, index: usize, visible: bool) -> Result<(), String> {
+        self.check_atom_index(index)?;
+        if let Some(molecule) = &mut self.molecule
+            && molecule.set_atom_visible(index, visible, queue)
+        {
+            self.labels.invalidate();
+            self.picking_texture_dirty = true;
+        }
+        Ok(())
+    }
+
+    /// Sets the radius and the color overriding the style for the atoms (indices start from 1), an empty style
+    /// removes the override. The atoms keep it when the representation or the color scheme changes.
+    pub fn set_atom_style(
+        &mut self,
+        device: &wgpu::Device,
+        config: &Config,
+        indices: &[usize],
+        atom_style: AtomStyle,
+    ) -> Result<(), String> {
+        for &index in indices {
+            self.check_atom_index(index)?;
+        }
+        if atom_style
+            .radius
+            .is_some_and(|radius| !radius.is_finite() || radius <= 0.0)
+        {
+            return Err("The radius of atoms must be positive".to_string());
+        }
+        let Some(molecule) = &mut self.molecule else {
+            return Err("No structure is loaded".to_string());
+        };
+        let indices: Vec<usize> = indices.iter().map(|i| i - 1).collect();
+        molecule.set_atom_style(&indices, atom_style, &config.style, device)?;
+        self.labels.invalidate();
+        self.measurement.invalidate();
+        self.picking_texture_dirty = true;
+        Ok(())
+    }
+
+    /// Radii and colors overriding the style by atom (zero-based).
+    pub fn atom_styles(&self) -> Vec<(usize, AtomStyle)> {
+        self.molecule
+            .iter()
+            .flat_map(|molecule| molecule.atom_styles())
+            .map(|(&i, &atom_style)| (i, atom_style))
+            .collect()
+    }
+
+    /// Elements of the molecule by atomic number: the number, how many atoms it has and how many of them are visible.
+    pub fn element_counts(&self) -> Vec<(i32, usize, usize)> {
+        let mut counts: Vec<(i32, usize, usize)> = Vec::new();
+        for atom in self.molecule.iter().flat_map(|molecule| molecule.atoms()) {
+            let i = match counts.binary_search_by_key(&atom.number, |&(number, _, _)| number) {
+                Ok(i) => i,
+                Err(i) => {
+                    counts.insert(i, (atom.number, 0, 0));
+                    i
+                }
+            };
+            counts[i].1 += 1;
+            counts[i].2 += atom.visible as usize;
+        }
+        counts
+    }
+
+    /// Shows or hides all atoms of the element with the atomic number together with their bonds.
+    pub fn set_element_visible(&mut self, queue: &wgpu::Queue, number: i32, visible: bool) -> Result<(), String> {
+        let Some(molecule) = &mut self.molecule else {
+            return Err("No structure is loaded".to_string());
+        };
+        let indices: Vec<usize> = molecule
+            .atoms()
+            .iter()
+            .enumerate()
+            .filter(|(_, atom)| atom.number == number)
+            .map(|(i, _)| i)
+            .collect();
+        if indices.is_empty() {
+            return Err(format!("No atoms of element {}", number));
+        }
+        if molecule.set_atoms_visible(&indices, visible, queue) {
+            self.labels.invalidate();
+            self.picking_texture_dirty = true;
+        }
+        Ok(())
+    }
+
+    /// Shows or hides all dummy atoms, point charges and ghost atoms together with their bonds.
+    pub fn set_pseudo_atoms_visible(&mut self, queue: &wgpu::Queue, visible: bool) {
+        let Some(molecule) = &mut self.molecule else {
+            return;
+        };
+        let indices: Vec<usize> = molecule
+            .atoms()
+            .iter()
+            .enumerate()
+            .filter(|(_, atom)| is_pseudo_element(atom.number))
+            .map(|(i, _)| i)
+            .collect();
+        if molecule.set_atoms_visible(&indices, visible, queue) {
+            self.labels.invalidate();
+            self.picking_texture_dirty = true;
+        }
+    }
+
+    /// Distance, angle or dihedral of the selected atoms, if 2 to 4 atoms are selected.
+    pub fn measurement(&self) -> Option<&MeasurementInfo> {
+        self.measurement.info()
+    }
+
+    /// Fits a plane through the atoms (indices start from 1), returns the index of the plane starting from 1.
+    pub fn add_plane(&mut self, indices: &[usize]) -> Result<usize, String> {
+        for &index in indices {
+            self.check_atom_index(index)?;
+        }
+        let Some(molecule) = &self.molecule else {
+            return Err("No structure is loaded".to_string());
+        };
+        let indices: Vec<usize> = indices.iter().map(|i| i - 1).collect();
+        self.measurement.add_plane(molecule.atoms(), &indices)
+    }
+
+    pub fn plane(&self, index: usize) -> Result<&PlaneInfo, String> {
+        self.measurement.plane(index)
+    }
+
+    pub fn clear_planes(&mut self) {
+        if self.measurement.clear_planes() {
+            self.labels.set_measurement_texts(Vec::new());
+        }
+    }
+
+    /// Measures the angle between two planes, indices start from 1.
+    pub fn measure_planes(&mut self, plane_1: usize, plane_2: usize) -> Result<(), String> {
+        let label = self.measurement.measure_planes(plane_1, plane_2)?;
+        self.labels.set_measurement_texts(vec![label]);
+        Ok(())
+    }
+
+    /// Measures the angle between the plane and the bond between two atoms, indices start from 1.
+    pub fn measure_plane_bond(&mut self, plane: usize, atom_1: usize, atom_2: usize) -> Result<(), String> {
+        self.check_atom_index(atom_1)?;
+        self.check_atom_index(atom_2)?;
+        if let Some(molecule) = &self.molecule {
+            let label = self
+                .measurement
+                .measure_plane_bond(molecule.atoms(), plane, atom_1 - 1, atom_2 - 1)?;
+            self.labels.set_measurement_texts(vec![label]);
+        }
+        Ok(())
+    }
+
+    /// Sets the frames of the loaded structure for the animation and shows the first one.
+    pub fn set_frames(
+        &mut self,
+        device: &wgpu::Device,
+        config: &Config,
+        frames: &[AtomicCoordinates],
+    ) -> Result<(), String> {
+        let Some(molecule) = &self.molecule else {
+            return Err("No structure is loaded".to_string());
+        };
+        let numbers: Vec<i32> = molecule.atoms().iter().map(|atom| atom.number).collect();
+        self.animation = Some(Animation::new(frames, &numbers, molecule.origin)?);
+        self.vibration = None;
+        self.update_frame(device, config);
+        Ok(())
+    }
+
+    /// Atom positions of every loaded frame in the coordinates of the structure, the current positions if no frames
+    /// are loaded.
+    pub fn frame_coordinates(&self) -> Vec<Vec<[f64; 3]>> {
+        let (Some(molecule), Some(animation)) = (&self.molecule, &self.animation) else {
+            return vec![self.atom_coordinates()];
+        };
+        let [x, y, z] = molecule.origin;
+        (0..animation.num_frames())
+            .filter_map(|index| animation.frame_positions(index))
+            .map(|positions| {
+                positions
+                    .iter()
+                    .map(|p| [x + p.x as f64, y + p.y as f64, z + p.z as f64])
+                    .collect()
+            })
+            .collect()
+    }
+
+    pub fn animation(&self) -> Option<&Animation> {
+        self.animation.as_ref()
+    }
+
+    pub fn animation_mut(&mut self) -> Result<&mut Animation, String> {
+        self.animation
+            .as_mut()
+            .ok_or_else(|| "No frames are loaded".to_string())
+    }
+
+    /// Binds values of every atom in every frame to the property color scheme and recolors the current frame,
+    /// `None` unbinds them.
+    pub fn set_frame_properties(
+        &mut self,
+        device: &wgpu::Device,
+        config: &Config,
+        properties: Option<Vec<Vec<f64>>>,
+    ) -> Result<(), String> {
+        self.animation_mut()?.set_properties(properties)?;
+        self.update_frame(device, config);
+        Ok(())
+    }
+
+    /// Advances the playing animation to the time in milliseconds, returns `true` if the atoms have moved.
+    pub fn advance_animation(&mut self, device: &wgpu::Device, config: &Config, time: f64) -> bool {
+        if !self.animation.as_mut().is_some_and(|animation| animation.advance(time)) {
+            return false;
+        }
+        self.update_frame(device, config);
+        true
+    }
+
+    /// Moves the atoms to the current frame of the animation.
+    pub fn update_frame(&mut self, device: &wgpu::Device, config: &Config) {
+        self.drop_vibration(device, config);
+        if let (Some(molecule), Some(animation)) = (self.molecule.as_mut(), &self.animation) {
+            molecule.set_property_values(animation.property_values(), &config.style);
+            molecule.set_positions(&animation.positions(), &config.style, device);
+            self.symmetry = None;
+            self.atoms_moved();
+        }
+    }
+
+    pub fn cell(&self) -> Option<&UnitCell> {
+        self.cell.as_ref()
+    }
+
+    /// Replaces the periodic cell of the structure. With `scale_atoms` the atoms keep their fractional
+    /// coordinates and move with the cell, otherwise their Cartesian positions are kept.
+    pub fn set_cell(
+        &mut self,
+        device: &wgpu::Device,
+        config: &Config,
+        cell: UnitCell,
+        scale_atoms: bool,
+    ) -> Result<(), String> {
+        if self.molecule.is_none() {
+            return Err("No structure is loaded".to_string());
+        }
+        if scale_atoms {
+            let Some(current) = self.cell else {
+                return Err("The structure has no cell to scale the atoms with".to_string());
+            };
+            self.drop_vibration(device, config);
+            if let Some(molecule) = self.molecule.as_mut() {
+                let origin = molecule.origin;
+                let moved: Vec<Vec3<f32>> = molecule
+                    .atoms()
+                    .iter()
+                    .map(|atom| {
+                        let p = atom.position;
+                        let point = [p.x as f64 + origin[0], p.y as f64 + origin[1], p.z as f64 + origin[2]];
+                        let [x, y, z] = cell.to_cartesian(current.to_fractional(point));
+                        Vec3::new((x - origin[0]) as f32, (y - origin[1]) as f32, (z - origin[2]) as f32)
+                    })
+                    .collect();
+                molecule.set_positions(&moved, &config.style, device);
+                self.symmetry = None;
+                self.atoms_moved();
+            }
+        }
+        if let Some(molecule) = self.molecule.as_mut() {
+            molecule.set_cell(cell, &config.style, device);
+        }
+        self.cell = Some(cell);
+        self.cell_box.invalidate();
+        self.supercell.invalidate();
+        Ok(())
+    }
+
+    /// Numbers of copies of the cell along the lattice vectors a, b and c.
+    pub fn supercell(&self) -> [u32; 3] {
+        self.supercell.repetitions()
+    }
+
+    /// Replicates the structure into `repetitions` cells along the lattice vectors, [1, 1, 1] shows the structure
+    /// alone. The copies follow the atoms when they move.
+    pub fn set_supercell(&mut self, repetitions: [u32; 3]) -> Result<(), String> {
+        if self.cell.is_none() && repetitions != [1, 1, 1] {
+            return Err("The structure has no periodic cell".to_string());
+        }
+        self.supercell.set_repetitions(repetitions)
+    }
+
+    /// Sets the normal modes of the loaded structure, the current positions of the atoms are the equilibrium.
+    /// Frames of the animation are dropped, the atoms do not move until the oscillation is played.
+    pub fn set_vibrational_modes(
+        &mut self,
+        device: &wgpu::Device,
+        config: &Config,
+        modes: &VibrationalModes,
+    ) -> Result<(), String> {
+        self.drop_vibration(device, config);
+        let Some(molecule) = &self.molecule else {
+            return Err("No structure is loaded".to_string());
+        };
+        let equilibrium = molecule.atoms().iter().map(|atom| atom.position).collect();
+        self.vibration = Some(Vibration::new(modes, equilibrium)?);
+        self.animation = None;
+        Ok(())
+    }
+
+    pub fn vibration(&self) -> Option<&Vibration> {
+        self.vibration.as_ref()
+    }
+
+    pub fn vibration_mut(&mut self) -> Result<&mut Vibration, String> {
+        self.vibration
+            .as_mut()
+            .ok_or_else(|| "No vibrational modes are loaded".to_string())
+    }
+
+    /// Advances the playing oscillation to the time in milliseconds, returns `true` if the atoms have moved.
+    pub fn advance_vibration(&mut self, device: &wgpu::Device, config: &Config, time: f64) -> bool {
+        if !self.vibration.as_mut().is_some_and(|vibration| vibration.advance(time)) {
+            return false;
+        }
+        self.update_vibration(device, config);
+        true
+    }
+
+    /// Moves the atoms to the current phase of the oscillation along the selected mode.
+    pub fn update_vibration(&mut self, device: &wgpu::Device, config: &Config) {
+        if let (Some(molecule), Some(vibration)) = (self.molecule.as_mut(), &self.vibration) {
+            molecule.set_positions(&vibration.positions(), &config.style, device);
+            self.atoms_moved();
+        }
+    }
+
+    /// Returns the atoms to the equilibrium and forgets the normal modes, e.g. before the structure is edited.
+    fn drop_vibration(&mut self, device: &wgpu::Device, config: &Config) {
+        if let (Some(molecule), Some(vibration)) = (self.molecule.as_mut(), self.vibration.take()) {
+            molecule.set_positions(vibration.equilibrium(), &config.style, device);
+            self.atoms_moved();
+        }
+    }
+
+    /// Rotates the side of `atom_2` of the bond about it by the angle in degrees, clockwise when looking
+    /// from `atom_1` to `atom_2`. Indices start from 1.
+    pub fn rotate_bond(
+        &mut self,
+        device: &wgpu::Device,
+        config: &Config,
+        atom_1: usize,
+        atom_2: usize,
+        angle: f32,
+    ) -> Result<(), String> {
+        self.check_atom_index(atom_1)?;
+        self.check_atom_index(atom_2)?;
+        self.drop_vibration(device, config);
+        if let Some(molecule) = self.molecule.as_mut() {
+            molecule.rotate_bond(atom_1 - 1, atom_2 - 1, angle, &config.style, device)?;
+            self.symmetry = None;
+            self.atoms_moved();
+            self.check_clashes();
+        }
+        Ok(())
+    }
+
+    /// Sets the dihedral of the atoms (indices start from 1) to the angle in degrees by rotating the side
+    /// of the third atom about the bond between the second and the third ones.
+    pub fn set_dihedral(
+        &mut self,
+        device: &wgpu::Device,
+        config: &Config,
+        atoms: [usize; 4],
+        angle: f64,
+    ) -> Result<(), String> {
+        for index in atoms {
+            self.check_atom_index(index)?;
+        }
+        let Some(molecule) = &self.molecule else {
+            return Ok(());
+        };
+        let p = positions(molecule.atoms(), &atoms.map(|i| i - 1));
+        let current = dihedral(p[0], p[1], p[2], p[3]).to_degrees();
+        let delta = (angle - current + 180.0).rem_euclid(360.0) - 180.0;
+        self.rotate_bond(device, config, atoms[1], atoms[2], delta as f32)
+    }
+
+    /// Positions of the atoms in the coordinates of the structure, e.g. after rotating bonds.
+    pub fn atom_coordinates(&self) -> Vec<[f64; 3]> {
+        let Some(molecule) = &self.molecule else {
+            return Vec::new();
+        };
+        let [x, y, z] = molecule.origin;
+        molecule
+            .atoms()
+            .iter()
+            .map(|atom| {
+                let p = atom.position;
+                [x + p.x as f64, y + p.y as f64, z + p.z as f64]
+            })
+            .collect()
+    }
+
+    /// Bonds between the atoms with zero-based indices.
+    pub fn bonds(&self) -> &[Bond] {
+        self.molecule.as_ref().map_or(&[], |molecule| molecule.bonds())
+    }
+
+    /// Detects the point group of the structure and turns on the symmetry-constrained editing mode,
+    /// in which `move_atom` moves the symmetry-equivalent atoms too. Returns the Schoenflies symbol.
+    pub fn detect_symmetry(&mut self, tolerance: f64) -> Result<&str, String> {
+        let Some(molecule) = &self.molecule else {
+            return Err("No structure is loaded".to_string());
+        };
+        let numbers: Vec<i32> = molecule.atoms().iter().map(|atom| atom.number).collect();
+        let indices: Vec<usize> = (0..numbers.len()).collect();
+        let symmetry = Symmetry::detect(&positions(molecule.atoms(), &indices), &numbers, tolerance)?;
+        Ok(self.symmetry.insert(symmetry).point_group())
+    }
+
+    pub fn symmetry(&self) -> Option<&Symmetry> {
+        self.symmetry.as_ref()
+    }
+
+    /// Turns off the symmetry-constrained editing mode.
+    pub fn clear_symmetry(&mut self) {
+        self.symmetry = None;
+    }
+
+    /// Moves the atom (index starts from 1) by the displacement in Angstroms. In the symmetry-constrained
+    /// editing mode the symmetry-equivalent atoms are moved too, so that the structure keeps its point group.
+    pub fn move_atom(
+        &mut self,
+        device: &wgpu::Device,
+        config: &Config,
+        index: usize,
+        displacement: Vec3<f64>,
+    ) -> Result<(), String> {
+        self.check_atom_index(index)?;
+        self.drop_vibration(device, config);
+        let Some(molecule) = self.molecule.as_mut() else {
+            return Ok(());
+        };
+        let mut moved: Vec<Vec3<f32>> = molecule.atoms().iter().map(|atom| atom.position).collect();
+        let to_f32 = |p: Vec3<f64>| Vec3::new(p.x as f32, p.y as f32, p.z as f32);
+        match &self.symmetry {
+            Some(symmetry) => {
+                let indices: Vec<usize> = (0..moved.len()).collect();
+                let current = positions(molecule.atoms(), &indices);
+                for (i, position) in symmetry.move_atom(&current, index - 1, displacement) {
+                    moved[i] = to_f32(position);
+                }
+            }
+            None => moved[index - 1] += to_f32(displacement),
+        }
+        molecule.set_positions(&moved, &config.style, device);
+        self.atoms_moved();
+        self.check_clashes();
+        Ok(())
+    }
+
+    /// Moves the atom (index starts from 1) in the plane of the screen by the distance in pixels,
+    /// see `move_atom`.
+    pub fn drag_atom(
+        &mut self,
+        device: &wgpu::Device,
+        config: &Config,
+        index: usize,
+        dx: f32,
+        dy: f32,
+    ) -> Result<(), String> {
+        self.check_atom_index(index)?;
+        let Some(position) = self
+            .molecule
+            .as_ref()
+            .map(|molecule| molecule.atoms()[index - 1].position)
+        else {
+            return Ok(());
+        };
+        let (width, height) = self.renderer.get_size();
+        let Some(molecule_matrix) = self.molecule_matrix() else {
+            return Ok(());
+        };
+        let matrix = *self.projection_manager.get_matrix() * *self.camera.get_matrix() * molecule_matrix;
+        let Some(inverse) = matrix.inverted() else {
+            return Ok(());
+        };
+        // The atom keeps its depth, the point under the cursor follows it
+        let ndc = matrix.transform_point(position);
+        let shifted = ndc + Vec3::new(2.0 * dx / width as f32, -2.0 * dy / height as f32, 0.0);
+        let d = inverse.transform_point(shifted) - position;
+        self.move_atom(device, config, index, Vec3::new(d.x as f64, d.y as f64, d.z as f64))
+    }
+
+    /// Point under the pixel (x, y) in the coordinates of the structure at the depth of its center, e.g. to put
+    /// a new atom there. None without a structure.
+    pub fn screen_point(&mut self, x: f32, y: f32) -> Option<[f64; 3]> {
+        let origin = self.molecule.as_ref()?.origin;
+        let (width, height) = self.renderer.get_size();
+        let molecule_matrix = self.molecule_matrix()?;
+        let matrix = *self.projection_manager.get_matrix() * *self.camera.get_matrix() * molecule_matrix;
+        let inverse = matrix.inverted()?;
+        let depth = matrix.transform_point(Vec3::new(0.0, 0.0, 0.0)).z;
+        let ndc = Vec3::new(2.0 * x / width as f32 - 1.0, 1.0 - 2.0 * y / height as f32, depth);
+        let p = inverse.transform_point(ndc);
+        Some([origin[0] + p.x as f64, origin[1] + p.y as f64, origin[2] + p.z as f64])
+    }
+
+    /// Atoms closer than `fraction` of the sum of their van der Waals radii are highlighted after every edit
+    /// (moved atoms and rotated bonds), `None` turns the check off and clears the highlighting.
+    pub fn set_clash_check(&mut self, fraction: Option<f64>) -> Result<(), String> {
+        if let Some(fraction) = fraction.filter(|f| !(f.is_finite() && *f > 0.0 && *f <= 1.0)) {
+            return Err(format!("Clash fraction must be in range (0, 1], got {}", fraction));
+        }
+        self.clash_fraction = fraction;
+        self.check_clashes();
+        Ok(())
+    }
+
+    pub fn clash_check(&self) -> Option<f64> {
+        self.clash_fraction
+    }
+
+    /// Clashes found after the last edit, with zero-based atom indices.
+    pub fn clashes(&self) -> &[Clash] {
+        &self.clashes
+    }
+
+    // Finds the clashes of the current positions with the bonds of the structure and highlights their atoms
+    fn check_clashes(&mut self) {
+        let Some(molecule) = self.molecule.as_mut() else {
+            return;
+        };
+        self.clashes = match self.clash_fraction {
+            Some(fraction) => {
+                let atoms = molecule.atoms();
+                let data = AtomicCoordinates {
+                    atomic_num: atoms.iter().map(|atom| atom.number).collect(),
+                    x: atoms.iter().map(|atom| atom.position.x as f64).collect(),
+                    y: atoms.iter().map(|atom| atom.position.y as f64).collect(),
+                    z: atoms.iter().map(|atom| atom.position.z as f64).collect(),
+                    connectivity: None,
+                    charges: None,
+                    chains: None,
+                    cell: self.cell,
+                    isotopes: None,
+                };
+                contacts::find_clashes(&data, molecule.bonds(), fraction)
+            }
+            None => Vec::new(),
+        };
+        let clashing: Vec<usize> = self
+            .clashes
+            .iter()
+            .flat_map(|clash| [clash.atom_index_1, clash.atom_index_2])
+            .collect();
+        molecule.set_clashing_atoms(&clashing);
+    }
+
+    /// Atoms closer than `fraction` of the sum of their covalent radii, i.e. overlapping each other, are drawn
+    /// with warning halos whatever changed the structure, `None` turns the halos off.
+    pub fn set_covalent_clash_check(&mut self, fraction: Option<f64>) -> Result<(), String> {
+        self.clash_halos.set_fraction(fraction)
+    }
+
+    pub fn covalent_clash_check(&self) -> Option<f64> {
+        self.clash_halos.fraction()
+    }
+
+    /// Atoms of the current positions closer than the fraction of `set_covalent_clash_check` of the sum of their
+    /// covalent radii, with zero-based indices.
+    pub fn covalent_clashes(&mut self) -> &[Clash] {
+        match &self.molecule {
+            Some(molecule) => self.clash_halos.clashes(molecule, self.cell.as_ref()),
+            None => &[],
+        }
+    }
+
+    fn atoms_moved(&mut self) {
+        self.labels.invalidate();
+        self.picking_texture_dirty = true;
+        self.measure_selection();
+    }
+
+    fn measure_selection(&mut self) {
+        // The focus mode follows the selection and the positions of atoms
+        self.focus_dirty = self.focus_mode.is_some();
+        if let Some(molecule) = &self.molecule {
+            let label = self.measurement.measure(molecule.atoms(), molecule.selected_atoms());
+            self.labels.set_measurement_texts(label.into_iter().collect());
+        }
+    }
+
+    fn check_atom_index(&self, index: usize) -> Result<(), String> {
+        let num_atoms = self
+            .molecule
+            .as_ref()
+            .map_or(0, |molecule| molecule.atoms_instance_count());
+        if index == 0 || index > num_atoms {
+            return Err(format!("Atom index {} is out of range 1..={}", index, num_atoms));
+        }
+        Ok(())
+    }
+
+    pub fn clear_atom_labels(&mut self) {
+        self.labels.clear_custom_texts();
+    }
+
+    // Impostor cubes for ray casting, otherwise real geometry, quads for point sprites
+    fn atom_mesh(&self, molecule: &Molecule) -> (&Mesh, &VertexBuffer) {
+        if self.lod.level() == LodLevel::Points {
+            (&self.rect_mesh, &self.rect_vb)
+        } else if molecule.is_ray_casting() {
+            (&self.cube_mesh, &self.cube_vb)
+        } else {
+            (
+                &self.sphere_meshes[self.sphere_detail],
+                &self.sphere_vbs[self.sphere_detail],
+            )
+        }
+    }
+
+    fn bond_mesh(&self, molecule: &Molecule) -> (&Mesh, &VertexBuffer) {
+        if self.lod.level() == LodLevel::Simplified {
+            (&self.simplified_cylinder_mesh, &self.simplified_cylinder_vb)
+        } else if molecule.is_ray_casting() {
+            (&self.cube_mesh, &self.cube_vb)
+        } else {
+            (&self.cylinder_mesh, &self.cylinder_vb)
+        }
+    }
+
+    pub async fn load_volume_cube(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        cube: VolumeCube,
+        progress: &Progress,
+        cancel: &CancellationToken,
+    ) -> Result<(), String> {
+        let grid = VolumeGrid::new(&cube)?;
+        // A structure without atoms is centered on the box of the cube, which fills the view
+        if let Some(molecule) = &mut self.molecule
+            && molecule.atoms().is_empty()
+        {
+            let center = grid.position(grid.dims.map(|n| (n - 1) as f64 / 2.0));
+            molecule.origin = center;
+            let radius = grid
+                .corners()
+                .iter()
+                .map(|corner| (0..3).map(|c| (corner[c] - center[c]).powi(2)).sum::<f64>().sqrt())
+                .fold(0.0, f64::max);
+            self.setup_camera(radius as f32);
+        }
+        self.volume_cube = Some(cube);
+        self.update_isosurface(device, progress, cancel).await?;
+        self.update_slice(device, queue)
+    }
+
+    /// Smallest and largest coordinates of the corners of the box of the loaded cube, in Angstroms.
+    pub fn volume_bounds(&self) -> Option<([f64; 3], [f64; 3])> {
+        let grid = VolumeGrid::new(self.volume_cube.as_ref()?).ok()?;
+        let corners = grid.corners();
+        Some((
+            std::array::from_fn(|c| corners.iter().map(|corner| corner[c]).fold(f64::INFINITY, f64::min)),
+            std::array::from_fn(|c| corners.iter().map(|corner| corner[c]).fold(f64::NEG_INFINITY, f64::max)),
+        ))
+    }
+
+    pub fn isosurface_settings(&self) -> IsosurfaceSettings {
+        self.isosurface_settings
+    }
+
+    pub fn isosurface(&self) -> Option<&Isosurface> {
+        self.isosurface.as_ref()
+    }
+
+    /// Shows or hides the lobes of the volume cube, they appear once a cube is loaded.
+    pub async fn set_isosurface(
+        &mut self,
+        device: &wgpu::Device,
+        settings: IsosurfaceSettings,
+        visible: bool,
+        progress: &Progress,
+        cancel: &CancellationToken,
+    ) -> Result<(), String> {
+        settings.validate()?;
+        self.isosurface_settings = settings;
+        self.isosurface_visible = visible;
+        self.update_isosurface(device, progress, cancel).await
+    }
+
+    async fn update_isosurface(
+        &mut self,
+        device: &wgpu::Device,
+        progress: &Progress,
+        cancel: &CancellationToken,
+    ) -> Result<(), String> {
+        self.isosurface = match &self.volume_cube {
+            Some(cube) if self.isosurface_visible => Some(
+                Isosurface::new(
+                    device,
+                    cube,
+                    self.molecule.as_ref().map_or([0.0; 3], |molecule| molecule.origin),
+                    &self.isosurface_settings,
+                    progress,
+                    cancel,
+                )
+                .await?,
+            ),
+            _ => None,
+        };
+        Ok(())
+    }
+
+    pub fn slice_settings(&self) -> SliceSettings {
+        self.slice_settings
+    }
+
+    pub fn is_slice_visible(&self) -> bool {
+        self.slice_visible
+    }
+
+    pub fn set_slice(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        settings: SliceSettings,
+        visible: bool,
+    ) -> Result<(), String> {
+        self.slice_settings = settings;
+        self.slice_visible = visible;
+        self.update_slice(device, queue)
+    }
+
+    fn update_slice(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) -> Result<(), String> {
+        self.slice = match &self.volume_cube {
+            Some(cube) if self.slice_visible => Slice::new(
+                device,
+                queue,
+                &self.renderer.slice_bind_group_layout,
+                &self.renderer.slice_sampler,
+                &VolumeGrid::new(cube)?,
+                self.molecule.as_ref().map_or([0.0; 3], |molecule| molecule.origin),
+                &self.slice_settings,
+            ),
+            _ => None,
+        };
+        Ok(())
+    }
+
+    pub fn render(
+        &mut self,
+        surface: &wgpu::Surface,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        config: &Config,
+        render_mode: u32,
+    ) {
+        if self.molecule.is_none() {
+            return;
+        }
+
+        // Get current texture from surface
+        let surface_texture = match surface.get_current_texture() {
+            Ok(surface_texture) => surface_texture,
+            Err(_) => return,
+        };
+
+        let view = surface_texture
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+
+        self.render_to_view(&view, device, queue, config, render_mode);
+        surface_texture.present();
+    }
+
+    /// Renders the scene offscreen at the size in pixels and returns it as a PNG file, every pixel of it averages
+    /// `supersampling` × `supersampling` rendered ones. `surface_config` is the current configuration
+    /// of the renderer, it is restored afterwards.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn capture_image(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        config: &Config,
+        surface_config: &wgpu::SurfaceConfiguration,
+        width: u32,
+        height: u32,
+        supersampling: u32,
+    ) -> Result<Vec<u8>, String> {
+        if supersampling == 0 || supersampling > MAX_SUPERSAMPLING {
+            return Err(format!(
+                "Supersampling must be in range 1..={}, got {}",
+                MAX_SUPERSAMPLING, supersampling
+            ));
+        }
+        if self.molecule.is_none() {
+            return Err("No structure is loaded".to_string());
+        }
+        let (render_width, render_height) = (
+            width.saturating_mul(supersampling),
+            height.saturating_mul(supersampling),
+        );
+        let target = CaptureTarget::new(device, self.renderer.format(), render_width, render_height)?;
+
+        let render_config = wgpu::SurfaceConfiguration {
+            width: render_width,
+            height: render_height,
+            ..surface_config.clone()
+        };
+        self.renderer.resize(device, &render_config);
+        self.projection_manager.set_viewport(render_width, render_height);
+        self.render_to_view(&target.view, device, queue, config, 0);
+        self.renderer.resize(device, surface_config);
         self.projection_manager
-            .perspective_projection
-            .set_near_far_plane(0.1, 10.0 * scene_size / fov_factor);
-
-        self.camera.reset_to_default();
-        self.camera.set_position(Vec3::new(0.0, 0.0, 3.0 * scene_size));
-    }
+            .set_viewport(surface_config.width, surface_config.height);
+        self.picking_texture_dirty = true;
 
-    pub fn resize(&mut self, device: &wgpu::Device, config: &wgpu::SurfaceConfiguration) {
-        self.renderer.resize(device, config);
+        let pixels = target.read(device, queue).await?;
+        encode_png(&downsample(&pixels, width, height, supersampling), width, height)
     }
 
-    pub fn load_atomic_coordinates(&mut self, device: &wgpu::Device, config: &Config, data: &AtomicCoordinates) {
-        match Molecule::new(device, config, data) {
-            Ok(molecule) => {
-                self.setup_camera(molecule.radius);
-                self.molecule = Some(molecule);
-            }
-            Err(_) => {}
-        }
+    /// Renders the scene into any texture view with the format of the surface configuration
+    /// the renderer was created with, e.g. an offscreen texture.
+    pub fn render_to_view(
+        &mut self,
+        view: &wgpu::TextureView,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        config: &Config,
+        render_mode: u32,
+    ) {
+        self.render_to_viewport(view, device, queue, config, render_mode, None);
     }
 
-    pub fn render(
+    /// Renders the scene into a part of the texture view, the rest of it is kept. The projection
+    /// must be set up for the size of the viewport.
+    pub fn render_to_viewport(
         &mut self,
-        surface: &wgpu::Surface,
+        view: &wgpu::TextureView,
         device: &wgpu::Device,
         queue: &wgpu::Queue,
         config: &Config,
         render_mode: u32,
+        viewport: Option<Viewport>,
     ) {
+        if self.molecule.is_none() {
+            return;
+        }
+        let Some(scene_matrix) = self.molecule_matrix() else {
+            return;
+        };
+        let _span = profiling::span("render");
+        if self.focus_dirty {
+            self.update_focus(queue);
+        }
+
+        // With effects the scene is drawn into an intermediate texture first
+        let (width, height) = self.renderer.get_size();
+        let effects_enabled = config.style.effects.is_enabled();
+        if effects_enabled {
+            let format = self.renderer.format();
+            self.post_process
+                .get_or_insert_with(|| PostProcess::new(device, format, width, height))
+                .resize(device, width, height);
+        }
+        let post_process = self.post_process.as_ref().filter(|_| effects_enabled);
+        let target = post_process.map_or(view, |post_process| &post_process.color_texture_view);
+
+        if let Some(molecule) = self.molecule.as_mut() {
+            molecule.write_dirty_instances(device, queue);
+        }
         let molecule = match &self.molecule {
             Some(molecule) => molecule,
             None => return,
@@ -80,54 +1891,168 @@ impl Scene {
         // Calculate matrices
         let projection_matrix = *self.projection_manager.get_matrix();
         let view_matrix = *self.camera.get_matrix();
-        let scene_matrix = *self.transform.get_matrix() * molecule.transform;
         let final_matrix = projection_matrix * view_matrix * scene_matrix;
         let is_perspective = self.projection_manager.mode == ProjectionMode::Perspective;
 
-        // Update uniform buffer with all 4 matrices + projection type flag
-        // matrix = (16 float × 4 байта) = 64 bytes
-        let mut uniforms_data = [0u8; 272];
-        uniforms_data[0..64].copy_from_slice(bytemuck::cast_slice(&projection_matrix.data));
-        uniforms_data[64..128].copy_from_slice(bytemuck::cast_slice(&view_matrix.data));
-        uniforms_data[128..192].copy_from_slice(bytemuck::cast_slice(&scene_matrix.data));
-        uniforms_data[192..256].copy_from_slice(bytemuck::cast_slice(&final_matrix.data));
-        uniforms_data[256..260].copy_from_slice(&render_mode.to_le_bytes());
-        uniforms_data[260..264].copy_from_slice(&(if is_perspective { 1u32 } else { 0u32 }).to_le_bytes());
+        let radius_pixels = radius_in_pixels(
+            molecule.atom_radius * self.transform.scale.x,
+            &projection_matrix,
+            &(view_matrix * scene_matrix),
+            viewport.map_or(self.renderer.get_size().0, |viewport| viewport.width),
+        );
+        let lod_level = self
+            .lod
+            .update(&config.lod, molecule.atoms_instance_count(), radius_pixels);
+        self.sphere_detail = sphere_detail(&config.style.geometry, radius_pixels, self.sphere_detail);
 
-        queue.write_buffer(&self.renderer.uniform_buffer, 0, &uniforms_data);
+        self.labels.update(
+            device,
+            molecule.atoms(),
+            molecule.bonds(),
+            molecule.selected_atoms(),
+            &config.style.contrasting_label(),
+        );
+        self.measurement.update(
+            device,
+            molecule.atoms(),
+            molecule.is_ray_casting(),
+            &config.style.measurement,
+        );
+        self.cell_box.update(
+            device,
+            self.cell.as_ref(),
+            molecule.origin,
+            molecule.is_ray_casting(),
+            &config.style.cell_box,
+        );
+        self.clash_halos.update(device, molecule, self.cell.as_ref());
+        self.supercell.update(device, molecule, self.cell.as_ref());
+        if let Some(vibration) = self.vibration.as_mut() {
+            vibration.update_arrows(device, &config.style.vibration);
+        }
+        if self.labels.num_glyphs > 0 && self.font_atlas.is_none() {
+            self.font_atlas = Some(FontAtlas::new(
+                device,
+                queue,
+                &self.renderer.font_atlas_bind_group_layout,
+                &self.renderer.font_sampler,
+            ));
+        }
 
-        // Get current texture from surface
-        let surface_texture = match surface.get_current_texture() {
-            Ok(surface_texture) => surface_texture,
-            Err(_) => return,
-        };
+        #[cfg(feature = "debug-tools")]
+        let render_mode = self.debug_view.map_or(render_mode, DebugView::render_mode);
 
-        let view = surface_texture
-            .texture
-            .create_view(&wgpu::TextureViewDescriptor::default());
+        let mut uniforms = Uniforms::new(
+            &projection_matrix,
+            &view_matrix,
+            &scene_matrix,
+            render_mode,
+            is_perspective,
+            lod_level.shader_value(),
+        );
+        let fog = &config.style.fog;
+        if fog.enabled {
+            // Fractions of the depth of the bounding sphere to distances from the camera in view space
+            let center = -(view_matrix * scene_matrix).transform_point(Vec3::new(0.0, 0.0, 0.0)).z;
+            let radius = molecule.radius * self.transform.scale.x;
+            let front = center - radius;
+            let clear = config.style.clear_color();
+            let fog_color = [clear.r as f32, clear.g as f32, clear.b as f32, clear.a as f32];
+            uniforms = uniforms.with_fog(
+                fog_color,
+                front + fog.start * 2.0 * radius,
+                front + fog.end * 2.0 * radius,
+            );
+        }
+        queue.write_buffer(&self.renderer.uniform_buffer, 0, bytemuck::bytes_of(&uniforms));
+
+        // Other structures are drawn with the transforms of their nodes, hidden ones are skipped
+        let scene = *self.transform.get_matrix();
+        let mut overlays = Vec::new();
+        for (i, overlay) in self.overlays.iter_mut().enumerate() {
+            if let Some(matrix) = self.nodes.world_matrix(&scene, overlay.node) {
+                overlay.molecule.write_dirty_instances(device, queue);
+                overlay.write_uniforms(queue, &uniforms.with_scene(&matrix));
+                overlays.push(i);
+            }
+        }
 
         // Create command encoder
         let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
             label: Some("Render Encoder"),
         });
 
-        let has_transparent_objects = molecule.bounding_spheres_instance_count() > 0;
+        // Bonds are hidden when atoms are point sprites
+        let num_bonds = if lod_level == LodLevel::Points {
+            0
+        } else {
+            molecule.bonds_instance_count() as u32
+        };
+
+        // Large molecules: visible atoms and bonds are selected by a compute pass and drawn indirectly
+        let gpu_driven =
+            molecule.atoms_instance_count() >= config.lod.gpu_culling_min_atoms && Culling::is_supported(device);
+        if gpu_driven {
+            let atom_index_count = self.atom_mesh(molecule).0.num_indices;
+            let bond_index_count = self.bond_mesh(molecule).0.num_indices;
+            let culling = self
+                .culling
+                .get_or_insert_with(|| Culling::new(device, &self.renderer.bind_group_layout));
+            culling.cull(
+                device,
+                queue,
+                &mut encoder,
+                &self.renderer.bind_group,
+                &[
+                    CullingLayer {
+                        instances: &molecule.atoms_instance_buffer,
+                        count: molecule.atoms_instance_count() as u32,
+                        index_count: atom_index_count,
+                    },
+                    CullingLayer {
+                        instances: &molecule.bonds_instance_buffer,
+                        count: num_bonds,
+                        index_count: bond_index_count,
+                    },
+                ],
+            );
+        }
+
+        // Without compute shaders large molecules are culled on the CPU by chunks of instances
+        let (atom_ranges, bond_ranges) =
+            if !gpu_driven && molecule.atoms_instance_count() >= config.lod.cpu_culling_min_atoms {
+                self.chunk_culling.update(molecule);
+                let (atoms, bonds) = self.chunk_culling.visible(&final_matrix);
+                (atoms, if num_bonds > 0 { bonds } else { Vec::new() })
+            } else {
+                (
+                    std::iter::once(0..molecule.atoms_instance_count() as u32).collect(),
+                    std::iter::once(0..num_bonds).collect(),
+                )
+            };
+
+        let has_transparent_objects = molecule.bounding_spheres_instance_count() > 0
+            || self.clash_halos.num_instances > 0
+            || self.measurement.num_plane_instances > 0
+            || self
+                .isosurface
+                .as_ref()
+                .is_some_and(|isosurface| isosurface.transparent);
 
         // Pass 1: Render opaque objects
         {
+            let _span = profiling::span("opaque pass");
             let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: Some("Opaque Render Pass"),
                 color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &view,
+                    view: target,
                     depth_slice: None,
                     resolve_target: None,
                     ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color {
-                            r: config.style.background_color.r as f64,
-                            g: config.style.background_color.g as f64,
-                            b: config.style.background_color.b as f64,
-                            a: 1.0,
-                        }),
+                        load: match viewport {
+                            Some(_) if post_process.is_none() => wgpu::LoadOp::Load,
+                            _ => wgpu::LoadOp::Clear(self.clear_color(config)),
+                        },
                         store: wgpu::StoreOp::Store,
                     },
                 })],
@@ -144,29 +2069,151 @@ impl Scene {
                 multiview_mask: None,
             });
 
-            render_pass.set_pipeline(&self.renderer.pipeline);
-            render_pass.set_vertex_buffer(0, self.cube_vb.vertex_buffer.slice(..));
-            render_pass.set_index_buffer(self.cube_vb.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+            if let Some(viewport) = viewport {
+                viewport.apply(&mut render_pass);
+            }
+            render_pass.set_pipeline(self.opaque_pipeline());
             render_pass.set_bind_group(0, &self.renderer.bind_group, &[]);
 
-            // Render atoms (opaque)
-            if molecule.atoms_instance_count() > 0 {
-                render_pass.set_vertex_buffer(1, molecule.atoms_instance_buffer.slice(..));
-                render_pass.draw_indexed(
-                    0..self.cube_mesh.num_indices,
-                    0,
-                    0..molecule.atoms_instance_count() as u32,
-                );
+            if let Some(culling) = self.culling.as_ref().filter(|_| gpu_driven) {
+                // Atoms and bonds share the impostor cube when ray casting
+                let (_, atom_vb) = self.atom_mesh(molecule);
+                let (_, bond_vb) = self.bond_mesh(molecule);
+                render_pass.set_vertex_buffer(0, atom_vb.vertex_buffer.slice(..));
+                render_pass.set_index_buffer(atom_vb.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+                if std::ptr::eq(atom_vb, bond_vb) {
+                    culling.draw(&mut render_pass, 0..2);
+                } else {
+                    culling.draw(&mut render_pass, 0..1);
+                    render_pass.set_vertex_buffer(0, bond_vb.vertex_buffer.slice(..));
+                    render_pass.set_index_buffer(bond_vb.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+                    culling.draw(&mut render_pass, 1..2);
+                }
+            } else {
+                // Render atoms (opaque)
+                if molecule.atoms_instance_count() > 0 {
+                    let (mesh, vb) = self.atom_mesh(molecule);
+                    render_pass.set_vertex_buffer(0, vb.vertex_buffer.slice(..));
+                    render_pass.set_index_buffer(vb.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+                    render_pass.set_vertex_buffer(1, molecule.atoms_instance_buffer.slice(..));
+                    for range in atom_ranges {
+                        render_pass.draw_indexed(0..mesh.num_indices, 0, range);
+                    }
+                }
+
+                // Render bonds (opaque)
+                if num_bonds > 0 {
+                    let (mesh, vb) = self.bond_mesh(molecule);
+                    render_pass.set_vertex_buffer(0, vb.vertex_buffer.slice(..));
+                    render_pass.set_index_buffer(vb.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+                    render_pass.set_vertex_buffer(1, molecule.bonds_instance_buffer.slice(..));
+                    for range in bond_ranges {
+                        render_pass.draw_indexed(0..mesh.num_indices, 0, range);
+                    }
+                }
             }
 
-            // Render bonds (opaque)
-            if molecule.bonds_instance_count() > 0 {
-                render_pass.set_vertex_buffer(1, molecule.bonds_instance_buffer.slice(..));
-                render_pass.draw_indexed(
-                    0..self.cube_mesh.num_indices,
-                    0,
-                    0..molecule.bonds_instance_count() as u32,
-                );
+            // Render the other structures of the scene
+            for &i in &overlays {
+                let overlay = &self.overlays[i].molecule;
+                render_pass.set_bind_group(0, &self.overlays[i].bind_group, &[]);
+                if overlay.atoms_instance_count() > 0 {
+                    let (mesh, vb) = self.atom_mesh(overlay);
+                    render_pass.set_vertex_buffer(0, vb.vertex_buffer.slice(..));
+                    render_pass.set_index_buffer(vb.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+                    render_pass.set_vertex_buffer(1, overlay.atoms_instance_buffer.slice(..));
+                    render_pass.draw_indexed(0..mesh.num_indices, 0, 0..overlay.atoms_instance_count() as u32);
+                }
+                if overlay.bonds_instance_count() > 0 && lod_level != LodLevel::Points {
+                    let (mesh, vb) = self.bond_mesh(overlay);
+                    render_pass.set_vertex_buffer(0, vb.vertex_buffer.slice(..));
+                    render_pass.set_index_buffer(vb.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+                    render_pass.set_vertex_buffer(1, overlay.bonds_instance_buffer.slice(..));
+                    render_pass.draw_indexed(0..mesh.num_indices, 0, 0..overlay.bonds_instance_count() as u32);
+                }
+            }
+            if !overlays.is_empty() {
+                render_pass.set_bind_group(0, &self.renderer.bind_group, &[]);
+            }
+
+            // Render copies of the structure in the neighboring cells
+            if let Some(buffer) = &self.supercell.atoms_instance_buffer {
+                let (mesh, vb) = self.atom_mesh(molecule);
+                render_pass.set_vertex_buffer(0, vb.vertex_buffer.slice(..));
+                render_pass.set_index_buffer(vb.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+                render_pass.set_vertex_buffer(1, buffer.slice(..));
+                render_pass.draw_indexed(0..mesh.num_indices, 0, 0..self.supercell.num_atom_instances);
+            }
+            if let Some(buffer) = self
+                .supercell
+                .bonds_instance_buffer
+                .as_ref()
+                .filter(|_| lod_level != LodLevel::Points)
+            {
+                let (mesh, vb) = self.bond_mesh(molecule);
+                render_pass.set_vertex_buffer(0, vb.vertex_buffer.slice(..));
+                render_pass.set_index_buffer(vb.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+                render_pass.set_vertex_buffer(1, buffer.slice(..));
+                render_pass.draw_indexed(0..mesh.num_indices, 0, 0..self.supercell.num_bond_instances);
+            }
+
+            // Render lines and arcs of the measurement
+            if let Some(buffer) = &self.measurement.instance_buffer {
+                let (mesh, vb) = self.bond_mesh(molecule);
+                render_pass.set_vertex_buffer(0, vb.vertex_buffer.slice(..));
+                render_pass.set_index_buffer(vb.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+                render_pass.set_vertex_buffer(1, buffer.slice(..));
+                render_pass.draw_indexed(0..mesh.num_indices, 0, 0..self.measurement.num_instances);
+            }
+
+            // Render edges of the periodic cell
+            if let Some(buffer) = &self.cell_box.instance_buffer {
+                let (mesh, vb) = self.bond_mesh(molecule);
+                render_pass.set_vertex_buffer(0, vb.vertex_buffer.slice(..));
+                render_pass.set_index_buffer(vb.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+                render_pass.set_vertex_buffer(1, buffer.slice(..));
+                render_pass.draw_indexed(0..mesh.num_indices, 0, 0..self.cell_box.num_instances);
+            }
+
+            // Render displacement arrows of the vibration, always as meshes: shafts and then heads
+            if let Some(vibration) = &self.vibration
+                && let Some(buffer) = &vibration.arrows_instance_buffer
+            {
+                let num_arrows = vibration.num_arrows;
+                render_pass.set_vertex_buffer(1, buffer.slice(..));
+                render_pass.set_vertex_buffer(0, self.cylinder_vb.vertex_buffer.slice(..));
+                render_pass.set_index_buffer(self.cylinder_vb.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+                render_pass.draw_indexed(0..self.cylinder_mesh.num_indices, 0, 0..num_arrows);
+                render_pass.set_vertex_buffer(0, self.cone_vb.vertex_buffer.slice(..));
+                render_pass.set_index_buffer(self.cone_vb.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+                render_pass.draw_indexed(0..self.cone_mesh.num_indices, 0, num_arrows..2 * num_arrows);
+            }
+
+            // Render opaque lobes of the isosurface
+            if let Some(isosurface) = self.isosurface.as_ref().filter(|isosurface| !isosurface.transparent) {
+                for lobe in &isosurface.lobes {
+                    render_pass.set_vertex_buffer(0, lobe.vertex_buffer.slice(..));
+                    render_pass.set_vertex_buffer(1, lobe.instance_buffer.slice(..));
+                    render_pass.set_index_buffer(lobe.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+                    render_pass.draw_indexed(0..lobe.num_indices, 0, 0..1);
+                }
+            }
+
+            // Render atom labels
+            if let (Some(buffer), Some(font_atlas)) = (&self.labels.instance_buffer, &self.font_atlas) {
+                render_pass.set_pipeline(&self.renderer.labels_pipeline);
+                render_pass.set_bind_group(1, &font_atlas.bind_group, &[]);
+                render_pass.set_vertex_buffer(0, buffer.slice(..));
+                render_pass.draw(0..6, 0..self.labels.num_glyphs);
+            }
+
+            // Render volume slice (alpha blended over opaque objects)
+            if let Some(slice) = &self.slice {
+                render_pass.set_pipeline(&self.renderer.slice_pipeline);
+                render_pass.set_bind_group(1, &slice.bind_group, &[]);
+                render_pass.set_vertex_buffer(0, slice.vertex_buffer.slice(..));
+                render_pass.set_index_buffer(slice.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+                render_pass.draw_indexed(0..slice.num_indices, 0, 0..1);
             }
         }
 
@@ -174,6 +2221,7 @@ impl Scene {
         if has_transparent_objects {
             // Pass 2: Render transparent objects to WBOIT buffers
             {
+                let _span = profiling::span("transparent pass");
                 let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                     label: Some("WBOIT Transparent Pass"),
                     color_attachments: &[
@@ -211,26 +2259,61 @@ impl Scene {
                     multiview_mask: None,
                 });
 
+                if let Some(viewport) = viewport {
+                    viewport.apply(&mut render_pass);
+                }
+                let (mesh, vb) = self.atom_mesh(molecule);
                 render_pass.set_pipeline(&self.renderer.transparent_pipeline);
-                render_pass.set_vertex_buffer(0, self.cube_vb.vertex_buffer.slice(..));
-                render_pass.set_index_buffer(self.cube_vb.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+                render_pass.set_vertex_buffer(0, vb.vertex_buffer.slice(..));
+                render_pass.set_index_buffer(vb.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
                 render_pass.set_bind_group(0, &self.renderer.bind_group, &[]);
 
                 // Render bounding spheres (transparent)
-                render_pass.set_vertex_buffer(1, molecule.atom_selections_instance_buffer.slice(..));
-                render_pass.draw_indexed(
-                    0..self.cube_mesh.num_indices,
-                    0,
-                    0..molecule.bounding_spheres_instance_count() as u32,
-                );
+                if molecule.bounding_spheres_instance_count() > 0 {
+                    render_pass.set_vertex_buffer(1, molecule.atom_selections_instance_buffer.slice(..));
+                    render_pass.draw_indexed(
+                        0..mesh.num_indices,
+                        0,
+                        0..molecule.bounding_spheres_instance_count() as u32,
+                    );
+                }
+
+                // Render warning halos of clashing atoms (transparent)
+                if let Some(buffer) = &self.clash_halos.instance_buffer {
+                    render_pass.set_vertex_buffer(1, buffer.slice(..));
+                    render_pass.draw_indexed(0..mesh.num_indices, 0, 0..self.clash_halos.num_instances);
+                }
+
+                // Render disks of the measured planes, always as meshes
+                if let Some(buffer) = &self.measurement.planes_instance_buffer {
+                    render_pass.set_vertex_buffer(0, self.cylinder_vb.vertex_buffer.slice(..));
+                    render_pass.set_index_buffer(self.cylinder_vb.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+                    render_pass.set_vertex_buffer(1, buffer.slice(..));
+                    render_pass.draw_indexed(
+                        0..self.cylinder_mesh.num_indices,
+                        0,
+                        0..self.measurement.num_plane_instances,
+                    );
+                }
+
+                // Render translucent lobes of the isosurface
+                if let Some(isosurface) = self.isosurface.as_ref().filter(|isosurface| isosurface.transparent) {
+                    for lobe in &isosurface.lobes {
+                        render_pass.set_vertex_buffer(0, lobe.vertex_buffer.slice(..));
+                        render_pass.set_vertex_buffer(1, lobe.instance_buffer.slice(..));
+                        render_pass.set_index_buffer(lobe.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+                        render_pass.draw_indexed(0..lobe.num_indices, 0, 0..1);
+                    }
+                }
             }
 
             // Pass 3: Composite WBOIT result onto framebuffer
             {
+                let _span = profiling::span("composite pass");
                 let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                     label: Some("WBOIT Composite Pass"),
                     color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                        view: &view,
+                        view: target,
                         depth_slice: None,
                         resolve_target: None,
                         ops: wgpu::Operations {
@@ -244,43 +2327,69 @@ impl Scene {
                     multiview_mask: None,
                 });
 
+                if let Some(viewport) = viewport {
+                    viewport.apply(&mut render_pass);
+                }
                 render_pass.set_pipeline(&self.renderer.composite_pipeline);
                 render_pass.set_bind_group(0, &self.renderer.wboit_bind_group, &[]);
                 render_pass.draw(0..6, 0..1); // Full-screen quad
             }
         }
 
+        // Pass 4: Effects from the intermediate texture onto the framebuffer
+        if let Some(post_process) = post_process {
+            let _span = profiling::span("effects pass");
+            let params = PostProcessParams::new(
+                &config.style.effects,
+                &projection_matrix,
+                focus_depth(molecule, &(view_matrix * scene_matrix)),
+                self.transform.scale.x,
+                viewport.unwrap_or(Viewport {
+                    x: 0,
+                    y: 0,
+                    width,
+                    height,
+                }),
+            );
+            post_process.draw(
+                device,
+                queue,
+                &mut encoder,
+                view,
+                &self.renderer.depth_texture_view,
+                &params,
+            );
+        }
+
         // Submit commands
         queue.submit(std::iter::once(encoder.finish()));
-        surface_texture.present();
         self.picking_texture_dirty = true;
     }
 
     fn render_picking_pass(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        let _span = profiling::span("picking pass");
+        let Some(scene_matrix) = self.molecule_matrix() else {
+            return;
+        };
+        if let Some(molecule) = self.molecule.as_mut() {
+            molecule.write_dirty_instances(device, queue);
+        }
         let molecule = match &self.molecule {
             Some(molecule) => molecule,
             None => return,
         };
 
-        // Calculate matrices (same as main render)
-        let projection_matrix = *self.projection_manager.get_matrix();
-        let view_matrix = *self.camera.get_matrix();
-        let scene_matrix = *self.transform.get_matrix() * molecule.transform;
-        let final_matrix = projection_matrix * view_matrix * scene_matrix;
-        let is_perspective = self.projection_manager.mode == ProjectionMode::Perspective;
-        let render_mode = 1u32; // Picking mode
-        let lighting_model = 0u32; // No lighting for picking
-
-        let mut uniforms_data = [0u8; 272];
-        uniforms_data[0..64].copy_from_slice(bytemuck::cast_slice(&projection_matrix.data));
-        uniforms_data[64..128].copy_from_slice(bytemuck::cast_slice(&view_matrix.data));
-        uniforms_data[128..192].copy_from_slice(bytemuck::cast_slice(&scene_matrix.data));
-        uniforms_data[192..256].copy_from_slice(bytemuck::cast_slice(&final_matrix.data));
-        uniforms_data[256..260].copy_from_slice(&render_mode.to_le_bytes());
-        uniforms_data[260..264].copy_from_slice(&(if is_perspective { 1u32 } else { 0u32 }).to_le_bytes());
-        uniforms_data[264..268].copy_from_slice(&lighting_model.to_le_bytes());
-
-        queue.write_buffer(&self.renderer.uniform_buffer, 0, &uniforms_data);
+        // Same matrices as the main render in picking mode, atoms are picked with the geometry they are rendered with
+        let lod_level = self.lod.level();
+        let uniforms = Uniforms::new(
+            self.projection_manager.get_matrix(),
+            self.camera.get_matrix(),
+            &scene_matrix,
+            1,
+            self.projection_manager.mode == ProjectionMode::Perspective,
+            lod_level.shader_value(),
+        );
+        queue.write_buffer(&self.renderer.uniform_buffer, 0, bytemuck::bytes_of(&uniforms));
 
         let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
             label: Some("Picking Encoder"),
@@ -294,7 +2403,8 @@ impl Scene {
                     depth_slice: None,
                     resolve_target: None,
                     ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        // Zero ID is the background
+                        load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
                         store: wgpu::StoreOp::Store,
                     },
                 })],
@@ -311,28 +2421,60 @@ impl Scene {
                 multiview_mask: None,
             });
 
+            let (mesh, vb) = self.atom_mesh(molecule);
             render_pass.set_pipeline(&self.renderer.picking_pipeline);
-            render_pass.set_vertex_buffer(0, self.cube_vb.vertex_buffer.slice(..));
-            render_pass.set_index_buffer(self.cube_vb.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+            render_pass.set_vertex_buffer(0, vb.vertex_buffer.slice(..));
+            render_pass.set_index_buffer(vb.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
             render_pass.set_bind_group(0, &self.renderer.bind_group, &[]);
 
-            // Render atoms only (bonds don't have picking IDs)
             render_pass.set_vertex_buffer(1, molecule.atoms_instance_buffer.slice(..));
-            render_pass.draw_indexed(
-                0..self.cube_mesh.num_indices,
-                0,
-                0..molecule.atoms_instance_count() as u32,
-            );
+            render_pass.draw_indexed(0..mesh.num_indices, 0, 0..molecule.atoms_instance_count() as u32);
+
+            // Point sprites of atoms are drawn without bonds
+            if lod_level != LodLevel::Points && molecule.bonds_instance_count() > 0 {
+                let (mesh, vb) = self.bond_mesh(molecule);
+                render_pass.set_vertex_buffer(0, vb.vertex_buffer.slice(..));
+                render_pass.set_index_buffer(vb.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+                render_pass.set_vertex_buffer(1, molecule.bonds_instance_buffer.slice(..));
+                render_pass.draw_indexed(0..mesh.num_indices, 0, 0..molecule.bonds_instance_count() as u32);
+            }
+
+            // Lines and arcs of the measurement
+            if let Some(buffer) = &self.measurement.instance_buffer {
+                let (mesh, vb) = self.bond_mesh(molecule);
+                render_pass.set_vertex_buffer(0, vb.vertex_buffer.slice(..));
+                render_pass.set_index_buffer(vb.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+                render_pass.set_vertex_buffer(1, buffer.slice(..));
+                render_pass.draw_indexed(0..mesh.num_indices, 0, 0..self.measurement.num_instances);
+            }
+
+            // Disks of the measured planes
+            if let Some(buffer) = &self.measurement.planes_instance_buffer {
+                render_pass.set_vertex_buffer(0, self.cylinder_vb.vertex_buffer.slice(..));
+                render_pass.set_index_buffer(self.cylinder_vb.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+                render_pass.set_vertex_buffer(1, buffer.slice(..));
+                render_pass.draw_indexed(
+                    0..self.cylinder_mesh.num_indices,
+                    0,
+                    0..self.measurement.num_plane_instances,
+                );
+            }
         }
 
         queue.submit(std::iter::once(encoder.finish()));
         self.picking_texture_dirty = false;
     }
 
-    pub async fn read_picking_pixel(&self, x: u32, y: u32, device: &wgpu::Device, queue: &wgpu::Queue) -> usize {
+    pub async fn read_picking_pixel(
+        &self,
+        x: u32,
+        y: u32,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) -> Option<PickedObject> {
         let (width, height) = self.renderer.get_size();
         if x >= width || y >= height {
-            return 0;
+            return None;
         }
 
         let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
@@ -384,15 +2526,215 @@ impl Scene {
                 drop(data);
                 self.renderer.picking_staging_buffer.unmap();
 
-                color_to_id(pixel[0], pixel[1], pixel[2])
+                PickedObject::from_picking_id(color_to_id(pixel[0], pixel[1], pixel[2], pixel[3]))
             }
             _ => {
                 self.renderer.picking_staging_buffer.unmap();
-                0
+                None
             }
         }
     }
 
+    /// Objects in the rectangle of `width` × `height` pixels with the top left corner (x, y), read back from
+    /// the picking texture in one transfer. The rectangle is clipped to the texture.
+    pub async fn read_picking_region(
+        &self,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) -> HashSet<PickedObject> {
+        let (texture_width, texture_height) = self.renderer.get_size();
+        let width = width.min(texture_width.saturating_sub(x));
+        let height = height.min(texture_height.saturating_sub(y));
+        let mut objects = HashSet::new();
+        if width == 0 || height == 0 {
+            return objects;
+        }
+
+        // Rows in the buffer are padded to the alignment of texture copies
+        let alignment = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let bytes_per_row = (width * 4).div_ceil(alignment) * alignment;
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Picking Region Staging Buffer"),
+            size: bytes_per_row as u64 * height as u64,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Picking Region Read Encoder"),
+        });
+        encoder.copy_texture_to_buffer(
+            wgpu::TexelCopyTextureInfo {
+                texture: &self.renderer.picking_texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d { x, y, z: 0 },
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::TexelCopyBufferInfo {
+                buffer: &buffer,
+                layout: wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(bytes_per_row),
+                    rows_per_image: Some(height),
+                },
+            },
+            wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+        );
+        queue.submit(std::iter::once(encoder.finish()));
+
+        let buffer_slice = buffer.slice(..);
+        let (sender, receiver) = flume::bounded(1);
+        buffer_slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        let _ = device.poll(wgpu::PollType::Wait {
+            submission_index: None,
+            timeout: None,
+        });
+
+        if let Ok(Ok(())) = receiver.recv_async().await {
+            let data = buffer_slice.get_mapped_range();
+            for row in data.chunks_exact(bytes_per_row as usize) {
+                for pixel in row[..width as usize * 4].chunks_exact(4) {
+                    let id = color_to_id(pixel[0], pixel[1], pixel[2], pixel[3]);
+                    objects.extend(PickedObject::from_picking_id(id));
+                }
+            }
+        }
+        objects
+    }
+
+    /// Ray through the center of the pixel (x, y) in molecule space.
+    fn screen_ray(&mut self, x: u32, y: u32) -> Option<Ray> {
+        let (width, height) = self.renderer.get_size();
+        if x >= width || y >= height {
+            return None;
+        }
+
+        let molecule_matrix = self.molecule_matrix()?;
+        let matrix = *self.projection_manager.get_matrix() * *self.camera.get_matrix() * molecule_matrix;
+        let inverse = matrix.inverted()?;
+        let ndc_x = 2.0 * (x as f32 + 0.5) / width as f32 - 1.0;
+        let ndc_y = 1.0 - 2.0 * (y as f32 + 0.5) / height as f32;
+        let near = inverse.transform_point(Vec3::new(ndc_x, ndc_y, -1.0));
+        let far = inverse.transform_point(Vec3::new(ndc_x, ndc_y, 1.0));
+
+        Some(Ray {
+            origin: near,
+            direction: (far - near).normalized(),
+        })
+    }
+
+    /// Label under the pixel (x, y). Labels are not drawn into the picking texture, their rectangles are
+    /// tested on the CPU.
+    fn label_at(&mut self, x: u32, y: u32) -> Option<PickedObject> {
+        let (width, height) = self.renderer.get_size();
+        if x >= width || y >= height || self.labels.num_glyphs == 0 {
+            return None;
+        }
+        let point = (
+            2.0 * (x as f32 + 0.5) / width as f32 - 1.0,
+            1.0 - 2.0 * (y as f32 + 0.5) / height as f32,
+        );
+        let projection = *self.projection_manager.get_matrix();
+        let view = *self.camera.get_matrix();
+        let scene = self.molecule_matrix()?;
+        let is_perspective = self.projection_manager.mode == ProjectionMode::Perspective;
+        self.labels.pick(point, &projection, &view, &scene, is_perspective)
+    }
+
+    /// Object under the pixel (x, y) found by CPU ray casting.
+    pub fn object_at(&mut self, x: u32, y: u32) -> Option<PickedObject> {
+        if let Some(label) = self.label_at(x, y) {
+            return Some(label);
+        }
+        let ray = self.screen_ray(x, y)?;
+        let molecule = self.molecule.as_mut().and_then(|molecule| molecule.pick(&ray));
+        let measurement = self.measurement.pick(&ray);
+        let nearest = match (molecule, measurement) {
+            (Some(a), Some(b)) => Some(if b.distance < a.distance { b } else { a }),
+            (a, b) => a.or(b),
+        };
+        nearest.map(|Hit { target, .. }| match target {
+            Target::Atom(i) => PickedObject::Atom(i),
+            Target::Bond(i) => PickedObject::Bond(i),
+            Target::Measurement => PickedObject::Measurement,
+            Target::Plane(i) => PickedObject::Plane(i),
+        })
+    }
+
+    /// Atom (index starts from 1) under the pixel (x, y) found by CPU ray casting, 0 = no atom.
+    /// The label of an atom stands for the atom.
+    pub fn atom_at(&mut self, x: u32, y: u32) -> usize {
+        match self.object_at(x, y) {
+            Some(PickedObject::Atom(i) | PickedObject::Label(i)) => i + 1,
+            _ => 0,
+        }
+    }
+
+    /// Object under the cursor at the last hover or click.
+    pub fn picked_object(&self) -> Option<PickedObject> {
+        self.picked
+    }
+
+    /// Description of the object for the host, None if it is gone, e.g. the measurement was cleared.
+    pub fn pick_info(&self, object: PickedObject) -> Option<PickInfo> {
+        let atoms = match object {
+            PickedObject::Atom(i) | PickedObject::Label(i) => vec![i + 1],
+            PickedObject::Bond(i) => {
+                let bond = self.bonds().get(i)?;
+                vec![bond.atom_index_1 + 1, bond.atom_index_2 + 1]
+            }
+            PickedObject::Measurement => self.measurement()?.atoms(),
+            PickedObject::Plane(i) => self.plane(i + 1).ok()?.atoms(),
+        };
+        Some(PickInfo::new(object, atoms))
+    }
+
+    pub fn atom_info(&self, index: usize) -> Option<AtomInfo> {
+        self.molecule.as_ref()?.atom_info(index)
+    }
+
+    /// Object under the pixel (x, y), from the picking texture unless picking is done on the CPU.
+    pub async fn pick_object(
+        &mut self,
+        x: u32,
+        y: u32,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) -> Option<PickedObject> {
+        if self.cpu_picking {
+            return self.object_at(x, y);
+        }
+        if let Some(label) = self.label_at(x, y) {
+            return Some(label);
+        }
+
+        if self.picking_texture_dirty {
+            self.render_picking_pass(device, queue);
+        }
+
+        self.read_picking_pixel(x, y, device, queue).await
+    }
+
+    /// Atom (index starts from 1) under the pixel (x, y) or under its label, 0 = no atom. The picked object
+    /// is kept for `picked_object`.
+    async fn pick(&mut self, x: u32, y: u32, device: &wgpu::Device, queue: &wgpu::Queue) -> usize {
+        self.picked = self.pick_object(x, y, device, queue).await;
+        match self.picked {
+            Some(PickedObject::Atom(i) | PickedObject::Label(i)) => i + 1,
+            _ => 0,
+        }
+    }
+
     /// Returns (atom_info, needs_render)
     pub async fn new_cursor_position(
         &mut self,
@@ -405,28 +2747,158 @@ impl Scene {
             return (None, false);
         }
 
-        if self.picking_texture_dirty {
-            self.render_picking_pass(device, queue);
+        let atom_index = self.pick(x, y, device, queue).await;
+
+        let molecule = self.molecule.as_mut().unwrap();
+        molecule.highlight_atom(atom_index)
+    }
+
+    pub async fn toggle_atom_selection(&mut self, x: u32, y: u32, device: &wgpu::Device, queue: &wgpu::Queue) -> bool {
+        if self.molecule.is_none() {
+            return false;
         }
 
-        let atom_index = self.read_picking_pixel(x, y, device, queue).await;
+        let atom_index = self.pick(x, y, device, queue).await;
 
         let molecule = self.molecule.as_mut().unwrap();
-        molecule.highlight_atom(atom_index, device)
+        let toggled = molecule.toggle_atom_selection(atom_index);
+        if toggled {
+            self.measure_selection();
+        }
+        toggled
     }
 
-    pub async fn toggle_atom_selection(&mut self, x: u32, y: u32, device: &wgpu::Device, queue: &wgpu::Queue) -> bool {
+    /// Indices (start from 1) of the selected atoms in the order of selection.
+    pub fn selected_atoms(&self) -> Vec<usize> {
+        self.molecule.as_ref().map_or_else(Vec::new, |molecule| {
+            molecule.selected_atoms().iter().map(|i| i + 1).collect()
+        })
+    }
+
+    /// Adds the atoms (indices start from 1) to the selection, returns false if all of them were selected.
+    pub fn select_atoms(&mut self, indices: &[usize]) -> Result<bool, String> {
+        for &index in indices {
+            self.check_atom_index(index)?;
+        }
+        let Some(molecule) = self.molecule.as_mut() else {
+            return Ok(false);
+        };
+
+        let indices: Vec<usize> = indices.iter().map(|i| i - 1).collect();
+        let changed = molecule.select_atoms(&indices);
+        if changed {
+            self.measure_selection();
+        }
+        Ok(changed)
+    }
+
+    /// Atoms (zero-based) with centers projected into the rectangle between the pixels (x0, y0) and (x1, y1),
+    /// both corners included. Hidden atoms and atoms outside of the depth range of the view are skipped.
+    fn atoms_in_rect(&mut self, x0: u32, y0: u32, x1: u32, y1: u32) -> Vec<usize> {
+        let (width, height) = self.renderer.get_size();
+        let Some(molecule_matrix) = self.molecule_matrix() else {
+            return Vec::new();
+        };
+        let matrix = *self.projection_manager.get_matrix() * *self.camera.get_matrix() * molecule_matrix;
+        let Some(molecule) = &self.molecule else {
+            return Vec::new();
+        };
+
+        let (left, right) = (x0.min(x1) as f32, x0.max(x1) as f32 + 1.0);
+        let (top, bottom) = (y0.min(y1) as f32, y0.max(y1) as f32 + 1.0);
+        molecule
+            .atoms()
+            .iter()
+            .enumerate()
+            .filter(|(_, atom)| atom.visible)
+            .filter_map(|(i, atom)| {
+                let ndc = matrix.transform_point(atom.position);
+                let x = (ndc.x + 1.0) / 2.0 * width as f32;
+                let y = (1.0 - ndc.y) / 2.0 * height as f32;
+                let inside = (-1.0..=1.0).contains(&ndc.z) && (left..right).contains(&x) && (top..bottom).contains(&y);
+                inside.then_some(i)
+            })
+            .collect()
+    }
+
+    /// Selects the atoms inside the rectangle between the pixels (x0, y0) and (x1, y1). Additive selection
+    /// keeps the selected atoms, otherwise the atoms in the rectangle replace them.
+    /// Returns false if the selection has not changed.
+    pub fn select_rect(&mut self, x0: u32, y0: u32, x1: u32, y1: u32, additive: bool) -> bool {
+        let indices = self.atoms_in_rect(x0, y0, x1, y1);
+        self.select_indices(indices, additive)
+    }
+
+    /// Same as `select_rect`, but only the atoms seen in the rectangle are selected, not the ones hidden
+    /// behind other atoms. Visible atoms are read back from the picking texture at once; with CPU picking
+    /// all atoms inside are selected.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn select_visible_rect(
+        &mut self,
+        x0: u32,
+        y0: u32,
+        x1: u32,
+        y1: u32,
+        additive: bool,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) -> bool {
         if self.molecule.is_none() {
             return false;
         }
+        if self.cpu_picking {
+            return self.select_rect(x0, y0, x1, y1, additive);
+        }
 
         if self.picking_texture_dirty {
             self.render_picking_pass(device, queue);
         }
+        let (width, height) = (x0.abs_diff(x1) + 1, y0.abs_diff(y1) + 1);
+        let objects = self
+            .read_picking_region(x0.min(x1), y0.min(y1), width, height, device, queue)
+            .await;
+        let mut indices: Vec<usize> = objects
+            .into_iter()
+            .filter_map(|object| match object {
+                PickedObject::Atom(i) => Some(i),
+                _ => None,
+            })
+            .collect();
+        indices.sort_unstable();
+        self.select_indices(indices, additive)
+    }
 
-        let atom_index = self.read_picking_pixel(x, y, device, queue).await;
+    /// Adds the atoms (zero-based indices) to the selection or replaces it with them.
+    fn select_indices(&mut self, indices: Vec<usize>, additive: bool) -> bool {
+        let Some(molecule) = self.molecule.as_mut() else {
+            return false;
+        };
 
-        let molecule = self.molecule.as_mut().unwrap();
-        molecule.toggle_atom_selection(atom_index, device)
+        let changed = if additive {
+            molecule.select_atoms(&indices)
+        } else if molecule.selected_atoms() != indices.as_slice() {
+            molecule.clear_selection();
+            molecule.select_atoms(&indices);
+            true
+        } else {
+            false
+        };
+        if changed {
+            self.measure_selection();
+        }
+        changed
+    }
+
+    /// Returns false if no atoms were selected.
+    pub fn clear_selection(&mut self) -> bool {
+        let Some(molecule) = self.molecule.as_mut() else {
+            return false;
+        };
+
+        let changed = molecule.clear_selection();
+        if changed {
+            self.measure_selection();
+        }
+        changed
     }
 }
@@ -0,0 +1,98 @@
+struct Uniforms {
+    projection_transform: mat4x4<f32>,
+    view_transform: mat4x4<f32>,
+    scene_transform: mat4x4<f32>,
+    final_transform: mat4x4<f32>, // projection_transform * view_transform * scene_transform
+    render_mode: u32,             // 0 = normal, 1 = picking, 2-5 = debug views
+    is_perspective: u32,          // 0 = orthographic, 1 = perspective
+    lod_level: u32,               // 0 = full, 1 = simplified bonds, 2 = atoms as point sprites
+};
+
+@group(0) @binding(0)
+var<uniform> uniforms: Uniforms;
+
+// Same layout as InstanceData, scalars avoid the 16 byte alignment of vec3
+struct Instance {
+    rotation: vec4<f32>,
+    position_x: f32,
+    position_y: f32,
+    position_z: f32,
+    scale_xy: f32,
+    scale_z: f32,
+    color: u32,
+    picking_id: u32,
+    flags: u32,
+};
+
+// Arguments of an indexed indirect draw
+struct DrawIndexedIndirect {
+    index_count: u32,
+    instance_count: atomic<u32>,
+    first_index: u32,
+    base_vertex: i32,
+    first_instance: u32,
+};
+
+struct Layer {
+    first: u32, // slot of the first visible instance of the layer in `visible`
+    count: u32, // number of instances in `instances`
+    draw: u32,  // index of the draw command of the layer in `draws`
+    padding: u32,
+};
+
+@group(1) @binding(0)
+var<storage, read> instances: array<Instance>;
+@group(1) @binding(1)
+var<storage, read_write> visible: array<Instance>;
+@group(1) @binding(2)
+var<storage, read_write> draws: array<DrawIndexedIndirect>;
+@group(1) @binding(3)
+var<uniform> layer: Layer;
+
+const WORKGROUP_SIZE: u32 = 64u;
+const HIDDEN_FLAG: u32 = 8u;
+
+fn row(m: mat4x4<f32>, i: u32) -> vec4<f32> {
+    return vec4<f32>(m[0][i], m[1][i], m[2][i], m[3][i]);
+}
+
+// Planes of the view frustum are extracted from the final transform (Gribb & Hartmann), depth range is [0, 1]
+fn is_in_frustum(center: vec3<f32>, radius: f32) -> bool {
+    let m = uniforms.final_transform;
+    let x = row(m, 0u);
+    let y = row(m, 1u);
+    let z = row(m, 2u);
+    let w = row(m, 3u);
+    let planes = array<vec4<f32>, 6>(w + x, w - x, w + y, w - y, z, w - z);
+    for (var i = 0u; i < 6u; i++) {
+        let plane = planes[i];
+        if (dot(plane.xyz, center) + plane.w < -radius * length(plane.xyz)) {
+            return false;
+        }
+    }
+    return true;
+}
+
+@compute @workgroup_size(64)
+fn cs_main(@builtin(global_invocation_id) id: vec3<u32>, @builtin(num_workgroups) groups: vec3<u32>) {
+    // Large layers are dispatched as a 2D grid of workgroups
+    let index = id.x + id.y * groups.x * WORKGROUP_SIZE;
+    if (index >= layer.count) {
+        return;
+    }
+
+    let instance = instances[index];
+    if ((instance.flags & HIDDEN_FLAG) != 0u) {
+        return;
+    }
+
+    // Meshes fit into the unit cube scaled by the instance, whatever the rotation is
+    let center = vec3<f32>(instance.position_x, instance.position_y, instance.position_z);
+    let radius = length(vec3<f32>(instance.scale_xy, instance.scale_xy, instance.scale_z));
+    if (!is_in_frustum(center, radius)) {
+        return;
+    }
+
+    let slot = atomicAdd(&draws[layer.draw].instance_count, 1u);
+    visible[layer.first + slot] = instance;
+}
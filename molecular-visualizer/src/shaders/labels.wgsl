@@ -0,0 +1,76 @@
+struct Uniforms {
+    projection_transform: mat4x4<f32>,
+    view_transform: mat4x4<f32>,
+    scene_transform: mat4x4<f32>,
+    final_transform: mat4x4<f32>, // projection_transform * view_transform * scene_transform
+    render_mode: u32,             // 0 = normal, 1 = picking, 2-5 = debug views
+    is_perspective: u32,          // 0 = orthographic, 1 = perspective
+    lod_level: u32,               // 0 = full, 1 = simplified bonds, 2 = atoms as point sprites
+};
+
+@group(0) @binding(0)
+var<uniform> uniforms: Uniforms;
+
+// Red channel is the glyph, green channel is the glyph with the outline
+@group(1) @binding(0)
+var font_texture: texture_2d<f32>;
+@group(1) @binding(1)
+var font_sampler: sampler;
+
+struct GlyphInput {
+    @location(0) anchor: vec4<f32>,   // center of the atom, lift towards the camera
+    @location(1) rect: vec4<f32>,     // offset of the bottom left corner and size of the quad in view space
+    @location(2) uv: vec4<f32>,       // u_min, v_min, u_max, v_max
+    @location(3) color: vec4<f32>,
+    @location(4) outline_color: vec4<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) color: vec4<f32>,
+    @location(2) outline_color: vec4<f32>,
+};
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32, glyph: GlyphInput) -> VertexOutput {
+    var corners = array<vec2<f32>, 6>(
+        vec2<f32>(0.0, 0.0),
+        vec2<f32>(1.0, 0.0),
+        vec2<f32>(1.0, 1.0),
+        vec2<f32>(0.0, 0.0),
+        vec2<f32>(1.0, 1.0),
+        vec2<f32>(0.0, 1.0),
+    );
+    let corner = corners[vertex_index];
+
+    let scene_scale = length(uniforms.scene_transform[0].xyz);
+    let center_view = (uniforms.view_transform * uniforms.scene_transform * vec4<f32>(glyph.anchor.xyz, 1.0)).xyz;
+
+    // Move the label to the surface of the atom facing the camera
+    var towards_camera = vec3<f32>(0.0, 0.0, 1.0);
+    if (uniforms.is_perspective == 1u) {
+        towards_camera = -normalize(center_view);
+    }
+    let lifted = center_view + towards_camera * glyph.anchor.w * scene_scale;
+
+    // Quad in the XY plane of view space always faces the camera
+    let vertex_view = lifted + vec3<f32>((glyph.rect.xy + corner * glyph.rect.zw) * scene_scale, 0.0);
+
+    var output: VertexOutput;
+    output.position = uniforms.projection_transform * vec4<f32>(vertex_view, 1.0);
+    // V grows downwards in the atlas
+    output.uv = mix(glyph.uv.xw, glyph.uv.zy, corner);
+    output.color = glyph.color;
+    output.outline_color = glyph.outline_color;
+    return output;
+}
+
+@fragment
+fn fs_main(input: VertexOutput) -> @location(0) vec4<f32> {
+    let coverage = textureSample(font_texture, font_sampler, input.uv).rg;
+    if coverage.g < 0.5 {
+        discard;
+    }
+    return mix(input.outline_color, input.color, smoothstep(0.25, 0.75, coverage.r));
+}
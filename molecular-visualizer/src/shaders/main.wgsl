@@ -3,8 +3,13 @@ struct Uniforms {
     view_transform: mat4x4<f32>,
     scene_transform: mat4x4<f32>,
     final_transform: mat4x4<f32>, // projection_transform * view_transform * scene_transform
-    render_mode: u32,             // 0 = normal, 1 = picking
+    render_mode: u32,             // 0 = normal, 1 = picking, 2-5 = debug views
     is_perspective: u32,          // 0 = orthographic, 1 = perspective
+    lod_level: u32,               // 0 = full, 1 = simplified bonds, 2 = atoms as point sprites
+    fog_enabled: u32,             // 0 = off, 1 = depth cueing
+    fog_color: vec4<f32>,         // background color, transparent over a transparent background
+    fog_start: f32,               // distances from the camera where the fog begins and where it is opaque
+    fog_end: f32,
 };
 
 @group(0) @binding(0)
@@ -16,14 +21,12 @@ struct VertexInput {
 };
 
 struct InstanceInput {
-    @location(2) model_matrix_0: vec4<f32>,
-    @location(3) model_matrix_1: vec4<f32>,
-    @location(4) model_matrix_2: vec4<f32>,
-    @location(5) model_matrix_3: vec4<f32>,
-    @location(6) color: vec4<f32>,
-    @location(7) picking_color: vec4<f32>,
-    @location(8) lighting_model: u32,   // 0 = flat color, 1 = Blinn Phong
-    @location(9) ray_casting_type: u32, // 0 = usual rendering, 1 = sphere ray casting, 2 = cylinder ray casting
+    @location(2) rotation: vec4<f32>, // unit quaternion (x, y, z, w)
+    @location(3) position: vec3<f32>,
+    @location(4) scale: vec2<f32>,    // scale along X and Y, scale along Z
+    @location(5) color: vec4<f32>,
+    @location(6) picking_id: u32,
+    @location(7) flags: u32,          // bits 0-1: ray casting type, bit 2: Blinn Phong lighting, bit 3: hidden
 };
 
 struct VertexOutput {
@@ -36,6 +39,7 @@ struct VertexOutput {
     @location(5) sphere_center_view: vec3<f32>,
     @location(6) vertex_pos_view: vec3<f32>,
     @location(7) cylinder_axis_view: vec3<f32>,
+    @location(8) sprite_coord: vec2<f32>,
 };
 
 struct RayCastingOutput {
@@ -66,6 +70,46 @@ fn wboit_weight(color: vec4<f32>, depth: f32) -> f32 {
     return weight;
 }
 
+// 0 = usual rendering, 1 = sphere ray casting, 2 = cylinder ray casting
+fn instance_ray_casting_type(instance: InstanceInput) -> u32 {
+    return instance.flags & 3u;
+}
+
+// 0 = flat color, 1 = Blinn Phong
+fn instance_lighting_model(instance: InstanceInput) -> u32 {
+    return (instance.flags >> 2u) & 1u;
+}
+
+// The one-based index of the object in RGB (up to 256³ - 1 objects of a class), the class in alpha, see utils.rs
+fn picking_color(id: u32) -> vec4<f32> {
+    let rgba = vec4<u32>(id >> 16u, id >> 8u, id, id >> 24u) & vec4<u32>(255u);
+    return vec4<f32>(rgba) / 255.0;
+}
+
+// Debug view of picking IDs: neighboring IDs get unrelated colors (PCG hash), unpickable objects are black
+fn debug_id_color(id: u32) -> vec4<f32> {
+    if (id == 0u) {
+        return vec4<f32>(0.0, 0.0, 0.0, 1.0);
+    }
+    let state = id * 747796405u + 2891336453u;
+    let word = ((state >> ((state >> 28u) + 4u)) ^ state) * 277803737u;
+    let hash = (word >> 22u) ^ word;
+    let rgb = vec3<u32>(hash, hash >> 8u, hash >> 16u) & vec3<u32>(255u);
+    return vec4<f32>(0.25 + 0.75 * vec3<f32>(rgb) / 255.0, 1.0);
+}
+
+// translation * rotation * scale
+fn instance_model_transform(instance: InstanceInput) -> mat4x4<f32> {
+    let q = instance.rotation;
+    let s = vec3<f32>(instance.scale.x, instance.scale.x, instance.scale.y);
+    return mat4x4<f32>(
+        vec4<f32>(1.0 - 2.0 * (q.y * q.y + q.z * q.z), 2.0 * (q.x * q.y + q.w * q.z), 2.0 * (q.x * q.z - q.w * q.y), 0.0) * s.x,
+        vec4<f32>(2.0 * (q.x * q.y - q.w * q.z), 1.0 - 2.0 * (q.x * q.x + q.z * q.z), 2.0 * (q.y * q.z + q.w * q.x), 0.0) * s.y,
+        vec4<f32>(2.0 * (q.x * q.z + q.w * q.y), 2.0 * (q.y * q.z - q.w * q.x), 1.0 - 2.0 * (q.x * q.x + q.y * q.y), 0.0) * s.z,
+        vec4<f32>(instance.position, 1.0)
+    );
+}
+
 fn get_scale(matrix: mat4x4<f32>) -> vec3<f32> {
     return vec3<f32>(
         length(vec3<f32>(matrix[0][0], matrix[0][1], matrix[0][2])),
@@ -125,12 +169,7 @@ fn inverse(m: mat4x4<f32>) -> mat4x4<f32> {
 }
 
 fn ray_casting_position(vertex: VertexInput, instance: InstanceInput) -> VertexOutput {
-    let model_transform = mat4x4<f32>(
-        instance.model_matrix_0,
-        instance.model_matrix_1,
-        instance.model_matrix_2,
-        instance.model_matrix_3,
-    );
+    let model_transform = instance_model_transform(instance);
 
     var output: VertexOutput;
     output.position = uniforms.final_transform * model_transform * vec4<f32>(vertex.position, 1.0);
@@ -164,12 +203,7 @@ fn ray_casting_position(vertex: VertexInput, instance: InstanceInput) -> VertexO
 }
 
 fn default_position(vertex: VertexInput, instance: InstanceInput) -> VertexOutput {
-    let model_transform = mat4x4<f32>(
-        instance.model_matrix_0,
-        instance.model_matrix_1,
-        instance.model_matrix_2,
-        instance.model_matrix_3,
-    );
+    let model_transform = instance_model_transform(instance);
 
     var output: VertexOutput;
     output.position = uniforms.final_transform * model_transform * vec4<f32>(vertex.position, 1.0);
@@ -178,16 +212,67 @@ fn default_position(vertex: VertexInput, instance: InstanceInput) -> VertexOutpu
     output.sphere_center_view = vec3<f32>(0.0, 0.0, 0.0);
     output.vertex_pos_view = vec3<f32>(0.0, 0.0, 0.0);
     output.cylinder_axis_view = vec3<f32>(0.0, 0.0, 1.0);
+    output.sprite_coord = vec2<f32>(0.0, 0.0);
+    return output;
+}
+
+fn sprite_position(vertex: VertexInput, instance: InstanceInput) -> VertexOutput {
+    let model_transform = instance_model_transform(instance);
+
+    let radius = get_scale(model_transform).x * get_scale(uniforms.scene_transform).x;
+    let center_view = (uniforms.view_transform * uniforms.scene_transform * model_transform * vec4<f32>(0.0, 0.0, 0.0, 1.0)).xyz;
+
+    // Quad in the XY plane of view space always faces the camera
+    let vertex_view = center_view + vec3<f32>(vertex.position.xy * radius, 0.0);
+
+    var output: VertexOutput;
+    output.position = uniforms.projection_transform * vec4<f32>(vertex_view, 1.0);
+    output.normal = vec3<f32>(0.0, 0.0, 1.0);
+    output.ray_casting_scale = vec3<f32>(0.0, 0.0, 0.0);
+    output.sphere_center_view = vec3<f32>(0.0, 0.0, 0.0);
+    output.vertex_pos_view = vec3<f32>(0.0, 0.0, 0.0);
+    output.cylinder_axis_view = vec3<f32>(0.0, 0.0, 1.0);
+    output.sprite_coord = vertex.position.xy;
     return output;
 }
 
+// Level of detail replaces ray casted geometry with cheaper one, 3 = point sprite
+fn lod_ray_casting_type(ray_casting_type: u32) -> u32 {
+    switch uniforms.lod_level {
+        case 1u {
+            // Bonds are drawn with low-poly cylinder meshes
+            if (ray_casting_type == 2u) {
+                return 0u;
+            }
+            return ray_casting_type;
+        }
+        case 2u {
+            // Only atoms are drawn, as quads
+            return 3u;
+        }
+        default {
+            return ray_casting_type;
+        }
+    }
+}
+
 @vertex
 fn vs_main(vertex: VertexInput, instance: InstanceInput) -> VertexOutput {
     var output: VertexOutput;
-    switch instance.ray_casting_type {
+    // Hidden instances collapse into a point outside of the clip volume
+    if ((instance.flags & 8u) != 0u) {
+        output.position = vec4<f32>(0.0, 0.0, 2.0, 1.0);
+        return output;
+    }
+
+    let ray_casting_type = lod_ray_casting_type(instance_ray_casting_type(instance));
+    switch ray_casting_type {
         case 1u, 2u {
             output = ray_casting_position(vertex, instance);
         }
+        case 3u {
+            output = sprite_position(vertex, instance);
+        }
         default {
             output = default_position(vertex, instance);
         }
@@ -195,15 +280,18 @@ fn vs_main(vertex: VertexInput, instance: InstanceInput) -> VertexOutput {
 
     switch uniforms.render_mode {
         case 1u {
-            output.color = instance.picking_color;
+            output.color = picking_color(instance.picking_id);
+        }
+        case 4u {
+            output.color = debug_id_color(instance.picking_id);
         }
         default {
             output.color = instance.color;
         }
     }
 
-    output.lighting_model = instance.lighting_model;
-    output.ray_casting_type = instance.ray_casting_type;
+    output.lighting_model = instance_lighting_model(instance);
+    output.ray_casting_type = ray_casting_type;
     return output;
 }
 
@@ -415,6 +503,55 @@ fn calculate_fragment_color(in: VertexOutput, normal: vec3<f32>) -> vec4<f32> {
     return in.color;
 }
 
+// Distance from the camera to the point of the depth, inverts both perspective and orthographic projections
+fn view_distance(depth: f32) -> f32 {
+    let p = uniforms.projection_transform;
+    return (depth * p[3][3] - p[3][2]) / (depth * p[2][3] - p[2][2]);
+}
+
+// Blends the color toward the background with the distance of the depth, alpha fades over a transparent one
+fn apply_fog(color: vec4<f32>, depth: f32) -> vec4<f32> {
+    if (uniforms.fog_enabled == 0u || uniforms.render_mode != 0u) {
+        return color;
+    }
+    let amount = smoothstep(uniforms.fog_start, uniforms.fog_end, view_distance(depth));
+    return vec4<f32>(
+        mix(color.rgb, uniforms.fog_color.rgb, amount),
+        color.a * mix(1.0, uniforms.fog_color.a, amount)
+    );
+}
+
+// 2 = normals, 3 = depth, 4 = picking IDs (colored in vs_main), 5 = overdraw (summed up by additive blending)
+fn debug_color(in: VertexOutput, normal: vec3<f32>, depth: f32) -> vec4<f32> {
+    switch uniforms.render_mode {
+        case 2u {
+            return vec4<f32>(normalize(normal) * 0.5 + 0.5, 1.0);
+        }
+        case 3u {
+            let distance = view_distance(depth);
+            // Alternating bands of 0.1 Angstrom show the slope of surfaces
+            let band = 0.6 + 0.4 * step(0.5, fract(distance * 5.0));
+            return vec4<f32>(vec3<f32>(band / (1.0 + 0.1 * distance)), 1.0);
+        }
+        case 5u {
+            return vec4<f32>(0.12, 0.05, 0.02, 1.0);
+        }
+        default {
+            return in.color;
+        }
+    }
+    return in.color;
+}
+
+// Cuts a disc out of the sprite quad and shades it like a sphere without ray casting
+fn sprite_normal(in: VertexOutput) -> vec3<f32> {
+    let distance_squared = dot(in.sprite_coord, in.sprite_coord);
+    if (distance_squared > 1.0) {
+        discard;
+    }
+    return vec3<f32>(in.sprite_coord, sqrt(1.0 - distance_squared));
+}
+
 @fragment
 fn fs_main(in: VertexOutput) -> FragmentOutput {
     var output: FragmentOutput;
@@ -428,13 +565,20 @@ fn fs_main(in: VertexOutput) -> FragmentOutput {
             let clip_space_pos: vec4<f32> = uniforms.projection_transform * vec4<f32>(rc_out.intersection_point, 1.0);
             output.depth = clip_space_pos.z / clip_space_pos.w;
         }
+        case 3u {
+            normal = sprite_normal(in);
+            output.depth = in.position.z;
+        }
         default {
             normal = in.normal;
             output.depth = in.position.z;
         }
     }
 
-    output.color = calculate_fragment_color(in, normal);
+    output.color = apply_fog(calculate_fragment_color(in, normal), output.depth);
+    if (uniforms.render_mode >= 2u) {
+        output.color = debug_color(in, normal, output.depth);
+    }
     return output;
 }
 
@@ -453,13 +597,17 @@ fn fs_transparent(in: VertexOutput) -> WboitFragmentOutput {
             let clip_space_pos: vec4<f32> = uniforms.projection_transform * vec4<f32>(rc_out.intersection_point, 1.0);
             depth = clip_space_pos.z / clip_space_pos.w;
         }
+        case 3u {
+            normal = sprite_normal(in);
+            depth = in.position.z;
+        }
         default {
             normal = in.normal;
             depth = in.position.z;
         }
     }
 
-    let color = calculate_fragment_color(in, normal);
+    let color = apply_fog(calculate_fragment_color(in, normal), depth);
 
     // Calculate WBOIT weight
     let weight = wboit_weight(color, depth);
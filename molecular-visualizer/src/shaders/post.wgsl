@@ -0,0 +1,193 @@
+// Post-processing of presentation renders: depth of field, cartoon-style outlines and FXAA
+
+struct Params {
+    inverse_projection: mat4x4<f32>,
+    outline_color: vec4<f32>,
+    viewport: vec4<f32>,     // x, y, width, height in pixels
+    focus_depth: f32,        // distance from the camera that is in focus
+    focus_range: f32,        // distance from the focus that stays sharp
+    max_blur: f32,           // blur radius in pixels of the most defocused objects
+    outline_thickness: f32,  // in pixels
+    outline_threshold: f32,  // depth step between neighbouring pixels drawn as an edge
+    flags: u32,              // bit 0: depth of field, bit 1: outline, bit 2: anti-aliasing
+    padding: vec2<u32>,
+};
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+};
+
+@group(0) @binding(0)
+var<uniform> params: Params;
+
+@group(0) @binding(1)
+var color_texture: texture_2d<f32>;
+
+// Bound as an unfilterable float texture, as GLSL can not load texels of depth textures
+@group(0) @binding(2)
+var depth_texture: texture_2d<f32>;
+
+const DEPTH_OF_FIELD: u32 = 1u;
+const OUTLINE: u32 = 2u;
+const ANTIALIASING: u32 = 4u;
+const BLUR_SAMPLES: u32 = 24u;
+const GOLDEN_ANGLE: f32 = 2.39996323;
+// Depth of background pixels
+const FAR_AWAY: f32 = 1.0e9;
+// FXAA: smaller luma contrast is not an edge, the blur along edges is at most FXAA_SPAN_MAX pixels long
+const FXAA_MIN_CONTRAST: f32 = 0.05;
+const FXAA_REDUCE_MIN: f32 = 1.0 / 128.0;
+const FXAA_REDUCE_MUL: f32 = 1.0 / 8.0;
+const FXAA_SPAN_MAX: f32 = 8.0;
+
+// Full-screen quad vertices (two triangles)
+var<private> positions: array<vec2<f32>, 6> = array<vec2<f32>, 6>(
+    vec2<f32>(-1.0, -1.0),
+    vec2<f32>(1.0, -1.0),
+    vec2<f32>(1.0, 1.0),
+    vec2<f32>(-1.0, -1.0),
+    vec2<f32>(1.0, 1.0),
+    vec2<f32>(-1.0, 1.0),
+);
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+    var output: VertexOutput;
+    output.position = vec4<f32>(positions[vertex_index], 0.0, 1.0);
+    return output;
+}
+
+// Keeps samples inside of the viewport
+fn clamp_coords(coords: vec2<i32>) -> vec2<i32> {
+    let low = vec2<i32>(params.viewport.xy);
+    let high = low + vec2<i32>(params.viewport.zw) - vec2<i32>(1, 1);
+    return clamp(coords, low, high);
+}
+
+// Distance from the camera along the view direction
+fn view_depth(coords: vec2<i32>) -> f32 {
+    let depth = textureLoad(depth_texture, coords, 0).r;
+    if (depth >= 1.0) {
+        return FAR_AWAY;
+    }
+    let uv = (vec2<f32>(coords) + 0.5 - params.viewport.xy) / params.viewport.zw;
+    let position = params.inverse_projection * vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, depth, 1.0);
+    return -position.z / position.w;
+}
+
+// Scene color with premultiplied alpha, darkened behind silhouettes of nearer objects
+fn outlined_color(coords: vec2<i32>) -> vec4<f32> {
+    let color = textureLoad(color_texture, coords, 0);
+    if ((params.flags & OUTLINE) == 0u) {
+        return color;
+    }
+
+    let t = params.outline_thickness;
+    let d = t * 0.7071;
+    let offsets = array<vec2<f32>, 8>(
+        vec2<f32>(t, 0.0),
+        vec2<f32>(-t, 0.0),
+        vec2<f32>(0.0, t),
+        vec2<f32>(0.0, -t),
+        vec2<f32>(d, d),
+        vec2<f32>(-d, d),
+        vec2<f32>(d, -d),
+        vec2<f32>(-d, -d),
+    );
+    var nearest = FAR_AWAY;
+    for (var i = 0u; i < 8u; i++) {
+        nearest = min(nearest, view_depth(clamp_coords(coords + vec2<i32>(round(offsets[i])))));
+    }
+    if (view_depth(coords) - nearest > params.outline_threshold) {
+        // Over a transparent background the outline is as opaque as its color
+        return mix(color, vec4<f32>(params.outline_color.rgb, 1.0), params.outline_color.a);
+    }
+    return color;
+}
+
+// Perceptual luma of the linear color
+fn luma(color: vec4<f32>) -> f32 {
+    return sqrt(dot(color.rgb, vec3<f32>(0.299, 0.587, 0.114)));
+}
+
+// Bilinear interpolation of outlined colors at the position in pixels
+fn sample_outlined(position: vec2<f32>) -> vec4<f32> {
+    let p = position - 0.5;
+    let base = vec2<i32>(floor(p));
+    let t = fract(p);
+    let c00 = outlined_color(clamp_coords(base));
+    let c10 = outlined_color(clamp_coords(base + vec2<i32>(1, 0)));
+    let c01 = outlined_color(clamp_coords(base + vec2<i32>(0, 1)));
+    let c11 = outlined_color(clamp_coords(base + vec2<i32>(1, 1)));
+    return mix(mix(c00, c10, t.x), mix(c01, c11, t.x), t.y);
+}
+
+// FXAA: the outlined color blurred along the edge direction estimated from the luma of the diagonal neighbours,
+// so edges of the composited frame, including transparent objects and outlines, are smoothed
+fn antialiased_color(coords: vec2<i32>) -> vec4<f32> {
+    let color = outlined_color(coords);
+    if ((params.flags & ANTIALIASING) == 0u) {
+        return color;
+    }
+
+    let luma_m = luma(color);
+    let luma_nw = luma(outlined_color(clamp_coords(coords + vec2<i32>(-1, -1))));
+    let luma_ne = luma(outlined_color(clamp_coords(coords + vec2<i32>(1, -1))));
+    let luma_sw = luma(outlined_color(clamp_coords(coords + vec2<i32>(-1, 1))));
+    let luma_se = luma(outlined_color(clamp_coords(coords + vec2<i32>(1, 1))));
+    let luma_min = min(luma_m, min(min(luma_nw, luma_ne), min(luma_sw, luma_se)));
+    let luma_max = max(luma_m, max(max(luma_nw, luma_ne), max(luma_sw, luma_se)));
+    if (luma_max - luma_min < FXAA_MIN_CONTRAST) {
+        return color;
+    }
+
+    // Perpendicular to the luma gradient, longer along straight edges
+    var direction = vec2<f32>(luma_sw + luma_se - luma_nw - luma_ne, luma_nw + luma_sw - luma_ne - luma_se);
+    let reduce = max((luma_nw + luma_ne + luma_sw + luma_se) * 0.25 * FXAA_REDUCE_MUL, FXAA_REDUCE_MIN);
+    let scale = 1.0 / (min(abs(direction.x), abs(direction.y)) + reduce);
+    direction = clamp(direction * scale, vec2<f32>(-FXAA_SPAN_MAX), vec2<f32>(FXAA_SPAN_MAX));
+
+    let center = vec2<f32>(coords) + 0.5;
+    let inner = 0.5 * (sample_outlined(center - direction / 6.0) + sample_outlined(center + direction / 6.0));
+    let outer = 0.5 * inner
+        + 0.25 * (sample_outlined(center - direction * 0.5) + sample_outlined(center + direction * 0.5));
+    // The wider blur crossed another edge
+    let luma_outer = luma(outer);
+    if (luma_outer < luma_min || luma_outer > luma_max) {
+        return inner;
+    }
+    return outer;
+}
+
+fn blur_radius(depth: f32) -> f32 {
+    let defocus = (abs(depth - params.focus_depth) - params.focus_range) / params.focus_range;
+    return params.max_blur * clamp(defocus, 0.0, 1.0);
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let coords = vec2<i32>(in.position.xy);
+    let color = antialiased_color(coords);
+    if ((params.flags & DEPTH_OF_FIELD) == 0u) {
+        return color;
+    }
+
+    let radius = blur_radius(view_depth(coords));
+    if (radius < 0.5) {
+        return color;
+    }
+
+    // Gather over a disc of samples, only samples blurred at least as much spread over this pixel,
+    // so sharp objects keep their edges in front of a blurred background
+    var sum = color;
+    var weight = 1.0;
+    for (var i = 0u; i < BLUR_SAMPLES; i++) {
+        let distance = radius * sqrt((f32(i) + 0.5) / f32(BLUR_SAMPLES));
+        let angle = f32(i) * GOLDEN_ANGLE;
+        let sample_coords = clamp_coords(coords + vec2<i32>(round(distance * vec2<f32>(cos(angle), sin(angle)))));
+        let w = clamp(blur_radius(view_depth(sample_coords)) - distance + 1.0, 0.0, 1.0);
+        sum += outlined_color(sample_coords) * w;
+        weight += w;
+    }
+    return sum / weight;
+}
@@ -0,0 +1,45 @@
+struct Uniforms {
+    projection_transform: mat4x4<f32>,
+    view_transform: mat4x4<f32>,
+    scene_transform: mat4x4<f32>,
+    final_transform: mat4x4<f32>, // projection_transform * view_transform * scene_transform
+    render_mode: u32,             // 0 = normal, 1 = picking, 2-5 = debug views
+    is_perspective: u32,          // 0 = orthographic, 1 = perspective
+    lod_level: u32,               // 0 = full, 1 = simplified bonds, 2 = atoms as point sprites
+};
+
+@group(0) @binding(0)
+var<uniform> uniforms: Uniforms;
+
+@group(1) @binding(0)
+var slice_texture: texture_2d<f32>;
+@group(1) @binding(1)
+var slice_sampler: sampler;
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) uv: vec2<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+@vertex
+fn vs_main(vertex: VertexInput) -> VertexOutput {
+    var output: VertexOutput;
+    output.position = uniforms.final_transform * vec4<f32>(vertex.position, 1.0);
+    output.uv = vertex.uv;
+    return output;
+}
+
+@fragment
+fn fs_main(input: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(slice_texture, slice_sampler, input.uv);
+    // Texels outside of the volume are fully transparent
+    if color.a <= 0.0 {
+        discard;
+    }
+    return color;
+}
@@ -0,0 +1,223 @@
+use bytemuck::{Pod, Zeroable};
+use shared_lib::colormap::Colormap;
+use shared_lib::volume::VolumeGrid;
+use wgpu::util::DeviceExt;
+
+use super::core::Vec3;
+
+// Texture is SLICE_RESOLUTION × SLICE_RESOLUTION texels, a row is a multiple of 256 bytes
+const SLICE_RESOLUTION: u32 = 256;
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+pub struct SliceVertex {
+    pub position: [f32; 3],
+    pub uv: [f32; 2],
+}
+
+impl SliceVertex {
+    pub fn desc() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<SliceVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &[
+                wgpu::VertexAttribute {
+                    offset: 0,
+                    shader_location: 0,
+                    format: wgpu::VertexFormat::Float32x3,
+                },
+                wgpu::VertexAttribute {
+                    offset: std::mem::size_of::<[f32; 3]>() as wgpu::BufferAddress,
+                    shader_location: 1,
+                    format: wgpu::VertexFormat::Float32x2,
+                },
+            ],
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct SliceSettings {
+    /// Any point of the plane, in Angstroms.
+    pub point: [f64; 3],
+    pub normal: Vec3<f32>,
+    pub colormap: Colormap,
+    pub opacity: f32,
+}
+
+impl Default for SliceSettings {
+    fn default() -> Self {
+        Self {
+            point: [0.0; 3],
+            normal: Vec3::new(0.0, 0.0, 1.0),
+            colormap: Colormap::default(),
+            opacity: 0.8,
+        }
+    }
+}
+
+/// Plane through a volume cube textured with interpolated values of the cube.
+pub struct Slice {
+    pub vertex_buffer: wgpu::Buffer,
+    pub index_buffer: wgpu::Buffer,
+    pub bind_group: wgpu::BindGroup,
+    pub num_indices: u32,
+}
+
+impl Slice {
+    /// Returns None if the plane does not cross the volume.
+    pub fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        layout: &wgpu::BindGroupLayout,
+        sampler: &wgpu::Sampler,
+        grid: &VolumeGrid,
+        origin: [f64; 3],
+        settings: &SliceSettings,
+    ) -> Option<Self> {
+        let normal = settings.normal.normalized();
+        if normal.length() == 0.0 {
+            return None;
+        }
+
+        // Orthonormal basis of the plane
+        let helper = if normal.x.abs() < 0.9 {
+            Vec3::new(1.0, 0.0, 0.0)
+        } else {
+            Vec3::new(0.0, 1.0, 0.0)
+        };
+        let u = Vec3::cross_product(normal, helper).normalized();
+        let v = Vec3::cross_product(normal, u);
+
+        // Vertices are relative to the origin of the molecule, like atom positions
+        let relative = |p: [f64; 3]| {
+            Vec3::new(
+                (p[0] - origin[0]) as f32,
+                (p[1] - origin[1]) as f32,
+                (p[2] - origin[2]) as f32,
+            )
+        };
+        let point = relative(settings.point);
+
+        // The quad covers the projection of the whole grid box onto the plane
+        let (mut u_min, mut u_max, mut v_min, mut v_max) = (f32::MAX, f32::MIN, f32::MAX, f32::MIN);
+        for corner in grid.corners() {
+            let d = relative(corner) - point;
+            let (du, dv) = (Vec3::dot_product(d, u), Vec3::dot_product(d, v));
+            u_min = u_min.min(du);
+            u_max = u_max.max(du);
+            v_min = v_min.min(dv);
+            v_max = v_max.max(dv);
+        }
+        let at = |s: f32, t: f32| point + u * (u_min + s * (u_max - u_min)) + v * (v_min + t * (v_max - v_min));
+
+        let (min, max) = grid.value_range();
+        let (low, high) = if settings.colormap.is_diverging() {
+            let limit = min.abs().max(max.abs());
+            (-limit, limit)
+        } else {
+            (min, max)
+        };
+        let span = if high > low { high - low } else { 1.0 };
+        let alpha = (settings.opacity.clamp(0.0, 1.0) * 255.0).round() as u8;
+
+        let n = SLICE_RESOLUTION as usize;
+        let mut texels = vec![0u8; n * n * 4];
+        let mut inside = false;
+        for j in 0..n {
+            for i in 0..n {
+                let p = at((i as f32 + 0.5) / n as f32, (j as f32 + 0.5) / n as f32);
+                let p = [p.x as f64 + origin[0], p.y as f64 + origin[1], p.z as f64 + origin[2]];
+                if let Some(value) = grid.sample(p) {
+                    let color = settings.colormap.sample((value - low) / span);
+                    let offset = (j * n + i) * 4;
+                    for c in 0..3 {
+                        texels[offset + c] = (color[c] * 255.0).round() as u8;
+                    }
+                    texels[offset + 3] = alpha;
+                    inside = true;
+                }
+            }
+        }
+        if !inside {
+            return None;
+        }
+
+        let vertices: Vec<SliceVertex> = [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)]
+            .iter()
+            .map(|&(s, t)| {
+                let p = at(s, t);
+                SliceVertex {
+                    position: [p.x, p.y, p.z],
+                    uv: [s, t],
+                }
+            })
+            .collect();
+        let indices: [u16; 6] = [0, 1, 2, 0, 2, 3];
+
+        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Slice Vertex Buffer"),
+            contents: bytemuck::cast_slice(&vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Slice Index Buffer"),
+            contents: bytemuck::cast_slice(&indices),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+
+        let size = wgpu::Extent3d {
+            width: SLICE_RESOLUTION,
+            height: SLICE_RESOLUTION,
+            depth_or_array_layers: 1,
+        };
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Slice Texture"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        queue.write_texture(
+            wgpu::TexelCopyTextureInfo {
+                texture: &texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            &texels,
+            wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(4 * SLICE_RESOLUTION),
+                rows_per_image: Some(SLICE_RESOLUTION),
+            },
+            size,
+        );
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Slice Bind Group"),
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(sampler),
+                },
+            ],
+        });
+
+        Some(Self {
+            vertex_buffer,
+            index_buffer,
+            bind_group,
+            num_indices: indices.len() as u32,
+        })
+    }
+}
@@ -0,0 +1,74 @@
+use files_exporter::options::ExportOptions;
+use serde::Serialize;
+use shared_lib::types::{AtomicCoordinates, Molecule, Node};
+
+const ATOMIC_COORDINATES_TYPE: &str = "mircmd:chemistry:atomic_coordinates";
+const MOLECULE_TYPE: &str = "mircmd:chemistry:molecule";
+// Formats holding a sequence of structures, the others take one structure per file
+const MULTI_FRAME_FORMATS: &[&str] = &["xyz", "pdb"];
+
+/// Export format as reported to JavaScript.
+#[derive(Serialize)]
+pub struct ExportFormat {
+    pub name: &'static str,
+    pub extension: &'static str,
+    pub multi_frame: bool,
+}
+
+pub fn formats() -> Vec<ExportFormat> {
+    files_exporter::formats()
+        .map(|(name, extension)| ExportFormat {
+            name,
+            extension,
+            multi_frame: MULTI_FRAME_FORMATS.contains(&name),
+        })
+        .collect()
+}
+
+/// Content of the file in the format (case insensitive) with the named structures as consecutive frames.
+/// `options` is the JSON of the exporter options, e.g. `{"xyz": {"precision": 10}}`. The total charge of the
+/// molecule is the rounded sum of the atomic charges of the first structure, if it has them.
+pub fn export_structures(
+    structures: &[(String, AtomicCoordinates)],
+    format: &str,
+    options: Option<&str>,
+) -> Result<String, String> {
+    let Some((_, first)) = structures.first() else {
+        return Err("No structures to export".to_string());
+    };
+    if structures.len() > 1 && !MULTI_FRAME_FORMATS.iter().any(|name| name.eq_ignore_ascii_case(format)) {
+        return Err(format!(
+            "Format {} holds a single structure, export the current frame instead",
+            format
+        ));
+    }
+    let options = ExportOptions::from_json(options.unwrap_or_default())?;
+
+    let charge = first
+        .charges
+        .as_ref()
+        .map_or(0, |charges| charges.iter().sum::<f64>().round() as i32);
+    let molecule = Molecule {
+        n_atoms: first.atomic_num.len() as i32,
+        atomic_num: first.atomic_num.clone(),
+        charge,
+        name: "Molecule".to_string(),
+        multiplicity: None,
+    };
+    let mut children = Vec::with_capacity(structures.len());
+    for (name, coordinates) in structures {
+        children.push(Node {
+            name: name.clone(),
+            r#type: ATOMIC_COORDINATES_TYPE.to_string(),
+            data: serde_json::to_vec(coordinates).map_err(|e| format!("Failed to serialize {}: {}", name, e))?,
+            children: Vec::new(),
+        });
+    }
+    let node = Node {
+        name: molecule.name.clone(),
+        r#type: MOLECULE_TYPE.to_string(),
+        data: serde_json::to_vec(&molecule).map_err(|e| format!("Failed to serialize molecule: {}", e))?,
+        children,
+    };
+    files_exporter::export_node_with_options(&node, format, &options)
+}
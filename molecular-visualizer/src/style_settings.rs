@@ -0,0 +1,121 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+use shared_lib::periodic_table::{RadiusSet, get_element_by_number, get_element_by_symbol};
+
+use super::config::{Representation, ScaledRadii, Style};
+use super::types::Color;
+
+/// Style settings passed by the host, they replace the defaults of `Style::new`.
+/// Only the settings given in the document are changed.
+#[derive(Clone, Debug, Default)]
+pub struct StyleSettings {
+    pub background_color: Option<Color>,
+    // Ball-and-stick radii of atoms by atomic number
+    pub atom_radii: Vec<(i32, f32)>,
+    // Some(None) switches back to the display radii
+    pub radius_set: Option<Option<ScaledRadii>>,
+    pub bond_radius: Option<f32>,
+    pub bond_tolerance: Option<f64>,
+    pub representation: Option<Representation>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct StyleSettingsJson {
+    background_color: Option<[f32; 3]>,
+    // Keys are element symbols or atomic numbers
+    #[serde(default)]
+    atom_radii: HashMap<String, f32>,
+    radius_set: Option<String>,
+    radius_scale: Option<f32>,
+    bond_radius: Option<f32>,
+    bond_tolerance: Option<f64>,
+    representation: Option<String>,
+}
+
+impl StyleSettings {
+    /// Settings from JSON, e.g. `{"background_color": [1, 1, 1], "atom_radii": {"C": 0.3, "8": 0.35},
+    /// "bond_radius": 0.12, "bond_tolerance": 0.2, "representation": "licorice"}`. Colors are RGB in range [0, 1],
+    /// radii are in Angstroms, the tolerance is relative to the sum of covalent radii of the atoms.
+    /// `"radius_set": "vdw", "radius_scale": 0.25` takes ball-and-stick radii of atoms from the "covalent", "vdw"
+    /// or "ionic" radii of the periodic table times the scale (1 by default), "display" restores the radii of the
+    /// style.
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let settings: StyleSettingsJson =
+            serde_json::from_str(json).map_err(|e| format!("Failed to parse style settings: {}", e))?;
+
+        let mut atom_radii = Vec::with_capacity(settings.atom_radii.len());
+        for (element, &radius) in &settings.atom_radii {
+            let element = match element.parse::<i32>() {
+                Ok(number) => get_element_by_number(number),
+                Err(_) => get_element_by_symbol(element),
+            }
+            .ok_or_else(|| format!("Unknown element: {}", element))?;
+            if !radius.is_finite() || radius <= 0.0 {
+                return Err(format!("Invalid radius of {}: {}", element.symbol, radius));
+            }
+            atom_radii.push((element.atomic_number, radius));
+        }
+        atom_radii.sort_by_key(|&(number, _)| number);
+
+        let radius_scale = settings.radius_scale.unwrap_or(1.0);
+        if !radius_scale.is_finite() || radius_scale <= 0.0 {
+            return Err(format!("Invalid radius scale: {}", radius_scale));
+        }
+        let radius_set = match settings.radius_set.as_deref() {
+            None if settings.radius_scale.is_some() => return Err("Radius scale needs a radius set".to_string()),
+            None => None,
+            Some("display") => Some(None),
+            Some(name) => Some(Some(ScaledRadii {
+                set: RadiusSet::from_name(name)?,
+                scale: radius_scale,
+            })),
+        };
+
+        if let Some(radius) = settings.bond_radius.filter(|r| !r.is_finite() || *r <= 0.0) {
+            return Err(format!("Invalid bond radius: {}", radius));
+        }
+        if let Some(tolerance) = settings.bond_tolerance.filter(|t| !t.is_finite() || *t <= -1.0) {
+            return Err(format!("Invalid bond tolerance: {}", tolerance));
+        }
+
+        let representation = settings
+            .representation
+            .as_deref()
+            .map(Representation::from_name)
+            .transpose()?;
+
+        Ok(Self {
+            background_color: settings.background_color.map(|[r, g, b]| Color::new(r, g, b, 1.0)),
+            atom_radii,
+            radius_set,
+            bond_radius: settings.bond_radius,
+            bond_tolerance: settings.bond_tolerance,
+            representation,
+        })
+    }
+
+    pub fn apply(&self, style: &mut Style) {
+        if let Some(color) = self.background_color {
+            style.background_color = color;
+        }
+        for &(number, radius) in &self.atom_radii {
+            if let Some(atom) = style.atoms.get_mut(&number) {
+                atom.radius = radius;
+            }
+        }
+        if let Some(radius_set) = self.radius_set {
+            style.radius_set = radius_set;
+        }
+        if let Some(radius) = self.bond_radius {
+            style.bond.thickness = radius;
+        }
+        if let Some(tolerance) = self.bond_tolerance {
+            style.geom_bond_tolerance = tolerance;
+        }
+        if let Some(representation) = self.representation {
+            style.representation = representation;
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use shared_lib::cell::UnitCell;
+use wgpu::util::DeviceExt;
+
+use super::core::mesh::InstanceData;
+use super::molecule::Molecule;
+
+/// Largest number of copies of the cell along a lattice vector.
+pub const MAX_REPETITIONS: u32 = 10;
+
+/// Copies of the structure in the neighboring periodic cells, a supercell of na × nb × nc cells.
+/// The structure itself is the cell (0, 0, 0), the copies are translated by combinations of the lattice vectors
+/// and are not pickable.
+pub struct Supercell {
+    pub atoms_instance_buffer: Option<wgpu::Buffer>,
+    pub bonds_instance_buffer: Option<wgpu::Buffer>,
+    pub num_atom_instances: u32,
+    pub num_bond_instances: u32,
+    repetitions: [u32; 3],
+    // Revision of the molecule the copies were made of
+    revision: Option<u64>,
+}
+
+impl Supercell {
+    pub fn new() -> Self {
+        Self {
+            atoms_instance_buffer: None,
+            bonds_instance_buffer: None,
+            num_atom_instances: 0,
+            num_bond_instances: 0,
+            repetitions: [1, 1, 1],
+            revision: None,
+        }
+    }
+
+    pub fn repetitions(&self) -> [u32; 3] {
+        self.repetitions
+    }
+
+    pub fn set_repetitions(&mut self, repetitions: [u32; 3]) -> Result<(), String> {
+        if let Some(n) = repetitions.iter().find(|n| !(1..=MAX_REPETITIONS).contains(n)) {
+            return Err(format!(
+                "Invalid number of cells: {}, must be from 1 to {}",
+                n, MAX_REPETITIONS
+            ));
+        }
+        if self.repetitions != repetitions {
+            self.repetitions = repetitions;
+            self.invalidate();
+        }
+        Ok(())
+    }
+
+    /// Copies have to be rebuilt, e.g. after the cell or the structure has been replaced.
+    pub fn invalidate(&mut self) {
+        self.revision = None;
+    }
+
+    /// Rebuilds the copies if the box was invalidated or the molecule has changed since the last call.
+    pub fn update(&mut self, device: &wgpu::Device, molecule: &Molecule, cell: Option<&UnitCell>) {
+        if self.revision == Some(molecule.revision()) {
+            return;
+        }
+        self.revision = Some(molecule.revision());
+
+        let [na, nb, nc] = self.repetitions;
+        let translations: Vec<[f32; 3]> = match cell {
+            Some(cell) => (0..nc)
+                .flat_map(|k| (0..nb).flat_map(move |j| (0..na).map(move |i| [i, j, k])))
+                .skip(1)
+                .map(|n| std::array::from_fn(|c| (0..3).map(|v| n[v] as f64 * cell.vectors[v][c]).sum::<f64>() as f32))
+                .collect(),
+            None => Vec::new(),
+        };
+        let (atoms, bonds) = if translations.is_empty() {
+            (Vec::new(), Vec::new())
+        } else {
+            molecule.plain_instances()
+        };
+        let replicate = |instances: &[InstanceData]| -> Vec<InstanceData> {
+            translations
+                .iter()
+                .flat_map(|t| {
+                    instances.iter().map(move |instance| InstanceData {
+                        position: std::array::from_fn(|c| instance.position[c] + t[c]),
+                        ..*instance
+                    })
+                })
+                .collect()
+        };
+        let create_buffer = |instances: &[InstanceData], label: &str| {
+            (!instances.is_empty()).then(|| {
+                device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some(label),
+                    contents: bytemuck::cast_slice(instances),
+                    usage: wgpu::BufferUsages::VERTEX,
+                })
+            })
+        };
+
+        let atoms = replicate(&atoms);
+        let bonds = replicate(&bonds);
+        self.num_atom_instances = atoms.len() as u32;
+        self.num_bond_instances = bonds.len() as u32;
+        self.atoms_instance_buffer = create_buffer(&atoms, "Supercell Atoms Instance Buffer");
+        self.bonds_instance_buffer = create_buffer(&bonds, "Supercell Bonds Instance Buffer");
+    }
+}
@@ -0,0 +1,93 @@
+use shared_lib::types::AtomicCoordinates;
+use wasm_bindgen::prelude::*;
+
+use super::thumbnails::{DEFAULT_THUMBNAIL_SIZE, Thumbnails, structure_hash};
+
+/// Offscreen renderer of small structure previews for the host, e.g. its file browser and recent structures.
+#[wasm_bindgen]
+pub struct ThumbnailRenderer {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    thumbnails: Thumbnails,
+}
+
+fn parse_structure(data: &[u8]) -> Result<AtomicCoordinates, JsValue> {
+    serde_json::from_slice(data).map_err(|e| JsValue::from_str(&format!("Failed to deserialize data: {e}")))
+}
+
+fn parse_hash(hash: &str) -> Result<u64, JsValue> {
+    u64::from_str_radix(hash, 16).map_err(|_| JsValue::from_str(&format!("Invalid structure hash: {hash}")))
+}
+
+#[wasm_bindgen]
+impl ThumbnailRenderer {
+    /// Use as: `const thumbnails = await ThumbnailRenderer.create();`, no canvas is needed.
+    pub async fn create() -> Result<ThumbnailRenderer, JsValue> {
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor::default());
+        let adapter = instance
+            .request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: wgpu::PowerPreference::LowPower,
+                compatible_surface: None,
+                force_fallback_adapter: false,
+            })
+            .await
+            .map_err(|e| JsValue::from_str(&format!("Failed to find an appropriate adapter: {e}")))?;
+        let (device, queue) = adapter
+            .request_device(&wgpu::DeviceDescriptor {
+                label: Some("Thumbnail Device"),
+                required_features: wgpu::Features::empty(),
+                required_limits: wgpu::Limits::default(),
+                memory_hints: wgpu::MemoryHints::default(),
+                experimental_features: wgpu::ExperimentalFeatures::default(),
+                trace: wgpu::Trace::Off,
+            })
+            .await
+            .map_err(|e| JsValue::from_str(&format!("Failed to create device: {e}")))?;
+        let thumbnails = Thumbnails::new(&device, wgpu::TextureFormat::Rgba8UnormSrgb);
+        Ok(ThumbnailRenderer {
+            device,
+            queue,
+            thumbnails,
+        })
+    }
+
+    /// Hash of the structure (same data as for `MolecularVisualizer.create`) as 16 hex digits, the key of its
+    /// thumbnails in the cache. It does not change between sessions, so the host may store thumbnails by it.
+    pub fn structure_hash(data: Vec<u8>) -> Result<String, JsValue> {
+        Ok(format!("{:016x}", structure_hash(&parse_structure(&data)?)))
+    }
+
+    /// PNG file of `size` × `size` pixels (128 by default, up to 512) with the structure, rendered once and then
+    /// taken from the cache.
+    pub async fn thumbnail(&mut self, data: Vec<u8>, size: Option<u32>) -> Result<Vec<u8>, JsValue> {
+        let data = parse_structure(&data)?;
+        self.thumbnails
+            .thumbnail(&self.device, &self.queue, &data, size.unwrap_or(DEFAULT_THUMBNAIL_SIZE))
+            .await
+            .map_err(|e| JsValue::from_str(&e))
+    }
+
+    /// Cached thumbnail by the structure hash without the structure itself, e.g. for the recent structures list.
+    pub fn cached_thumbnail(&mut self, hash: String, size: Option<u32>) -> Result<Option<Vec<u8>>, JsValue> {
+        let hash = parse_hash(&hash)?;
+        Ok(self
+            .thumbnails
+            .cached(hash, size.unwrap_or(DEFAULT_THUMBNAIL_SIZE))
+            .map(<[u8]>::to_vec))
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn cache_capacity(&self) -> usize {
+        self.thumbnails.capacity()
+    }
+
+    /// Number of thumbnails to keep (256 by default), the least recently used ones are dropped first.
+    #[wasm_bindgen(setter)]
+    pub fn set_cache_capacity(&mut self, capacity: usize) {
+        self.thumbnails.set_capacity(capacity);
+    }
+
+    pub fn clear_cache(&mut self) {
+        self.thumbnails.clear();
+    }
+}
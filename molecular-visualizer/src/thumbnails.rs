@@ -0,0 +1,184 @@
+use std::collections::{HashMap, VecDeque};
+
+use shared_lib::cancellation::CancellationToken;
+use shared_lib::types::AtomicCoordinates;
+
+use super::config::Config;
+use super::executor::Progress;
+use super::renderer::Renderer;
+use super::scene::Scene;
+
+pub const DEFAULT_THUMBNAIL_SIZE: u32 = 128;
+pub const MAX_THUMBNAIL_SIZE: u32 = 512;
+pub const DEFAULT_CACHE_CAPACITY: usize = 256;
+// Thumbnails are small, two samples per pixel along each axis keep the edges smooth at little cost
+const SUPERSAMPLING: u32 = 2;
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// Hash of everything in the structure that changes its picture: elements, coordinates, explicit bonds and
+/// the periodic cell. It is the same across sessions and builds (FNV-1a), so the host may keep thumbnails by it.
+pub fn structure_hash(data: &AtomicCoordinates) -> u64 {
+    let mut hash = FNV_OFFSET;
+    let mut write = |bytes: &[u8]| {
+        for &byte in bytes {
+            hash = (hash ^ byte as u64).wrapping_mul(FNV_PRIME);
+        }
+    };
+    write(&(data.atomic_num.len() as u64).to_le_bytes());
+    for i in 0..data.atomic_num.len() {
+        write(&data.atomic_num[i].to_le_bytes());
+        for values in [&data.x, &data.y, &data.z] {
+            write(&values.get(i).copied().unwrap_or(f64::NAN).to_le_bytes());
+        }
+    }
+    match &data.connectivity {
+        Some(connections) => {
+            write(&[1]);
+            for connection in connections {
+                write(&(connection.atom_1 as u64).to_le_bytes());
+                write(&(connection.atom_2 as u64).to_le_bytes());
+                write(&[connection.order]);
+            }
+        }
+        None => write(&[0]),
+    }
+    match &data.cell {
+        Some(cell) => {
+            write(&[1]);
+            cell.vectors.iter().flatten().for_each(|x| write(&x.to_le_bytes()));
+        }
+        None => write(&[0]),
+    }
+    hash
+}
+
+/// Style of thumbnails: the level of detail follows the apparent size of atoms whatever their number,
+/// so tiny atoms of large structures are drawn as point sprites.
+fn thumbnail_config() -> Config {
+    let mut config = Config::new();
+    config.lod.min_atoms = 0;
+    config
+}
+
+/// Small previews of structures, e.g. for a file browser or a list of recent structures, rendered offscreen
+/// with a simplified style into PNG files. Rendered thumbnails are cached by the structure hash and the size,
+/// the least recently used ones are dropped beyond the capacity.
+pub struct Thumbnails {
+    renderer: Renderer,
+    surface_config: wgpu::SurfaceConfiguration,
+    config: Config,
+    capacity: usize,
+    cache: HashMap<(u64, u32), Vec<u8>>,
+    // Keys of the cache from the least recently used
+    order: VecDeque<(u64, u32)>,
+}
+
+impl Thumbnails {
+    /// Thumbnails rendered with the format, one of the 8-bit RGBA or BGRA formats.
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat) -> Self {
+        let surface_config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format,
+            width: DEFAULT_THUMBNAIL_SIZE,
+            height: DEFAULT_THUMBNAIL_SIZE,
+            present_mode: wgpu::PresentMode::Fifo,
+            alpha_mode: wgpu::CompositeAlphaMode::Auto,
+            view_formats: vec![],
+            desired_maximum_frame_latency: 2,
+        };
+        Self {
+            renderer: Renderer::new(device, &surface_config),
+            surface_config,
+            config: thumbnail_config(),
+            capacity: DEFAULT_CACHE_CAPACITY,
+            cache: HashMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of thumbnails to keep, zero disables the cache.
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        self.evict();
+    }
+
+    pub fn clear(&mut self) {
+        self.cache.clear();
+        self.order.clear();
+    }
+
+    /// Cached PNG file of the structure with the hash, if any.
+    pub fn cached(&mut self, hash: u64, size: u32) -> Option<&[u8]> {
+        let key = (hash, size);
+        if !self.cache.contains_key(&key) {
+            return None;
+        }
+        self.touch(key);
+        self.cache.get(&key).map(Vec::as_slice)
+    }
+
+    /// PNG file of `size` × `size` pixels with the structure, from the cache if it was rendered before.
+    pub async fn thumbnail(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        data: &AtomicCoordinates,
+        size: u32,
+    ) -> Result<Vec<u8>, String> {
+        if size == 0 || size > MAX_THUMBNAIL_SIZE {
+            return Err(format!(
+                "Thumbnail size must be in range 1..={}, got {}",
+                MAX_THUMBNAIL_SIZE, size
+            ));
+        }
+        let hash = structure_hash(data);
+        if let Some(png) = self.cached(hash, size) {
+            return Ok(png.to_vec());
+        }
+
+        let mut scene = Scene::with_renderer(device, self.renderer.clone(), &self.config);
+        scene
+            .load_atomic_coordinates(
+                device,
+                &self.config,
+                data,
+                &Progress::new(None),
+                &CancellationToken::none(),
+            )
+            .await?;
+        let surface_config = wgpu::SurfaceConfiguration {
+            width: size,
+            height: size,
+            ..self.surface_config.clone()
+        };
+        let png = scene
+            .capture_image(device, queue, &self.config, &surface_config, size, size, SUPERSAMPLING)
+            .await?;
+
+        if self.capacity > 0 {
+            self.cache.insert((hash, size), png.clone());
+            self.touch((hash, size));
+            self.evict();
+        }
+        Ok(png)
+    }
+
+    fn touch(&mut self, key: (u64, u32)) {
+        self.order.retain(|k| *k != key);
+        self.order.push_back(key);
+    }
+
+    fn evict(&mut self) {
+        while self.order.len() > self.capacity {
+            if let Some(key) = self.order.pop_front() {
+                self.cache.remove(&key);
+            }
+        }
+    }
+}
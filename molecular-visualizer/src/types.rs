@@ -1,5 +1,29 @@
 use bytemuck::{Pod, Zeroable};
 
+/// Fraction of the brightness left to dimmed atoms and bonds.
+pub const DIMMED_BRIGHTNESS: f32 = 0.3;
+/// Fraction of the saturation left to desaturated atoms and bonds.
+pub const DESATURATED_SATURATION: f32 = 0.15;
+
+/// How atoms and bonds out of focus are drawn.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Dimming {
+    /// Darker, see `DIMMED_BRIGHTNESS`.
+    Fade,
+    /// Nearly grey with the same luminance, see `DESATURATED_SATURATION`.
+    Desaturate,
+}
+
+impl Dimming {
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        match name.to_lowercase().as_str() {
+            "fade" | "dim" => Ok(Self::Fade),
+            "desaturate" | "gray" | "grey" => Ok(Self::Desaturate),
+            _ => Err(format!("Unknown dimming: {}", name)),
+        }
+    }
+}
+
 #[repr(C)]
 #[derive(Copy, Clone, Debug, Pod, Zeroable)]
 pub struct Color {
@@ -13,4 +37,42 @@ impl Color {
     pub fn new(r: f32, g: f32, b: f32, a: f32) -> Self {
         Self { r, g, b, a }
     }
+
+    /// Darker color of objects out of focus, the alpha is kept.
+    pub fn dimmed(&self) -> Self {
+        Self::new(
+            self.r * DIMMED_BRIGHTNESS,
+            self.g * DIMMED_BRIGHTNESS,
+            self.b * DIMMED_BRIGHTNESS,
+            self.a,
+        )
+    }
+
+    /// Color of objects out of focus dimmed in the way, the alpha is kept.
+    pub fn dimmed_by(&self, dimming: Dimming) -> Self {
+        match dimming {
+            Dimming::Fade => self.dimmed(),
+            Dimming::Desaturate => {
+                let luminance = 0.2126 * self.r + 0.7152 * self.g + 0.0722 * self.b;
+                let gray = Self::new(luminance, luminance, luminance, self.a);
+                self.mixed(gray, 1.0 - DESATURATED_SATURATION)
+            }
+        }
+    }
+
+    /// Color between this one (`t` = 0) and the other one (`t` = 1).
+    pub fn mixed(&self, other: Color, t: f32) -> Self {
+        let mix = |a: f32, b: f32| a + (b - a) * t;
+        Self::new(
+            mix(self.r, other.r),
+            mix(self.g, other.g),
+            mix(self.b, other.b),
+            mix(self.a, other.a),
+        )
+    }
+
+    /// Quantizes the color to 8 bits per channel.
+    pub fn to_rgba8(&self) -> [u8; 4] {
+        [self.r, self.g, self.b, self.a].map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8)
+    }
 }
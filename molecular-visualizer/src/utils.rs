@@ -1,25 +1,118 @@
-use super::core::Mat4;
-use super::types::Color;
+use wasm_bindgen::prelude::*;
 
-pub fn id_to_color(id: usize) -> Color {
-    // Supports up to 256³ = 16,777,216 objects
+// Picking IDs are 32-bit: the class of the object in the high byte and its one-based index in the lower 24 bits,
+// so up to 256³ - 1 = 16,777,215 objects of each class. Atoms are class 0, so their IDs are just `index + 1`.
+const INDEX_BITS: u32 = 24;
+const INDEX_MASK: u32 = (1 << INDEX_BITS) - 1;
 
-    let r = ((id >> 16) & 0xFF) as f32 / 255.0;
-    let g = ((id >> 8) & 0xFF) as f32 / 255.0;
-    let b = (id & 0xFF) as f32 / 255.0;
-    Color::new(r, g, b, 1.0)
+const ATOM_CLASS: u32 = 0;
+const BOND_CLASS: u32 = 1;
+const LABEL_CLASS: u32 = 2;
+const MEASUREMENT_CLASS: u32 = 3;
+const PLANE_CLASS: u32 = 4;
+
+/// Object under the cursor, indices are zero-based.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum PickedObject {
+    Atom(usize),
+    /// Index in the list of bonds of the molecule.
+    Bond(usize),
+    /// Label of the atom with the index.
+    Label(usize),
+    /// Lines and arcs of the current measurement.
+    Measurement,
+    /// Disk of the measured plane with the index.
+    Plane(usize),
+}
+
+impl PickedObject {
+    /// ID written into the picking texture, 0 (not pickable) if the index does not fit into 24 bits.
+    pub fn picking_id(&self) -> u32 {
+        let (class, index) = match *self {
+            PickedObject::Atom(i) => (ATOM_CLASS, i),
+            PickedObject::Bond(i) => (BOND_CLASS, i),
+            PickedObject::Label(i) => (LABEL_CLASS, i),
+            PickedObject::Measurement => (MEASUREMENT_CLASS, 0),
+            PickedObject::Plane(i) => (PLANE_CLASS, i),
+        };
+        match u32::try_from(index + 1) {
+            Ok(id) if id <= INDEX_MASK => class << INDEX_BITS | id,
+            _ => 0,
+        }
+    }
+
+    /// Inverse of `picking_id`, None for the background and unknown classes.
+    pub fn from_picking_id(id: u32) -> Option<Self> {
+        let index = (id & INDEX_MASK).checked_sub(1)? as usize;
+        match id >> INDEX_BITS {
+            ATOM_CLASS => Some(PickedObject::Atom(index)),
+            BOND_CLASS => Some(PickedObject::Bond(index)),
+            LABEL_CLASS => Some(PickedObject::Label(index)),
+            MEASUREMENT_CLASS => Some(PickedObject::Measurement),
+            PLANE_CLASS => Some(PickedObject::Plane(index)),
+            _ => None,
+        }
+    }
+
+    /// Name of the class for the host.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            PickedObject::Atom(_) => "atom",
+            PickedObject::Bond(_) => "bond",
+            PickedObject::Label(_) => "label",
+            PickedObject::Measurement => "measurement",
+            PickedObject::Plane(_) => "plane",
+        }
+    }
+}
+
+/// Object under the cursor as reported to the host.
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+pub struct PickInfo {
+    object: PickedObject,
+    atoms: Vec<usize>,
+}
+
+impl PickInfo {
+    pub fn new(object: PickedObject, atoms: Vec<usize>) -> Self {
+        Self { object, atoms }
+    }
+}
+
+#[wasm_bindgen]
+impl PickInfo {
+    /// "atom", "bond", "label", "measurement" or "plane".
+    #[wasm_bindgen(getter)]
+    pub fn kind(&self) -> String {
+        self.object.kind().to_string()
+    }
+
+    /// Index of the atom, bond, atom of the label or plane starting from 1, 0 for the measurement.
+    #[wasm_bindgen(getter)]
+    pub fn index(&self) -> usize {
+        match self.object {
+            PickedObject::Atom(i) | PickedObject::Bond(i) | PickedObject::Label(i) | PickedObject::Plane(i) => i + 1,
+            PickedObject::Measurement => 0,
+        }
+    }
+
+    /// Atoms of the object, indices start from 1: the atom itself, the bonded atoms, the measured atoms
+    /// or the fitted atoms of the plane.
+    #[wasm_bindgen(getter)]
+    pub fn atoms(&self) -> Vec<usize> {
+        self.atoms.clone()
+    }
 }
 
-pub fn color_to_id(r: u8, g: u8, b: u8) -> usize {
-    (r as usize) << 16 | (g as usize) << 8 | (b as usize)
+// Same as `picking_color` in main.wgsl: the index in RGB, the class in alpha. Only the render tests encode IDs
+// on the CPU
+#[cfg(test)]
+pub fn id_to_color(id: u32) -> [u8; 4] {
+    [(id >> 16) as u8, (id >> 8) as u8, id as u8, (id >> INDEX_BITS) as u8]
 }
 
-pub fn get_model_matrix(mat: &Mat4<f32>) -> [[f32; 4]; 4] {
-    let matrix = mat.data;
-    [
-        [matrix[0], matrix[1], matrix[2], matrix[3]],
-        [matrix[4], matrix[5], matrix[6], matrix[7]],
-        [matrix[8], matrix[9], matrix[10], matrix[11]],
-        [matrix[12], matrix[13], matrix[14], matrix[15]],
-    ]
+// Inverse of `id_to_color`
+pub fn color_to_id(r: u8, g: u8, b: u8, a: u8) -> u32 {
+    (a as u32) << INDEX_BITS | (r as u32) << 16 | (g as u32) << 8 | (b as u32)
 }
@@ -0,0 +1,291 @@
+use std::f64::consts::PI;
+
+use shared_lib::types::VibrationalModes;
+use wgpu::util::DeviceExt;
+
+use super::config::Vibration as VibrationStyle;
+use super::core::mesh::{InstanceData, LIGHTING_FLAG};
+use super::core::{Quaternion, Vec3};
+
+pub const DEFAULT_AMPLITUDE: f32 = 0.3;
+pub const MAX_AMPLITUDE: f32 = 2.0;
+pub const DEFAULT_FREQUENCY: f32 = 1.0;
+pub const MAX_FREQUENCY: f32 = 10.0;
+// Longest step of the oscillation, e.g. when frames were not drawn while the page was hidden
+const MAX_STEP_MS: f64 = 100.0;
+// Arrows of atoms moving less than this fraction of the largest displacement of the mode are not drawn
+const MIN_ARROW_FRACTION: f32 = 0.05;
+// Radius and length of arrow heads relative to the radius of the shafts
+const HEAD_RADIUS_FACTOR: f32 = 2.5;
+const HEAD_LENGTH_FACTOR: f32 = 6.0;
+
+/// Oscillation of the atoms around their equilibrium positions along one of the normal modes of the structure.
+pub struct Vibration {
+    // Wavenumbers of the modes in cm⁻¹
+    frequencies: Vec<f64>,
+    // Displacements of the atoms for every mode, scaled so that the largest one of the mode has the length 1
+    modes: Vec<Vec<Vec3<f32>>>,
+    equilibrium: Vec<Vec3<f32>>,
+    mode: usize,
+    // Largest displacement of an atom in Angstroms
+    amplitude: f32,
+    // Oscillations per second of the animation, not the frequency of the mode
+    frequency: f32,
+    // Fraction of the current oscillation
+    phase: f64,
+    playing: bool,
+    // Time of the last advance in milliseconds, `None` right after the oscillation has started
+    last_time: Option<f64>,
+    // Arrows along the displacements of the atoms
+    arrows: bool,
+    pub arrows_instance_buffer: Option<wgpu::Buffer>,
+    // Shafts are followed by the same number of heads in the buffer
+    pub num_arrows: u32,
+    arrows_dirty: bool,
+}
+
+impl Vibration {
+    /// Modes must have the atoms of the structure in the same order, `equilibrium` are the current positions
+    /// of the atoms.
+    pub fn new(modes: &VibrationalModes, equilibrium: Vec<Vec3<f32>>) -> Result<Self, String> {
+        if modes.displacements.is_empty() {
+            return Err("No vibrational modes are given".to_string());
+        }
+        if modes.frequencies.len() != modes.displacements.len() {
+            return Err(format!(
+                "Number of frequencies {} differs from the number of modes {}",
+                modes.frequencies.len(),
+                modes.displacements.len()
+            ));
+        }
+        let mut normalized = Vec::with_capacity(modes.displacements.len());
+        for (index, displacements) in modes.displacements.iter().enumerate() {
+            if displacements.len() != equilibrium.len() {
+                return Err(format!(
+                    "Mode {} has {} atoms, the structure has {}",
+                    index + 1,
+                    displacements.len(),
+                    equilibrium.len()
+                ));
+            }
+            let largest = displacements
+                .iter()
+                .map(|&[x, y, z]| (x * x + y * y + z * z).sqrt())
+                .fold(0.0, f64::max);
+            let scale = if largest > 0.0 { 1.0 / largest } else { 0.0 };
+            normalized.push(
+                displacements
+                    .iter()
+                    .map(|&[x, y, z]| Vec3::new((x * scale) as f32, (y * scale) as f32, (z * scale) as f32))
+                    .collect(),
+            );
+        }
+        Ok(Self {
+            frequencies: modes.frequencies.clone(),
+            modes: normalized,
+            equilibrium,
+            mode: 0,
+            amplitude: DEFAULT_AMPLITUDE,
+            frequency: DEFAULT_FREQUENCY,
+            phase: 0.0,
+            playing: false,
+            last_time: None,
+            arrows: false,
+            arrows_instance_buffer: None,
+            num_arrows: 0,
+            arrows_dirty: true,
+        })
+    }
+
+    /// Index of the selected mode starting from 0.
+    pub fn mode(&self) -> usize {
+        self.mode
+    }
+
+    /// Wavenumbers of the modes in cm⁻¹.
+    pub fn frequencies(&self) -> &[f64] {
+        &self.frequencies
+    }
+
+    /// Selects the mode (starts from 0), the oscillation starts from the equilibrium.
+    pub fn set_mode(&mut self, index: usize) -> Result<(), String> {
+        if index >= self.modes.len() {
+            return Err(format!("Mode index {} is out of range 0..{}", index, self.modes.len()));
+        }
+        self.mode = index;
+        self.phase = 0.0;
+        self.last_time = None;
+        self.arrows_dirty = true;
+        Ok(())
+    }
+
+    pub fn amplitude(&self) -> f32 {
+        self.amplitude
+    }
+
+    /// Largest displacement of an atom in Angstroms.
+    pub fn set_amplitude(&mut self, amplitude: f32) -> Result<(), String> {
+        if amplitude.is_nan() || amplitude <= 0.0 || amplitude > MAX_AMPLITUDE {
+            return Err(format!(
+                "Amplitude must be in range (0, {}], got {}",
+                MAX_AMPLITUDE, amplitude
+            ));
+        }
+        self.amplitude = amplitude;
+        self.arrows_dirty = true;
+        Ok(())
+    }
+
+    pub fn frequency(&self) -> f32 {
+        self.frequency
+    }
+
+    /// Oscillations per second.
+    pub fn set_frequency(&mut self, frequency: f32) -> Result<(), String> {
+        if frequency.is_nan() || frequency <= 0.0 || frequency > MAX_FREQUENCY {
+            return Err(format!(
+                "Frequency must be in range (0, {}], got {}",
+                MAX_FREQUENCY, frequency
+            ));
+        }
+        self.frequency = frequency;
+        Ok(())
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    pub fn play(&mut self) {
+        self.playing = true;
+        self.last_time = None;
+    }
+
+    /// Stops the oscillation, the atoms return to the equilibrium.
+    pub fn stop(&mut self) {
+        self.playing = false;
+        self.phase = 0.0;
+        self.arrows_dirty = true;
+    }
+
+    /// Fraction of the current oscillation, 0.25 is the largest displacement along the mode.
+    pub fn set_phase(&mut self, phase: f64) {
+        self.phase = phase.rem_euclid(1.0);
+        self.arrows_dirty = true;
+    }
+
+    /// Advances the oscillation to the time in milliseconds, returns `true` if the atoms have moved.
+    pub fn advance(&mut self, time: f64) -> bool {
+        if !self.playing {
+            return false;
+        }
+        let elapsed = match self.last_time {
+            Some(last_time) => (time - last_time).clamp(0.0, MAX_STEP_MS),
+            None => 0.0,
+        };
+        self.last_time = Some(time);
+        if elapsed == 0.0 {
+            return false;
+        }
+        self.phase = (self.phase + elapsed * self.frequency as f64 / 1000.0) % 1.0;
+        self.arrows_dirty = true;
+        true
+    }
+
+    pub fn arrows_visible(&self) -> bool {
+        self.arrows
+    }
+
+    pub fn set_arrows_visible(&mut self, visible: bool) {
+        self.arrows = visible;
+        self.arrows_dirty = true;
+    }
+
+    pub fn equilibrium(&self) -> &[Vec3<f32>] {
+        &self.equilibrium
+    }
+
+    /// Atom positions displaced along the selected mode at the current phase.
+    pub fn positions(&self) -> Vec<Vec3<f32>> {
+        let factor = self.amplitude * (2.0 * PI * self.phase).sin() as f32;
+        self.equilibrium
+            .iter()
+            .zip(&self.modes[self.mode])
+            .map(|(&position, &displacement)| position + displacement * factor)
+            .collect()
+    }
+
+    /// Rebuilds the instances of the arrows if the atoms have moved since the last call.
+    pub fn update_arrows(&mut self, device: &wgpu::Device, style: &VibrationStyle) {
+        if !self.arrows_dirty {
+            return;
+        }
+        self.arrows_dirty = false;
+
+        let mut shafts = Vec::new();
+        let mut heads = Vec::new();
+        if self.arrows {
+            for (position, &displacement) in self.positions().into_iter().zip(&self.modes[self.mode]) {
+                let fraction = displacement.length();
+                if fraction < MIN_ARROW_FRACTION {
+                    continue;
+                }
+                let (shaft, head) = arrow_instances(position, displacement / fraction, fraction, style);
+                shafts.push(shaft);
+                heads.push(head);
+            }
+        }
+        self.num_arrows = shafts.len() as u32;
+        shafts.extend(heads);
+        self.arrows_instance_buffer = if shafts.is_empty() {
+            None
+        } else {
+            Some(device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Vibration Arrows Instance Buffer"),
+                contents: bytemuck::cast_slice(&shafts),
+                usage: wgpu::BufferUsages::VERTEX,
+            }))
+        };
+    }
+
+    /// Arrows have to be rebuilt, e.g. after they were turned on or the style has changed.
+    pub fn invalidate(&mut self) {
+        self.arrows_dirty = true;
+    }
+}
+
+/// Lit shaft (cylinder mesh) and head (cone mesh) of the arrow from the atom along the unit direction,
+/// `fraction` of the full length of the style.
+fn arrow_instances(
+    start: Vec3<f32>,
+    direction: Vec3<f32>,
+    fraction: f32,
+    style: &VibrationStyle,
+) -> (InstanceData, InstanceData) {
+    let rotation = Quaternion::rotation_to(Vec3::new(0.0, 0.0, 1.0), direction);
+    let rotation = [rotation.x, rotation.y, rotation.z, rotation.w];
+    let head_length = style.arrow_thickness * HEAD_LENGTH_FACTOR;
+    let length = (style.arrow_length * fraction).max(head_length);
+    let shaft_length = length - head_length;
+    let shaft_center = start + direction * (shaft_length / 2.0);
+    let head_center = start + direction * (shaft_length + head_length / 2.0);
+    let color = style.arrow_color.to_rgba8();
+
+    let shaft = InstanceData {
+        rotation,
+        position: [shaft_center.x, shaft_center.y, shaft_center.z],
+        scale: [style.arrow_thickness, shaft_length / 2.0],
+        color,
+        picking_id: 0,
+        flags: LIGHTING_FLAG,
+    };
+    let head = InstanceData {
+        rotation,
+        position: [head_center.x, head_center.y, head_center.z],
+        scale: [style.arrow_thickness * HEAD_RADIUS_FACTOR, head_length / 2.0],
+        color,
+        picking_id: 0,
+        flags: LIGHTING_FLAG,
+    };
+    (shaft, head)
+}
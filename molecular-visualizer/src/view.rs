@@ -0,0 +1,180 @@
+use wasm_bindgen::prelude::*;
+
+use super::core::{Quaternion, Transform, Vec3};
+
+/// Tilt of automatically oriented molecules in degrees about the screen X and Y axes, so that planar and
+/// linear structures show some depth.
+pub const AUTO_ORIENTATION_TILT: [f32; 2] = [15.0, -20.0];
+// Sweeps of the Jacobi method, 3×3 matrices converge in a few of them
+const JACOBI_SWEEPS: usize = 16;
+
+/// Rotation and zoom of the molecule. Views of linked visualizers are copied from one to the others,
+/// the zoom is relative to the size of each molecule.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug)]
+pub struct ViewState {
+    rotation: Quaternion<f32>,
+    scale: f32,
+}
+
+#[wasm_bindgen]
+impl ViewState {
+    /// Rotation as a unit quaternion (w, x, y, z) and the zoom factor.
+    #[wasm_bindgen(constructor)]
+    pub fn new(w: f32, x: f32, y: f32, z: f32, scale: f32) -> Self {
+        Self {
+            rotation: Quaternion::new(w, x, y, z),
+            scale,
+        }
+    }
+
+    /// Unit quaternion (w, x, y, z).
+    #[wasm_bindgen(getter)]
+    pub fn rotation(&self) -> Vec<f32> {
+        vec![self.rotation.w, self.rotation.x, self.rotation.y, self.rotation.z]
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
+}
+
+impl ViewState {
+    pub fn of(transform: &Transform) -> Self {
+        Self {
+            rotation: transform.rotation,
+            scale: transform.scale.x,
+        }
+    }
+
+    pub fn apply(&self, transform: &mut Transform) {
+        transform.set_rotation(self.rotation);
+        transform.set_scale(Vec3::new(self.scale, self.scale, self.scale));
+    }
+}
+
+/// Rotation of the molecule with the atoms at `positions` for its first view: the principal axis of the largest
+/// extent is horizontal, the one of the smallest extent points at the viewer, then the default tilt is applied.
+/// No rotation for fewer than 2 atoms.
+pub fn principal_orientation(positions: &[Vec3<f32>]) -> Quaternion<f32> {
+    if positions.len() < 2 {
+        return Quaternion::new(1.0, 0.0, 0.0, 0.0);
+    }
+    let points: Vec<[f64; 3]> = positions.iter().map(|p| [p.x as f64, p.y as f64, p.z as f64]).collect();
+    let n = points.len() as f64;
+    let center: [f64; 3] = std::array::from_fn(|k| points.iter().map(|p| p[k]).sum::<f64>() / n);
+    let mut covariance = [[0.0; 3]; 3];
+    for p in &points {
+        let d: [f64; 3] = std::array::from_fn(|k| p[k] - center[k]);
+        for i in 0..3 {
+            for j in 0..3 {
+                covariance[i][j] += d[i] * d[j];
+            }
+        }
+    }
+    let [first, second] = principal_axes(covariance);
+    let third = cross(first, second);
+
+    // Rows of the rotation are the axes, it turns the first one to X, the second one to Y and the third one to Z
+    let alignment = rotation_to_quaternion([first, second, third]);
+    let [pitch, yaw] = AUTO_ORIENTATION_TILT;
+    // Turning about Y last keeps the first axis horizontal
+    Quaternion::from_axis_and_angle(Vec3::new(0.0, 1.0, 0.0), yaw)
+        * Quaternion::from_axis_and_angle(Vec3::new(1.0, 0.0, 0.0), pitch)
+        * alignment
+}
+
+// Unit eigenvectors of the two largest eigenvalues of the symmetric matrix (cyclic Jacobi method), each with its
+// largest component positive so that the orientation does not depend on rounding
+fn principal_axes(mut m: [[f64; 3]; 3]) -> [[f64; 3]; 2] {
+    // Columns are the eigenvectors
+    let mut v = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+    let scale: f64 = m.iter().flatten().map(|x| x * x).sum();
+    for _ in 0..JACOBI_SWEEPS {
+        let off_diagonal = m[0][1] * m[0][1] + m[0][2] * m[0][2] + m[1][2] * m[1][2];
+        if off_diagonal <= 1e-24 * scale {
+            break;
+        }
+        for (p, q) in [(0, 1), (0, 2), (1, 2)] {
+            if m[p][q] == 0.0 {
+                continue;
+            }
+            let theta = (m[q][q] - m[p][p]) / (2.0 * m[p][q]);
+            let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+            let c = 1.0 / (t * t + 1.0).sqrt();
+            let s = t * c;
+            // m is turned from both sides, v collects the turns
+            for row in m.iter_mut() {
+                let (a, b) = (row[p], row[q]);
+                row[p] = c * a - s * b;
+                row[q] = s * a + c * b;
+            }
+            let (a, b) = (m[p], m[q]);
+            m[p] = std::array::from_fn(|k| c * a[k] - s * b[k]);
+            m[q] = std::array::from_fn(|k| s * a[k] + c * b[k]);
+            for row in v.iter_mut() {
+                let (a, b) = (row[p], row[q]);
+                row[p] = c * a - s * b;
+                row[q] = s * a + c * b;
+            }
+        }
+    }
+    let mut order = [0, 1, 2];
+    order.sort_by(|&i, &j| m[j][j].total_cmp(&m[i][i]));
+    [order[0], order[1]].map(|i| {
+        let axis: [f64; 3] = std::array::from_fn(|k| v[k][i]);
+        let largest = axis
+            .into_iter()
+            .max_by(|a, b| a.abs().total_cmp(&b.abs()))
+            .unwrap_or(1.0);
+        axis.map(|x| if largest < 0.0 { -x } else { x })
+    })
+}
+
+fn cross(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+// Unit quaternion of the proper rotation matrix r[row][column] (Shepperd's method)
+fn rotation_to_quaternion(r: [[f64; 3]; 3]) -> Quaternion<f32> {
+    let trace = r[0][0] + r[1][1] + r[2][2];
+    let (w, x, y, z) = if trace > 0.0 {
+        let s = 2.0 * (trace + 1.0).sqrt();
+        (
+            0.25 * s,
+            (r[2][1] - r[1][2]) / s,
+            (r[0][2] - r[2][0]) / s,
+            (r[1][0] - r[0][1]) / s,
+        )
+    } else if r[0][0] > r[1][1] && r[0][0] > r[2][2] {
+        let s = 2.0 * (1.0 + r[0][0] - r[1][1] - r[2][2]).sqrt();
+        (
+            (r[2][1] - r[1][2]) / s,
+            0.25 * s,
+            (r[0][1] + r[1][0]) / s,
+            (r[0][2] + r[2][0]) / s,
+        )
+    } else if r[1][1] > r[2][2] {
+        let s = 2.0 * (1.0 + r[1][1] - r[0][0] - r[2][2]).sqrt();
+        (
+            (r[0][2] - r[2][0]) / s,
+            (r[0][1] + r[1][0]) / s,
+            0.25 * s,
+            (r[1][2] + r[2][1]) / s,
+        )
+    } else {
+        let s = 2.0 * (1.0 + r[2][2] - r[0][0] - r[1][1]).sqrt();
+        (
+            (r[1][0] - r[0][1]) / s,
+            (r[0][2] + r[2][0]) / s,
+            (r[1][2] + r[2][1]) / s,
+            0.25 * s,
+        )
+    };
+    Quaternion::new(w as f32, x as f32, y as f32, z as f32)
+}
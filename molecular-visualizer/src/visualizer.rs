@@ -1,13 +1,114 @@
+use std::collections::HashSet;
 use std::sync::Arc;
 
-use shared_lib::types::AtomicCoordinates;
+use serde::Serialize;
+use shared_lib::atom_types::{self, TypingScheme};
+use shared_lib::cancellation::CancellationToken;
+use shared_lib::cell::UnitCell;
+use shared_lib::colormap::{Colormap, Palette};
+use shared_lib::comparison::{self, ToleranceProfile};
+use shared_lib::contacts::{self, Clash, DEFAULT_CLASH_FRACTION, DEFAULT_COVALENT_CLASH_FRACTION};
+use shared_lib::diffraction::{self, DiffractionSettings};
+use shared_lib::editing;
+use shared_lib::force_field::ForceField;
+use shared_lib::hydrogens;
+use shared_lib::mass_spectrum::{self, IsotopeSettings};
+use shared_lib::periodic_table;
+use shared_lib::powder::{self, PowderSettings};
+use shared_lib::profiling;
+use shared_lib::symmetry::SymmetryElement;
+use shared_lib::templates;
+use shared_lib::types::{AtomicCoordinates, VibrationalModes, VolumeCube};
 use wasm_bindgen::prelude::*;
 use web_sys::HtmlCanvasElement;
 
-use super::atom::AtomInfo;
-use super::config::Config;
-use super::core::Vec3;
-use super::scene::Scene;
+use super::animation::DEFAULT_FRAME_RATE;
+use super::atom::{AtomInfo, AtomStyle};
+use super::autosave::Autosave;
+use super::capture::DEFAULT_SUPERSAMPLING;
+use super::color_scheme::ColorScheme;
+use super::config::{Config, Representation};
+use super::core::{CameraLimits, ProjectionMode, Vec3};
+#[cfg(feature = "debug-tools")]
+use super::debug_tools::DebugView;
+use super::executor::{Progress, cancellation_token};
+use super::isosurface::IsosurfaceSettings;
+use super::labels::{BondLabels, LabelKind};
+use super::measurement::{MeasurementInfo, PlaneInfo};
+use super::presets::StylePreset;
+use super::report::{DEFAULT_IMAGE_HEIGHT, DEFAULT_IMAGE_WIDTH, Report, ReportFormat};
+use super::scene::{DEFAULT_FOCUS_CUTOFF, FocusMode, Scene};
+use super::slice::SliceSettings;
+use super::structure_export;
+use super::style_settings::StyleSettings;
+use super::types::{Color, Dimming};
+use super::utils::{PickInfo, PickedObject};
+use super::vibration::{DEFAULT_AMPLITUDE, DEFAULT_FREQUENCY};
+use super::view::ViewState;
+
+// Clash of the live or covalent check as reported to JavaScript
+#[derive(Serialize)]
+struct ClashInfo {
+    atoms: [usize; 2],
+    distance: f64,
+    fraction: f64,
+}
+
+// Structural edit of the builder as reported to JavaScript, atom indices start from 1
+#[derive(Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum EditEvent {
+    AddAtom {
+        atom: usize,
+        element: &'static str,
+        position: [f64; 3],
+    },
+    DeleteAtoms {
+        atoms: Vec<usize>,
+    },
+    AddBond {
+        atoms: [usize; 2],
+        order: u8,
+    },
+    RemoveBond {
+        atoms: [usize; 2],
+    },
+    AttachTemplate {
+        atom: usize,
+        template: &'static str,
+        replaced: bool,
+        elements: Vec<&'static str>,
+        positions: Vec<[f64; 3]>,
+    },
+    AddHydrogens {
+        positions: Vec<[f64; 3]>,
+    },
+}
+
+// Symmetry of the symmetry-constrained editing mode as reported to JavaScript
+#[derive(Serialize)]
+struct SymmetryInfo<'a> {
+    point_group: &'a str,
+    center: [f64; 3],
+    elements: Vec<SymmetryElement>,
+}
+
+// Entry of the element legend as reported to JavaScript
+#[derive(Serialize)]
+struct ElementInfo {
+    symbol: &'static str,
+    number: i32,
+    count: usize,
+    visible: usize,
+}
+
+// Radius and color of an atom overriding the style as reported to JavaScript
+#[derive(Serialize)]
+struct AtomStyleInfo {
+    index: usize,
+    radius: Option<f32>,
+    color: Option<[f32; 3]>,
+}
 
 #[wasm_bindgen]
 pub struct MolecularVisualizer {
@@ -18,79 +119,72 @@ pub struct MolecularVisualizer {
     scene: Scene,
     visualizer_config: Config,
     node_data: AtomicCoordinates,
+    autosave: Autosave,
+    // Atomic number of the atoms added by clicks in the builder mode, None when the mode is off
+    builder_element: Option<i32>,
+    // Template attached to the atoms clicked in the builder mode instead of selecting them
+    builder_template: Option<&'static templates::Template>,
+    on_autosave: Option<js_sys::Function>,
+    on_edit: Option<js_sys::Function>,
+    on_measurement: Option<js_sys::Function>,
+    on_object_click: Option<js_sys::Function>,
+    on_object_hover: Option<js_sys::Function>,
+    on_selection_changed: Option<js_sys::Function>,
+    on_view_changed: Option<js_sys::Function>,
 }
 
 #[wasm_bindgen]
 impl MolecularVisualizer {
     /// Creates a new MolecularVisualizer instance.
-    /// Use as: `const visualizer = await MolecularVisualizer.create(canvas);`
-    pub async fn create(canvas: HtmlCanvasElement, data: Vec<u8>) -> Result<MolecularVisualizer, JsValue> {
-        let width = canvas.width();
-        let height = canvas.height();
-
-        // Create wgpu instance with WebGPU backend
-        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor::default());
-
-        // Create surface from canvas
-        let surface = instance
-            .create_surface(wgpu::SurfaceTarget::Canvas(canvas))
-            .map_err(|e| JsValue::from_str(&format!("Failed to create surface: {e}")))?;
-
-        // Request adapter (GPU handle)
-        let adapter = instance
-            .request_adapter(&wgpu::RequestAdapterOptions {
-                power_preference: wgpu::PowerPreference::default(),
-                compatible_surface: Some(&surface),
-                force_fallback_adapter: false,
-            })
-            .await
-            .map_err(|e| JsValue::from_str(&format!("Failed to find an appropriate adapter: {e}")))?;
-
-        // Request device and queue
-        let (device, queue): (wgpu::Device, wgpu::Queue) = adapter
-            .request_device(&wgpu::DeviceDescriptor {
-                label: Some("WebGPU Device"),
-                required_features: wgpu::Features::empty(),
-                required_limits: wgpu::Limits::default(),
-                memory_hints: wgpu::MemoryHints::default(),
-                experimental_features: wgpu::ExperimentalFeatures::default(),
-                trace: wgpu::Trace::Off,
-            })
-            .await
-            .map_err(|e| JsValue::from_str(&format!("Failed to create device: {e}")))?;
-
-        let device = Arc::new(device);
-
-        // Configure surface
-        let surface_caps = surface.get_capabilities(&adapter);
-        let surface_format = surface_caps
-            .formats
-            .iter()
-            .find(|f| f.is_srgb())
-            .copied()
-            .unwrap_or(surface_caps.formats[0]);
-
-        let config = wgpu::SurfaceConfiguration {
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-            format: surface_format,
-            width,
-            height,
-            present_mode: wgpu::PresentMode::Fifo,
-            alpha_mode: surface_caps.alpha_modes[0],
-            view_formats: vec![],
-            desired_maximum_frame_latency: 2,
+    /// Use as: `const visualizer = await MolecularVisualizer.create(canvas, data, on_progress, is_cancelled, style);`
+    /// `on_progress(stage, done, total)` is called while heavy parts of the scene are being built.
+    /// `is_cancelled()` is polled between build chunks; returning true rejects the promise.
+    /// `style` is an optional JSON document with style settings, see `update_style`.
+    pub async fn create(
+        canvas: HtmlCanvasElement,
+        data: Vec<u8>,
+        on_progress: Option<js_sys::Function>,
+        is_cancelled: Option<js_sys::Function>,
+        style: Option<String>,
+    ) -> Result<MolecularVisualizer, JsValue> {
+        let mut visualizer_config = Config::new();
+        let settings = match style {
+            Some(style) => StyleSettings::from_json(&style).map_err(|e| JsValue::from_str(&e))?,
+            None => StyleSettings::default(),
         };
-        surface.configure(&device, &config);
+        settings.apply(&mut visualizer_config.style);
 
-        let visualizer_config = Config::new();
+        let CanvasGpu {
+            surface,
+            backend,
+            device,
+            queue,
+            config,
+        } = CanvasGpu::new(canvas).await?;
+        let (width, height) = (config.width, config.height);
+        let device = Arc::new(device);
 
-        let mut scene = Scene::new(&device, &config);
+        let mut scene = Scene::new(&device, &config, &visualizer_config);
         scene.projection_manager.set_viewport(width, height);
+        // Reading back the picking texture is not reliable on WebGL2
+        scene.cpu_picking = backend == wgpu::Backend::Gl;
 
         let node_data: AtomicCoordinates = serde_json::from_slice(&data)
             .map_err(|e| JsValue::from_str(&format!("Failed to deserialize data: {e}")))?;
+        // The representation chosen by the host (e.g. kept for the node) wins over the one by the size
+        if settings.representation.is_none()
+            && let Some(representation) = visualizer_config.view_defaults.representation(&node_data)
+        {
+            visualizer_config.style.representation = representation;
+        }
 
-        scene.load_atomic_coordinates(&device, &visualizer_config, &node_data);
+        let progress = Progress::new(on_progress);
+        let cancel = cancellation_token(is_cancelled);
+        scene
+            .load_atomic_coordinates(&device, &visualizer_config, &node_data, &progress, &cancel)
+            .await
+            .map_err(|e| JsValue::from_str(&e))?;
+        scene.orient(visualizer_config.auto_orientation);
 
         let device = Arc::into_inner(device).unwrap();
 
@@ -101,10 +195,41 @@ impl MolecularVisualizer {
             config,
             scene,
             visualizer_config,
+            autosave: Autosave::new(&node_data),
             node_data,
+            builder_element: None,
+            builder_template: None,
+            on_autosave: None,
+            on_edit: None,
+            on_measurement: None,
+            on_object_click: None,
+            on_object_hover: None,
+            on_selection_changed: None,
+            on_view_changed: None,
         })
     }
 
+    /// `on_profile(spans)` is called after every loaded structure and rendered frame with a JSON list of
+    /// spans with `name`, `start` and `duration` in milliseconds of `performance.now()` and `depth` of nesting,
+    /// nested spans first. Spans are recorded only in builds with the profiling feature, see `profiling_enabled`.
+    #[wasm_bindgen]
+    pub fn set_profiling_callback(on_profile: Option<js_sys::Function>) {
+        #[cfg(target_arch = "wasm32")]
+        profiling::set_clock(performance_now);
+        profiling::set_sink(on_profile.map(|callback| -> profiling::Sink {
+            Box::new(move |spans| {
+                if let Ok(json) = serde_json::to_string(&spans) {
+                    let _ = callback.call1(&JsValue::NULL, &JsValue::from_str(&json));
+                }
+            })
+        }));
+    }
+
+    #[wasm_bindgen]
+    pub fn profiling_enabled() -> bool {
+        profiling::enabled()
+    }
+
     #[wasm_bindgen]
     pub fn resize(&mut self, width: u32, height: u32) {
         if width > 0 && height > 0 {
@@ -127,44 +252,2213 @@ impl MolecularVisualizer {
         self.scene.transform.rotate(pitch, yaw, roll);
         self.scene
             .render(&self.surface, &self.device, &self.queue, &self.visualizer_config, 0);
+        self.notify_view_changed();
     }
 
+    /// Starts rotating the molecule with the arcball at the canvas pixel, `time` is in milliseconds,
+    /// e.g. of `performance.now()`. An inertial rotation is stopped.
     #[wasm_bindgen]
-    pub fn scale_scene(&mut self, factor: f32) {
-        if factor == 1.0 || factor == 0.0 {
-            return;
-        }
-
-        self.scene.transform.scale(Vec3::new(factor, factor, factor));
+    pub fn begin_rotation(&mut self, x: f32, y: f32, time: f64) {
         self.scene
-            .render(&self.surface, &self.device, &self.queue, &self.visualizer_config, 0);
+            .begin_rotation(x, y, self.config.width, self.config.height, time);
     }
 
+    /// Rotates the molecule so that the point under the cursor follows it.
     #[wasm_bindgen]
-    pub async fn new_cursor_position(&mut self, x: u32, y: u32) -> Option<AtomInfo> {
-        let (atom, needs_render) = self.scene.new_cursor_position(x, y, &self.device, &self.queue).await;
-
-        if needs_render {
+    pub fn drag_rotation(&mut self, x: f32, y: f32, time: f64) {
+        if self.scene.drag_rotation(x, y, time) {
             self.scene
                 .render(&self.surface, &self.device, &self.queue, &self.visualizer_config, 0);
+            self.notify_view_changed();
         }
+    }
 
-        atom
+    /// Ends the rotation with the mouse. Returns `true` if it continues with inertia, then `animate`
+    /// should be called on every animation frame while it returns `true`.
+    #[wasm_bindgen]
+    pub fn end_rotation(&mut self, time: f64) -> bool {
+        self.scene.end_rotation(time)
     }
 
+    /// Continues the inertial rotation and the camera flight to focused atoms, plays the frames and
+    /// the vibration, returns `true` while the molecule is still spinning, the camera is flying, the animation
+    /// is playing or the atoms are vibrating.
     #[wasm_bindgen]
-    pub async fn toggle_atom_selection(&mut self, x: u32, y: u32) {
-        if self.scene.toggle_atom_selection(x, y, &self.device, &self.queue).await {
+    pub fn animate(&mut self, time: f64) -> bool {
+        let rotated = self.scene.step_inertia(time);
+        let flown = self.scene.step_camera_flight(time);
+        let moved = self
+            .scene
+            .advance_animation(&self.device, &self.visualizer_config, time);
+        let vibrated = self
+            .scene
+            .advance_vibration(&self.device, &self.visualizer_config, time);
+        if rotated || flown || moved || vibrated {
             self.scene
                 .render(&self.surface, &self.device, &self.queue, &self.visualizer_config, 0);
         }
+        if rotated {
+            self.notify_view_changed();
+        }
+        self.scene.is_spinning() || self.scene.is_camera_flying() || self.is_playing() || self.is_vibrating()
     }
 
+    /// Inertia of the rotation after release: `damping` is the fraction of the angular speed lost per second.
     #[wasm_bindgen]
-    pub fn render(&mut self) -> Result<(), JsValue> {
+    pub fn set_inertia(&mut self, enabled: bool, damping: f32) -> Result<(), JsValue> {
         self.scene
-            .render(&self.surface, &self.device, &self.queue, &self.visualizer_config, 0);
+            .set_inertia(enabled.then_some(damping))
+            .map_err(|e| JsValue::from_str(&e))
+    }
+
+    /// Loads coordinate sets of the structure for the animation, e.g. steps of a geometry optimization.
+    /// `data` is a JSON array of atomic coordinates with the atoms of the structure in the same order,
+    /// the first frame is shown.
+    #[wasm_bindgen]
+    pub fn load_frames(&mut self, data: Vec<u8>) -> Result<(), JsValue> {
+        let frames: Vec<AtomicCoordinates> = serde_json::from_slice(&data)
+            .map_err(|e| JsValue::from_str(&format!("Failed to deserialize frames: {e}")))?;
+        self.scene
+            .set_frames(&self.device, &self.visualizer_config, &frames)
+            .map_err(|e| JsValue::from_str(&e))?;
+        self.render()
+    }
+
+    /// Binds a value of every atom in every frame, e.g. per-atom RMSD or charges along a trajectory, to the
+    /// "property" color scheme. `data` is a JSON array with the values of the atoms for every loaded frame. Values
+    /// between frames are interpolated as positions, their range over all frames is mapped onto the colormap.
+    #[wasm_bindgen]
+    pub fn load_frame_properties(&mut self, data: Vec<u8>) -> Result<(), JsValue> {
+        let properties: Vec<Vec<f64>> = serde_json::from_slice(&data)
+            .map_err(|e| JsValue::from_str(&format!("Failed to deserialize properties: {e}")))?;
+        self.scene
+            .set_frame_properties(&self.device, &self.visualizer_config, Some(properties))
+            .map_err(|e| JsValue::from_str(&e))?;
+        self.render()
+    }
+
+    /// Unbinds the values of the frames, with the "property" color scheme atoms take the colors of their elements.
+    #[wasm_bindgen]
+    pub fn clear_frame_properties(&mut self) -> Result<(), JsValue> {
+        self.scene
+            .set_frame_properties(&self.device, &self.visualizer_config, None)
+            .map_err(|e| JsValue::from_str(&e))?;
+        self.render()
+    }
+
+    #[wasm_bindgen]
+    pub fn num_frames(&self) -> usize {
+        self.scene.animation().map_or(1, |animation| animation.num_frames())
+    }
+
+    /// Current frame (starts from 0), the fractional part is the way to the next frame.
+    #[wasm_bindgen]
+    pub fn frame(&self) -> f64 {
+        self.scene.animation().map_or(0.0, |animation| animation.frame())
+    }
+
+    /// Jumps to the frame (starts from 0), the playback is paused.
+    #[wasm_bindgen]
+    pub fn set_frame(&mut self, index: usize) -> Result<(), JsValue> {
+        self.scene
+            .animation_mut()
+            .and_then(|animation| animation.set_frame(index))
+            .map_err(|e| JsValue::from_str(&e))?;
+        self.scene.update_frame(&self.device, &self.visualizer_config);
+        self.render()
+    }
+
+    /// Moves by `delta` frames forwards or backwards, the playback is paused.
+    #[wasm_bindgen]
+    pub fn step_frame(&mut self, delta: i32) -> Result<(), JsValue> {
+        self.scene
+            .animation_mut()
+            .map_err(|e| JsValue::from_str(&e))?
+            .step(delta);
+        self.scene.update_frame(&self.device, &self.visualizer_config);
+        self.render()
+    }
+
+    /// Starts the playback, then `animate` should be called on every animation frame while it returns `true`.
+    #[wasm_bindgen]
+    pub fn play(&mut self) -> Result<(), JsValue> {
+        self.scene.animation_mut().map_err(|e| JsValue::from_str(&e))?.play();
+        Ok(())
+    }
+
+    #[wasm_bindgen]
+    pub fn pause(&mut self) {
+        if let Ok(animation) = self.scene.animation_mut() {
+            animation.pause();
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn is_playing(&self) -> bool {
+        self.scene.animation().is_some_and(|animation| animation.is_playing())
+    }
+
+    /// Frames per second.
+    #[wasm_bindgen]
+    pub fn frame_rate(&self) -> f32 {
+        self.scene
+            .animation()
+            .map_or(DEFAULT_FRAME_RATE, |animation| animation.frame_rate())
+    }
+
+    #[wasm_bindgen]
+    pub fn set_frame_rate(&mut self, frame_rate: f32) -> Result<(), JsValue> {
+        self.scene
+            .animation_mut()
+            .and_then(|animation| animation.set_frame_rate(frame_rate))
+            .map_err(|e| JsValue::from_str(&e))
+    }
+
+    /// With looping the playback starts again from the first frame, otherwise it stops at the last one.
+    #[wasm_bindgen]
+    pub fn set_looping(&mut self, looping: bool) -> Result<(), JsValue> {
+        self.scene.animation_mut().map_err(|e| JsValue::from_str(&e))?.looping = looping;
+        Ok(())
+    }
+
+    /// Loads normal modes of the structure, e.g. from a frequency calculation. `data` is JSON
+    /// `{"frequencies": [...], "displacements": [[[dx, dy, dz], ...], ...]}` with wavenumbers in cm⁻¹ and
+    /// displacements of the atoms of the structure in the same order. Frames of the animation are dropped.
+    #[wasm_bindgen]
+    pub fn load_vibrational_modes(&mut self, data: Vec<u8>) -> Result<(), JsValue> {
+        let modes: VibrationalModes = serde_json::from_slice(&data)
+            .map_err(|e| JsValue::from_str(&format!("Failed to deserialize vibrational modes: {e}")))?;
+        self.scene
+            .set_vibrational_modes(&self.device, &self.visualizer_config, &modes)
+            .map_err(|e| JsValue::from_str(&e))?;
+        self.render()
+    }
+
+    /// Wavenumbers of the loaded normal modes in cm⁻¹, imaginary frequencies are negative.
+    #[wasm_bindgen]
+    pub fn vibrational_frequencies(&self) -> Vec<f64> {
+        self.scene
+            .vibration()
+            .map_or_else(Vec::new, |vibration| vibration.frequencies().to_vec())
+    }
+
+    /// Index of the vibrating mode starting from 0.
+    #[wasm_bindgen]
+    pub fn vibration_mode(&self) -> usize {
+        self.scene.vibration().map_or(0, |vibration| vibration.mode())
+    }
+
+    /// Selects the normal mode (starts from 0), the oscillation starts from the equilibrium.
+    #[wasm_bindgen]
+    pub fn set_vibration_mode(&mut self, index: usize) -> Result<(), JsValue> {
+        self.scene
+            .vibration_mut()
+            .and_then(|vibration| vibration.set_mode(index))
+            .map_err(|e| JsValue::from_str(&e))?;
+        self.scene.update_vibration(&self.device, &self.visualizer_config);
+        self.render()
+    }
+
+    /// Largest displacement of an atom in Angstroms.
+    #[wasm_bindgen]
+    pub fn vibration_amplitude(&self) -> f32 {
+        self.scene
+            .vibration()
+            .map_or(DEFAULT_AMPLITUDE, |vibration| vibration.amplitude())
+    }
+
+    #[wasm_bindgen]
+    pub fn set_vibration_amplitude(&mut self, amplitude: f32) -> Result<(), JsValue> {
+        self.scene
+            .vibration_mut()
+            .and_then(|vibration| vibration.set_amplitude(amplitude))
+            .map_err(|e| JsValue::from_str(&e))?;
+        self.scene.update_vibration(&self.device, &self.visualizer_config);
+        self.render()
+    }
+
+    /// Oscillations per second of the animation.
+    #[wasm_bindgen]
+    pub fn vibration_frequency(&self) -> f32 {
+        self.scene
+            .vibration()
+            .map_or(DEFAULT_FREQUENCY, |vibration| vibration.frequency())
+    }
+
+    #[wasm_bindgen]
+    pub fn set_vibration_frequency(&mut self, frequency: f32) -> Result<(), JsValue> {
+        self.scene
+            .vibration_mut()
+            .and_then(|vibration| vibration.set_frequency(frequency))
+            .map_err(|e| JsValue::from_str(&e))
+    }
+
+    /// Shows the atoms at the fraction of the oscillation, 0.25 is the largest displacement along the mode.
+    #[wasm_bindgen]
+    pub fn set_vibration_phase(&mut self, phase: f64) -> Result<(), JsValue> {
+        self.scene
+            .vibration_mut()
+            .map_err(|e| JsValue::from_str(&e))?
+            .set_phase(phase);
+        self.scene.update_vibration(&self.device, &self.visualizer_config);
+        self.render()
+    }
+
+    /// Arrows along the displacements of the atoms in the selected mode.
+    #[wasm_bindgen]
+    pub fn vibration_arrows(&self) -> bool {
+        self.scene
+            .vibration()
+            .is_some_and(|vibration| vibration.arrows_visible())
+    }
+
+    #[wasm_bindgen]
+    pub fn set_vibration_arrows(&mut self, visible: bool) -> Result<(), JsValue> {
+        self.scene
+            .vibration_mut()
+            .map_err(|e| JsValue::from_str(&e))?
+            .set_arrows_visible(visible);
+        self.render()
+    }
+
+    /// Starts the oscillation, then `animate` should be called on every animation frame while it returns `true`.
+    #[wasm_bindgen]
+    pub fn play_vibration(&mut self) -> Result<(), JsValue> {
+        self.scene.vibration_mut().map_err(|e| JsValue::from_str(&e))?.play();
+        Ok(())
+    }
 
+    /// Stops the oscillation, the atoms return to the equilibrium.
+    #[wasm_bindgen]
+    pub fn stop_vibration(&mut self) -> Result<(), JsValue> {
+        if let Ok(vibration) = self.scene.vibration_mut() {
+            vibration.stop();
+            self.scene.update_vibration(&self.device, &self.visualizer_config);
+            self.render()?;
+        }
         Ok(())
     }
+
+    #[wasm_bindgen]
+    pub fn is_vibrating(&self) -> bool {
+        self.scene.vibration().is_some_and(|vibration| vibration.is_playing())
+    }
+
+    /// Torsion driver: rotates the side of `atom_2` of the bond about it by the angle in degrees, clockwise
+    /// when looking from `atom_1` to `atom_2`. Indices start from 1, bonds in rings can not be rotated.
+    #[wasm_bindgen]
+    pub fn rotate_bond(&mut self, atom_1: usize, atom_2: usize, angle: f32) -> Result<(), JsValue> {
+        self.scene
+            .rotate_bond(&self.device, &self.visualizer_config, atom_1, atom_2, angle)
+            .map_err(|e| JsValue::from_str(&e))?;
+        self.autosave.record(format!("Rotate bond {}-{}", atom_1, atom_2));
+        self.render()
+    }
+
+    /// Sets the dihedral of four atoms (indices start from 1) to the angle in degrees by rotating the side
+    /// of the third atom about the bond between the second and the third ones.
+    #[wasm_bindgen]
+    pub fn set_dihedral(&mut self, atoms: Vec<usize>, angle: f64) -> Result<(), JsValue> {
+        let atoms: [usize; 4] = atoms
+            .try_into()
+            .map_err(|_| JsValue::from_str("A dihedral is defined by 4 atoms"))?;
+        self.scene
+            .set_dihedral(&self.device, &self.visualizer_config, atoms, angle)
+            .map_err(|e| JsValue::from_str(&e))?;
+        self.autosave.record(format!(
+            "Set dihedral {}-{}-{}-{}",
+            atoms[0], atoms[1], atoms[2], atoms[3]
+        ));
+        self.render()
+    }
+
+    /// Detects the point group and turns on the symmetry-constrained editing mode: moving an atom moves all
+    /// its symmetry-equivalent atoms. `tolerance` in Angstroms defaults to the distance tolerance, see `set_tolerances`.
+    /// Returns the Schoenflies symbol.
+    #[wasm_bindgen]
+    pub fn detect_symmetry(&mut self, tolerance: Option<f64>) -> Result<String, JsValue> {
+        self.scene
+            .detect_symmetry(tolerance.unwrap_or(self.visualizer_config.tolerances.distance))
+            .map(|point_group| point_group.to_string())
+            .map_err(|e| JsValue::from_str(&e))
+    }
+
+    /// Point group of the symmetry-constrained editing mode, `None` if it is off.
+    #[wasm_bindgen]
+    pub fn point_group(&self) -> Option<String> {
+        self.scene.symmetry().map(|symmetry| symmetry.point_group().to_string())
+    }
+
+    /// JSON with the point group of the symmetry-constrained editing mode, the center in Angstroms and the symmetry
+    /// elements through it: `{"kind": "axis", "direction": [x, y, z], "order": n}` from the highest order (0 for the
+    /// axis of linear structures), `{"kind": "mirror", "normal": [x, y, z]}`, `{"kind": "inversion"}` and
+    /// `{"kind": "improper_axis", ...}`.
+    #[wasm_bindgen]
+    pub fn symmetry_elements(&self) -> Result<Vec<u8>, JsValue> {
+        let symmetry = self
+            .scene
+            .symmetry()
+            .ok_or_else(|| JsValue::from_str("Symmetry is not detected"))?;
+        let info = SymmetryInfo {
+            point_group: symmetry.point_group(),
+            center: symmetry.center(),
+            elements: symmetry.elements(),
+        };
+        serde_json::to_vec(&info).map_err(|e| JsValue::from_str(&format!("Failed to serialize symmetry: {e}")))
+    }
+
+    /// Atoms (indices start from 1) equivalent to the atom by symmetry, including the atom itself.
+    #[wasm_bindgen]
+    pub fn symmetry_equivalent_atoms(&self, index: usize) -> Result<Vec<usize>, JsValue> {
+        let symmetry = self
+            .scene
+            .symmetry()
+            .ok_or_else(|| JsValue::from_str("Symmetry is not detected"))?;
+        if index == 0 || index > self.node_data.atomic_num.len() {
+            return Err(JsValue::from_str(&format!("Atom index {} is out of range", index)));
+        }
+        Ok(symmetry.equivalent_atoms(index - 1).iter().map(|i| i + 1).collect())
+    }
+
+    #[wasm_bindgen]
+    pub fn clear_symmetry(&mut self) {
+        self.scene.clear_symmetry();
+    }
+
+    /// Moves the atom (index starts from 1) by the displacement in Angstroms, together with its
+    /// symmetry-equivalent atoms in the symmetry-constrained editing mode.
+    #[wasm_bindgen]
+    pub fn move_atom(&mut self, index: usize, dx: f64, dy: f64, dz: f64) -> Result<(), JsValue> {
+        self.scene
+            .move_atom(&self.device, &self.visualizer_config, index, Vec3::new(dx, dy, dz))
+            .map_err(|e| JsValue::from_str(&e))?;
+        self.autosave.record(format!("Move atom {}", index));
+        self.render()
+    }
+
+    /// Moves the atom (index starts from 1) in the plane of the screen by the distance in canvas pixels.
+    #[wasm_bindgen]
+    pub fn drag_atom(&mut self, index: usize, dx: f32, dy: f32) -> Result<(), JsValue> {
+        self.scene
+            .drag_atom(&self.device, &self.visualizer_config, index, dx, dy)
+            .map_err(|e| JsValue::from_str(&e))?;
+        self.autosave.record(format!("Move atom {}", index));
+        self.render()
+    }
+
+    /// Labels the atom (index starts from 1) with the mass number of an isotope, e.g. 2 for deuterium, 0 restores
+    /// the natural isotopic composition. Labelled atoms are shown as "D", "T" or e.g. "13C" and have the masses
+    /// of their isotopes in the mass calculations.
+    #[wasm_bindgen]
+    pub fn set_isotope(&mut self, index: usize, mass_number: u32) -> Result<(), JsValue> {
+        self.scene
+            .set_mass_number(index, mass_number)
+            .map_err(|e| JsValue::from_str(&e))?;
+        self.autosave.record(format!("Set isotope of atom {}", index));
+        self.render()
+    }
+
+    /// Mass numbers of the atoms, 0 for the natural isotopic composition.
+    #[wasm_bindgen]
+    pub fn isotopes(&self) -> Vec<u32> {
+        self.scene.mass_numbers()
+    }
+
+    /// Live clash check of edits: after every moved atom or rotated bond, atoms closer than `fraction`
+    /// (0.7 by default) of the sum of their van der Waals radii are drawn in a warning color, see `clashes`.
+    /// Bonded atoms and atoms bonded to the same atom never clash. The check is on by default.
+    #[wasm_bindgen]
+    pub fn set_clash_check(&mut self, enabled: bool, fraction: Option<f64>) -> Result<(), JsValue> {
+        let fraction = enabled.then(|| fraction.unwrap_or(DEFAULT_CLASH_FRACTION));
+        self.scene
+            .set_clash_check(fraction)
+            .map_err(|e| JsValue::from_str(&e))?;
+        self.render()
+    }
+
+    /// Fraction of the van der Waals radii of the live clash check, `None` if it is off.
+    #[wasm_bindgen]
+    pub fn clash_check(&self) -> Option<f64> {
+        self.scene.clash_check()
+    }
+
+    /// Clashes found after the last edit as JSON `[{"atoms": [1, 5], "distance": 0.92, "fraction": 0.38}, ...]`
+    /// with atom indices starting from 1 and the distance in Angstroms also as a fraction of the sum of
+    /// van der Waals radii.
+    #[wasm_bindgen]
+    pub fn clashes(&self) -> Result<Vec<u8>, JsValue> {
+        let clashes: Vec<ClashInfo> = self
+            .scene
+            .clashes()
+            .iter()
+            .map(|clash| ClashInfo {
+                atoms: [clash.atom_index_1 + 1, clash.atom_index_2 + 1],
+                distance: clash.distance,
+                fraction: clash.fraction,
+            })
+            .collect();
+        serde_json::to_vec(&clashes).map_err(|e| JsValue::from_str(&format!("Failed to serialize clashes: {e}")))
+    }
+
+    /// Covalent clash check: atoms closer than `fraction` (0.75 by default) of the sum of their covalent radii,
+    /// i.e. overlapping each other, e.g. after a bad edit or in a structure with disordered positions, are drawn
+    /// with orange warning halos and listed in the `report`, see `covalent_clashes`. Unlike the live clash check,
+    /// bonded atoms are included and the structure is checked after every change. The check is on by default.
+    #[wasm_bindgen]
+    pub fn set_covalent_clash_check(&mut self, enabled: bool, fraction: Option<f64>) -> Result<(), JsValue> {
+        let fraction = enabled.then(|| fraction.unwrap_or(DEFAULT_COVALENT_CLASH_FRACTION));
+        self.scene
+            .set_covalent_clash_check(fraction)
+            .map_err(|e| JsValue::from_str(&e))?;
+        self.render()
+    }
+
+    /// Fraction of the covalent radii of the covalent clash check, `None` if it is off.
+    #[wasm_bindgen]
+    pub fn covalent_clash_check(&self) -> Option<f64> {
+        self.scene.covalent_clash_check()
+    }
+
+    /// Clashes of the current structure found by the covalent clash check as JSON in the format of `clashes`,
+    /// the fraction is of the sum of covalent radii.
+    #[wasm_bindgen]
+    pub fn covalent_clashes(&mut self) -> Result<Vec<u8>, JsValue> {
+        let clashes: Vec<ClashInfo> = self
+            .scene
+            .covalent_clashes()
+            .iter()
+            .map(|clash| ClashInfo {
+                atoms: [clash.atom_index_1 + 1, clash.atom_index_2 + 1],
+                distance: clash.distance,
+                fraction: clash.fraction,
+            })
+            .collect();
+        serde_json::to_vec(&clashes).map_err(|e| JsValue::from_str(&format!("Failed to serialize clashes: {e}")))
+    }
+
+    /// Parameters of the periodic cell: lengths a, b, c in Angstroms and angles α, β, γ in degrees.
+    #[wasm_bindgen]
+    pub fn cell_parameters(&self) -> Option<Vec<f64>> {
+        self.scene.cell().map(|cell| cell.parameters().to_vec())
+    }
+
+    /// Lattice vectors of the periodic cell in Angstroms, `[ax, ay, az, bx, by, bz, cx, cy, cz]`.
+    #[wasm_bindgen]
+    pub fn cell_vectors(&self) -> Option<Vec<f64>> {
+        self.scene.cell().map(|cell| cell.vectors.concat())
+    }
+
+    /// Sets the periodic cell from `[a, b, c, α, β, γ]` with lengths in Angstroms and angles in degrees,
+    /// vector a is along x and b is in the xy plane. With `scale_atoms` the atoms keep their fractional
+    /// coordinates, otherwise their Cartesian positions.
+    #[wasm_bindgen]
+    pub fn set_cell_parameters(&mut self, parameters: Vec<f64>, scale_atoms: bool) -> Result<(), JsValue> {
+        let parameters: [f64; 6] = parameters
+            .try_into()
+            .map_err(|_| JsValue::from_str("A cell is defined by 6 parameters"))?;
+        let cell = UnitCell::from_parameters(parameters).map_err(|e| JsValue::from_str(&e))?;
+        self.set_cell(cell, scale_atoms)
+    }
+
+    /// Sets the periodic cell from lattice vectors `[ax, ay, az, bx, by, bz, cx, cy, cz]` in Angstroms,
+    /// see `set_cell_parameters`.
+    #[wasm_bindgen]
+    pub fn set_cell_vectors(&mut self, vectors: Vec<f64>, scale_atoms: bool) -> Result<(), JsValue> {
+        let [ax, ay, az, bx, by, bz, cx, cy, cz]: [f64; 9] = vectors
+            .try_into()
+            .map_err(|_| JsValue::from_str("A cell is defined by 9 components of the lattice vectors"))?;
+        let cell = UnitCell::new([[ax, ay, az], [bx, by, bz], [cx, cy, cz]]).map_err(|e| JsValue::from_str(&e))?;
+        self.set_cell(cell, scale_atoms)
+    }
+
+    fn set_cell(&mut self, cell: UnitCell, scale_atoms: bool) -> Result<(), JsValue> {
+        self.scene
+            .set_cell(&self.device, &self.visualizer_config, cell, scale_atoms)
+            .map_err(|e| JsValue::from_str(&e))?;
+        self.autosave.record("Change cell".to_string());
+        self.render()
+    }
+
+    /// Numbers of copies of the periodic cell along the lattice vectors, `[na, nb, nc]`.
+    #[wasm_bindgen]
+    pub fn supercell(&self) -> Vec<u32> {
+        self.scene.supercell().to_vec()
+    }
+
+    /// Replicates the structure into a supercell of `na × nb × nc` periodic cells, from 1 to 10 along each
+    /// lattice vector. The copies are drawn but cannot be picked.
+    #[wasm_bindgen]
+    pub fn set_supercell(&mut self, na: u32, nb: u32, nc: u32) -> Result<(), JsValue> {
+        self.scene
+            .set_supercell([na, nb, nc])
+            .map_err(|e| JsValue::from_str(&e))?;
+        self.render()
+    }
+
+    /// The structure with the current positions of the atoms and the current cell as JSON atomic coordinates,
+    /// e.g. to save a conformer after rotating bonds.
+    #[wasm_bindgen]
+    pub fn coordinates(&self) -> Result<Vec<u8>, JsValue> {
+        serde_json::to_vec(&self.current_coordinates())
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize coordinates: {e}")))
+    }
+
+    /// Formats of the structure export as JSON `[{"name": "xyz", "extension": "xyz", "multi_frame": true}, ...]`,
+    /// formats without `multi_frame` take only the current frame.
+    #[wasm_bindgen]
+    pub fn export_formats() -> Result<Vec<u8>, JsValue> {
+        serde_json::to_vec(&structure_export::formats())
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize export formats: {e}")))
+    }
+
+    /// Content of the file with the displayed structure in the export format, e.g. "xyz" or "pdb", to take
+    /// a frame of a trajectory for further calculations. Optional JSON options are those of the exporter,
+    /// e.g. `{"xyz": {"precision": 10}}`.
+    #[wasm_bindgen]
+    pub fn export_frame(&self, format: &str, options: Option<String>) -> Result<String, JsValue> {
+        let frame = self.frame();
+        let name = if frame.fract() == 0.0 {
+            format!("Frame {}", frame as usize + 1)
+        } else {
+            format!("Frame {:.2}", frame + 1.0)
+        };
+        structure_export::export_structures(&[(name, self.current_coordinates())], format, options.as_deref())
+            .map_err(|e| JsValue::from_str(&e))
+    }
+
+    /// Content of the file with all loaded frames in the export format, which must hold a sequence of structures,
+    /// e.g. "xyz" or "pdb". Without frames it is the displayed structure. Optional JSON options are those of
+    /// the exporter.
+    #[wasm_bindgen]
+    pub fn export_frames(&self, format: &str, options: Option<String>) -> Result<String, JsValue> {
+        let current = self.current_coordinates();
+        let frames: Vec<(String, AtomicCoordinates)> = self
+            .scene
+            .frame_coordinates()
+            .into_iter()
+            .enumerate()
+            .map(|(index, positions)| {
+                let coordinates = AtomicCoordinates {
+                    atomic_num: current.atomic_num.clone(),
+                    x: positions.iter().map(|p| p[0]).collect(),
+                    y: positions.iter().map(|p| p[1]).collect(),
+                    z: positions.iter().map(|p| p[2]).collect(),
+                    connectivity: current.connectivity.clone(),
+                    charges: current.charges.clone(),
+                    chains: current.chains.clone(),
+                    cell: current.cell,
+                    isotopes: current.isotopes.clone(),
+                };
+                (format!("Frame {}", index + 1), coordinates)
+            })
+            .collect();
+        structure_export::export_structures(&frames, format, options.as_deref()).map_err(|e| JsValue::from_str(&e))
+    }
+
+    /// Built-in templates as JSON `[{"name": "methyl", "category": "functional_group", "num_atoms": 5,
+    /// "attachable": true}, ...]`, the attachable ones for `attach_template`.
+    #[wasm_bindgen]
+    pub fn templates() -> Result<Vec<u8>, JsValue> {
+        let infos: Vec<templates::TemplateInfo> = templates::templates().iter().map(|t| t.info()).collect();
+        serde_json::to_vec(&infos).map_err(|e| JsValue::from_str(&format!("Failed to serialize templates: {e}")))
+    }
+
+    /// Attaches the built-in template (a functional group such as "methyl" or "phenyl", or a ligand such as
+    /// "pyridine") to the atom (index starts from 1) at a standard geometry: a terminal hydrogen or dummy atom is
+    /// replaced by the template, any other atom gets the template bonded to it. A ligand is bonded through its
+    /// donor atom. Atoms of the template follow the existing ones. The view is kept, while the selection, custom
+    /// labels and planes are cleared. Returns the new number of atoms.
+    #[wasm_bindgen]
+    pub async fn attach_template(&mut self, index: usize, name: String) -> Result<usize, JsValue> {
+        let num_atoms = self.node_data.atomic_num.len();
+        if index == 0 || index > num_atoms {
+            return Err(JsValue::from_str(&format!("Atom index {} is out of range", index)));
+        }
+        let template = templates::find_template(&name).map_err(|e| JsValue::from_str(&e))?;
+        let data =
+            templates::attach(&self.current_coordinates(), index - 1, template).map_err(|e| JsValue::from_str(&e))?;
+        let first = data.atomic_num.len() - template.num_attached_atoms();
+        let event = EditEvent::AttachTemplate {
+            atom: index,
+            template: template.name,
+            replaced: first < num_atoms,
+            elements: data.atomic_num[first..]
+                .iter()
+                .map(|&number| periodic_table::element_symbol(number))
+                .collect(),
+            positions: (first..data.atomic_num.len())
+                .map(|i| [data.x[i], data.y[i], data.z[i]])
+                .collect(),
+        };
+        self.apply_edit(data, format!("Attach {} to atom {}", template.name, index), event)
+            .await?;
+        Ok(self.node_data.atomic_num.len())
+    }
+
+    /// Attachable template (e.g. "methyl") attached to the atoms clicked in the builder mode, see
+    /// `attach_template`. `None` returns to bonding the clicked atoms.
+    #[wasm_bindgen]
+    pub fn set_builder_template(&mut self, name: Option<String>) -> Result<(), JsValue> {
+        self.builder_template = match name {
+            Some(name) => {
+                let template = templates::find_template(&name).map_err(|e| JsValue::from_str(&e))?;
+                if !template.is_attachable() {
+                    return Err(JsValue::from_str(&format!(
+                        "Template {} cannot be attached to an atom",
+                        template.name
+                    )));
+                }
+                Some(template)
+            }
+            None => None,
+        };
+        Ok(())
+    }
+
+    /// Template attached to the atoms clicked in the builder mode, `None` if the clicked atoms are bonded.
+    #[wasm_bindgen]
+    pub fn builder_template(&self) -> Option<String> {
+        self.builder_template.map(|template| template.name.to_string())
+    }
+
+    /// Turns on the builder mode, in which `builder_click` edits the structure, with the element (e.g. "C") of the
+    /// atoms added by clicks on the background. `None` turns the mode off. The selection is cleared either way.
+    #[wasm_bindgen]
+    pub fn set_builder_mode(&mut self, element: Option<String>) -> Result<(), JsValue> {
+        self.builder_element = match element {
+            Some(symbol) => Some(
+                periodic_table::get_element_by_symbol(&symbol)
+                    .ok_or_else(|| JsValue::from_str(&format!("Unknown element {}", symbol)))?
+                    .atomic_number,
+            ),
+            None => None,
+        };
+        self.clear_selection()
+    }
+
+    /// Element of the atoms added in the builder mode, `None` if the mode is off.
+    #[wasm_bindgen]
+    pub fn builder_mode(&self) -> Option<String> {
+        self.builder_element
+            .map(|number| periodic_table::element_symbol(number).to_string())
+    }
+
+    /// Click at the canvas pixel (x, y) in the builder mode: a click on the background adds an atom of the
+    /// builder element there, at the depth of the center of the structure. With a builder template the clicked
+    /// atom gets the template attached. Otherwise the first clicked atom is selected, and clicking another atom
+    /// then bonds the two atoms or removes their bond. Clicking the selected atom again deselects it.
+    #[wasm_bindgen]
+    pub async fn builder_click(&mut self, x: u32, y: u32) -> Result<(), JsValue> {
+        let Some(number) = self.builder_element else {
+            return Err(JsValue::from_str("The builder mode is off"));
+        };
+        let atom = match self.scene.pick_object(x, y, &self.device, &self.queue).await {
+            Some(PickedObject::Atom(i) | PickedObject::Label(i)) => i + 1,
+            Some(_) => return Ok(()),
+            None => {
+                let position = self
+                    .scene
+                    .screen_point(x as f32 + 0.5, y as f32 + 0.5)
+                    .ok_or_else(|| JsValue::from_str("No structure is loaded"))?;
+                self.clear_builder_selection();
+                let symbol = periodic_table::element_symbol(number).to_string();
+                self.add_atom(symbol, position[0], position[1], position[2]).await?;
+                return Ok(());
+            }
+        };
+        if let Some(template) = self.builder_template {
+            self.clear_builder_selection();
+            self.attach_template(atom, template.name.to_string()).await?;
+            return Ok(());
+        }
+        match self.scene.selected_atoms()[..] {
+            [first] if first != atom => {
+                self.clear_builder_selection();
+                self.toggle_bond(first, atom, None).await?;
+            }
+            [first] if first == atom => {
+                self.clear_builder_selection();
+                self.render()?;
+            }
+            _ => {
+                self.scene.clear_selection();
+                self.scene.select_atoms(&[atom]).map_err(|e| JsValue::from_str(&e))?;
+                self.notify_selection_changed();
+                self.render()?;
+            }
+        }
+        Ok(())
+    }
+
+    // Clears the atom selected by the first click of a bond in the builder mode
+    fn clear_builder_selection(&mut self) {
+        if self.scene.clear_selection() {
+            self.notify_selection_changed();
+        }
+    }
+
+    /// Adds an atom of the element (e.g. "C") at the position in Angstroms after the existing atoms. Returns its
+    /// index (starts from 1).
+    #[wasm_bindgen]
+    pub async fn add_atom(&mut self, element: String, x: f64, y: f64, z: f64) -> Result<usize, JsValue> {
+        let number = periodic_table::get_element_by_symbol(&element)
+            .ok_or_else(|| JsValue::from_str(&format!("Unknown element {}", element)))?
+            .atomic_number;
+        let data =
+            editing::add_atom(&self.current_coordinates(), number, [x, y, z]).map_err(|e| JsValue::from_str(&e))?;
+        let atom = data.atomic_num.len();
+        let event = EditEvent::AddAtom {
+            atom,
+            element: periodic_table::element_symbol(number),
+            position: [x, y, z],
+        };
+        self.apply_edit(data, format!("Add atom {}", atom), event).await?;
+        Ok(atom)
+    }
+
+    /// Deletes the atoms (indices start from 1) with their bonds, the following atoms move up. Returns the new
+    /// number of atoms.
+    #[wasm_bindgen]
+    pub async fn delete_atoms(&mut self, indices: Vec<usize>) -> Result<usize, JsValue> {
+        let mut atoms = indices;
+        atoms.sort_unstable();
+        atoms.dedup();
+        if let Some(&index) = atoms.iter().find(|&&i| i == 0 || i > self.node_data.atomic_num.len()) {
+            return Err(JsValue::from_str(&format!("Atom index {} is out of range", index)));
+        }
+        let zero_based: Vec<usize> = atoms.iter().map(|i| i - 1).collect();
+        let data =
+            editing::delete_atoms(&self.current_coordinates(), &zero_based).map_err(|e| JsValue::from_str(&e))?;
+        let description = match atoms[..] {
+            [atom] => format!("Delete atom {}", atom),
+            _ => format!("Delete {} atoms", atoms.len()),
+        };
+        self.apply_edit(data, description, EditEvent::DeleteAtoms { atoms })
+            .await?;
+        Ok(self.node_data.atomic_num.len())
+    }
+
+    /// Bonds the atoms (indices start from 1) with the order (1 – single by default, 2 – double, 3 – triple,
+    /// 4 – aromatic) or removes their bond if they are bonded. The bonds perceived from the distances become
+    /// the bonds of the structure. Returns true if the bond was added.
+    #[wasm_bindgen]
+    pub async fn toggle_bond(&mut self, atom_1: usize, atom_2: usize, order: Option<u8>) -> Result<bool, JsValue> {
+        let num_atoms = self.node_data.atomic_num.len();
+        if let Some(index) = [atom_1, atom_2].into_iter().find(|&i| i == 0 || i > num_atoms) {
+            return Err(JsValue::from_str(&format!("Atom index {} is out of range", index)));
+        }
+        let order = order.unwrap_or(1);
+        let (data, added) = editing::toggle_bond(
+            &self.current_coordinates(),
+            self.scene.bonds(),
+            atom_1 - 1,
+            atom_2 - 1,
+            order,
+        )
+        .map_err(|e| JsValue::from_str(&e))?;
+        let atoms = [atom_1, atom_2];
+        let (description, event) = if added {
+            (
+                format!("Bond atoms {} and {}", atom_1, atom_2),
+                EditEvent::AddBond { atoms, order },
+            )
+        } else {
+            (
+                format!("Remove bond {}-{}", atom_1, atom_2),
+                EditEvent::RemoveBond { atoms },
+            )
+        };
+        self.apply_edit(data, description, event).await?;
+        Ok(added)
+    }
+
+    /// Adds the hydrogens missing from the structure, inferred from the default valences of the elements and
+    /// the bonds of the atoms (see `hydrogens::add_hydrogens`). The hydrogens follow the existing atoms. Returns
+    /// the number of added hydrogens, the structure is not edited if there are none.
+    #[wasm_bindgen]
+    pub async fn add_hydrogens(&mut self) -> Result<usize, JsValue> {
+        let num_atoms = self.node_data.atomic_num.len();
+        let data = hydrogens::add_hydrogens(&self.current_coordinates(), self.scene.bonds())
+            .map_err(|e| JsValue::from_str(&e))?;
+        let added = data.atomic_num.len() - num_atoms;
+        if added > 0 {
+            let positions = (num_atoms..data.atomic_num.len())
+                .map(|i| [data.x[i], data.y[i], data.z[i]])
+                .collect();
+            self.apply_edit(
+                data,
+                format!("Add {} hydrogens", added),
+                EditEvent::AddHydrogens { positions },
+            )
+            .await?;
+        }
+        Ok(added)
+    }
+
+    /// Deletes the hydrogens bonded to atoms of the elements that get hydrogens from `add_hydrogens`, keeping
+    /// e.g. hydrides of metals. Returns the number of deleted hydrogens, the structure is not edited if there
+    /// are none.
+    #[wasm_bindgen]
+    pub async fn strip_hydrogens(&mut self) -> Result<usize, JsValue> {
+        let data = self.current_coordinates();
+        let hydrogens = hydrogens::terminal_hydrogens(&data, self.scene.bonds()).map_err(|e| JsValue::from_str(&e))?;
+        if hydrogens.is_empty() {
+            return Ok(0);
+        }
+        let stripped = editing::delete_atoms(&data, &hydrogens).map_err(|e| JsValue::from_str(&e))?;
+        let atoms: Vec<usize> = hydrogens.iter().map(|i| i + 1).collect();
+        let deleted = atoms.len();
+        self.apply_edit(
+            stripped,
+            format!("Strip {} hydrogens", deleted),
+            EditEvent::DeleteAtoms { atoms },
+        )
+        .await?;
+        Ok(deleted)
+    }
+
+    /// `on_edit(event)` is called after every structural edit with an object describing it, atom
+    /// indices start from 1:
+    /// `{"kind": "add_atom", "atom": 7, "element": "C", "position": [x, y, z]}`,
+    /// `{"kind": "delete_atoms", "atoms": [2, 5]}`, `{"kind": "add_bond", "atoms": [1, 7], "order": 1}` or
+    /// `{"kind": "remove_bond", "atoms": [1, 7]}` or, for `attach_template`,
+    /// `{"kind": "attach_template", "atom": 4, "template": "methyl", "replaced": true, "elements": ["C", ...],
+    /// "positions": [[x, y, z], ...]}`, where a replaced atom is deleted before the atoms of the template are
+    /// added, or `{"kind": "add_hydrogens", "positions": [[x, y, z], ...]}`. Applied in order to the structure they give the edited one, e.g. to keep the host and the Cartesian
+    /// editor in sync.
+    #[wasm_bindgen]
+    pub fn set_edit_callback(&mut self, on_edit: Option<js_sys::Function>) {
+        self.on_edit = on_edit;
+    }
+
+    // Shows the structure after a structural edit, which is recorded and reported to the host. The view is kept,
+    // while the selection, custom labels and planes are cleared.
+    async fn apply_edit(
+        &mut self,
+        data: AtomicCoordinates,
+        description: String,
+        event: EditEvent,
+    ) -> Result<(), JsValue> {
+        self.scene
+            .replace_atomic_coordinates(&self.device, &self.visualizer_config, &data)
+            .await
+            .map_err(|e| JsValue::from_str(&e))?;
+        self.node_data = data;
+        self.autosave.record(description);
+        if let Some(callback) = &self.on_edit {
+            let json = serde_json::to_string(&event)
+                .map_err(|e| JsValue::from_str(&format!("Failed to serialize edit: {e}")))?;
+            let _ = callback.call1(&JsValue::NULL, &js_sys::JSON::parse(&json)?);
+        }
+        self.render()
+    }
+
+    /// Key of the autosaved edits in the host storage, the hash of the structure as it was opened (or last saved).
+    #[wasm_bindgen]
+    pub fn autosave_key(&self) -> String {
+        self.autosave.key().to_string()
+    }
+
+    /// Whether the structure has been edited since it was opened or marked as saved.
+    #[wasm_bindgen]
+    pub fn has_unsaved_edits(&self) -> bool {
+        self.autosave.has_edits()
+    }
+
+    /// Summary of the unsaved edits, the oldest first, e.g. "Move atom 3 (×25)" for dragging an atom.
+    #[wasm_bindgen]
+    pub fn edit_history(&self) -> Vec<String> {
+        self.autosave
+            .edits()
+            .iter()
+            .map(|edit| match edit.count {
+                1 => edit.description.clone(),
+                count => format!("{} (×{})", edit.description, count),
+            })
+            .collect()
+    }
+
+    /// `on_autosave(key, snapshot)` stores the JSON snapshot of the unsaved edits under the key until the edits
+    /// are saved, replacing the previous one. It is called by `autosave`.
+    #[wasm_bindgen]
+    pub fn set_autosave_callback(&mut self, on_autosave: Option<js_sys::Function>) {
+        self.on_autosave = on_autosave;
+    }
+
+    /// Passes a snapshot of the edited structure and the edit history to the autosave callback if there are
+    /// edits since the last snapshot, e.g. on a timer and when the page is hidden. `time` is in milliseconds
+    /// since the Unix epoch (`Date.now()`). Returns true if a snapshot was taken.
+    #[wasm_bindgen]
+    pub fn autosave(&mut self, time: f64) -> Result<bool, JsValue> {
+        let Some(callback) = &self.on_autosave else {
+            return Ok(false);
+        };
+        if !self.autosave.is_dirty() {
+            return Ok(false);
+        }
+        let snapshot = self
+            .autosave
+            .snapshot(self.current_coordinates(), time)
+            .map_err(|e| JsValue::from_str(&e))?;
+        callback.call2(
+            &JsValue::NULL,
+            &JsValue::from_str(self.autosave.key()),
+            &JsValue::from_str(&snapshot),
+        )?;
+        Ok(true)
+    }
+
+    /// Restores the edited structure and its edit history from a snapshot stored by the autosave callback,
+    /// e.g. after the page was reloaded. The snapshot must be taken from the same structure. The view is kept,
+    /// while the selection, custom labels and planes are cleared.
+    #[wasm_bindgen]
+    pub async fn restore_autosave(&mut self, snapshot: String) -> Result<(), JsValue> {
+        let data = self.autosave.restore(&snapshot).map_err(|e| JsValue::from_str(&e))?;
+        self.scene
+            .replace_atomic_coordinates(&self.device, &self.visualizer_config, &data)
+            .await
+            .map_err(|e| JsValue::from_str(&e))?;
+        self.node_data = data;
+        self.render()
+    }
+
+    /// Forgets the unsaved edits once the host has saved the current structure, which becomes the original one
+    /// with a new autosave key. The host removes the snapshot stored under the old key.
+    #[wasm_bindgen]
+    pub fn mark_saved(&mut self) {
+        let saved = self.current_coordinates();
+        self.autosave.reset(&saved);
+    }
+
+    /// Sets the tolerances of structure comparisons from a profile name ("loose", "normal" or "strict") or JSON
+    /// changing any of "distance", "rmsd" (Angstroms) and "convergence" of the normal profile.
+    #[wasm_bindgen]
+    pub fn set_tolerances(&mut self, settings: &str) -> Result<(), JsValue> {
+        let tolerances = if settings.trim_start().starts_with('{') {
+            serde_json::from_str(settings)
+                .map_err(|e| JsValue::from_str(&format!("Failed to parse tolerances: {e}")))?
+        } else {
+            ToleranceProfile::from_name(settings)
+                .map_err(|e| JsValue::from_str(&e))?
+                .tolerances()
+        };
+        tolerances.validate().map_err(|e| JsValue::from_str(&e))?;
+        self.visualizer_config.tolerances = tolerances;
+        Ok(())
+    }
+
+    /// Tolerances of structure comparisons as JSON `{"distance": 0.1, "rmsd": 0.1, "convergence": 1e-6}`.
+    #[wasm_bindgen]
+    pub fn tolerances(&self) -> Result<Vec<u8>, JsValue> {
+        serde_json::to_vec(&self.visualizer_config.tolerances)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize tolerances: {e}")))
+    }
+
+    /// Atoms (indices start from 1) moved by more than the distance tolerance since the structure was opened,
+    /// restored or had a template attached, e.g. to highlight dragged atoms.
+    #[wasm_bindgen]
+    pub fn moved_atoms(&self) -> Result<Vec<usize>, JsValue> {
+        comparison::moved_atoms(
+            &self.node_data,
+            &self.current_coordinates(),
+            &self.visualizer_config.tolerances,
+        )
+        .map(|atoms| atoms.into_iter().map(|i| i + 1).collect())
+        .map_err(|e| JsValue::from_str(&e))
+    }
+
+    fn current_coordinates(&self) -> AtomicCoordinates {
+        let positions = self.scene.atom_coordinates();
+        AtomicCoordinates {
+            atomic_num: self.node_data.atomic_num.clone(),
+            x: positions.iter().map(|p| p[0]).collect(),
+            y: positions.iter().map(|p| p[1]).collect(),
+            z: positions.iter().map(|p| p[2]).collect(),
+            connectivity: self.node_data.connectivity.clone(),
+            charges: self.node_data.charges.clone(),
+            chains: self.node_data.chains.clone(),
+            cell: self.scene.cell().copied(),
+            isotopes: Some(self.scene.mass_numbers()).filter(|isotopes| isotopes.iter().any(|&n| n != 0)),
+        }
+    }
+
+    /// Simulated gas electron diffraction curves of the current structure as JSON
+    /// `{"s": [...], "sm": [...], "r": [...], "radial_distribution": [...]}`, e.g. to plot the model against
+    /// the experiment. Optional JSON settings may change any of "s_min", "s_max", "s_step" (Å⁻¹), "amplitude",
+    /// "r_max", "r_step" (Å) and "damping" (Å²).
+    #[wasm_bindgen]
+    pub fn diffraction_curves(&self, settings: Option<String>) -> Result<Vec<u8>, JsValue> {
+        let settings: DiffractionSettings = match settings {
+            Some(json) => serde_json::from_str(&json)
+                .map_err(|e| JsValue::from_str(&format!("Failed to parse diffraction settings: {e}")))?,
+            None => DiffractionSettings::default(),
+        };
+        let curves =
+            diffraction::simulate(&self.current_coordinates(), &settings).map_err(|e| JsValue::from_str(&e))?;
+        serde_json::to_vec(&curves)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize diffraction curves: {e}")))
+    }
+
+    /// Simulated powder X-ray diffraction pattern of the current periodic structure as JSON
+    /// `{"two_theta": [...], "intensity": [...], "reflections": [{"hkl": [h, k, l], "multiplicity": 6, "d": 2.82,
+    /// "two_theta": 31.7, "intensity": 100}, ...]}`, e.g. to compare an imported crystal with a measured pattern.
+    /// Optional JSON settings may change any of "wavelength" (Å), "two_theta_min", "two_theta_max",
+    /// "two_theta_step", "fwhm" (degrees) and "b_factor" (Å²).
+    #[wasm_bindgen]
+    pub fn powder_pattern(&self, settings: Option<String>) -> Result<Vec<u8>, JsValue> {
+        let settings: PowderSettings = match settings {
+            Some(json) => serde_json::from_str(&json)
+                .map_err(|e| JsValue::from_str(&format!("Failed to parse powder pattern settings: {e}")))?,
+            None => PowderSettings::default(),
+        };
+        let pattern = powder::simulate(&self.current_coordinates(), &settings).map_err(|e| JsValue::from_str(&e))?;
+        serde_json::to_vec(&pattern).map_err(|e| JsValue::from_str(&format!("Failed to serialize powder pattern: {e}")))
+    }
+
+    /// Isotopic distribution of the molecular ion of the current structure as a stick spectrum in JSON
+    /// `{"formula": "CO2", "monoisotopic_mz": 43.99, "peaks": [{"mz": 43.99, "abundance": 100}, ...]}`.
+    /// Optional JSON settings may change any of "charge", "resolution" (Da) and "min_abundance" (percent).
+    #[wasm_bindgen]
+    pub fn isotope_pattern(&self, settings: Option<String>) -> Result<Vec<u8>, JsValue> {
+        let settings: IsotopeSettings = match settings {
+            Some(json) => serde_json::from_str(&json)
+                .map_err(|e| JsValue::from_str(&format!("Failed to parse isotope pattern settings: {e}")))?,
+            None => IsotopeSettings::default(),
+        };
+        let pattern =
+            mass_spectrum::simulate(&self.current_coordinates(), &settings).map_err(|e| JsValue::from_str(&e))?;
+        serde_json::to_vec(&pattern)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize isotope pattern: {e}")))
+    }
+
+    /// Force-field atom type of every atom of the current structure with its current bonds, in the "uff" (default)
+    /// or "gaff" naming, e.g. to show them as custom labels.
+    #[wasm_bindgen]
+    pub fn atom_types(&self, scheme: Option<String>) -> Result<Vec<String>, JsValue> {
+        let scheme = match scheme {
+            Some(name) => TypingScheme::from_name(&name).map_err(|e| JsValue::from_str(&e))?,
+            None => TypingScheme::default(),
+        };
+        atom_types::perceive(&self.current_coordinates(), self.scene.bonds(), scheme).map_err(|e| JsValue::from_str(&e))
+    }
+
+    /// Energy terms of the simple force field for the current geometry as JSON `{"bond": 0.4, "angle": 1.2,
+    /// "torsion": 0.3, "vdw": -0.8, "total": 1.1, "clashes": 0}` in kcal/mol. The force field is set up for
+    /// the structure as it was opened, restored or had a template attached, so the terms show the strain
+    /// introduced by later edits.
+    #[wasm_bindgen]
+    pub fn energy_terms(&self) -> Result<Vec<u8>, JsValue> {
+        let force_field = ForceField::new(&self.node_data, self.scene.bonds()).map_err(|e| JsValue::from_str(&e))?;
+        let terms = force_field
+            .energy_terms(&self.current_coordinates())
+            .map_err(|e| JsValue::from_str(&e))?;
+        serde_json::to_vec(&terms).map_err(|e| JsValue::from_str(&format!("Failed to serialize energy terms: {e}")))
+    }
+
+    /// Pairs of clashing atoms (indices start from 1, two per pair) that did not clash when the structure was
+    /// opened, restored or had a template attached, e.g. to flag atoms dragged onto others.
+    #[wasm_bindgen]
+    pub fn new_clashes(&self) -> Vec<usize> {
+        let key = |clash: &Clash| (clash.atom_index_1, clash.atom_index_2, clash.image);
+        let bonds = self.scene.bonds();
+        let before: HashSet<_> = contacts::find_clashes(&self.node_data, bonds, DEFAULT_CLASH_FRACTION)
+            .iter()
+            .map(key)
+            .collect();
+        contacts::find_clashes(&self.current_coordinates(), bonds, DEFAULT_CLASH_FRACTION)
+            .iter()
+            .filter(|clash| !before.contains(&key(clash)))
+            .flat_map(|clash| [clash.atom_index_1 + 1, clash.atom_index_2 + 1])
+            .collect()
+    }
+
+    /// Renders the current view offscreen at the size in pixels, e.g. for publication-quality figures, and
+    /// returns a PNG file. Every pixel averages `supersampling` × `supersampling` rendered ones (2 by default,
+    /// up to 4). With the transparent background the colors are premultiplied by alpha. The canvas is not changed.
+    #[wasm_bindgen]
+    pub async fn capture_image(
+        &mut self,
+        width: u32,
+        height: u32,
+        supersampling: Option<u32>,
+    ) -> Result<Vec<u8>, JsValue> {
+        self.scene
+            .capture_image(
+                &self.device,
+                &self.queue,
+                &self.visualizer_config,
+                &self.config,
+                width,
+                height,
+                supersampling.unwrap_or(DEFAULT_SUPERSAMPLING),
+            )
+            .await
+            .map_err(|e| JsValue::from_str(&e))
+    }
+
+    /// Printable summary of the current structure as "markdown" or "html": an image of the current view, the formula
+    /// and the molecular weight, the point group, rotational constants, bond lengths and angles, the atoms of the
+    /// covalent clash check and the vibrational frequencies if modes are loaded. The image of `width` × `height`
+    /// pixels (800 × 600 by default) is embedded as a data URL, so the report is a single document. The title
+    /// defaults to the formula.
+    #[wasm_bindgen]
+    pub async fn report(
+        &mut self,
+        format: String,
+        title: Option<String>,
+        width: Option<u32>,
+        height: Option<u32>,
+    ) -> Result<String, JsValue> {
+        let format = ReportFormat::from_name(&format).map_err(|e| JsValue::from_str(&e))?;
+        let data = self.current_coordinates();
+        let mut report = Report::new(&data, self.scene.bonds(), &self.visualizer_config.tolerances);
+        report.add_clashes(&data, self.scene.covalent_clashes());
+        if let Some(title) = title {
+            report.title = title;
+        }
+        if let Some(vibration) = self.scene.vibration() {
+            report.frequencies = vibration.frequencies().to_vec();
+        }
+        let image = self
+            .scene
+            .capture_image(
+                &self.device,
+                &self.queue,
+                &self.visualizer_config,
+                &self.config,
+                width.unwrap_or(DEFAULT_IMAGE_WIDTH),
+                height.unwrap_or(DEFAULT_IMAGE_HEIGHT),
+                DEFAULT_SUPERSAMPLING,
+            )
+            .await
+            .map_err(|e| JsValue::from_str(&e))?;
+        report.image = Some(image);
+        Ok(report.render(format))
+    }
+
+    #[wasm_bindgen]
+    pub fn scale_scene(&mut self, factor: f32) {
+        if factor == 1.0 || factor == 0.0 {
+            return;
+        }
+
+        self.scene.transform.scale(Vec3::new(factor, factor, factor));
+        self.scene
+            .render(&self.surface, &self.device, &self.queue, &self.visualizer_config, 0);
+        self.notify_view_changed();
+    }
+
+    /// Turns the camera around its target (the center of the molecule unless panned) by `yaw` degrees about
+    /// the vertical axis and raises it by `pitch` degrees, up to the pitch limit. Unlike `rotate_scene` the
+    /// molecule keeps its orientation, so the view of linked visualizers does not change.
+    #[wasm_bindgen]
+    pub fn orbit_camera(&mut self, yaw: f32, pitch: f32) {
+        self.scene.orbit_camera(yaw, pitch);
+        self.scene
+            .render(&self.surface, &self.device, &self.queue, &self.visualizer_config, 0);
+    }
+
+    /// Moves the camera in the view plane so that the molecule follows the mouse moved by `dx`, `dy`
+    /// canvas pixels.
+    #[wasm_bindgen]
+    pub fn pan_camera(&mut self, dx: f32, dy: f32) {
+        self.scene.pan_camera(dx, dy, self.config.width, self.config.height);
+        self.scene
+            .render(&self.surface, &self.device, &self.queue, &self.visualizer_config, 0);
+    }
+
+    /// Moves the camera towards its target, dividing the distance by `factor` (above 1 zooms in) within
+    /// the distance limits.
+    #[wasm_bindgen]
+    pub fn dolly_camera(&mut self, factor: f32) {
+        self.scene.dolly_camera(factor);
+        self.scene
+            .render(&self.surface, &self.device, &self.queue, &self.visualizer_config, 0);
+    }
+
+    /// Flies the camera to frame the atoms (indices start from 1): its target moves to the center of their
+    /// bounding sphere and the distance fits the sphere into the view. `animate` should be called on every
+    /// animation frame while it returns `true`. With `dim` (false by default) the other atoms and their bonds
+    /// are dimmed until `clear_focus`.
+    #[wasm_bindgen]
+    pub fn focus_on(&mut self, indices: Vec<usize>, dim: Option<bool>) -> Result<(), JsValue> {
+        self.scene
+            .focus_on(&self.queue, &indices, dim.unwrap_or(false))
+            .map_err(|e| JsValue::from_str(&e))?;
+        self.render()
+    }
+
+    /// Shows the atoms dimmed by `focus_on` as they are, the camera stays.
+    #[wasm_bindgen]
+    pub fn clear_focus(&mut self) -> Result<(), JsValue> {
+        self.scene.clear_focus(&self.queue);
+        self.render()
+    }
+
+    /// Focus mode on the selection, e.g. to present an active site inside a protein: the selected atoms and the
+    /// atoms closer than `cutoff` (5 Angstroms by default) to them are drawn as they are, the rest of the structure
+    /// is drawn darker with "fade" or nearly gray with "desaturate". The focus follows the selection; "off" turns
+    /// the mode off.
+    #[wasm_bindgen]
+    pub fn set_focus_mode(&mut self, mode: &str, cutoff: Option<f32>) -> Result<(), JsValue> {
+        let focus_mode = match mode {
+            "off" | "none" => None,
+            _ => Some(FocusMode {
+                cutoff: cutoff.unwrap_or(DEFAULT_FOCUS_CUTOFF),
+                dimming: Dimming::from_name(mode).map_err(|e| JsValue::from_str(&e))?,
+            }),
+        };
+        self.scene
+            .set_focus_mode(&self.queue, focus_mode)
+            .map_err(|e| JsValue::from_str(&e))?;
+        self.render()
+    }
+
+    /// Limits of the camera navigation as [min_distance, max_distance, max_pitch], see `set_camera_limits`.
+    #[wasm_bindgen]
+    pub fn camera_limits(&self) -> Vec<f32> {
+        let limits = self.scene.camera.limits();
+        vec![limits.min_distance, limits.max_distance, limits.max_pitch]
+    }
+
+    /// Limits of the camera navigation: the closest and farthest distance to the target in Angstroms
+    /// (0.5 and 10000 by default) and the largest pitch above or below it in degrees (89 by default).
+    #[wasm_bindgen]
+    pub fn set_camera_limits(&mut self, min_distance: f32, max_distance: f32, max_pitch: f32) -> Result<(), JsValue> {
+        self.scene
+            .set_camera_limits(CameraLimits {
+                min_distance,
+                max_distance,
+                max_pitch,
+            })
+            .map_err(|e| JsValue::from_str(&e))?;
+        self.render()
+    }
+
+    /// "orthographic" or "perspective".
+    #[wasm_bindgen]
+    pub fn projection_mode(&self) -> String {
+        self.scene.projection_mode().name().to_string()
+    }
+
+    /// Switches between the orthographic and the perspective projection: "orthographic" or "perspective".
+    #[wasm_bindgen]
+    pub fn set_projection_mode(&mut self, name: &str) -> Result<(), JsValue> {
+        let mode = ProjectionMode::from_name(name).map_err(|e| JsValue::from_str(&e))?;
+        self.scene.set_projection_mode(mode);
+        self.render()
+    }
+
+    /// Field of view of the perspective projection in degrees.
+    #[wasm_bindgen]
+    pub fn fov(&self) -> f32 {
+        self.scene.fov()
+    }
+
+    /// Sets the field of view of the perspective projection in degrees (10 to 120). The camera moves so that
+    /// the molecule keeps its size, only the perspective distortion changes.
+    #[wasm_bindgen]
+    pub fn set_fov(&mut self, degrees: f32) -> Result<(), JsValue> {
+        self.scene.set_fov(degrees).map_err(|e| JsValue::from_str(&e))?;
+        self.render()
+    }
+
+    /// Zooms so that the visible atoms fill the canvas.
+    #[wasm_bindgen]
+    pub fn zoom_to_fit(&mut self) {
+        self.scene.zoom_to_fit();
+        self.scene
+            .render(&self.surface, &self.device, &self.queue, &self.visualizer_config, 0);
+        self.notify_view_changed();
+    }
+
+    /// Restores the initial rotation and zoom.
+    #[wasm_bindgen]
+    pub fn reset_view(&mut self) {
+        self.scene.reset_view();
+        self.scene
+            .render(&self.surface, &self.device, &self.queue, &self.visualizer_config, 0);
+        self.notify_view_changed();
+    }
+
+    #[wasm_bindgen]
+    pub fn auto_orientation(&self) -> bool {
+        self.visualizer_config.auto_orientation
+    }
+
+    /// Shows the structure along its principal axes with the largest extent horizontal and a slight tilt,
+    /// or as in the file. The view is reset to the new initial orientation.
+    #[wasm_bindgen]
+    pub fn set_auto_orientation(&mut self, enabled: bool) {
+        self.visualizer_config.auto_orientation = enabled;
+        self.scene.orient(enabled);
+        self.scene
+            .render(&self.surface, &self.device, &self.queue, &self.visualizer_config, 0);
+        self.notify_view_changed();
+    }
+
+    /// Adds another structure (serialized `AtomicCoordinates`) to the scene, e.g. a conformer to overlay with
+    /// the molecule. It keeps its position relative to the molecule as in the coordinates and is drawn with the
+    /// same style, but is not pickable or editable. Returns the id of its node, see `set_node_visible`.
+    #[wasm_bindgen]
+    pub async fn add_structure(&mut self, data: Vec<u8>) -> Result<u32, JsValue> {
+        let data: AtomicCoordinates = serde_json::from_slice(&data)
+            .map_err(|e| JsValue::from_str(&format!("Failed to deserialize data: {e}")))?;
+        let id = self
+            .scene
+            .add_structure(&self.device, &self.visualizer_config, data)
+            .await
+            .map_err(|e| JsValue::from_str(&e))?;
+        self.render()?;
+        Ok(id)
+    }
+
+    /// Removes the structure added with `add_structure`.
+    #[wasm_bindgen]
+    pub fn remove_structure(&mut self, id: u32) -> Result<(), JsValue> {
+        self.scene.remove_structure(id).map_err(|e| JsValue::from_str(&e))?;
+        self.render()
+    }
+
+    /// Moves the structure (or the molecule with the id 1) so that its center is at the center of the molecule
+    /// as loaded.
+    #[wasm_bindgen]
+    pub fn center_structure(&mut self, id: u32) -> Result<(), JsValue> {
+        self.scene.center_structure(id).map_err(|e| JsValue::from_str(&e))?;
+        self.render()
+    }
+
+    /// Superposes the structure (or the molecule with the id 1) onto the `target` one by the Kabsch alignment and
+    /// returns the RMSD in Angstroms of the aligned atoms. `pairs` lists pairs of atoms (indices start from 1, the
+    /// atom of `id` first), e.g. the selected atoms of the molecule with their counterparts in the structure; all
+    /// atoms are paired in order if it is empty.
+    #[wasm_bindgen]
+    pub fn align_structure(&mut self, id: u32, target: u32, pairs: Vec<usize>) -> Result<f64, JsValue> {
+        if !pairs.len().is_multiple_of(2) {
+            return Err(JsValue::from_str("Atom pairs need an even number of indices"));
+        }
+        let pairs = pairs
+            .chunks(2)
+            .map(|pair| match (pair[0].checked_sub(1), pair[1].checked_sub(1)) {
+                (Some(i), Some(j)) => Ok((i, j)),
+                _ => Err(JsValue::from_str("Atom indices start from 1")),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let alignment = self
+            .scene
+            .align_structure(
+                id,
+                target,
+                (!pairs.is_empty()).then_some(pairs.as_slice()),
+                &self.visualizer_config.tolerances,
+            )
+            .map_err(|e| JsValue::from_str(&e))?;
+        self.render()?;
+        Ok(alignment.rmsd)
+    }
+
+    /// Ids of the structures added with `add_structure`.
+    #[wasm_bindgen]
+    pub fn structures(&self) -> Vec<u32> {
+        self.scene.structures()
+    }
+
+    /// Shows or hides the node of the scene graph with its children: 0 is the root, 1 is the molecule, the other
+    /// structures have the ids returned by `add_structure`.
+    #[wasm_bindgen]
+    pub fn set_node_visible(&mut self, id: u32, visible: bool) -> Result<(), JsValue> {
+        let node = self
+            .scene
+            .node_mut(id)
+            .ok_or_else(|| JsValue::from_str(&format!("Unknown node: {id}")))?;
+        node.visible = visible;
+        self.render()
+    }
+
+    /// Moves the node of the scene graph to the position in Angstroms relative to its parent, its children
+    /// move with it.
+    #[wasm_bindgen]
+    pub fn set_node_position(&mut self, id: u32, x: f32, y: f32, z: f32) -> Result<(), JsValue> {
+        let node = self
+            .scene
+            .node_mut(id)
+            .ok_or_else(|| JsValue::from_str(&format!("Unknown node: {id}")))?;
+        node.transform.set_position(Vec3::new(x, y, z));
+        self.render()
+    }
+
+    /// Rotation and zoom of the molecule.
+    #[wasm_bindgen]
+    pub fn view(&self) -> ViewState {
+        self.scene.view_state()
+    }
+
+    /// Applies the rotation and zoom, e.g. of a linked visualizer. The view callback is not called.
+    #[wasm_bindgen]
+    pub fn set_view(&mut self, view: &ViewState) {
+        self.scene.set_view_state(view);
+        self.scene
+            .render(&self.surface, &self.device, &self.queue, &self.visualizer_config, 0);
+    }
+
+    /// `on_view_changed(view)` is called whenever the scene is rotated or zoomed with `rotate_scene`
+    /// or `scale_scene`. Passing the view to `set_view` of other visualizers links them to this one.
+    #[wasm_bindgen]
+    pub fn set_view_callback(&mut self, on_view_changed: Option<js_sys::Function>) {
+        self.on_view_changed = on_view_changed;
+    }
+
+    #[wasm_bindgen]
+    pub async fn new_cursor_position(&mut self, x: u32, y: u32) -> Option<AtomInfo> {
+        let hovered = self.scene.picked_object();
+        let (atom, needs_render) = self.scene.new_cursor_position(x, y, &self.device, &self.queue).await;
+
+        if needs_render {
+            self.scene
+                .render(&self.surface, &self.device, &self.queue, &self.visualizer_config, 0);
+        }
+        if self.scene.picked_object() != hovered
+            && let Some(callback) = &self.on_object_hover
+        {
+            let info = self
+                .scene
+                .picked_object()
+                .and_then(|object| self.scene.pick_info(object));
+            let _ = callback.call1(&JsValue::NULL, &info.map_or(JsValue::NULL, JsValue::from));
+        }
+
+        atom
+    }
+
+    #[wasm_bindgen]
+    pub async fn toggle_atom_selection(&mut self, x: u32, y: u32) {
+        if self.scene.toggle_atom_selection(x, y, &self.device, &self.queue).await {
+            self.scene
+                .render(&self.surface, &self.device, &self.queue, &self.visualizer_config, 0);
+            self.notify_selection_changed();
+        }
+        if let Some(callback) = &self.on_object_click
+            && let Some(info) = self
+                .scene
+                .picked_object()
+                .and_then(|object| self.scene.pick_info(object))
+        {
+            let _ = callback.call1(&JsValue::NULL, &JsValue::from(info));
+        }
+    }
+
+    /// `on_object_hover(object)` is called with the `PickInfo` of the atom, bond, label, measurement line or
+    /// plane under the cursor whenever it changes in `new_cursor_position`, `null` when the cursor leaves it.
+    #[wasm_bindgen]
+    pub fn set_object_hover_callback(&mut self, on_object_hover: Option<js_sys::Function>) {
+        self.on_object_hover = on_object_hover;
+    }
+
+    /// `on_object_click(object)` is called with the `PickInfo` of the object clicked in `toggle_atom_selection`,
+    /// clicks on the background are not reported.
+    #[wasm_bindgen]
+    pub fn set_object_click_callback(&mut self, on_object_click: Option<js_sys::Function>) {
+        self.on_object_click = on_object_click;
+    }
+
+    /// Rubber-band selection: selects the atoms with centers inside the rectangle between the canvas pixels
+    /// (x0, y0) and (x1, y1). With `additive` the selected atoms stay selected, otherwise they are replaced.
+    #[wasm_bindgen]
+    pub fn select_rect(&mut self, x0: u32, y0: u32, x1: u32, y1: u32, additive: bool) {
+        if self.scene.select_rect(x0, y0, x1, y1, additive) {
+            self.scene
+                .render(&self.surface, &self.device, &self.queue, &self.visualizer_config, 0);
+            self.notify_selection_changed();
+        }
+    }
+
+    /// Same as `select_rect`, but atoms hidden behind other atoms are not selected.
+    #[wasm_bindgen]
+    pub async fn select_visible_rect(&mut self, x0: u32, y0: u32, x1: u32, y1: u32, additive: bool) {
+        if self
+            .scene
+            .select_visible_rect(x0, y0, x1, y1, additive, &self.device, &self.queue)
+            .await
+        {
+            self.scene
+                .render(&self.surface, &self.device, &self.queue, &self.visualizer_config, 0);
+            self.notify_selection_changed();
+        }
+    }
+
+    /// Indices (start from 1) of the selected atoms in the order of selection.
+    #[wasm_bindgen]
+    pub fn get_selected_atoms(&self) -> Vec<usize> {
+        self.scene.selected_atoms()
+    }
+
+    /// Adds the atoms (indices start from 1) to the selection.
+    #[wasm_bindgen]
+    pub fn select_atoms(&mut self, indices: Vec<usize>) -> Result<(), JsValue> {
+        if self.scene.select_atoms(&indices).map_err(|e| JsValue::from_str(&e))? {
+            self.render()?;
+        }
+        Ok(())
+    }
+
+    #[wasm_bindgen]
+    pub fn clear_selection(&mut self) -> Result<(), JsValue> {
+        if self.scene.clear_selection() {
+            self.render()?;
+        }
+        Ok(())
+    }
+
+    /// `on_selection_changed(atoms)` is called with the indices of the selected atoms whenever
+    /// the user changes the selection in the view. Changes made through the API are not reported.
+    #[wasm_bindgen]
+    pub fn set_selection_callback(&mut self, on_selection_changed: Option<js_sys::Function>) {
+        self.on_selection_changed = on_selection_changed;
+    }
+
+    /// `on_measurement(measurement)` is called whenever the user selects 2, 3 or 4 atoms
+    /// making a new distance, angle or dihedral.
+    #[wasm_bindgen]
+    pub fn set_measurement_callback(&mut self, on_measurement: Option<js_sys::Function>) {
+        self.on_measurement = on_measurement;
+    }
+
+    /// Distance, angle or dihedral of the selected atoms, if 2 to 4 atoms are selected,
+    /// or the last measurement of planes until the selection changes.
+    #[wasm_bindgen]
+    pub fn measurement(&self) -> Option<MeasurementInfo> {
+        self.scene.measurement().cloned()
+    }
+
+    /// Fits the best plane through at least 3 atoms (indices start from 1), e.g. `get_selected_atoms()`,
+    /// and shows it as a translucent disk. Returns the index of the plane starting from 1.
+    #[wasm_bindgen]
+    pub fn add_plane(&mut self, indices: Vec<usize>) -> Result<usize, JsValue> {
+        let index = self.scene.add_plane(&indices).map_err(|e| JsValue::from_str(&e))?;
+        self.render()?;
+        Ok(index)
+    }
+
+    /// Plane with the index starting from 1.
+    #[wasm_bindgen]
+    pub fn plane(&self, index: usize) -> Result<PlaneInfo, JsValue> {
+        self.scene.plane(index).cloned().map_err(|e| JsValue::from_str(&e))
+    }
+
+    #[wasm_bindgen]
+    pub fn clear_planes(&mut self) -> Result<(), JsValue> {
+        self.scene.clear_planes();
+        self.render()
+    }
+
+    /// Angle between two planes (indices start from 1) from 0 to 90 degrees.
+    #[wasm_bindgen]
+    pub fn measure_plane_angle(&mut self, plane_1: usize, plane_2: usize) -> Result<MeasurementInfo, JsValue> {
+        self.scene
+            .measure_planes(plane_1, plane_2)
+            .map_err(|e| JsValue::from_str(&e))?;
+        self.render()?;
+        self.measurement().ok_or_else(|| JsValue::from_str("No measurement"))
+    }
+
+    /// Angle between the plane and the bond between two atoms (indices start from 1) from 0 to 90 degrees.
+    #[wasm_bindgen]
+    pub fn measure_plane_bond_angle(
+        &mut self,
+        plane: usize,
+        atom_1: usize,
+        atom_2: usize,
+    ) -> Result<MeasurementInfo, JsValue> {
+        self.scene
+            .measure_plane_bond(plane, atom_1, atom_2)
+            .map_err(|e| JsValue::from_str(&e))?;
+        self.render()?;
+        self.measurement().ok_or_else(|| JsValue::from_str("No measurement"))
+    }
+
+    /// Returns the atom under the canvas pixel (x, y) found by CPU ray casting, without highlighting it.
+    #[wasm_bindgen]
+    pub fn atom_at(&mut self, x: u32, y: u32) -> Option<AtomInfo> {
+        let index = self.scene.atom_at(x, y);
+        self.scene.atom_info(index)
+    }
+
+    /// Returns the object under the canvas pixel (x, y) found by CPU ray casting, without highlighting it.
+    #[wasm_bindgen]
+    pub fn object_at(&mut self, x: u32, y: u32) -> Option<PickInfo> {
+        let object = self.scene.object_at(x, y)?;
+        self.scene.pick_info(object)
+    }
+
+    /// Switches cursor picking between the GPU picking pass and CPU ray casting.
+    #[wasm_bindgen]
+    pub fn set_cpu_picking(&mut self, enabled: bool) {
+        self.scene.cpu_picking = enabled;
+    }
+
+    /// Switches between ray casted atoms and bonds and triangle meshes for them.
+    /// Meshes are cheaper per pixel and are the better choice for low-end devices.
+    #[wasm_bindgen]
+    pub fn set_ray_casting(&mut self, enabled: bool) {
+        self.visualizer_config.style.geometry.ray_casting = enabled;
+        self.scene.set_ray_casting(&self.device, enabled);
+        self.scene
+            .render(&self.surface, &self.device, &self.queue, &self.visualizer_config, 0);
+    }
+
+    /// Replaces the shading with a debug view: "normals", "depth", "picking_ids" or "overdraw",
+    /// an empty name restores it. Only in builds with the debug-tools feature.
+    #[cfg(feature = "debug-tools")]
+    #[wasm_bindgen]
+    pub fn set_debug_view(&mut self, name: &str) -> Result<(), JsValue> {
+        let view = if name.is_empty() {
+            None
+        } else {
+            Some(DebugView::from_name(name).map_err(|e| JsValue::from_str(&e))?)
+        };
+        self.scene.set_debug_view(view);
+        self.render()
+    }
+
+    /// Representation of the molecule, without one given in the style it was chosen by the size of the structure.
+    #[wasm_bindgen]
+    pub fn representation(&self) -> String {
+        self.visualizer_config.style.representation.name().to_string()
+    }
+
+    /// Switches the representation of the molecule: "ball_and_stick", "spacefill", "licorice" or "wireframe".
+    #[wasm_bindgen]
+    pub fn set_representation(&mut self, name: &str) -> Result<(), JsValue> {
+        self.visualizer_config.style.representation =
+            Representation::from_name(name).map_err(|e| JsValue::from_str(&e))?;
+        self.scene.set_representation(&self.device, &self.visualizer_config);
+        self.scene
+            .render(&self.surface, &self.device, &self.queue, &self.visualizer_config, 0);
+        Ok(())
+    }
+
+    /// Switches the colors of atoms: "jmol", "cpk", "colorblind_safe", "atomic_mass", "charge", "chain" or
+    /// "property". Charges and chains must be given by the file, "property" shows values bound to the frames by
+    /// `load_frame_properties`.
+    #[wasm_bindgen]
+    pub fn set_color_scheme(&mut self, name: &str) -> Result<(), JsValue> {
+        let previous = self.visualizer_config.style.color_scheme;
+        self.visualizer_config.style.color_scheme = ColorScheme::from_name(name).map_err(|e| JsValue::from_str(&e))?;
+        if let Err(e) = self.scene.set_color_scheme(&self.device, &self.visualizer_config) {
+            self.visualizer_config.style.color_scheme = previous;
+            return Err(JsValue::from_str(&e));
+        }
+        self.scene
+            .render(&self.surface, &self.device, &self.queue, &self.visualizer_config, 0);
+        Ok(())
+    }
+
+    /// Switches the palette of the color scheme: "standard", "colorblind_safe" or "grayscale".
+    /// Element, chain and property colors are taken from the palette.
+    #[wasm_bindgen]
+    pub fn set_palette(&mut self, name: &str) -> Result<(), JsValue> {
+        self.visualizer_config.style.palette = Palette::from_name(name).map_err(|e| JsValue::from_str(&e))?;
+        self.scene
+            .set_color_scheme(&self.device, &self.visualizer_config)
+            .map_err(|e| JsValue::from_str(&e))?;
+        self.render()
+    }
+
+    /// Turns on or off the effects for presentation renders: depth of field focused on the selected atoms
+    /// and a dark outline along silhouettes. Export the canvas right after the render they trigger.
+    #[wasm_bindgen]
+    pub fn set_effects(&mut self, depth_of_field: bool, outline: bool) {
+        let effects = &mut self.visualizer_config.style.effects;
+        effects.depth_of_field = depth_of_field;
+        effects.outline = outline;
+        self.scene
+            .render(&self.surface, &self.device, &self.queue, &self.visualizer_config, 0);
+    }
+
+    #[wasm_bindgen]
+    pub fn antialiasing(&self) -> bool {
+        self.visualizer_config.style.effects.antialiasing
+    }
+
+    /// Turns on or off FXAA post-processing smoothing jagged edges of the frame, including transparent objects
+    /// and outlines.
+    #[wasm_bindgen]
+    pub fn set_antialiasing(&mut self, enabled: bool) -> Result<(), JsValue> {
+        self.visualizer_config.style.effects.antialiasing = enabled;
+        self.render()
+    }
+
+    #[wasm_bindgen]
+    pub fn transparent_background(&self) -> bool {
+        self.visualizer_config.style.transparent_background
+    }
+
+    /// Clears the view to transparent instead of the background color, e.g. to composite captured images
+    /// with premultiplied alpha over other figures.
+    #[wasm_bindgen]
+    pub fn set_transparent_background(&mut self, transparent: bool) -> Result<(), JsValue> {
+        self.visualizer_config.style.transparent_background = transparent;
+        self.render()
+    }
+
+    #[wasm_bindgen]
+    pub fn fog(&self) -> bool {
+        self.visualizer_config.style.fog.enabled
+    }
+
+    /// Depth cueing: farther atoms fade into the background, so the depth of large structures is easier to see.
+    #[wasm_bindgen]
+    pub fn set_fog(&mut self, enabled: bool) -> Result<(), JsValue> {
+        self.visualizer_config.style.fog.enabled = enabled;
+        self.render()
+    }
+
+    /// Where the fog begins and where atoms disappear in it, as fractions of the depth of the structure:
+    /// 0 is its front and 1 is its back.
+    #[wasm_bindgen]
+    pub fn set_fog_range(&mut self, start: f32, end: f32) -> Result<(), JsValue> {
+        self.visualizer_config
+            .style
+            .fog
+            .set_range(start, end)
+            .map_err(|e| JsValue::from_str(&e))?;
+        self.render()
+    }
+
+    /// Switches the look with a style preset: "default", "publication", "presentation", "dark", "colorblind_safe",
+    /// "grayscale_print" or one registered with `register_style_preset`.
+    #[wasm_bindgen]
+    pub fn set_style_preset(&mut self, name: &str) -> Result<(), JsValue> {
+        let previous = self.visualizer_config.style.color_scheme;
+        self.visualizer_config
+            .apply_preset(name)
+            .map_err(|e| JsValue::from_str(&e))?;
+        if let Err(e) = self.scene.set_style(&self.device, &self.visualizer_config) {
+            // The color scheme needs data the structure does not have, the rest of the preset is kept
+            self.visualizer_config.style.color_scheme = previous;
+            self.scene
+                .set_style(&self.device, &self.visualizer_config)
+                .map_err(|e| JsValue::from_str(&e))?;
+            self.render()?;
+            return Err(JsValue::from_str(&e));
+        }
+        self.render()
+    }
+
+    /// Adds a custom style preset given as JSON with any of the keys "background_color", "color_scheme", "palette",
+    /// "representation", "bond_thickness", "label_color", "label_outline_color", "outline" and "depth_of_field".
+    /// Colors are [r, g, b] in range [0, 1].
+    #[wasm_bindgen]
+    pub fn register_style_preset(&mut self, name: &str, json: &str) -> Result<(), JsValue> {
+        let preset = StylePreset::from_json(json).map_err(|e| JsValue::from_str(&e))?;
+        self.visualizer_config
+            .presets
+            .register(name, preset)
+            .map_err(|e| JsValue::from_str(&e))
+    }
+
+    /// Names of the built-in and registered style presets.
+    #[wasm_bindgen]
+    pub fn style_presets(&self) -> Vec<String> {
+        self.visualizer_config.presets.names()
+    }
+
+    /// Changes style settings given as JSON with any of the keys "background_color" ([r, g, b] in range [0, 1]),
+    /// "atom_radii" (radii in Angstroms by element symbol or atomic number), "bond_radius" and "bond_tolerance"
+    /// (relative to the sum of covalent radii). Bonds are searched again if the tolerance has changed.
+    #[wasm_bindgen]
+    pub fn update_style(&mut self, json: &str) -> Result<(), JsValue> {
+        let settings = StyleSettings::from_json(json).map_err(|e| JsValue::from_str(&e))?;
+        let tolerance = self.visualizer_config.style.geom_bond_tolerance;
+        settings.apply(&mut self.visualizer_config.style);
+        if self.visualizer_config.style.geom_bond_tolerance != tolerance {
+            self.scene
+                .set_bond_tolerance(&self.device, &self.visualizer_config, &self.node_data);
+        }
+        self.scene
+            .set_style(&self.device, &self.visualizer_config)
+            .map_err(|e| JsValue::from_str(&e))?;
+        self.render()
+    }
+
+    /// Shows or hides labels of one type: "symbol", "index" or "custom".
+    /// Visible labels of an atom are drawn in one line.
+    #[wasm_bindgen]
+    pub fn set_labels_visible(&mut self, kind: &str, visible: bool) -> Result<(), JsValue> {
+        let kind = LabelKind::from_name(kind).map_err(|e| JsValue::from_str(&e))?;
+        self.scene.set_labels_visible(kind, visible);
+        self.render()
+    }
+
+    /// Shows the lengths of bonds in Angstroms at their middles: "all", "selected" for the bonds between selected
+    /// atoms or "none". Labels that would overlap others are left out.
+    #[wasm_bindgen]
+    pub fn set_bond_labels(&mut self, bonds: &str) -> Result<(), JsValue> {
+        let bond_labels = BondLabels::from_name(bonds).map_err(|e| JsValue::from_str(&e))?;
+        self.scene.set_bond_labels(bond_labels);
+        self.render()
+    }
+
+    /// Sets the custom label of the atom (index starts from 1), an empty text removes it.
+    #[wasm_bindgen]
+    pub fn set_atom_label(&mut self, index: usize, text: &str) -> Result<(), JsValue> {
+        self.scene
+            .set_atom_label(index, text)
+            .map_err(|e| JsValue::from_str(&e))?;
+        self.render()
+    }
+
+    #[wasm_bindgen]
+    pub fn set_atom_visible(&mut self, index: usize, visible: bool) -> Result<(), JsValue> {
+        self.scene
+            .set_atom_visible(&self.queue, index, visible)
+            .map_err(|e| JsValue::from_str(&e))?;
+        self.render()
+    }
+
+    /// JSON array of the elements of the structure ordered by atomic number: symbol, atomic number, number of
+    /// atoms and how many of them are visible.
+    #[wasm_bindgen]
+    pub fn elements(&self) -> Result<Vec<u8>, JsValue> {
+        let elements: Vec<ElementInfo> = self
+            .scene
+            .element_counts()
+            .into_iter()
+            .map(|(number, count, visible)| ElementInfo {
+                symbol: periodic_table::element_symbol(number),
+                number,
+                count,
+                visible,
+            })
+            .collect();
+        serde_json::to_vec(&elements).map_err(|e| JsValue::from_str(&format!("Failed to serialize elements: {e}")))
+    }
+
+    /// Shows or hides all atoms of the element with the symbol, e.g. "H".
+    #[wasm_bindgen]
+    pub fn set_element_visible(&mut self, symbol: &str, visible: bool) -> Result<(), JsValue> {
+        let element = periodic_table::get_element_by_symbol(symbol)
+            .ok_or_else(|| JsValue::from_str(&format!("Unknown element: {}", symbol)))?;
+        self.scene
+            .set_element_visible(&self.queue, element.atomic_number, visible)
+            .map_err(|e| JsValue::from_str(&e))?;
+        self.render()
+    }
+
+    #[wasm_bindgen]
+    pub fn pseudo_atoms_visible(&self) -> bool {
+        self.visualizer_config.show_pseudo_atoms
+    }
+
+    /// Shows or hides dummy atoms (X), point charges (Q) and ghost atoms (Bq) of this and later loaded structures.
+    #[wasm_bindgen]
+    pub fn set_pseudo_atoms_visible(&mut self, visible: bool) -> Result<(), JsValue> {
+        self.visualizer_config.show_pseudo_atoms = visible;
+        self.scene.set_pseudo_atoms_visible(&self.queue, visible);
+        self.render()
+    }
+
+    /// Sets the radius in Angstroms and the color ([r, g, b] in range [0, 1]) of the atoms (indices start from 1)
+    /// overriding the style, e.g. to emphasize a reactive center. An omitted value is taken from the style, with
+    /// both omitted the override is removed. The atoms keep it when the representation or the color scheme changes.
+    #[wasm_bindgen]
+    pub fn set_atom_style(
+        &mut self,
+        indices: Vec<usize>,
+        radius: Option<f32>,
+        color: Option<Vec<f32>>,
+    ) -> Result<(), JsValue> {
+        let color = match color.as_deref() {
+            None => None,
+            Some(&[r, g, b]) => Some(Color::new(r, g, b, 1.0)),
+            Some(color) => {
+                return Err(JsValue::from_str(&format!(
+                    "Color must have 3 components [r, g, b], got {}",
+                    color.len()
+                )));
+            }
+        };
+        self.scene
+            .set_atom_style(
+                &self.device,
+                &self.visualizer_config,
+                &indices,
+                AtomStyle { radius, color },
+            )
+            .map_err(|e| JsValue::from_str(&e))?;
+        self.render()
+    }
+
+    /// Removes the radii and colors overriding the style from all atoms.
+    #[wasm_bindgen]
+    pub fn clear_atom_styles(&mut self) -> Result<(), JsValue> {
+        let indices: Vec<usize> = self.scene.atom_styles().iter().map(|&(i, _)| i + 1).collect();
+        if indices.is_empty() {
+            return Ok(());
+        }
+        self.scene
+            .set_atom_style(&self.device, &self.visualizer_config, &indices, AtomStyle::default())
+            .map_err(|e| JsValue::from_str(&e))?;
+        self.render()
+    }
+
+    /// Radii and colors overriding the style as JSON: a list of {"index", "radius", "color"} with atom indices
+    /// starting from 1 and `null` for values taken from the style, to save them together with the view and restore
+    /// them with `set_atom_style`.
+    #[wasm_bindgen]
+    pub fn atom_styles(&self) -> Result<Vec<u8>, JsValue> {
+        let styles: Vec<AtomStyleInfo> = self
+            .scene
+            .atom_styles()
+            .into_iter()
+            .map(|(i, atom_style)| AtomStyleInfo {
+                index: i + 1,
+                radius: atom_style.radius,
+                color: atom_style.color.map(|color| [color.r, color.g, color.b]),
+            })
+            .collect();
+        serde_json::to_vec(&styles).map_err(|e| JsValue::from_str(&format!("Failed to serialize atom styles: {e}")))
+    }
+
+    #[wasm_bindgen]
+    pub fn clear_atom_labels(&mut self) -> Result<(), JsValue> {
+        self.scene.clear_atom_labels();
+        self.render()
+    }
+
+    #[wasm_bindgen]
+    pub fn render(&mut self) -> Result<(), JsValue> {
+        self.scene
+            .render(&self.surface, &self.device, &self.queue, &self.visualizer_config, 0);
+
+        Ok(())
+    }
+
+    /// Loads volumetric data (serialized `VolumeCube`) used by the isosurface and the slice plane.
+    /// `on_progress` and `is_cancelled` follow `create`, the isosurface of a large cube is built in chunks.
+    #[wasm_bindgen]
+    pub async fn load_volume_cube(
+        &mut self,
+        data: Vec<u8>,
+        on_progress: Option<js_sys::Function>,
+        is_cancelled: Option<js_sys::Function>,
+    ) -> Result<(), JsValue> {
+        let cube: VolumeCube = serde_json::from_slice(&data)
+            .map_err(|e| JsValue::from_str(&format!("Failed to deserialize volume cube: {e}")))?;
+        let progress = Progress::new(on_progress);
+        let cancel = cancellation_token(is_cancelled);
+        self.scene
+            .load_volume_cube(&self.device, &self.queue, cube, &progress, &cancel)
+            .await
+            .map_err(|e| JsValue::from_str(&e))?;
+        self.render()
+    }
+
+    /// Box of the loaded volume cube in Angstroms, `[min_x, min_y, min_z, max_x, max_y, max_z]`.
+    #[wasm_bindgen]
+    pub fn volume_bounds(&self) -> Option<Vec<f64>> {
+        self.scene.volume_bounds().map(|(min, max)| [min, max].concat())
+    }
+
+    /// Shows the slice plane through the point (px, py, pz) in Angstroms, perpendicular to (nx, ny, nz).
+    #[wasm_bindgen]
+    pub fn set_slice_plane(&mut self, px: f64, py: f64, pz: f64, nx: f32, ny: f32, nz: f32) -> Result<(), JsValue> {
+        let settings = SliceSettings {
+            point: [px, py, pz],
+            normal: Vec3::new(nx, ny, nz),
+            ..self.scene.slice_settings()
+        };
+        self.update_slice(settings, true)
+    }
+
+    /// Sets the colormap of the slice plane: "viridis", "coolwarm", "grayscale" or the colorblind-safe "cividis"
+    /// and "blue_orange".
+    #[wasm_bindgen]
+    pub fn set_slice_colormap(&mut self, name: &str) -> Result<(), JsValue> {
+        let settings = SliceSettings {
+            colormap: Colormap::from_name(name).map_err(|e| JsValue::from_str(&e))?,
+            ..self.scene.slice_settings()
+        };
+        self.update_slice(settings, self.scene.is_slice_visible())
+    }
+
+    /// Sets the opacity of the slice plane in range [0, 1].
+    #[wasm_bindgen]
+    pub fn set_slice_opacity(&mut self, opacity: f32) -> Result<(), JsValue> {
+        let settings = SliceSettings {
+            opacity,
+            ..self.scene.slice_settings()
+        };
+        self.update_slice(settings, self.scene.is_slice_visible())
+    }
+
+    #[wasm_bindgen]
+    pub fn hide_slice(&mut self) -> Result<(), JsValue> {
+        self.update_slice(self.scene.slice_settings(), false)
+    }
+
+    fn update_slice(&mut self, settings: SliceSettings, visible: bool) -> Result<(), JsValue> {
+        self.scene
+            .set_slice(&self.device, &self.queue, settings, visible)
+            .map_err(|e| JsValue::from_str(&e))?;
+        self.render()
+    }
+
+    /// Shows the positive and negative lobes of the volume cube at `+|isovalue|` and `-|isovalue|` with the opacity
+    /// in range [0, 1], translucent below 1. `on_progress` and `is_cancelled` follow `load_volume_cube`.
+    #[wasm_bindgen]
+    pub async fn set_isosurface(
+        &mut self,
+        isovalue: f64,
+        opacity: f32,
+        on_progress: Option<js_sys::Function>,
+        is_cancelled: Option<js_sys::Function>,
+    ) -> Result<(), JsValue> {
+        let settings = IsosurfaceSettings {
+            isovalue,
+            opacity,
+            ..self.scene.isosurface_settings()
+        };
+        let progress = Progress::new(on_progress);
+        let cancel = cancellation_token(is_cancelled);
+        self.update_isosurface(settings, true, &progress, &cancel).await
+    }
+
+    #[wasm_bindgen]
+    pub async fn hide_isosurface(&mut self) -> Result<(), JsValue> {
+        let settings = self.scene.isosurface_settings();
+        self.update_isosurface(settings, false, &Progress::new(None), &CancellationToken::none())
+            .await
+    }
+
+    /// Number of triangles of the shown lobes, 0 if the isosurface is hidden or no cube is loaded.
+    #[wasm_bindgen]
+    pub fn isosurface_triangles(&self) -> u32 {
+        self.scene
+            .isosurface()
+            .map_or(0, |isosurface| isosurface.num_triangles())
+    }
+
+    async fn update_isosurface(
+        &mut self,
+        settings: IsosurfaceSettings,
+        visible: bool,
+        progress: &Progress,
+        cancel: &CancellationToken,
+    ) -> Result<(), JsValue> {
+        self.scene
+            .set_isosurface(&self.device, settings, visible, progress, cancel)
+            .await
+            .map_err(|e| JsValue::from_str(&e))?;
+        self.render()
+    }
+
+    fn notify_view_changed(&self) {
+        if let Some(callback) = &self.on_view_changed {
+            let _ = callback.call1(&JsValue::NULL, &JsValue::from(self.scene.view_state()));
+        }
+    }
+
+    // Tells the host about the selection changed by the user
+    fn notify_selection_changed(&self) {
+        if let Some(callback) = &self.on_selection_changed {
+            let atoms: Vec<u32> = self.scene.selected_atoms().iter().map(|&i| i as u32).collect();
+            let _ = callback.call1(&JsValue::NULL, &js_sys::Uint32Array::from(atoms.as_slice()));
+        }
+        if let (Some(callback), Some(measurement)) = (&self.on_measurement, self.scene.measurement()) {
+            let _ = callback.call1(&JsValue::NULL, &JsValue::from(measurement.clone()));
+        }
+    }
+}
+
+/// Device and configured surface of a canvas, shared by the visualizers.
+pub(crate) struct CanvasGpu {
+    pub surface: wgpu::Surface<'static>,
+    pub backend: wgpu::Backend,
+    pub device: wgpu::Device,
+    pub queue: wgpu::Queue,
+    pub config: wgpu::SurfaceConfiguration,
+}
+
+impl CanvasGpu {
+    pub async fn new(canvas: HtmlCanvasElement) -> Result<Self, JsValue> {
+        let width = canvas.width();
+        let height = canvas.height();
+
+        // Create wgpu instance with WebGPU backend
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor::default());
+
+        // Create surface from canvas
+        let surface = instance
+            .create_surface(wgpu::SurfaceTarget::Canvas(canvas))
+            .map_err(|e| JsValue::from_str(&format!("Failed to create surface: {e}")))?;
+
+        // Request adapter (GPU handle)
+        let adapter = instance
+            .request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: wgpu::PowerPreference::default(),
+                compatible_surface: Some(&surface),
+                force_fallback_adapter: false,
+            })
+            .await
+            .map_err(|e| JsValue::from_str(&format!("Failed to find an appropriate adapter: {e}")))?;
+
+        // Request device and queue
+        let (device, queue): (wgpu::Device, wgpu::Queue) = adapter
+            .request_device(&wgpu::DeviceDescriptor {
+                label: Some("WebGPU Device"),
+                // Lets the GPU-driven path draw atoms and bonds with one multi-draw call
+                required_features: adapter.features() & wgpu::Features::INDIRECT_FIRST_INSTANCE,
+                required_limits: wgpu::Limits::default(),
+                memory_hints: wgpu::MemoryHints::default(),
+                experimental_features: wgpu::ExperimentalFeatures::default(),
+                trace: wgpu::Trace::Off,
+            })
+            .await
+            .map_err(|e| JsValue::from_str(&format!("Failed to create device: {e}")))?;
+
+        // Configure surface
+        let surface_caps = surface.get_capabilities(&adapter);
+        let surface_format = surface_caps
+            .formats
+            .iter()
+            .find(|f| f.is_srgb())
+            .copied()
+            .unwrap_or(surface_caps.formats[0]);
+
+        let config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format: surface_format,
+            width,
+            height,
+            present_mode: wgpu::PresentMode::Fifo,
+            // The canvas shows the page through the transparent background
+            alpha_mode: if surface_caps
+                .alpha_modes
+                .contains(&wgpu::CompositeAlphaMode::PreMultiplied)
+            {
+                wgpu::CompositeAlphaMode::PreMultiplied
+            } else {
+                surface_caps.alpha_modes[0]
+            },
+            view_formats: vec![],
+            desired_maximum_frame_latency: 2,
+        };
+        surface.configure(&device, &config);
+
+        Ok(Self {
+            surface,
+            backend: adapter.get_info().backend,
+            device,
+            queue,
+            config,
+        })
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn performance_now() -> f64 {
+    web_sys::window()
+        .and_then(|window| window.performance())
+        .map_or(0.0, |performance| performance.now())
 }
@@ -1,33 +1,404 @@
-import type { ProgramPluginContext } from './program_context';
+import type { PluginStorage, ProgramPluginContext } from './program_context';
 
 interface AtomInfo {
     symbol: string;
     tag: number;
 }
 
+interface MeasurementInfo {
+    kind: 'distance' | 'angle' | 'dihedral' | 'plane_angle' | 'plane_bond_angle';
+    atoms: Uint32Array;
+    planes: Uint32Array;
+    value: number;
+    text: string;
+}
+
+interface PlaneInfo {
+    atoms: Uint32Array;
+    normal: Float64Array;
+    rms_deviation: number;
+}
+
+interface PickInfo {
+    kind: 'atom' | 'bond' | 'label' | 'measurement' | 'plane';
+    index: number; // starts from 1, 0 for the measurement
+    atoms: Uint32Array;
+}
+
+interface ViewState {
+    rotation: Float32Array; // unit quaternion (w, x, y, z)
+    scale: number;
+}
+
+interface ClashInfo {
+    atoms: [number, number]; // start from 1
+    distance: number;
+    fraction: number; // of the sum of van der Waals radii
+}
+
+type SymmetryElement =
+    | { kind: 'axis' | 'improper_axis'; direction: [number, number, number]; order: number } // 0 for ∞
+    | { kind: 'mirror'; normal: [number, number, number] }
+    | { kind: 'inversion' };
+
+interface SymmetryInfo {
+    point_group: string;
+    center: [number, number, number]; // Angstroms
+    elements: SymmetryElement[];
+}
+
+interface ElementInfo {
+    symbol: string;
+    number: number; // atomic number
+    count: number;
+    visible: number; // atoms of the element that are shown
+}
+
+interface AtomStyleInfo {
+    index: number; // starts from 1
+    radius: number | null; // Angstroms, null if taken from the style
+    color: [number, number, number] | null; // null if taken from the style
+}
+
+interface ExportFormat {
+    name: string;
+    extension: string;
+    multi_frame: boolean; // the format holds all frames, the others take the current one
+}
+
+// Structural edit of the builder, applied in order to the structure they give the edited one
+export type EditEvent =
+    | { kind: 'add_atom'; atom: number; element: string; position: [number, number, number] } // Angstroms
+    | { kind: 'delete_atoms'; atoms: number[] }
+    | { kind: 'add_bond'; atoms: [number, number]; order: number } // 4 for aromatic
+    | { kind: 'remove_bond'; atoms: [number, number] }
+    // A replaced atom is deleted before the atoms of the template are added
+    | {
+          kind: 'attach_template';
+          atom: number;
+          template: string;
+          replaced: boolean;
+          elements: string[];
+          positions: [number, number, number][];
+      }
+    // Hydrogens added after the existing atoms
+    | { kind: 'add_hydrogens'; positions: [number, number, number][] };
+
+// Name of the DOM event dispatched from the host element with an EditEventDetail, e.g. for the Cartesian editor of
+// the same structure
+export const EDIT_EVENT = 'mircmd:chemistry:edit';
+export type EditEventDetail = EditEvent & { structure: string }; // key of the edited structure
+
+// Request to add the missing hydrogens or strip them with a HydrogensEventDetail, e.g. from the toolbar of the
+// Cartesian editor of the same structure. The edits are reported back as EditEvents.
+export const HYDROGENS_EVENT = 'mircmd:chemistry:hydrogens';
+type HydrogenEdit = 'add' | 'strip';
+export interface HydrogensEventDetail {
+    structure: string; // key of the structure, requests for other structures are ignored
+    edit: HydrogenEdit;
+}
+
+// Notice from the host that it has saved the edited structure with a SavedEventDetail, e.g. into its file. The
+// visualizer forgets the unsaved edits and removes their autosaved snapshot.
+export const SAVED_EVENT = 'mircmd:chemistry:saved';
+export interface SavedEventDetail {
+    structure: string; // key of the structure, notices for other structures are ignored
+}
+
+// Built-in template of the structure library
+interface TemplateInfo {
+    name: string;
+    category: string;
+    num_atoms: number;
+    attachable: boolean;
+}
+
+// Elements offered by the builder toolbar
+const builder_elements = ['C', 'H', 'N', 'O', 'S', 'P', 'F', 'Cl', 'Br', 'I'];
+
+// Colormaps of the slice plane, the last two are colorblind-safe
+const slice_colormaps = ['viridis', 'coolwarm', 'grayscale', 'cividis', 'blue_orange'];
+
+// Types of the nodes the plugin is opened on, without the mircmd:chemistry: prefix
+const node_kinds = ['atomic_coordinates', 'trajectory', 'vibrational_modes', 'volume_cube'] as const;
+type NodeKind = (typeof node_kinds)[number];
+
+// A volume cube node has no atoms, the cube is shown in a structure without them
+const empty_structure = new TextEncoder().encode('{"atomic_num":[],"x":[],"y":[],"z":[]}');
+
+interface EnergyTerms {
+    bond: number; // kcal/mol
+    angle: number;
+    torsion: number;
+    vdw: number;
+    total: number;
+    clashes: number;
+}
+
 interface MolecularVisualizerInstance {
     resize(width: number, height: number): void;
     scale_scene(factor: number): void;
     rotate_scene(pitch: number, yaw: number, roll: number): void;
+    begin_rotation(x: number, y: number, time: number): void;
+    drag_rotation(x: number, y: number, time: number): void;
+    end_rotation(time: number): boolean;
+    animate(time: number): boolean;
+    set_inertia(enabled: boolean, damping: number): void;
+    load_frames(data: Uint8Array): void;
+    load_frame_properties(data: Uint8Array): void; // JSON number[][], values of the atoms for every frame
+    clear_frame_properties(): void;
+    num_frames(): number;
+    frame(): number;
+    set_frame(index: number): void;
+    step_frame(delta: number): void;
+    play(): void;
+    pause(): void;
+    is_playing(): boolean;
+    frame_rate(): number;
+    set_frame_rate(frame_rate: number): void;
+    set_looping(looping: boolean): void;
+    load_vibrational_modes(data: Uint8Array): void;
+    vibrational_frequencies(): Float64Array;
+    vibration_mode(): number;
+    set_vibration_mode(index: number): void;
+    vibration_amplitude(): number;
+    set_vibration_amplitude(amplitude: number): void;
+    vibration_frequency(): number;
+    set_vibration_frequency(frequency: number): void;
+    set_vibration_phase(phase: number): void;
+    vibration_arrows(): boolean;
+    set_vibration_arrows(visible: boolean): void;
+    play_vibration(): void;
+    stop_vibration(): void;
+    is_vibrating(): boolean;
+    rotate_bond(atom_1: number, atom_2: number, angle: number): void;
+    set_dihedral(atoms: Uint32Array, angle: number): void;
+    cell_parameters(): Float64Array | undefined;
+    cell_vectors(): Float64Array | undefined;
+    set_cell_parameters(parameters: Float64Array, scale_atoms: boolean): void;
+    set_cell_vectors(vectors: Float64Array, scale_atoms: boolean): void;
+    supercell(): Uint32Array;
+    set_supercell(na: number, nb: number, nc: number): void;
+    coordinates(): Uint8Array;
+    export_frame(format: string, options?: string): string;
+    export_frames(format: string, options?: string): string;
+    attach_template(index: number, name: string): Promise<number>;
+    set_builder_mode(element?: string): void;
+    builder_mode(): string | undefined;
+    set_builder_template(name?: string): void;
+    builder_template(): string | undefined;
+    builder_click(x: number, y: number): Promise<void>;
+    add_atom(element: string, x: number, y: number, z: number): Promise<number>;
+    delete_atoms(indices: Uint32Array): Promise<number>;
+    toggle_bond(atom_1: number, atom_2: number, order?: number): Promise<boolean>;
+    add_hydrogens(): Promise<number>;
+    strip_hydrogens(): Promise<number>;
+    set_edit_callback(on_edit?: (event: EditEvent) => void): void;
+    autosave_key(): string;
+    has_unsaved_edits(): boolean;
+    edit_history(): string[];
+    set_autosave_callback(on_autosave?: (key: string, snapshot: string) => void): void;
+    autosave(time: number): boolean;
+    restore_autosave(snapshot: string): Promise<void>;
+    mark_saved(): void;
+    set_tolerances(settings: string): void;
+    tolerances(): Uint8Array;
+    moved_atoms(): Uint32Array;
+    diffraction_curves(settings?: string): Uint8Array;
+    powder_pattern(settings?: string): Uint8Array;
+    isotope_pattern(settings?: string): Uint8Array;
+    atom_types(scheme?: string): string[];
+    energy_terms(): Uint8Array;
+    new_clashes(): Uint32Array;
+    set_clash_check(enabled: boolean, fraction?: number): void;
+    clash_check(): number | undefined;
+    clashes(): Uint8Array;
+    set_covalent_clash_check(enabled: boolean, fraction?: number): void;
+    covalent_clash_check(): number | undefined;
+    covalent_clashes(): Uint8Array;
+    capture_image(width: number, height: number, supersampling?: number): Promise<Uint8Array>;
+    report(format: string, title?: string, width?: number, height?: number): Promise<string>;
+    detect_symmetry(tolerance?: number): string;
+    point_group(): string | undefined;
+    symmetry_elements(): Uint8Array; // JSON SymmetryInfo
+    symmetry_equivalent_atoms(index: number): Uint32Array;
+    clear_symmetry(): void;
+    move_atom(index: number, dx: number, dy: number, dz: number): void;
+    drag_atom(index: number, dx: number, dy: number): void;
+    orbit_camera(yaw: number, pitch: number): void;
+    pan_camera(dx: number, dy: number): void;
+    dolly_camera(factor: number): void;
+    camera_limits(): Float32Array;
+    set_camera_limits(min_distance: number, max_distance: number, max_pitch: number): void;
+    focus_on(indices: Uint32Array, dim?: boolean): void;
+    clear_focus(): void;
+    set_focus_mode(mode: 'fade' | 'desaturate' | 'off', cutoff?: number): void;
+    projection_mode(): 'orthographic' | 'perspective';
+    set_projection_mode(name: string): void;
+    fov(): number;
+    set_fov(degrees: number): void;
+    zoom_to_fit(): void;
+    reset_view(): void;
+    auto_orientation(): boolean;
+    set_auto_orientation(enabled: boolean): void;
+    add_structure(data: Uint8Array): Promise<number>;
+    remove_structure(id: number): void;
+    center_structure(id: number): void;
+    align_structure(id: number, target: number, pairs: Uint32Array): number; // RMSD in Angstroms
+    structures(): Uint32Array;
+    set_node_visible(id: number, visible: boolean): void;
+    set_node_position(id: number, x: number, y: number, z: number): void;
+    view(): ViewState;
+    set_view(view: ViewState): void;
+    set_view_callback(on_view_changed?: (view: ViewState) => void): void;
     new_cursor_position(x: number, y: number): Promise<AtomInfo | null>;
     toggle_atom_selection(x: number, y: number): Promise<void>;
+    select_rect(x0: number, y0: number, x1: number, y1: number, additive: boolean): void;
+    select_visible_rect(x0: number, y0: number, x1: number, y1: number, additive: boolean): Promise<void>;
+    get_selected_atoms(): Uint32Array;
+    select_atoms(indices: Uint32Array): void;
+    clear_selection(): void;
+    set_selection_callback(on_selection_changed?: (atoms: Uint32Array) => void): void;
+    atom_at(x: number, y: number): AtomInfo | null;
+    object_at(x: number, y: number): PickInfo | null;
+    set_object_hover_callback(on_object_hover?: (object: PickInfo | null) => void): void;
+    set_object_click_callback(on_object_click?: (object: PickInfo) => void): void;
+    set_cpu_picking(enabled: boolean): void;
+    set_ray_casting(enabled: boolean): void;
+    // Only in builds with the debug-tools feature
+    set_debug_view?(name: string): void;
+    representation(): string;
+    set_representation(name: string): void;
+    set_color_scheme(name: string): void;
+    set_palette(name: string): void;
+    set_effects(depth_of_field: boolean, outline: boolean): void;
+    antialiasing(): boolean;
+    set_antialiasing(enabled: boolean): void;
+    transparent_background(): boolean;
+    set_transparent_background(transparent: boolean): void;
+    fog(): boolean;
+    set_fog(enabled: boolean): void;
+    set_fog_range(start: number, end: number): void;
+    set_style_preset(name: string): void;
+    register_style_preset(name: string, json: string): void;
+    style_presets(): string[];
+    update_style(json: string): void;
+    set_labels_visible(kind: string, visible: boolean): void;
+    set_bond_labels(bonds: 'all' | 'selected' | 'none'): void;
+    set_atom_label(index: number, text: string): void;
+    clear_atom_labels(): void;
+    set_isotope(index: number, mass_number: number): void;
+    isotopes(): Uint32Array;
+    set_atom_visible(index: number, visible: boolean): void;
+    elements(): Uint8Array;
+    set_element_visible(symbol: string, visible: boolean): void;
+    pseudo_atoms_visible(): boolean;
+    set_pseudo_atoms_visible(visible: boolean): void;
+    set_atom_style(indices: Uint32Array, radius?: number, color?: Float32Array): void;
+    clear_atom_styles(): void;
+    atom_styles(): Uint8Array; // JSON AtomStyleInfo[]
+    set_measurement_callback(on_measurement?: (measurement: MeasurementInfo) => void): void;
+    measurement(): MeasurementInfo | null;
+    add_plane(indices: Uint32Array): number;
+    plane(index: number): PlaneInfo;
+    clear_planes(): void;
+    measure_plane_angle(plane_1: number, plane_2: number): MeasurementInfo;
+    measure_plane_bond_angle(plane: number, atom_1: number, atom_2: number): MeasurementInfo;
+    load_volume_cube(
+        data: Uint8Array,
+        on_progress?: (stage: string, done: number, total: number) => void,
+        is_cancelled?: () => boolean
+    ): Promise<void>;
+    volume_bounds(): Float64Array | undefined; // [min_x, min_y, min_z, max_x, max_y, max_z] in Angstroms
+    set_slice_plane(px: number, py: number, pz: number, nx: number, ny: number, nz: number): void;
+    set_slice_colormap(name: string): void;
+    set_slice_opacity(opacity: number): void;
+    hide_slice(): void;
+    set_isosurface(
+        isovalue: number,
+        opacity: number,
+        on_progress?: (stage: string, done: number, total: number) => void,
+        is_cancelled?: () => boolean
+    ): Promise<void>;
+    hide_isosurface(): Promise<void>;
+    isosurface_triangles(): number;
+    render(): void;
+}
+
+interface GridCellInfo {
+    label: string;
+    x: number;
+    y: number;
+    width: number;
+    height: number;
+}
+
+interface MoleculeGridVisualizerInstance {
+    add_structure(data: Uint8Array, label: string): Promise<void>;
+    resize(width: number, height: number): void;
+    scale_scene(factor: number): void;
+    rotate_scene(pitch: number, yaw: number, roll: number): void;
+    cells(): GridCellInfo[];
+    cell_at(x: number, y: number): number | undefined;
+    click(x: number, y: number): boolean;
+    expanded(): number | undefined;
+    set_expanded(index?: number): void;
     render(): void;
 }
 
+interface ThumbnailRendererInstance {
+    thumbnail(data: Uint8Array, size?: number): Promise<Uint8Array>;
+    cached_thumbnail(hash: string, size?: number): Uint8Array | undefined;
+    cache_capacity: number;
+    clear_cache(): void;
+}
+
 interface WasmModule {
     default: (wasm_url: URL) => Promise<void>;
     MolecularVisualizer: {
-        create(canvas: HTMLCanvasElement, data: Uint8Array): Promise<MolecularVisualizerInstance>;
+        create(
+            canvas: HTMLCanvasElement,
+            data: Uint8Array,
+            on_progress?: (stage: string, done: number, total: number) => void,
+            is_cancelled?: () => boolean,
+            style?: string
+        ): Promise<MolecularVisualizerInstance>;
+        set_profiling_callback(on_profile?: (spans: string) => void): void;
+        profiling_enabled(): boolean;
+        export_formats(): Uint8Array;
+        templates(): Uint8Array;
+    };
+    MoleculeGridVisualizer: {
+        create(canvas: HTMLCanvasElement): Promise<MoleculeGridVisualizerInstance>;
+    };
+    ThumbnailRenderer: {
+        create(): Promise<ThumbnailRendererInstance>;
+        structure_hash(data: Uint8Array): string;
     };
 }
 
+interface AutosaveSnapshot {
+    time: number;
+    edits: { description: string; count: number }[];
+}
+
+// Unsaved edits are written at most this often and when the page is hidden
+const autosave_interval_ms = 10_000;
+const autosave_prefix = 'molecular-visualizer:autosave:';
+// Snapshots left in the local storage of the browser expire after this time, only the newest ones are kept
+const autosave_max_age_ms = 30 * 24 * 60 * 60 * 1000;
+const autosave_max_snapshots = 20;
+
 let wasm_module: WasmModule | null = null;
+// Incremented on every run; a build started by an older run is cancelled.
+let current_run = 0;
 
 function supportedTypes(): string[] {
-    return ['mircmd:chemistry:atomic_coordinates'];
+    return node_kinds.map((kind) => `mircmd:chemistry:${kind}`);
 }
 
 async function run(ctx: ProgramPluginContext, data: Uint8Array): Promise<void> {
+    const run_id = ++current_run;
     clear_root(ctx.root);
 
     if (!wasm_module) {
@@ -40,8 +411,82 @@ async function run(ctx: ProgramPluginContext, data: Uint8Array): Promise<void> {
     const canvas = create_canvas(ctx.root);
     const container = canvas.parentElement as HTMLElement;
     const overlay = create_overlay(container);
-    const visualizer = await wasm_module.MolecularVisualizer.create(canvas, data);
+    const selection_box = create_selection_box(container);
+    const progress = create_progress(container);
+    const diagnostics = create_panel(container, 'right');
+    const clash_panel = create_panel(container, 'left');
+    const kind = node_kind(data);
+    let visualizer: MolecularVisualizerInstance;
+    try {
+        visualizer = await wasm_module.MolecularVisualizer.create(
+            canvas,
+            node_structure(kind, data),
+            (stage, done, total) => update_progress(progress, stage, done, total),
+            () => run_id !== current_run
+        );
+    } catch (error) {
+        if (run_id !== current_run) {
+            return;
+        }
+        throw error;
+    } finally {
+        progress.remove();
+    }
     visualizer.render();
+    if (kind === 'volume_cube') {
+        const cube_progress = create_progress(container);
+        try {
+            await visualizer.load_volume_cube(
+                data,
+                (stage, done, total) => update_progress(cube_progress, stage, done, total),
+                () => run_id !== current_run
+            );
+        } catch (error) {
+            if (run_id !== current_run) {
+                return;
+            }
+            throw error;
+        } finally {
+            cube_progress.remove();
+        }
+        create_volume_toolbar(container, visualizer);
+    } else if (kind === 'trajectory') {
+        visualizer.load_frames(data);
+    } else if (kind === 'vibrational_modes') {
+        visualizer.load_vibrational_modes(data);
+    }
+    update_clash_panel(clash_panel, visualizer);
+    const update_legend = create_element_legend(container, visualizer);
+    const templates = JSON.parse(new TextDecoder().decode(wasm_module.MolecularVisualizer.templates()));
+    const edit_hydrogens = create_builder_toolbar(container, visualizer, templates as TemplateInfo[]);
+    const key = structure_key(data);
+    visualizer.set_edit_callback((event) => {
+        update_legend();
+        update_clash_panel(clash_panel, visualizer);
+        const detail: EditEventDetail = { ...event, structure: key };
+        ctx.host.dispatchEvent(new CustomEvent(EDIT_EVENT, { detail, bubbles: true, composed: true }));
+    });
+    const on_hydrogens = (event: Event): void => {
+        if (!ctx.host.isConnected || run_id !== current_run) {
+            window.removeEventListener(HYDROGENS_EVENT, on_hydrogens);
+            return;
+        }
+        const detail = (event as CustomEvent<HydrogensEventDetail>).detail;
+        if (detail.structure === key) {
+            void edit_hydrogens(detail.edit);
+        }
+    };
+    window.addEventListener(HYDROGENS_EVENT, on_hydrogens);
+    const formats = JSON.parse(new TextDecoder().decode(wasm_module.MolecularVisualizer.export_formats()));
+    create_export_menu(container, canvas, visualizer, formats as ExportFormat[]);
+    visualizer.set_measurement_callback((measurement) =>
+        console.info(`Measured ${measurement.kind} of atoms ${measurement.atoms.join('-')}: ${measurement.text}`)
+    );
+    // Everything pickable under the cursor is clickable
+    visualizer.set_object_hover_callback((object) => {
+        canvas.style.cursor = object ? 'pointer' : '';
+    });
+    void setup_autosave(ctx.storage ?? local_storage(), visualizer, container, key, () => run_id !== current_run);
 
     // Handle resize
     const resize_observer = new ResizeObserver(() => {
@@ -58,20 +503,75 @@ async function run(ctx: ProgramPluginContext, data: Uint8Array): Promise<void> {
     });
     resize_observer.observe(canvas);
 
-    // Handle mouse rotation
+    // Handle arcball rotation with inertia,
+    // Shift + drag selects atoms in a rectangle (Ctrl/Cmd adds them to the selection),
+    // Alt + drag with two bonded atoms selected rotates the side of the second atom about the bond,
+    // Ctrl/Cmd + drag with one atom selected moves it (with its symmetry images after detect_symmetry)
     let is_dragging = false;
-    let has_dragged = false;
-    let is_async_busy = false;
+    let is_selecting_rect = false;
+    let torsion_bond: Uint32Array | null = null;
+    let moved_atom: number | null = null;
     let last_mouse_x = 0;
     let last_mouse_y = 0;
-    const rotation_sensitivity = 0.5;
+    const torsion_sensitivity = 0.5;
+    let has_dragged = false;
+    let is_async_busy = false;
+    let rect_start_x = 0;
+    let rect_start_y = 0;
+
+    const canvas_position = (event: MouseEvent): [number, number] => {
+        const rect = canvas.getBoundingClientRect();
+        const dpr = window.devicePixelRatio || 1;
+        return [(event.clientX - rect.left) * dpr, (event.clientY - rect.top) * dpr];
+    };
+
+    // One loop drives both the inertial rotation and the playback of frames
+    let is_animating = false;
+    let update_playback = (): void => {};
+    const animate = (): void => {
+        is_animating = visualizer.animate(performance.now());
+        update_playback();
+        if (is_animating) {
+            requestAnimationFrame(animate);
+        }
+    };
+    const start_animation = (): void => {
+        if (!is_animating) {
+            is_animating = true;
+            requestAnimationFrame(animate);
+        }
+    };
+    if (visualizer.num_frames() > 1) {
+        update_playback = create_playback_toolbar(container, visualizer, start_animation);
+    }
+    if (kind === 'vibrational_modes') {
+        create_vibration_toolbar(container, visualizer, start_animation);
+    }
+
+    const end_rotation = (): void => {
+        if (is_dragging && !is_selecting_rect && !torsion_bond && moved_atom === null
+            && visualizer.end_rotation(performance.now())) {
+            start_animation();
+        }
+    };
 
     canvas.addEventListener('mousedown', (event: MouseEvent) => {
         if (event.button === 0) {
             is_dragging = true;
+            is_selecting_rect = event.shiftKey;
             has_dragged = false;
+            const selected = visualizer.get_selected_atoms();
+            torsion_bond = event.altKey && !is_selecting_rect && selected.length === 2 ? selected : null;
+            const is_moving = (event.ctrlKey || event.metaKey) && !is_selecting_rect && selected.length === 1;
+            moved_atom = is_moving ? selected[0] : null;
             last_mouse_x = event.clientX;
             last_mouse_y = event.clientY;
+            if (!is_selecting_rect && !torsion_bond && moved_atom === null) {
+                const [x, y] = canvas_position(event);
+                visualizer.begin_rotation(x, y, performance.now());
+            }
+            rect_start_x = event.clientX;
+            rect_start_y = event.clientY;
         }
     });
 
@@ -83,26 +583,90 @@ async function run(ctx: ProgramPluginContext, data: Uint8Array): Promise<void> {
                 const dpr = window.devicePixelRatio || 1;
                 const canvas_x = Math.floor((event.clientX - rect.left) * dpr);
                 const canvas_y = Math.floor((event.clientY - rect.top) * dpr);
-                await visualizer.toggle_atom_selection(canvas_x, canvas_y);
+                if (visualizer.builder_mode() !== undefined) {
+                    await visualizer.builder_click(canvas_x, canvas_y);
+                } else {
+                    await visualizer.toggle_atom_selection(canvas_x, canvas_y);
+                }
+            } catch (error) {
+                console.warn(error);
             } finally {
                 is_async_busy = false;
             }
         }
     });
 
+    // Delete or Backspace deletes the selected atoms in the builder mode
+    canvas.tabIndex = 0;
+    canvas.addEventListener('keydown', async (event: KeyboardEvent) => {
+        if ((event.key !== 'Delete' && event.key !== 'Backspace') || visualizer.builder_mode() === undefined) {
+            return;
+        }
+        const selected = visualizer.get_selected_atoms();
+        if (selected.length > 0 && !is_async_busy) {
+            event.preventDefault();
+            is_async_busy = true;
+            try {
+                await visualizer.delete_atoms(selected);
+            } catch (error) {
+                console.warn(error);
+            } finally {
+                is_async_busy = false;
+            }
+        }
+    });
+
+    // Double click flies to the selected atoms and dims the others, without a selection the atoms are restored
+    canvas.addEventListener('dblclick', () => {
+        const selected = visualizer.get_selected_atoms();
+        if (selected.length > 0) {
+            visualizer.focus_on(selected, true);
+            start_animation();
+        } else {
+            visualizer.clear_focus();
+        }
+    });
+
     canvas.addEventListener('mousemove', async (event: MouseEvent) => {
-        if (is_dragging) {
+        if (is_dragging && is_selecting_rect) {
             has_dragged = true;
-            const delta_x = event.clientX - last_mouse_x;
-            const delta_y = event.clientY - last_mouse_y;
-
+            const rect = canvas.getBoundingClientRect();
+            update_selection_box(
+                selection_box,
+                rect_start_x - rect.left,
+                rect_start_y - rect.top,
+                event.clientX - rect.left,
+                event.clientY - rect.top
+            );
+            overlay.style.display = 'none';
+        } else if (is_dragging && moved_atom !== null) {
+            has_dragged = true;
+            const dpr = window.devicePixelRatio || 1;
+            visualizer.drag_atom(
+                moved_atom,
+                (event.clientX - last_mouse_x) * dpr,
+                (event.clientY - last_mouse_y) * dpr
+            );
             last_mouse_x = event.clientX;
             last_mouse_y = event.clientY;
-
-            const yaw = delta_x * rotation_sensitivity;
-            const pitch = delta_y * rotation_sensitivity;
-
-            visualizer.rotate_scene(pitch, yaw, 0);
+            update_clash_panel(clash_panel, visualizer);
+            overlay.style.display = 'none';
+        } else if (is_dragging && torsion_bond) {
+            has_dragged = true;
+            const angle = (event.clientX - last_mouse_x) * torsion_sensitivity;
+            last_mouse_x = event.clientX;
+            try {
+                visualizer.rotate_bond(torsion_bond[0], torsion_bond[1], angle);
+                update_clash_panel(clash_panel, visualizer);
+            } catch (error) {
+                console.warn(error);
+                torsion_bond = null;
+            }
+            overlay.style.display = 'none';
+        } else if (is_dragging) {
+            has_dragged = true;
+            const [x, y] = canvas_position(event);
+            visualizer.drag_rotation(x, y, performance.now());
             overlay.style.display = 'none';
         } else if (!is_async_busy) {
             is_async_busy = true;
@@ -123,12 +687,37 @@ async function run(ctx: ProgramPluginContext, data: Uint8Array): Promise<void> {
 
     canvas.addEventListener('mouseup', (event: MouseEvent) => {
         if (event.button === 0) {
+            // Moved atoms and turned bonds may strain the structure or bring atoms too close
+            if (has_dragged && (moved_atom !== null || torsion_bond)) {
+                update_diagnostics(diagnostics, visualizer);
+            }
+            if (is_selecting_rect && has_dragged) {
+                const rect = canvas.getBoundingClientRect();
+                const dpr = window.devicePixelRatio || 1;
+                const x0 = Math.floor((rect_start_x - rect.left) * dpr);
+                const y0 = Math.floor((rect_start_y - rect.top) * dpr);
+                const x1 = Math.floor((event.clientX - rect.left) * dpr);
+                const y1 = Math.floor((event.clientY - rect.top) * dpr);
+                const additive = event.ctrlKey || event.metaKey;
+                // With Alt only the atoms seen in the rectangle are selected
+                if (event.altKey) {
+                    void visualizer.select_visible_rect(x0, y0, x1, y1, additive);
+                } else {
+                    visualizer.select_rect(x0, y0, x1, y1, additive);
+                }
+            }
+            end_rotation();
             is_dragging = false;
+            is_selecting_rect = false;
+            selection_box.style.display = 'none';
         }
     });
 
     canvas.addEventListener('mouseleave', () => {
+        end_rotation();
         is_dragging = false;
+        is_selecting_rect = false;
+        selection_box.style.display = 'none';
         overlay.style.display = 'none';
     });
 
@@ -143,6 +732,210 @@ async function run(ctx: ProgramPluginContext, data: Uint8Array): Promise<void> {
     }, { passive: false });
 }
 
+function local_storage(): PluginStorage {
+    prune_autosaves(Date.now());
+    return {
+        get: (key) => window.localStorage.getItem(key),
+        set: (key, value) => {
+            try {
+                window.localStorage.setItem(key, value);
+            } catch (error) {
+                console.warn('Failed to autosave edits:', error);
+            }
+        },
+        remove: (key) => window.localStorage.removeItem(key),
+    };
+}
+
+// Removes the autosaved snapshots left in the local storage of the browser that expired or exceed the most kept,
+// the oldest first. Malformed snapshots are removed too.
+function prune_autosaves(now: number): void {
+    try {
+        const snapshots: { key: string; time: number }[] = [];
+        for (let i = 0; i < window.localStorage.length; i++) {
+            const key = window.localStorage.key(i);
+            if (!key?.startsWith(autosave_prefix)) {
+                continue;
+            }
+            let time = -Infinity;
+            try {
+                time = (JSON.parse(window.localStorage.getItem(key) ?? '') as AutosaveSnapshot).time;
+            } catch {
+                // Removed below
+            }
+            snapshots.push({ key, time: Number.isFinite(time) ? time : -Infinity });
+        }
+        snapshots.sort((a, b) => b.time - a.time);
+        snapshots.forEach(({ key, time }, index) => {
+            if (index >= autosave_max_snapshots || now - time > autosave_max_age_ms) {
+                window.localStorage.removeItem(key);
+            }
+        });
+    } catch (error) {
+        console.warn('Failed to remove old autosaved edits:', error);
+    }
+}
+
+// Offers to restore the edits autosaved for the structure, then keeps autosaving new edits until
+// the visualizer is replaced by another run. The snapshot is removed once it is restored or discarded,
+// and when the host reports the structure as saved.
+async function setup_autosave(
+    storage: PluginStorage,
+    visualizer: MolecularVisualizerInstance,
+    container: HTMLElement,
+    structure: string,
+    is_stale: () => boolean
+): Promise<void> {
+    const stored_key = autosave_prefix + visualizer.autosave_key();
+    const stored = await storage.get(stored_key);
+    if (stored && !is_stale()) {
+        if (await prompt_restore(container, stored)) {
+            try {
+                await visualizer.restore_autosave(stored);
+                await storage.remove(stored_key);
+            } catch (error) {
+                console.warn('Failed to restore autosaved edits:', error);
+            }
+        } else {
+            await storage.remove(stored_key);
+        }
+    }
+    if (is_stale()) {
+        return;
+    }
+    visualizer.set_autosave_callback((key, snapshot) => void storage.set(autosave_prefix + key, snapshot));
+
+    const on_saved = (event: Event): void => {
+        if (is_stale()) {
+            autosave();
+            return;
+        }
+        if ((event as CustomEvent<SavedEventDetail>).detail.structure === structure) {
+            // The saved structure gets a new autosave key, the snapshot under the old one is obsolete
+            void storage.remove(autosave_prefix + visualizer.autosave_key());
+            visualizer.mark_saved();
+        }
+    };
+    const autosave = (): void => {
+        if (is_stale()) {
+            window.clearInterval(timer);
+            document.removeEventListener('visibilitychange', on_hidden);
+            window.removeEventListener('pagehide', autosave);
+            window.removeEventListener(SAVED_EVENT, on_saved);
+            return;
+        }
+        try {
+            visualizer.autosave(Date.now());
+        } catch (error) {
+            console.warn('Failed to autosave edits:', error);
+        }
+    };
+    const on_hidden = (): void => {
+        if (document.visibilityState === 'hidden') {
+            autosave();
+        }
+    };
+    const timer = window.setInterval(autosave, autosave_interval_ms);
+    document.addEventListener('visibilitychange', on_hidden);
+    window.addEventListener('pagehide', autosave);
+    window.addEventListener(SAVED_EVENT, on_saved);
+}
+
+// Asks whether to restore the autosaved edits, resolves to true for "Restore" and false for "Discard".
+function prompt_restore(container: HTMLElement, stored: string): Promise<boolean> {
+    let summary = 'Unsaved edits of this structure were found.';
+    try {
+        const snapshot = JSON.parse(stored) as AutosaveSnapshot;
+        const count = snapshot.edits.reduce((sum, edit) => sum + edit.count, 0);
+        summary = `Unsaved edits (${count}) from ${new Date(snapshot.time).toLocaleString()} were found.`;
+    } catch {
+        // The visualizer reports a malformed snapshot when it is restored
+    }
+
+    const prompt = document.createElement('div');
+    prompt.style.position = 'absolute';
+    prompt.style.left = '50%';
+    prompt.style.top = '12px';
+    prompt.style.transform = 'translateX(-50%)';
+    prompt.style.backgroundColor = '#444444DD';
+    prompt.style.color = '#D8D8D8';
+    prompt.style.padding = '8px 12px';
+    prompt.style.borderRadius = '6px';
+    prompt.style.fontSize = '13px';
+    prompt.style.fontFamily = 'system-ui, -apple-system, sans-serif';
+    prompt.style.display = 'flex';
+    prompt.style.gap = '8px';
+    prompt.style.alignItems = 'center';
+    prompt.style.zIndex = '1001';
+
+    const text = document.createElement('span');
+    text.textContent = summary;
+    prompt.appendChild(text);
+
+    return new Promise((resolve) => {
+        for (const [label, restore] of [['Restore', true], ['Discard', false]] as const) {
+            const button = document.createElement('button');
+            button.textContent = label;
+            button.addEventListener('click', () => {
+                prompt.remove();
+                resolve(restore);
+            });
+            prompt.appendChild(button);
+        }
+        container.appendChild(prompt);
+    });
+}
+
+// Key of the structure in the DOM events of its plugins: FNV-1a of the data they were opened with, so the plugins of
+// the same structure agree on it and ignore the events of other structures
+export function structure_key(data: Uint8Array): string {
+    let hash = 0x811c9dc5;
+    for (const byte of data) {
+        hash = Math.imul(hash ^ byte, 0x01000193);
+    }
+    return (hash >>> 0).toString(16).padStart(8, '0');
+}
+
+// Kind of the node from the first field of its data as written by the importer: trajectories are arrays, volume
+// cubes start with their comments and vibrational modes with the frequencies. Large structures are not parsed twice.
+function node_kind(data: Uint8Array): NodeKind {
+    const start = new TextDecoder().decode(data.subarray(0, 64));
+    const match = /^\s*(?:(\[)|\{\s*"(\w+)")/.exec(start);
+    if (match?.[1]) {
+        return 'trajectory';
+    }
+    switch (match?.[2]) {
+        case 'comment1':
+            return 'volume_cube';
+        case 'frequencies':
+            return 'vibrational_modes';
+        default:
+            return 'atomic_coordinates';
+    }
+}
+
+// Structure the visualizer is created with: the first frame of a trajectory, the equilibrium structure of
+// vibrational modes or one without atoms for a volume cube
+function node_structure(kind: NodeKind, data: Uint8Array): Uint8Array {
+    const decode = (): unknown => JSON.parse(new TextDecoder().decode(data));
+    const encode = (structure: unknown): Uint8Array => new TextEncoder().encode(JSON.stringify(structure));
+    switch (kind) {
+        case 'atomic_coordinates':
+            return data;
+        case 'volume_cube':
+            return empty_structure;
+        case 'trajectory':
+            return encode((decode() as unknown[])[0]);
+        case 'vibrational_modes': {
+            const structure = (decode() as { atomic_coordinates?: unknown }).atomic_coordinates;
+            if (!structure) {
+                throw new Error('The vibrational modes have no structure');
+            }
+            return encode(structure);
+        }
+    }
+}
+
 function clear_root(root: ShadowRoot): void {
     root.textContent = '';
 }
@@ -189,6 +982,606 @@ function create_overlay(container: HTMLElement): HTMLDivElement {
     return overlay;
 }
 
+function create_selection_box(container: HTMLElement): HTMLDivElement {
+    const selection_box = document.createElement('div');
+    selection_box.style.position = 'absolute';
+    selection_box.style.border = '1px dashed #D8D8D8';
+    selection_box.style.backgroundColor = '#D8D8D822';
+    selection_box.style.pointerEvents = 'none';
+    selection_box.style.display = 'none';
+    selection_box.style.zIndex = '999';
+
+    container.appendChild(selection_box);
+    return selection_box;
+}
+
+function update_selection_box(selection_box: HTMLDivElement, x0: number, y0: number, x1: number, y1: number): void {
+    selection_box.style.left = `${Math.min(x0, x1)}px`;
+    selection_box.style.top = `${Math.min(y0, y1)}px`;
+    selection_box.style.width = `${Math.abs(x1 - x0)}px`;
+    selection_box.style.height = `${Math.abs(y1 - y0)}px`;
+    selection_box.style.display = 'block';
+}
+
+function create_panel(container: HTMLElement, corner: 'left' | 'right'): HTMLDivElement {
+    const panel = document.createElement('div');
+    panel.style.position = 'absolute';
+    panel.style[corner] = '8px';
+    panel.style.bottom = '8px';
+    panel.style.backgroundColor = '#44444499';
+    panel.style.color = '#D8D8D8';
+    panel.style.padding = '6px 10px';
+    panel.style.borderRadius = '6px';
+    panel.style.fontSize = '12px';
+    panel.style.fontFamily = 'system-ui, -apple-system, sans-serif';
+    panel.style.pointerEvents = 'none';
+    panel.style.display = 'none';
+    panel.style.whiteSpace = 'pre';
+    panel.style.zIndex = '1000';
+
+    container.appendChild(panel);
+    return panel;
+}
+
+// Playback of the frames of a trajectory: stepping, playing and pausing, the slider jumps to a frame and the number
+// input sets the frames per second. Returns a function that updates the toolbar while the frames are played.
+function create_playback_toolbar(
+    container: HTMLElement,
+    visualizer: MolecularVisualizerInstance,
+    start_animation: () => void
+): () => void {
+    const toolbar = document.createElement('div');
+    toolbar.style.position = 'absolute';
+    toolbar.style.left = '50%';
+    toolbar.style.bottom = '8px';
+    toolbar.style.transform = 'translateX(-50%)';
+    toolbar.style.backgroundColor = '#44444499';
+    toolbar.style.color = '#D8D8D8';
+    toolbar.style.padding = '4px 6px';
+    toolbar.style.borderRadius = '6px';
+    toolbar.style.fontSize = '12px';
+    toolbar.style.fontFamily = 'system-ui, -apple-system, sans-serif';
+    toolbar.style.display = 'flex';
+    toolbar.style.gap = '6px';
+    toolbar.style.alignItems = 'center';
+    toolbar.style.zIndex = '1000';
+
+    const num_frames = visualizer.num_frames();
+    const previous = document.createElement('button');
+    previous.textContent = '\u23EE';
+    previous.title = 'Previous frame';
+    const play = document.createElement('button');
+    const next = document.createElement('button');
+    next.textContent = '\u23ED';
+    next.title = 'Next frame';
+    const slider = document.createElement('input');
+    slider.type = 'range';
+    slider.min = '0';
+    slider.max = String(num_frames - 1);
+    slider.step = '1';
+    slider.style.width = '120px';
+    const counter = document.createElement('span');
+    counter.style.minWidth = '60px';
+    const frame_rate = document.createElement('input');
+    frame_rate.type = 'number';
+    frame_rate.min = '1';
+    frame_rate.max = '120';
+    frame_rate.value = String(visualizer.frame_rate());
+    frame_rate.title = 'Frames per second';
+    frame_rate.style.width = '44px';
+
+    const update = (): void => {
+        const frame = Math.round(visualizer.frame());
+        slider.value = String(frame);
+        counter.textContent = `${frame + 1} / ${num_frames}`;
+        play.textContent = visualizer.is_playing() ? '\u23F8' : '\u25B6';
+        play.title = visualizer.is_playing() ? 'Pause' : 'Play';
+    };
+    const apply = (action: () => void): void => {
+        try {
+            action();
+        } catch (error) {
+            console.warn(error);
+        }
+        update();
+    };
+    previous.addEventListener('click', () => apply(() => visualizer.step_frame(-1)));
+    next.addEventListener('click', () => apply(() => visualizer.step_frame(1)));
+    play.addEventListener('click', () =>
+        apply(() => {
+            if (visualizer.is_playing()) {
+                visualizer.pause();
+            } else {
+                visualizer.play();
+                start_animation();
+            }
+        })
+    );
+    slider.addEventListener('input', () => apply(() => visualizer.set_frame(Number(slider.value))));
+    frame_rate.addEventListener('change', () => apply(() => visualizer.set_frame_rate(Number(frame_rate.value))));
+
+    update();
+    toolbar.append(previous, play, next, slider, counter, frame_rate);
+    container.appendChild(toolbar);
+    return update;
+}
+
+// Normal modes with their wavenumbers, imaginary ones with "i": the chosen mode is played or stopped, the slider
+// sets the largest displacement of an atom and the arrows show the displacements.
+function create_vibration_toolbar(
+    container: HTMLElement,
+    visualizer: MolecularVisualizerInstance,
+    start_animation: () => void
+): void {
+    const toolbar = document.createElement('div');
+    toolbar.style.position = 'absolute';
+    toolbar.style.left = '50%';
+    toolbar.style.bottom = '8px';
+    toolbar.style.transform = 'translateX(-50%)';
+    toolbar.style.backgroundColor = '#44444499';
+    toolbar.style.color = '#D8D8D8';
+    toolbar.style.padding = '4px 6px';
+    toolbar.style.borderRadius = '6px';
+    toolbar.style.fontSize = '12px';
+    toolbar.style.fontFamily = 'system-ui, -apple-system, sans-serif';
+    toolbar.style.display = 'flex';
+    toolbar.style.gap = '6px';
+    toolbar.style.alignItems = 'center';
+    toolbar.style.zIndex = '1000';
+
+    const mode = document.createElement('select');
+    visualizer.vibrational_frequencies().forEach((frequency, index) => {
+        const wavenumber = `${Math.abs(frequency).toFixed(1)}${frequency < 0 ? 'i' : ''}`;
+        mode.add(new Option(`${index + 1}: ${wavenumber} cm\u207B\u00B9`, String(index)));
+    });
+    const play = document.createElement('button');
+    const amplitude = document.createElement('input');
+    amplitude.type = 'range';
+    amplitude.min = '0.05';
+    amplitude.max = '1';
+    amplitude.step = '0.05';
+    amplitude.value = String(visualizer.vibration_amplitude());
+    amplitude.title = 'Largest displacement of an atom, \u00C5';
+    amplitude.style.width = '80px';
+    const arrows_label = document.createElement('label');
+    const arrows = document.createElement('input');
+    arrows.type = 'checkbox';
+    arrows.checked = visualizer.vibration_arrows();
+    arrows_label.append(arrows, ' Arrows');
+
+    const update = (): void => {
+        play.textContent = visualizer.is_vibrating() ? '\u23F9' : '\u25B6';
+        play.title = visualizer.is_vibrating() ? 'Stop' : 'Play';
+    };
+    const apply = (action: () => void): void => {
+        try {
+            action();
+        } catch (error) {
+            console.warn(error);
+        }
+        update();
+    };
+    mode.addEventListener('change', () => apply(() => visualizer.set_vibration_mode(Number(mode.value))));
+    play.addEventListener('click', () =>
+        apply(() => {
+            if (visualizer.is_vibrating()) {
+                visualizer.stop_vibration();
+            } else {
+                visualizer.play_vibration();
+                start_animation();
+            }
+        })
+    );
+    amplitude.addEventListener('input', () => apply(() => visualizer.set_vibration_amplitude(Number(amplitude.value))));
+    arrows.addEventListener('change', () => apply(() => visualizer.set_vibration_arrows(arrows.checked)));
+
+    update();
+    toolbar.append(mode, play, amplitude, arrows_label);
+    container.appendChild(toolbar);
+}
+
+// Isosurface of the volume cube at the isovalue with its opacity, shown at first, and the slice plane perpendicular
+// to an axis at a fraction of the box of the cube with its colormap and opacity.
+function create_volume_toolbar(container: HTMLElement, visualizer: MolecularVisualizerInstance): void {
+    const toolbar = document.createElement('div');
+    toolbar.style.position = 'absolute';
+    toolbar.style.left = '8px';
+    toolbar.style.top = '40px';
+    toolbar.style.backgroundColor = '#44444499';
+    toolbar.style.color = '#D8D8D8';
+    toolbar.style.padding = '4px 6px';
+    toolbar.style.borderRadius = '6px';
+    toolbar.style.fontSize = '12px';
+    toolbar.style.fontFamily = 'system-ui, -apple-system, sans-serif';
+    toolbar.style.display = 'flex';
+    toolbar.style.gap = '6px';
+    toolbar.style.alignItems = 'center';
+    toolbar.style.zIndex = '1000';
+
+    const checkbox = (text: string, checked: boolean): [HTMLLabelElement, HTMLInputElement] => {
+        const label = document.createElement('label');
+        const input = document.createElement('input');
+        input.type = 'checkbox';
+        input.checked = checked;
+        label.append(input, ` ${text}`);
+        return [label, input];
+    };
+    const slider = (title: string, value: number): HTMLInputElement => {
+        const input = document.createElement('input');
+        input.type = 'range';
+        input.min = '0';
+        input.max = '1';
+        input.step = '0.05';
+        input.value = String(value);
+        input.title = title;
+        input.style.width = '60px';
+        return input;
+    };
+
+    const [surface_label, surface] = checkbox('Isosurface', true);
+    const isovalue = document.createElement('input');
+    isovalue.type = 'number';
+    isovalue.min = '0.001';
+    isovalue.step = '0.01';
+    isovalue.value = '0.05';
+    isovalue.title = 'Isovalue, the negative lobe is drawn at the opposite value';
+    isovalue.style.width = '60px';
+    const surface_opacity = slider('Opacity of the isosurface', 1);
+    // The lobes of a large cube are built in chunks, the inputs are disabled until they are ready
+    const surface_inputs = [surface, isovalue, surface_opacity];
+    const update_surface = async (): Promise<void> => {
+        if (surface.disabled) {
+            return;
+        }
+        const progress = create_progress(container);
+        surface_inputs.forEach((input) => (input.disabled = true));
+        try {
+            if (surface.checked) {
+                await visualizer.set_isosurface(
+                    Number(isovalue.value),
+                    Number(surface_opacity.value),
+                    (stage, done, total) => update_progress(progress, stage, done, total),
+                    () => !container.isConnected
+                );
+            } else {
+                await visualizer.hide_isosurface();
+            }
+            surface_label.title = `${visualizer.isosurface_triangles()} triangles`;
+        } catch (error) {
+            console.warn(error);
+        } finally {
+            progress.remove();
+            surface_inputs.forEach((input) => (input.disabled = false));
+        }
+    };
+    for (const input of surface_inputs) {
+        input.addEventListener('change', () => void update_surface());
+    }
+
+    const [slice_label, slice] = checkbox('Slice', false);
+    const axis = document.createElement('select');
+    for (const name of ['X', 'Y', 'Z']) {
+        axis.add(new Option(name, name));
+    }
+    axis.value = 'Z';
+    const position = slider('Position of the slice in the box of the cube', 0.5);
+    position.step = '0.01';
+    const colormap = document.createElement('select');
+    for (const name of slice_colormaps) {
+        colormap.add(new Option(name.replaceAll('_', ' '), name));
+    }
+    const slice_opacity = slider('Opacity of the slice', 0.8);
+    const update_slice = (): void => {
+        const bounds = visualizer.volume_bounds();
+        try {
+            if (!slice.checked || !bounds) {
+                visualizer.hide_slice();
+                return;
+            }
+            const a = ['X', 'Y', 'Z'].indexOf(axis.value);
+            const point = [0, 1, 2].map((c) => (bounds[c] + bounds[c + 3]) / 2);
+            point[a] = bounds[a] + (bounds[a + 3] - bounds[a]) * Number(position.value);
+            const normal = [0, 1, 2].map((c) => (c === a ? 1 : 0));
+            visualizer.set_slice_colormap(colormap.value);
+            visualizer.set_slice_opacity(Number(slice_opacity.value));
+            visualizer.set_slice_plane(point[0], point[1], point[2], normal[0], normal[1], normal[2]);
+        } catch (error) {
+            console.warn(error);
+        }
+    };
+    slice.addEventListener('change', update_slice);
+    for (const input of [axis, colormap, slice_opacity]) {
+        input.addEventListener('change', update_slice);
+    }
+    // The slice follows the slider while it is dragged
+    position.addEventListener('input', update_slice);
+
+    void update_surface();
+    toolbar.append(surface_label, isovalue, surface_opacity, slice_label, axis, position, colormap, slice_opacity);
+    container.appendChild(toolbar);
+}
+
+// Elements of the structure with the numbers of atoms, clicking an element shows or hides all its atoms.
+// Dummy atoms, point charges and ghost atoms (atomic numbers below 1) are shown or hidden together.
+// Returns a function that updates the legend after the atoms have changed.
+function create_element_legend(container: HTMLElement, visualizer: MolecularVisualizerInstance): () => void {
+    const legend = document.createElement('div');
+    legend.style.position = 'absolute';
+    legend.style.right = '8px';
+    legend.style.top = '8px';
+    legend.style.backgroundColor = '#44444499';
+    legend.style.padding = '4px 6px';
+    legend.style.borderRadius = '6px';
+    legend.style.fontSize = '12px';
+    legend.style.fontFamily = 'system-ui, -apple-system, sans-serif';
+    legend.style.display = 'flex';
+    legend.style.flexDirection = 'column';
+    legend.style.zIndex = '1000';
+
+    const update = (): void => {
+        const elements = JSON.parse(new TextDecoder().decode(visualizer.elements())) as ElementInfo[];
+        legend.textContent = '';
+        legend.style.display = elements.length > 0 ? 'flex' : 'none';
+        for (const element of elements) {
+            const entry = document.createElement('div');
+            entry.textContent = `${element.symbol} ${element.count}`;
+            entry.title = `${element.visible} of ${element.count} shown`;
+            entry.style.padding = '1px 4px';
+            entry.style.cursor = 'pointer';
+            entry.style.color = element.visible > 0 ? '#D8D8D8' : '#808080';
+            entry.style.textDecoration = element.visible > 0 ? 'none' : 'line-through';
+            entry.addEventListener('click', () => {
+                try {
+                    if (element.number < 1) {
+                        visualizer.set_pseudo_atoms_visible(element.visible === 0);
+                    } else {
+                        visualizer.set_element_visible(element.symbol, element.visible === 0);
+                    }
+                } catch (error) {
+                    console.warn(error);
+                }
+                update();
+            });
+            legend.appendChild(entry);
+        }
+    };
+    update();
+    container.appendChild(legend);
+    return update;
+}
+
+// Builder mode with the element of the added atoms: clicking the background adds an atom, clicking two atoms
+// bonds them or removes their bond, or with a fragment chosen clicking an atom attaches the fragment to it, Delete
+// removes the selected atoms. The +H and −H buttons add the missing hydrogens and strip them, as does the returned
+// function.
+function create_builder_toolbar(
+    container: HTMLElement,
+    visualizer: MolecularVisualizerInstance,
+    templates: TemplateInfo[]
+): (edit: HydrogenEdit) => Promise<void> {
+    const toolbar = document.createElement('div');
+    toolbar.style.position = 'absolute';
+    toolbar.style.left = '8px';
+    toolbar.style.top = '8px';
+    toolbar.style.backgroundColor = '#44444499';
+    toolbar.style.color = '#D8D8D8';
+    toolbar.style.padding = '4px 6px';
+    toolbar.style.borderRadius = '6px';
+    toolbar.style.fontSize = '12px';
+    toolbar.style.fontFamily = 'system-ui, -apple-system, sans-serif';
+    toolbar.style.display = 'flex';
+    toolbar.style.gap = '6px';
+    toolbar.style.alignItems = 'center';
+    toolbar.style.zIndex = '1000';
+
+    const label = document.createElement('label');
+    label.title = 'Click the background to add an atom, two atoms to bond them or remove their bond '
+        + '(or attach the fragment to an atom), Delete removes the selected atoms';
+    const checkbox = document.createElement('input');
+    checkbox.type = 'checkbox';
+    label.append(checkbox, ' Build');
+    const element = document.createElement('select');
+    for (const symbol of builder_elements) {
+        element.add(new Option(symbol, symbol));
+    }
+    const fragment = document.createElement('select');
+    fragment.title = 'Functional group or ligand attached to the clicked atom, replacing a terminal hydrogen';
+    fragment.add(new Option('Bond', ''));
+    for (const template of templates.filter((template) => template.attachable)) {
+        fragment.add(new Option(template.name.replaceAll('_', ' '), template.name));
+    }
+    const update = (): void => {
+        try {
+            visualizer.set_builder_mode(checkbox.checked ? element.value : undefined);
+            visualizer.set_builder_template(fragment.value || undefined);
+        } catch (error) {
+            console.warn(error);
+        }
+    };
+    checkbox.addEventListener('change', update);
+    for (const select of [element, fragment]) {
+        select.addEventListener('change', () => {
+            if (checkbox.checked) {
+                update();
+            }
+        });
+    }
+
+    const add_button = document.createElement('button');
+    add_button.textContent = '+H';
+    add_button.title = 'Add the missing hydrogens from the valences of the atoms';
+    const strip_button = document.createElement('button');
+    strip_button.textContent = '\u2212H';
+    strip_button.title = 'Strip the hydrogens of carbon and other nonmetals';
+    const edit_hydrogens = async (edit: HydrogenEdit): Promise<void> => {
+        if (add_button.disabled) {
+            return;
+        }
+        add_button.disabled = strip_button.disabled = true;
+        try {
+            const count = edit === 'add' ? await visualizer.add_hydrogens() : await visualizer.strip_hydrogens();
+            console.info(`${edit === 'add' ? 'Added' : 'Stripped'} ${count} hydrogens`);
+        } catch (error) {
+            console.warn(error);
+        } finally {
+            add_button.disabled = strip_button.disabled = false;
+        }
+    };
+    add_button.addEventListener('click', () => void edit_hydrogens('add'));
+    strip_button.addEventListener('click', () => void edit_hydrogens('strip'));
+
+    toolbar.append(label, element, fragment, add_button, strip_button);
+    container.appendChild(toolbar);
+    return edit_hydrogens;
+}
+
+// Context menu of the canvas saving the current frame or all frames of a trajectory in the export formats
+function create_export_menu(
+    container: HTMLElement,
+    canvas: HTMLCanvasElement,
+    visualizer: MolecularVisualizerInstance,
+    formats: ExportFormat[]
+): void {
+    const menu = document.createElement('div');
+    menu.style.position = 'absolute';
+    menu.style.backgroundColor = '#444444EE';
+    menu.style.color = '#D8D8D8';
+    menu.style.padding = '4px 0';
+    menu.style.borderRadius = '6px';
+    menu.style.fontSize = '12px';
+    menu.style.fontFamily = 'system-ui, -apple-system, sans-serif';
+    menu.style.display = 'none';
+    menu.style.zIndex = '1001';
+
+    const add_entry = (text: string, file_name: string, export_content: () => string): void => {
+        const entry = document.createElement('div');
+        entry.textContent = text;
+        entry.style.padding = '2px 12px';
+        entry.style.cursor = 'pointer';
+        entry.addEventListener('click', () => {
+            menu.style.display = 'none';
+            try {
+                download(export_content(), file_name);
+            } catch (error) {
+                console.warn(error);
+            }
+        });
+        menu.appendChild(entry);
+    };
+
+    canvas.addEventListener('contextmenu', (event: MouseEvent) => {
+        event.preventDefault();
+        menu.textContent = '';
+        const frame = Math.round(visualizer.frame()) + 1;
+        for (const format of formats) {
+            const label = format.name.toUpperCase();
+            add_entry(`Export frame as ${label}`, `frame_${frame}.${format.extension}`, () =>
+                visualizer.export_frame(format.name)
+            );
+        }
+        if (visualizer.num_frames() > 1) {
+            for (const format of formats.filter((format) => format.multi_frame)) {
+                const label = format.name.toUpperCase();
+                add_entry(`Export all frames as ${label}`, `frames.${format.extension}`, () =>
+                    visualizer.export_frames(format.name)
+                );
+            }
+        }
+        const rect = container.getBoundingClientRect();
+        menu.style.left = `${event.clientX - rect.left}px`;
+        menu.style.top = `${event.clientY - rect.top}px`;
+        menu.style.display = 'block';
+    });
+    document.addEventListener('mousedown', (event: MouseEvent) => {
+        if (!menu.contains(event.target as Node)) {
+            menu.style.display = 'none';
+        }
+    });
+    container.appendChild(menu);
+}
+
+function download(content: string, file_name: string): void {
+    const blob = new Blob([content], { type: 'text/plain' });
+    const url = URL.createObjectURL(blob);
+    const link = document.createElement('a');
+    link.href = url;
+    link.download = file_name;
+    link.click();
+    URL.revokeObjectURL(url);
+}
+
+// Clashes of the last edit and overlapping atoms of the structure, hidden while there are none
+function update_clash_panel(panel: HTMLDivElement, visualizer: MolecularVisualizerInstance): void {
+    const decode = (json: Uint8Array): ClashInfo[] => JSON.parse(new TextDecoder().decode(json)) as ClashInfo[];
+    const clashes = decode(visualizer.clashes());
+    const overlaps = decode(visualizer.covalent_clashes());
+    if (clashes.length === 0 && overlaps.length === 0) {
+        panel.style.display = 'none';
+        return;
+    }
+    const describe = (clash: ClashInfo, radii: string): string =>
+        `Atoms ${clash.atoms[0]}-${clash.atoms[1]}: ${clash.distance.toFixed(2)} Å ` +
+        `(${Math.round(clash.fraction * 100)}% of ${radii} radii)`;
+    const lines: string[] = [];
+    if (clashes.length > 0) {
+        lines.push(`Clashes: ${clashes.length}`, ...clashes.map((clash) => describe(clash, 'vdW')));
+    }
+    if (overlaps.length > 0) {
+        lines.push(`Overlapping atoms: ${overlaps.length}`, ...overlaps.map((clash) => describe(clash, 'covalent')));
+    }
+    panel.textContent = lines.join('\n');
+    // Orange of the halos of overlapping atoms, otherwise the warning color of clashing atoms
+    panel.style.color = overlaps.length > 0 ? '#FFB040' : '#FF80FF';
+    panel.style.display = 'block';
+}
+
+function update_diagnostics(diagnostics: HTMLDivElement, visualizer: MolecularVisualizerInstance): void {
+    let terms: EnergyTerms;
+    try {
+        terms = JSON.parse(new TextDecoder().decode(visualizer.energy_terms())) as EnergyTerms;
+    } catch (error) {
+        console.warn('Failed to evaluate the force field:', error);
+        diagnostics.style.display = 'none';
+        return;
+    }
+    const lines = [
+        `Bonds: ${terms.bond.toFixed(2)}`,
+        `Angles: ${terms.angle.toFixed(2)}`,
+        `Torsions: ${terms.torsion.toFixed(2)}`,
+        `Van der Waals: ${terms.vdw.toFixed(2)}`,
+        `Total: ${terms.total.toFixed(2)} kcal/mol`,
+        `Clashes: ${terms.clashes}`,
+    ];
+    const clashes = visualizer.new_clashes();
+    for (let i = 0; i < clashes.length; i += 2) {
+        lines.push(`New clash: atoms ${clashes[i]}-${clashes[i + 1]}`);
+    }
+    diagnostics.textContent = lines.join('\n');
+    diagnostics.style.color = clashes.length > 0 ? '#FF8080' : '#D8D8D8';
+    diagnostics.style.display = 'block';
+}
+
+function create_progress(container: HTMLElement): HTMLDivElement {
+    const progress = document.createElement('div');
+    progress.style.position = 'absolute';
+    progress.style.left = '50%';
+    progress.style.top = '50%';
+    progress.style.transform = 'translate(-50%, -50%)';
+    progress.style.color = '#D8D8D8';
+    progress.style.fontSize = '13px';
+    progress.style.fontFamily = 'system-ui, -apple-system, sans-serif';
+    progress.style.pointerEvents = 'none';
+    progress.style.zIndex = '1000';
+
+    container.appendChild(progress);
+    return progress;
+}
+
+function update_progress(progress: HTMLDivElement, stage: string, done: number, total: number): void {
+    const percent = total > 0 ? Math.floor((done / total) * 100) : 100;
+    progress.textContent = `Building ${stage}: ${percent}%`;
+}
+
 function update_overlay(
     overlay: HTMLDivElement,
     atom: AtomInfo | null,
@@ -223,6 +1616,42 @@ function update_overlay(
     overlay.style.top = `${top}px`;
 }
 
+// Rotating or zooming any of the visualizers applies the same view to the others.
+// Returns a function that unlinks them.
+export function link_views(visualizers: MolecularVisualizerInstance[]): () => void {
+    for (const source of visualizers) {
+        source.set_view_callback((view) => {
+            for (const target of visualizers) {
+                if (target !== source) {
+                    target.set_view(view);
+                }
+            }
+        });
+    }
+    return () => {
+        for (const visualizer of visualizers) {
+            visualizer.set_view_callback(undefined);
+        }
+    };
+}
+
+// Renders the canvas with the presentation effects and returns it as a PNG image.
+// The effects are turned off again afterwards.
+export function export_image(
+    visualizer: MolecularVisualizerInstance,
+    canvas: HTMLCanvasElement,
+    depth_of_field: boolean,
+    outline: boolean
+): Promise<Blob> {
+    // The image is taken in the same task as the render, before the canvas is presented and cleared
+    visualizer.set_effects(depth_of_field, outline);
+    const image = new Promise<Blob>((resolve, reject) => {
+        canvas.toBlob((blob) => (blob ? resolve(blob) : reject(new Error('Failed to export the image'))), 'image/png');
+    });
+    visualizer.set_effects(false, false);
+    return image;
+}
+
 // Export instantiate function compatible with current plugin loader
 export function instantiate(): {
     run: (ctx: ProgramPluginContext, data: Uint8Array) => Promise<void>;
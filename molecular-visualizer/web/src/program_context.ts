@@ -1,5 +1,14 @@
+// Key-value storage of the host that outlives the page, e.g. for autosaved edits.
+export interface PluginStorage {
+  get(key: string): Promise<string | null> | string | null;
+  set(key: string, value: string): Promise<void> | void;
+  remove(key: string): Promise<void> | void;
+}
+
 export interface ProgramPluginContext {
   host: HTMLElement;
   root: ShadowRoot;
   addStyles: (cssText: string) => void;
+  // The local storage of the browser is used if the host provides none
+  storage?: PluginStorage;
 }
@@ -5,5 +5,6 @@ export function instantiate() {
     "mircmd:chemistry:atomic_coordinates_group": "icons/atomic_coordinates_group.png",
     "mircmd:chemistry:unex": "icons/unex.png",
     "mircmd:chemistry:volume_cube": "icons/volume_cube.png",
+    "mircmd:chemistry:volume_cube_group": "icons/volume_cube.png",
   };
 }
@@ -0,0 +1,108 @@
+// Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
+// Licensed under the MIT License
+
+//! Bond search on the neighbor grid against the previous sweep and prune along X.
+//! Run with `cargo bench -p shared_lib`.
+
+use std::time::{Duration, Instant};
+
+use shared_lib::connectivity::{DEFAULT_BOND_TOLERANCE, find_bonds};
+use shared_lib::periodic_table::get_element_by_number;
+use shared_lib::types::AtomicCoordinates;
+
+const SPACING: f64 = 1.5;
+const REPEATS: u32 = 3;
+
+/// Carbon atoms on a simple cubic lattice of `n` points along each axis, every atom bonded to its six nearest.
+fn lattice(n: [usize; 3]) -> AtomicCoordinates {
+    let mut data = AtomicCoordinates {
+        atomic_num: vec![],
+        x: vec![],
+        y: vec![],
+        z: vec![],
+        connectivity: None,
+        charges: None,
+        chains: None,
+        cell: None,
+        isotopes: None,
+    };
+    for i in 0..n[0] {
+        for j in 0..n[1] {
+            for k in 0..n[2] {
+                data.atomic_num.push(6);
+                data.x.push(i as f64 * SPACING);
+                data.y.push(j as f64 * SPACING);
+                data.z.push(k as f64 * SPACING);
+            }
+        }
+    }
+    data
+}
+
+/// Bonds found by sorting atoms along X and comparing each atom with the following ones until
+/// they are farther along X than the longest bond.
+fn sweep_and_prune(data: &AtomicCoordinates) -> Vec<(usize, usize)> {
+    let mut atoms: Vec<(f64, f64, f64, f64, usize)> = (0..data.atomic_num.len())
+        .filter_map(|i| {
+            let element = get_element_by_number(data.atomic_num[i])?;
+            Some((data.x[i], data.y[i], data.z[i], element.covalent_radius, i))
+        })
+        .collect();
+    atoms.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let tol_factor = 1.0 + DEFAULT_BOND_TOLERANCE;
+    let max_radius = atoms.iter().map(|atom| atom.3).fold(0.0, f64::max);
+
+    let mut result = Vec::new();
+    for (i, &(xi, yi, zi, ri, origin_i)) in atoms.iter().enumerate() {
+        let limit = (ri + max_radius) * tol_factor;
+        for &(xj, yj, zj, rj, origin_j) in &atoms[i + 1..] {
+            if xj - xi > limit {
+                break;
+            }
+            let cutoff = (ri + rj) * tol_factor;
+            if (xj - xi).powi(2) + (yj - yi).powi(2) + (zj - zi).powi(2) < cutoff * cutoff {
+                result.push((origin_i.max(origin_j), origin_i.min(origin_j)));
+            }
+        }
+    }
+    result
+}
+
+fn best_of(mut f: impl FnMut() -> usize) -> (usize, Duration) {
+    (0..REPEATS)
+        .map(|_| {
+            let start = Instant::now();
+            let result = f();
+            (result, start.elapsed())
+        })
+        .min_by_key(|&(_, elapsed)| elapsed)
+        .unwrap()
+}
+
+fn main() {
+    let structures = [
+        ("cluster 28×28×28", [28, 28, 28]),
+        ("rod along X 2500×3×3", [2500, 3, 3]),
+        ("rod along Y 3×2500×3", [3, 2500, 3]),
+        ("sheet across X 1×150×150", [1, 150, 150]),
+    ];
+    println!(
+        "{:<26}{:>8}{:>8}{:>12}{:>12}{:>9}",
+        "structure", "atoms", "bonds", "sweep, ms", "grid, ms", "speedup"
+    );
+    for (name, size) in structures {
+        let data = lattice(size);
+        let (sweep_bonds, sweep) = best_of(|| sweep_and_prune(&data).len());
+        let (grid_bonds, grid) = best_of(|| find_bonds(&data, DEFAULT_BOND_TOLERANCE).len());
+        assert_eq!(sweep_bonds, grid_bonds, "{}: different bonds", name);
+        println!(
+            "{:<26}{:>8}{:>8}{:>12.1}{:>12.1}{:>8.1}×",
+            name,
+            data.atomic_num.len(),
+            grid_bonds,
+            sweep.as_secs_f64() * 1e3,
+            grid.as_secs_f64() * 1e3,
+            sweep.as_secs_f64() / grid.as_secs_f64()
+        );
+    }
+}
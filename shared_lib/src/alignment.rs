@@ -0,0 +1,175 @@
+// Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
+// Licensed under the MIT License
+
+//! Superposition of structures: the rotation and translation minimizing the RMSD of pairs of atoms (Kabsch
+//! alignment), found as the quaternion of the largest eigenvalue of Horn's 4×4 matrix.
+
+use crate::comparison::Tolerances;
+use crate::geometry;
+use crate::math::{Mat4, Quaternion};
+
+// Sweeps of the Jacobi eigenvalue solver if the RMSD has not converged earlier
+const MAX_SWEEPS: usize = 50;
+
+/// Superposition of a mobile structure onto a target one: the mobile atoms are rotated about their centroid,
+/// which is then moved to the centroid of the target atoms.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Alignment {
+    pub rotation: Quaternion<f64>,
+    /// Centroid of the aligned atoms of the mobile structure.
+    pub center: [f64; 3],
+    /// Centroid of the aligned atoms of the target structure.
+    pub target_center: [f64; 3],
+    /// RMSD in Angstroms of the aligned pairs of atoms after the superposition.
+    pub rmsd: f64,
+}
+
+impl Alignment {
+    /// Matrix moving the mobile structure onto the target one.
+    pub fn matrix(&self) -> Mat4<f64> {
+        let mut matrix = Mat4::new();
+        matrix.translate(self.target_center.into());
+        matrix.rotate(self.rotation);
+        matrix.translate(self.center.map(|x| -x).into());
+        matrix
+    }
+
+    /// Position of the atom of the mobile structure after the superposition.
+    pub fn apply(&self, position: [f64; 3]) -> [f64; 3] {
+        self.matrix().transform_point(position.into()).into()
+    }
+
+    /// Whether the aligned structures are the same within `tolerances.rmsd`.
+    pub fn is_match(&self, tolerances: &Tolerances) -> bool {
+        self.rmsd <= tolerances.rmsd
+    }
+}
+
+/// Superposition of the `mobile` points onto the `target` ones paired by index.
+pub fn kabsch(mobile: &[[f64; 3]], target: &[[f64; 3]], tolerances: &Tolerances) -> Result<Alignment, String> {
+    if mobile.len() != target.len() {
+        return Err(format!(
+            "Different numbers of atoms to align: {} and {}",
+            mobile.len(),
+            target.len()
+        ));
+    }
+    if mobile.is_empty() {
+        return Err("No atoms to align".to_string());
+    }
+    let n = mobile.len() as f64;
+    let centroid =
+        |points: &[[f64; 3]]| -> [f64; 3] { std::array::from_fn(|c| points.iter().map(|p| p[c]).sum::<f64>() / n) };
+    let center = centroid(mobile);
+    let target_center = centroid(target);
+
+    // Correlation of the centered coordinates and their squared norms
+    let mut s = [[0.0; 3]; 3];
+    let mut norms = 0.0;
+    for (&a, &b) in mobile.iter().zip(target) {
+        let a = geometry::sub(a, center);
+        let b = geometry::sub(b, target_center);
+        for (row, &a) in s.iter_mut().zip(&a) {
+            for (value, &b) in row.iter_mut().zip(&b) {
+                *value += a * b;
+            }
+        }
+        norms += geometry::dot(a, a) + geometry::dot(b, b);
+    }
+    let [[xx, xy, xz], [yx, yy, yz], [zx, zy, zz]] = s;
+    let horn = [
+        [xx + yy + zz, yz - zy, zx - xz, xy - yx],
+        [yz - zy, xx - yy - zz, xy + yx, zx + xz],
+        [zx - xz, xy + yx, -xx + yy - zz, yz + zy],
+        [xy - yx, zx + xz, yz + zy, -xx - yy + zz],
+    ];
+
+    let (eigenvalue, q) = largest_eigenpair(horn, |eigenvalue| rmsd(norms, eigenvalue, n), tolerances.convergence);
+    Ok(Alignment {
+        rotation: Quaternion::new(q[0], q[1], q[2], q[3]),
+        center,
+        target_center,
+        rmsd: rmsd(norms, eigenvalue, n),
+    })
+}
+
+/// Superposition of the `mobile` atoms onto the `target` ones using the pairs of atoms (zero-based, mobile
+/// first), all atoms paired in order if `None`.
+pub fn align(
+    mobile: &[[f64; 3]],
+    target: &[[f64; 3]],
+    pairs: Option<&[(usize, usize)]>,
+    tolerances: &Tolerances,
+) -> Result<Alignment, String> {
+    let Some(pairs) = pairs else {
+        if mobile.len() != target.len() {
+            return Err("The structures have different numbers of atoms, pairs of atoms are needed".to_string());
+        }
+        return kabsch(mobile, target, tolerances);
+    };
+    let position = |points: &[[f64; 3]], i: usize| -> Result<[f64; 3], String> {
+        points
+            .get(i)
+            .copied()
+            .ok_or_else(|| format!("Atom index {} is out of range 1..={}", i + 1, points.len()))
+    };
+    let mut mobile_points = Vec::with_capacity(pairs.len());
+    let mut target_points = Vec::with_capacity(pairs.len());
+    for &(i, j) in pairs {
+        mobile_points.push(position(mobile, i)?);
+        target_points.push(position(target, j)?);
+    }
+    kabsch(&mobile_points, &target_points, tolerances)
+}
+
+fn rmsd(norms: f64, eigenvalue: f64, n: f64) -> f64 {
+    ((norms - 2.0 * eigenvalue) / n).max(0.0).sqrt()
+}
+
+/// Largest eigenvalue of the symmetric matrix with its unit eigenvector, by Jacobi rotations until the RMSD
+/// computed from the eigenvalue changes by less than `convergence` between sweeps.
+fn largest_eigenpair(mut m: [[f64; 4]; 4], rmsd: impl Fn(f64) -> f64, convergence: f64) -> (f64, [f64; 4]) {
+    let mut vectors: [[f64; 4]; 4] = std::array::from_fn(|i| std::array::from_fn(|j| (i == j) as u8 as f64));
+    // Index of the largest diagonal element
+    let largest = |m: &[[f64; 4]; 4]| (0..4).max_by(|&i, &j| m[i][i].total_cmp(&m[j][j])).unwrap_or(0);
+    let eigenvalue = |m: &[[f64; 4]; 4]| m[largest(m)][largest(m)];
+    let mut previous = rmsd(eigenvalue(&m));
+    for _ in 0..MAX_SWEEPS {
+        for p in 0..3 {
+            for q in p + 1..4 {
+                if m[p][q] == 0.0 {
+                    continue;
+                }
+                let theta = (m[q][q] - m[p][p]) / (2.0 * m[p][q]);
+                let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+                let c = 1.0 / (t * t + 1.0).sqrt();
+                let s = t * c;
+                // m = Jᵀ m J with the rotation J in the plane of p and q, the columns of `vectors` follow
+                for row in m.iter_mut() {
+                    let (a, b) = (row[p], row[q]);
+                    row[p] = c * a - s * b;
+                    row[q] = s * a + c * b;
+                }
+                let (row_p, row_q) = (m[p], m[q]);
+                for k in 0..4 {
+                    m[p][k] = c * row_p[k] - s * row_q[k];
+                    m[q][k] = s * row_p[k] + c * row_q[k];
+                }
+                for row in vectors.iter_mut() {
+                    let (a, b) = (row[p], row[q]);
+                    row[p] = c * a - s * b;
+                    row[q] = s * a + c * b;
+                }
+            }
+        }
+        let current = rmsd(eigenvalue(&m));
+        if (current - previous).abs() < convergence {
+            break;
+        }
+        previous = current;
+    }
+    let k = largest(&m);
+    let vector: [f64; 4] = std::array::from_fn(|i| vectors[i][k]);
+    let norm = vector.iter().map(|x| x * x).sum::<f64>().sqrt();
+    (m[k][k], vector.map(|x| x / norm))
+}
@@ -0,0 +1,441 @@
+// Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
+// Licensed under the MIT License
+
+//! Force-field atom types perceived from the element, the bonded neighbors and ring membership. Bond orders
+//! are used when the file gives them, otherwise hybridization follows from the number of neighbors and
+//! the angles between them, so types are also perceived for structures with bonds found from distances.
+
+use std::collections::VecDeque;
+
+use crate::connectivity::Bond;
+use crate::geometry;
+use crate::periodic_table::get_element_by_number;
+use crate::types::AtomicCoordinates;
+
+/// Rings with more atoms are not perceived, atoms of such rings only count as chain atoms.
+pub const MAX_RING_SIZE: usize = 12;
+// Smallest angle in degrees between the two neighbors of a linear (sp) atom
+const LINEAR_ANGLE: f64 = 155.0;
+// Smallest sum of the angles in degrees between the three neighbors of a planar (sp2) atom
+const PLANAR_ANGLE_SUM: f64 = 350.0;
+const AROMATIC_ORDER: u8 = 4;
+
+/// Naming of the atom types.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum TypingScheme {
+    /// Universal force field, e.g. "C_3", "C_R", "O_2", "H_".
+    #[default]
+    Uff,
+    /// Types of the general Amber force field, e.g. "c3", "ca", "os", "hc". Types depending on neighbors
+    /// further than the bonded atoms are not distinguished, e.g. hydrogens next to electronegative atoms are "hc".
+    Gaff,
+}
+
+impl TypingScheme {
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "uff" => Ok(TypingScheme::Uff),
+            "gaff" => Ok(TypingScheme::Gaff),
+            _ => Err(format!("Unknown atom typing scheme: {}", name)),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            TypingScheme::Uff => "uff",
+            TypingScheme::Gaff => "gaff",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Hybridization {
+    Sp,
+    Sp2,
+    Sp3,
+}
+
+// What the typing rules look at for one atom
+struct Environment {
+    number: i32,
+    // Bonded atom, the bond vector from this atom and the bond order
+    neighbors: Vec<(usize, [f64; 3], u8)>,
+    hybridization: Hybridization,
+    aromatic: bool,
+    ring_size: Option<usize>,
+}
+
+impl Environment {
+    fn degree(&self) -> usize {
+        self.neighbors.len()
+    }
+
+    fn count(&self, environments: &[Environment], number: i32) -> usize {
+        self.neighbors
+            .iter()
+            .filter(|(j, _, _)| environments[*j].number == number)
+            .count()
+    }
+}
+
+/// Size of the smallest ring every atom belongs to, `None` for chain atoms.
+pub fn ring_sizes(num_atoms: usize, bonds: &[Bond]) -> Vec<Option<usize>> {
+    let neighbors = bonded_atoms(num_atoms, bonds);
+    (0..num_atoms).map(|atom| smallest_ring(&neighbors, atom)).collect()
+}
+
+fn bonded_atoms(num_atoms: usize, bonds: &[Bond]) -> Vec<Vec<usize>> {
+    let mut result = vec![Vec::new(); num_atoms];
+    for bond in bonds
+        .iter()
+        .filter(|b| b.atom_index_1 < num_atoms && b.atom_index_2 < num_atoms)
+    {
+        // Bonds to several periodic images of one atom close no ring of their own here
+        if !result[bond.atom_index_1].contains(&bond.atom_index_2) {
+            result[bond.atom_index_1].push(bond.atom_index_2);
+            result[bond.atom_index_2].push(bond.atom_index_1);
+        }
+    }
+    result
+}
+
+// Breadth-first search labelling every atom with the neighbor of the start it was reached through: the smallest
+// ring closes at the first bond between atoms reached through different neighbors
+fn smallest_ring(neighbors: &[Vec<usize>], start: usize) -> Option<usize> {
+    let max_depth = MAX_RING_SIZE / 2;
+    let mut depth: Vec<Option<(usize, usize)>> = vec![None; neighbors.len()];
+    let mut queue = VecDeque::new();
+    for &n in &neighbors[start] {
+        depth[n] = Some((1, n));
+        queue.push_back(n);
+    }
+    let mut best: Option<usize> = None;
+    while let Some(atom) = queue.pop_front() {
+        let Some((d, branch)) = depth[atom] else {
+            continue;
+        };
+        for &n in &neighbors[atom] {
+            if n == start {
+                continue;
+            }
+            match depth[n] {
+                None if d < max_depth => {
+                    depth[n] = Some((d + 1, branch));
+                    queue.push_back(n);
+                }
+                Some((other_depth, other_branch)) if other_branch != branch => {
+                    let size = d + other_depth + 1;
+                    if size <= MAX_RING_SIZE && best.is_none_or(|best| size < best) {
+                        best = Some(size);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    best
+}
+
+// Rings of 5 and 6 atoms, each once, as the atoms in ring order
+fn small_rings(neighbors: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    fn extend(neighbors: &[Vec<usize>], path: &mut Vec<usize>, rings: &mut Vec<Vec<usize>>) {
+        let start = path[0];
+        let last = path[path.len() - 1];
+        for &n in &neighbors[last] {
+            // The start is the lowest atom of the ring and the ring is only taken in one direction
+            if n == start && path.len() >= 5 && path[1] < last {
+                rings.push(path.clone());
+            } else if n > start && path.len() < 6 && !path.contains(&n) {
+                path.push(n);
+                extend(neighbors, path, rings);
+                path.pop();
+            }
+        }
+    }
+
+    let mut rings = Vec::new();
+    for start in 0..neighbors.len() {
+        extend(neighbors, &mut vec![start], &mut rings);
+    }
+    rings
+}
+
+fn hybridization(numbers: &[i32], number: i32, neighbors: &[(usize, [f64; 3], u8)]) -> Hybridization {
+    let degree = neighbors.len();
+    let doubles = neighbors.iter().filter(|(_, _, order)| *order == 2).count();
+    let conjugated = matches!(number, 5..=8);
+    if conjugated && (neighbors.iter().any(|(_, _, order)| *order == 3) || doubles >= 2) {
+        return Hybridization::Sp;
+    }
+    if neighbors
+        .iter()
+        .any(|(_, _, order)| *order == 2 || *order == AROMATIC_ORDER)
+        && degree <= 3
+    {
+        return Hybridization::Sp2;
+    }
+    let angle = |i: usize, j: usize| geometry::angle(neighbors[i].1, [0.0; 3], neighbors[j].1);
+    match (number, degree) {
+        (6 | 7, 2) if angle(0, 1) > LINEAR_ANGLE => Hybridization::Sp,
+        (6 | 7, 2) => Hybridization::Sp2,
+        (6 | 7, 1) => Hybridization::Sp,
+        (5..=7, 3) if angle(0, 1) + angle(1, 2) + angle(0, 2) > PLANAR_ANGLE_SUM => Hybridization::Sp2,
+        (5, 0..=2) => Hybridization::Sp2,
+        // Terminal oxygen and sulfur are double bonded, unless they carry the hydrogen of a hydroxide
+        (8 | 16, 1) if numbers[neighbors[0].0] != 1 => Hybridization::Sp2,
+        _ => Hybridization::Sp3,
+    }
+}
+
+fn environments(data: &AtomicCoordinates, bonds: &[Bond]) -> Vec<Environment> {
+    let num_atoms = data.atomic_num.len();
+    let position = |i: usize| [data.x[i], data.y[i], data.z[i]];
+    let mut neighbors: Vec<Vec<(usize, [f64; 3], u8)>> = vec![Vec::new(); num_atoms];
+    for bond in bonds
+        .iter()
+        .filter(|b| b.atom_index_1 < num_atoms && b.atom_index_2 < num_atoms)
+    {
+        let (i, j) = (bond.atom_index_1, bond.atom_index_2);
+        let shift = match &data.cell {
+            Some(cell) => cell.to_cartesian(bond.image.map(f64::from)),
+            None => [0.0; 3],
+        };
+        let vector = geometry::sub(position(j), position(i));
+        let vector: [f64; 3] = std::array::from_fn(|k| vector[k] + shift[k]);
+        neighbors[i].push((j, vector, bond.order));
+        neighbors[j].push((i, vector.map(|x| -x), bond.order));
+    }
+
+    let ring_sizes = ring_sizes(num_atoms, bonds);
+    let mut result: Vec<Environment> = neighbors
+        .into_iter()
+        .enumerate()
+        .map(|(i, neighbors)| {
+            let number = data.atomic_num[i];
+            Environment {
+                number,
+                hybridization: hybridization(&data.atomic_num, number, &neighbors),
+                aromatic: neighbors.iter().any(|(_, _, order)| *order == AROMATIC_ORDER),
+                neighbors,
+                ring_size: ring_sizes[i],
+            }
+        })
+        .collect();
+
+    // Rings of 6 planar carbon and nitrogen atoms, and rings of 5 with one of them replaced by a heteroatom
+    // giving its lone pair (pyrrole, furan, thiophene), are aromatic
+    let bonded = bonded_atoms(num_atoms, bonds);
+    for ring in small_rings(&bonded) {
+        let planar = |i: usize| matches!(result[i].number, 6 | 7) && result[i].hybridization == Hybridization::Sp2;
+        let donor = |i: usize| match result[i].number {
+            7 => result[i].degree() == 3 && result[i].hybridization == Hybridization::Sp2,
+            8 | 16 => result[i].degree() == 2,
+            _ => false,
+        };
+        let aromatic = match ring.len() {
+            6 => ring.iter().all(|&i| planar(i)),
+            _ => {
+                let donors = ring.iter().filter(|&&i| donor(i)).count();
+                ring.iter().all(|&i| planar(i) || donor(i)) && (1..=2).contains(&donors)
+            }
+        };
+        if aromatic {
+            for &i in &ring {
+                result[i].aromatic = true;
+                result[i].hybridization = Hybridization::Sp2;
+            }
+        }
+    }
+    result
+}
+
+/// Force-field atom type of every atom with the bonds given by zero-based atom indices, e.g. from
+/// `connectivity::find_bonds`. Elements without a type of their own in the scheme get the element symbol,
+/// padded with "_" to two characters in UFF.
+pub fn perceive(data: &AtomicCoordinates, bonds: &[Bond], scheme: TypingScheme) -> Result<Vec<String>, String> {
+    let num_atoms = data.atomic_num.len();
+    if data.x.len() != num_atoms || data.y.len() != num_atoms || data.z.len() != num_atoms {
+        return Err("Numbers of atoms and coordinates differ".to_string());
+    }
+    let environments = environments(data, bonds);
+    Ok((0..num_atoms)
+        .map(|i| match scheme {
+            TypingScheme::Uff => uff_type(&environments, i),
+            TypingScheme::Gaff => gaff_type(&environments, i),
+        })
+        .collect())
+}
+
+/// Hybridization of every atom as used by the typing rules, aromatic atoms are sp2.
+pub fn hybridizations(data: &AtomicCoordinates, bonds: &[Bond]) -> Vec<Hybridization> {
+    environments(data, bonds)
+        .iter()
+        .map(|environment| environment.hybridization)
+        .collect()
+}
+
+fn symbol(number: i32) -> &'static str {
+    get_element_by_number(number).map_or("Xx", |element| element.symbol)
+}
+
+fn uff_type(environments: &[Environment], i: usize) -> String {
+    let atom = &environments[i];
+    let degree = atom.degree();
+    let label = match (atom.number, atom.hybridization) {
+        (1, _) if degree == 2 => "H_b",
+        (1, _) => "H_",
+        (5, Hybridization::Sp3) => "B_3",
+        (5, _) => "B_2",
+        (6 | 7 | 8 | 16, _) if atom.aromatic => match atom.number {
+            6 => "C_R",
+            7 => "N_R",
+            8 => "O_R",
+            _ => "S_R",
+        },
+        (6, Hybridization::Sp) => "C_1",
+        (6, Hybridization::Sp2) => "C_2",
+        (6, Hybridization::Sp3) => "C_3",
+        (7, Hybridization::Sp) => "N_1",
+        // Planar nitrogen with three neighbors (amides, anilines) is resonant
+        (7, Hybridization::Sp2) if degree == 3 => "N_R",
+        (7, Hybridization::Sp2) => "N_2",
+        (7, Hybridization::Sp3) => "N_3",
+        (8, Hybridization::Sp3) => "O_3",
+        (8, _) => "O_2",
+        (14, _) => "Si3",
+        (15, _) if degree >= 4 => "P_3+5",
+        (15, _) => "P_3+3",
+        (16, Hybridization::Sp2) => "S_2",
+        (16, _) if degree >= 4 => "S_3+6",
+        (16, _) if degree == 3 => "S_3+4",
+        (16, _) => "S_3+2",
+        (9, _) => "F_",
+        (17, _) => "Cl",
+        (35, _) => "Br",
+        (53, _) => "I_",
+        (3, _) => "Li",
+        (11, _) => "Na",
+        (12, _) => "Mg3+2",
+        (13, _) => "Al3",
+        (19, _) => "K_",
+        (20, _) => "Ca6+2",
+        (26, _) => "Fe3+2",
+        (29, _) => "Cu3+1",
+        (30, _) => "Zn3+2",
+        (34, _) => "Se3+2",
+        (number, _) => return format!("{:_<2}", symbol(number)),
+    };
+    label.to_string()
+}
+
+fn gaff_type(environments: &[Environment], i: usize) -> String {
+    let atom = &environments[i];
+    let degree = atom.degree();
+    let neighbor = |k: usize| &environments[atom.neighbors[k].0];
+    let any_neighbor =
+        |test: &dyn Fn(&Environment) -> bool| atom.neighbors.iter().any(|(j, _, _)| test(&environments[*j]));
+    // Oxygen or sulfur bonded to nothing else, i.e. by a double bond
+    let terminal_chalcogen = |e: &Environment| matches!(e.number, 8 | 16) && e.degree() == 1;
+    let carbonyl_carbon = |e: &Environment| {
+        e.number == 6
+            && e.hybridization == Hybridization::Sp2
+            && !e.aromatic
+            && e.neighbors
+                .iter()
+                .any(|(j, _, _)| terminal_chalcogen(&environments[*j]))
+    };
+    let label = match (atom.number, atom.hybridization) {
+        (1, _) if degree == 0 => "hc",
+        (1, _) => match neighbor(0) {
+            e if e.number == 6 && e.aromatic => "ha",
+            e if e.number == 6 => "hc",
+            e if e.number == 7 => "hn",
+            e if e.number == 8 && e.count(environments, 1) == 2 => "hw",
+            e if e.number == 8 => "ho",
+            e if e.number == 16 => "hs",
+            e if e.number == 15 => "hp",
+            _ => "hc",
+        },
+        (6, _) if atom.aromatic => "ca",
+        (6, Hybridization::Sp) => "c1",
+        (6, Hybridization::Sp2) if any_neighbor(&terminal_chalcogen) => "c",
+        (6, Hybridization::Sp2) => "c2",
+        (6, Hybridization::Sp3) => match atom.ring_size {
+            Some(3) => "cx",
+            Some(4) => "cy",
+            _ => "c3",
+        },
+        (7, _) if atom.aromatic && degree == 3 => "na",
+        (7, _) if atom.aromatic => "nb",
+        (7, Hybridization::Sp) => "n1",
+        (7, Hybridization::Sp2) if degree == 3 && atom.count(environments, 8) == 2 => "no",
+        (7, Hybridization::Sp2) if degree == 3 && any_neighbor(&carbonyl_carbon) => "n",
+        (7, Hybridization::Sp2) if degree == 3 && any_neighbor(&|e: &Environment| e.aromatic) => "nh",
+        (7, Hybridization::Sp2) if degree == 3 => "na",
+        (7, Hybridization::Sp2) => "n2",
+        (7, Hybridization::Sp3) if degree >= 4 => "n4",
+        (7, Hybridization::Sp3) => "n3",
+        (8, _) if degree == 1 && atom.hybridization != Hybridization::Sp3 => "o",
+        (8, _) if atom.count(environments, 1) == 2 && degree == 2 => "ow",
+        (8, _) if atom.count(environments, 1) >= 1 => "oh",
+        (8, _) => "os",
+        (15, _) if degree >= 4 => "p5",
+        (15, _) => "p3",
+        (16, _) if degree == 1 => "s",
+        (16, _) if atom.count(environments, 1) >= 1 && degree <= 2 => "sh",
+        (16, _) if degree <= 2 => "ss",
+        (16, _) if degree == 3 => "s4",
+        (16, _) => "s6",
+        (number, _) => return symbol(number).to_ascii_lowercase(),
+    };
+    label.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{connectivity, templates};
+
+    /// Types of small molecules are the same with the bond orders of the templates and with bonds found from distances.
+    #[test]
+    fn atom_type_perception() {
+        let types = |name: &str, scheme: TypingScheme| {
+            let mut data = templates::find_template(name).unwrap().coordinates();
+            let bonds = connectivity::find_bonds(&data, connectivity::DEFAULT_BOND_TOLERANCE);
+            let with_orders = perceive(&data, &bonds, scheme).unwrap();
+            data.connectivity = None;
+            let bonds = connectivity::find_bonds(&data, connectivity::DEFAULT_BOND_TOLERANCE);
+            assert_eq!(perceive(&data, &bonds, scheme).unwrap(), with_orders, "{}", name);
+            with_orders
+        };
+        assert_eq!(types("pyridine", TypingScheme::Uff)[..2], ["N_R", "C_R"]);
+        assert_eq!(types("pyridine", TypingScheme::Gaff)[..2], ["nb", "ca"]);
+        assert_eq!(types("benzene", TypingScheme::Gaff)[6], "ha");
+        assert_eq!(
+            types("acetone", TypingScheme::Uff)[..5],
+            ["C_2", "O_2", "C_3", "C_3", "H_"]
+        );
+        assert_eq!(types("acetone", TypingScheme::Gaff)[..5], ["c", "o", "c3", "c3", "hc"]);
+        assert_eq!(
+            types("acetonitrile", TypingScheme::Uff),
+            ["C_3", "C_1", "H_", "H_", "H_", "N_1"]
+        );
+        assert_eq!(types("water", TypingScheme::Gaff), ["ow", "hw", "hw"]);
+        assert_eq!(
+            types("glycine", TypingScheme::Gaff)[..7],
+            ["n3", "c3", "c", "o", "oh", "ho", "hn"]
+        );
+        assert_eq!(
+            types("glycine", TypingScheme::Uff)[..5],
+            ["N_3", "C_3", "C_2", "O_2", "O_3"]
+        );
+
+        let data = templates::find_template("phenyl").unwrap().coordinates();
+        let bonds = connectivity::find_bonds(&data, connectivity::DEFAULT_BOND_TOLERANCE);
+        let rings = ring_sizes(data.atomic_num.len(), &bonds);
+        assert_eq!(rings.iter().filter(|size| **size == Some(6)).count(), 6);
+        assert!(rings.iter().all(|size| size.is_none_or(|size| size == 6)));
+        assert_eq!(TypingScheme::from_name("GAFF").unwrap(), TypingScheme::Gaff);
+        assert!(TypingScheme::from_name("mmff").is_err());
+    }
+}
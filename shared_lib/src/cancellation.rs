@@ -0,0 +1,70 @@
+// Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
+// Licensed under the MIT License
+
+use std::cell::Cell;
+
+pub const CANCELLED_MESSAGE: &str = "Operation cancelled.";
+
+// Number of `check` calls between two polls of the host callback.
+const DEFAULT_POLL_INTERVAL: u32 = 1024;
+
+pub struct CancellationToken {
+    is_cancelled: Option<Box<dyn Fn() -> bool>>,
+    poll_interval: u32,
+    calls: Cell<u32>,
+    cancelled: Cell<bool>,
+}
+
+impl CancellationToken {
+    pub fn new(is_cancelled: impl Fn() -> bool + 'static) -> Self {
+        Self {
+            is_cancelled: Some(Box::new(is_cancelled)),
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            calls: Cell::new(0),
+            cancelled: Cell::new(false),
+        }
+    }
+
+    /// Token that is never cancelled.
+    pub fn none() -> Self {
+        Self {
+            is_cancelled: None,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            calls: Cell::new(0),
+            cancelled: Cell::new(false),
+        }
+    }
+
+    pub fn with_poll_interval(mut self, poll_interval: u32) -> Self {
+        self.poll_interval = poll_interval.max(1);
+        self
+    }
+
+    /// Polls the host immediately. Once cancelled, the token stays cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        if !self.cancelled.get()
+            && let Some(is_cancelled) = &self.is_cancelled
+        {
+            self.cancelled.set(is_cancelled());
+        }
+        self.cancelled.get()
+    }
+
+    /// Cheap check for hot loops: polls the host only every `poll_interval` calls.
+    pub fn check(&self) -> Result<(), String> {
+        if self.cancelled.get() {
+            return Err(CANCELLED_MESSAGE.to_string());
+        }
+
+        let calls = self.calls.get() + 1;
+        if calls >= self.poll_interval {
+            self.calls.set(0);
+            if self.is_cancelled() {
+                return Err(CANCELLED_MESSAGE.to_string());
+            }
+        } else {
+            self.calls.set(calls);
+        }
+        Ok(())
+    }
+}
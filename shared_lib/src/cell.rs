@@ -0,0 +1,107 @@
+// Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
+// Licensed under the MIT License
+
+use serde::{Deserialize, Serialize};
+
+// Smallest volume in cubic Angstroms of a valid cell
+const MIN_VOLUME: f64 = 1e-6;
+
+/// Periodic cell given by its lattice vectors in Angstroms, the corner of the cell is at the origin.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct UnitCell {
+    /// Lattice vectors a, b and c.
+    pub vectors: [[f64; 3]; 3],
+}
+
+pub(crate) fn dot(u: [f64; 3], v: [f64; 3]) -> f64 {
+    u[0] * v[0] + u[1] * v[1] + u[2] * v[2]
+}
+
+fn cross(u: [f64; 3], v: [f64; 3]) -> [f64; 3] {
+    [
+        u[1] * v[2] - u[2] * v[1],
+        u[2] * v[0] - u[0] * v[2],
+        u[0] * v[1] - u[1] * v[0],
+    ]
+}
+
+impl UnitCell {
+    /// Cell from lattice vectors, they must be right-handed and not coplanar.
+    pub fn new(vectors: [[f64; 3]; 3]) -> Result<Self, String> {
+        let cell = Self { vectors };
+        let volume = cell.volume();
+        if !volume.is_finite() || volume < MIN_VOLUME {
+            return Err(format!(
+                "Lattice vectors must be right-handed and not coplanar, the volume is {}",
+                volume
+            ));
+        }
+        Ok(cell)
+    }
+
+    /// Cell from lengths in Angstroms and angles in degrees (α between b and c, β between a and c,
+    /// γ between a and b). Vector a is along x, b is in the xy plane.
+    pub fn from_parameters(parameters: [f64; 6]) -> Result<Self, String> {
+        let [a, b, c, alpha, beta, gamma] = parameters;
+        if [a, b, c].iter().any(|&length| !length.is_finite() || length <= 0.0) {
+            return Err(format!("Cell lengths must be positive, got {}, {}, {}", a, b, c));
+        }
+        if [alpha, beta, gamma]
+            .iter()
+            .any(|&angle| angle.is_nan() || angle <= 0.0 || angle >= 180.0)
+        {
+            return Err(format!(
+                "Cell angles must be in range (0, 180), got {}, {}, {}",
+                alpha, beta, gamma
+            ));
+        }
+        let (alpha, beta, gamma) = (alpha.to_radians(), beta.to_radians(), gamma.to_radians());
+        let cx = c * beta.cos();
+        let cy = c * (alpha.cos() - beta.cos() * gamma.cos()) / gamma.sin();
+        let cz2 = c * c - cx * cx - cy * cy;
+        if cz2 <= 0.0 {
+            return Err("Cell angles do not form a valid cell".to_string());
+        }
+        Self::new([
+            [a, 0.0, 0.0],
+            [b * gamma.cos(), b * gamma.sin(), 0.0],
+            [cx, cy, cz2.sqrt()],
+        ])
+    }
+
+    /// Lengths in Angstroms and angles in degrees: a, b, c, α, β, γ.
+    pub fn parameters(&self) -> [f64; 6] {
+        let [a, b, c] = self.vectors;
+        let length = |v: [f64; 3]| dot(v, v).sqrt();
+        let angle = |u: [f64; 3], v: [f64; 3]| {
+            (dot(u, v) / (length(u) * length(v)))
+                .clamp(-1.0, 1.0)
+                .acos()
+                .to_degrees()
+        };
+        [length(a), length(b), length(c), angle(b, c), angle(a, c), angle(a, b)]
+    }
+
+    pub fn volume(&self) -> f64 {
+        let [a, b, c] = self.vectors;
+        dot(a, cross(b, c))
+    }
+
+    /// Cartesian coordinates in Angstroms of a point given in fractions of the lattice vectors.
+    pub fn to_cartesian(&self, fractional: [f64; 3]) -> [f64; 3] {
+        std::array::from_fn(|c| (0..3).map(|i| fractional[i] * self.vectors[i][c]).sum())
+    }
+
+    /// Reciprocal vectors a*, b* and c* in Å⁻¹ without the factor 2π, a·a* = 1 and a·b* = 0.
+    pub fn reciprocal_vectors(&self) -> [[f64; 3]; 3] {
+        let [a, b, c] = self.vectors;
+        let volume = self.volume();
+        [cross(b, c), cross(c, a), cross(a, b)].map(|v| v.map(|x| x / volume))
+    }
+
+    /// Fractions of the lattice vectors of a Cartesian point.
+    pub fn to_fractional(&self, point: [f64; 3]) -> [f64; 3] {
+        // Rows of the inverse are the reciprocal vectors
+        self.reciprocal_vectors().map(|reciprocal| dot(reciprocal, point))
+    }
+}
@@ -0,0 +1,181 @@
+// Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
+// Licensed under the MIT License
+
+const VIRIDIS: &[[f32; 3]] = &[
+    [0.267, 0.005, 0.329],
+    [0.282, 0.141, 0.458],
+    [0.254, 0.265, 0.530],
+    [0.207, 0.372, 0.553],
+    [0.164, 0.471, 0.558],
+    [0.128, 0.567, 0.551],
+    [0.135, 0.659, 0.518],
+    [0.267, 0.749, 0.441],
+    [0.478, 0.821, 0.3176],
+    [0.741, 0.873, 0.150],
+    [0.993, 0.906, 0.144],
+];
+
+const COOLWARM: &[[f32; 3]] = &[
+    [0.230, 0.299, 0.754],
+    [0.552, 0.690, 0.996],
+    [0.865, 0.865, 0.865],
+    [0.958, 0.603, 0.482],
+    [0.706, 0.016, 0.150],
+];
+
+const GRAYSCALE: &[[f32; 3]] = &[[0.0, 0.0, 0.0], [1.0, 1.0, 1.0]];
+
+// Blue to yellow with luminance growing linearly, looks nearly the same with any color vision deficiency
+const CIVIDIS: &[[f32; 3]] = &[
+    [0.0, 0.125, 0.302],
+    [0.255, 0.302, 0.420],
+    [0.486, 0.482, 0.471],
+    [0.737, 0.686, 0.435],
+    [1.0, 0.918, 0.275],
+];
+
+// Okabe-Ito blue and vermillion around light gray, the ends stay apart with any color vision deficiency
+const BLUE_ORANGE: &[[f32; 3]] = &[
+    [0.0, 0.447, 0.698],
+    [0.550, 0.700, 0.830],
+    [0.870, 0.870, 0.870],
+    [0.900, 0.620, 0.420],
+    [0.835, 0.369, 0.0],
+];
+
+// Machado et al. (2009) simulation matrices of the full deficiencies, rows act on linear RGB
+const PROTANOPIA: [[f32; 3]; 3] = [
+    [0.152286, 1.052583, -0.204868],
+    [0.114503, 0.786281, 0.099216],
+    [-0.003882, -0.048116, 1.051998],
+];
+const DEUTERANOPIA: [[f32; 3]; 3] = [
+    [0.367322, 0.860646, -0.227968],
+    [0.280085, 0.672501, 0.047413],
+    [-0.011820, 0.042940, 0.968881],
+];
+const TRITANOPIA: [[f32; 3]; 3] = [
+    [1.255528, -0.076749, -0.178779],
+    [-0.078411, 0.930809, 0.147602],
+    [0.004733, 0.691367, 0.303900],
+];
+
+/// Maps scalar values in range [0, 1] to RGB colors.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Colormap {
+    #[default]
+    Viridis,
+    Coolwarm,
+    Grayscale,
+    Cividis,
+    BlueOrange,
+}
+
+impl Colormap {
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        match name.to_ascii_lowercase().replace(['-', ' '], "_").as_str() {
+            "viridis" => Ok(Colormap::Viridis),
+            "coolwarm" => Ok(Colormap::Coolwarm),
+            "grayscale" | "greyscale" | "gray" | "grey" => Ok(Colormap::Grayscale),
+            "cividis" => Ok(Colormap::Cividis),
+            "blue_orange" | "blueorange" => Ok(Colormap::BlueOrange),
+            _ => Err(format!("Unknown colormap: {}", name)),
+        }
+    }
+
+    /// Whether the colormap is meant for values symmetric around zero.
+    pub fn is_diverging(&self) -> bool {
+        matches!(self, Colormap::Coolwarm | Colormap::BlueOrange)
+    }
+
+    fn control_points(&self) -> &'static [[f32; 3]] {
+        match self {
+            Colormap::Viridis => VIRIDIS,
+            Colormap::Coolwarm => COOLWARM,
+            Colormap::Grayscale => GRAYSCALE,
+            Colormap::Cividis => CIVIDIS,
+            Colormap::BlueOrange => BLUE_ORANGE,
+        }
+    }
+
+    /// Linearly interpolates between control points, `t` is clamped to [0, 1].
+    pub fn sample(&self, t: f64) -> [f32; 3] {
+        let points = self.control_points();
+        let position = t.clamp(0.0, 1.0) as f32 * (points.len() - 1) as f32;
+        let index = (position.floor() as usize).min(points.len() - 2);
+        let fraction = position - index as f32;
+        let (a, b) = (points[index], points[index + 1]);
+        std::array::from_fn(|c| a[c] + (b[c] - a[c]) * fraction)
+    }
+}
+
+/// Colormaps and element colors for figures read by different audiences.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Palette {
+    #[default]
+    Standard,
+    // Distinguishable with protanopia, deuteranopia and tritanopia
+    ColorblindSafe,
+    // Gray levels only, for printing in black and white
+    Grayscale,
+}
+
+impl Palette {
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        match name.to_ascii_lowercase().replace(['-', ' '], "_").as_str() {
+            "standard" | "default" => Ok(Palette::Standard),
+            "colorblind_safe" | "colorblind" => Ok(Palette::ColorblindSafe),
+            "grayscale" | "greyscale" | "print" => Ok(Palette::Grayscale),
+            _ => Err(format!("Unknown palette: {}", name)),
+        }
+    }
+
+    /// Colormap of values from low to high.
+    pub fn sequential(&self) -> Colormap {
+        match self {
+            Palette::Standard => Colormap::Viridis,
+            Palette::ColorblindSafe => Colormap::Cividis,
+            Palette::Grayscale => Colormap::Grayscale,
+        }
+    }
+
+    /// Colormap of values symmetric around zero.
+    pub fn diverging(&self) -> Colormap {
+        match self {
+            Palette::Standard => Colormap::Coolwarm,
+            Palette::ColorblindSafe => Colormap::BlueOrange,
+            Palette::Grayscale => Colormap::Grayscale,
+        }
+    }
+}
+
+/// Types of dichromacy, for checking that colors stay distinguishable.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ColorVisionDeficiency {
+    Protanopia,
+    Deuteranopia,
+    Tritanopia,
+}
+
+impl ColorVisionDeficiency {
+    pub const ALL: [ColorVisionDeficiency; 3] = [
+        ColorVisionDeficiency::Protanopia,
+        ColorVisionDeficiency::Deuteranopia,
+        ColorVisionDeficiency::Tritanopia,
+    ];
+
+    /// The linear RGB color as seen with the deficiency.
+    pub fn simulate(&self, rgb: [f32; 3]) -> [f32; 3] {
+        let matrix = match self {
+            ColorVisionDeficiency::Protanopia => &PROTANOPIA,
+            ColorVisionDeficiency::Deuteranopia => &DEUTERANOPIA,
+            ColorVisionDeficiency::Tritanopia => &TRITANOPIA,
+        };
+        matrix.map(|row| (row[0] * rgb[0] + row[1] * rgb[1] + row[2] * rgb[2]).clamp(0.0, 1.0))
+    }
+}
+
+/// Relative luminance (Rec. 709) of a linear RGB color, this is what is left of the color in grayscale print.
+pub fn relative_luminance(rgb: [f32; 3]) -> f32 {
+    0.2126 * rgb[0] + 0.7152 * rgb[1] + 0.0722 * rgb[2]
+}
@@ -0,0 +1,199 @@
+// Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
+// Licensed under the MIT License
+
+//! Tolerances of the "are these the same" features: duplicate structures, symmetry-equivalent atoms,
+//! convergence of alignments and atoms highlighted as moved. They come from one profile, so the features agree
+//! with one another, e.g. structures reported as duplicates never show moved atoms.
+
+use serde::{Deserialize, Serialize};
+
+use crate::geometry;
+use crate::types::AtomicCoordinates;
+
+/// Named set of tolerances, `Normal` by default.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ToleranceProfile {
+    /// Noisy geometries, e.g. from experiment or loose optimizations.
+    Loose,
+    #[default]
+    Normal,
+    /// Tightly optimized geometries.
+    Strict,
+}
+
+impl ToleranceProfile {
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "loose" => Ok(ToleranceProfile::Loose),
+            "normal" | "default" => Ok(ToleranceProfile::Normal),
+            "strict" => Ok(ToleranceProfile::Strict),
+            _ => Err(format!("Unknown tolerance profile: {}", name)),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ToleranceProfile::Loose => "loose",
+            ToleranceProfile::Normal => "normal",
+            ToleranceProfile::Strict => "strict",
+        }
+    }
+
+    pub fn tolerances(&self) -> Tolerances {
+        match self {
+            ToleranceProfile::Loose => Tolerances {
+                distance: 0.3,
+                rmsd: 0.3,
+                convergence: 1e-4,
+            },
+            ToleranceProfile::Normal => Tolerances {
+                distance: 0.1,
+                rmsd: 0.1,
+                convergence: 1e-6,
+            },
+            ToleranceProfile::Strict => Tolerances {
+                distance: 0.01,
+                rmsd: 0.01,
+                convergence: 1e-8,
+            },
+        }
+    }
+}
+
+/// Tolerances of structure comparisons, the ones of the normal profile by default.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Tolerances {
+    /// Largest distance in Angstroms between atoms considered at the same place: atoms of duplicate
+    /// structures, symmetry-equivalent atoms. Atoms displaced further are highlighted as moved.
+    pub distance: f64,
+    /// Largest RMSD in Angstroms of aligned structures considered the same.
+    pub rmsd: f64,
+    /// Change of RMSD in Angstroms between iterations of an alignment below which it has converged.
+    pub convergence: f64,
+}
+
+impl Default for Tolerances {
+    fn default() -> Self {
+        ToleranceProfile::default().tolerances()
+    }
+}
+
+impl Tolerances {
+    /// Errors of tolerances that are not positive finite numbers.
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, value) in [
+            ("distance", self.distance),
+            ("rmsd", self.rmsd),
+            ("convergence", self.convergence),
+        ] {
+            if !value.is_finite() || value <= 0.0 {
+                return Err(format!("Invalid {} tolerance: {}", name, value));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Atoms (zero-based) displaced by more than `tolerances.distance` between two geometries of the same atoms,
+/// e.g. before and after an edit. The coordinates are compared as they are, without alignment.
+pub fn moved_atoms(
+    a: &AtomicCoordinates,
+    b: &AtomicCoordinates,
+    tolerances: &Tolerances,
+) -> Result<Vec<usize>, String> {
+    if a.atomic_num != b.atomic_num {
+        return Err("The structures have different atoms".to_string());
+    }
+    let position = |data: &AtomicCoordinates, i: usize| [data.x[i], data.y[i], data.z[i]];
+    let limit = tolerances.distance * tolerances.distance;
+    Ok((0..a.atomic_num.len())
+        .filter(|&i| {
+            let d = geometry::sub(position(a, i), position(b, i));
+            geometry::dot(d, d) > limit
+        })
+        .collect())
+}
+
+/// Whether the geometries have the same atoms in the same order and no atom has moved, see `moved_atoms`.
+pub fn is_duplicate(a: &AtomicCoordinates, b: &AtomicCoordinates, tolerances: &Tolerances) -> bool {
+    moved_atoms(a, b, tolerances).is_ok_and(|moved| moved.is_empty())
+}
+
+/// For every geometry the index of the first earlier geometry it duplicates, e.g. repeated frames of
+/// a trajectory or conformers found twice.
+pub fn find_duplicates(frames: &[AtomicCoordinates], tolerances: &Tolerances) -> Vec<Option<usize>> {
+    let mut duplicates: Vec<Option<usize>> = Vec::with_capacity(frames.len());
+    for (i, frame) in frames.iter().enumerate() {
+        // Only the first geometries of groups of duplicates are compared with
+        let original = (0..i).find(|&j| duplicates[j].is_none() && is_duplicate(&frames[j], frame, tolerances));
+        duplicates.push(original);
+    }
+    duplicates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators;
+    use crate::math::Vec3;
+    use crate::symmetry::Symmetry;
+    use crate::test_structures::{carbon_dioxide, water};
+
+    /// Duplicates, moved atoms and symmetry of a slightly displaced structure depend on the tolerance profile alone.
+    #[test]
+    fn tolerance_profiles() {
+        assert_eq!(
+            ToleranceProfile::from_name(" Strict").unwrap(),
+            ToleranceProfile::Strict
+        );
+        assert_eq!(ToleranceProfile::from_name("default").unwrap().name(), "normal");
+        assert!(ToleranceProfile::from_name("tight").is_err());
+        let [loose, normal, strict] = [
+            ToleranceProfile::Loose,
+            ToleranceProfile::Normal,
+            ToleranceProfile::Strict,
+        ]
+        .map(|p| p.tolerances());
+        assert_eq!(Tolerances::default(), normal);
+        assert!(loose.distance > normal.distance && normal.distance > strict.distance);
+        assert!(loose.convergence > normal.convergence && normal.convergence > strict.convergence);
+        assert!(
+            Tolerances {
+                distance: 0.0,
+                ..normal
+            }
+            .validate()
+            .is_err()
+        );
+        assert!(strict.validate().is_ok());
+
+        let data = water();
+        let frames = [
+            generators::displace(&data, 0.0, 1).unwrap(),
+            generators::displace(&data, 0.02, 2).unwrap(),
+            generators::displace(&data, 0.5, 3).unwrap(),
+            generators::displace(&data, 0.0, 4).unwrap(),
+        ];
+        assert_eq!(find_duplicates(&frames, &loose), [None, Some(0), None, Some(0)]);
+        assert_eq!(find_duplicates(&frames, &strict), [None, None, None, Some(0)]);
+        assert!(moved_atoms(&frames[0], &frames[2], &loose).is_ok_and(|moved| !moved.is_empty()));
+        assert!(moved_atoms(&frames[0], &frames[1], &strict).is_ok_and(|moved| !moved.is_empty()));
+        assert!(moved_atoms(&frames[0], &carbon_dioxide(), &loose).is_err());
+
+        // One hydrogen moved within the plane of the molecule by more than the strict distance tolerance
+        let mut distorted = water();
+        distorted.y[1] += 0.03;
+        let point_group = |data: &AtomicCoordinates, tolerances: &Tolerances| {
+            let positions: Vec<Vec3<f64>> = (0..3).map(|i| Vec3::new(data.x[i], data.y[i], data.z[i])).collect();
+            Symmetry::detect(&positions, &data.atomic_num, tolerances.distance)
+                .unwrap()
+                .point_group()
+                .to_string()
+        };
+        assert_eq!(point_group(&data, &strict), "C2v");
+        assert_eq!(point_group(&distorted, &strict), "Cs");
+        assert_eq!(moved_atoms(&data, &distorted, &strict).unwrap(), [1]);
+    }
+}
@@ -0,0 +1,138 @@
+// Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
+// Licensed under the MIT License
+
+use std::ops::Range;
+
+use crate::cell::UnitCell;
+use crate::neighbors::NeighborGrid;
+use crate::periodic_table::{get_element_by_number, is_pseudo_element};
+use crate::profiling;
+use crate::types::{AtomicCoordinates, Connection};
+
+/// Bonds are perceived between atoms closer than the sum of their covalent radii enlarged by this fraction.
+pub const DEFAULT_BOND_TOLERANCE: f64 = 0.15;
+
+pub struct Bond {
+    pub atom_index_1: usize,
+    pub atom_index_2: usize,
+    pub order: u8,
+    /// Cell of the second atom relative to the first one in lattice vectors, zeros unless the bond crosses
+    /// a boundary of the periodic cell.
+    pub image: [i32; 3],
+}
+
+pub struct BondSearch {
+    // Covalent radius and original index of the atoms binned in the grid, in the order of its points
+    atoms: Vec<(f64, usize)>,
+    tol_factor: f64,
+    grid: NeighborGrid,
+}
+
+impl BondSearch {
+    pub fn num_atoms(&self) -> usize {
+        self.atoms.len()
+    }
+}
+
+/// Prepares the bond search of the structure. With a periodic cell bonds are also searched between atoms
+/// and periodic images of atoms, so atoms at the faces of the cell are bonded to atoms at the opposite faces.
+/// Every image closer than the bond length is bonded: only the nearest one, unless the cell is less than
+/// three bonds wide, e.g. a primitive cell of diamond where an atom is bonded to four images of the other one.
+pub fn prepare(data: &AtomicCoordinates, geom_bond_tolerance: f64, cell: Option<&UnitCell>) -> BondSearch {
+    let _span = profiling::span("bond search grid");
+    let mut atoms = Vec::new();
+    let mut positions = Vec::new();
+    let mut max_radius: f64 = 0.0;
+    for i in 0..data.atomic_num.len() {
+        let atomic_number = data.atomic_num[i];
+        if is_pseudo_element(atomic_number) {
+            continue;
+        }
+        let Some(element) = get_element_by_number(atomic_number) else {
+            continue;
+        };
+        max_radius = max_radius.max(element.covalent_radius);
+        atoms.push((element.covalent_radius, i));
+        positions.push([data.x[i], data.y[i], data.z[i]]);
+    }
+
+    let tol_factor = 1.0 + geom_bond_tolerance;
+    let longest_bond = 2.0 * max_radius * tol_factor;
+    BondSearch {
+        atoms,
+        tol_factor,
+        grid: NeighborGrid::new(positions, longest_bond, cell),
+    }
+}
+
+fn push_bond(result: &mut Vec<Bond>, origin_i: usize, origin_j: usize, image: [i32; 3]) {
+    // Sort the pair for consistency, the image is then of the other atom
+    if origin_i > origin_j {
+        result.push(Bond {
+            atom_index_1: origin_i,
+            atom_index_2: origin_j,
+            order: 1,
+            image,
+        })
+    } else {
+        result.push(Bond {
+            atom_index_1: origin_j,
+            atom_index_2: origin_i,
+            order: 1,
+            image: image.map(|n| -n),
+        })
+    }
+}
+
+/// Runs the bond search for atoms in `range`, so that a long search can be split into chunks.
+pub fn search_range(search: &BondSearch, range: Range<usize>, result: &mut Vec<Bond>) {
+    let _span = profiling::span("bond search");
+    for i in range {
+        let (ri, origin_i) = search.atoms[i];
+        search.grid.for_each_pair(i, |j, image, distance_sq| {
+            let (rj, origin_j) = search.atoms[j];
+            let cutoff = (ri + rj) * search.tol_factor;
+            if distance_sq < cutoff * cutoff {
+                push_bond(result, origin_i, origin_j, image);
+            }
+        });
+    }
+}
+
+/// Bonds given by the structure, otherwise perceived from interatomic distances, through the boundaries
+/// of the periodic cell if the structure has one.
+pub fn find_bonds(data: &AtomicCoordinates, geom_bond_tolerance: f64) -> Vec<Bond> {
+    match &data.connectivity {
+        Some(connections) => from_connectivity(data.atomic_num.len(), connections),
+        None => {
+            let search = prepare(data, geom_bond_tolerance, data.cell.as_ref());
+            let mut result = Vec::new();
+            search_range(&search, 0..search.num_atoms(), &mut result);
+            result
+        }
+    }
+}
+
+/// Takes bonds given by the file, skipping ones that refer to missing atoms.
+pub fn from_connectivity(num_atoms: usize, connectivity: &[Connection]) -> Vec<Bond> {
+    connectivity
+        .iter()
+        .filter(|c| c.atom_1 < num_atoms && c.atom_2 < num_atoms && c.atom_1 != c.atom_2)
+        .map(|c| Bond {
+            atom_index_1: c.atom_1,
+            atom_index_2: c.atom_2,
+            order: c.order,
+            image: [0, 0, 0],
+        })
+        .collect()
+}
+
+/// Indices of bonded atoms for every atom.
+pub fn neighbors(num_atoms: usize, bonds: &[Bond]) -> Vec<Vec<usize>> {
+    let mut result = vec![Vec::new(); num_atoms];
+    for bond in bonds {
+        result[bond.atom_index_1].push(bond.atom_index_2);
+        result[bond.atom_index_2].push(bond.atom_index_1);
+    }
+    result
+}
@@ -0,0 +1,185 @@
+// Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
+// Licensed under the MIT License
+
+use std::collections::HashSet;
+
+use crate::connectivity::Bond;
+use crate::geometry;
+use crate::neighbors::NeighborGrid;
+use crate::periodic_table::{get_covalent_radius, get_vdw_radius};
+use crate::types::AtomicCoordinates;
+
+/// Longest H···A distance of a hydrogen bond in Angstroms.
+pub const DEFAULT_HBOND_DISTANCE: f64 = 2.5;
+/// Smallest D–H···A angle of a hydrogen bond in degrees.
+pub const DEFAULT_HBOND_ANGLE: f64 = 120.0;
+/// Atoms clash when closer than this fraction of the sum of their van der Waals radii.
+pub const DEFAULT_CLASH_FRACTION: f64 = 0.7;
+/// Atoms overlap when closer than this fraction of the sum of their covalent radii, shorter than any bond.
+pub const DEFAULT_COVALENT_CLASH_FRACTION: f64 = 0.75;
+
+// Nitrogen, oxygen and fluorine are donors and acceptors of hydrogen bonds
+const HBOND_ELEMENTS: [i32; 3] = [7, 8, 9];
+
+pub struct HydrogenBond {
+    pub donor: usize,
+    pub hydrogen: usize,
+    pub acceptor: usize,
+    /// Cell of the acceptor relative to the hydrogen in lattice vectors, zeros without a periodic cell.
+    pub image: [i32; 3],
+    /// H···A distance in Angstroms.
+    pub distance: f64,
+    /// D–H···A angle in degrees.
+    pub angle: f64,
+}
+
+pub struct Clash {
+    pub atom_index_1: usize,
+    pub atom_index_2: usize,
+    /// Cell of the second atom relative to the first one in lattice vectors, zeros without a periodic cell.
+    pub image: [i32; 3],
+    pub distance: f64,
+    /// Distance as a fraction of the sum of the radii of the check, van der Waals ones for `find_clashes` and
+    /// covalent ones for `find_covalent_clashes`.
+    pub fraction: f64,
+}
+
+fn add(u: [f64; 3], v: [f64; 3]) -> [f64; 3] {
+    [u[0] + v[0], u[1] + v[1], u[2] + v[2]]
+}
+
+fn position(data: &AtomicCoordinates, i: usize) -> [f64; 3] {
+    [data.x[i], data.y[i], data.z[i]]
+}
+
+/// Hydrogen bonds D–H···A between N, O and F atoms, where the hydrogen is bonded to the donor (by `bonds`)
+/// and the acceptor is closer than `max_distance` to the hydrogen at a D–H···A angle of at least `min_angle`.
+/// Acceptors are also searched among periodic images if the structure has a cell.
+pub fn find_hydrogen_bonds(
+    data: &AtomicCoordinates,
+    bonds: &[Bond],
+    max_distance: f64,
+    min_angle: f64,
+) -> Vec<HydrogenBond> {
+    let acceptors: Vec<usize> = (0..data.atomic_num.len())
+        .filter(|&i| HBOND_ELEMENTS.contains(&data.atomic_num[i]))
+        .collect();
+    let grid = NeighborGrid::new(
+        acceptors.iter().map(|&i| position(data, i)).collect(),
+        max_distance,
+        data.cell.as_ref(),
+    );
+
+    let mut result = Vec::new();
+    for bond in bonds {
+        let (i, j) = (bond.atom_index_1, bond.atom_index_2);
+        // The image is of the second atom, the donor is placed next to the hydrogen
+        let (hydrogen, donor, donor_image) = match (data.atomic_num[i], data.atomic_num[j]) {
+            (1, n) if HBOND_ELEMENTS.contains(&n) => (i, j, bond.image),
+            (n, 1) if HBOND_ELEMENTS.contains(&n) => (j, i, bond.image.map(|n| -n)),
+            _ => continue,
+        };
+        let h = position(data, hydrogen);
+        let d = add(position(data, donor), grid.translation(donor_image));
+        grid.for_each_neighbor(h, |k, image, distance_sq| {
+            let acceptor = acceptors[k];
+            if acceptor == donor && image == donor_image {
+                return;
+            }
+            let a = add(grid.point(k), grid.translation(image));
+            let angle = geometry::angle(d, h, a);
+            if angle >= min_angle {
+                result.push(HydrogenBond {
+                    donor,
+                    hydrogen,
+                    acceptor,
+                    image,
+                    distance: distance_sq.sqrt(),
+                    angle,
+                });
+            }
+        });
+    }
+    result
+}
+
+/// Pair of atoms with the cell of the second one, ordered so that both directions give the same key.
+pub(crate) fn pair(i: usize, j: usize, image: [i32; 3]) -> (usize, usize, [i32; 3]) {
+    if i <= j {
+        (i, j, image)
+    } else {
+        (j, i, image.map(|n| -n))
+    }
+}
+
+/// Bonded atoms and atoms bonded to the same atom (the 1–2 and 1–3 pairs of `bonds`) as keys of `pair`.
+pub(crate) fn excluded_pairs(num_atoms: usize, bonds: &[Bond]) -> HashSet<(usize, usize, [i32; 3])> {
+    // Bonded atoms with the cells of their images
+    let mut neighbors: Vec<Vec<(usize, [i32; 3])>> = vec![Vec::new(); num_atoms];
+    for bond in bonds {
+        neighbors[bond.atom_index_1].push((bond.atom_index_2, bond.image));
+        neighbors[bond.atom_index_2].push((bond.atom_index_1, bond.image.map(|n| -n)));
+    }
+    let mut excluded: HashSet<(usize, usize, [i32; 3])> = HashSet::new();
+    for (i, bonded) in neighbors.iter().enumerate() {
+        for &(k, to_k) in bonded {
+            excluded.insert(pair(i, k, to_k));
+            for &(j, to_j) in &neighbors[k] {
+                excluded.insert(pair(i, j, std::array::from_fn(|a| to_k[a] + to_j[a])));
+            }
+        }
+    }
+    excluded
+}
+
+/// Pairs of atoms closer than `fraction` of the sum of their van der Waals radii, except bonded atoms and
+/// atoms bonded to the same atom (the 1–2 and 1–3 pairs of `bonds`), also with periodic images.
+pub fn find_clashes(data: &AtomicCoordinates, bonds: &[Bond], fraction: f64) -> Vec<Clash> {
+    let excluded = excluded_pairs(data.atomic_num.len(), bonds);
+    close_pairs(data, get_vdw_radius, fraction, |i, j, image| {
+        !excluded.contains(&pair(i, j, image))
+    })
+}
+
+/// Pairs of atoms closer than `fraction` of the sum of their covalent radii, also with periodic images. Bonded
+/// atoms are not excluded: with a fraction below one they only overlap if they are much closer than a bond.
+/// Atoms without a covalent radius (pseudo-atoms) are skipped.
+pub fn find_covalent_clashes(data: &AtomicCoordinates, fraction: f64) -> Vec<Clash> {
+    close_pairs(data, get_covalent_radius, fraction, |_, _, _| true)
+}
+
+// Pairs of atoms closer than the fraction of the sum of their radii that are `included`
+fn close_pairs(
+    data: &AtomicCoordinates,
+    radius: fn(i32) -> Option<f64>,
+    fraction: f64,
+    included: impl Fn(usize, usize, [i32; 3]) -> bool,
+) -> Vec<Clash> {
+    let atoms: Vec<(usize, f64)> = (0..data.atomic_num.len())
+        .filter_map(|i| radius(data.atomic_num[i]).map(|radius| (i, radius)))
+        .collect();
+    let max_radius = atoms.iter().map(|&(_, radius)| radius).fold(0.0, f64::max);
+    let grid = NeighborGrid::new(
+        atoms.iter().map(|&(i, _)| position(data, i)).collect(),
+        2.0 * max_radius * fraction,
+        data.cell.as_ref(),
+    );
+
+    let mut result = Vec::new();
+    for (k, &(i, ri)) in atoms.iter().enumerate() {
+        grid.for_each_pair(k, |l, image, distance_sq| {
+            let (j, rj) = atoms[l];
+            let distance = distance_sq.sqrt();
+            if distance < (ri + rj) * fraction && included(i, j, image) {
+                result.push(Clash {
+                    atom_index_1: i,
+                    atom_index_2: j,
+                    image,
+                    distance,
+                    fraction: distance / (ri + rj),
+                });
+            }
+        });
+    }
+    result
+}
@@ -0,0 +1,203 @@
+// Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
+// Licensed under the MIT License
+
+use serde::{Deserialize, Serialize};
+
+use crate::scattering::electron_amplitude;
+use crate::types::AtomicCoordinates;
+
+// Direct sums over atom pairs take too long for larger structures
+const MAX_ATOMS: usize = 2000;
+const MAX_POINTS: usize = 100_000;
+
+/// Grids and damping of simulated gas electron diffraction curves.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct DiffractionSettings {
+    /// Range and step of the scattering variable s = 4π sin(θ/2)/λ in Å⁻¹.
+    pub s_min: f64,
+    pub s_max: f64,
+    pub s_step: f64,
+    /// Mean vibrational amplitude of all atom pairs in Angstroms.
+    pub amplitude: f64,
+    /// Damping factor b of exp(-b s²) in Å² applied to sM(s) before the Fourier transform.
+    pub damping: f64,
+    /// Largest distance and step of the radial distribution in Angstroms.
+    pub r_max: f64,
+    pub r_step: f64,
+}
+
+impl Default for DiffractionSettings {
+    fn default() -> Self {
+        Self {
+            s_min: 2.0,
+            s_max: 35.0,
+            s_step: 0.2,
+            amplitude: 0.05,
+            damping: 0.002,
+            r_max: 6.0,
+            r_step: 0.01,
+        }
+    }
+}
+
+/// Theoretical molecular intensity sM(s) and the radial distribution f(r) on the grids of the settings.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct DiffractionCurves {
+    pub s: Vec<f64>,
+    pub sm: Vec<f64>,
+    pub r: Vec<f64>,
+    pub radial_distribution: Vec<f64>,
+}
+
+/// Grid from `start` to `end` inclusive, the last point may be a fraction of the step short of the end.
+pub(crate) fn grid(start: f64, end: f64, step: f64, name: &str) -> Result<Vec<f64>, String> {
+    if !(start.is_finite() && end.is_finite() && step.is_finite()) || start < 0.0 || end <= start || step <= 0.0 {
+        return Err(format!(
+            "Invalid {} grid from {} to {} with the step {}",
+            name, start, end, step
+        ));
+    }
+    let count = ((end - start) / step + 1e-9).floor() as usize + 1;
+    if count > MAX_POINTS {
+        return Err(format!(
+            "The {} grid has {} points, at most {} are allowed",
+            name, count, MAX_POINTS
+        ));
+    }
+    Ok((0..count).map(|i| start + i as f64 * step).collect())
+}
+
+/// Simulates sM(s) = s I_mol(s) / I_at(s) of the structure in the independent atom model with the tabulated
+/// amplitudes of `scattering::electron_amplitude`, and its radial distribution f(r) = Σ sM(s) exp(-b s²) sin(s r) Δs.
+/// Unlike the experiment the model has no missing low-angle data, so the sum starts from s = 0 instead of `s_min`.
+/// Phase shifts of the amplitudes, which depend on the energy of the beam, and inelastic scattering are neglected,
+/// so pairs of very light and very heavy atoms are less accurate. Dummy atoms (atomic numbers below 1) do not scatter.
+pub fn simulate(coordinates: &AtomicCoordinates, settings: &DiffractionSettings) -> Result<DiffractionCurves, String> {
+    if !(settings.amplitude.is_finite() && settings.amplitude >= 0.0) {
+        return Err(format!("Invalid vibrational amplitude: {}", settings.amplitude));
+    }
+    if !(settings.damping.is_finite() && settings.damping >= 0.0) {
+        return Err(format!("Invalid damping factor: {}", settings.damping));
+    }
+    let s = grid(settings.s_min, settings.s_max, settings.s_step, "s")?;
+    let r = grid(settings.r_step, settings.r_max, settings.r_step, "r")?;
+
+    let atoms: Vec<(i32, [f64; 3])> = (0..coordinates.atomic_num.len())
+        .filter(|&i| coordinates.atomic_num[i] > 0)
+        .map(|i| {
+            (
+                coordinates.atomic_num[i],
+                [coordinates.x[i], coordinates.y[i], coordinates.z[i]],
+            )
+        })
+        .collect();
+    if atoms.len() < 2 {
+        return Err("At least two atoms are needed to simulate diffraction".to_string());
+    }
+    if atoms.len() > MAX_ATOMS {
+        return Err(format!(
+            "The structure has {} atoms, at most {} are supported",
+            atoms.len(),
+            MAX_ATOMS
+        ));
+    }
+
+    // Distances grouped by the pair of elements, as the amplitudes depend only on them
+    let mut elements: Vec<i32> = atoms.iter().map(|&(z, _)| z).collect();
+    elements.sort_unstable();
+    elements.dedup();
+    let element_index = |z: i32| elements.binary_search(&z).unwrap();
+    let mut distances: Vec<Vec<Vec<f64>>> = vec![vec![Vec::new(); elements.len()]; elements.len()];
+    for (i, &(zi, pi)) in atoms.iter().enumerate() {
+        for &(zj, pj) in &atoms[i + 1..] {
+            let distance = (0..3).map(|c| (pi[c] - pj[c]).powi(2)).sum::<f64>().sqrt();
+            if distance > 0.0 {
+                let (a, b) = (element_index(zi), element_index(zj));
+                distances[a.min(b)][a.max(b)].push(distance);
+            }
+        }
+    }
+    let counts: Vec<usize> = elements
+        .iter()
+        .map(|&z| atoms.iter().filter(|&&(zi, _)| zi == z).count())
+        .collect();
+
+    let half_amplitude_squared = settings.amplitude * settings.amplitude / 2.0;
+    let molecular_intensity = |s: f64| {
+        let f: Vec<f64> = elements.iter().map(|&z| electron_amplitude(z, s)).collect();
+        let atomic: f64 = f.iter().zip(&counts).map(|(f, &n)| n as f64 * f * f).sum();
+        let damping = (-half_amplitude_squared * s * s).exp();
+        let mut molecular = 0.0;
+        for a in 0..elements.len() {
+            for b in a..elements.len() {
+                // The sine over the distance is sin(s r) / (s r) multiplied by s
+                let sum: f64 = distances[a][b].iter().map(|&d| (s * d).sin() / d).sum();
+                molecular += 2.0 * f[a] * f[b] * sum;
+            }
+        }
+        molecular * damping / atomic
+    };
+    let sm: Vec<f64> = s.iter().map(|&s| molecular_intensity(s)).collect();
+
+    let transform_s = grid(0.0, settings.s_max, settings.s_step, "s")?;
+    let damped: Vec<f64> = transform_s
+        .iter()
+        .map(|&s| molecular_intensity(s) * (-settings.damping * s * s).exp())
+        .collect();
+    let radial_distribution = r
+        .iter()
+        .map(|&r| {
+            transform_s
+                .iter()
+                .zip(&damped)
+                .map(|(&s, &sm)| sm * (s * r).sin())
+                .sum::<f64>()
+                * settings.s_step
+        })
+        .collect();
+
+    Ok(DiffractionCurves {
+        s,
+        sm,
+        r,
+        radial_distribution,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_structures::carbon_dioxide;
+
+    /// Radial distribution of carbon dioxide simulated from sM(s) peaks at the C=O and O···O distances.
+    #[test]
+    fn diffraction_curves() {
+        let settings = DiffractionSettings::default();
+        let data = carbon_dioxide();
+        let curves = simulate(&data, &settings).unwrap();
+        assert_eq!(curves.s.len(), curves.sm.len());
+        assert!(
+            (curves.s[0] - settings.s_min).abs() < 1e-9 && (curves.s.last().unwrap() - settings.s_max).abs() < 1e-9
+        );
+        let peak = |from: f64, to: f64| {
+            let (r, _) = curves
+                .r
+                .iter()
+                .zip(&curves.radial_distribution)
+                .filter(|&(&r, _)| r > from && r < to)
+                .fold((0.0, f64::MIN), |peak, (&r, &f)| if f > peak.1 { (r, f) } else { peak });
+            r
+        };
+        let [o1, c, o2] = [0, 1, 2].map(|i| [data.x[i], data.y[i], data.z[i]]);
+        let distance = |a: [f64; 3], b: [f64; 3]| (0..3).map(|i| (a[i] - b[i]).powi(2)).sum::<f64>().sqrt();
+        assert!((peak(0.5, 1.7) - distance(c, o1)).abs() < 0.03, "{}", peak(0.5, 1.7));
+        assert!((peak(1.7, 3.0) - distance(o1, o2)).abs() < 0.03, "{}", peak(1.7, 3.0));
+
+        let invalid = DiffractionSettings {
+            s_step: 0.0,
+            ..settings
+        };
+        assert!(simulate(&data, &invalid).is_err());
+    }
+}
@@ -0,0 +1,253 @@
+// Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
+// Licensed under the MIT License
+
+//! Structural edits of the molecule builder: atoms added at a position, atoms deleted and bonds added or removed.
+//! Charges, chains and isotopes follow the atoms, and the bonds given by the structure are renumbered.
+
+use crate::connectivity::Bond;
+use crate::periodic_table::get_element_by_number;
+use crate::types::{AtomicCoordinates, Connection};
+
+fn check_atoms(data: &AtomicCoordinates) -> Result<usize, String> {
+    let num_atoms = data.atomic_num.len();
+    if data.x.len() != num_atoms || data.y.len() != num_atoms || data.z.len() != num_atoms {
+        return Err("Numbers of atoms and coordinates differ".to_string());
+    }
+    Ok(num_atoms)
+}
+
+fn check_index(index: usize, num_atoms: usize) -> Result<(), String> {
+    if index >= num_atoms {
+        return Err(format!("Atom index {} is out of range", index + 1));
+    }
+    Ok(())
+}
+
+/// Adds an atom of the element (or pseudo-element) at the position in Angstroms after the existing atoms. The atom
+/// is not bonded to any atom if the structure gives its bonds, otherwise its bonds are perceived as usual. It has
+/// no charge and the natural isotopic composition.
+pub fn add_atom(data: &AtomicCoordinates, number: i32, position: [f64; 3]) -> Result<AtomicCoordinates, String> {
+    check_atoms(data)?;
+    if get_element_by_number(number).is_none() {
+        return Err(format!("Unknown atomic number {}", number));
+    }
+    Ok(append_atoms(data, &[(number, position, None)]))
+}
+
+// Atoms appended after the existing ones with no charge and the natural isotopic composition, each one given
+// as its atomic number, position and the zero-based index of the atom it is single bonded to. The bond is added
+// if the structure gives its bonds, and the atom joins the chain of the bonded atom.
+pub(crate) fn append_atoms(data: &AtomicCoordinates, atoms: &[(i32, [f64; 3], Option<usize>)]) -> AtomicCoordinates {
+    let first = data.atomic_num.len();
+    let num_atoms = first + atoms.len();
+    let append = |values: &[f64], k: usize| values.iter().copied().chain(atoms.iter().map(|a| a.1[k])).collect();
+    AtomicCoordinates {
+        atomic_num: data
+            .atomic_num
+            .iter()
+            .copied()
+            .chain(atoms.iter().map(|a| a.0))
+            .collect(),
+        x: append(&data.x, 0),
+        y: append(&data.y, 1),
+        z: append(&data.z, 2),
+        connectivity: data.connectivity.as_ref().map(|connections| {
+            let bonds = atoms.iter().enumerate().filter_map(|(k, a)| {
+                a.2.map(|atom_1| Connection {
+                    atom_1,
+                    atom_2: first + k,
+                    order: 1,
+                })
+            });
+            connections.iter().copied().chain(bonds).collect()
+        }),
+        charges: data.charges.as_ref().map(|charges| {
+            let mut charges = charges.clone();
+            charges.resize(num_atoms, 0.0);
+            charges
+        }),
+        chains: data.chains.as_ref().map(|chains| {
+            let mut chains = chains.clone();
+            chains.resize(first, String::new());
+            let bonded = |a: &(i32, [f64; 3], Option<usize>)| a.2.and_then(|i| chains.get(i)).cloned();
+            let added: Vec<String> = atoms.iter().map(|a| bonded(a).unwrap_or_default()).collect();
+            chains.extend(added);
+            chains
+        }),
+        cell: data.cell,
+        isotopes: data.isotopes.as_ref().map(|isotopes| {
+            let mut isotopes = isotopes.clone();
+            isotopes.resize(num_atoms, 0);
+            isotopes
+        }),
+    }
+}
+
+/// Deletes the atoms with zero-based indices together with their bonds, the following atoms move up. At least one
+/// atom must be kept.
+pub fn delete_atoms(data: &AtomicCoordinates, indices: &[usize]) -> Result<AtomicCoordinates, String> {
+    let num_atoms = check_atoms(data)?;
+    let mut deleted = vec![false; num_atoms];
+    for &index in indices {
+        check_index(index, num_atoms)?;
+        deleted[index] = true;
+    }
+    let kept: Vec<usize> = (0..num_atoms).filter(|&i| !deleted[i]).collect();
+    if kept.is_empty() {
+        return Err("Cannot delete all atoms of the structure".to_string());
+    }
+
+    let mut new_index = vec![None; num_atoms];
+    for (k, &i) in kept.iter().enumerate() {
+        new_index[i] = Some(k);
+    }
+    let connectivity = data.connectivity.as_ref().map(|connections| {
+        connections
+            .iter()
+            .filter_map(|c| match (new_index.get(c.atom_1)?, new_index.get(c.atom_2)?) {
+                (Some(atom_1), Some(atom_2)) => Some(Connection {
+                    atom_1: *atom_1,
+                    atom_2: *atom_2,
+                    order: c.order,
+                }),
+                _ => None,
+            })
+            .collect()
+    });
+    let pick = |values: &[f64]| kept.iter().map(|&i| values[i]).collect();
+    Ok(AtomicCoordinates {
+        atomic_num: kept.iter().map(|&i| data.atomic_num[i]).collect(),
+        x: pick(&data.x),
+        y: pick(&data.y),
+        z: pick(&data.z),
+        connectivity,
+        charges: data
+            .charges
+            .as_ref()
+            .map(|charges| kept.iter().map(|&i| charges.get(i).copied().unwrap_or(0.0)).collect()),
+        chains: data.chains.as_ref().map(|chains| {
+            kept.iter()
+                .map(|&i| chains.get(i).cloned().unwrap_or_default())
+                .collect()
+        }),
+        cell: data.cell,
+        isotopes: data
+            .isotopes
+            .as_ref()
+            .map(|isotopes| kept.iter().map(|&i| isotopes.get(i).copied().unwrap_or(0)).collect()),
+    })
+}
+
+/// Removes the bond between the atoms with zero-based indices if they are bonded, otherwise bonds them with the
+/// order (1 – single, 2 – double, 3 – triple, 4 – aromatic). Returns the structure and whether the bond was added.
+/// If the structure does not give its bonds, the `bonds` perceived for it (e.g. by `connectivity::find_bonds`)
+/// become its connectivity first, except for the bonds through the boundaries of the periodic cell.
+pub fn toggle_bond(
+    data: &AtomicCoordinates,
+    bonds: &[Bond],
+    atom_1: usize,
+    atom_2: usize,
+    order: u8,
+) -> Result<(AtomicCoordinates, bool), String> {
+    let num_atoms = check_atoms(data)?;
+    check_index(atom_1, num_atoms)?;
+    check_index(atom_2, num_atoms)?;
+    if atom_1 == atom_2 {
+        return Err(format!("Atom {} cannot be bonded to itself", atom_1 + 1));
+    }
+    if !(1..=Connection::AROMATIC).contains(&order) {
+        return Err(format!("Bond order must be 1, 2, 3 or 4 (aromatic), got {}", order));
+    }
+
+    let mut connections = data.connectivity.clone().unwrap_or_else(|| {
+        bonds
+            .iter()
+            .filter(|bond| bond.image == [0, 0, 0])
+            .map(|bond| Connection {
+                atom_1: bond.atom_index_1,
+                atom_2: bond.atom_index_2,
+                order: bond.order,
+            })
+            .collect()
+    });
+    let bonded = |c: &Connection| (c.atom_1, c.atom_2) == (atom_1, atom_2) || (c.atom_1, c.atom_2) == (atom_2, atom_1);
+    let added = !connections.iter().any(bonded);
+    if added {
+        connections.push(Connection { atom_1, atom_2, order });
+    } else {
+        connections.retain(|c| !bonded(c));
+    }
+    let result = AtomicCoordinates {
+        atomic_num: data.atomic_num.clone(),
+        x: data.x.clone(),
+        y: data.y.clone(),
+        z: data.z.clone(),
+        connectivity: Some(connections),
+        charges: data.charges.clone(),
+        chains: data.chains.clone(),
+        cell: data.cell,
+        isotopes: data.isotopes.clone(),
+    };
+    Ok((result, added))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connectivity;
+    use crate::test_structures::{carbon_dioxide, water};
+
+    /// Atoms added, deleted and bonded with the bonds and per-atom data following the atoms.
+    #[test]
+    fn edits() {
+        let chlorine = add_atom(&water(), 17, [3.0, 0.0, 0.0]).unwrap();
+        assert_eq!(chlorine.atomic_num, [8, 1, 1, 17]);
+        assert_eq!((chlorine.x[3], chlorine.connectivity.is_none()), (3.0, true));
+        assert_eq!(
+            add_atom(&water(), 200, [0.0; 3]).err().as_deref(),
+            Some("Unknown atomic number 200")
+        );
+        let mut charged = carbon_dioxide();
+        charged.charges = Some(vec![-0.4, 0.8, -0.4]);
+        charged.isotopes = Some(vec![18, 0, 0]);
+        let added = add_atom(&charged, 1, [0.0, 1.0, 0.0]).unwrap();
+        assert_eq!(added.charges.unwrap(), [-0.4, 0.8, -0.4, 0.0]);
+        assert_eq!(added.isotopes.unwrap(), [18, 0, 0, 0]);
+
+        // Deleting an oxygen renumbers the remaining bond
+        let monoxide = delete_atoms(&charged, &[0]).unwrap();
+        assert_eq!(monoxide.atomic_num, [6, 8]);
+        assert_eq!(
+            monoxide.connectivity.unwrap(),
+            [Connection {
+                atom_1: 0,
+                atom_2: 1,
+                order: 2
+            }]
+        );
+        assert_eq!(monoxide.charges.unwrap(), [0.8, -0.4]);
+        assert_eq!(monoxide.isotopes.unwrap(), [0, 0]);
+        assert_eq!(
+            delete_atoms(&water(), &[0, 1, 2]).err().as_deref(),
+            Some("Cannot delete all atoms of the structure")
+        );
+        assert_eq!(
+            delete_atoms(&water(), &[3]).err().as_deref(),
+            Some("Atom index 4 is out of range")
+        );
+
+        // Perceived bonds become the connectivity of the structure
+        let bonds = connectivity::find_bonds(&water(), 1.15);
+        assert_eq!(bonds.len(), 2);
+        let (bonded, added) = toggle_bond(&water(), &bonds, 1, 2, 1).unwrap();
+        assert!(added);
+        assert_eq!(bonded.connectivity.as_ref().unwrap().len(), 3);
+        let (unbonded, added) = toggle_bond(&bonded, &[], 2, 1, 1).unwrap();
+        assert!(!added);
+        assert_eq!(unbonded.connectivity.as_ref().unwrap().len(), 2);
+        let (hydroxide, _) = toggle_bond(&water(), &bonds, 0, 1, 1).unwrap();
+        assert_eq!(hydroxide.connectivity.unwrap().len(), 1);
+        assert!(toggle_bond(&water(), &bonds, 1, 1, 1).is_err());
+        assert!(toggle_bond(&water(), &bonds, 0, 1, 5).is_err());
+    }
+}
@@ -0,0 +1,402 @@
+// Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
+// Licensed under the MIT License
+
+//! Simple generic force field for diagnostics of edited geometries: harmonic bonds and angles, torsions about
+//! single and double bonds and Lennard-Jones van der Waals contacts. Parameters follow from the elements and
+//! hybridizations alone, so energies are only meaningful to compare geometries of the same structure, e.g.
+//! before and after an edit, and not between different molecules or with quantum chemistry.
+
+use std::collections::HashSet;
+
+use serde::Serialize;
+
+use crate::atom_types::{Hybridization, hybridizations};
+use crate::connectivity::Bond;
+use crate::contacts::{DEFAULT_CLASH_FRACTION, excluded_pairs, pair};
+use crate::geometry::{self, cross, dot, sub};
+use crate::neighbors::NeighborGrid;
+use crate::periodic_table::{get_element_by_number, get_vdw_radius};
+use crate::types::AtomicCoordinates;
+
+/// Force constant of bonds in kcal/(mol·Å²).
+pub const BOND_FORCE_CONSTANT: f64 = 700.0;
+/// Force constant of angles in kcal/(mol·rad²).
+pub const ANGLE_FORCE_CONSTANT: f64 = 100.0;
+/// Barrier in kcal/mol of the rotation about a single bond between sp3 atoms, or between sp2 atoms.
+pub const SINGLE_BOND_BARRIER: f64 = 2.0;
+/// Barrier in kcal/mol of the rotation about a double or aromatic bond.
+pub const MULTIPLE_BOND_BARRIER: f64 = 20.0;
+/// Depth in kcal/mol of the van der Waals well of every pair of atoms.
+pub const VDW_WELL_DEPTH: f64 = 0.1;
+// Pairs further apart than this multiple of the sum of van der Waals radii do not interact, the energy of the
+// other pairs is shifted to vanish at this distance so it does not jump when an optimization moves them apart
+const VDW_CUTOFF_FACTOR: f64 = 2.0;
+// Torsions with an angle wider than this in degrees are not defined
+const LINEAR_ANGLE: f64 = 170.0;
+// Bond orders with their lengths as fractions of the sum of covalent radii (the single bond length)
+pub(crate) const BOND_ORDERS: [(f64, f64); 4] = [(1.0, 1.0), (1.5, 0.915), (2.0, 0.87), (3.0, 0.79)];
+const AROMATIC_ORDER: u8 = 4;
+
+/// Energy of every term of the force field in kcal/mol with the number of steric clashes.
+#[derive(Serialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct EnergyTerms {
+    pub bond: f64,
+    pub angle: f64,
+    pub torsion: f64,
+    pub vdw: f64,
+    pub total: f64,
+    /// Pairs of atoms closer than `contacts::DEFAULT_CLASH_FRACTION` of the sum of their van der Waals radii,
+    /// the same pairs as `contacts::find_clashes` reports.
+    pub clashes: usize,
+}
+
+// Atom with the cell of its image relative to the first atom of the term
+type Site = (usize, [i32; 3]);
+
+struct BondTerm {
+    atoms: [Site; 2],
+    length: f64,
+}
+
+struct AngleTerm {
+    atoms: [Site; 3],
+    angle: f64,
+}
+
+struct TorsionTerm {
+    atoms: [Site; 4],
+    // Half of the barrier per torsion about the bond, the periodicity and the cosine of the minima
+    half_barrier: f64,
+    periodicity: f64,
+    phase: f64,
+}
+
+/// Terms of the force field of one structure. They are set up once from the bonds and the geometry, e.g. of
+/// the structure as it was opened, and the energy is evaluated for any geometry of the same atoms.
+pub struct ForceField {
+    bonds: Vec<BondTerm>,
+    angles: Vec<AngleTerm>,
+    torsions: Vec<TorsionTerm>,
+    radii: Vec<Option<f64>>,
+    excluded: HashSet<(usize, usize, [i32; 3])>,
+}
+
+fn add_images(a: [i32; 3], b: [i32; 3]) -> [i32; 3] {
+    std::array::from_fn(|k| a[k] + b[k])
+}
+
+fn position(data: &AtomicCoordinates, (i, image): Site) -> [f64; 3] {
+    let shift = match &data.cell {
+        Some(cell) => cell.to_cartesian(image.map(f64::from)),
+        None => [0.0; 3],
+    };
+    [data.x[i] + shift[0], data.y[i] + shift[1], data.z[i] + shift[2]]
+}
+
+fn covalent_radius(number: i32) -> Result<f64, String> {
+    get_element_by_number(number)
+        .map(|element| element.covalent_radius)
+        .ok_or_else(|| format!("No covalent radius of atomic number {}", number))
+}
+
+fn check_atoms(data: &AtomicCoordinates) -> Result<(), String> {
+    let num_atoms = data.atomic_num.len();
+    if data.x.len() != num_atoms || data.y.len() != num_atoms || data.z.len() != num_atoms {
+        return Err("Numbers of atoms and coordinates differ".to_string());
+    }
+    Ok(())
+}
+
+// Order of the bond, given by the file or, for bonds found from distances, the order whose length is closest
+// to the bond length between atoms that can form multiple bonds
+fn bond_order(data: &AtomicCoordinates, bond: &Bond, hybridizations: &[Hybridization], single: f64) -> f64 {
+    match bond.order {
+        2 => return 2.0,
+        3 => return 3.0,
+        AROMATIC_ORDER => return 1.5,
+        _ if data.connectivity.is_some() => return 1.0,
+        _ => {}
+    }
+    let (i, j) = (bond.atom_index_1, bond.atom_index_2);
+    let candidates = match (hybridizations[i], hybridizations[j]) {
+        (Hybridization::Sp3, _) | (_, Hybridization::Sp3) => 1,
+        (Hybridization::Sp, Hybridization::Sp) => 4,
+        _ => 3,
+    };
+    let length = geometry::distance(position(data, (i, [0; 3])), position(data, (j, bond.image)));
+    BOND_ORDERS[..candidates]
+        .iter()
+        .min_by(|a, b| (a.1 * single - length).abs().total_cmp(&(b.1 * single - length).abs()))
+        .map_or(1.0, |&(order, _)| order)
+}
+
+fn ideal_angle(hybridization: Hybridization) -> f64 {
+    match hybridization {
+        Hybridization::Sp => 180.0,
+        Hybridization::Sp2 => 120.0,
+        Hybridization::Sp3 => 109.47,
+    }
+}
+
+impl ForceField {
+    /// Terms of the bonds given by zero-based atom indices, e.g. from `connectivity::find_bonds`. Angles are
+    /// set up about atoms with up to four neighbors.
+    pub fn new(data: &AtomicCoordinates, bonds: &[Bond]) -> Result<Self, String> {
+        check_atoms(data)?;
+        let num_atoms = data.atomic_num.len();
+        if let Some(bond) = bonds
+            .iter()
+            .find(|b| b.atom_index_1 >= num_atoms || b.atom_index_2 >= num_atoms)
+        {
+            return Err(format!(
+                "Bond between atoms {} and {} of {} atoms",
+                bond.atom_index_1, bond.atom_index_2, num_atoms
+            ));
+        }
+        let hybridizations = hybridizations(data, bonds);
+
+        // Bonded atoms with the cells of their images and the index of the bond
+        let mut neighbors: Vec<Vec<(usize, [i32; 3], usize)>> = vec![Vec::new(); num_atoms];
+        let mut bond_terms = Vec::with_capacity(bonds.len());
+        let mut orders = Vec::with_capacity(bonds.len());
+        for (index, bond) in bonds.iter().enumerate() {
+            let (i, j) = (bond.atom_index_1, bond.atom_index_2);
+            let single = covalent_radius(data.atomic_num[i])? + covalent_radius(data.atomic_num[j])?;
+            let order = bond_order(data, bond, &hybridizations, single);
+            let factor = BOND_ORDERS.iter().find(|(o, _)| *o == order).map_or(1.0, |(_, f)| *f);
+            bond_terms.push(BondTerm {
+                atoms: [(i, [0; 3]), (j, bond.image)],
+                length: single * factor,
+            });
+            orders.push(order);
+            neighbors[i].push((j, bond.image, index));
+            neighbors[j].push((i, bond.image.map(|n| -n), index));
+        }
+
+        let mut angles = Vec::new();
+        for (center, bonded) in neighbors.iter().enumerate().filter(|(_, bonded)| bonded.len() <= 4) {
+            for (k, &(a, to_a, _)) in bonded.iter().enumerate() {
+                for &(b, to_b, _) in &bonded[k + 1..] {
+                    angles.push(AngleTerm {
+                        atoms: [(a, to_a), (center, [0; 3]), (b, to_b)],
+                        angle: ideal_angle(hybridizations[center]),
+                    });
+                }
+            }
+        }
+
+        let mut torsions = Vec::new();
+        for (index, bond) in bonds.iter().enumerate() {
+            let (j, k) = (bond.atom_index_1, bond.atom_index_2);
+            let (half_barrier, periodicity, phase) = match (hybridizations[j], hybridizations[k]) {
+                (Hybridization::Sp3, Hybridization::Sp3) => (SINGLE_BOND_BARRIER / 2.0, 3.0, -1.0),
+                (Hybridization::Sp2, Hybridization::Sp2) if orders[index] > 1.0 => {
+                    (MULTIPLE_BOND_BARRIER / 2.0, 2.0, 1.0)
+                }
+                (Hybridization::Sp2, Hybridization::Sp2) => (SINGLE_BOND_BARRIER / 2.0, 2.0, 1.0),
+                _ => continue,
+            };
+            let count = (neighbors[j].len() - 1) * (neighbors[k].len() - 1);
+            for &(i, to_i, _) in neighbors[j].iter().filter(|n| n.2 != index) {
+                for &(l, to_l, _) in neighbors[k].iter().filter(|n| n.2 != index) {
+                    let to_l = add_images(bond.image, to_l);
+                    // Three-membered rings have no torsions
+                    if i == l && to_i == to_l {
+                        continue;
+                    }
+                    torsions.push(TorsionTerm {
+                        atoms: [(i, to_i), (j, [0; 3]), (k, bond.image), (l, to_l)],
+                        half_barrier: half_barrier / count as f64,
+                        periodicity,
+                        phase,
+                    });
+                }
+            }
+        }
+
+        Ok(Self {
+            bonds: bond_terms,
+            angles,
+            torsions,
+            radii: data.atomic_num.iter().map(|&n| get_vdw_radius(n)).collect(),
+            excluded: excluded_pairs(num_atoms, bonds),
+        })
+    }
+
+    /// Energy of every term for a geometry of the atoms the force field was set up for.
+    pub fn energy_terms(&self, data: &AtomicCoordinates) -> Result<EnergyTerms, String> {
+        self.evaluate(data, None)
+    }
+
+    /// Energy of every term with its gradient in kcal/(mol·Å) with respect to the Cartesian coordinates of
+    /// every atom, the forces on the atoms are the negative gradient.
+    pub fn energy_and_gradient(&self, data: &AtomicCoordinates) -> Result<(EnergyTerms, Vec<[f64; 3]>), String> {
+        let mut gradient = vec![[0.0; 3]; data.atomic_num.len()];
+        let terms = self.evaluate(data, Some(&mut gradient))?;
+        Ok((terms, gradient))
+    }
+
+    fn evaluate(&self, data: &AtomicCoordinates, mut gradient: Option<&mut [[f64; 3]]>) -> Result<EnergyTerms, String> {
+        check_atoms(data)?;
+        if data.atomic_num.len() != self.radii.len() {
+            return Err(format!(
+                "The force field is set up for {} atoms, the structure has {}",
+                self.radii.len(),
+                data.atomic_num.len()
+            ));
+        }
+        let mut terms = EnergyTerms::default();
+        // Adds the derivatives of a term with respect to the positions of its atoms
+        let mut accumulate = |atoms: &[Site], derivatives: &[[f64; 3]]| {
+            if let Some(gradient) = gradient.as_deref_mut() {
+                for (&(i, _), derivative) in atoms.iter().zip(derivatives) {
+                    for k in 0..3 {
+                        gradient[i][k] += derivative[k];
+                    }
+                }
+            }
+        };
+
+        for term in &self.bonds {
+            let [a, b] = term.atoms.map(|site| position(data, site));
+            let length = geometry::distance(a, b);
+            let stretch = length - term.length;
+            terms.bond += 0.5 * BOND_FORCE_CONSTANT * stretch * stretch;
+            if length > 0.0 {
+                let da = sub(a, b).map(|x| BOND_FORCE_CONSTANT * stretch * x / length);
+                accumulate(&term.atoms, &[da, da.map(|x| -x)]);
+            }
+        }
+
+        for term in &self.angles {
+            let [a, b, c] = term.atoms.map(|site| position(data, site));
+            let theta = geometry::angle(a, b, c);
+            let bend = (theta - term.angle).to_radians();
+            terms.angle += 0.5 * ANGLE_FORCE_CONSTANT * bend * bend;
+            let (u, v) = (sub(a, b), sub(c, b));
+            let (lu, lv) = (dot(u, u).sqrt(), dot(v, v).sqrt());
+            let sine = theta.to_radians().sin();
+            if lu > 0.0 && lv > 0.0 && sine > 1e-8 {
+                let cosine = theta.to_radians().cos();
+                let factor = -ANGLE_FORCE_CONSTANT * bend / sine;
+                let da: [f64; 3] = std::array::from_fn(|k| factor * (v[k] / (lu * lv) - cosine * u[k] / (lu * lu)));
+                let dc: [f64; 3] = std::array::from_fn(|k| factor * (u[k] / (lu * lv) - cosine * v[k] / (lv * lv)));
+                let db = std::array::from_fn(|k| -da[k] - dc[k]);
+                accumulate(&term.atoms, &[da, db, dc]);
+            }
+        }
+
+        for term in &self.torsions {
+            let [a, b, c, d] = term.atoms.map(|site| position(data, site));
+            if geometry::angle(a, b, c) > LINEAR_ANGLE || geometry::angle(b, c, d) > LINEAR_ANGLE {
+                continue;
+            }
+            let phi = geometry::dihedral(a, b, c, d).to_radians();
+            terms.torsion += term.half_barrier * (1.0 - term.phase * (term.periodicity * phi).cos());
+
+            // Derivatives of the dihedral angle (Blondel and Karplus)
+            let (b1, b2, b3) = (sub(b, a), sub(c, b), sub(d, c));
+            let (m, n) = (cross(b1, b2), cross(b2, b3));
+            let (mm, nn, b2b2) = (dot(m, m), dot(n, n), dot(b2, b2));
+            if mm < 1e-12 || nn < 1e-12 {
+                continue;
+            }
+            let slope = term.half_barrier * term.phase * term.periodicity * (term.periodicity * phi).sin();
+            let length = b2b2.sqrt();
+            let da = m.map(|x| -slope * length / mm * x);
+            let dd = n.map(|x| slope * length / nn * x);
+            let (p, q) = (dot(b1, b2) / b2b2, dot(b3, b2) / b2b2);
+            let db = std::array::from_fn(|k| -(1.0 + p) * da[k] + q * dd[k]);
+            let dc = std::array::from_fn(|k| p * da[k] - (1.0 + q) * dd[k]);
+            accumulate(&term.atoms, &[da, db, dc, dd]);
+        }
+
+        let atoms: Vec<(usize, f64)> = (0..self.radii.len())
+            .filter_map(|i| self.radii[i].map(|radius| (i, radius)))
+            .collect();
+        let max_radius = atoms.iter().map(|&(_, radius)| radius).fold(0.0, f64::max);
+        let grid = NeighborGrid::new(
+            atoms.iter().map(|&(i, _)| position(data, (i, [0; 3]))).collect(),
+            2.0 * max_radius * VDW_CUTOFF_FACTOR,
+            data.cell.as_ref(),
+        );
+        for (k, &(i, ri)) in atoms.iter().enumerate() {
+            grid.for_each_pair(k, |l, image, distance_sq| {
+                let (j, rj) = atoms[l];
+                let minimum = ri + rj;
+                if distance_sq >= (minimum * VDW_CUTOFF_FACTOR).powi(2) || self.excluded.contains(&pair(i, j, image)) {
+                    return;
+                }
+                let ratio = (minimum * minimum / distance_sq).powi(3);
+                let cutoff_ratio = VDW_CUTOFF_FACTOR.powi(-6);
+                terms.vdw +=
+                    VDW_WELL_DEPTH * (ratio * ratio - 2.0 * ratio - cutoff_ratio * cutoff_ratio + 2.0 * cutoff_ratio);
+                if distance_sq < (minimum * DEFAULT_CLASH_FRACTION).powi(2) {
+                    terms.clashes += 1;
+                }
+                // dE/d(r²) times the derivative of r² with respect to the position of the first atom
+                let slope = -6.0 * VDW_WELL_DEPTH * (ratio * ratio - ratio) / distance_sq;
+                let sites = [(i, [0; 3]), (j, image)];
+                let delta = sub(position(data, sites[0]), position(data, sites[1]));
+                let di = delta.map(|x| 2.0 * slope * x);
+                accumulate(&sites, &[di, di.map(|x| -x)]);
+            });
+        }
+
+        terms.total = terms.bond + terms.angle + terms.torsion + terms.vdw;
+        Ok(terms)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{connectivity, contacts, generators, templates};
+
+    #[test]
+    fn force_field_energy() {
+        for name in ["benzene", "acetone", "acetonitrile", "glycine"] {
+            let mut data = templates::find_template(name).unwrap().coordinates();
+            for _ in 0..2 {
+                let bonds = connectivity::find_bonds(&data, connectivity::DEFAULT_BOND_TOLERANCE);
+                let force_field = ForceField::new(&data, &bonds).unwrap();
+                let terms = force_field.energy_terms(&data).unwrap();
+                assert!(
+                    terms.bond < 2.0 && terms.angle < 3.0 && terms.torsion < 1.0,
+                    "{} {:?}",
+                    name,
+                    terms
+                );
+                assert_eq!(terms.clashes, 0, "{}", name);
+
+                let displaced = generators::displace(&data, 0.05, 1).unwrap();
+                let strained = force_field.energy_terms(&displaced).unwrap();
+                assert!(strained.bond > terms.bond && strained.total > terms.total, "{}", name);
+                // Bond orders are perceived from the lengths without the bonds of the file
+                data.connectivity = None;
+            }
+        }
+
+        // An atom dragged onto another one clashes with it
+        let data = templates::find_template("acetone").unwrap().coordinates();
+        let bonds = connectivity::find_bonds(&data, connectivity::DEFAULT_BOND_TOLERANCE);
+        let force_field = ForceField::new(&data, &bonds).unwrap();
+        let before = force_field.energy_terms(&data).unwrap();
+        let hydrogens: Vec<usize> = (0..data.atomic_num.len())
+            .filter(|&i| data.atomic_num[i] == 1)
+            .collect();
+        let (first, last) = (hydrogens[0], *hydrogens.last().unwrap());
+        let mut edited = generators::displace(&data, 0.0, 0).unwrap();
+        edited.x[first] = edited.x[last] + 0.5;
+        edited.y[first] = edited.y[last];
+        edited.z[first] = edited.z[last];
+        let after = force_field.energy_terms(&edited).unwrap();
+        assert!(after.clashes >= 1 && after.vdw > before.vdw + 10.0, "{:?}", after);
+        assert_eq!(after.total, after.bond + after.angle + after.torsion + after.vdw);
+        let clashes = contacts::find_clashes(&edited, &bonds, contacts::DEFAULT_CLASH_FRACTION);
+        assert_eq!(after.clashes, clashes.len());
+
+        let water = templates::find_template("water").unwrap().coordinates();
+        assert!(force_field.energy_terms(&water).is_err());
+    }
+}
@@ -0,0 +1,274 @@
+// Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
+// Licensed under the MIT License
+
+//! Random structures: displaced geometries and boxes of solvent. Every generator takes a seed, the same seed
+//! always gives the same structure.
+
+use std::collections::HashMap;
+
+use crate::cell::UnitCell;
+use crate::contacts::DEFAULT_CLASH_FRACTION;
+use crate::geometry::dot;
+use crate::periodic_table::get_vdw_radius;
+use crate::random::Rng;
+use crate::types::{AtomicCoordinates, Connection};
+
+/// Attempts to place one solvent molecule before the box is considered full.
+pub const MAX_PLACEMENT_ATTEMPTS: usize = 1000;
+
+/// Copy of the structure with every coordinate shifted by a normally distributed amount with the standard
+/// deviation `amplitude` in Angstroms, e.g. to break the symmetry before an optimization.
+pub fn displace(data: &AtomicCoordinates, amplitude: f64, seed: u64) -> Result<AtomicCoordinates, String> {
+    if !amplitude.is_finite() || amplitude < 0.0 {
+        return Err(format!("Invalid amplitude of displacements: {}", amplitude));
+    }
+    let mut rng = Rng::new(seed);
+    let (mut x, mut y, mut z) = (data.x.clone(), data.y.clone(), data.z.clone());
+    for ((x, y), z) in x.iter_mut().zip(y.iter_mut()).zip(z.iter_mut()) {
+        *x += rng.normal(0.0, amplitude);
+        *y += rng.normal(0.0, amplitude);
+        *z += rng.normal(0.0, amplitude);
+    }
+    Ok(AtomicCoordinates {
+        atomic_num: data.atomic_num.clone(),
+        x,
+        y,
+        z,
+        connectivity: data.connectivity.clone(),
+        charges: data.charges.clone(),
+        chains: data.chains.clone(),
+        cell: data.cell,
+        isotopes: data.isotopes.clone(),
+    })
+}
+
+// Atoms placed so far, hashed into cells of the periodic box to find clashes of a new molecule quickly
+struct PlacedAtoms {
+    lengths: [f64; 3],
+    dims: [usize; 3],
+    cells: HashMap<[usize; 3], Vec<([f64; 3], f64)>>,
+}
+
+impl PlacedAtoms {
+    fn new(lengths: [f64; 3], cutoff: f64) -> Self {
+        let dims = lengths.map(|length| ((length / cutoff.max(1e-6)) as usize).clamp(1, 1024));
+        Self {
+            lengths,
+            dims,
+            cells: HashMap::new(),
+        }
+    }
+
+    fn cell(&self, position: [f64; 3]) -> [usize; 3] {
+        std::array::from_fn(|k| {
+            let fraction = position[k].rem_euclid(self.lengths[k]) / self.lengths[k];
+            ((fraction * self.dims[k] as f64) as usize).min(self.dims[k] - 1)
+        })
+    }
+
+    fn insert(&mut self, position: [f64; 3], radius: f64) {
+        let cell = self.cell(position);
+        self.cells.entry(cell).or_default().push((position, radius));
+    }
+
+    // Whether the atom is closer than the clash fraction of the sum of van der Waals radii to a placed atom,
+    // with minimum image distances of the box
+    fn clashes(&self, position: [f64; 3], radius: f64) -> bool {
+        let center = self.cell(position);
+        let mut visited: Vec<[usize; 3]> = Vec::with_capacity(27);
+        for offset in 0..27 {
+            let shift = [offset / 9, offset / 3 % 3, offset % 3];
+            let cell: [usize; 3] = std::array::from_fn(|k| (center[k] + self.dims[k] + shift[k] - 1) % self.dims[k]);
+            // Boxes with fewer than 3 cells along an axis see the same cell more than once
+            if visited.contains(&cell) {
+                continue;
+            }
+            visited.push(cell);
+            let Some(atoms) = self.cells.get(&cell) else {
+                continue;
+            };
+            for &(other, other_radius) in atoms {
+                let delta: [f64; 3] = std::array::from_fn(|k| {
+                    let d = position[k] - other[k];
+                    d - self.lengths[k] * (d / self.lengths[k]).round()
+                });
+                let limit = DEFAULT_CLASH_FRACTION * (radius + other_radius);
+                if dot(delta, delta) < limit * limit {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+}
+
+fn centroid(data: &AtomicCoordinates) -> [f64; 3] {
+    let n = data.atomic_num.len().max(1) as f64;
+    [
+        data.x.iter().sum::<f64>() / n,
+        data.y.iter().sum::<f64>() / n,
+        data.z.iter().sum::<f64>() / n,
+    ]
+}
+
+fn append(target: &mut AtomicCoordinates, molecule: &AtomicCoordinates, positions: &[[f64; 3]]) {
+    let offset = target.atomic_num.len();
+    target.atomic_num.extend_from_slice(&molecule.atomic_num);
+    for p in positions {
+        target.x.push(p[0]);
+        target.y.push(p[1]);
+        target.z.push(p[2]);
+    }
+    if let (Some(bonds), Some(connections)) = (target.connectivity.as_mut(), molecule.connectivity.as_ref()) {
+        bonds.extend(connections.iter().map(|c| Connection {
+            atom_1: c.atom_1 + offset,
+            atom_2: c.atom_2 + offset,
+            order: c.order,
+        }));
+    }
+    if let Some(isotopes) = target.isotopes.as_mut() {
+        let labels = molecule.isotopes.as_deref().unwrap_or_default();
+        isotopes.extend((0..molecule.atomic_num.len()).map(|i| labels.get(i).copied().unwrap_or(0)));
+    }
+}
+
+/// Orthorhombic periodic box with the edges `lengths` in Angstroms filled with `count` copies of the solvent
+/// at random positions and orientations. The solute, if given, is put at the center of the box first.
+/// Molecules are not allowed to clash (see `contacts::find_clashes`), an error is returned if `count`
+/// molecules do not fit. Bonds are kept if both the solvent and the solute have them, isotope labels (e.g. of
+/// heavy water) if any of them has.
+pub fn solvent_box(
+    solute: Option<&AtomicCoordinates>,
+    solvent: &AtomicCoordinates,
+    lengths: [f64; 3],
+    count: usize,
+    seed: u64,
+) -> Result<AtomicCoordinates, String> {
+    if lengths.iter().any(|length| !length.is_finite() || *length <= 0.0) {
+        return Err(format!("Invalid lengths of the box: {:?}", lengths));
+    }
+    if solvent.atomic_num.is_empty() {
+        return Err("The solvent has no atoms".to_string());
+    }
+    let cell = UnitCell::new([[lengths[0], 0.0, 0.0], [0.0, lengths[1], 0.0], [0.0, 0.0, lengths[2]]])?;
+    let radius = |atomic_num: i32| get_vdw_radius(atomic_num).unwrap_or(0.0);
+    let max_radius = solvent
+        .atomic_num
+        .iter()
+        .chain(solute.iter().flat_map(|solute| solute.atomic_num.iter()))
+        .map(|&n| radius(n))
+        .fold(0.0, f64::max);
+
+    let mut result = AtomicCoordinates {
+        atomic_num: Vec::new(),
+        x: Vec::new(),
+        y: Vec::new(),
+        z: Vec::new(),
+        connectivity: solvent
+            .connectivity
+            .as_ref()
+            .filter(|_| solute.is_none_or(|solute| solute.connectivity.is_some()))
+            .map(|_| Vec::new()),
+        charges: None,
+        chains: None,
+        cell: Some(cell),
+        isotopes: (solvent.isotopes.is_some() || solute.is_some_and(|solute| solute.isotopes.is_some())).then(Vec::new),
+    };
+    let mut placed = PlacedAtoms::new(lengths, 2.0 * DEFAULT_CLASH_FRACTION * max_radius);
+    let box_center = lengths.map(|length| length / 2.0);
+
+    if let Some(solute) = solute {
+        let center = centroid(solute);
+        let positions: Vec<[f64; 3]> = (0..solute.atomic_num.len())
+            .map(|i| {
+                let p = [solute.x[i], solute.y[i], solute.z[i]];
+                std::array::from_fn(|k| p[k] - center[k] + box_center[k])
+            })
+            .collect();
+        for (p, &n) in positions.iter().zip(&solute.atomic_num) {
+            placed.insert(*p, radius(n));
+        }
+        append(&mut result, solute, &positions);
+    }
+
+    let mut rng = Rng::new(seed);
+    let center = centroid(solvent);
+    let local: Vec<[f64; 3]> = (0..solvent.atomic_num.len())
+        .map(|i| {
+            [
+                solvent.x[i] - center[0],
+                solvent.y[i] - center[1],
+                solvent.z[i] - center[2],
+            ]
+        })
+        .collect();
+    for placed_count in 0..count {
+        let positions = (0..MAX_PLACEMENT_ATTEMPTS).find_map(|_| {
+            let rotation = rng.rotation();
+            let origin = lengths.map(|length| rng.uniform(0.0, length));
+            let positions: Vec<[f64; 3]> = local
+                .iter()
+                .map(|p| std::array::from_fn(|k| dot(rotation[k], *p) + origin[k]))
+                .collect();
+            let clashes = positions
+                .iter()
+                .zip(&solvent.atomic_num)
+                .any(|(p, &n)| placed.clashes(*p, radius(n)));
+            (!clashes).then_some(positions)
+        });
+        let Some(positions) = positions else {
+            return Err(format!(
+                "Only {} of {} solvent molecules fit into the box of {:?} Angstroms",
+                placed_count, count, lengths
+            ));
+        };
+        for (p, &n) in positions.iter().zip(&solvent.atomic_num) {
+            placed.insert(*p, radius(n));
+        }
+        append(&mut result, solvent, &positions);
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_structures::carbon_dioxide;
+    use crate::{connectivity, contacts, templates};
+
+    /// Generators give the same structure for the same seed, solvent molecules do not clash.
+    #[test]
+    fn seeded_generators() {
+        let mut rng = Rng::new(7);
+        let sample = rng.sample(100, 10);
+        assert_eq!(sample.len(), 10);
+        assert!(sample.windows(2).all(|pair| pair[0] < pair[1]) && sample[9] < 100);
+        assert_eq!(Rng::new(7).sample(100, 10), sample);
+        assert_eq!(rng.sample(3, 10), [0, 1, 2]);
+        let rotation = rng.rotation();
+        for i in 0..3 {
+            for j in 0..3 {
+                let dot: f64 = (0..3).map(|k| rotation[i][k] * rotation[j][k]).sum();
+                assert!((dot - if i == j { 1.0 } else { 0.0 }).abs() < 1e-12);
+            }
+        }
+
+        let data = carbon_dioxide();
+        let displaced = displace(&data, 0.1, 1).unwrap();
+        assert_eq!(displaced.x, displace(&data, 0.1, 1).unwrap().x);
+        assert_ne!(displaced.x, displace(&data, 0.1, 2).unwrap().x);
+        assert_eq!(displace(&data, 0.0, 1).unwrap().x, data.x);
+        assert!(displace(&data, -1.0, 1).is_err());
+
+        let water = templates::find_template("water").unwrap().coordinates();
+        let solvent_box = |count, seed| solvent_box(Some(&data), &water, [12.0; 3], count, seed);
+        let solvated = solvent_box(40, 3).unwrap();
+        assert_eq!(solvated.atomic_num.len(), 3 + 40 * 3);
+        assert_eq!(solvated.connectivity.as_ref().map(Vec::len), Some(2 + 40 * 2));
+        assert_eq!(solvated.z, solvent_box(40, 3).unwrap().z);
+        assert_ne!(solvated.z, solvent_box(40, 4).unwrap().z);
+        let bonds = connectivity::find_bonds(&solvated, connectivity::DEFAULT_BOND_TOLERANCE);
+        assert!(contacts::find_clashes(&solvated, &bonds, contacts::DEFAULT_CLASH_FRACTION).is_empty());
+        assert!(solvent_box(10_000, 3).is_err_and(|e| e.contains("fit")));
+    }
+}
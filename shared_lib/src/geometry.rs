@@ -0,0 +1,82 @@
+// Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
+// Licensed under the MIT License
+
+use crate::math::Vec3;
+
+// Coordinates of the analysis code are arrays, the arithmetic is shared with the renderer through `math`
+
+pub(crate) fn sub(u: [f64; 3], v: [f64; 3]) -> [f64; 3] {
+    (Vec3::from(u) - Vec3::from(v)).into()
+}
+
+pub(crate) fn dot(u: [f64; 3], v: [f64; 3]) -> f64 {
+    Vec3::dot_product(u.into(), v.into())
+}
+
+pub(crate) fn cross(u: [f64; 3], v: [f64; 3]) -> [f64; 3] {
+    Vec3::cross_product(u.into(), v.into()).into()
+}
+
+pub(crate) fn normalize(u: [f64; 3]) -> [f64; 3] {
+    let norm = dot(u, u).sqrt();
+    u.map(|x| x / norm)
+}
+
+/// Unit vector perpendicular to `u`.
+pub(crate) fn perpendicular(u: [f64; 3]) -> [f64; 3] {
+    // Cross product with the axis least aligned with the vector
+    let axis = if u[0].abs() <= u[1].abs() && u[0].abs() <= u[2].abs() {
+        [1.0, 0.0, 0.0]
+    } else if u[1].abs() <= u[2].abs() {
+        [0.0, 1.0, 0.0]
+    } else {
+        [0.0, 0.0, 1.0]
+    };
+    normalize(cross(u, axis))
+}
+
+pub fn distance(a: [f64; 3], b: [f64; 3]) -> f64 {
+    let d = sub(a, b);
+    dot(d, d).sqrt()
+}
+
+/// Angle a–b–c in degrees, zero if an end coincides with the vertex.
+pub fn angle(a: [f64; 3], b: [f64; 3], c: [f64; 3]) -> f64 {
+    let (u, v) = (sub(a, b), sub(c, b));
+    let norm = (dot(u, u) * dot(v, v)).sqrt();
+    if norm == 0.0 {
+        return 0.0;
+    }
+    (dot(u, v) / norm).clamp(-1.0, 1.0).acos().to_degrees()
+}
+
+/// Dihedral angle a–b–c–d in degrees from -180 to 180, positive for clockwise rotation of a to d
+/// when looking from b to c (the IUPAC convention).
+pub fn dihedral(a: [f64; 3], b: [f64; 3], c: [f64; 3], d: [f64; 3]) -> f64 {
+    let (b1, b2, b3) = (sub(b, a), sub(c, b), sub(d, c));
+    let n2 = cross(b2, b3);
+    let y = dot(b2, b2).sqrt() * dot(b1, n2);
+    let x = dot(cross(b1, b2), n2);
+    y.atan2(x).to_degrees()
+}
+
+/// Position of the atom d bonded to c at the distance, with the angle b–c–d and the dihedral a–b–c–d in degrees,
+/// as given by a Z-matrix line (the natural extension reference frame method). If a, b and c lie on a line,
+/// the dihedral is measured from an arbitrary plane through b and c.
+pub fn place_atom(a: [f64; 3], b: [f64; 3], c: [f64; 3], distance: f64, angle: f64, dihedral: f64) -> [f64; 3] {
+    let bc = normalize(sub(c, b));
+    let normal = cross(sub(b, a), bc);
+    let n = if dot(normal, normal) > 1e-12 {
+        normalize(normal)
+    } else {
+        perpendicular(bc)
+    };
+    let m = cross(n, bc);
+    let (angle, dihedral) = (angle.to_radians(), dihedral.to_radians());
+    let local = [
+        -distance * angle.cos(),
+        distance * angle.sin() * dihedral.cos(),
+        distance * angle.sin() * dihedral.sin(),
+    ];
+    std::array::from_fn(|k| c[k] + bc[k] * local[0] + m[k] * local[1] + n[k] * local[2])
+}
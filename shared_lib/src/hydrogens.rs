@@ -0,0 +1,316 @@
+// Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
+// Licensed under the MIT License
+
+//! Hydrogens missing from a structure (e.g. one from X-ray diffraction or a PDB file without them) inferred from
+//! the default valences of the elements and the bonds of every atom, and the counterpart stripping them. Bond
+//! orders given by the structure are used, otherwise they follow from the bond lengths. Formal charges are not
+//! known, so e.g. ammonium gets three hydrogens. Atoms bonded to metals are left as they are, since their bonds
+//! are often dative.
+
+use crate::connectivity::Bond;
+use crate::editing::{append_atoms, delete_atoms};
+use crate::force_field::BOND_ORDERS;
+use crate::geometry::{cross, dot, normalize, perpendicular, place_atom, sub};
+use crate::periodic_table::{get_covalent_radius, is_pseudo_element};
+use crate::types::AtomicCoordinates;
+
+const HYDROGEN: i32 = 1;
+const AROMATIC_ORDER: u8 = 4;
+// Half of the tetrahedral angle in degrees
+const HALF_TETRAHEDRAL_ANGLE: f64 = 54.7356;
+
+// Default valences of the elements that get hydrogens, the lowest one not exceeded by the bonds of an atom is used
+fn valences(number: i32) -> &'static [u32] {
+    match number {
+        5 => &[3],
+        6 | 14 => &[4],
+        7 => &[3],
+        15 => &[3, 5],
+        8 | 34 => &[2],
+        16 => &[2, 4, 6],
+        9 | 17 | 35 | 53 => &[1],
+        _ => &[],
+    }
+}
+
+// Elements whose bonds to each other may be multiple when their orders follow from the bond lengths
+fn forms_multiple_bonds(number: i32) -> bool {
+    matches!(number, 5..=8 | 15 | 16)
+}
+
+fn add(u: [f64; 3], v: [f64; 3]) -> [f64; 3] {
+    std::array::from_fn(|k| u[k] + v[k])
+}
+
+fn scale(u: [f64; 3], factor: f64) -> [f64; 3] {
+    u.map(|x| x * factor)
+}
+
+fn position(data: &AtomicCoordinates, i: usize) -> [f64; 3] {
+    [data.x[i], data.y[i], data.z[i]]
+}
+
+// Bonded atom with the vector to it (to the bonded image through the boundaries of the periodic cell) and
+// the bond order in valence units
+struct Neighbor {
+    atom: usize,
+    vector: [f64; 3],
+    order: f64,
+}
+
+// Order of a bond: given by the structure, otherwise the order whose length is closest to the bond length between
+// atoms that can form multiple bonds
+fn bond_order(data: &AtomicCoordinates, bond: &Bond, length: f64) -> f64 {
+    match bond.order {
+        2 => return 2.0,
+        3 => return 3.0,
+        AROMATIC_ORDER => return 1.5,
+        _ if data.connectivity.is_some() => return 1.0,
+        _ => {}
+    }
+    let (a, b) = (data.atomic_num[bond.atom_index_1], data.atomic_num[bond.atom_index_2]);
+    if !forms_multiple_bonds(a) || !forms_multiple_bonds(b) {
+        return 1.0;
+    }
+    let Some(single) = get_covalent_radius(a).zip(get_covalent_radius(b)).map(|(a, b)| a + b) else {
+        return 1.0;
+    };
+    BOND_ORDERS
+        .iter()
+        .min_by(|a, b| (a.1 * single - length).abs().total_cmp(&(b.1 * single - length).abs()))
+        .map_or(1.0, |&(order, _)| order)
+}
+
+fn neighbors(data: &AtomicCoordinates, bonds: &[Bond]) -> Vec<Vec<Neighbor>> {
+    let num_atoms = data.atomic_num.len();
+    let mut result: Vec<Vec<Neighbor>> = (0..num_atoms).map(|_| Vec::new()).collect();
+    for bond in bonds {
+        let (i, j) = (bond.atom_index_1, bond.atom_index_2);
+        if i >= num_atoms || j >= num_atoms || i == j {
+            continue;
+        }
+        if is_pseudo_element(data.atomic_num[i]) || is_pseudo_element(data.atomic_num[j]) {
+            continue;
+        }
+        let shift = data
+            .cell
+            .map_or([0.0; 3], |cell| cell.to_cartesian(bond.image.map(f64::from)));
+        let vector = add(sub(position(data, j), position(data, i)), shift);
+        let order = bond_order(data, bond, dot(vector, vector).sqrt());
+        result[i].push(Neighbor { atom: j, vector, order });
+        result[j].push(Neighbor {
+            atom: i,
+            vector: scale(vector, -1.0),
+            order,
+        });
+    }
+    result
+}
+
+// Whether hydrogens of the atom are inferred: an element with default valences that is not bonded to a metal
+fn gets_hydrogens(numbers: &[i32], number: i32, neighbors: &[Neighbor]) -> bool {
+    !valences(number).is_empty()
+        && neighbors
+            .iter()
+            .all(|n| numbers[n.atom] == HYDROGEN || !valences(numbers[n.atom]).is_empty())
+}
+
+// Unit vectors from an atom to its missing hydrogens for the number of electron domains (4 – tetrahedral,
+// 3 – trigonal, 2 – linear) given the unit vectors to its bonded atoms. A single bonded atom is staggered
+// (or, for trigonal atoms, eclipsed in plane) with the `reference` vector from the atom to a further atom.
+fn directions(bonded: &[[f64; 3]], reference: Option<[f64; 3]>, domains: usize) -> Vec<[f64; 3]> {
+    match (bonded, domains) {
+        ([], 4) => [[1.0, 1.0, 1.0], [1.0, -1.0, -1.0], [-1.0, 1.0, -1.0], [-1.0, -1.0, 1.0]]
+            .map(normalize)
+            .to_vec(),
+        ([], 3) => {
+            let sin = 60f64.to_radians().sin();
+            vec![[1.0, 0.0, 0.0], [-0.5, sin, 0.0], [-0.5, -sin, 0.0]]
+        }
+        ([], _) => vec![[1.0, 0.0, 0.0], [-1.0, 0.0, 0.0]],
+        (&[u], _) => {
+            let (angle, dihedrals): (f64, &[f64]) = match domains {
+                4 => (109.4712, &[180.0, 60.0, -60.0]),
+                3 => (120.0, &[180.0, 0.0]),
+                _ => (180.0, &[0.0]),
+            };
+            let reference = reference.unwrap_or(u);
+            dihedrals
+                .iter()
+                .map(|&dihedral| place_atom(reference, u, [0.0; 3], 1.0, angle, dihedral))
+                .collect()
+        }
+        (&[u, v], 3 | 4) => {
+            let sum = add(u, v);
+            let bisector = if dot(sum, sum) > 1e-6 {
+                normalize(scale(sum, -1.0))
+            } else {
+                perpendicular(u)
+            };
+            if domains == 3 {
+                return vec![bisector];
+            }
+            let normal = cross(u, v);
+            let normal = if dot(normal, normal) > 1e-6 {
+                normalize(normal)
+            } else {
+                normalize(cross(bisector, u))
+            };
+            let (sin, cos) = HALF_TETRAHEDRAL_ANGLE.to_radians().sin_cos();
+            [1.0, -1.0]
+                .map(|sign| add(scale(bisector, cos), scale(normal, sign * sin)))
+                .to_vec()
+        }
+        (&[u, v, w], 4) => {
+            let sum = add(add(u, v), w);
+            if dot(sum, sum) > 1e-6 {
+                vec![normalize(scale(sum, -1.0))]
+            } else {
+                vec![normalize(cross(sub(v, u), sub(w, u)))]
+            }
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Adds the hydrogens missing from the atoms of B, C, Si, N, P, O, S, Se and halogens up to the lowest default
+/// valence of the element not exceeded by the bonds of the atom, given by zero-based atom indices, e.g. from
+/// `connectivity::find_bonds`. The hydrogens are placed at the sum of the covalent radii in a tetrahedral,
+/// trigonal or linear arrangement depending on the multiple bonds of the atom, staggered with respect to its
+/// neighbors. They follow the existing atoms, bonded to their atoms if the structure gives its bonds. Nitrogen
+/// of an aromatic ring gets no hydrogen, so that of pyrrole is not restored.
+pub fn add_hydrogens(data: &AtomicCoordinates, bonds: &[Bond]) -> Result<AtomicCoordinates, String> {
+    let num_atoms = data.atomic_num.len();
+    if data.x.len() != num_atoms || data.y.len() != num_atoms || data.z.len() != num_atoms {
+        return Err("Numbers of atoms and coordinates differ".to_string());
+    }
+    let hydrogen_radius = get_covalent_radius(HYDROGEN).unwrap_or(0.32);
+    let neighbors = neighbors(data, bonds);
+    let mut added = Vec::new();
+    for (i, bonded) in neighbors.iter().enumerate() {
+        let number = data.atomic_num[i];
+        if !gets_hydrogens(&data.atomic_num, number, bonded) {
+            continue;
+        }
+        let used: f64 = bonded.iter().map(|n| n.order).sum();
+        let Some(&valence) = valences(number).iter().find(|&&v| f64::from(v) >= used - 1e-6) else {
+            continue;
+        };
+        let missing = (f64::from(valence) - used + 1e-6).floor() as usize;
+        if missing == 0 {
+            continue;
+        }
+
+        // Every multiple bond takes a domain of the tetrahedron, lone pairs keep theirs
+        let multiple = (used - bonded.len() as f64).round().max(0.0) as usize;
+        let domains = 4usize.saturating_sub(multiple).max(2);
+        let units: Vec<[f64; 3]> = bonded.iter().map(|n| normalize(n.vector)).collect();
+        let reference = bonded.first().and_then(|first| {
+            neighbors[first.atom]
+                .iter()
+                .map(|n| add(first.vector, n.vector))
+                .find(|v| dot(*v, *v) > 1e-6)
+        });
+        let length = get_covalent_radius(number).unwrap_or(0.75) + hydrogen_radius;
+        let origin = position(data, i);
+        added.extend(
+            directions(&units, reference, domains)
+                .into_iter()
+                .take(missing)
+                .map(|direction| (HYDROGEN, add(origin, scale(direction, length)), Some(i))),
+        );
+    }
+    Ok(append_atoms(data, &added))
+}
+
+/// Zero-based indices of the hydrogens bonded to a single atom of the elements that get hydrogens from
+/// `add_hydrogens`, with the bonds given by zero-based atom indices. Hydrides of metals, molecular hydrogen,
+/// hydrogens bridging atoms and hydrogens of atoms bonded to metals are not included.
+pub fn terminal_hydrogens(data: &AtomicCoordinates, bonds: &[Bond]) -> Result<Vec<usize>, String> {
+    let num_atoms = data.atomic_num.len();
+    if data.x.len() != num_atoms || data.y.len() != num_atoms || data.z.len() != num_atoms {
+        return Err("Numbers of atoms and coordinates differ".to_string());
+    }
+    let neighbors = neighbors(data, bonds);
+    Ok((0..num_atoms)
+        .filter(|&i| match neighbors[i][..] {
+            [ref parent] if data.atomic_num[i] == HYDROGEN => {
+                let number = data.atomic_num[parent.atom];
+                gets_hydrogens(&data.atomic_num, number, &neighbors[parent.atom])
+            }
+            _ => false,
+        })
+        .collect())
+}
+
+/// Deletes the `terminal_hydrogens` of the structure, the counterpart of `add_hydrogens`.
+pub fn strip_hydrogens(data: &AtomicCoordinates, bonds: &[Bond]) -> Result<AtomicCoordinates, String> {
+    delete_atoms(data, &terminal_hydrogens(data, bonds)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_structures::{iron, water};
+    use crate::{connectivity, editing, smiles, templates};
+
+    /// Hydrogens stripped and restored from bond orders given by the structure or from bond lengths, at the sum of
+    /// the covalent radii with the geometry of the atom.
+    #[test]
+    fn hydrogen_inference() {
+        let count = |data: &AtomicCoordinates| data.atomic_num.iter().filter(|&&number| number == 1).count();
+        let distance = |data: &AtomicCoordinates, i: usize, j: usize| {
+            ((data.x[i] - data.x[j]).powi(2) + (data.y[i] - data.y[j]).powi(2) + (data.z[i] - data.z[j]).powi(2)).sqrt()
+        };
+        let restore = |data: &AtomicCoordinates| {
+            let stripped = strip_hydrogens(data, &connectivity::find_bonds(data, 0.15)).unwrap();
+            assert_eq!(count(&stripped), 0);
+            add_hydrogens(&stripped, &connectivity::find_bonds(&stripped, 0.15)).unwrap()
+        };
+
+        // Aromatic bonds given by the structure, the hydrogens are bonded and in the plane of the ring
+        let benzene = restore(&smiles::build("c1ccccc1").unwrap());
+        assert_eq!(benzene.atomic_num, [6, 6, 6, 6, 6, 6, 1, 1, 1, 1, 1, 1]);
+        assert_eq!(benzene.connectivity.as_ref().unwrap().len(), 12);
+        for h in 6..12 {
+            assert!((distance(&benzene, h - 6, h) - 1.07).abs() < 0.01);
+            assert!(
+                (distance(&benzene, (h - 5) % 6, h) - 2.15).abs() < 0.05,
+                "{}",
+                distance(&benzene, (h - 5) % 6, h)
+            );
+        }
+
+        // Bond orders from the bond lengths
+        for (smiles, hydrogens) in [
+            ("CCO", 6),
+            ("CC=O", 4),
+            ("CC#N", 3),
+            ("C=CC=C", 6),
+            ("OC(=O)c1ccccc1", 6),
+        ] {
+            let mut data = smiles::build(smiles).unwrap();
+            data.connectivity = None;
+            assert_eq!(count(&restore(&data)), hydrogens, "{}", smiles);
+        }
+
+        // Isolated atoms get a tetrahedral arrangement of hydrogens
+        let oxygen = add_hydrogens(&editing::delete_atoms(&water(), &[1, 2]).unwrap(), &[]).unwrap();
+        assert_eq!(oxygen.atomic_num, [8, 1, 1]);
+        let angle = crate::geometry::angle(
+            [oxygen.x[1], oxygen.y[1], oxygen.z[1]],
+            [oxygen.x[0], oxygen.y[0], oxygen.z[0]],
+            [oxygen.x[2], oxygen.y[2], oxygen.z[2]],
+        );
+        assert!((angle - 109.47).abs() < 0.01);
+
+        // Hydrogens of atoms bonded to metals are kept
+        let ammine = templates::attach(&iron(), 0, templates::find_template("ammonia").unwrap()).unwrap();
+        let bonds = connectivity::find_bonds(&ammine, 0.15);
+        assert_eq!(bonds.len(), 4);
+        assert_eq!(strip_hydrogens(&ammine, &bonds).unwrap().atomic_num.len(), 5);
+        let stripped = editing::delete_atoms(&ammine, &[2, 3, 4]).unwrap();
+        let bonds = connectivity::find_bonds(&stripped, 0.15);
+        assert_eq!(add_hydrogens(&stripped, &bonds).unwrap().atomic_num, [26, 7]);
+    }
+}
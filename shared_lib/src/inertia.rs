@@ -0,0 +1,113 @@
+// Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
+// Licensed under the MIT License
+
+use std::f64::consts::PI;
+
+use crate::periodic_table::{get_atomic_mass, get_isotope_mass, get_isotopes};
+use crate::types::AtomicCoordinates;
+
+// h / (8π²) in GHz·u·Å²
+const ROTATIONAL_CONSTANT_FACTOR: f64 = 505.379_009;
+// Moments below this in u·Å² are zero, e.g. about the axis of a linear molecule
+const MIN_MOMENT: f64 = 1e-6;
+
+/// Mass of the most abundant isotope in daltons, the rotational spectrum is dominated by the main isotopologue.
+/// Elements without tabulated isotopes get the standard atomic weight.
+fn isotopic_mass(atomic_number: i32) -> Option<f64> {
+    match get_isotopes(atomic_number) {
+        Some(isotopes) => isotopes
+            .iter()
+            .max_by(|a, b| a.abundance.total_cmp(&b.abundance))
+            .map(|isotope| isotope.mass),
+        None => get_atomic_mass(atomic_number),
+    }
+}
+
+/// Eigenvalues of the symmetric matrix in increasing order.
+fn symmetric_eigenvalues(m: [[f64; 3]; 3]) -> [f64; 3] {
+    let off_diagonal = m[0][1] * m[0][1] + m[0][2] * m[0][2] + m[1][2] * m[1][2];
+    if off_diagonal == 0.0 {
+        let mut diagonal = [m[0][0], m[1][1], m[2][2]];
+        diagonal.sort_by(f64::total_cmp);
+        return diagonal;
+    }
+    // Trigonometric solution of the characteristic polynomial of the shifted and scaled matrix
+    let q = (m[0][0] + m[1][1] + m[2][2]) / 3.0;
+    let p = (((0..3).map(|i| (m[i][i] - q).powi(2)).sum::<f64>() + 2.0 * off_diagonal) / 6.0).sqrt();
+    let b: [[f64; 3]; 3] =
+        std::array::from_fn(|i| std::array::from_fn(|j| (m[i][j] - if i == j { q } else { 0.0 }) / p));
+    let determinant = b[0][0] * (b[1][1] * b[2][2] - b[1][2] * b[2][1])
+        - b[0][1] * (b[1][0] * b[2][2] - b[1][2] * b[2][0])
+        + b[0][2] * (b[1][0] * b[2][1] - b[1][1] * b[2][0]);
+    let phi = (determinant / 2.0).clamp(-1.0, 1.0).acos() / 3.0;
+    let largest = q + 2.0 * p * phi.cos();
+    let smallest = q + 2.0 * p * (phi + 2.0 * PI / 3.0).cos();
+    [smallest, 3.0 * q - largest - smallest, largest]
+}
+
+// Masses and positions of the atoms with mass
+fn massive_atoms(coordinates: &AtomicCoordinates) -> Result<Vec<(f64, [f64; 3])>, String> {
+    let num_atoms = coordinates.atomic_num.len();
+    if coordinates.x.len() != num_atoms || coordinates.y.len() != num_atoms || coordinates.z.len() != num_atoms {
+        return Err("Numbers of atoms and coordinates differ".to_string());
+    }
+    let mass_numbers = coordinates.mass_numbers()?;
+    let mut atoms: Vec<(f64, [f64; 3])> = Vec::new();
+    for (i, &mass_number) in mass_numbers.iter().enumerate() {
+        let number = coordinates.atomic_num[i];
+        if number < 1 {
+            continue;
+        }
+        let mass = match mass_number {
+            0 => isotopic_mass(number),
+            mass_number => get_isotope_mass(number, mass_number),
+        }
+        .ok_or_else(|| format!("Unknown atomic number: {}", number))?;
+        atoms.push((mass, [coordinates.x[i], coordinates.y[i], coordinates.z[i]]));
+    }
+    if atoms.is_empty() {
+        return Err("The structure has no atoms".to_string());
+    }
+    Ok(atoms)
+}
+
+fn mass_center(atoms: &[(f64, [f64; 3])]) -> [f64; 3] {
+    let total: f64 = atoms.iter().map(|&(mass, _)| mass).sum();
+    std::array::from_fn(|c| atoms.iter().map(|&(mass, p)| mass * p[c]).sum::<f64>() / total)
+}
+
+/// Center of mass in Angstroms with masses of the main isotopologue, labelled atoms have the masses of their
+/// isotopes. Dummy atoms (atomic numbers below 1) are massless.
+pub fn center_of_mass(coordinates: &AtomicCoordinates) -> Result<[f64; 3], String> {
+    Ok(mass_center(&massive_atoms(coordinates)?))
+}
+
+/// Inertia tensor about the center of mass in u·Å², with the same masses as `center_of_mass`.
+pub fn inertia_tensor(coordinates: &AtomicCoordinates) -> Result<[[f64; 3]; 3], String> {
+    let atoms = massive_atoms(coordinates)?;
+    let center = mass_center(&atoms);
+    let mut tensor = [[0.0; 3]; 3];
+    for &(mass, p) in &atoms {
+        let r: [f64; 3] = std::array::from_fn(|c| p[c] - center[c]);
+        let r2 = r[0] * r[0] + r[1] * r[1] + r[2] * r[2];
+        for i in 0..3 {
+            for j in 0..3 {
+                tensor[i][j] += mass * (if i == j { r2 } else { 0.0 } - r[i] * r[j]);
+            }
+        }
+    }
+    Ok(tensor)
+}
+
+/// Principal moments of inertia about the center of mass in u·Å² in increasing order, the eigenvalues of
+/// `inertia_tensor`.
+pub fn principal_moments(coordinates: &AtomicCoordinates) -> Result<[f64; 3], String> {
+    Ok(symmetric_eigenvalues(inertia_tensor(coordinates)?).map(|moment| moment.max(0.0)))
+}
+
+/// Rotational constants A ≥ B ≥ C in GHz of the main isotopologue, or of the labelled one (e.g. HDO), `None` for
+/// zero moments of inertia, i.e. A of linear molecules and all of them for single atoms.
+pub fn rotational_constants(coordinates: &AtomicCoordinates) -> Result<[Option<f64>; 3], String> {
+    let moments = principal_moments(coordinates)?;
+    Ok(moments.map(|moment| (moment > MIN_MOMENT).then(|| ROTATIONAL_CONSTANT_FACTOR / moment)))
+}
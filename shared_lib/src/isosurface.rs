@@ -0,0 +1,182 @@
+// Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
+// Licensed under the MIT License
+
+mod tables;
+
+use std::collections::HashMap;
+use std::ops::Range;
+
+use crate::cancellation::CancellationToken;
+use crate::types::VolumeCube;
+use crate::volume::VolumeGrid;
+
+use tables::{CORNER_OFFSETS, EDGE_CORNERS, EDGE_TABLE, TRI_TABLE};
+
+/// Triangle mesh of an isosurface, positions are in Angstroms.
+/// Normals point outwards, i.e. away from the region where values exceed the isovalue
+/// (or are below it for a negative isovalue). Triangles are counter-clockwise seen from outside.
+#[derive(Default)]
+pub struct IsoMesh {
+    pub positions: Vec<[f32; 3]>,
+    pub normals: Vec<[f32; 3]>,
+    pub indices: Vec<u32>,
+}
+
+impl IsoMesh {
+    pub fn num_triangles(&self) -> usize {
+        self.indices.len() / 3
+    }
+}
+
+fn normalize(v: [f64; 3]) -> [f64; 3] {
+    let length = (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt();
+    if length > 0.0 { v.map(|x| x / length) } else { v }
+}
+
+struct Builder<'a> {
+    grid: VolumeGrid<'a>,
+    isovalue: f64,
+    // -1 flips gradients, so normals point outwards of the enclosed region.
+    orientation: f64,
+    mesh: IsoMesh,
+    // Vertex index by (linear index of the lower grid point of the edge, edge axis).
+    edge_vertices: HashMap<(usize, usize), u32>,
+}
+
+impl Builder<'_> {
+    fn edge_vertex(&mut self, a: [usize; 3], b: [usize; 3]) -> u32 {
+        let axis = (0..3).find(|&c| a[c] != b[c]).unwrap_or(0);
+        let (a, b) = if a[axis] < b[axis] { (a, b) } else { (b, a) };
+        let key = (self.grid.cube.index(a[0], a[1], a[2]), axis);
+        if let Some(&index) = self.edge_vertices.get(&key) {
+            return index;
+        }
+
+        let (value_a, value_b) = (self.grid.value(a), self.grid.value(b));
+        let t = if (value_b - value_a).abs() > f64::EPSILON {
+            (self.isovalue - value_a) / (value_b - value_a)
+        } else {
+            0.5
+        };
+        let (pa, pb) = (
+            self.grid.position(a.map(|x| x as f64)),
+            self.grid.position(b.map(|x| x as f64)),
+        );
+        let (ga, gb) = (self.grid.gradient(a), self.grid.gradient(b));
+        let normal = normalize(std::array::from_fn(|c| {
+            -self.orientation * (ga[c] + t * (gb[c] - ga[c]))
+        }));
+
+        let index = self.mesh.positions.len() as u32;
+        self.mesh
+            .positions
+            .push(std::array::from_fn(|c| (pa[c] + t * (pb[c] - pa[c])) as f32));
+        self.mesh.normals.push(normal.map(|x| x as f32));
+        self.edge_vertices.insert(key, index);
+        index
+    }
+
+    /// Adds a triangle, flipping its winding if it disagrees with the vertex normals.
+    fn push_triangle(&mut self, triangle: [u32; 3]) {
+        let [p0, p1, p2] = triangle.map(|i| self.mesh.positions[i as usize]);
+        let e1 = [p1[0] - p0[0], p1[1] - p0[1], p1[2] - p0[2]];
+        let e2 = [p2[0] - p0[0], p2[1] - p0[1], p2[2] - p0[2]];
+        let face = [
+            e1[1] * e2[2] - e1[2] * e2[1],
+            e1[2] * e2[0] - e1[0] * e2[2],
+            e1[0] * e2[1] - e1[1] * e2[0],
+        ];
+        let normal = triangle
+            .iter()
+            .map(|&i| self.mesh.normals[i as usize])
+            .fold([0.0; 3], |acc, n| [acc[0] + n[0], acc[1] + n[1], acc[2] + n[2]]);
+
+        if face[0] * normal[0] + face[1] * normal[1] + face[2] * normal[2] < 0.0 {
+            self.mesh
+                .indices
+                .extend_from_slice(&[triangle[0], triangle[2], triangle[1]]);
+        } else {
+            self.mesh.indices.extend_from_slice(&triangle);
+        }
+    }
+
+    fn polygonise(&mut self, p: [usize; 3]) {
+        let corners: [[usize; 3]; 8] = CORNER_OFFSETS.map(|o| [p[0] + o[0], p[1] + o[1], p[2] + o[2]]);
+        let mut config = 0;
+        for (n, corner) in corners.iter().enumerate() {
+            if self.grid.value(*corner) < self.isovalue {
+                config |= 1 << n;
+            }
+        }
+        let edges = EDGE_TABLE[config];
+        if edges == 0 {
+            return;
+        }
+
+        let mut vertices = [0u32; 12];
+        for (edge, [a, b]) in EDGE_CORNERS.iter().enumerate() {
+            if edges & (1 << edge) != 0 {
+                vertices[edge] = self.edge_vertex(corners[*a], corners[*b]);
+            }
+        }
+        for triangle in TRI_TABLE[config].chunks(3).take_while(|t| t[0] >= 0) {
+            self.push_triangle([0, 1, 2].map(|n| vertices[triangle[n] as usize]));
+        }
+    }
+}
+
+/// Marching cubes over the slabs of cells between consecutive grid planes along the first axis, so a large cube
+/// can be processed in chunks, e.g. with yields to the event loop between them.
+pub struct Extraction<'a> {
+    builder: Builder<'a>,
+}
+
+impl<'a> Extraction<'a> {
+    pub fn new(cube: &'a VolumeCube, isovalue: f64) -> Result<Self, String> {
+        Ok(Self {
+            builder: Builder {
+                grid: VolumeGrid::new(cube)?,
+                isovalue,
+                orientation: if isovalue < 0.0 { -1.0 } else { 1.0 },
+                mesh: IsoMesh::default(),
+                edge_vertices: HashMap::new(),
+            },
+        })
+    }
+
+    /// Number of slabs, every one of them must be passed to `extract_slabs` once.
+    pub fn num_slabs(&self) -> usize {
+        self.builder.grid.dims[0] - 1
+    }
+
+    /// Number of cells in a slab.
+    pub fn slab_cells(&self) -> usize {
+        let [_, n2, n3] = self.builder.grid.dims;
+        (n2 - 1) * (n3 - 1)
+    }
+
+    pub fn extract_slabs(&mut self, slabs: Range<usize>) {
+        let [_, n2, n3] = self.builder.grid.dims;
+        for i in slabs {
+            for j in 0..n2 - 1 {
+                for k in 0..n3 - 1 {
+                    self.builder.polygonise([i, j, k]);
+                }
+            }
+        }
+    }
+
+    pub fn finish(self) -> IsoMesh {
+        self.builder.mesh
+    }
+}
+
+/// Extracts the isosurface `value == isovalue` from the cube with the marching cubes algorithm.
+pub fn extract(cube: &VolumeCube, isovalue: f64, cancel: &CancellationToken) -> Result<IsoMesh, String> {
+    let mut extraction = Extraction::new(cube, isovalue)?;
+    for slab in 0..extraction.num_slabs() {
+        cancel.check()?;
+        extraction.extract_slabs(slab..slab + 1);
+    }
+    Ok(extraction.finish())
+}
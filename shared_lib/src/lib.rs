@@ -1,2 +1,34 @@
+pub mod alignment;
+pub mod atom_types;
+pub mod cancellation;
+pub mod cell;
+pub mod colormap;
+pub mod comparison;
+pub mod connectivity;
+pub mod contacts;
+pub mod diffraction;
+pub mod editing;
+pub mod force_field;
+pub mod generators;
+pub mod geometry;
+pub mod hydrogens;
+pub mod inertia;
+pub mod isosurface;
+pub mod mass_spectrum;
+pub mod math;
+pub mod neighbors;
+pub mod optimization;
+pub mod ordering;
 pub mod periodic_table;
+pub mod powder;
+pub mod profiling;
+pub mod properties;
+pub mod random;
+pub mod scattering;
+pub mod smiles;
+pub mod symmetry;
+pub mod templates;
+#[cfg(test)]
+mod test_structures;
 pub mod types;
+pub mod volume;
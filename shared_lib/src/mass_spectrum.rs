@@ -0,0 +1,230 @@
+// Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
+// Licensed under the MIT License
+
+use serde::{Deserialize, Serialize};
+
+use crate::periodic_table::{Isotope, element_symbol, get_isotope_mass, get_isotopes};
+use crate::types::AtomicCoordinates;
+
+const ELECTRON_MASS: f64 = 0.000548579909;
+// Combinations less probable than this fraction of the most probable one are dropped while convolving
+const PRUNE_THRESHOLD: f64 = 1e-9;
+
+/// Charge state, peak merging and cutoff of a simulated isotopic pattern.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct IsotopeSettings {
+    /// Charge of the ion in elementary charges, electrons are removed from or added to the neutral molecule.
+    pub charge: i32,
+    /// Peaks closer than this in daltons are merged into one at their weighted mean mass, the default keeps
+    /// nominal masses apart and merges their fine structure as a low resolution spectrometer does.
+    pub resolution: f64,
+    /// Weaker peaks in percent of the strongest one are not listed.
+    pub min_abundance: f64,
+}
+
+impl Default for IsotopeSettings {
+    fn default() -> Self {
+        Self {
+            charge: 1,
+            resolution: 0.1,
+            min_abundance: 0.01,
+        }
+    }
+}
+
+/// Stick of the spectrum, the abundance is in percent of the strongest peak.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct IsotopePeak {
+    pub mz: f64,
+    pub abundance: f64,
+}
+
+/// Isotopic distribution of the ion in order of increasing m/z.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct IsotopePattern {
+    /// Molecular formula in the Hill order.
+    pub formula: String,
+    /// m/z of the ion made of the most abundant isotopes only.
+    pub monoisotopic_mz: f64,
+    pub peaks: Vec<IsotopePeak>,
+}
+
+/// Molecular formula in the Hill order: C and H first, then the other elements alphabetically, or all of them
+/// alphabetically without carbon. Dummy atoms (atomic numbers below 1) are skipped.
+pub fn formula(atomic_numbers: &[i32]) -> String {
+    let mut counts: Vec<(&'static str, usize)> = Vec::new();
+    for &n in atomic_numbers.iter().filter(|&&n| n > 0) {
+        let symbol = element_symbol(n);
+        match counts.iter_mut().find(|(s, _)| *s == symbol) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((symbol, 1)),
+        }
+    }
+    let has_carbon = counts.iter().any(|&(s, _)| s == "C");
+    let rank = |symbol: &str| match symbol {
+        "C" if has_carbon => 0,
+        "H" if has_carbon => 1,
+        _ => 2,
+    };
+    counts.sort_by(|a, b| rank(a.0).cmp(&rank(b.0)).then(a.0.cmp(b.0)));
+    counts
+        .iter()
+        .map(|&(symbol, count)| match count {
+            1 => symbol.to_string(),
+            _ => format!("{}{}", symbol, count),
+        })
+        .collect()
+}
+
+// Sorts the peaks by mass, merges the close ones and drops the improbable ones
+fn merge(mut peaks: Vec<(f64, f64)>, resolution: f64) -> Vec<(f64, f64)> {
+    peaks.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let strongest = peaks.iter().map(|&(_, p)| p).fold(0.0, f64::max);
+    let mut merged: Vec<(f64, f64)> = Vec::new();
+    for (mass, probability) in peaks {
+        if probability < PRUNE_THRESHOLD * strongest {
+            continue;
+        }
+        match merged.last_mut() {
+            Some(peak) if mass - peak.0 < resolution => {
+                let total = peak.1 + probability;
+                peak.0 = (peak.0 * peak.1 + mass * probability) / total;
+                peak.1 = total;
+            }
+            _ => merged.push((mass, probability)),
+        }
+    }
+    merged
+}
+
+fn convolve(a: &[(f64, f64)], b: &[(f64, f64)], resolution: f64) -> Vec<(f64, f64)> {
+    let peaks = a
+        .iter()
+        .flat_map(|&(ma, pa)| b.iter().map(move |&(mb, pb)| (ma + mb, pa * pb)))
+        .collect();
+    merge(peaks, resolution)
+}
+
+/// Distribution of `count` atoms of the element by squaring and multiplying its isotopes.
+fn element_distribution(isotopes: &[Isotope], mut count: usize, resolution: f64) -> Vec<(f64, f64)> {
+    let mut power: Vec<(f64, f64)> = isotopes.iter().map(|i| (i.mass, i.abundance)).collect();
+    let mut result = vec![(0.0, 1.0)];
+    while count > 0 {
+        if count & 1 == 1 {
+            result = convolve(&result, &power, resolution);
+        }
+        count >>= 1;
+        if count > 0 {
+            power = convolve(&power, &power, resolution);
+        }
+    }
+    result
+}
+
+/// Simulates the isotopic distribution of the molecular ion from natural abundances of the stable isotopes,
+/// labelled atoms (see `AtomicCoordinates::isotopes`) are only their isotopes. Dummy atoms (atomic numbers below 1)
+/// are skipped.
+pub fn simulate(coordinates: &AtomicCoordinates, settings: &IsotopeSettings) -> Result<IsotopePattern, String> {
+    if settings.charge == 0 {
+        return Err("The charge of the ion must not be zero".to_string());
+    }
+    if !(settings.resolution.is_finite() && settings.resolution > 0.0) {
+        return Err(format!("Invalid resolution: {}", settings.resolution));
+    }
+    if !(settings.min_abundance.is_finite() && (0.0..100.0).contains(&settings.min_abundance)) {
+        return Err(format!("Invalid minimum abundance: {}", settings.min_abundance));
+    }
+
+    // Atomic numbers with the mass numbers of labelled atoms, 0 for the natural composition
+    let mut elements: Vec<(i32, u32)> = coordinates
+        .atomic_num
+        .iter()
+        .copied()
+        .zip(coordinates.mass_numbers()?)
+        .filter(|&(n, _)| n > 0)
+        .collect();
+    if elements.is_empty() {
+        return Err("The structure has no atoms".to_string());
+    }
+    elements.sort_unstable();
+    let mut distribution = vec![(0.0, 1.0)];
+    let mut monoisotopic_mass = 0.0;
+    for group in elements.chunk_by(|a, b| a == b) {
+        let (number, mass_number) = group[0];
+        if mass_number != 0 {
+            let mass = get_isotope_mass(number, mass_number).unwrap_or(mass_number as f64) * group.len() as f64;
+            monoisotopic_mass += mass;
+            distribution = convolve(&distribution, &[(mass, 1.0)], settings.resolution);
+            continue;
+        }
+        let isotopes = get_isotopes(number)
+            .ok_or_else(|| format!("No stable isotopes are known for {}", element_symbol(number)))?;
+        let most_abundant = isotopes
+            .iter()
+            .max_by(|a, b| a.abundance.total_cmp(&b.abundance))
+            .unwrap();
+        monoisotopic_mass += most_abundant.mass * group.len() as f64;
+        let element = element_distribution(&isotopes, group.len(), settings.resolution);
+        distribution = convolve(&distribution, &element, settings.resolution);
+    }
+
+    let charge = settings.charge as f64;
+    let mz = |mass: f64| (mass - charge * ELECTRON_MASS) / charge.abs();
+    let strongest = distribution.iter().map(|&(_, p)| p).fold(0.0, f64::max);
+    let peaks = distribution
+        .iter()
+        .map(|&(mass, probability)| IsotopePeak {
+            mz: mz(mass),
+            abundance: probability / strongest * 100.0,
+        })
+        .filter(|peak| peak.abundance >= settings.min_abundance)
+        .collect();
+
+    Ok(IsotopePattern {
+        formula: formula(&coordinates.atomic_num),
+        monoisotopic_mz: mz(monoisotopic_mass),
+        peaks,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_structures::carbon_dioxide;
+
+    /// CO2⁺: M+1 mostly from ¹³C and ¹⁷O, M+2 from ¹⁸O, the doubly charged ion is at half the m/z.
+    #[test]
+    fn isotope_pattern() {
+        let data = carbon_dioxide();
+        let settings = IsotopeSettings::default();
+        let pattern = simulate(&data, &settings).unwrap();
+        assert_eq!(pattern.formula, "CO2");
+        assert!(
+            (pattern.monoisotopic_mz - 43.98928).abs() < 1e-4,
+            "{}",
+            pattern.monoisotopic_mz
+        );
+        let abundances: Vec<f64> = pattern.peaks.iter().map(|peak| peak.abundance).collect();
+        assert_eq!(abundances.len(), 3, "{:?}", abundances);
+        assert_eq!(abundances[0], 100.0);
+        assert!((abundances[1] - 1.16).abs() < 0.02, "{}", abundances[1]);
+        assert!((abundances[2] - 0.42).abs() < 0.02, "{}", abundances[2]);
+        assert!((pattern.peaks[1].mz - pattern.peaks[0].mz - 1.0).abs() < 0.01);
+
+        let doubly_charged = IsotopeSettings { charge: 2, ..settings };
+        let pattern2 = simulate(&data, &doubly_charged).unwrap();
+        assert!((pattern2.monoisotopic_mz - (43.98983 - 2.0 * 0.00055) / 2.0).abs() < 1e-4);
+        assert!(simulate(&data, &IsotopeSettings { charge: 0, ..settings }).is_err());
+        // Fully labelled ¹³CO2 has a single carbon isotope, the pattern shifts by its mass
+        let labelled = AtomicCoordinates {
+            isotopes: Some(vec![0, 13, 0]),
+            ..carbon_dioxide()
+        };
+        let labelled_pattern = simulate(&labelled, &settings).unwrap();
+        assert!((labelled_pattern.monoisotopic_mz - pattern.monoisotopic_mz - 1.00335).abs() < 1e-4);
+        assert!(labelled_pattern.peaks[1].abundance < abundances[1]);
+        assert_eq!(formula(&[8, 1, 1, 6, 17, -1]), "CH2ClO");
+        assert_eq!(formula(&[17, 1]), "ClH");
+    }
+}
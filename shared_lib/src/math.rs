@@ -0,0 +1,13 @@
+// Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
+// Licensed under the MIT License
+
+//! Vectors, column-major 4×4 matrices and quaternions generic over the float type: f32 for rendering, f64 for
+//! analysis.
+
+pub mod matrix;
+pub mod quaternion;
+pub mod vector;
+
+pub use matrix::Mat4;
+pub use quaternion::Quaternion;
+pub use vector::Vec3;
@@ -0,0 +1,327 @@
+// Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
+// Licensed under the MIT License
+
+use super::quaternion::Quaternion;
+use super::vector::Vec3;
+use num_traits::Float;
+use std::ops::Mul;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Mat4<T: Float> {
+    pub data: [T; 16],
+}
+
+impl<T: Float> Mat4<T> {
+    fn identity_data() -> [T; 16] {
+        let zero = T::zero();
+        let one = T::one();
+        [
+            one, zero, zero, zero, zero, one, zero, zero, zero, zero, one, zero, zero, zero, zero, one,
+        ]
+    }
+
+    pub fn new() -> Self {
+        Self {
+            data: Self::identity_data(),
+        }
+    }
+
+    pub fn from_array(data: [T; 16]) -> Self {
+        Self { data }
+    }
+
+    pub fn set_to_identity(&mut self) {
+        self.data = Self::identity_data()
+    }
+
+    pub fn translate(&mut self, vector: Vec3<T>) {
+        let mut mat4 = Self::new();
+        mat4.data[12] = vector.x;
+        mat4.data[13] = vector.y;
+        mat4.data[14] = vector.z;
+
+        *self = *self * mat4
+    }
+
+    pub fn scale(&mut self, vector: Vec3<T>) {
+        let mut mat4 = Self::new();
+        mat4.data[0] = vector.x;
+        mat4.data[5] = vector.y;
+        mat4.data[10] = vector.z;
+
+        *self = *self * mat4
+    }
+
+    pub fn rotate(&mut self, quat: Quaternion<T>) {
+        *self = *self * quat.to_rotation_matrix()
+    }
+
+    pub fn look_at(&mut self, eye: Vec3<T>, center: Vec3<T>, up: Vec3<T>) {
+        let zero = T::zero();
+        let one = T::one();
+
+        let forward = (center - eye).normalized();
+        let side = Vec3::cross_product(forward, up).normalized();
+        let up_vec = Vec3::cross_product(side, forward);
+
+        self.data[0] = side.x;
+        self.data[4] = side.y;
+        self.data[8] = side.z;
+        self.data[12] = -Vec3::dot_product(side, eye);
+
+        self.data[1] = up_vec.x;
+        self.data[5] = up_vec.y;
+        self.data[9] = up_vec.z;
+        self.data[13] = -Vec3::dot_product(up_vec, eye);
+
+        self.data[2] = -forward.x;
+        self.data[6] = -forward.y;
+        self.data[10] = -forward.z;
+        self.data[14] = Vec3::dot_product(forward, eye);
+
+        self.data[3] = zero;
+        self.data[7] = zero;
+        self.data[11] = zero;
+        self.data[15] = one;
+    }
+
+    pub fn perspective(&mut self, fov: T, aspect: T, near_plane: T, far_plane: T) {
+        self.set_to_identity();
+
+        let zero = T::zero();
+        let one = T::one();
+        let two = one + one;
+
+        let rad = fov.to_radians();
+        let f = one / (rad / two).tan();
+
+        self.data[0] = f / aspect;
+        self.data[5] = f;
+        self.data[10] = (far_plane + near_plane) / (near_plane - far_plane);
+        self.data[11] = -one;
+        self.data[14] = (two * far_plane * near_plane) / (near_plane - far_plane);
+        self.data[15] = zero;
+    }
+
+    /// Inverse matrix, None if the matrix is singular.
+    pub fn inverted(&self) -> Option<Self> {
+        let m = &self.data;
+        let mut inv = [T::zero(); 16];
+
+        inv[0] = m[5] * m[10] * m[15] - m[5] * m[11] * m[14] - m[9] * m[6] * m[15]
+            + m[9] * m[7] * m[14]
+            + m[13] * m[6] * m[11]
+            - m[13] * m[7] * m[10];
+        inv[4] = -m[4] * m[10] * m[15] + m[4] * m[11] * m[14] + m[8] * m[6] * m[15]
+            - m[8] * m[7] * m[14]
+            - m[12] * m[6] * m[11]
+            + m[12] * m[7] * m[10];
+        inv[8] = m[4] * m[9] * m[15] - m[4] * m[11] * m[13] - m[8] * m[5] * m[15]
+            + m[8] * m[7] * m[13]
+            + m[12] * m[5] * m[11]
+            - m[12] * m[7] * m[9];
+        inv[12] = -m[4] * m[9] * m[14] + m[4] * m[10] * m[13] + m[8] * m[5] * m[14]
+            - m[8] * m[6] * m[13]
+            - m[12] * m[5] * m[10]
+            + m[12] * m[6] * m[9];
+        inv[1] = -m[1] * m[10] * m[15] + m[1] * m[11] * m[14] + m[9] * m[2] * m[15]
+            - m[9] * m[3] * m[14]
+            - m[13] * m[2] * m[11]
+            + m[13] * m[3] * m[10];
+        inv[5] = m[0] * m[10] * m[15] - m[0] * m[11] * m[14] - m[8] * m[2] * m[15]
+            + m[8] * m[3] * m[14]
+            + m[12] * m[2] * m[11]
+            - m[12] * m[3] * m[10];
+        inv[9] = -m[0] * m[9] * m[15] + m[0] * m[11] * m[13] + m[8] * m[1] * m[15]
+            - m[8] * m[3] * m[13]
+            - m[12] * m[1] * m[11]
+            + m[12] * m[3] * m[9];
+        inv[13] = m[0] * m[9] * m[14] - m[0] * m[10] * m[13] - m[8] * m[1] * m[14]
+            + m[8] * m[2] * m[13]
+            + m[12] * m[1] * m[10]
+            - m[12] * m[2] * m[9];
+        inv[2] =
+            m[1] * m[6] * m[15] - m[1] * m[7] * m[14] - m[5] * m[2] * m[15] + m[5] * m[3] * m[14] + m[13] * m[2] * m[7]
+                - m[13] * m[3] * m[6];
+        inv[6] = -m[0] * m[6] * m[15] + m[0] * m[7] * m[14] + m[4] * m[2] * m[15]
+            - m[4] * m[3] * m[14]
+            - m[12] * m[2] * m[7]
+            + m[12] * m[3] * m[6];
+        inv[10] =
+            m[0] * m[5] * m[15] - m[0] * m[7] * m[13] - m[4] * m[1] * m[15] + m[4] * m[3] * m[13] + m[12] * m[1] * m[7]
+                - m[12] * m[3] * m[5];
+        inv[14] = -m[0] * m[5] * m[14] + m[0] * m[6] * m[13] + m[4] * m[1] * m[14]
+            - m[4] * m[2] * m[13]
+            - m[12] * m[1] * m[6]
+            + m[12] * m[2] * m[5];
+        inv[3] =
+            -m[1] * m[6] * m[11] + m[1] * m[7] * m[10] + m[5] * m[2] * m[11] - m[5] * m[3] * m[10] - m[9] * m[2] * m[7]
+                + m[9] * m[3] * m[6];
+        inv[7] =
+            m[0] * m[6] * m[11] - m[0] * m[7] * m[10] - m[4] * m[2] * m[11] + m[4] * m[3] * m[10] + m[8] * m[2] * m[7]
+                - m[8] * m[3] * m[6];
+        inv[11] =
+            -m[0] * m[5] * m[11] + m[0] * m[7] * m[9] + m[4] * m[1] * m[11] - m[4] * m[3] * m[9] - m[8] * m[1] * m[7]
+                + m[8] * m[3] * m[5];
+        inv[15] =
+            m[0] * m[5] * m[10] - m[0] * m[6] * m[9] - m[4] * m[1] * m[10] + m[4] * m[2] * m[9] + m[8] * m[1] * m[6]
+                - m[8] * m[2] * m[5];
+
+        let det = m[0] * inv[0] + m[1] * inv[4] + m[2] * inv[8] + m[3] * inv[12];
+        if det == T::zero() {
+            return None;
+        }
+        let inv_det = T::one() / det;
+        Some(Self::from_array(inv.map(|v| v * inv_det)))
+    }
+
+    /// Transforms a point, dividing by the resulting w.
+    pub fn transform_point(&self, p: Vec3<T>) -> Vec3<T> {
+        let m = &self.data;
+        let x = m[0] * p.x + m[4] * p.y + m[8] * p.z + m[12];
+        let y = m[1] * p.x + m[5] * p.y + m[9] * p.z + m[13];
+        let z = m[2] * p.x + m[6] * p.y + m[10] * p.z + m[14];
+        let w = m[3] * p.x + m[7] * p.y + m[11] * p.z + m[15];
+        Vec3::new(x / w, y / w, z / w)
+    }
+
+    pub fn ortho(&mut self, left: T, right: T, bottom: T, top: T, near_plane: T, far_plane: T) {
+        self.set_to_identity();
+
+        let one = T::one();
+        let two = one + one;
+
+        let width = right - left;
+        let height = top - bottom;
+        let depth = far_plane - near_plane;
+
+        self.data[0] = two / width;
+        self.data[5] = two / height;
+        self.data[10] = -two / depth;
+        self.data[12] = -(right + left) / width;
+        self.data[13] = -(top + bottom) / height;
+        self.data[14] = -(far_plane + near_plane) / depth;
+    }
+}
+
+impl<T: Float> Default for Mat4<T> {
+    /// Identity matrix.
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Float> Mul for Mat4<T> {
+    type Output = Self;
+    fn mul(self, other: Self) -> Self {
+        let mut data = Self::identity_data();
+
+        for col in 0..4 {
+            for row in 0..4 {
+                let mut sum = T::zero();
+                for i in 0..4 {
+                    let a = self.data[i * 4 + row];
+                    let b = other.data[col * 4 + i];
+                    sum = sum + a * b;
+                }
+                data[col * 4 + row] = sum;
+            }
+        }
+        Self::from_array(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_vec_near(a: Vec3<f64>, b: Vec3<f64>) {
+        assert!((a - b).length() < 1e-9, "{} != {}", a, b);
+    }
+
+    /// Column-major products and inverses of transformation matrices.
+    #[test]
+    fn products() {
+        // Translation after scaling: the point is scaled first
+        let mut m = Mat4::<f64>::new();
+        m.translate(Vec3::new(1.0, 2.0, 3.0));
+        m.scale(Vec3::new(2.0, 2.0, 2.0));
+        assert_vec_near(m.transform_point(Vec3::new(1.0, 1.0, 1.0)), Vec3::new(3.0, 4.0, 5.0));
+        let a = Mat4::from_array(std::array::from_fn(|i| i as f64 + 1.0));
+        // Diagonally dominant, so well conditioned
+        let b = Mat4::from_array(std::array::from_fn(|i| {
+            if i % 5 == 0 { 4.0 } else { (i as f64 * 0.5).sin() }
+        }));
+        let p = Vec3::new(0.3, -0.2, 0.7);
+        let product = a * b;
+        // (A B) p = A (B p) for the homogeneous coordinates, compared before the division by w
+        let ab = product.transform_point(p);
+        let bp = b.transform_point(p);
+        let w_b = b.data[3] * p.x + b.data[7] * p.y + b.data[11] * p.z + b.data[15];
+        let a_bp = {
+            let m = &a.data;
+            let (x, y, z, w) = (bp.x * w_b, bp.y * w_b, bp.z * w_b, w_b);
+            Vec3::new(
+                m[0] * x + m[4] * y + m[8] * z + m[12] * w,
+                m[1] * x + m[5] * y + m[9] * z + m[13] * w,
+                m[2] * x + m[6] * y + m[10] * z + m[14] * w,
+            ) / (m[3] * x + m[7] * y + m[11] * z + m[15] * w)
+        };
+        assert_vec_near(ab, a_bp);
+        assert_eq!(Mat4::<f64>::new() * a, a);
+        assert_eq!(Mat4::<f64>::default(), Mat4::new());
+        let inverse = (b * m).inverted().unwrap();
+        assert_vec_near((inverse * b * m).transform_point(p), p);
+        assert!(Mat4::<f64>::from_array([0.0; 16]).inverted().is_none());
+
+        let z_90 = Quaternion::from_axis_and_angle(Vec3::new(0.0, 0.0, 1.0), 90.0);
+        let mut rotated = Mat4::<f64>::new();
+        rotated.rotate(z_90);
+        assert_eq!(rotated, z_90.to_rotation_matrix());
+    }
+
+    /// Camera and projection matrices.
+    #[test]
+    fn view_and_projection() {
+        // The eye is at the origin of view space looking down -Z
+        let mut view = Mat4::<f64>::new();
+        view.look_at(
+            Vec3::new(0.0, 0.0, 10.0),
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+        );
+        assert_vec_near(view.transform_point(Vec3::new(0.0, 0.0, 10.0)), Vec3::zero());
+        assert_vec_near(view.transform_point(Vec3::zero()), Vec3::new(0.0, 0.0, -10.0));
+        assert_vec_near(
+            view.transform_point(Vec3::new(1.0, 2.0, 0.0)),
+            Vec3::new(1.0, 2.0, -10.0),
+        );
+
+        // The near and far planes map to -1 and 1 of normalized device coordinates
+        let mut perspective = Mat4::<f64>::new();
+        perspective.perspective(90.0, 2.0, 1.0, 100.0);
+        assert_vec_near(
+            perspective.transform_point(Vec3::new(0.0, 0.0, -1.0)),
+            Vec3::new(0.0, 0.0, -1.0),
+        );
+        assert_vec_near(
+            perspective.transform_point(Vec3::new(0.0, 0.0, -100.0)),
+            Vec3::new(0.0, 0.0, 1.0),
+        );
+        // The 90° field of view spans the height at the distance of 1
+        assert_vec_near(
+            perspective.transform_point(Vec3::new(2.0, 1.0, -1.0)),
+            Vec3::new(1.0, 1.0, -1.0),
+        );
+        let mut ortho = Mat4::<f64>::new();
+        ortho.ortho(-2.0, 2.0, -1.0, 1.0, 1.0, 11.0);
+        assert_vec_near(
+            ortho.transform_point(Vec3::new(2.0, -1.0, -1.0)),
+            Vec3::new(1.0, -1.0, -1.0),
+        );
+        assert_vec_near(
+            ortho.transform_point(Vec3::new(0.0, 0.0, -11.0)),
+            Vec3::new(0.0, 0.0, 1.0),
+        );
+    }
+}
@@ -1,3 +1,6 @@
+// Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
+// Licensed under the MIT License
+
 use super::matrix::Mat4;
 use super::vector::Vec3;
 use num_traits::Float;
@@ -127,3 +130,41 @@ impl<T: Float> Mul for Quaternion<T> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_vec_near(a: Vec3<f64>, b: Vec3<f64>) {
+        assert!((a - b).length() < 1e-9, "{} != {}", a, b);
+    }
+
+    /// Rotation by 90° about Z turns X into Y, products of quaternions compose the rotations.
+    #[test]
+    fn rotations() {
+        let p = Vec3::new(0.3, -0.2, 0.7);
+        let z_90 = Quaternion::from_axis_and_angle(Vec3::new(0.0, 0.0, 1.0), 90.0);
+        let x_90 = Quaternion::from_axis_and_angle(Vec3::new(1.0, 0.0, 0.0), 90.0);
+        assert_vec_near(
+            z_90.to_rotation_matrix().transform_point(Vec3::new(1.0, 0.0, 0.0)),
+            Vec3::new(0.0, 1.0, 0.0),
+        );
+        let composed = (z_90 * x_90).to_rotation_matrix();
+        let sequential = z_90.to_rotation_matrix() * x_90.to_rotation_matrix();
+        assert_vec_near(composed.transform_point(p), sequential.transform_point(p));
+        // Y → Z by X, then Z stays by Z
+        assert_vec_near(
+            composed.transform_point(Vec3::new(0.0, 1.0, 0.0)),
+            Vec3::new(0.0, 0.0, 1.0),
+        );
+        for (from, to) in [
+            (Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 2.0, 0.0)),
+            (Vec3::new(1.0, 1.0, 0.0), Vec3::new(-1.0, -1.0, 0.0)),
+            (Vec3::new(0.0, 0.0, 3.0), Vec3::new(0.0, 0.0, 1.0)),
+        ] {
+            let rotation = Quaternion::rotation_to(from, to).to_rotation_matrix();
+            assert_vec_near(rotation.transform_point(from.normalized()), to.normalized());
+        }
+        assert!(Quaternion::rotation_to(p, p).approx_eq(Quaternion::new(1.0, 0.0, 0.0, 0.0)));
+    }
+}
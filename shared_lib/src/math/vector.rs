@@ -1,3 +1,6 @@
+// Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
+// Licensed under the MIT License
+
 use num_traits::Float;
 use std::fmt;
 use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
@@ -161,8 +164,32 @@ impl<T: Float> DivAssign<T> for Vec3<T> {
     }
 }
 
+impl<T: Float> From<[T; 3]> for Vec3<T> {
+    fn from(v: [T; 3]) -> Self {
+        Self::new(v[0], v[1], v[2])
+    }
+}
+
+impl<T: Float> From<Vec3<T>> for [T; 3] {
+    fn from(v: Vec3<T>) -> Self {
+        [v.x, v.y, v.z]
+    }
+}
+
 impl<T: Float + fmt::Display> fmt::Display for Vec3<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "Vec3D({:.2}, {:.2}, {:.2})", self.x, self.y, self.z)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn array_conversion() {
+        let p = Vec3::new(0.3, -0.2, 0.7);
+        let array: [f64; 3] = p.into();
+        assert_eq!(Vec3::from(array), p);
+    }
+}
@@ -0,0 +1,303 @@
+// Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
+// Licensed under the MIT License
+
+use std::collections::HashMap;
+
+use crate::cell::UnitCell;
+
+// Largest number of bins of the periodic grid along a lattice vector
+const MAX_BINS: usize = 64;
+// Largest number of bins of the bounding box per point, sparser points are hashed
+const MAX_BINS_PER_POINT: usize = 8;
+
+/// Neighbor search on a grid of bins at least as wide as the cutoff (spatial hashing). Points closer than
+/// the cutoff are in the same or adjacent bins, so a query visits 27 bins whatever the shape of the structure,
+/// unlike a sweep along one axis, which degrades to comparing all pairs for structures narrow along it.
+/// With a periodic cell the bins divide the cell and neighbors are also found among periodic images of the points.
+pub struct NeighborGrid {
+    points: Vec<[f64; 3]>,
+    cutoff: f64,
+    bins: Bins,
+}
+
+enum Bins {
+    Dense(DenseBins),
+    /// Occupied cubic bins by their integer coordinates, for points too sparse to allocate all bins
+    /// of their bounding box.
+    Sparse {
+        width: f64,
+        bins: HashMap<[i64; 3], Vec<usize>>,
+    },
+    Periodic(PeriodicBins),
+}
+
+/// All cubic bins of the bounding box of the points, with indices of points sorted by bins.
+struct DenseBins {
+    origin: [f64; 3],
+    width: f64,
+    dims: [usize; 3],
+    // Points of the bin `b` are `indices[starts[b]..starts[b + 1]]`
+    starts: Vec<usize>,
+    indices: Vec<usize>,
+}
+
+impl DenseBins {
+    /// Bins of the points, `None` if there would be many more bins than points.
+    fn new(points: &[[f64; 3]], width: f64) -> Option<Self> {
+        let finite = || points.iter().filter(|point| point.iter().all(|x| x.is_finite()));
+        let mut min = [f64::INFINITY; 3];
+        let mut max = [f64::NEG_INFINITY; 3];
+        for point in finite() {
+            for a in 0..3 {
+                min[a] = min[a].min(point[a]);
+                max[a] = max[a].max(point[a]);
+            }
+        }
+        let limit = (MAX_BINS_PER_POINT * points.len()).max(64) as f64;
+        let extents: [f64; 3] = std::array::from_fn(|a| ((max[a] - min[a]) / width).floor().max(0.0) + 1.0);
+        if extents.iter().any(|x| !x.is_finite() || *x > limit) || extents.iter().product::<f64>() > limit {
+            return None;
+        }
+        let dims = extents.map(|x| x as usize);
+
+        let mut result = Self {
+            origin: min,
+            width,
+            dims,
+            starts: vec![0; dims[0] * dims[1] * dims[2] + 1],
+            indices: Vec::new(),
+        };
+        // Counting sort of the points by bins, points with non-finite coordinates have no neighbors
+        let bins: Vec<Option<usize>> = points.iter().map(|&point| result.flat_index(point)).collect();
+        for bin in bins.iter().flatten() {
+            result.starts[bin + 1] += 1;
+        }
+        for b in 1..result.starts.len() {
+            result.starts[b] += result.starts[b - 1];
+        }
+        let mut next = result.starts.clone();
+        result.indices = vec![0; result.starts[result.starts.len() - 1]];
+        for (i, bin) in bins.iter().enumerate() {
+            if let Some(bin) = *bin {
+                result.indices[next[bin]] = i;
+                next[bin] += 1;
+            }
+        }
+        Some(result)
+    }
+
+    /// Bin of the position by axes, may be outside the box.
+    fn bin(&self, position: [f64; 3]) -> [i64; 3] {
+        std::array::from_fn(|a| ((position[a] - self.origin[a]) / self.width).floor() as i64)
+    }
+
+    fn flat_index(&self, position: [f64; 3]) -> Option<usize> {
+        let bin = self.bin(position);
+        let inside = (0..3).all(|a| (0..self.dims[a] as i64).contains(&bin[a]));
+        inside.then(|| (bin[2] as usize * self.dims[1] + bin[1] as usize) * self.dims[0] + bin[0] as usize)
+    }
+
+    fn for_each_candidate(&self, position: [f64; 3], mut f: impl FnMut(usize)) {
+        let bin = self.bin(position);
+        // Bins around the position clamped to the box
+        let range = |a: usize| {
+            let start = bin[a].saturating_sub(1).max(0);
+            let end = bin[a].saturating_add(1).min(self.dims[a] as i64 - 1);
+            start as usize..(end + 1).max(start) as usize
+        };
+        for z in range(2) {
+            for y in range(1) {
+                let row = (z * self.dims[1] + y) * self.dims[0];
+                let xs = range(0);
+                if xs.is_empty() {
+                    continue;
+                }
+                // Bins of a row are consecutive, so are their points
+                let points = &self.indices[self.starts[row + xs.start]..self.starts[row + xs.end]];
+                points.iter().for_each(|&j| f(j));
+            }
+        }
+    }
+}
+
+/// Points binned by their fractional coordinates wrapped into the cell.
+struct PeriodicBins {
+    cell: UnitCell,
+    // Fractional coordinates of the points, not wrapped
+    fractional: Vec<[f64; 3]>,
+    bins: [usize; 3],
+    // Images besides the nearest one that may be within the cutoff on each side along a lattice vector,
+    // non-zero only for cells less than three cutoffs wide
+    reach: [i32; 3],
+    // Indices of points in each bin
+    grid: Vec<Vec<usize>>,
+}
+
+impl PeriodicBins {
+    fn new(cell: &UnitCell, points: &[[f64; 3]], cutoff: f64) -> Self {
+        // Widths of the cell across the planes of the other two lattice vectors
+        let widths = cell
+            .reciprocal_vectors()
+            .map(|reciprocal| 1.0 / reciprocal.iter().map(|x| x * x).sum::<f64>().sqrt());
+        let bins = widths.map(|width| ((width / cutoff) as usize).clamp(1, MAX_BINS));
+        let reach = std::array::from_fn(|a| match bins[a] {
+            0..3 => (cutoff / widths[a] + 0.5).ceil() as i32,
+            _ => 0,
+        });
+        let mut result = Self {
+            cell: *cell,
+            fractional: points.iter().map(|&point| cell.to_fractional(point)).collect(),
+            bins,
+            reach,
+            grid: vec![Vec::new(); bins[0] * bins[1] * bins[2]],
+        };
+        for i in 0..points.len() {
+            let index = result.flat_index(result.bin(result.fractional[i]));
+            result.grid[index].push(i);
+        }
+        result
+    }
+
+    fn bin(&self, fractional: [f64; 3]) -> [usize; 3] {
+        std::array::from_fn(|a| {
+            let wrapped = fractional[a] - fractional[a].floor();
+            ((wrapped * self.bins[a] as f64) as usize).min(self.bins[a] - 1)
+        })
+    }
+
+    fn flat_index(&self, bin: [usize; 3]) -> usize {
+        (bin[2] * self.bins[1] + bin[1]) * self.bins[0] + bin[0]
+    }
+
+    /// Bins along the axis around `bin`, every bin once even if the cell is less than three bins wide.
+    fn adjacent(&self, axis: usize, bin: usize) -> Vec<usize> {
+        let n = self.bins[axis];
+        if n < 3 {
+            (0..n).collect()
+        } else {
+            vec![(bin + n - 1) % n, bin, (bin + 1) % n]
+        }
+    }
+
+    fn for_each_neighbor(&self, position: [f64; 3], cutoff_sq: f64, mut f: impl FnMut(usize, [i32; 3], f64)) {
+        let fp = self.cell.to_fractional(position);
+        let [bx, by, bz] = self.bin(fp);
+        let [ra, rb, rc] = self.reach;
+        for z in self.adjacent(2, bz) {
+            for y in self.adjacent(1, by) {
+                for x in self.adjacent(0, bx) {
+                    for &j in &self.grid[self.flat_index([x, y, z])] {
+                        let fj = self.fractional[j];
+                        // Shift of the point into the cell nearest to the position, then the further images
+                        // of small cells
+                        let nearest: [i32; 3] = std::array::from_fn(|a| -(fj[a] - fp[a]).round() as i32);
+                        for image in
+                            (-rc..=rc).flat_map(|w| (-rb..=rb).flat_map(move |v| (-ra..=ra).map(move |u| [u, v, w])))
+                        {
+                            let shift: [i32; 3] = std::array::from_fn(|a| nearest[a] + image[a]);
+                            let delta = self
+                                .cell
+                                .to_cartesian(std::array::from_fn(|a| fj[a] + shift[a] as f64 - fp[a]));
+                            let distance_sq = delta.iter().map(|d| d * d).sum::<f64>();
+                            if distance_sq < cutoff_sq {
+                                f(j, shift, distance_sq);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl NeighborGrid {
+    /// Bins the points for queries of neighbors closer than `cutoff`, through the boundaries of the periodic
+    /// cell if one is given.
+    pub fn new(points: Vec<[f64; 3]>, cutoff: f64, cell: Option<&UnitCell>) -> Self {
+        // A zero cutoff finds nothing, any positive width keeps the bins valid
+        let width = if cutoff > 0.0 { cutoff } else { 1.0 };
+        let bins = match cell {
+            Some(cell) => Bins::Periodic(PeriodicBins::new(cell, &points, width)),
+            None => match DenseBins::new(&points, width) {
+                Some(dense) => Bins::Dense(dense),
+                None => {
+                    let mut bins: HashMap<[i64; 3], Vec<usize>> = HashMap::new();
+                    for (i, point) in points.iter().enumerate() {
+                        bins.entry(Self::sparse_bin(*point, width)).or_default().push(i);
+                    }
+                    Bins::Sparse { width, bins }
+                }
+            },
+        };
+        Self { points, cutoff, bins }
+    }
+
+    fn sparse_bin(point: [f64; 3], width: f64) -> [i64; 3] {
+        point.map(|x| (x / width).floor() as i64)
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    pub fn point(&self, i: usize) -> [f64; 3] {
+        self.points[i]
+    }
+
+    /// Calls `f(j, image, distance²)` for every point `j` closer than the cutoff to the position. `image` is
+    /// the cell of the found copy of the point in lattice vectors, zeros without a periodic cell.
+    /// A point may be found several times as different images in cells less than three cutoffs wide.
+    pub fn for_each_neighbor(&self, position: [f64; 3], mut f: impl FnMut(usize, [i32; 3], f64)) {
+        let cutoff_sq = self.cutoff * self.cutoff;
+        match &self.bins {
+            Bins::Dense(dense) => dense.for_each_candidate(position, |j| {
+                let point = self.points[j];
+                let distance_sq = (0..3).map(|a| (point[a] - position[a]).powi(2)).sum::<f64>();
+                if distance_sq < cutoff_sq {
+                    f(j, [0, 0, 0], distance_sq);
+                }
+            }),
+            Bins::Sparse { width, bins } => {
+                let [bx, by, bz] = Self::sparse_bin(position, *width);
+                let around = |b: i64| b.saturating_sub(1)..=b.saturating_add(1);
+                for z in around(bz) {
+                    for y in around(by) {
+                        for x in around(bx) {
+                            let Some(bin) = bins.get(&[x, y, z]) else { continue };
+                            for &j in bin {
+                                let point = self.points[j];
+                                let distance_sq = (0..3).map(|a| (point[a] - position[a]).powi(2)).sum::<f64>();
+                                if distance_sq < cutoff_sq {
+                                    f(j, [0, 0, 0], distance_sq);
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            Bins::Periodic(periodic) => periodic.for_each_neighbor(position, cutoff_sq, f),
+        }
+    }
+
+    /// Neighbors of the point `i` that form pairs with it once: points after it and, in a periodic cell,
+    /// its own images in one direction.
+    pub fn for_each_pair(&self, i: usize, mut f: impl FnMut(usize, [i32; 3], f64)) {
+        self.for_each_neighbor(self.points[i], |j, image, distance_sq| {
+            if j > i || (j == i && image > [0, 0, 0]) {
+                f(j, image, distance_sq);
+            }
+        });
+    }
+
+    /// Translation of a periodic image in Cartesian coordinates, zero without a periodic cell.
+    pub fn translation(&self, image: [i32; 3]) -> [f64; 3] {
+        match &self.bins {
+            Bins::Periodic(periodic) => periodic.cell.to_cartesian(image.map(f64::from)),
+            Bins::Dense(_) | Bins::Sparse { .. } => [0.0; 3],
+        }
+    }
+}
@@ -0,0 +1,147 @@
+// Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
+// Licensed under the MIT License
+
+//! Geometry optimization with the generic force field of `force_field`, meant to clean up hand-edited or
+//! generated geometries before a quantum chemistry calculation rather than to find accurate minima. Steepest
+//! descent moves the atoms along the forces with a step that grows while the energy decreases and shrinks when
+//! it rises, every accepted step is a frame of the trajectory.
+
+use serde::Serialize;
+
+use crate::connectivity::Bond;
+use crate::force_field::{EnergyTerms, ForceField};
+use crate::types::AtomicCoordinates;
+
+/// Default number of steps after which the optimization stops unconverged.
+pub const DEFAULT_MAX_STEPS: usize = 500;
+/// Default largest force on an atom in kcal/(mol·Å) below which the geometry is converged.
+pub const DEFAULT_CONVERGENCE: f64 = 0.5;
+// Largest displacement of an atom in one step in Å
+const MAX_DISPLACEMENT: f64 = 0.2;
+// First step in Å per kcal/(mol·Å) of force
+const INITIAL_STEP: f64 = 1e-3;
+// The step is multiplied by these after an accepted and a rejected step
+const STEP_GROWTH: f64 = 1.2;
+const STEP_SHRINK: f64 = 0.5;
+// Steps shorter than this in Å cannot lower the energy any further
+const MIN_DISPLACEMENT: f64 = 1e-6;
+
+/// Geometry of one step of the optimization with its energy and forces.
+#[derive(Serialize, Clone, Debug)]
+pub struct Frame {
+    /// Step at which the geometry was reached, 0 for the starting geometry.
+    pub step: usize,
+    pub energy: EnergyTerms,
+    /// Largest force on an atom and the root mean square force in kcal/(mol·Å).
+    pub max_force: f64,
+    pub rms_force: f64,
+    pub x: Vec<f64>,
+    pub y: Vec<f64>,
+    pub z: Vec<f64>,
+}
+
+/// Trajectory of an optimization, the last frame is the optimized geometry.
+#[derive(Serialize, Clone, Debug)]
+pub struct Optimization {
+    pub frames: Vec<Frame>,
+    /// The largest force fell below the convergence threshold within the steps.
+    pub converged: bool,
+    /// Steps taken, including the rejected ones that raised the energy.
+    pub steps: usize,
+}
+
+impl Frame {
+    /// Structure at the geometry of the frame, with the atoms, bonds, charges, chains, cell and isotopes of the
+    /// starting structure.
+    pub fn coordinates(&self, data: &AtomicCoordinates) -> AtomicCoordinates {
+        with_positions(data, self.x.clone(), self.y.clone(), self.z.clone())
+    }
+}
+
+fn with_positions(data: &AtomicCoordinates, x: Vec<f64>, y: Vec<f64>, z: Vec<f64>) -> AtomicCoordinates {
+    AtomicCoordinates {
+        atomic_num: data.atomic_num.clone(),
+        x,
+        y,
+        z,
+        connectivity: data.connectivity.clone(),
+        charges: data.charges.clone(),
+        chains: data.chains.clone(),
+        cell: data.cell,
+        isotopes: data.isotopes.clone(),
+    }
+}
+
+fn forces(gradient: &[[f64; 3]]) -> (f64, f64) {
+    let squares: Vec<f64> = gradient.iter().map(|g| g.iter().map(|x| x * x).sum()).collect();
+    let max = squares.iter().copied().fold(0.0, f64::max).sqrt();
+    let rms = if squares.is_empty() {
+        0.0
+    } else {
+        (squares.iter().sum::<f64>() / squares.len() as f64).sqrt()
+    };
+    (max, rms)
+}
+
+/// Optimizes the geometry with the force field set up from the bonds given by zero-based atom indices, e.g.
+/// from `connectivity::find_bonds`, for at most `max_steps` steps or until the largest force on an atom is below
+/// `convergence` in kcal/(mol·Å). Atoms without force field terms, e.g. pseudo-elements, keep their positions.
+pub fn optimize(
+    data: &AtomicCoordinates,
+    bonds: &[Bond],
+    max_steps: usize,
+    convergence: f64,
+) -> Result<Optimization, String> {
+    if !(convergence.is_finite() && convergence > 0.0) {
+        return Err(format!("Convergence threshold must be positive, got {}", convergence));
+    }
+    let force_field = ForceField::new(data, bonds)?;
+    let mut current = with_positions(data, data.x.clone(), data.y.clone(), data.z.clone());
+    let (mut energy, mut gradient) = force_field.energy_and_gradient(&current)?;
+    let (max_force, rms_force) = forces(&gradient);
+    let frame = |step, energy, max_force, rms_force, current: &AtomicCoordinates| Frame {
+        step,
+        energy,
+        max_force,
+        rms_force,
+        x: current.x.clone(),
+        y: current.y.clone(),
+        z: current.z.clone(),
+    };
+    let mut frames = vec![frame(0, energy, max_force, rms_force, &current)];
+    let mut converged = max_force < convergence;
+
+    let mut step_size = INITIAL_STEP;
+    let mut steps = 0;
+    let mut trial = frames[0].coordinates(data);
+    while !converged && steps < max_steps {
+        steps += 1;
+        let largest = forces(&gradient).0;
+        let scale = step_size.min(MAX_DISPLACEMENT / largest);
+        if scale * largest < MIN_DISPLACEMENT {
+            break;
+        }
+        for (i, g) in gradient.iter().enumerate() {
+            trial.x[i] = current.x[i] - scale * g[0];
+            trial.y[i] = current.y[i] - scale * g[1];
+            trial.z[i] = current.z[i] - scale * g[2];
+        }
+        let (trial_energy, trial_gradient) = force_field.energy_and_gradient(&trial)?;
+        if trial_energy.total < energy.total {
+            std::mem::swap(&mut current, &mut trial);
+            (energy, gradient) = (trial_energy, trial_gradient);
+            let (max_force, rms_force) = forces(&gradient);
+            frames.push(frame(steps, energy, max_force, rms_force, &current));
+            converged = max_force < convergence;
+            step_size *= STEP_GROWTH;
+        } else {
+            step_size *= STEP_SHRINK;
+        }
+    }
+
+    Ok(Optimization {
+        frames,
+        converged,
+        steps,
+    })
+}
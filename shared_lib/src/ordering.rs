@@ -0,0 +1,274 @@
+// Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
+// Licensed under the MIT License
+
+use std::cmp::Reverse;
+
+use serde::{Deserialize, Serialize};
+
+use crate::connectivity::{self, DEFAULT_BOND_TOLERANCE};
+use crate::periodic_table::get_element_by_number;
+use crate::types::{AtomicCoordinates, Connection};
+
+/// New order of the atoms. Atoms which compare equal keep their original order.
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum AtomOrder {
+    /// By elements in the Hill order: C and H first, then the other elements alphabetically, or all of them
+    /// alphabetically without carbon. Dummy atoms are the last.
+    Element,
+    /// By the distance from the centroid of the atoms, the nearest first.
+    Centroid,
+    /// By ranks of atoms in the bond graph, which do not depend on the input order except among equivalent
+    /// atoms: heavier and more bonded atoms first, hydrogens last.
+    Canonical,
+    /// Indices of the atoms (starting from 1) in the new order, every atom once.
+    Manual(Vec<usize>),
+}
+
+impl AtomOrder {
+    /// Parses "element", "centroid", "canonical" or a comma-separated list of atom indices starting from 1.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        match text.trim().to_ascii_lowercase().as_str() {
+            "element" => Ok(AtomOrder::Element),
+            "centroid" => Ok(AtomOrder::Centroid),
+            "canonical" => Ok(AtomOrder::Canonical),
+            list => list
+                .split(',')
+                .map(|index| index.trim().parse::<usize>())
+                .collect::<Result<Vec<_>, _>>()
+                .map(AtomOrder::Manual)
+                .map_err(|_| format!("Unknown atom order: {}", text)),
+        }
+    }
+}
+
+/// Correspondence between the original and the new indices of the atoms, both starting from 1, so that data
+/// kept aside the coordinates (e.g. frozen flags) can be permuted the same way.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct AtomMapping {
+    /// Original index of every atom in the new order.
+    pub old: Vec<usize>,
+    /// New index of every atom in the original order.
+    pub new: Vec<usize>,
+}
+
+impl AtomMapping {
+    /// Mapping of the permutation returned by `atom_order`.
+    pub fn new(order: &[usize]) -> Self {
+        Self {
+            old: order.iter().map(|i| i + 1).collect(),
+            new: inverse(order).iter().map(|i| i + 1).collect(),
+        }
+    }
+}
+
+/// Permutation of the atoms of the structure: the zero-based original index of every atom in the new order.
+pub fn atom_order(data: &AtomicCoordinates, order: &AtomOrder) -> Result<Vec<usize>, String> {
+    let num_atoms = data.atomic_num.len();
+    let mut result: Vec<usize> = (0..num_atoms).collect();
+    match order {
+        AtomOrder::Element => {
+            let symbols: Vec<Option<&str>> = data
+                .atomic_num
+                .iter()
+                .map(|&n| get_element_by_number(n).filter(|_| n > 0).map(|element| element.symbol))
+                .collect();
+            let has_carbon = symbols.contains(&Some("C"));
+            let rank = |symbol: Option<&str>| match symbol {
+                Some("C") if has_carbon => 0,
+                Some("H") if has_carbon => 1,
+                Some(_) => 2,
+                None => 3,
+            };
+            result.sort_by(|&i, &j| {
+                rank(symbols[i])
+                    .cmp(&rank(symbols[j]))
+                    .then(symbols[i].cmp(&symbols[j]))
+            });
+        }
+        AtomOrder::Centroid => {
+            check_lengths(data)?;
+            let n = num_atoms.max(1) as f64;
+            let center = [&data.x, &data.y, &data.z].map(|values| values.iter().sum::<f64>() / n);
+            let distance_sq = |i: usize| {
+                (data.x[i] - center[0]).powi(2) + (data.y[i] - center[1]).powi(2) + (data.z[i] - center[2]).powi(2)
+            };
+            result.sort_by(|&i, &j| distance_sq(i).total_cmp(&distance_sq(j)));
+        }
+        AtomOrder::Canonical => {
+            check_lengths(data)?;
+            let ranks = canonical_ranks(data);
+            result.sort_by_key(|&i| ranks[i]);
+        }
+        AtomOrder::Manual(indices) => {
+            let mut seen = vec![false; num_atoms];
+            for &index in indices {
+                if index == 0 || index > num_atoms {
+                    return Err(format!("Atom index {} is out of range", index));
+                }
+                if std::mem::replace(&mut seen[index - 1], true) {
+                    return Err(format!("Atom {} is listed more than once", index));
+                }
+            }
+            if indices.len() != num_atoms {
+                return Err(format!(
+                    "The order lists {} atoms but the structure has {}",
+                    indices.len(),
+                    num_atoms
+                ));
+            }
+            result = indices.iter().map(|index| index - 1).collect();
+        }
+    }
+    Ok(result)
+}
+
+/// Inverse permutation: the new index of every atom in the original order.
+pub fn inverse(order: &[usize]) -> Vec<usize> {
+    let mut result = vec![0; order.len()];
+    for (new, &old) in order.iter().enumerate() {
+        result[old] = new;
+    }
+    result
+}
+
+/// Values of the atoms in the new order, e.g. per-atom data kept aside the coordinates.
+pub fn permute<T: Clone>(values: &[T], order: &[usize]) -> Vec<T> {
+    order.iter().map(|&i| values[i].clone()).collect()
+}
+
+/// The structure with the atoms in the new order, their charges, chains, isotopes and bonds permuted alike.
+pub fn reorder(data: &AtomicCoordinates, order: &[usize]) -> Result<AtomicCoordinates, String> {
+    check_lengths(data)?;
+    let num_atoms = data.atomic_num.len();
+    if order.len() != num_atoms {
+        return Err(format!(
+            "The order lists {} atoms but the structure has {}",
+            order.len(),
+            num_atoms
+        ));
+    }
+    let new_index = inverse(order);
+    let connectivity = match &data.connectivity {
+        Some(connections) => Some(
+            connections
+                .iter()
+                .map(|c| match (new_index.get(c.atom_1), new_index.get(c.atom_2)) {
+                    (Some(&atom_1), Some(&atom_2)) => Ok(Connection {
+                        atom_1,
+                        atom_2,
+                        order: c.order,
+                    }),
+                    _ => Err(format!(
+                        "Bond {}-{} refers to a missing atom",
+                        c.atom_1 + 1,
+                        c.atom_2 + 1
+                    )),
+                })
+                .collect::<Result<Vec<_>, _>>()?,
+        ),
+        None => None,
+    };
+    Ok(AtomicCoordinates {
+        atomic_num: permute(&data.atomic_num, order),
+        x: permute(&data.x, order),
+        y: permute(&data.y, order),
+        z: permute(&data.z, order),
+        connectivity,
+        charges: data.charges.as_ref().map(|charges| permute(charges, order)),
+        chains: data.chains.as_ref().map(|chains| permute(chains, order)),
+        cell: data.cell,
+        isotopes: data.isotopes.as_ref().map(|isotopes| permute(isotopes, order)),
+    })
+}
+
+fn check_lengths(data: &AtomicCoordinates) -> Result<(), String> {
+    let num_atoms = data.atomic_num.len();
+    if data.x.len() != num_atoms || data.y.len() != num_atoms || data.z.len() != num_atoms {
+        return Err("Numbers of atoms and coordinates differ".to_string());
+    }
+    if data.charges.as_ref().is_some_and(|charges| charges.len() != num_atoms)
+        || data.chains.as_ref().is_some_and(|chains| chains.len() != num_atoms)
+        || data
+            .isotopes
+            .as_ref()
+            .is_some_and(|isotopes| isotopes.len() != num_atoms)
+    {
+        return Err("Charges, chains and isotopes must be given for all atoms".to_string());
+    }
+    Ok(())
+}
+
+/// Distinct ranks of the atoms from 0. Atoms are first ranked by invariants of their own, then the ranks are
+/// refined by the ranks of the neighbors until they stop splitting (Morgan's algorithm). Atoms which remain
+/// tied are equivalent for the refinement, the first of them in the original order takes the lowest rank and
+/// the others are refined again.
+fn canonical_ranks(data: &AtomicCoordinates) -> Vec<usize> {
+    let num_atoms = data.atomic_num.len();
+    let bonds = connectivity::find_bonds(data, DEFAULT_BOND_TOLERANCE);
+    let mut neighbors: Vec<Vec<(usize, u8)>> = vec![Vec::new(); num_atoms];
+    for bond in &bonds {
+        neighbors[bond.atom_index_1].push((bond.atom_index_2, bond.order));
+        neighbors[bond.atom_index_2].push((bond.atom_index_1, bond.order));
+    }
+    let invariants: Vec<_> = (0..num_atoms)
+        .map(|i| {
+            let n = data.atomic_num[i];
+            let valence: u32 = neighbors[i].iter().map(|&(_, order)| u32::from(order)).sum();
+            (n == 1, Reverse(n), Reverse(neighbors[i].len()), Reverse(valence))
+        })
+        .collect();
+    let mut ranks = dense_ranks(&invariants);
+
+    loop {
+        ranks = refine(ranks, &neighbors);
+        let mut counts = vec![0usize; num_atoms];
+        ranks.iter().for_each(|&rank| counts[rank] += 1);
+        let Some(tied) = (0..num_atoms).find(|&rank| counts[rank] > 1) else {
+            return ranks;
+        };
+        // The first tied atom stays ahead of the rest of its class
+        let first = ranks.iter().position(|&rank| rank == tied).unwrap();
+        let keys: Vec<(usize, bool)> = (0..num_atoms)
+            .map(|i| (ranks[i], i != first && ranks[i] == tied))
+            .collect();
+        ranks = dense_ranks(&keys);
+    }
+}
+
+/// Splits classes of atoms by the sorted ranks of their neighbors until the number of classes stops growing.
+fn refine(mut ranks: Vec<usize>, neighbors: &[Vec<(usize, u8)>]) -> Vec<usize> {
+    let mut classes = count_classes(&ranks);
+    loop {
+        let keys: Vec<(usize, Vec<(usize, u8)>)> = (0..ranks.len())
+            .map(|i| {
+                let mut around: Vec<(usize, u8)> = neighbors[i].iter().map(|&(j, order)| (ranks[j], order)).collect();
+                around.sort_unstable();
+                (ranks[i], around)
+            })
+            .collect();
+        let refined = dense_ranks(&keys);
+        let refined_classes = count_classes(&refined);
+        if refined_classes == classes {
+            return ranks;
+        }
+        ranks = refined;
+        classes = refined_classes;
+    }
+}
+
+fn count_classes(ranks: &[usize]) -> usize {
+    ranks.iter().max().map_or(0, |max| max + 1)
+}
+
+/// Ranks from 0 in the order of the keys, equal keys share a rank.
+fn dense_ranks<K: Ord>(keys: &[K]) -> Vec<usize> {
+    let mut indices: Vec<usize> = (0..keys.len()).collect();
+    indices.sort_by(|&i, &j| keys[i].cmp(&keys[j]));
+    let mut result = vec![0; keys.len()];
+    for k in 1..indices.len() {
+        let (previous, current) = (indices[k - 1], indices[k]);
+        result[current] = result[previous] + usize::from(keys[previous] != keys[current]);
+    }
+    result
+}
@@ -18,10 +18,40 @@ impl Element {
     }
 }
 
+// Pseudo-elements are sites without a nucleus of a real element: they have atomic numbers below 1, no mass and no
+// covalent radius, so they are never bonded, weighed or counted in formulas.
+
+/// Dummy atom X, e.g. a reference point of a Z-matrix. Other atomic numbers below 1 are dummy atoms as well.
+pub const DUMMY_ATOM: i32 = -1;
+/// Point charge Q of an embedding, e.g. the environment of a QM/MM calculation.
+pub const POINT_CHARGE: i32 = -2;
+/// Ghost atom Bq: basis functions without a nucleus, e.g. of the counterpoise correction.
+pub const GHOST_ATOM: i32 = -3;
+
+pub fn is_pseudo_element(atomic_number: i32) -> bool {
+    atomic_number < 1
+}
+
+/// Name of the pseudo-element, None for real elements.
+pub fn pseudo_element_name(atomic_number: i32) -> Option<&'static str> {
+    match atomic_number {
+        POINT_CHARGE => Some("point charge"),
+        GHOST_ATOM => Some("ghost atom"),
+        n if is_pseudo_element(n) => Some("dummy atom"),
+        _ => None,
+    }
+}
+
+/// Symbol of the element or pseudo-element, "X" for other atomic numbers below 1 and unknown atomic numbers.
+pub fn element_symbol(atomic_number: i32) -> &'static str {
+    get_element_by_number(atomic_number).map_or("X", |element| element.symbol)
+}
+
 pub fn get_element_by_number(atomic_number: i32) -> Option<Element> {
     match atomic_number {
-        -1 => Some(Element::new(-1, "X", 0.0)),
-        -2 => Some(Element::new(-2, "Q", 0.0)),
+        GHOST_ATOM => Some(Element::new(GHOST_ATOM, "Bq", 0.0)),
+        POINT_CHARGE => Some(Element::new(POINT_CHARGE, "Q", 0.0)),
+        DUMMY_ATOM => Some(Element::new(DUMMY_ATOM, "X", 0.0)),
         1 => Some(Element::new(1, "H", 0.32)),
         2 => Some(Element::new(2, "He", 0.46)),
         3 => Some(Element::new(3, "Li", 1.33)),
@@ -146,8 +176,9 @@ pub fn get_element_by_number(atomic_number: i32) -> Option<Element> {
 
 pub fn get_element_by_symbol(symbol: &str) -> Option<Element> {
     match symbol {
-        "X" => Some(Element::new(-1, "X", 0.0)),
-        "Q" => Some(Element::new(-2, "Q", 0.0)),
+        "X" => Some(Element::new(DUMMY_ATOM, "X", 0.0)),
+        "Q" => Some(Element::new(POINT_CHARGE, "Q", 0.0)),
+        "Bq" => Some(Element::new(GHOST_ATOM, "Bq", 0.0)),
         "H" => Some(Element::new(1, "H", 0.32)),
         "He" => Some(Element::new(2, "He", 0.46)),
         "Li" => Some(Element::new(3, "Li", 1.33)),
@@ -269,3 +300,487 @@ pub fn get_element_by_symbol(symbol: &str) -> Option<Element> {
         _ => None,
     }
 }
+
+/// Van der Waals radius in Angstroms (Bondi, Mantina et al. for main group elements).
+/// Elements without a tabulated value get the conventional 2.0, dummy atoms get None.
+pub fn get_vdw_radius(atomic_number: i32) -> Option<f64> {
+    if !(1..=118).contains(&atomic_number) {
+        return None;
+    }
+    let radius = match atomic_number {
+        1 => 1.2,
+        2 => 1.4,
+        3 => 1.82,
+        4 => 1.53,
+        5 => 1.92,
+        6 => 1.7,
+        7 => 1.55,
+        8 => 1.52,
+        9 => 1.47,
+        10 => 1.54,
+        11 => 2.27,
+        12 => 1.73,
+        13 => 1.84,
+        14 => 2.1,
+        15 => 1.8,
+        16 => 1.8,
+        17 => 1.75,
+        18 => 1.88,
+        19 => 2.75,
+        20 => 2.31,
+        28 => 1.63,
+        29 => 1.4,
+        30 => 1.39,
+        31 => 1.87,
+        32 => 2.11,
+        33 => 1.85,
+        34 => 1.9,
+        35 => 1.85,
+        36 => 2.02,
+        37 => 3.03,
+        38 => 2.49,
+        46 => 1.63,
+        47 => 1.72,
+        48 => 1.58,
+        49 => 1.93,
+        50 => 2.17,
+        51 => 2.06,
+        52 => 2.06,
+        53 => 1.98,
+        54 => 2.16,
+        55 => 3.43,
+        56 => 2.68,
+        78 => 1.75,
+        79 => 1.66,
+        80 => 1.55,
+        81 => 1.96,
+        82 => 2.02,
+        83 => 2.07,
+        84 => 1.97,
+        85 => 2.02,
+        86 => 2.2,
+        87 => 3.48,
+        88 => 2.83,
+        92 => 1.86,
+        _ => 2.0,
+    };
+    Some(radius)
+}
+
+/// Covalent radius in Angstroms (Pyykkö and Atsumi, single bonds), None for pseudo-elements and elements
+/// without a tabulated value.
+pub fn get_covalent_radius(atomic_number: i32) -> Option<f64> {
+    get_element_by_number(atomic_number)
+        .map(|element| element.covalent_radius)
+        .filter(|&radius| radius > 0.0)
+}
+
+/// Effective ionic radius in Angstroms of the most common ion of the element in octahedral coordination
+/// (Shannon), high-spin for transition metals. None for elements that rarely form ions, e.g. hydrogen, carbon
+/// and noble gases, and for pseudo-elements.
+pub fn get_ionic_radius(atomic_number: i32) -> Option<f64> {
+    let radius = match atomic_number {
+        3 => 0.76,   // Li+
+        4 => 0.45,   // Be2+
+        7 => 1.46,   // N3-
+        8 => 1.4,    // O2-
+        9 => 1.33,   // F-
+        11 => 1.02,  // Na+
+        12 => 0.72,  // Mg2+
+        13 => 0.535, // Al3+
+        14 => 0.4,   // Si4+
+        15 => 0.38,  // P5+
+        16 => 1.84,  // S2-
+        17 => 1.81,  // Cl-
+        19 => 1.38,  // K+
+        20 => 1.0,   // Ca2+
+        21 => 0.745, // Sc3+
+        22 => 0.605, // Ti4+
+        23 => 0.64,  // V3+
+        24 => 0.615, // Cr3+
+        25 => 0.83,  // Mn2+
+        26 => 0.78,  // Fe2+
+        27 => 0.745, // Co2+
+        28 => 0.69,  // Ni2+
+        29 => 0.73,  // Cu2+
+        30 => 0.74,  // Zn2+
+        31 => 0.62,  // Ga3+
+        32 => 0.53,  // Ge4+
+        33 => 0.58,  // As3+
+        34 => 1.98,  // Se2-
+        35 => 1.96,  // Br-
+        37 => 1.52,  // Rb+
+        38 => 1.18,  // Sr2+
+        39 => 0.9,   // Y3+
+        40 => 0.72,  // Zr4+
+        41 => 0.64,  // Nb5+
+        42 => 0.59,  // Mo6+
+        43 => 0.645, // Tc4+
+        44 => 0.68,  // Ru3+
+        45 => 0.665, // Rh3+
+        46 => 0.86,  // Pd2+
+        47 => 1.15,  // Ag+
+        48 => 0.95,  // Cd2+
+        49 => 0.8,   // In3+
+        50 => 0.69,  // Sn4+
+        51 => 0.76,  // Sb3+
+        52 => 2.21,  // Te2-
+        53 => 2.2,   // I-
+        55 => 1.67,  // Cs+
+        56 => 1.35,  // Ba2+
+        57 => 1.032, // La3+
+        58 => 1.01,  // Ce3+
+        59 => 0.99,  // Pr3+
+        60 => 0.983, // Nd3+
+        61 => 0.97,  // Pm3+
+        62 => 0.958, // Sm3+
+        63 => 0.947, // Eu3+
+        64 => 0.938, // Gd3+
+        65 => 0.923, // Tb3+
+        66 => 0.912, // Dy3+
+        67 => 0.901, // Ho3+
+        68 => 0.89,  // Er3+
+        69 => 0.88,  // Tm3+
+        70 => 0.868, // Yb3+
+        71 => 0.861, // Lu3+
+        72 => 0.71,  // Hf4+
+        73 => 0.64,  // Ta5+
+        74 => 0.6,   // W6+
+        75 => 0.63,  // Re4+
+        76 => 0.63,  // Os4+
+        77 => 0.68,  // Ir3+
+        78 => 0.8,   // Pt2+
+        79 => 1.37,  // Au+
+        80 => 1.02,  // Hg2+
+        81 => 1.5,   // Tl+
+        82 => 1.19,  // Pb2+
+        83 => 1.03,  // Bi3+
+        90 => 0.94,  // Th4+
+        92 => 0.89,  // U4+
+        _ => return None,
+    };
+    Some(radius)
+}
+
+/// Tabulated radii of the elements selectable by name, e.g. to size atoms of a representation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RadiusSet {
+    Covalent,
+    VanDerWaals,
+    Ionic,
+}
+
+impl RadiusSet {
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        match name.to_ascii_lowercase().replace(['-', ' '], "_").as_str() {
+            "covalent" => Ok(RadiusSet::Covalent),
+            "vdw" | "van_der_waals" => Ok(RadiusSet::VanDerWaals),
+            "ionic" => Ok(RadiusSet::Ionic),
+            _ => Err(format!("Unknown radius set: {}", name)),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            RadiusSet::Covalent => "covalent",
+            RadiusSet::VanDerWaals => "vdw",
+            RadiusSet::Ionic => "ionic",
+        }
+    }
+
+    /// Radius of the element in Angstroms, None if the set has no value for it.
+    pub fn radius(&self, atomic_number: i32) -> Option<f64> {
+        match self {
+            RadiusSet::Covalent => get_covalent_radius(atomic_number),
+            RadiusSet::VanDerWaals => get_vdw_radius(atomic_number),
+            RadiusSet::Ionic => get_ionic_radius(atomic_number),
+        }
+    }
+}
+
+/// Standard atomic weights, mass numbers of the most stable isotopes for elements without stable ones.
+const ATOMIC_MASSES: [f64; 118] = [
+    1.008, 4.0026, 6.94, 9.0122, 10.81, 12.011, 14.007, 15.999, 18.998, 20.18, //
+    22.99, 24.305, 26.982, 28.085, 30.974, 32.06, 35.45, 39.948, 39.098, 40.078, //
+    44.956, 47.867, 50.942, 51.996, 54.938, 55.845, 58.933, 58.693, 63.546, 65.38, //
+    69.723, 72.63, 74.922, 78.971, 79.904, 83.798, 85.468, 87.62, 88.906, 91.224, //
+    92.906, 95.95, 97.0, 101.07, 102.91, 106.42, 107.87, 112.41, 114.82, 118.71, //
+    121.76, 127.6, 126.9, 131.29, 132.91, 137.33, 138.91, 140.12, 140.91, 144.24, //
+    145.0, 150.36, 151.96, 157.25, 158.93, 162.5, 164.93, 167.26, 168.93, 173.05, //
+    174.97, 178.49, 180.95, 183.84, 186.21, 190.23, 192.22, 195.08, 196.97, 200.59, //
+    204.38, 207.2, 208.98, 209.0, 210.0, 222.0, 223.0, 226.0, 227.0, 232.04, //
+    231.04, 238.03, 237.0, 244.0, 243.0, 247.0, 247.0, 251.0, 252.0, 257.0, //
+    258.0, 259.0, 262.0, 267.0, 268.0, 269.0, 270.0, 269.0, 278.0, 281.0, //
+    282.0, 285.0, 286.0, 289.0, 290.0, 293.0, 294.0, 294.0,
+];
+
+/// Atomic mass in daltons, dummy atoms get None.
+pub fn get_atomic_mass(atomic_number: i32) -> Option<f64> {
+    if !(1..=118).contains(&atomic_number) {
+        return None;
+    }
+    Some(ATOMIC_MASSES[atomic_number as usize - 1])
+}
+
+/// Stable isotope of an element with its natural abundance.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Isotope {
+    pub mass_number: u32,
+    /// Atomic mass in daltons.
+    pub mass: f64,
+    /// Fraction of the atoms of the element in nature, the abundances of an element sum up to 1.
+    pub abundance: f64,
+}
+
+// Mass number, atomic mass and abundance of the stable isotopes (IUPAC, AME2016)
+fn isotope_table(atomic_number: i32) -> Option<&'static [(u32, f64, f64)]> {
+    let isotopes: &[(u32, f64, f64)] = match atomic_number {
+        1 => &[(1, 1.00782503, 0.999885), (2, 2.01410178, 0.000115)],
+        2 => &[(3, 3.01602932, 0.00000134), (4, 4.00260325, 0.99999866)],
+        3 => &[(6, 6.01512289, 0.0759), (7, 7.01600344, 0.9241)],
+        4 => &[(9, 9.0121831, 1.0)],
+        5 => &[(10, 10.01293695, 0.199), (11, 11.00930536, 0.801)],
+        6 => &[(12, 12.0, 0.9893), (13, 13.00335484, 0.0107)],
+        7 => &[(14, 14.00307401, 0.99636), (15, 15.00010890, 0.00364)],
+        8 => &[
+            (16, 15.99491462, 0.99757),
+            (17, 16.99913176, 0.00038),
+            (18, 17.99915961, 0.00205),
+        ],
+        9 => &[(19, 18.99840316, 1.0)],
+        10 => &[
+            (20, 19.99244018, 0.9048),
+            (21, 20.99384668, 0.0027),
+            (22, 21.99138511, 0.0925),
+        ],
+        11 => &[(23, 22.98976928, 1.0)],
+        12 => &[
+            (24, 23.98504170, 0.7899),
+            (25, 24.98583698, 0.1),
+            (26, 25.98259297, 0.1101),
+        ],
+        13 => &[(27, 26.98153853, 1.0)],
+        14 => &[
+            (28, 27.97692653, 0.92223),
+            (29, 28.97649466, 0.04685),
+            (30, 29.97377014, 0.03092),
+        ],
+        15 => &[(31, 30.97376200, 1.0)],
+        16 => &[
+            (32, 31.97207117, 0.9499),
+            (33, 32.97145891, 0.0075),
+            (34, 33.96786700, 0.0425),
+            (36, 35.96708071, 0.0001),
+        ],
+        17 => &[(35, 34.96885268, 0.7576), (37, 36.96590260, 0.2424)],
+        18 => &[
+            (36, 35.96754511, 0.003336),
+            (38, 37.96273211, 0.000629),
+            (40, 39.96238312, 0.996035),
+        ],
+        19 => &[
+            (39, 38.96370649, 0.932581),
+            (40, 39.96399817, 0.000117),
+            (41, 40.96182526, 0.067302),
+        ],
+        20 => &[
+            (40, 39.96259086, 0.96941),
+            (42, 41.95861783, 0.00647),
+            (43, 42.95876644, 0.00135),
+            (44, 43.95548156, 0.02086),
+            (46, 45.95368900, 0.00004),
+            (48, 47.95252276, 0.00187),
+        ],
+        21 => &[(45, 44.95590828, 1.0)],
+        22 => &[
+            (46, 45.95262772, 0.0825),
+            (47, 46.95175879, 0.0744),
+            (48, 47.94794198, 0.7372),
+            (49, 48.94786568, 0.0541),
+            (50, 49.94478689, 0.0518),
+        ],
+        23 => &[(50, 49.94715601, 0.0025), (51, 50.94395704, 0.9975)],
+        24 => &[
+            (50, 49.94604183, 0.04345),
+            (52, 51.94050623, 0.83789),
+            (53, 52.94064815, 0.09501),
+            (54, 53.93887916, 0.02365),
+        ],
+        25 => &[(55, 54.93804391, 1.0)],
+        26 => &[
+            (54, 53.93960899, 0.05845),
+            (56, 55.93493633, 0.91754),
+            (57, 56.93539284, 0.02119),
+            (58, 57.93327443, 0.00282),
+        ],
+        27 => &[(59, 58.93319429, 1.0)],
+        28 => &[
+            (58, 57.93534241, 0.68077),
+            (60, 59.93078588, 0.26223),
+            (61, 60.93105557, 0.011399),
+            (62, 61.92834537, 0.036346),
+            (64, 63.92796682, 0.009255),
+        ],
+        29 => &[(63, 62.92959772, 0.6915), (65, 64.92778970, 0.3085)],
+        30 => &[
+            (64, 63.92914201, 0.4917),
+            (66, 65.92603381, 0.2773),
+            (67, 66.92712775, 0.0404),
+            (68, 67.92484455, 0.1845),
+            (70, 69.9253192, 0.0061),
+        ],
+        31 => &[(69, 68.9255735, 0.60108), (71, 70.92470258, 0.39892)],
+        32 => &[
+            (70, 69.92424875, 0.2057),
+            (72, 71.922075826, 0.2745),
+            (73, 72.923458956, 0.0775),
+            (74, 73.921177761, 0.365),
+            (76, 75.921402726, 0.0773),
+        ],
+        33 => &[(75, 74.92159457, 1.0)],
+        34 => &[
+            (74, 73.922475934, 0.0089),
+            (76, 75.919213704, 0.0937),
+            (77, 76.919914154, 0.0763),
+            (78, 77.91730928, 0.2377),
+            (80, 79.9165218, 0.4961),
+            (82, 81.9166995, 0.0873),
+        ],
+        35 => &[(79, 78.9183376, 0.5069), (81, 80.9162897, 0.4931)],
+        36 => &[
+            (78, 77.92036494, 0.00355),
+            (80, 79.91637808, 0.02286),
+            (82, 81.91348273, 0.11593),
+            (83, 82.91412716, 0.115),
+            (84, 83.9114977282, 0.56987),
+            (86, 85.9106106269, 0.17279),
+        ],
+        37 => &[(85, 84.9117897379, 0.7217), (87, 86.909180531, 0.2783)],
+        38 => &[
+            (84, 83.9134191, 0.0056),
+            (86, 85.9092606, 0.0986),
+            (87, 86.9088775, 0.07),
+            (88, 87.9056125, 0.8258),
+        ],
+        39 => &[(89, 88.9058403, 1.0)],
+        41 => &[(93, 92.906373, 1.0)],
+        45 => &[(103, 102.905498, 1.0)],
+        46 => &[
+            (102, 101.9056022, 0.0102),
+            (104, 103.9040305, 0.1114),
+            (105, 104.9050796, 0.2233),
+            (106, 105.9034804, 0.2733),
+            (108, 107.9038916, 0.2646),
+            (110, 109.9051722, 0.1172),
+        ],
+        47 => &[(107, 106.9050916, 0.51839), (109, 108.9047553, 0.48161)],
+        50 => &[
+            (112, 111.90482387, 0.0097),
+            (114, 113.9027827, 0.0066),
+            (115, 114.903344699, 0.0034),
+            (116, 115.9017428, 0.1454),
+            (117, 116.90295398, 0.0768),
+            (118, 117.90160657, 0.2422),
+            (119, 118.90331117, 0.0859),
+            (120, 119.90220163, 0.3258),
+            (122, 121.9034438, 0.0463),
+            (124, 123.9052766, 0.0579),
+        ],
+        53 => &[(127, 126.9044719, 1.0)],
+        55 => &[(133, 132.905451961, 1.0)],
+        78 => &[
+            (190, 189.9599297, 0.00012),
+            (192, 191.9610387, 0.00782),
+            (194, 193.9626809, 0.3286),
+            (195, 194.9647917, 0.3378),
+            (196, 195.96495209, 0.2521),
+            (198, 197.9678949, 0.07356),
+        ],
+        79 => &[(197, 196.96656879, 1.0)],
+        80 => &[
+            (196, 195.9658326, 0.0015),
+            (198, 197.9667686, 0.0997),
+            (199, 198.96828064, 0.1687),
+            (200, 199.96832659, 0.231),
+            (201, 200.97030284, 0.1318),
+            (202, 201.9706434, 0.2986),
+            (204, 203.97349398, 0.0687),
+        ],
+        _ => return None,
+    };
+    Some(isotopes)
+}
+
+/// Stable isotopes in order of increasing mass, None for elements without tabulated ones.
+pub fn get_isotopes(atomic_number: i32) -> Option<Vec<Isotope>> {
+    isotope_table(atomic_number).map(|isotopes| {
+        isotopes
+            .iter()
+            .map(|&(mass_number, mass, abundance)| Isotope {
+                mass_number,
+                mass,
+                abundance,
+            })
+            .collect()
+    })
+}
+
+// Heaviest mass number accepted for isotope labels
+const MAX_MASS_NUMBER: u32 = 300;
+
+// Mass number and atomic mass of radioactive isotopes common as labels and tracers (AME2016)
+const RADIOACTIVE_ISOTOPES: &[(i32, u32, f64)] = &[
+    (1, 3, 3.01604928),
+    (6, 11, 11.0114336),
+    (6, 14, 14.0032420),
+    (7, 13, 13.0057386),
+    (8, 15, 15.0030656),
+    (9, 18, 18.0009380),
+    (15, 32, 31.9739076),
+    (16, 35, 34.9690323),
+    (53, 125, 124.9046302),
+    (53, 131, 130.9061246),
+];
+
+/// Atomic mass in daltons of the isotope with the mass number, e.g. of a labelled atom. Isotopes without
+/// tabulated masses get their mass number, the same approximation as the standard weights of elements without
+/// stable isotopes. None for dummy atoms and mass numbers below the atomic number.
+pub fn get_isotope_mass(atomic_number: i32, mass_number: u32) -> Option<f64> {
+    if !(1..=118).contains(&atomic_number) || !(atomic_number as u32..=MAX_MASS_NUMBER).contains(&mass_number) {
+        return None;
+    }
+    let stable = isotope_table(atomic_number)
+        .and_then(|isotopes| isotopes.iter().find(|isotope| isotope.0 == mass_number))
+        .map(|isotope| isotope.1);
+    let radioactive = || {
+        RADIOACTIVE_ISOTOPES
+            .iter()
+            .find(|isotope| isotope.0 == atomic_number && isotope.1 == mass_number)
+            .map(|isotope| isotope.2)
+    };
+    Some(stable.or_else(radioactive).unwrap_or(mass_number as f64))
+}
+
+/// Label of the isotope: "D" and "T" for hydrogens, the mass number before the symbol for the others, e.g. "13C".
+pub fn isotope_label(atomic_number: i32, mass_number: u32) -> Option<String> {
+    let element = get_element_by_number(atomic_number)?;
+    match (atomic_number, mass_number) {
+        (1, 2) => Some("D".to_string()),
+        (1, 3) => Some("T".to_string()),
+        _ => Some(format!("{}{}", mass_number, element.symbol)),
+    }
+}
+
+/// Atomic number and mass number of an isotope label such as "D", "T" or "13C", see `isotope_label`.
+pub fn parse_isotope_label(label: &str) -> Option<(i32, u32)> {
+    match label {
+        "D" => return Some((1, 2)),
+        "T" => return Some((1, 3)),
+        _ => {}
+    }
+    let split = label.find(|c: char| !c.is_ascii_digit())?;
+    let mass_number: u32 = label[..split].parse().ok()?;
+    let element = get_element_by_symbol(&label[split..])?;
+    get_isotope_mass(element.atomic_number, mass_number)?;
+    Some((element.atomic_number, mass_number))
+}
@@ -0,0 +1,272 @@
+// Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
+// Licensed under the MIT License
+
+use std::f64::consts::PI;
+
+use serde::{Deserialize, Serialize};
+
+use crate::cell::dot;
+use crate::diffraction::grid;
+use crate::scattering::xray_form_factor;
+use crate::types::AtomicCoordinates;
+
+// Structure factors are summed over all atoms for every reflection
+const MAX_REFLECTIONS: usize = 500_000;
+// Reflections at the same angle are merged into one peak of the list
+const MERGE_TOLERANCE: f64 = 1e-6;
+// Weaker peaks relative to the strongest one are not listed, e.g. systematic absences
+const MIN_RELATIVE_INTENSITY: f64 = 1e-4;
+
+/// Radiation, angle range and peak width of a simulated powder X-ray diffraction pattern.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct PowderSettings {
+    /// Wavelength in Angstroms, Cu Kα1 by default.
+    pub wavelength: f64,
+    /// Range and step of the diffraction angle 2θ in degrees.
+    pub two_theta_min: f64,
+    pub two_theta_max: f64,
+    pub two_theta_step: f64,
+    /// Full width at half maximum of the Lorentzian peaks in degrees 2θ.
+    pub fwhm: f64,
+    /// Isotropic displacement parameter B of the Debye-Waller factor exp(-B sin²θ/λ²) in Å², the same for all atoms.
+    pub b_factor: f64,
+}
+
+impl Default for PowderSettings {
+    fn default() -> Self {
+        Self {
+            wavelength: 1.5406,
+            two_theta_min: 5.0,
+            two_theta_max: 90.0,
+            two_theta_step: 0.02,
+            fwhm: 0.1,
+            b_factor: 1.0,
+        }
+    }
+}
+
+/// Peak of the pattern, symmetry equivalent reflections and accidental overlaps are merged.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct Reflection {
+    /// Miller indices of one of the merged reflections, the largest ones in lexicographic order.
+    pub hkl: [i32; 3],
+    pub multiplicity: u32,
+    /// Interplanar spacing in Angstroms.
+    pub d: f64,
+    pub two_theta: f64,
+    /// Integrated intensity relative to the strongest peak, which is 100.
+    pub intensity: f64,
+}
+
+/// Profile scaled so that its maximum is 100 and the peaks in order of increasing angle.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PowderPattern {
+    pub two_theta: Vec<f64>,
+    pub intensity: Vec<f64>,
+    pub reflections: Vec<Reflection>,
+}
+
+/// Simulates the powder pattern of the periodic structure from structure factors of all reflections within
+/// the resolution limit of `two_theta_max`, corrected by the Lorentz-polarization factor of an unpolarized beam.
+/// Atoms scatter with the tabulated X-ray form factors damped by an isotropic Debye-Waller factor, dummy atoms
+/// (atomic numbers below 1) do not scatter.
+pub fn simulate(coordinates: &AtomicCoordinates, settings: &PowderSettings) -> Result<PowderPattern, String> {
+    let cell = coordinates
+        .cell
+        .ok_or_else(|| "A periodic cell is needed to simulate a powder pattern".to_string())?;
+    if !(settings.wavelength.is_finite() && settings.wavelength > 0.0) {
+        return Err(format!("Invalid wavelength: {}", settings.wavelength));
+    }
+    if !(settings.fwhm.is_finite() && settings.fwhm > 0.0) {
+        return Err(format!("Invalid peak width: {}", settings.fwhm));
+    }
+    if !(settings.b_factor.is_finite() && settings.b_factor >= 0.0) {
+        return Err(format!("Invalid displacement parameter: {}", settings.b_factor));
+    }
+    if settings.two_theta_max >= 180.0 {
+        return Err(format!("2θ must be below 180°, got {}", settings.two_theta_max));
+    }
+    let two_theta = grid(
+        settings.two_theta_min,
+        settings.two_theta_max,
+        settings.two_theta_step,
+        "2θ",
+    )?;
+
+    let atoms: Vec<(i32, [f64; 3])> = (0..coordinates.atomic_num.len())
+        .filter(|&i| coordinates.atomic_num[i] > 0)
+        .map(|i| {
+            let point = [coordinates.x[i], coordinates.y[i], coordinates.z[i]];
+            (coordinates.atomic_num[i], cell.to_fractional(point))
+        })
+        .collect();
+    if atoms.is_empty() {
+        return Err("The structure has no atoms".to_string());
+    }
+
+    // |h| = |G·a| <= |a| / d_min for reciprocal vectors G = h a* + k b* + l c* with |G| = 1 / d
+    let max_inverse_d = 2.0 * (settings.two_theta_max / 2.0).to_radians().sin() / settings.wavelength;
+    let reciprocal = cell.reciprocal_vectors();
+    let limits = cell.vectors.map(|v| (dot(v, v).sqrt() * max_inverse_d).floor() as i32);
+    let count = limits.iter().map(|&n| 2 * n as usize + 1).product::<usize>();
+    if count > MAX_REFLECTIONS {
+        return Err(format!(
+            "{} reflections are within the resolution limit, at most {} are supported",
+            count, MAX_REFLECTIONS
+        ));
+    }
+
+    // (hkl, d, integrated intensity) of every reflection
+    let mut reflections: Vec<([i32; 3], f64, f64)> = Vec::new();
+    for h in -limits[0]..=limits[0] {
+        for k in -limits[1]..=limits[1] {
+            for l in -limits[2]..=limits[2] {
+                let g: [f64; 3] = std::array::from_fn(|i| {
+                    h as f64 * reciprocal[0][i] + k as f64 * reciprocal[1][i] + l as f64 * reciprocal[2][i]
+                });
+                let inverse_d = dot(g, g).sqrt();
+                if inverse_d == 0.0 || inverse_d > max_inverse_d {
+                    continue;
+                }
+                let theta = (settings.wavelength * inverse_d / 2.0).asin();
+                let sin_theta_over_lambda = inverse_d / 2.0;
+                let debye_waller = (-settings.b_factor * sin_theta_over_lambda * sin_theta_over_lambda).exp();
+                let (mut real, mut imaginary) = (0.0, 0.0);
+                for &(z, [x, y, w]) in &atoms {
+                    let phase = 2.0 * PI * (h as f64 * x + k as f64 * y + l as f64 * w);
+                    let f = xray_form_factor(z, sin_theta_over_lambda) * debye_waller;
+                    real += f * phase.cos();
+                    imaginary += f * phase.sin();
+                }
+                let cos_two_theta = (2.0 * theta).cos();
+                let lorentz_polarization = (1.0 + cos_two_theta * cos_two_theta) / (theta.sin().powi(2) * theta.cos());
+                let intensity = (real * real + imaginary * imaginary) * lorentz_polarization;
+                reflections.push(([h, k, l], 1.0 / inverse_d, intensity));
+            }
+        }
+    }
+
+    // Merge reflections with the same spacing
+    reflections.sort_by(|a, b| b.1.total_cmp(&a.1));
+    let mut peaks: Vec<Reflection> = Vec::new();
+    for (hkl, d, intensity) in reflections {
+        match peaks.last_mut() {
+            Some(peak) if (peak.d - d).abs() < MERGE_TOLERANCE * d => {
+                peak.hkl = peak.hkl.max(hkl);
+                peak.multiplicity += 1;
+                peak.intensity += intensity;
+            }
+            _ => peaks.push(Reflection {
+                hkl,
+                multiplicity: 1,
+                d,
+                two_theta: 2.0 * (settings.wavelength / (2.0 * d)).asin().to_degrees(),
+                intensity,
+            }),
+        }
+    }
+
+    let half_width = settings.fwhm / 2.0;
+    let mut intensity: Vec<f64> = two_theta
+        .iter()
+        .map(|&angle| {
+            peaks
+                .iter()
+                .map(|peak| {
+                    peak.intensity * half_width * half_width
+                        / ((angle - peak.two_theta).powi(2) + half_width * half_width)
+                })
+                .sum()
+        })
+        .collect();
+    let max_intensity = intensity.iter().copied().fold(0.0, f64::max);
+    if max_intensity > 0.0 {
+        intensity.iter_mut().for_each(|value| *value *= 100.0 / max_intensity);
+    }
+
+    let strongest = peaks.iter().map(|peak| peak.intensity).fold(0.0, f64::max);
+    peaks
+        .retain(|peak| peak.intensity > MIN_RELATIVE_INTENSITY * strongest && peak.two_theta >= settings.two_theta_min);
+    for peak in &mut peaks {
+        peak.intensity *= 100.0 / strongest;
+    }
+
+    Ok(PowderPattern {
+        two_theta,
+        intensity,
+        reflections: peaks,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cell::UnitCell;
+
+    /// Rock salt: the (200) peak is the strongest, reflections with mixed parity of hkl are absent.
+    #[test]
+    fn powder_pattern() {
+        let a = 5.64;
+        let fcc = [[0.0, 0.0, 0.0], [0.5, 0.5, 0.0], [0.5, 0.0, 0.5], [0.0, 0.5, 0.5]];
+        let shifted = fcc.map(|[x, y, z]| [x + 0.5, y, z]);
+        let positions: Vec<[f64; 3]> = fcc.iter().chain(&shifted).map(|p| p.map(|x| x * a)).collect();
+        let data = AtomicCoordinates {
+            atomic_num: [vec![11; 4], vec![17; 4]].concat(),
+            x: positions.iter().map(|p| p[0]).collect(),
+            y: positions.iter().map(|p| p[1]).collect(),
+            z: positions.iter().map(|p| p[2]).collect(),
+            connectivity: None,
+            charges: None,
+            chains: None,
+            cell: Some(UnitCell::from_parameters([a, a, a, 90.0, 90.0, 90.0]).unwrap()),
+            isotopes: None,
+        };
+        let pattern = simulate(&data, &PowderSettings::default()).unwrap();
+
+        let strongest = pattern
+            .reflections
+            .iter()
+            .max_by(|a, b| a.intensity.total_cmp(&b.intensity))
+            .unwrap();
+        assert_eq!((strongest.hkl, strongest.multiplicity), ([2, 0, 0], 6));
+        assert!((strongest.two_theta - 31.70).abs() < 0.01, "{}", strongest.two_theta);
+        let (index, _) = pattern
+            .intensity
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .unwrap();
+        assert!((pattern.two_theta[index] - strongest.two_theta).abs() < 0.02);
+        assert!(pattern.reflections.iter().any(|peak| peak.hkl == [1, 1, 1]));
+        assert!(
+            pattern
+                .reflections
+                .iter()
+                .all(|peak| peak.hkl.iter().all(|i| i % 2 == 0) || peak.hkl.iter().all(|i| i % 2 != 0))
+        );
+
+        // Thermal motion weakens the peaks at high angles
+        let high_angle = |pattern: &PowderPattern| {
+            pattern
+                .reflections
+                .iter()
+                .find(|peak| peak.hkl == [4, 2, 2])
+                .unwrap()
+                .intensity
+        };
+        let settings = PowderSettings {
+            b_factor: 5.0,
+            ..Default::default()
+        };
+        assert!(high_angle(&simulate(&data, &settings).unwrap()) < high_angle(&pattern));
+        let settings = PowderSettings {
+            b_factor: -1.0,
+            ..Default::default()
+        };
+        assert!(simulate(&data, &settings).is_err());
+
+        let molecule = AtomicCoordinates { cell: None, ..data };
+        assert!(simulate(&molecule, &PowderSettings::default()).is_err());
+    }
+}
@@ -0,0 +1,173 @@
+// Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
+// Licensed under the MIT License
+
+//! Timing spans of the slow stages (parsing, bond search, geometry building, render passes) for reports
+//! like "this file is slow". Spans are recorded only with the `profiling` feature, otherwise they compile
+//! to nothing. Spans are recorded per thread, only the ones of the calling thread are returned.
+
+use serde::Serialize;
+
+/// Finished span.
+#[derive(Serialize, Clone, Debug)]
+pub struct SpanRecord {
+    pub name: &'static str,
+    /// Start in milliseconds of the clock, see `set_clock`.
+    pub start: f64,
+    /// Duration in milliseconds.
+    pub duration: f64,
+    /// Number of enclosing spans, 0 for top-level ones.
+    pub depth: u32,
+}
+
+/// Receives the spans of every finished top-level span, nested ones first.
+pub type Sink = Box<dyn Fn(Vec<SpanRecord>)>;
+
+/// Whether spans are recorded, i.e. the crate is built with the `profiling` feature.
+pub const fn enabled() -> bool {
+    cfg!(feature = "profiling")
+}
+
+/// Replaces the clock of the thread (milliseconds from any origin), e.g. with `performance.now()` of a browser.
+pub fn set_clock(clock: fn() -> f64) {
+    imp::set_clock(clock);
+}
+
+/// Passes the spans of the thread to `sink` as soon as a top-level span ends instead of keeping them
+/// for `take_records`. None keeps them again.
+pub fn set_sink(sink: Option<Sink>) {
+    imp::set_sink(sink);
+}
+
+/// Spans of the thread finished since the last call, nested ones before the enclosing ones.
+pub fn take_records() -> Vec<SpanRecord> {
+    imp::take_records()
+}
+
+/// Starts a span that ends when the returned guard is dropped: `let _span = profiling::span("parse");`.
+pub fn span(name: &'static str) -> Span {
+    Span {
+        _guard: imp::Guard::start(name),
+    }
+}
+
+#[must_use = "the span ends when the guard is dropped"]
+pub struct Span {
+    _guard: imp::Guard,
+}
+
+#[cfg(feature = "profiling")]
+mod imp {
+    use std::cell::RefCell;
+
+    use super::{Sink, SpanRecord};
+
+    // Recording stops beyond this many spans until they are taken, so a host that never takes them
+    // does not run out of memory
+    const MAX_RECORDS: usize = 10_000;
+
+    struct State {
+        clock: fn() -> f64,
+        depth: u32,
+        records: Vec<SpanRecord>,
+        sink: Option<Sink>,
+    }
+
+    thread_local! {
+        static STATE: RefCell<State> = RefCell::new(State {
+            clock: default_clock,
+            depth: 0,
+            records: Vec::new(),
+            sink: None,
+        });
+    }
+
+    // There is no clock on wasm32-unknown-unknown without the host, see `set_clock`
+    #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+    fn default_clock() -> f64 {
+        0.0
+    }
+
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    fn default_clock() -> f64 {
+        use std::sync::OnceLock;
+        use std::time::Instant;
+
+        static ORIGIN: OnceLock<Instant> = OnceLock::new();
+        ORIGIN.get_or_init(Instant::now).elapsed().as_secs_f64() * 1000.0
+    }
+
+    pub fn set_clock(clock: fn() -> f64) {
+        STATE.with_borrow_mut(|state| state.clock = clock);
+    }
+
+    pub fn set_sink(sink: Option<Sink>) {
+        STATE.with_borrow_mut(|state| state.sink = sink);
+    }
+
+    pub fn take_records() -> Vec<SpanRecord> {
+        STATE.with_borrow_mut(|state| std::mem::take(&mut state.records))
+    }
+
+    pub struct Guard {
+        name: &'static str,
+        start: f64,
+    }
+
+    impl Guard {
+        pub fn start(name: &'static str) -> Self {
+            let start = STATE.with_borrow_mut(|state| {
+                state.depth += 1;
+                (state.clock)()
+            });
+            Self { name, start }
+        }
+    }
+
+    impl Drop for Guard {
+        fn drop(&mut self) {
+            let finished = STATE.with_borrow_mut(|state| {
+                state.depth = state.depth.saturating_sub(1);
+                if state.records.len() < MAX_RECORDS {
+                    state.records.push(SpanRecord {
+                        name: self.name,
+                        start: self.start,
+                        duration: (state.clock)() - self.start,
+                        depth: state.depth,
+                    });
+                }
+                match state.depth {
+                    0 => state.sink.take().map(|sink| (sink, std::mem::take(&mut state.records))),
+                    _ => None,
+                }
+            });
+            // The sink is called outside of the state, so it may start spans itself
+            if let Some((sink, records)) = finished {
+                sink(records);
+                STATE.with_borrow_mut(|state| {
+                    state.sink.get_or_insert(sink);
+                });
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "profiling"))]
+mod imp {
+    use super::{Sink, SpanRecord};
+
+    pub fn set_clock(_clock: fn() -> f64) {}
+
+    pub fn set_sink(_sink: Option<Sink>) {}
+
+    pub fn take_records() -> Vec<SpanRecord> {
+        Vec::new()
+    }
+
+    pub struct Guard;
+
+    impl Guard {
+        pub fn start(_name: &'static str) -> Self {
+            Self
+        }
+    }
+}
@@ -0,0 +1,150 @@
+// Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
+// Licensed under the MIT License
+
+use serde::{Deserialize, Serialize};
+
+use crate::inertia::{center_of_mass, inertia_tensor, principal_moments, rotational_constants};
+use crate::mass_spectrum::formula;
+use crate::periodic_table::{get_atomic_mass, get_isotope_mass};
+use crate::types::{AtomicCoordinates, Molecule};
+
+/// Molar mass in g/mol from the standard atomic weights. Dummy atoms (atomic numbers below 1) are massless.
+pub fn molar_mass(atomic_numbers: &[i32]) -> Result<f64, String> {
+    atomic_numbers
+        .iter()
+        .filter(|&&n| n > 0)
+        .map(|&n| get_atomic_mass(n).ok_or_else(|| format!("Unknown atomic number: {}", n)))
+        .sum()
+}
+
+/// Masses of the atoms in daltons: standard atomic weights, the masses of their isotopes for labelled atoms (see
+/// `AtomicCoordinates::isotopes`). Dummy atoms (atomic numbers below 1) are massless.
+pub fn atomic_masses(coordinates: &AtomicCoordinates) -> Result<Vec<f64>, String> {
+    let mass_numbers = coordinates.mass_numbers()?;
+    coordinates
+        .atomic_num
+        .iter()
+        .zip(mass_numbers)
+        .map(|(&n, mass_number)| match (n, mass_number) {
+            (..1, _) => Ok(0.0),
+            (_, 0) => get_atomic_mass(n).ok_or_else(|| format!("Unknown atomic number: {}", n)),
+            _ => get_isotope_mass(n, mass_number).ok_or_else(|| format!("Unknown atomic number: {}", n)),
+        })
+        .collect()
+}
+
+/// Number of electrons of the molecule with the total charge, dummy atoms (atomic numbers below 1) have none.
+pub fn electron_count(atomic_numbers: &[i32], charge: i32) -> i64 {
+    atomic_numbers
+        .iter()
+        .filter(|&&n| n > 0)
+        .map(|&n| n as i64)
+        .sum::<i64>()
+        - charge as i64
+}
+
+/// Lowest spin multiplicity compatible with the number of electrons: singlet for even numbers, doublet for odd
+/// ones.
+pub fn lowest_multiplicity(atomic_numbers: &[i32], charge: i32) -> u32 {
+    if electron_count(atomic_numbers, charge) % 2 == 0 {
+        1
+    } else {
+        2
+    }
+}
+
+/// Checks that the charge leaves electrons to the molecule and that they can have the multiplicity: it is odd
+/// for even numbers of electrons, even for odd ones, and at most one more than the number of electrons.
+pub fn check_multiplicity(atomic_numbers: &[i32], charge: i32, multiplicity: u32) -> Result<(), String> {
+    let num_electrons = electron_count(atomic_numbers, charge);
+    if num_electrons < 0 {
+        return Err(format!("Charge {} is larger than the total nuclear charge", charge));
+    }
+    if multiplicity == 0 {
+        return Err("Multiplicity must be positive".to_string());
+    }
+    if (num_electrons + multiplicity as i64) % 2 == 0 || multiplicity as i64 > num_electrons + 1 {
+        return Err(format!(
+            "Multiplicity {} is impossible with {} electrons",
+            multiplicity, num_electrons
+        ));
+    }
+    Ok(())
+}
+
+/// Composition, charge and multiplicity of a molecule node, e.g. as given by the file.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ElectronicState {
+    /// Molecular formula in the Hill order.
+    pub formula: String,
+    /// Number of atoms without the dummy ones.
+    pub num_atoms: usize,
+    /// g/mol
+    pub molar_mass: f64,
+    pub charge: i32,
+    pub num_electrons: i64,
+    /// Given by the file or the lowest one compatible with the number of electrons
+    pub multiplicity: u32,
+    /// Whether the multiplicity is given by the file
+    pub multiplicity_given: bool,
+}
+
+impl ElectronicState {
+    /// Fails if the multiplicity given by the file is impossible, see `check_multiplicity`.
+    pub fn new(molecule: &Molecule) -> Result<Self, String> {
+        let numbers = &molecule.atomic_num;
+        let multiplicity = match molecule.multiplicity {
+            Some(multiplicity) => {
+                check_multiplicity(numbers, molecule.charge, multiplicity)?;
+                multiplicity
+            }
+            None => lowest_multiplicity(numbers, molecule.charge),
+        };
+        Ok(Self {
+            formula: formula(numbers),
+            num_atoms: numbers.iter().filter(|&&n| n > 0).count(),
+            molar_mass: molar_mass(numbers)?,
+            charge: molecule.charge,
+            num_electrons: electron_count(numbers, molecule.charge),
+            multiplicity,
+            multiplicity_given: molecule.multiplicity.is_some(),
+        })
+    }
+}
+
+/// Composition, mass and rotational properties of a molecule. The mass distribution is that of the main
+/// isotopologue as in `inertia`, the molar mass is averaged over the natural isotopic abundances. Labelled atoms
+/// have the masses of their isotopes in both.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct MolecularProperties {
+    /// Molecular formula in the Hill order.
+    pub formula: String,
+    /// Number of atoms without the dummy ones.
+    pub num_atoms: usize,
+    /// g/mol
+    pub molar_mass: f64,
+    /// Angstroms
+    pub center_of_mass: [f64; 3],
+    /// About the center of mass in u·Å²
+    pub inertia_tensor: [[f64; 3]; 3],
+    /// u·Å² in increasing order
+    pub principal_moments: [f64; 3],
+    /// A ≥ B ≥ C in GHz, `None` for zero moments of inertia
+    pub rotational_constants: [Option<f64>; 3],
+}
+
+impl MolecularProperties {
+    /// Properties of the structure as given, atoms of periodic structures are not brought together across the
+    /// boundaries of the cell.
+    pub fn new(coordinates: &AtomicCoordinates) -> Result<Self, String> {
+        Ok(Self {
+            formula: formula(&coordinates.atomic_num),
+            num_atoms: coordinates.atomic_num.iter().filter(|&&n| n > 0).count(),
+            molar_mass: atomic_masses(coordinates)?.iter().sum(),
+            center_of_mass: center_of_mass(coordinates)?,
+            inertia_tensor: inertia_tensor(coordinates)?,
+            principal_moments: principal_moments(coordinates)?,
+            rotational_constants: rotational_constants(coordinates)?,
+        })
+    }
+}
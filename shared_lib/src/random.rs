@@ -0,0 +1,107 @@
+// Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
+// Licensed under the MIT License
+
+//! Seeded random numbers of the structure generators. The same seed always gives the same sequence on every
+//! platform, so generated structures are reproducible in tests and publications. Changing the algorithm or
+//! the way values are drawn changes the generated structures and must be avoided.
+
+use std::f64::consts::PI;
+
+/// Small deterministic generator (xorshift64*), not suitable for cryptography.
+#[derive(Clone, Debug)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        // The multiplication spreads small seeds over all bits, the state must not be zero
+        Self(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// Integer in `0..n`, `n` must be positive.
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    /// Number in `[0, 1)` with 53 random bits.
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Number in `[min, max)`.
+    pub fn uniform(&mut self, min: f64, max: f64) -> f64 {
+        min + (max - min) * self.next_f64()
+    }
+
+    /// Normally distributed number (Box–Muller transform), draws two numbers.
+    pub fn normal(&mut self, mean: f64, std_dev: f64) -> f64 {
+        // 1 - u is in (0, 1], so the logarithm is finite
+        let u = 1.0 - self.next_f64();
+        let v = self.next_f64();
+        mean + std_dev * (-2.0 * u.ln()).sqrt() * (2.0 * PI * v).cos()
+    }
+
+    /// Direction uniformly distributed on the unit sphere.
+    pub fn unit_vector(&mut self) -> [f64; 3] {
+        let z = self.uniform(-1.0, 1.0);
+        let phi = self.uniform(0.0, 2.0 * PI);
+        let r = (1.0 - z * z).sqrt();
+        [r * phi.cos(), r * phi.sin(), z]
+    }
+
+    /// Rotation matrix uniformly distributed over all orientations (from a random unit quaternion).
+    pub fn rotation(&mut self) -> [[f64; 3]; 3] {
+        let (u1, u2, u3) = (self.next_f64(), self.next_f64(), self.next_f64());
+        let (a, b) = ((1.0 - u1).sqrt(), u1.sqrt());
+        let (w, x, y, z) = (
+            a * (2.0 * PI * u2).sin(),
+            a * (2.0 * PI * u2).cos(),
+            b * (2.0 * PI * u3).sin(),
+            b * (2.0 * PI * u3).cos(),
+        );
+        [
+            [
+                1.0 - 2.0 * (y * y + z * z),
+                2.0 * (x * y - w * z),
+                2.0 * (x * z + w * y),
+            ],
+            [
+                2.0 * (x * y + w * z),
+                1.0 - 2.0 * (x * x + z * z),
+                2.0 * (y * z - w * x),
+            ],
+            [
+                2.0 * (x * z - w * y),
+                2.0 * (y * z + w * x),
+                1.0 - 2.0 * (x * x + y * y),
+            ],
+        ]
+    }
+
+    /// Shuffles the items in place (Fisher–Yates).
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+
+    /// `count` distinct indices from `0..total` in increasing order, all of them if `count >= total`.
+    pub fn sample(&mut self, total: usize, count: usize) -> Vec<usize> {
+        let mut indices: Vec<usize> = (0..total).collect();
+        // Partial Fisher–Yates: the first `count` items are a uniform sample
+        let count = count.min(total);
+        for i in 0..count {
+            let j = i + self.below(total - i);
+            indices.swap(i, j);
+        }
+        indices.truncate(count);
+        indices.sort_unstable();
+        indices
+    }
+}
@@ -0,0 +1,171 @@
+// Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
+// Licensed under the MIT License
+
+use std::f64::consts::PI;
+
+const BOHR2ANGSTROM: f64 = 0.529177210903;
+
+// Cromer-Mann coefficients a1, b1, a2, b2, a3, b3, a4, b4, c of the X-ray form factors of neutral atoms,
+// f(sinθ/λ) = Σ ai exp(-bi (sinθ/λ)²) + c with bi in Å² (International Tables for Crystallography, Vol. C,
+// Table 6.1.1.4)
+#[rustfmt::skip]
+const CROMER_MANN: &[(i32, [f64; 9])] = &[
+    (1, [0.489918, 20.6593, 0.262003, 7.74039, 0.196767, 49.5519, 0.049879, 2.20159, 0.001305]),
+    (2, [0.8734, 9.1037, 0.6309, 3.3568, 0.3112, 22.9276, 0.178, 0.9821, 0.0064]),
+    (3, [1.1282, 3.9546, 0.7508, 1.0524, 0.6175, 85.3905, 0.4653, 168.261, 0.0377]),
+    (4, [1.5919, 43.6427, 1.1278, 1.8623, 0.5391, 103.483, 0.7029, 0.542, 0.0385]),
+    (5, [2.0545, 23.2185, 1.3326, 1.021, 1.0979, 60.3498, 0.7068, 0.1403, -0.1932]),
+    (6, [2.31, 20.8439, 1.02, 10.2075, 1.5886, 0.5687, 0.865, 51.6512, 0.2156]),
+    (7, [12.2126, 0.0057, 3.1322, 9.8933, 2.0125, 28.9975, 1.1663, 0.5826, -11.529]),
+    (8, [3.0485, 13.2771, 2.2868, 5.7011, 1.5463, 0.3239, 0.867, 32.9089, 0.2508]),
+    (9, [3.5392, 10.2825, 2.6412, 4.2944, 1.517, 0.2615, 1.0243, 26.1476, 0.2776]),
+    (10, [3.9553, 8.4042, 3.1125, 3.4262, 1.4546, 0.2306, 1.1251, 21.7184, 0.3515]),
+    (11, [4.7626, 3.285, 3.1736, 8.8422, 1.2674, 0.3136, 1.1128, 129.424, 0.676]),
+    (12, [5.4204, 2.8275, 2.1735, 79.2611, 1.2269, 0.3808, 2.3073, 7.1937, 0.8584]),
+    (13, [6.4202, 3.0387, 1.9002, 0.7426, 1.5936, 31.5472, 1.9646, 85.0886, 1.1151]),
+    (14, [6.2915, 2.4386, 3.0353, 32.3337, 1.9891, 0.6785, 1.541, 81.6937, 1.1407]),
+    (15, [6.4345, 1.9067, 4.1791, 27.157, 1.78, 0.526, 1.4908, 68.1645, 1.1149]),
+    (16, [6.9053, 1.4679, 5.2034, 22.2151, 1.4379, 0.2536, 1.5863, 56.172, 0.8669]),
+    (17, [11.4604, 0.0104, 7.1964, 1.1662, 6.2556, 18.5194, 1.6455, 47.7784, -9.5574]),
+    (18, [7.4845, 0.9072, 6.7723, 14.8407, 0.6539, 43.8983, 1.6442, 33.3929, 1.4445]),
+    (19, [8.2186, 12.7949, 7.4398, 0.7748, 1.0519, 213.187, 0.8659, 41.6841, 1.4228]),
+    (20, [8.6266, 10.4421, 7.3873, 0.6599, 1.5899, 85.7484, 1.0211, 178.437, 1.3751]),
+    (21, [9.189, 9.0213, 7.3679, 0.5729, 1.6409, 136.108, 1.468, 51.3531, 1.3329]),
+    (22, [9.7595, 7.8508, 7.3558, 0.5, 1.6991, 35.6338, 1.9021, 116.105, 1.2807]),
+    (23, [10.2971, 6.8657, 7.3511, 0.4385, 2.0703, 26.8938, 2.0571, 102.478, 1.2199]),
+    (24, [10.6406, 6.1038, 7.3537, 0.392, 3.324, 20.2626, 1.4922, 98.7399, 1.1832]),
+    (25, [11.2819, 5.3409, 7.3573, 0.3432, 3.0193, 17.8674, 2.2441, 83.7543, 1.0896]),
+    (26, [11.7695, 4.7611, 7.3573, 0.3072, 3.5222, 15.3535, 2.3045, 76.8805, 1.0369]),
+    (27, [12.2841, 4.2791, 7.3409, 0.2784, 4.0034, 13.5359, 2.3488, 71.1692, 1.0118]),
+    (28, [12.8376, 3.8785, 7.292, 0.2565, 4.4438, 12.1763, 2.38, 66.3421, 1.0341]),
+    (29, [13.338, 3.5828, 7.1676, 0.247, 5.6158, 11.3966, 1.6735, 64.8126, 1.191]),
+    (30, [14.0743, 3.2655, 7.0318, 0.2333, 5.1652, 10.3163, 2.41, 58.7097, 1.3041]),
+    (31, [15.2354, 3.0669, 6.7006, 0.2412, 4.3591, 10.7805, 2.9623, 61.4135, 1.7189]),
+    (32, [16.0816, 2.8509, 6.3747, 0.2516, 3.7068, 11.4468, 3.683, 54.7625, 2.1313]),
+    (33, [16.6723, 2.6345, 6.0701, 0.2647, 3.4313, 12.9479, 4.2779, 47.7972, 2.531]),
+    (34, [17.0006, 2.4098, 5.8196, 0.2726, 3.9731, 15.2372, 4.3543, 43.8163, 2.8409]),
+    (35, [17.1789, 2.1723, 5.2358, 16.5796, 5.6377, 0.2609, 3.9851, 41.4328, 2.9557]),
+    (36, [17.3555, 1.9384, 6.7286, 16.5623, 5.5493, 0.2261, 3.5375, 39.3972, 2.825]),
+    (37, [17.1784, 1.7888, 9.6435, 17.3151, 5.1399, 0.2748, 1.5292, 164.934, 3.4873]),
+    (38, [17.5663, 1.5564, 9.8184, 14.0988, 5.422, 0.1664, 2.6694, 132.376, 2.5064]),
+    (46, [19.3319, 0.698655, 15.5017, 7.98929, 5.29537, 25.2052, 0.605844, 76.8986, 5.26593]),
+    (47, [19.2808, 0.6446, 16.6885, 7.4726, 4.8045, 24.6605, 1.0463, 99.8156, 5.179]),
+    (50, [19.1889, 5.8303, 19.1005, 0.5031, 4.4585, 26.8909, 2.4663, 83.9571, 4.7821]),
+    (53, [20.1472, 4.347, 18.9949, 0.3814, 7.5138, 27.766, 2.2735, 66.8776, 4.0712]),
+    (55, [20.3892, 3.569, 19.1062, 0.3107, 10.662, 24.3879, 1.4953, 213.904, 3.3352]),
+    (56, [20.3361, 3.216, 19.297, 0.2756, 10.888, 20.2073, 2.6959, 167.202, 2.7731]),
+    (78, [27.0059, 1.51293, 17.7639, 8.81174, 15.7131, 0.424593, 5.7837, 38.6103, 11.6883]),
+    (79, [16.8819, 0.4611, 18.5913, 8.6216, 25.5582, 1.4826, 5.86, 36.3956, 12.0658]),
+    (80, [20.6809, 0.545, 19.0417, 8.4484, 21.6575, 1.5729, 5.9676, 38.3246, 12.6089]),
+    (82, [31.0617, 0.6902, 13.0637, 2.3576, 18.442, 8.618, 5.9696, 47.2579, 13.4118]),
+];
+
+fn cromer_mann(atomic_number: i32) -> Option<&'static [f64; 9]> {
+    CROMER_MANN
+        .iter()
+        .find(|(z, _)| *z == atomic_number)
+        .map(|(_, coefficients)| coefficients)
+}
+
+/// Thomas-Fermi screening radius of the Wentzel (screened Coulomb) atom in Angstroms.
+fn screening_radius(atomic_number: i32) -> f64 {
+    0.885 * BOHR2ANGSTROM * (atomic_number as f64).powf(-1.0 / 3.0)
+}
+
+/// X-ray form factor of a neutral atom at rest in electrons, `x` = sin(θ)/λ in Å⁻¹, from the tabulated
+/// coefficients. Elements missing from the table get the Wentzel atom.
+pub fn xray_form_factor(atomic_number: i32, x: f64) -> f64 {
+    let x2 = x * x;
+    match cromer_mann(atomic_number) {
+        Some(coefficients) => {
+            coefficients[..8]
+                .chunks(2)
+                .map(|ab| ab[0] * (-ab[1] * x2).exp())
+                .sum::<f64>()
+                + coefficients[8]
+        }
+        None => {
+            let radius = screening_radius(atomic_number);
+            atomic_number as f64 / (1.0 + 16.0 * PI * PI * x2 * radius * radius)
+        }
+    }
+}
+
+/// Elastic electron scattering amplitude of a neutral atom in Angstroms in the first Born approximation,
+/// `s` = 4π sin(θ/2)/λ in Å⁻¹. It is the Mott-Bethe transform (2 / a₀) (Z - f(s)) / s² of the tabulated X-ray
+/// form factor f, elements missing from the table get the Wentzel atom.
+pub fn electron_amplitude(atomic_number: i32, s: f64) -> f64 {
+    let prefactor = 2.0 / BOHR2ANGSTROM;
+    let Some(coefficients) = cromer_mann(atomic_number) else {
+        let radius = screening_radius(atomic_number);
+        return prefactor * atomic_number as f64 / (s * s + 1.0 / (radius * radius));
+    };
+    // Z - f is taken as Σ ai (1 - exp(-bi x²)), so the amplitude stays finite at s = 0
+    let x2 = (s / (4.0 * PI)).powi(2);
+    let sum: f64 = coefficients[..8]
+        .chunks(2)
+        .map(|ab| {
+            if x2 > 0.0 {
+                -ab[0] * (-ab[1] * x2).exp_m1() / x2
+            } else {
+                ab[0] * ab[1]
+            }
+        })
+        .sum();
+    prefactor * sum / (16.0 * PI * PI)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Coefficients of every element add up to its number of electrons at zero angle within the accuracy of the fit.
+    #[test]
+    fn cromer_mann_coefficients() {
+        let mut previous = 0;
+        for (z, coefficients) in CROMER_MANN {
+            assert!(*z > previous, "{}", z);
+            previous = *z;
+            let electrons = coefficients[0] + coefficients[2] + coefficients[4] + coefficients[6] + coefficients[8];
+            assert!((electrons - *z as f64).abs() < 2e-3 * *z as f64, "{}: {}", z, electrons);
+        }
+    }
+
+    /// Form factors start from the number of electrons and fall off, the Wentzel atom is of the same magnitude.
+    #[test]
+    fn xray_form_factors() {
+        assert!((xray_form_factor(8, 0.0) - 8.0).abs() < 0.01);
+        assert!((xray_form_factor(42, 0.0) - 42.0).abs() < 1e-9);
+        // Tabulated value of oxygen at sin(θ)/λ = 0.5 Å⁻¹
+        assert!(
+            (xray_form_factor(8, 0.5) - 2.34).abs() < 0.02,
+            "{}",
+            xray_form_factor(8, 0.5)
+        );
+        for x in [0.0, 0.2, 0.5, 1.0] {
+            assert!(xray_form_factor(8, x + 0.1) < xray_form_factor(8, x));
+            let molybdenum = xray_form_factor(42, x);
+            assert!(molybdenum > xray_form_factor(38, x) * 0.5 && molybdenum < xray_form_factor(46, x) * 2.0);
+        }
+    }
+
+    /// Amplitudes of carbon (about 2.5 Å at s = 0) fall off smoothly, heavier atoms scatter more at large angles.
+    /// Near s = 0 the diffuse outer shell of carbon scatters more than oxygen.
+    #[test]
+    fn electron_amplitudes() {
+        let carbon = electron_amplitude(6, 0.0);
+        assert!((carbon - 2.49).abs() < 0.02, "{}", carbon);
+        assert!((electron_amplitude(6, 1e-6) - carbon).abs() < 1e-6);
+        for s in [0.0, 2.0, 10.0, 30.0] {
+            assert!(electron_amplitude(6, s) < electron_amplitude(53, s));
+            assert!(electron_amplitude(6, s + 1.0) < electron_amplitude(6, s));
+        }
+        for s in [10.0, 30.0] {
+            assert!(electron_amplitude(6, s) < electron_amplitude(8, s));
+            assert!(electron_amplitude(8, s) < electron_amplitude(53, s));
+        }
+        // The Wentzel atom of an element missing from the table is of the same magnitude
+        let molybdenum = electron_amplitude(42, 10.0);
+        assert!(molybdenum > electron_amplitude(38, 10.0) * 0.5 && molybdenum < electron_amplitude(46, 10.0) * 2.0);
+    }
+}
@@ -0,0 +1,688 @@
+// Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
+// Licensed under the MIT License
+
+//! Molecules from SMILES strings. The parser reads the OpenSMILES syntax: atoms of the organic subset with
+//! implicit hydrogens, bracket atoms with isotopes, hydrogen counts and charges, bonds, branches, ring closures
+//! and disconnected parts. Stereochemistry (`@`, `/` and `\`) is read but not reproduced, the wildcard atom `*`
+//! becomes a dummy atom.
+//!
+//! Coordinates are embedded from the connectivity: distances of bonded atoms and of atoms bonded to the same
+//! atom follow from covalent radii and hybridizations, all other atoms are kept from clashing. The distances are
+//! first satisfied from random positions in four dimensions, where atoms can pass each other, the fourth
+//! dimension is then squeezed out and the bonds about double and aromatic bonds flattened. The geometry is an
+//! approximate starting point for an optimization, not an optimized structure.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::contacts::DEFAULT_CLASH_FRACTION;
+use crate::geometry::cross;
+use crate::periodic_table::{DUMMY_ATOM, get_element_by_number, get_element_by_symbol, get_vdw_radius};
+use crate::random::Rng;
+use crate::types::{AtomicCoordinates, Connection};
+
+// Seed of the random starting positions, so the same string always gives the same geometry
+const SEED: u64 = 2026;
+// Embeddings from different starting positions of which the one satisfying the distances best is kept
+const ATTEMPTS: usize = 3;
+// Steps of every stage of the embedding
+const STEPS: usize = 1500;
+// Bond orders with their lengths as fractions of the sum of covalent radii, as in the force field
+const BOND_LENGTHS: [(u8, f64); 4] = [(1, 1.0), (Connection::AROMATIC, 0.915), (2, 0.87), (3, 0.79)];
+// Covalent radius of carbon used for dummy atoms
+const DUMMY_RADIUS: f64 = 0.75;
+// Van der Waals radius of atoms that have none tabulated
+const DEFAULT_VDW_RADIUS: f64 = 2.0;
+// Weight of the flatness of double and aromatic bonds relative to the distances
+const PLANARITY_WEIGHT: f64 = 0.1;
+// Weight of the fourth coordinate while it is squeezed out
+const SQUEEZE_WEIGHT: f64 = 1.0;
+// Largest displacement of an atom in one step in Å
+const MAX_STEP: f64 = 0.3;
+
+/// Atom of a SMILES string.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SmilesAtom {
+    /// Atomic number, `DUMMY_ATOM` for the wildcard `*`.
+    pub atomic_number: i32,
+    /// Mass number of a labelled atom, 0 for the natural isotopic composition.
+    pub mass_number: u32,
+    /// Formal charge in elementary charges.
+    pub charge: i32,
+    /// Written in lowercase as a member of an aromatic ring.
+    pub aromatic: bool,
+    /// Number of hydrogens, given in brackets or implicit from the default valences of the organic subset.
+    pub hydrogens: u32,
+}
+
+/// Bond between two atoms given by zero-based indices, with the order as in `Connection`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SmilesBond {
+    pub atoms: [usize; 2],
+    pub order: u8,
+}
+
+/// Atoms and bonds of a SMILES string without the hydrogens, which are counted by the atoms.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SmilesGraph {
+    pub atoms: Vec<SmilesAtom>,
+    pub bonds: Vec<SmilesBond>,
+}
+
+impl SmilesGraph {
+    fn add_bond(&mut self, a: usize, b: usize, order: Option<u8>, position: usize) -> Result<(), String> {
+        if a == b {
+            return Err(format!("Atom bonded to itself at position {} of SMILES", position + 1));
+        }
+        if self
+            .bonds
+            .iter()
+            .any(|bond| bond.atoms == [a, b] || bond.atoms == [b, a])
+        {
+            return Err(format!("Atoms bonded twice at position {} of SMILES", position + 1));
+        }
+        let aromatic = self.atoms[a].aromatic && self.atoms[b].aromatic;
+        let order = order.unwrap_or(if aromatic { Connection::AROMATIC } else { 1 });
+        self.bonds.push(SmilesBond { atoms: [a, b], order });
+        Ok(())
+    }
+
+    // Sum of the orders of the bonds of the atom, aromatic bonds count as single
+    fn valence(&self, atom: usize) -> u32 {
+        self.bonds
+            .iter()
+            .filter(|bond| bond.atoms.contains(&atom))
+            .map(|bond| {
+                if bond.order == Connection::AROMATIC {
+                    1
+                } else {
+                    bond.order as u32
+                }
+            })
+            .sum()
+    }
+}
+
+/// Parses a SMILES string into atoms and bonds.
+pub fn parse(smiles: &str) -> Result<SmilesGraph, String> {
+    let chars: Vec<char> = smiles.trim().chars().collect();
+    if chars.is_empty() {
+        return Err("Empty SMILES string".to_string());
+    }
+
+    let mut graph = SmilesGraph::default();
+    // Whether the hydrogens of the atom follow from the default valences
+    let mut implicit = Vec::new();
+    let mut previous: Option<usize> = None;
+    let mut branches = Vec::new();
+    // Open ring closures by their numbers with the atom and the bond order given at the opening
+    let mut rings: HashMap<u32, (usize, Option<u8>)> = HashMap::new();
+    let mut bond: Option<u8> = None;
+    let mut position = 0;
+    while position < chars.len() {
+        let c = chars[position];
+        match c {
+            '-' | '=' | '#' | '$' | ':' | '/' | '\\' => {
+                if previous.is_none() || bond.is_some() {
+                    return Err(format!(
+                        "Unexpected bond '{}' at position {} of SMILES",
+                        c,
+                        position + 1
+                    ));
+                }
+                bond = Some(bond_order(c, position)?);
+                position += 1;
+            }
+            '(' => {
+                let atom =
+                    previous.ok_or_else(|| format!("Branch without an atom at position {} of SMILES", position + 1))?;
+                if bond.is_some() {
+                    return Err(format!("Unexpected branch at position {} of SMILES", position + 1));
+                }
+                branches.push(atom);
+                position += 1;
+            }
+            ')' => {
+                if bond.is_some() {
+                    return Err(format!("Bond without an atom at position {} of SMILES", position + 1));
+                }
+                previous = Some(
+                    branches
+                        .pop()
+                        .ok_or_else(|| format!("Unopened branch at position {} of SMILES", position + 1))?,
+                );
+                position += 1;
+            }
+            '.' => {
+                if bond.is_some() {
+                    return Err(format!("Bond without an atom at position {} of SMILES", position + 1));
+                }
+                previous = None;
+                position += 1;
+            }
+            '0'..='9' | '%' => {
+                let atom = previous
+                    .ok_or_else(|| format!("Ring closure without an atom at position {} of SMILES", position + 1))?;
+                let (number, next) = ring_number(&chars, position)?;
+                match rings.remove(&number) {
+                    Some((other, opening)) => {
+                        let order = match (opening, bond) {
+                            (Some(a), Some(b)) if a != b => {
+                                return Err(format!("Ring closure {} with different bond orders", number));
+                            }
+                            (opening, closing) => opening.or(closing),
+                        };
+                        graph.add_bond(other, atom, order, position)?;
+                    }
+                    None => {
+                        rings.insert(number, (atom, bond));
+                    }
+                }
+                bond = None;
+                position = next;
+            }
+            _ => {
+                let (atom, bracket, next) = parse_atom(&chars, position)?;
+                graph.atoms.push(atom);
+                implicit.push(!bracket);
+                let index = graph.atoms.len() - 1;
+                if let Some(previous) = previous {
+                    graph.add_bond(previous, index, bond.take(), position)?;
+                }
+                previous = Some(index);
+                position = next;
+            }
+        }
+    }
+    if bond.is_some() {
+        return Err("SMILES ends with a bond".to_string());
+    }
+    if !branches.is_empty() {
+        return Err("Unclosed branch in SMILES".to_string());
+    }
+    if let Some(number) = rings.keys().min() {
+        return Err(format!("Unclosed ring {} in SMILES", number));
+    }
+
+    for (index, implicit) in implicit.into_iter().enumerate() {
+        if implicit {
+            let valence = graph.valence(index);
+            graph.atoms[index].hydrogens = implicit_hydrogens(&graph.atoms[index], valence);
+        }
+    }
+    Ok(graph)
+}
+
+/// Builds the molecule of a SMILES string with explicit hydrogens, bonds, formal charges and isotopes at an
+/// embedded geometry centered at the origin.
+pub fn build(smiles: &str) -> Result<AtomicCoordinates, String> {
+    let graph = parse(smiles)?;
+
+    let mut numbers: Vec<i32> = graph.atoms.iter().map(|atom| atom.atomic_number).collect();
+    let mut charges: Vec<f64> = graph.atoms.iter().map(|atom| atom.charge as f64).collect();
+    let mut isotopes: Vec<u32> = graph.atoms.iter().map(|atom| atom.mass_number).collect();
+    let mut aromatic: Vec<bool> = graph.atoms.iter().map(|atom| atom.aromatic).collect();
+    let mut connections: Vec<Connection> = graph
+        .bonds
+        .iter()
+        .map(|bond| Connection {
+            atom_1: bond.atoms[0],
+            atom_2: bond.atoms[1],
+            order: bond.order,
+        })
+        .collect();
+    for (index, atom) in graph.atoms.iter().enumerate() {
+        for _ in 0..atom.hydrogens {
+            connections.push(Connection {
+                atom_1: index,
+                atom_2: numbers.len(),
+                order: 1,
+            });
+            numbers.push(1);
+            charges.push(0.0);
+            isotopes.push(0);
+            aromatic.push(false);
+        }
+    }
+
+    let positions = embed(&numbers, &connections, &aromatic)?;
+    let num_atoms = numbers.len();
+    let center = positions
+        .iter()
+        .fold([0.0; 3], |sum, p| [sum[0] + p[0], sum[1] + p[1], sum[2] + p[2]])
+        .map(|sum| sum / num_atoms as f64);
+
+    let coordinates = AtomicCoordinates {
+        atomic_num: numbers,
+        x: positions.iter().map(|p| p[0] - center[0]).collect(),
+        y: positions.iter().map(|p| p[1] - center[1]).collect(),
+        z: positions.iter().map(|p| p[2] - center[2]).collect(),
+        connectivity: Some(connections),
+        charges: charges.iter().any(|&q| q != 0.0).then_some(charges),
+        chains: None,
+        cell: None,
+        isotopes: isotopes.iter().any(|&a| a != 0).then_some(isotopes),
+    };
+    coordinates.mass_numbers()?;
+    Ok(coordinates)
+}
+
+fn bond_order(symbol: char, position: usize) -> Result<u8, String> {
+    match symbol {
+        '=' => Ok(2),
+        '#' => Ok(3),
+        ':' => Ok(Connection::AROMATIC),
+        '$' => Err(format!(
+            "Quadruple bond at position {} of SMILES is not supported",
+            position + 1
+        )),
+        _ => Ok(1),
+    }
+}
+
+// Number of a ring closure, a digit or '%' with two digits, and the position after it
+fn ring_number(chars: &[char], position: usize) -> Result<(u32, usize), String> {
+    if chars[position] != '%' {
+        return Ok((chars[position].to_digit(10).unwrap_or(0), position + 1));
+    }
+    let digits: String = chars[position + 1..].iter().take(2).collect();
+    match digits.parse::<u32>() {
+        Ok(number) if digits.len() == 2 && digits.chars().all(|c| c.is_ascii_digit()) => Ok((number, position + 3)),
+        _ => Err(format!(
+            "Expected two digits after '%' at position {} of SMILES",
+            position + 1
+        )),
+    }
+}
+
+// Digits starting at the position as a number with the position after them, None if there are none
+fn read_number(chars: &[char], mut position: usize) -> (Option<u32>, usize) {
+    let start = position;
+    while position < chars.len() && chars[position].is_ascii_digit() {
+        position += 1;
+    }
+    let digits: String = chars[start..position].iter().collect();
+    (digits.parse().ok(), position)
+}
+
+fn atomic_number(symbol: &str) -> Option<i32> {
+    get_element_by_symbol(symbol)
+        .map(|element| element.atomic_number)
+        .filter(|&n| n > 0)
+}
+
+// Atom starting at the position, whether it is a bracket atom and the position after it
+fn parse_atom(chars: &[char], position: usize) -> Result<(SmilesAtom, bool, usize), String> {
+    let mut atom = SmilesAtom {
+        atomic_number: DUMMY_ATOM,
+        mass_number: 0,
+        charge: 0,
+        aromatic: false,
+        hydrogens: 0,
+    };
+    let unexpected = |position: usize| match chars.get(position) {
+        Some(c) => format!("Unexpected '{}' at position {} of SMILES", c, position + 1),
+        None => "Unclosed bracket atom in SMILES".to_string(),
+    };
+
+    if chars[position] != '[' {
+        // Organic subset, two-letter symbols first
+        let two: String = chars[position..].iter().take(2).collect();
+        let (symbol, next) = match two.as_str() {
+            "Cl" | "Br" => (two.clone(), position + 2),
+            _ => (chars[position].to_string(), position + 1),
+        };
+        match symbol.as_str() {
+            "*" => {}
+            "B" | "C" | "N" | "O" | "P" | "S" | "F" | "I" | "Cl" | "Br" => {
+                atom.atomic_number = atomic_number(&symbol).ok_or_else(|| unexpected(position))?;
+            }
+            "b" | "c" | "n" | "o" | "p" | "s" => {
+                atom.atomic_number = atomic_number(&symbol.to_uppercase()).ok_or_else(|| unexpected(position))?;
+                atom.aromatic = true;
+            }
+            _ => return Err(unexpected(position)),
+        }
+        return Ok((atom, false, next));
+    }
+
+    let (mass_number, mut position) = read_number(chars, position + 1);
+    atom.mass_number = mass_number.unwrap_or(0);
+
+    let first = *chars.get(position).ok_or_else(|| unexpected(position))?;
+    let second = chars.get(position + 1).copied().filter(|c| c.is_ascii_lowercase());
+    if first == '*' {
+        position += 1;
+    } else if first.is_ascii_lowercase() {
+        // Aromatic selenium and arsenic, then the aromatic organic subset
+        let two: String = [first].into_iter().chain(second).collect();
+        let symbol = if two == "se" || two == "as" {
+            two
+        } else {
+            first.to_string()
+        };
+        if !matches!(symbol.as_str(), "b" | "c" | "n" | "o" | "p" | "s" | "se" | "as") {
+            return Err(unexpected(position));
+        }
+        let mut letters = symbol.chars();
+        let capitalized: String = letters
+            .next()
+            .map(|c| c.to_ascii_uppercase())
+            .into_iter()
+            .chain(letters)
+            .collect();
+        atom.atomic_number = atomic_number(&capitalized).ok_or_else(|| unexpected(position))?;
+        atom.aromatic = true;
+        position += symbol.len();
+    } else {
+        let two: Option<String> = second.map(|second| [first, second].iter().collect());
+        match two.as_deref().and_then(atomic_number) {
+            Some(number) => {
+                atom.atomic_number = number;
+                position += 2;
+            }
+            None => {
+                atom.atomic_number = atomic_number(&first.to_string()).ok_or_else(|| unexpected(position))?;
+                position += 1;
+            }
+        }
+    }
+
+    // Chirality is not reproduced
+    while chars.get(position) == Some(&'@') {
+        position += 1;
+    }
+    let class: String = chars[position.min(chars.len())..].iter().take(2).collect();
+    if chars[position - 1] == '@' && matches!(class.as_str(), "TH" | "AL" | "SP" | "TB" | "OH") {
+        position = read_number(chars, position + 2).1;
+    }
+
+    if chars.get(position) == Some(&'H') {
+        let (count, next) = read_number(chars, position + 1);
+        atom.hydrogens = count.unwrap_or(1);
+        position = next;
+    }
+
+    if let Some(&sign) = chars.get(position).filter(|&&c| c == '+' || c == '-') {
+        let sign_value = if sign == '+' { 1 } else { -1 };
+        let (count, next) = read_number(chars, position + 1);
+        let (magnitude, next) = match count {
+            Some(count) => (count as i32, next),
+            None => {
+                let repeated = chars[position..].iter().take_while(|&&c| c == sign).count();
+                (repeated as i32, position + repeated)
+            }
+        };
+        atom.charge = sign_value * magnitude;
+        position = next;
+    }
+
+    // Atom class, has no meaning for the structure
+    if chars.get(position) == Some(&':') {
+        let (class, next) = read_number(chars, position + 1);
+        if class.is_none() {
+            return Err(unexpected(position + 1));
+        }
+        position = next;
+    }
+
+    if chars.get(position) != Some(&']') {
+        return Err(unexpected(position));
+    }
+    Ok((atom, true, position + 1))
+}
+
+// Hydrogens of an atom of the organic subset from the lowest default valence that is not exceeded by its bonds.
+// An aromatic atom has one bond of its lowest valence in the aromatic system.
+fn implicit_hydrogens(atom: &SmilesAtom, valence: u32) -> u32 {
+    let valences: &[u32] = match atom.atomic_number {
+        5 => &[3],
+        6 => &[4],
+        7 | 15 => &[3, 5],
+        8 => &[2],
+        16 => &[2, 4, 6],
+        9 | 17 | 35 | 53 => &[1],
+        _ => return 0,
+    };
+    if atom.aromatic {
+        return (valences[0] - 1).saturating_sub(valence);
+    }
+    valences.iter().find(|&&v| v >= valence).map_or(0, |&v| v - valence)
+}
+
+fn covalent_radius(number: i32) -> Result<f64, String> {
+    if number < 1 {
+        return Ok(DUMMY_RADIUS);
+    }
+    get_element_by_number(number)
+        .map(|element| element.covalent_radius)
+        .filter(|&r| r > 0.0)
+        .ok_or_else(|| format!("No covalent radius of atomic number {}", number))
+}
+
+// Distance between two atoms kept within bounds
+struct Restraint {
+    atoms: [usize; 2],
+    lower: f64,
+    upper: f64,
+}
+
+struct Embedding {
+    restraints: Vec<Restraint>,
+    // Atoms i-j=k-l about double and aromatic bonds that should lie in a plane
+    planes: Vec<[usize; 4]>,
+}
+
+impl Embedding {
+    fn new(numbers: &[i32], connections: &[Connection], aromatic: &[bool]) -> Result<Self, String> {
+        let num_atoms = numbers.len();
+        let mut neighbors: Vec<Vec<(usize, f64)>> = vec![Vec::new(); num_atoms];
+        let mut restraints = Vec::new();
+        for connection in connections {
+            let (i, j) = (connection.atom_1, connection.atom_2);
+            let factor = BOND_LENGTHS
+                .iter()
+                .find(|(order, _)| *order == connection.order)
+                .map_or(1.0, |(_, factor)| *factor);
+            let length = (covalent_radius(numbers[i])? + covalent_radius(numbers[j])?) * factor;
+            restraints.push(Restraint {
+                atoms: [i, j],
+                lower: length,
+                upper: length,
+            });
+            neighbors[i].push((j, length));
+            neighbors[j].push((i, length));
+        }
+
+        let angles: Vec<f64> = (0..num_atoms)
+            .map(|atom| {
+                let orders = connections
+                    .iter()
+                    .filter(|c| c.atom_1 == atom || c.atom_2 == atom)
+                    .map(|c| c.order);
+                let (mut doubles, mut triples, mut aromatic_bonds) = (0, 0, 0);
+                for order in orders {
+                    match order {
+                        2 => doubles += 1,
+                        3 => triples += 1,
+                        Connection::AROMATIC => aromatic_bonds += 1,
+                        _ => {}
+                    }
+                }
+                if triples > 0 || doubles > 1 {
+                    180.0
+                } else if doubles > 0 || aromatic_bonds > 0 || aromatic[atom] {
+                    120.0
+                } else {
+                    109.47
+                }
+            })
+            .collect();
+
+        // Atoms bonded to the same atom, which has up to four neighbors, at the distance of the ideal angle
+        let mut close = HashSet::new();
+        for connection in connections {
+            close.insert(pair(connection.atom_1, connection.atom_2));
+        }
+        for (center, bonded) in neighbors.iter().enumerate() {
+            for (k, &(a, to_a)) in bonded.iter().enumerate() {
+                for &(b, to_b) in &bonded[k + 1..] {
+                    if bonded.len() > 4 || !close.insert(pair(a, b)) {
+                        continue;
+                    }
+                    let cosine = angles[center].to_radians().cos();
+                    let distance = (to_a * to_a + to_b * to_b - 2.0 * to_a * to_b * cosine).sqrt();
+                    restraints.push(Restraint {
+                        atoms: [a, b],
+                        lower: distance,
+                        upper: distance,
+                    });
+                }
+            }
+        }
+
+        let radii: Vec<f64> = numbers
+            .iter()
+            .map(|&n| get_vdw_radius(n).unwrap_or(DEFAULT_VDW_RADIUS))
+            .collect();
+        for i in 0..num_atoms {
+            for j in i + 1..num_atoms {
+                if !close.contains(&(i, j)) {
+                    restraints.push(Restraint {
+                        atoms: [i, j],
+                        lower: DEFAULT_CLASH_FRACTION * (radii[i] + radii[j]),
+                        upper: f64::INFINITY,
+                    });
+                }
+            }
+        }
+
+        let mut planes = Vec::new();
+        for connection in connections
+            .iter()
+            .filter(|c| c.order == 2 || c.order == Connection::AROMATIC)
+        {
+            let (j, k) = (connection.atom_1, connection.atom_2);
+            if angles[j] != 120.0 || angles[k] != 120.0 {
+                continue;
+            }
+            for &(i, _) in neighbors[j].iter().filter(|n| n.0 != k) {
+                for &(l, _) in neighbors[k].iter().filter(|n| n.0 != j) {
+                    if i != l {
+                        planes.push([i, j, k, l]);
+                    }
+                }
+            }
+        }
+
+        Ok(Self { restraints, planes })
+    }
+
+    // Penalty of the positions with its gradient. The fourth coordinate is weighted by `squeeze`, the planes are
+    // only flattened in three dimensions.
+    fn penalty(&self, positions: &[[f64; 4]], squeeze: f64, flat: bool, gradient: &mut [[f64; 4]]) -> f64 {
+        gradient.iter_mut().for_each(|g| *g = [0.0; 4]);
+        let mut penalty = 0.0;
+        for restraint in &self.restraints {
+            let [i, j] = restraint.atoms;
+            let delta: [f64; 4] = std::array::from_fn(|k| positions[i][k] - positions[j][k]);
+            let distance = delta.iter().map(|d| d * d).sum::<f64>().sqrt().max(1e-6);
+            let error = if distance < restraint.lower {
+                distance - restraint.lower
+            } else if distance > restraint.upper {
+                distance - restraint.upper
+            } else {
+                continue;
+            };
+            penalty += error * error;
+            for k in 0..4 {
+                let g = 2.0 * error * delta[k] / distance;
+                gradient[i][k] += g;
+                gradient[j][k] -= g;
+            }
+        }
+        for (position, g) in positions.iter().zip(gradient.iter_mut()) {
+            penalty += squeeze * position[3] * position[3];
+            g[3] += 2.0 * squeeze * position[3];
+        }
+        if flat {
+            for &[i, j, k, l] in &self.planes {
+                let a: [f64; 3] = std::array::from_fn(|m| positions[i][m] - positions[j][m]);
+                let b: [f64; 3] = std::array::from_fn(|m| positions[k][m] - positions[j][m]);
+                let c: [f64; 3] = std::array::from_fn(|m| positions[l][m] - positions[j][m]);
+                let (bc, ca, ab) = (cross(b, c), cross(c, a), cross(a, b));
+                let volume = a[0] * bc[0] + a[1] * bc[1] + a[2] * bc[2];
+                penalty += PLANARITY_WEIGHT * volume * volume;
+                let factor = 2.0 * PLANARITY_WEIGHT * volume;
+                for m in 0..3 {
+                    gradient[i][m] += factor * bc[m];
+                    gradient[k][m] += factor * ca[m];
+                    gradient[l][m] += factor * ab[m];
+                    gradient[j][m] -= factor * (bc[m] + ca[m] + ab[m]);
+                }
+            }
+        }
+        penalty
+    }
+
+    // Steepest descent with a step that grows while the penalty decreases and shrinks otherwise
+    fn minimize(&self, positions: &mut [[f64; 4]], squeeze: f64, flat: bool) -> f64 {
+        let mut gradient = vec![[0.0; 4]; positions.len()];
+        let mut penalty = self.penalty(positions, squeeze, flat, &mut gradient);
+        let mut trial = positions.to_vec();
+        let mut trial_gradient = gradient.clone();
+        let mut step: f64 = 0.01;
+        for _ in 0..STEPS {
+            let largest = gradient
+                .iter()
+                .map(|g| g.iter().map(|x| x * x).sum::<f64>().sqrt())
+                .fold(0.0, f64::max);
+            if largest < 1e-6 {
+                break;
+            }
+            let scale = step.min(MAX_STEP / largest);
+            for ((t, p), g) in trial.iter_mut().zip(positions.iter()).zip(&gradient) {
+                *t = std::array::from_fn(|k| p[k] - scale * g[k]);
+                if flat {
+                    t[3] = 0.0;
+                }
+            }
+            let trial_penalty = self.penalty(&trial, squeeze, flat, &mut trial_gradient);
+            if trial_penalty < penalty {
+                positions.copy_from_slice(&trial);
+                std::mem::swap(&mut gradient, &mut trial_gradient);
+                penalty = trial_penalty;
+                step *= 1.2;
+            } else {
+                step *= 0.5;
+            }
+        }
+        penalty
+    }
+}
+
+fn pair(a: usize, b: usize) -> (usize, usize) {
+    (a.min(b), a.max(b))
+}
+
+fn embed(numbers: &[i32], connections: &[Connection], aromatic: &[bool]) -> Result<Vec<[f64; 3]>, String> {
+    let embedding = Embedding::new(numbers, connections, aromatic)?;
+    let num_atoms = numbers.len();
+    // Random positions spread over about the volume of the molecule
+    let spread = 1.5 * (num_atoms as f64).cbrt();
+    let mut rng = Rng::new(SEED);
+    let mut best: Option<(f64, Vec<[f64; 4]>)> = None;
+    for _ in 0..ATTEMPTS {
+        let mut positions: Vec<[f64; 4]> = (0..num_atoms)
+            .map(|_| std::array::from_fn(|_| rng.uniform(-spread, spread)))
+            .collect();
+        embedding.minimize(&mut positions, 0.0, false);
+        embedding.minimize(&mut positions, SQUEEZE_WEIGHT, false);
+        positions.iter_mut().for_each(|p| p[3] = 0.0);
+        let penalty = embedding.minimize(&mut positions, 0.0, true);
+        if best.as_ref().is_none_or(|(lowest, _)| penalty < *lowest) {
+            best = Some((penalty, positions));
+        }
+    }
+    let positions = best.map(|(_, positions)| positions).unwrap_or_default();
+    Ok(positions.iter().map(|p| [p[0], p[1], p[2]]).collect())
+}
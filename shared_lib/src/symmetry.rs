@@ -0,0 +1,734 @@
+// Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
+// Licensed under the MIT License
+
+//! Point groups: symmetry operations mapping a structure onto itself, the Schoenflies symbol of the group they
+//! form and the symmetry elements (rotation axes, mirror planes, inversion center) through the center.
+
+use std::cmp::Reverse;
+use std::collections::HashSet;
+
+use serde::Serialize;
+
+use crate::alignment;
+use crate::comparison::Tolerances;
+use crate::math::Vec3;
+use crate::types::AtomicCoordinates;
+
+type Matrix = [[f64; 3]; 3];
+
+// Symmetry is searched by testing candidate axes through pairs of atoms, which is quadratic in the number of atoms
+pub const MAX_ATOMS: usize = 200;
+// Highest order of rotation axes searched for
+const MAX_AXIS_ORDER: u32 = 6;
+// Icosahedral groups are the largest point groups of finite order
+const MAX_OPERATIONS: usize = 120;
+const IDENTITY: Matrix = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+
+/// Symmetry element through the center of the structure, directions are unit vectors.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SymmetryElement {
+    /// Proper rotation axis of the highest order about it, 0 for the axis of linear structures.
+    Axis {
+        direction: [f64; 3],
+        order: u32,
+    },
+    /// Improper rotation axis that is not implied by a mirror plane or the inversion alone, e.g. S4 of methane.
+    ImproperAxis {
+        direction: [f64; 3],
+        order: u32,
+    },
+    Mirror {
+        normal: [f64; 3],
+    },
+    Inversion,
+}
+
+/// Symmetry operations mapping the structure onto itself about its center, the point group they form
+/// and the atoms each operation maps every atom to.
+pub struct Symmetry {
+    center: Vec3<f64>,
+    operations: Vec<Matrix>,
+    // Image of every atom under every operation
+    images: Vec<Vec<usize>>,
+    point_group: String,
+    linear: bool,
+}
+
+impl Symmetry {
+    /// Finds the symmetry operations of atoms with the positions and atomic numbers.
+    pub fn detect(positions: &[Vec3<f64>], numbers: &[i32], tolerance: f64) -> Result<Self, String> {
+        if positions.is_empty() {
+            return Err("No structure is loaded".to_string());
+        }
+        if positions.len() > MAX_ATOMS {
+            return Err(format!(
+                "Symmetry is detected for structures with up to {} atoms",
+                MAX_ATOMS
+            ));
+        }
+        if tolerance.is_nan() || tolerance <= 0.0 {
+            return Err(format!("Invalid symmetry tolerance: {}", tolerance));
+        }
+
+        let center = positions.iter().fold(Vec3::zero(), |sum, &p| sum + p) / positions.len() as f64;
+        let r: Vec<Vec3<f64>> = positions.iter().map(|&p| p - center).collect();
+        let image = |m: &Matrix| images(m, &r, numbers, tolerance);
+        let linear = is_linear(&r, tolerance);
+
+        // Operations are accepted within the tolerance, so they are snapped to the exact mappings of the atoms onto
+        // their images before they are multiplied. Otherwise the errors add up in the products, e.g. two near-C2
+        // axes combine into a rotation by a small angle. Rotations about the axis of a linear structure all map
+        // the atoms the same way and are kept as they are.
+        let mut operations = vec![IDENTITY];
+        let add = |m: Matrix, operations: &mut Vec<Matrix>| {
+            let Some(mapped) = image(&m) else {
+                return;
+            };
+            let m = match linear {
+                true => m,
+                false => snapped(&r, &mapped, determinant(&m) < 0.0).unwrap_or(m),
+            };
+            if operation_order(&m) <= 2 * MAX_AXIS_ORDER
+                && (determinant(&m) < 0.0 || rotation_order(&m) <= MAX_AXIS_ORDER)
+                && image(&m).is_some()
+                && !operations.iter().any(|o| same(o, &m))
+            {
+                operations.push(m);
+            }
+        };
+        add(scaled(&IDENTITY, -1.0), &mut operations);
+        for axis in candidate_axes(&r, numbers, tolerance) {
+            add(reflection(axis), &mut operations);
+            for n in 2..=MAX_AXIS_ORDER {
+                let rotation = rotation(axis, 360.0 / n as f64);
+                add(rotation, &mut operations);
+                add(multiply(&reflection(axis), &rotation), &mut operations);
+            }
+        }
+
+        // Products of the found operations complete the group, e.g. powers of rotations
+        let mut i = 0;
+        while i < operations.len() && operations.len() <= MAX_OPERATIONS {
+            for j in 0..=i {
+                for m in [
+                    multiply(&operations[i], &operations[j]),
+                    multiply(&operations[j], &operations[i]),
+                ] {
+                    add(m, &mut operations);
+                }
+            }
+            i += 1;
+        }
+
+        let images = operations.iter().map(|m| image(m).unwrap_or_default()).collect();
+        let point_group = point_group(&operations, linear);
+        Ok(Self {
+            center,
+            operations,
+            images,
+            point_group,
+            linear,
+        })
+    }
+
+    /// Finds the symmetry operations of the structure, `tolerance` is the largest distance in Angstroms between
+    /// an atom moved by an operation and its image.
+    pub fn from_coordinates(coordinates: &AtomicCoordinates, tolerance: f64) -> Result<Self, String> {
+        let positions: Vec<Vec3<f64>> = (0..coordinates.atomic_num.len())
+            .map(|i| Vec3::new(coordinates.x[i], coordinates.y[i], coordinates.z[i]))
+            .collect();
+        Self::detect(&positions, &coordinates.atomic_num, tolerance)
+    }
+
+    /// Schoenflies symbol, e.g. "C2v". Linear molecules are "C∞v" or "D∞h".
+    pub fn point_group(&self) -> &str {
+        &self.point_group
+    }
+
+    /// Center of the structure all symmetry elements pass through.
+    pub fn center(&self) -> [f64; 3] {
+        [self.center.x, self.center.y, self.center.z]
+    }
+
+    /// Symmetry elements of the operations: rotation axes from the highest order, mirror planes, the inversion
+    /// center and improper axes.
+    pub fn elements(&self) -> Vec<SymmetryElement> {
+        let parallel = |a: Vec3<f64>, b: Vec3<f64>| Vec3::dot_product(a, b).abs() > 1.0 - 1e-3;
+        // Highest order of the operations about every distinct direction
+        let add = |axes: &mut Vec<(Vec3<f64>, u32)>, axis: Vec3<f64>, order: u32| match axes
+            .iter_mut()
+            .find(|(a, _)| parallel(*a, axis))
+        {
+            Some((_, highest)) => *highest = (*highest).max(order),
+            None => axes.push((canonical(axis), order)),
+        };
+        let mut axes = Vec::new();
+        let mut improper_axes = Vec::new();
+        let mut mirrors = Vec::new();
+        let mut inversion = false;
+        for m in &self.operations {
+            if determinant(m) > 0.0 {
+                let order = rotation_order(m);
+                if order > 1 {
+                    add(&mut axes, rotation_axis(m), order);
+                }
+            } else if trace(m) < -2.9 {
+                inversion = true;
+            } else if (trace(m) - 1.0).abs() < 1e-3 {
+                add(&mut mirrors, rotation_axis(&scaled(m, -1.0)), 1);
+            } else {
+                add(&mut improper_axes, rotation_axis(&scaled(m, -1.0)), operation_order(m));
+            }
+        }
+        axes.sort_by_key(|&(_, order)| Reverse(order));
+        improper_axes.sort_by_key(|&(_, order)| Reverse(order));
+
+        if self.linear {
+            // Axes perpendicular to the axis of a linear structure and mirror planes containing it are infinitely
+            // many, only the axis, of infinite order, and the plane perpendicular to it are listed
+            axes.truncate(1);
+            if let Some((axis, order)) = axes.first_mut() {
+                *order = 0;
+                mirrors.retain(|&(normal, _)| parallel(normal, *axis));
+            }
+            improper_axes.clear();
+        }
+
+        let mut elements: Vec<SymmetryElement> = Vec::new();
+        for (axis, order) in axes {
+            elements.push(SymmetryElement::Axis {
+                direction: [axis.x, axis.y, axis.z],
+                order,
+            });
+        }
+        for (normal, _) in mirrors {
+            elements.push(SymmetryElement::Mirror {
+                normal: [normal.x, normal.y, normal.z],
+            });
+        }
+        if inversion {
+            elements.push(SymmetryElement::Inversion);
+        }
+        for (axis, order) in improper_axes {
+            elements.push(SymmetryElement::ImproperAxis {
+                direction: [axis.x, axis.y, axis.z],
+                order,
+            });
+        }
+        elements
+    }
+
+    /// Atoms (zero-based) the atom is mapped to by the operations, including the atom itself.
+    pub fn equivalent_atoms(&self, atom: usize) -> Vec<usize> {
+        let mut atoms: Vec<usize> = self.images.iter().map(|images| images[atom]).collect();
+        atoms.sort_unstable();
+        atoms.dedup();
+        atoms
+    }
+
+    /// New positions of the atom and its symmetry-equivalent images after moving the atom by the displacement.
+    /// The displacement is projected onto the directions keeping the atom on the symmetry elements it lies on,
+    /// e.g. an atom on a rotation axis moves only along the axis.
+    pub fn move_atom(&self, positions: &[Vec3<f64>], atom: usize, displacement: Vec3<f64>) -> Vec<(usize, Vec3<f64>)> {
+        let stabilizer: Vec<&Matrix> = self
+            .operations
+            .iter()
+            .zip(&self.images)
+            .filter(|(_, images)| images[atom] == atom)
+            .map(|(m, _)| m)
+            .collect();
+        let projected = stabilizer
+            .iter()
+            .fold(Vec3::zero(), |sum, m| sum + apply(m, displacement))
+            / stabilizer.len() as f64;
+
+        let r = positions[atom] - self.center + projected;
+        let mut moved: Vec<(usize, Vec3<f64>)> = Vec::new();
+        for (m, images) in self.operations.iter().zip(&self.images) {
+            let image = images[atom];
+            if !moved.iter().any(|&(i, _)| i == image) {
+                moved.push((image, self.center + apply(m, r)));
+            }
+        }
+        moved
+    }
+}
+
+fn apply(m: &Matrix, v: Vec3<f64>) -> Vec3<f64> {
+    Vec3::new(
+        m[0][0] * v.x + m[0][1] * v.y + m[0][2] * v.z,
+        m[1][0] * v.x + m[1][1] * v.y + m[1][2] * v.z,
+        m[2][0] * v.x + m[2][1] * v.y + m[2][2] * v.z,
+    )
+}
+
+fn multiply(a: &Matrix, b: &Matrix) -> Matrix {
+    let mut m = [[0.0; 3]; 3];
+    for (i, row) in m.iter_mut().enumerate() {
+        for (j, value) in row.iter_mut().enumerate() {
+            *value = (0..3).map(|k| a[i][k] * b[k][j]).sum();
+        }
+    }
+    m
+}
+
+fn scaled(m: &Matrix, factor: f64) -> Matrix {
+    m.map(|row| row.map(|value| value * factor))
+}
+
+fn same(a: &Matrix, b: &Matrix) -> bool {
+    a.iter()
+        .flatten()
+        .zip(b.iter().flatten())
+        .all(|(x, y)| (x - y).abs() < 1e-3)
+}
+
+fn trace(m: &Matrix) -> f64 {
+    m[0][0] + m[1][1] + m[2][2]
+}
+
+fn determinant(m: &Matrix) -> f64 {
+    m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1]) - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+        + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+}
+
+/// Rotation by the angle in degrees about the unit axis.
+fn rotation(axis: Vec3<f64>, angle: f64) -> Matrix {
+    let (sin, cos) = angle.to_radians().sin_cos();
+    let [x, y, z] = [axis.x, axis.y, axis.z];
+    let t = 1.0 - cos;
+    [
+        [t * x * x + cos, t * x * y - sin * z, t * x * z + sin * y],
+        [t * x * y + sin * z, t * y * y + cos, t * y * z - sin * x],
+        [t * x * z - sin * y, t * y * z + sin * x, t * z * z + cos],
+    ]
+}
+
+/// Reflection in the plane through the center with the unit normal.
+fn reflection(normal: Vec3<f64>) -> Matrix {
+    let n = [normal.x, normal.y, normal.z];
+    let mut m = IDENTITY;
+    for (i, row) in m.iter_mut().enumerate() {
+        for (j, value) in row.iter_mut().enumerate() {
+            *value -= 2.0 * n[i] * n[j];
+        }
+    }
+    m
+}
+
+/// Atom every atom is mapped to by the operation, `None` if some atom has no image of the same element.
+fn images(m: &Matrix, r: &[Vec3<f64>], numbers: &[i32], tolerance: f64) -> Option<Vec<usize>> {
+    let tolerance2 = tolerance * tolerance;
+    r.iter()
+        .zip(numbers)
+        .map(|(&p, &number)| {
+            let moved = apply(m, p);
+            (0..r.len()).find(|&j| numbers[j] == number && (r[j] - moved).length_squared() < tolerance2)
+        })
+        .collect()
+}
+
+fn is_linear(r: &[Vec3<f64>], tolerance: f64) -> bool {
+    let Some(&direction) = r
+        .iter()
+        .max_by(|a, b| a.length_squared().total_cmp(&b.length_squared()))
+    else {
+        return true;
+    };
+    if direction.length() < tolerance {
+        return true;
+    }
+    let direction = direction.normalized();
+    r.iter()
+        .all(|&p| (p - direction * Vec3::dot_product(p, direction)).length() < tolerance)
+}
+
+/// Possible directions of symmetry elements: towards atoms, towards midpoints of pairs of atoms which may be
+/// equivalent, the normals of planes through such pairs and the center and the normals of planes exchanging them.
+/// Every operation maps each shell of atoms of one element at one distance from the center onto itself,
+/// so only pairs of the smallest shells are used.
+fn candidate_axes(r: &[Vec3<f64>], numbers: &[i32], tolerance: f64) -> Vec<Vec3<f64>> {
+    let mut axes = Vec::new();
+    let mut keys = HashSet::new();
+    let mut add = |v: Vec3<f64>| {
+        if v.length() < 1e-6 {
+            return;
+        }
+        let v = canonical(v);
+        let key = [v.x, v.y, v.z].map(|c| (c * 1000.0).round() as i64);
+        if keys.insert(key) {
+            axes.push(v);
+        }
+    };
+    for a in [
+        Vec3::new(1.0, 0.0, 0.0),
+        Vec3::new(0.0, 1.0, 0.0),
+        Vec3::new(0.0, 0.0, 1.0),
+    ] {
+        add(a);
+    }
+    for &p in r {
+        add(p);
+    }
+    // Normal of the plane of planar structures, also when all atoms are different
+    for pair in r.windows(2) {
+        add(Vec3::cross_product(pair[0], pair[1]));
+    }
+
+    let mut shells: Vec<Vec<usize>> = Vec::new();
+    for (i, &p) in r.iter().enumerate() {
+        match shells
+            .iter_mut()
+            .find(|shell| numbers[shell[0]] == numbers[i] && (r[shell[0]].length() - p.length()).abs() < tolerance)
+        {
+            Some(shell) => shell.push(i),
+            None => shells.push(vec![i]),
+        }
+    }
+    shells.retain(|shell| shell.len() > 1);
+    shells.sort_by_key(|shell| shell.len());
+    for shell in shells.iter().take(2) {
+        for (k, &i) in shell.iter().enumerate() {
+            for &j in &shell[k + 1..] {
+                add(r[i] + r[j]);
+                add(r[i] - r[j]);
+                add(Vec3::cross_product(r[i], r[j]));
+            }
+        }
+    }
+    axes
+}
+
+/// Unit vector along the direction pointing to positive x, or y, or z if perpendicular to the previous axes.
+/// Opposite directions give the same elements.
+fn canonical(v: Vec3<f64>) -> Vec3<f64> {
+    let v = v.normalized();
+    if v.x < -1e-6 || (v.x.abs() <= 1e-6 && (v.y < -1e-6 || (v.y.abs() <= 1e-6 && v.z < 0.0))) {
+        -v
+    } else {
+        v
+    }
+}
+
+/// Axis of a proper rotation, the eigenvector with the eigenvalue 1.
+fn rotation_axis(m: &Matrix) -> Vec3<f64> {
+    let axis = Vec3::new(m[2][1] - m[1][2], m[0][2] - m[2][0], m[1][0] - m[0][1]);
+    if axis.length() > 1e-6 {
+        return axis.normalized();
+    }
+    // Rotations by 180°: columns of M + I are along the axis
+    (0..3)
+        .map(|j| Vec3::new(m[0][j], m[1][j], m[2][j]) + Vec3::new(IDENTITY[0][j], IDENTITY[1][j], IDENTITY[2][j]))
+        .max_by(|a, b| a.length_squared().total_cmp(&b.length_squared()))
+        .unwrap_or(Vec3::zero())
+        .normalized()
+}
+
+/// Order of a rotation from the angle of it, 1 for the identity.
+fn rotation_order(m: &Matrix) -> u32 {
+    order_of_angle((trace(m) - 1.0) / 2.0)
+}
+
+/// Order of a proper rotation or of an improper one: a rotation by φ followed by the reflection in the plane
+/// perpendicular to the axis, whose trace is 2cosφ - 1. Reflections and the inversion have the order 1 and 2.
+fn operation_order(m: &Matrix) -> u32 {
+    if determinant(m) > 0.0 {
+        rotation_order(m)
+    } else {
+        order_of_angle((trace(m) + 1.0) / 2.0)
+    }
+}
+
+fn order_of_angle(cos: f64) -> u32 {
+    let angle = cos.clamp(-1.0, 1.0).acos().to_degrees();
+    if angle < 1.0 { 1 } else { (360.0 / angle).round() as u32 }
+}
+
+/// Orthogonal matrix, a proper or an improper one, mapping the atoms onto their images with the least squares
+/// error. The atoms are centered, so are their images.
+fn snapped(r: &[Vec3<f64>], images: &[usize], improper: bool) -> Option<Matrix> {
+    // An improper operation is the inversion of a proper rotation of the inverted atoms
+    let sign = if improper { -1.0 } else { 1.0 };
+    let mobile: Vec<[f64; 3]> = r.iter().map(|&p| [p.x * sign, p.y * sign, p.z * sign]).collect();
+    let target: Vec<[f64; 3]> = images.iter().map(|&j| [r[j].x, r[j].y, r[j].z]).collect();
+    let alignment = alignment::kabsch(&mobile, &target, &Tolerances::default()).ok()?;
+    let origin = alignment.apply([0.0; 3]);
+    let columns: [[f64; 3]; 3] = std::array::from_fn(|j| {
+        let image = alignment.apply(std::array::from_fn(|i| IDENTITY[i][j]));
+        std::array::from_fn(|i| image[i] - origin[i])
+    });
+    Some(std::array::from_fn(|i| std::array::from_fn(|j| sign * columns[j][i])))
+}
+
+fn point_group(operations: &[Matrix], linear: bool) -> String {
+    let inversion = operations.iter().any(|m| trace(m) < -2.9);
+    if linear {
+        return if inversion { "D∞h" } else { "C∞v" }.to_string();
+    }
+    let proper: Vec<&Matrix> = operations.iter().filter(|m| determinant(m) > 0.0).collect();
+    let improper: Vec<&Matrix> = operations.iter().filter(|m| determinant(m) < 0.0).collect();
+    // Normals of mirror planes: trace of reflections is 1
+    let mirrors: Vec<Vec3<f64>> = improper
+        .iter()
+        .filter(|m| (trace(m) - 1.0).abs() < 1e-3)
+        .map(|m| rotation_axis(&scaled(m, -1.0)))
+        .collect();
+
+    let perpendicular = |a: Vec3<f64>, b: Vec3<f64>| Vec3::dot_product(a, b).abs() < 1e-3;
+    let parallel = |a: Vec3<f64>, b: Vec3<f64>| Vec3::dot_product(a, b).abs() > 1.0 - 1e-3;
+
+    let order = operations.len();
+    let mut high_order_axes: Vec<Vec3<f64>> = Vec::new();
+    for m in proper.iter().filter(|m| rotation_order(m) >= 3) {
+        let axis = rotation_axis(m);
+        if !high_order_axes.iter().any(|&a| parallel(a, axis)) {
+            high_order_axes.push(axis);
+        }
+    }
+    // Several axes of order 3 or higher: cubic and icosahedral groups
+    if high_order_axes.len() > 1 {
+        let name = match (order, inversion, improper.is_empty()) {
+            (12, _, _) => "T",
+            (24, true, _) => "Th",
+            (24, false, false) => "Td",
+            (24, false, true) => "O",
+            (48, _, _) => "Oh",
+            (60, _, _) => "I",
+            (120, _, _) => "Ih",
+            _ => return format!("group of order {}", order),
+        };
+        return name.to_string();
+    }
+
+    let Some(principal) = proper.iter().max_by_key(|m| rotation_order(m)).copied() else {
+        return "C1".to_string();
+    };
+    let n = rotation_order(principal);
+    if n == 1 {
+        return if !mirrors.is_empty() {
+            "Cs"
+        } else if inversion {
+            "Ci"
+        } else {
+            "C1"
+        }
+        .to_string();
+    }
+
+    // With several C2 axes and no higher one any of them can be the principal axis, the one with the most
+    // mirror planes containing it or perpendicular to it is chosen
+    let candidates: Vec<Vec3<f64>> = proper
+        .iter()
+        .filter(|m| rotation_order(m) == n)
+        .map(|m| rotation_axis(m))
+        .collect();
+    let c2_axes: Vec<Vec3<f64>> = proper
+        .iter()
+        .filter(|m| rotation_order(m) == 2)
+        .map(|m| rotation_axis(m))
+        .collect();
+    let z = candidates
+        .iter()
+        .copied()
+        .max_by_key(|&z| {
+            mirrors
+                .iter()
+                .filter(|&&m| parallel(m, z) || perpendicular(m, z))
+                .count()
+        })
+        .unwrap_or(Vec3::zero());
+
+    let dihedral = c2_axes.iter().any(|&axis| perpendicular(axis, z));
+    let horizontal_mirror = mirrors.iter().any(|&normal| parallel(normal, z));
+    let vertical_mirror = mirrors.iter().any(|&normal| perpendicular(normal, z));
+    match (dihedral, horizontal_mirror, vertical_mirror) {
+        (true, true, _) => format!("D{}h", n),
+        (true, false, true) => format!("D{}d", n),
+        (true, false, false) => format!("D{}", n),
+        (false, true, _) => format!("C{}h", n),
+        (false, false, true) => format!("C{}v", n),
+        // Improper operations without mirror planes: an improper axis of twice the order of the rotation axis
+        (false, false, false) if !improper.is_empty() => format!("S{}", 2 * n),
+        (false, false, false) => format!("C{}", n),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::smiles;
+    use crate::test_structures::{carbon_dioxide, hydrogen_peroxide, water};
+
+    /// Point groups of common shapes, positions are given as (atomic number, x, y, z).
+    #[test]
+    fn point_groups() {
+        let detect = |atoms: &[(i32, f64, f64, f64)]| {
+            let positions: Vec<Vec3<f64>> = atoms.iter().map(|&(_, x, y, z)| Vec3::new(x, y, z)).collect();
+            let numbers: Vec<i32> = atoms.iter().map(|&(number, ..)| number).collect();
+            Symmetry::detect(&positions, &numbers, 0.05)
+                .unwrap()
+                .point_group()
+                .to_string()
+        };
+        let ring = |number, radius: f64, z, start: f64, count| {
+            (0..count).map(move |k| {
+                let angle = (start + 360.0 * k as f64 / count as f64).to_radians();
+                (number, radius * angle.cos(), radius * angle.sin(), z)
+            })
+        };
+        let h = 0.629;
+        let methane = [
+            (6, 0.0, 0.0, 0.0),
+            (1, h, h, h),
+            (1, h, -h, -h),
+            (1, -h, h, -h),
+            (1, -h, -h, h),
+        ];
+        let benzene: Vec<_> = ring(6, 1.39, 0.0, 0.0, 6).chain(ring(1, 2.47, 0.0, 0.0, 6)).collect();
+        let ammonia: Vec<_> = [(7, 0.0, 0.0, 0.0)]
+            .into_iter()
+            .chain(ring(1, 0.94, -0.38, 0.0, 3))
+            .collect();
+        let ethane: Vec<_> = [(6, 0.0, 0.0, 0.77), (6, 0.0, 0.0, -0.77)]
+            .into_iter()
+            .chain(ring(1, 1.02, 1.16, 0.0, 3))
+            .chain(ring(1, 1.02, -1.16, 60.0, 3))
+            .collect();
+        let allene = [
+            (6, 0.0, 0.0, 0.0),
+            (6, 0.0, 0.0, 1.31),
+            (6, 0.0, 0.0, -1.31),
+            (1, 0.93, 0.0, 1.87),
+            (1, -0.93, 0.0, 1.87),
+            (1, 0.0, 0.93, -1.87),
+            (1, 0.0, -0.93, -1.87),
+        ];
+        let diazene = [
+            (7, 0.62, 0.0, 0.0),
+            (7, -0.62, 0.0, 0.0),
+            (1, 0.95, 0.95, 0.0),
+            (1, -0.95, -0.95, 0.0),
+        ];
+        let carbon_dioxide = [(6, 0.0, 0.0, 0.0), (8, 1.16, 0.0, 0.0), (8, -1.16, 0.0, 0.0)];
+        let hydrogen_peroxide = hydrogen_peroxide();
+        let hydrogen_peroxide: Vec<_> = (0..4)
+            .map(|i| {
+                let d = &hydrogen_peroxide;
+                (d.atomic_num[i], d.x[i], d.y[i], d.z[i])
+            })
+            .collect();
+
+        assert_eq!(detect(&methane), "Td");
+        assert_eq!(detect(&benzene), "D6h");
+        assert_eq!(detect(&ammonia), "C3v");
+        assert_eq!(detect(&ethane), "D3d");
+        assert_eq!(detect(&allene), "D2d");
+        assert_eq!(detect(&diazene), "C2h");
+        assert_eq!(detect(&carbon_dioxide), "D∞h");
+        assert_eq!(detect(&hydrogen_peroxide), "C2");
+        assert_eq!(detect(&methane[..4]), "C3v");
+        assert_eq!(
+            detect(&[(6, 0.0, 0.0, 0.0), (1, 1.0, 0.2, 0.0), (8, 0.0, 1.3, 0.4)]),
+            "Cs"
+        );
+    }
+
+    /// Symmetry elements of water, methane and carbon dioxide: rotation axes with their orders, mirror planes,
+    /// the inversion center and the improper S4 axes of methane. Of the infinitely many elements of linear
+    /// molecules only the axis and the plane perpendicular to it are listed.
+    #[test]
+    fn symmetry_elements() {
+        let counts = |data: &AtomicCoordinates| {
+            let symmetry = Symmetry::from_coordinates(data, 0.05).unwrap();
+            let mut axes = Vec::new();
+            let (mut mirrors, mut inversion, mut improper) = (0, false, Vec::new());
+            for element in symmetry.elements() {
+                match element {
+                    SymmetryElement::Axis { order, .. } => axes.push(order),
+                    SymmetryElement::Mirror { .. } => mirrors += 1,
+                    SymmetryElement::Inversion => inversion = true,
+                    SymmetryElement::ImproperAxis { order, .. } => improper.push(order),
+                }
+            }
+            (axes, mirrors, inversion, improper)
+        };
+        let h = 0.629;
+        let methane = AtomicCoordinates {
+            atomic_num: vec![6, 1, 1, 1, 1],
+            x: vec![0.0, h, h, -h, -h],
+            y: vec![0.0, h, -h, h, -h],
+            z: vec![0.0, h, -h, -h, h],
+            ..water()
+        };
+
+        assert_eq!(counts(&water()), (vec![2], 2, false, vec![]));
+        assert_eq!(counts(&methane), (vec![3, 3, 3, 3, 2, 2, 2], 6, false, vec![4, 4, 4]));
+        assert_eq!(counts(&carbon_dioxide()), (vec![0], 1, true, vec![]));
+
+        // The C2 axis of water is along Y through the centroid, the mirror planes contain it
+        let symmetry = Symmetry::from_coordinates(&water(), 0.05).unwrap();
+        assert!((symmetry.center()[1] - 0.586 * 2.0 / 3.0).abs() < 1e-9);
+        let elements = symmetry.elements();
+        let SymmetryElement::Axis { direction, .. } = elements[0] else {
+            panic!("{:?}", elements[0]);
+        };
+        assert!((direction[1] - 1.0).abs() < 1e-6, "{:?}", direction);
+        for element in &elements[1..] {
+            let SymmetryElement::Mirror { normal } = element else {
+                panic!("{:?}", element);
+            };
+            assert!(normal[1].abs() < 1e-6, "{:?}", normal);
+        }
+        let json = serde_json::to_string(&SymmetryElement::Mirror {
+            normal: [0.0, 0.0, 1.0],
+        })
+        .unwrap();
+        assert_eq!(json, r#"{"kind":"mirror","normal":[0.0,0.0,1.0]}"#);
+    }
+
+    /// Structures from SMILES are only nearly symmetric, the products of their operations must not accumulate the
+    /// errors into rotations by small angles.
+    #[test]
+    fn near_symmetric_structures() {
+        for (smiles, point_group) in [("C=C", "D2h"), ("ClC(Cl)=O", "C2v"), ("C", "Td"), ("c1ccccc1", "D6h")] {
+            let data = smiles::build(smiles).unwrap();
+            for tolerance in [0.05, 0.1, 0.3] {
+                let symmetry = Symmetry::from_coordinates(&data, tolerance).unwrap();
+                assert_eq!(symmetry.point_group(), point_group, "{} within {}", smiles, tolerance);
+                for m in &symmetry.operations {
+                    assert!(
+                        operation_order(m) <= 2 * MAX_AXIS_ORDER,
+                        "{} within {}",
+                        smiles,
+                        tolerance
+                    );
+                }
+            }
+        }
+    }
+
+    /// Exact ethylene with its atoms displaced by up to 0.03 Å still has the D2h operations, mapping every atom
+    /// onto its images.
+    #[test]
+    fn displaced_ethylene() {
+        let positions = [
+            [0.0, 0.0, 0.667],
+            [0.0, 0.0, -0.667],
+            [0.0, 0.923, 1.238],
+            [0.0, -0.923, 1.238],
+            [0.0, 0.923, -1.238],
+            [0.0, -0.923, -1.238],
+        ];
+        let shifts = [0.02, -0.03, 0.01, 0.03, -0.02, -0.01];
+        let positions: Vec<Vec3<f64>> = positions
+            .iter()
+            .zip(shifts)
+            .map(|(p, shift)| Vec3::new(p[0] + shift, p[1] - shift, p[2] + shift / 2.0))
+            .collect();
+        let symmetry = Symmetry::detect(&positions, &[6, 6, 1, 1, 1, 1], 0.1).unwrap();
+        assert_eq!(symmetry.point_group(), "D2h");
+        assert_eq!(symmetry.operations.len(), 8);
+        assert_eq!(symmetry.equivalent_atoms(2), [2, 3, 4, 5]);
+    }
+}
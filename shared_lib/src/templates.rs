@@ -0,0 +1,878 @@
+// Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
+// Licensed under the MIT License
+
+use serde::Serialize;
+
+use crate::connectivity::{self, DEFAULT_BOND_TOLERANCE};
+use crate::geometry::{self, cross, dot, normalize, perpendicular, sub};
+use crate::periodic_table::get_element_by_number;
+use crate::types::{AtomicCoordinates, Connection};
+
+// Turns of an attached template about the new bond tried to keep it away from the structure
+const TWIST_STEPS: usize = 12;
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Category {
+    AminoAcid,
+    Ligand,
+    Solvent,
+    FunctionalGroup,
+}
+
+impl Category {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Category::AminoAcid => "amino_acid",
+            Category::Ligand => "ligand",
+            Category::Solvent => "solvent",
+            Category::FunctionalGroup => "functional_group",
+        }
+    }
+}
+
+/// Atom of a template in internal coordinates, a line of a Z-matrix: bonded to the atom `refs[0]` at the
+/// distance `values[0]` in Angstroms, with the angle to `refs[1]` and the dihedral to `refs[2]` in degrees.
+/// References are zero-based indices of earlier atoms, the second and the third atoms use only the first one
+/// and two of them. `order` is the order of the bond to `refs[0]`.
+#[derive(Clone, Copy)]
+struct ZAtom {
+    number: i32,
+    refs: [usize; 3],
+    order: u8,
+    values: [f64; 3],
+}
+
+const fn z(number: i32, refs: [usize; 3], order: u8, values: [f64; 3]) -> ZAtom {
+    ZAtom {
+        number,
+        refs,
+        order,
+        values,
+    }
+}
+
+/// How a template is bonded to an atom of a structure.
+#[derive(Clone, Copy)]
+enum Attachment {
+    /// Functional group: the atom bonded to the structure and the hydrogen of the parent hydride it stands for.
+    Group { anchor: usize, leaving: usize },
+    /// Ligand: the donor atom bonded to the structure along its lone pair, opposite to its bonds in the ligand.
+    Donor(usize),
+}
+
+/// Molecule or fragment at a standard geometry, built from a Z-matrix.
+pub struct Template {
+    pub name: &'static str,
+    pub category: Category,
+    atoms: &'static [ZAtom],
+    /// Bonds closing rings as (atom, atom, order), besides the bond of every atom to its first reference.
+    rings: &'static [(usize, usize, u8)],
+    /// How the template is bonded to an atom of a structure, if it can be attached.
+    attachment: Option<Attachment>,
+}
+
+/// Description of a template for listings.
+#[derive(Serialize)]
+pub struct TemplateInfo {
+    pub name: &'static str,
+    pub category: Category,
+    pub num_atoms: usize,
+    pub attachable: bool,
+}
+
+const H: i32 = 1;
+const C: i32 = 6;
+const N: i32 = 7;
+const O: i32 = 8;
+const F: i32 = 9;
+const P: i32 = 15;
+const S: i32 = 16;
+const CL: i32 = 17;
+const BR: i32 = 35;
+
+const ORIGIN: [usize; 3] = [0, 0, 0];
+const TETRAHEDRAL: f64 = 109.47;
+const AROMATIC: u8 = Connection::AROMATIC;
+
+// Backbone of neutral L-amino acids: N, CA, C, O, OXT, HXT and two hydrogens on N, followed by CB (or the second
+// hydrogen of glycine) and HA
+const BACKBONE: [ZAtom; 8] = [
+    z(N, ORIGIN, 0, [0.0; 3]),
+    z(C, [0, 0, 0], 1, [1.46, 0.0, 0.0]),
+    z(C, [1, 0, 0], 1, [1.52, 110.0, 0.0]),
+    z(O, [2, 1, 0], 2, [1.21, 124.0, 0.0]),
+    z(O, [2, 1, 0], 1, [1.34, 112.0, 180.0]),
+    z(H, [4, 2, 3], 1, [0.97, 106.0, 0.0]),
+    z(H, [0, 1, 2], 1, [1.01, TETRAHEDRAL, 60.0]),
+    z(H, [0, 1, 2], 1, [1.01, TETRAHEDRAL, -60.0]),
+];
+const HA: ZAtom = z(H, [1, 0, 2], 1, [1.09, TETRAHEDRAL, 118.0]);
+const fn beta(number: i32, distance: f64) -> ZAtom {
+    z(number, [1, 0, 2], 1, [distance, 110.5, -122.0])
+}
+
+const fn concat<const A: usize, const B: usize, const R: usize>(a: [ZAtom; A], b: [ZAtom; B]) -> [ZAtom; R] {
+    assert!(A + B == R);
+    let mut result = [a[0]; R];
+    let mut i = 0;
+    while i < R {
+        result[i] = if i < A { a[i] } else { b[i - A] };
+        i += 1;
+    }
+    result
+}
+
+const GLYCINE: [ZAtom; 10] = concat(BACKBONE, [beta(H, 1.09), HA]);
+const ALANINE: [ZAtom; 13] = concat(
+    BACKBONE,
+    [
+        beta(C, 1.53),
+        HA,
+        z(H, [8, 1, 0], 1, [1.09, TETRAHEDRAL, 180.0]),
+        z(H, [8, 1, 0], 1, [1.09, TETRAHEDRAL, 60.0]),
+        z(H, [8, 1, 0], 1, [1.09, TETRAHEDRAL, -60.0]),
+    ],
+);
+const SERINE: [ZAtom; 14] = concat(
+    BACKBONE,
+    [
+        beta(C, 1.53),
+        HA,
+        z(O, [8, 1, 0], 1, [1.42, 111.0, 60.0]),
+        z(H, [10, 8, 1], 1, [0.96, 109.0, 180.0]),
+        z(H, [8, 1, 10], 1, [1.09, TETRAHEDRAL, 120.0]),
+        z(H, [8, 1, 10], 1, [1.09, TETRAHEDRAL, -120.0]),
+    ],
+);
+const CYSTEINE: [ZAtom; 14] = concat(
+    BACKBONE,
+    [
+        beta(C, 1.53),
+        HA,
+        z(S, [8, 1, 0], 1, [1.81, 114.0, 60.0]),
+        z(H, [10, 8, 1], 1, [1.34, 96.0, 180.0]),
+        z(H, [8, 1, 10], 1, [1.09, TETRAHEDRAL, 120.0]),
+        z(H, [8, 1, 10], 1, [1.09, TETRAHEDRAL, -120.0]),
+    ],
+);
+const VALINE: [ZAtom; 19] = concat(
+    BACKBONE,
+    [
+        beta(C, 1.54),
+        HA,
+        z(C, [8, 1, 0], 1, [1.53, 110.5, 180.0]),
+        z(C, [8, 1, 10], 1, [1.53, 110.5, 120.0]),
+        z(H, [8, 1, 10], 1, [1.09, TETRAHEDRAL, -120.0]),
+        z(H, [10, 8, 1], 1, [1.09, TETRAHEDRAL, 180.0]),
+        z(H, [10, 8, 1], 1, [1.09, TETRAHEDRAL, 60.0]),
+        z(H, [10, 8, 1], 1, [1.09, TETRAHEDRAL, -60.0]),
+        z(H, [11, 8, 1], 1, [1.09, TETRAHEDRAL, 180.0]),
+        z(H, [11, 8, 1], 1, [1.09, TETRAHEDRAL, 60.0]),
+        z(H, [11, 8, 1], 1, [1.09, TETRAHEDRAL, -60.0]),
+    ],
+);
+
+const AMMONIA: [ZAtom; 4] = [
+    z(N, ORIGIN, 0, [0.0; 3]),
+    z(H, [0, 0, 0], 1, [1.012, 0.0, 0.0]),
+    z(H, [0, 1, 0], 1, [1.012, 106.7, 0.0]),
+    z(H, [0, 1, 2], 1, [1.012, 106.7, 113.8]),
+];
+const CARBON_MONOXIDE: [ZAtom; 2] = [z(C, ORIGIN, 0, [0.0; 3]), z(O, [0, 0, 0], 3, [1.128, 0.0, 0.0])];
+const PHOSPHINE: [ZAtom; 4] = [
+    z(P, ORIGIN, 0, [0.0; 3]),
+    z(H, [0, 0, 0], 1, [1.42, 0.0, 0.0]),
+    z(H, [0, 1, 0], 1, [1.42, 93.3, 0.0]),
+    z(H, [0, 1, 2], 1, [1.42, 93.3, 93.5]),
+];
+// Regular hexagons: the ring closes for any bond length at 120° angles
+const PYRIDINE: [ZAtom; 11] = [
+    z(N, ORIGIN, 0, [0.0; 3]),
+    z(C, [0, 0, 0], AROMATIC, [1.38, 0.0, 0.0]),
+    z(C, [1, 0, 0], AROMATIC, [1.38, 120.0, 0.0]),
+    z(C, [2, 1, 0], AROMATIC, [1.38, 120.0, 0.0]),
+    z(C, [3, 2, 1], AROMATIC, [1.38, 120.0, 0.0]),
+    z(C, [4, 3, 2], AROMATIC, [1.38, 120.0, 0.0]),
+    z(H, [1, 2, 3], 1, [1.08, 120.0, 180.0]),
+    z(H, [2, 3, 4], 1, [1.08, 120.0, 180.0]),
+    z(H, [3, 4, 5], 1, [1.08, 120.0, 180.0]),
+    z(H, [4, 5, 0], 1, [1.08, 120.0, 180.0]),
+    z(H, [5, 0, 1], 1, [1.08, 120.0, 180.0]),
+];
+
+const WATER: [ZAtom; 3] = [
+    z(O, ORIGIN, 0, [0.0; 3]),
+    z(H, [0, 0, 0], 1, [0.9572, 0.0, 0.0]),
+    z(H, [0, 1, 0], 1, [0.9572, 104.52, 0.0]),
+];
+const METHANOL: [ZAtom; 6] = [
+    z(C, ORIGIN, 0, [0.0; 3]),
+    z(O, [0, 0, 0], 1, [1.427, 0.0, 0.0]),
+    z(H, [1, 0, 0], 1, [0.956, 108.9, 0.0]),
+    z(H, [0, 1, 2], 1, [1.093, TETRAHEDRAL, 180.0]),
+    z(H, [0, 1, 2], 1, [1.093, TETRAHEDRAL, 60.0]),
+    z(H, [0, 1, 2], 1, [1.093, TETRAHEDRAL, -60.0]),
+];
+const ETHANOL: [ZAtom; 9] = [
+    z(C, ORIGIN, 0, [0.0; 3]),
+    z(C, [0, 0, 0], 1, [1.52, 0.0, 0.0]),
+    z(O, [1, 0, 0], 1, [1.43, 108.0, 0.0]),
+    z(H, [2, 1, 0], 1, [0.96, 108.5, 180.0]),
+    z(H, [1, 0, 2], 1, [1.09, 110.0, 120.0]),
+    z(H, [1, 0, 2], 1, [1.09, 110.0, -120.0]),
+    z(H, [0, 1, 2], 1, [1.09, 110.0, 180.0]),
+    z(H, [0, 1, 2], 1, [1.09, 110.0, 60.0]),
+    z(H, [0, 1, 2], 1, [1.09, 110.0, -60.0]),
+];
+const ACETONE: [ZAtom; 10] = [
+    z(C, ORIGIN, 0, [0.0; 3]),
+    z(O, [0, 0, 0], 2, [1.21, 0.0, 0.0]),
+    z(C, [0, 1, 0], 1, [1.51, 121.7, 0.0]),
+    z(C, [0, 1, 2], 1, [1.51, 121.7, 180.0]),
+    z(H, [2, 0, 1], 1, [1.09, 110.0, 0.0]),
+    z(H, [2, 0, 1], 1, [1.09, 110.0, 120.0]),
+    z(H, [2, 0, 1], 1, [1.09, 110.0, -120.0]),
+    z(H, [3, 0, 1], 1, [1.09, 110.0, 0.0]),
+    z(H, [3, 0, 1], 1, [1.09, 110.0, 120.0]),
+    z(H, [3, 0, 1], 1, [1.09, 110.0, -120.0]),
+];
+const ACETONITRILE: [ZAtom; 6] = [
+    z(C, ORIGIN, 0, [0.0; 3]),
+    z(C, [0, 0, 0], 1, [1.46, 0.0, 0.0]),
+    z(H, [0, 1, 0], 1, [1.09, TETRAHEDRAL, 0.0]),
+    z(H, [0, 1, 2], 1, [1.09, TETRAHEDRAL, 120.0]),
+    z(H, [0, 1, 2], 1, [1.09, TETRAHEDRAL, -120.0]),
+    z(N, [1, 0, 2], 3, [1.16, 180.0, 0.0]),
+];
+// The C–S–C angle is 97.4° with O–S–C angles of 106.7°
+const DIMETHYL_SULFOXIDE: [ZAtom; 10] = [
+    z(S, ORIGIN, 0, [0.0; 3]),
+    z(O, [0, 0, 0], 2, [1.49, 0.0, 0.0]),
+    z(C, [0, 1, 0], 1, [1.80, 106.7, 0.0]),
+    z(C, [0, 1, 2], 1, [1.80, 106.7, 103.3]),
+    z(H, [2, 0, 1], 1, [1.09, TETRAHEDRAL, 180.0]),
+    z(H, [2, 0, 1], 1, [1.09, TETRAHEDRAL, 60.0]),
+    z(H, [2, 0, 1], 1, [1.09, TETRAHEDRAL, -60.0]),
+    z(H, [3, 0, 1], 1, [1.09, TETRAHEDRAL, 180.0]),
+    z(H, [3, 0, 1], 1, [1.09, TETRAHEDRAL, 60.0]),
+    z(H, [3, 0, 1], 1, [1.09, TETRAHEDRAL, -60.0]),
+];
+const CHLOROFORM: [ZAtom; 5] = [
+    z(C, ORIGIN, 0, [0.0; 3]),
+    z(H, [0, 0, 0], 1, [1.07, 0.0, 0.0]),
+    z(CL, [0, 1, 0], 1, [1.76, 108.0, 0.0]),
+    z(CL, [0, 1, 2], 1, [1.76, 108.0, 120.0]),
+    z(CL, [0, 1, 2], 1, [1.76, 108.0, -120.0]),
+];
+const DICHLOROMETHANE: [ZAtom; 5] = [
+    z(C, ORIGIN, 0, [0.0; 3]),
+    z(CL, [0, 0, 0], 1, [1.77, 0.0, 0.0]),
+    z(CL, [0, 1, 0], 1, [1.77, 112.0, 0.0]),
+    z(H, [0, 1, 2], 1, [1.09, 108.5, 121.0]),
+    z(H, [0, 1, 2], 1, [1.09, 108.5, -121.0]),
+];
+const BENZENE: [ZAtom; 12] = [
+    z(C, ORIGIN, 0, [0.0; 3]),
+    z(C, [0, 0, 0], AROMATIC, [1.39, 0.0, 0.0]),
+    z(C, [1, 0, 0], AROMATIC, [1.39, 120.0, 0.0]),
+    z(C, [2, 1, 0], AROMATIC, [1.39, 120.0, 0.0]),
+    z(C, [3, 2, 1], AROMATIC, [1.39, 120.0, 0.0]),
+    z(C, [4, 3, 2], AROMATIC, [1.39, 120.0, 0.0]),
+    z(H, [0, 1, 2], 1, [1.08, 120.0, 180.0]),
+    z(H, [1, 2, 3], 1, [1.08, 120.0, 180.0]),
+    z(H, [2, 3, 4], 1, [1.08, 120.0, 180.0]),
+    z(H, [3, 4, 5], 1, [1.08, 120.0, 180.0]),
+    z(H, [4, 5, 0], 1, [1.08, 120.0, 180.0]),
+    z(H, [5, 0, 1], 1, [1.08, 120.0, 180.0]),
+];
+
+// Functional groups are the parent hydrides R–H: the hydrogen replaced by the structure comes first, followed
+// by the atom bonded to the structure
+const METHYL: [ZAtom; 5] = [
+    z(H, ORIGIN, 0, [0.0; 3]),
+    z(C, [0, 0, 0], 1, [1.09, 0.0, 0.0]),
+    z(H, [1, 0, 0], 1, [1.09, TETRAHEDRAL, 0.0]),
+    z(H, [1, 0, 2], 1, [1.09, TETRAHEDRAL, 120.0]),
+    z(H, [1, 0, 2], 1, [1.09, TETRAHEDRAL, -120.0]),
+];
+const ETHYL: [ZAtom; 8] = [
+    z(H, ORIGIN, 0, [0.0; 3]),
+    z(C, [0, 0, 0], 1, [1.09, 0.0, 0.0]),
+    z(C, [1, 0, 0], 1, [1.53, TETRAHEDRAL, 0.0]),
+    z(H, [1, 2, 0], 1, [1.09, TETRAHEDRAL, 120.0]),
+    z(H, [1, 2, 0], 1, [1.09, TETRAHEDRAL, -120.0]),
+    z(H, [2, 1, 0], 1, [1.09, TETRAHEDRAL, 180.0]),
+    z(H, [2, 1, 0], 1, [1.09, TETRAHEDRAL, 60.0]),
+    z(H, [2, 1, 0], 1, [1.09, TETRAHEDRAL, -60.0]),
+];
+const TRIFLUOROMETHYL: [ZAtom; 5] = [
+    z(H, ORIGIN, 0, [0.0; 3]),
+    z(C, [0, 0, 0], 1, [1.09, 0.0, 0.0]),
+    z(F, [1, 0, 0], 1, [1.33, TETRAHEDRAL, 0.0]),
+    z(F, [1, 0, 2], 1, [1.33, TETRAHEDRAL, 120.0]),
+    z(F, [1, 0, 2], 1, [1.33, TETRAHEDRAL, -120.0]),
+];
+const PHENYL: [ZAtom; 12] = [
+    z(H, ORIGIN, 0, [0.0; 3]),
+    z(C, [0, 0, 0], 1, [1.08, 0.0, 0.0]),
+    z(C, [1, 0, 0], AROMATIC, [1.39, 120.0, 0.0]),
+    z(C, [2, 1, 0], AROMATIC, [1.39, 120.0, 180.0]),
+    z(C, [3, 2, 1], AROMATIC, [1.39, 120.0, 0.0]),
+    z(C, [4, 3, 2], AROMATIC, [1.39, 120.0, 0.0]),
+    z(C, [5, 4, 3], AROMATIC, [1.39, 120.0, 0.0]),
+    z(H, [2, 3, 4], 1, [1.08, 120.0, 180.0]),
+    z(H, [3, 4, 5], 1, [1.08, 120.0, 180.0]),
+    z(H, [4, 5, 6], 1, [1.08, 120.0, 180.0]),
+    z(H, [5, 6, 1], 1, [1.08, 120.0, 180.0]),
+    z(H, [6, 1, 2], 1, [1.08, 120.0, 180.0]),
+];
+const HYDROXYL: [ZAtom; 3] = [
+    z(H, ORIGIN, 0, [0.0; 3]),
+    z(O, [0, 0, 0], 1, [0.96, 0.0, 0.0]),
+    z(H, [1, 0, 0], 1, [0.96, 104.5, 0.0]),
+];
+const METHOXY: [ZAtom; 6] = [
+    z(H, ORIGIN, 0, [0.0; 3]),
+    z(O, [0, 0, 0], 1, [0.96, 0.0, 0.0]),
+    z(C, [1, 0, 0], 1, [1.43, 108.9, 0.0]),
+    z(H, [2, 1, 0], 1, [1.09, TETRAHEDRAL, 180.0]),
+    z(H, [2, 1, 0], 1, [1.09, TETRAHEDRAL, 60.0]),
+    z(H, [2, 1, 0], 1, [1.09, TETRAHEDRAL, -60.0]),
+];
+const AMINO: [ZAtom; 4] = [
+    z(H, ORIGIN, 0, [0.0; 3]),
+    z(N, [0, 0, 0], 1, [1.01, 0.0, 0.0]),
+    z(H, [1, 0, 0], 1, [1.01, 106.7, 0.0]),
+    z(H, [1, 0, 2], 1, [1.01, 106.7, 113.8]),
+];
+const NITRO: [ZAtom; 4] = [
+    z(H, ORIGIN, 0, [0.0; 3]),
+    z(N, [0, 0, 0], 1, [1.02, 0.0, 0.0]),
+    z(O, [1, 0, 0], 2, [1.22, 117.5, 0.0]),
+    z(O, [1, 0, 2], 1, [1.22, 117.5, 180.0]),
+];
+const CYANO: [ZAtom; 3] = [
+    z(H, ORIGIN, 0, [0.0; 3]),
+    z(C, [0, 0, 0], 1, [1.066, 0.0, 0.0]),
+    z(N, [1, 0, 0], 3, [1.156, 180.0, 0.0]),
+];
+const FORMYL: [ZAtom; 4] = [
+    z(H, ORIGIN, 0, [0.0; 3]),
+    z(C, [0, 0, 0], 1, [1.11, 0.0, 0.0]),
+    z(O, [1, 0, 0], 2, [1.21, 121.0, 0.0]),
+    z(H, [1, 0, 2], 1, [1.11, 117.0, 180.0]),
+];
+const ACETYL: [ZAtom; 7] = [
+    z(H, ORIGIN, 0, [0.0; 3]),
+    z(C, [0, 0, 0], 1, [1.11, 0.0, 0.0]),
+    z(O, [1, 0, 0], 2, [1.21, 120.0, 0.0]),
+    z(C, [1, 0, 2], 1, [1.50, 115.0, 180.0]),
+    z(H, [3, 1, 2], 1, [1.09, TETRAHEDRAL, 0.0]),
+    z(H, [3, 1, 2], 1, [1.09, TETRAHEDRAL, 120.0]),
+    z(H, [3, 1, 2], 1, [1.09, TETRAHEDRAL, -120.0]),
+];
+// The hydroxyl hydrogen is syn to the carbonyl oxygen
+const CARBOXYL: [ZAtom; 5] = [
+    z(H, ORIGIN, 0, [0.0; 3]),
+    z(C, [0, 0, 0], 1, [1.10, 0.0, 0.0]),
+    z(O, [1, 0, 0], 2, [1.21, 124.0, 0.0]),
+    z(O, [1, 0, 2], 1, [1.34, 111.0, 180.0]),
+    z(H, [3, 1, 2], 1, [0.97, 106.0, 0.0]),
+];
+const AMIDE: [ZAtom; 6] = [
+    z(H, ORIGIN, 0, [0.0; 3]),
+    z(C, [0, 0, 0], 1, [1.10, 0.0, 0.0]),
+    z(O, [1, 0, 0], 2, [1.22, 122.5, 0.0]),
+    z(N, [1, 0, 2], 1, [1.35, 112.7, 180.0]),
+    z(H, [3, 1, 2], 1, [1.01, 119.0, 0.0]),
+    z(H, [3, 1, 2], 1, [1.01, 121.0, 180.0]),
+];
+const THIOL: [ZAtom; 3] = [
+    z(H, ORIGIN, 0, [0.0; 3]),
+    z(S, [0, 0, 0], 1, [1.34, 0.0, 0.0]),
+    z(H, [1, 0, 0], 1, [1.34, 92.1, 0.0]),
+];
+const FLUORO: [ZAtom; 2] = [z(H, ORIGIN, 0, [0.0; 3]), z(F, [0, 0, 0], 1, [0.92, 0.0, 0.0])];
+const CHLORO: [ZAtom; 2] = [z(H, ORIGIN, 0, [0.0; 3]), z(CL, [0, 0, 0], 1, [1.27, 0.0, 0.0])];
+const BROMO: [ZAtom; 2] = [z(H, ORIGIN, 0, [0.0; 3]), z(BR, [0, 0, 0], 1, [1.41, 0.0, 0.0])];
+
+const fn molecule(name: &'static str, category: Category, atoms: &'static [ZAtom]) -> Template {
+    Template {
+        name,
+        category,
+        atoms,
+        rings: &[],
+        attachment: None,
+    }
+}
+
+const fn group(name: &'static str, atoms: &'static [ZAtom]) -> Template {
+    Template {
+        name,
+        category: Category::FunctionalGroup,
+        atoms,
+        rings: &[],
+        attachment: Some(Attachment::Group { anchor: 1, leaving: 0 }),
+    }
+}
+
+const fn ligand(name: &'static str, category: Category, atoms: &'static [ZAtom], donor: usize) -> Template {
+    Template {
+        attachment: Some(Attachment::Donor(donor)),
+        ..molecule(name, category, atoms)
+    }
+}
+
+const TEMPLATES: &[Template] = &[
+    molecule("glycine", Category::AminoAcid, &GLYCINE),
+    molecule("alanine", Category::AminoAcid, &ALANINE),
+    molecule("serine", Category::AminoAcid, &SERINE),
+    molecule("cysteine", Category::AminoAcid, &CYSTEINE),
+    molecule("valine", Category::AminoAcid, &VALINE),
+    ligand("ammonia", Category::Ligand, &AMMONIA, 0),
+    ligand("carbon_monoxide", Category::Ligand, &CARBON_MONOXIDE, 0),
+    ligand("phosphine", Category::Ligand, &PHOSPHINE, 0),
+    Template {
+        rings: &[(5, 0, AROMATIC)],
+        ..ligand("pyridine", Category::Ligand, &PYRIDINE, 0)
+    },
+    molecule("water", Category::Solvent, &WATER),
+    molecule("methanol", Category::Solvent, &METHANOL),
+    molecule("ethanol", Category::Solvent, &ETHANOL),
+    molecule("acetone", Category::Solvent, &ACETONE),
+    ligand("acetonitrile", Category::Solvent, &ACETONITRILE, 5),
+    molecule("dimethyl_sulfoxide", Category::Solvent, &DIMETHYL_SULFOXIDE),
+    molecule("chloroform", Category::Solvent, &CHLOROFORM),
+    molecule("dichloromethane", Category::Solvent, &DICHLOROMETHANE),
+    Template {
+        rings: &[(5, 0, AROMATIC)],
+        ..molecule("benzene", Category::Solvent, &BENZENE)
+    },
+    group("methyl", &METHYL),
+    group("ethyl", &ETHYL),
+    group("trifluoromethyl", &TRIFLUOROMETHYL),
+    Template {
+        rings: &[(6, 1, AROMATIC)],
+        ..group("phenyl", &PHENYL)
+    },
+    group("hydroxyl", &HYDROXYL),
+    group("methoxy", &METHOXY),
+    group("amino", &AMINO),
+    group("nitro", &NITRO),
+    group("cyano", &CYANO),
+    group("formyl", &FORMYL),
+    group("acetyl", &ACETYL),
+    group("carboxyl", &CARBOXYL),
+    group("amide", &AMIDE),
+    group("thiol", &THIOL),
+    group("fluoro", &FLUORO),
+    group("chloro", &CHLORO),
+    group("bromo", &BROMO),
+];
+
+/// All templates of the library by categories.
+pub fn templates() -> &'static [Template] {
+    TEMPLATES
+}
+
+/// Template by its name, case-insensitive.
+pub fn find_template(name: &str) -> Result<&'static Template, String> {
+    TEMPLATES
+        .iter()
+        .find(|template| template.name.eq_ignore_ascii_case(name.trim()))
+        .ok_or_else(|| format!("Unknown template: {}", name))
+}
+
+impl Template {
+    pub fn num_atoms(&self) -> usize {
+        self.atoms.len()
+    }
+
+    /// Whether the template can be attached to an atom of a structure, see `attach`.
+    pub fn is_attachable(&self) -> bool {
+        self.attachment.is_some()
+    }
+
+    /// Number of atoms that `attach` adds to a structure, all atoms of a ligand and the atoms of a functional group
+    /// but the hydrogen of its parent hydride.
+    pub fn num_attached_atoms(&self) -> usize {
+        match self.attachment {
+            Some(Attachment::Group { .. }) => self.num_atoms() - 1,
+            Some(Attachment::Donor(_)) => self.num_atoms(),
+            None => 0,
+        }
+    }
+
+    pub fn info(&self) -> TemplateInfo {
+        TemplateInfo {
+            name: self.name,
+            category: self.category,
+            num_atoms: self.num_atoms(),
+            attachable: self.is_attachable(),
+        }
+    }
+
+    /// The template as a structure with its bonds, the first atom at the origin and the second one on the X axis.
+    pub fn coordinates(&self) -> AtomicCoordinates {
+        let positions = self.positions();
+        AtomicCoordinates {
+            atomic_num: self.atoms.iter().map(|atom| atom.number).collect(),
+            x: positions.iter().map(|p| p[0]).collect(),
+            y: positions.iter().map(|p| p[1]).collect(),
+            z: positions.iter().map(|p| p[2]).collect(),
+            connectivity: Some(self.bonds()),
+            charges: None,
+            chains: None,
+            cell: None,
+            isotopes: None,
+        }
+    }
+
+    fn positions(&self) -> Vec<[f64; 3]> {
+        let mut result: Vec<[f64; 3]> = Vec::with_capacity(self.atoms.len());
+        for (i, atom) in self.atoms.iter().enumerate() {
+            let [bond, angle, dihedral] = atom.refs;
+            let [distance, angle_value, dihedral_value] = atom.values;
+            let position = match i {
+                0 => [0.0; 3],
+                1 => [distance, 0.0, 0.0],
+                _ => {
+                    let (b, c) = (result[angle], result[bond]);
+                    // The third atom lies in the XY plane
+                    let a = if i == 2 {
+                        [b[0], b[1] + 1.0, b[2]]
+                    } else {
+                        result[dihedral]
+                    };
+                    geometry::place_atom(a, b, c, distance, angle_value, dihedral_value)
+                }
+            };
+            result.push(position);
+        }
+        result
+    }
+
+    fn bonds(&self) -> Vec<Connection> {
+        let chain = self
+            .atoms
+            .iter()
+            .enumerate()
+            .skip(1)
+            .map(|(i, atom)| (atom.refs[0], i, atom.order));
+        chain
+            .chain(self.rings.iter().copied())
+            .map(|(atom_1, atom_2, order)| Connection { atom_1, atom_2, order })
+            .collect()
+    }
+}
+
+fn position(data: &AtomicCoordinates, i: usize) -> [f64; 3] {
+    [data.x[i], data.y[i], data.z[i]]
+}
+
+fn add(u: [f64; 3], v: [f64; 3]) -> [f64; 3] {
+    [u[0] + v[0], u[1] + v[1], u[2] + v[2]]
+}
+
+fn scale(u: [f64; 3], factor: f64) -> [f64; 3] {
+    u.map(|x| x * factor)
+}
+
+/// Rotation by the angle in radians about the unit axis (Rodrigues' formula).
+fn axis_rotation(axis: [f64; 3], angle: f64) -> [[f64; 3]; 3] {
+    let (sin, cos) = angle.sin_cos();
+    let [x, y, z] = axis;
+    let skew = [[0.0, -z, y], [z, 0.0, -x], [-y, x, 0.0]];
+    std::array::from_fn(|i| {
+        std::array::from_fn(|j| {
+            let diagonal = if i == j { cos } else { 0.0 };
+            diagonal + sin * skew[i][j] + (1.0 - cos) * axis[i] * axis[j]
+        })
+    })
+}
+
+/// Rotation turning the unit vector `from` to the unit vector `to`.
+fn alignment(from: [f64; 3], to: [f64; 3]) -> [[f64; 3]; 3] {
+    let axis = cross(from, to);
+    let cos = dot(from, to).clamp(-1.0, 1.0);
+    if dot(axis, axis) > 1e-12 {
+        axis_rotation(normalize(axis), cos.acos())
+    } else if cos > 0.0 {
+        axis_rotation([1.0, 0.0, 0.0], 0.0)
+    } else {
+        axis_rotation(perpendicular(from), std::f64::consts::PI)
+    }
+}
+
+fn rotate(matrix: &[[f64; 3]; 3], v: [f64; 3]) -> [f64; 3] {
+    matrix.map(|row| dot(row, v))
+}
+
+fn covalent_radius(number: i32) -> Result<f64, String> {
+    get_element_by_number(number)
+        .filter(|_| number > 0)
+        .map(|element| element.covalent_radius)
+        .ok_or_else(|| format!("No covalent radius of atomic number {}", number))
+}
+
+/// Attaches the template to the atom `index` (zero-based) of the structure at a standard geometry. A terminal
+/// hydrogen or dummy atom is replaced by the template bonded to its neighbor, any other atom is bonded to the
+/// template directly, which then points away from the neighbors of the atom. A functional group takes the place
+/// of the hydrogen of its parent hydride, a ligand is bonded through its donor atom along the lone pair. The bond
+/// length is the sum of covalent radii and the template is turned about the bond to keep it as far as possible
+/// from the other atoms. Atoms of the template follow the atoms of the structure, indices after a replaced atom
+/// decrease by one.
+pub fn attach(data: &AtomicCoordinates, index: usize, template: &Template) -> Result<AtomicCoordinates, String> {
+    let num_atoms = data.atomic_num.len();
+    if data.x.len() != num_atoms || data.y.len() != num_atoms || data.z.len() != num_atoms {
+        return Err("Numbers of atoms and coordinates differ".to_string());
+    }
+    if index >= num_atoms {
+        return Err(format!("Atom index {} is out of range", index + 1));
+    }
+    let attachment = template
+        .attachment
+        .ok_or_else(|| format!("Template {} cannot be attached to an atom", template.name))?;
+
+    // Bonded atoms of the selected one at the positions of the bonded images
+    let translation = |image: [i32; 3]| {
+        data.cell
+            .map_or([0.0; 3], |cell| cell.to_cartesian(image.map(f64::from)))
+    };
+    let around: Vec<(usize, [f64; 3])> = connectivity::find_bonds(data, DEFAULT_BOND_TOLERANCE)
+        .iter()
+        .filter_map(|bond| {
+            if bond.atom_index_1 == index {
+                Some((
+                    bond.atom_index_2,
+                    add(position(data, bond.atom_index_2), translation(bond.image)),
+                ))
+            } else if bond.atom_index_2 == index {
+                let image = bond.image.map(|n| -n);
+                Some((
+                    bond.atom_index_1,
+                    add(position(data, bond.atom_index_1), translation(image)),
+                ))
+            } else {
+                None
+            }
+        })
+        .collect();
+
+    let selected = position(data, index);
+    let replaced = data.atomic_num[index] <= 1 && around.len() == 1;
+    let (parent, origin, direction) = if replaced {
+        let (parent, origin) = around[0];
+        (parent, origin, normalize(sub(selected, origin)))
+    } else {
+        let sum = around
+            .iter()
+            .fold([0.0; 3], |sum, &(_, p)| add(sum, normalize(sub(p, selected))));
+        let direction = match around.first() {
+            None => [1.0, 0.0, 0.0],
+            Some(&(_, p)) if dot(sum, sum) < 1e-6 => perpendicular(sub(p, selected)),
+            Some(_) => scale(normalize(sum), -1.0),
+        };
+        (index, selected, direction)
+    };
+
+    // Atoms of the template that are attached and the direction from the anchor to the parent atom
+    let positions = template.positions();
+    let (anchor, fragment, outward): (usize, Vec<usize>, [f64; 3]) = match attachment {
+        Attachment::Group { anchor, leaving } => (
+            anchor,
+            (0..template.num_atoms()).filter(|&i| i != leaving).collect(),
+            normalize(sub(positions[leaving], positions[anchor])),
+        ),
+        Attachment::Donor(donor) => {
+            let bonds = template.bonds();
+            let sum = bonds
+                .iter()
+                .filter_map(|c| match (c.atom_1 == donor, c.atom_2 == donor) {
+                    (true, _) => Some(c.atom_2),
+                    (_, true) => Some(c.atom_1),
+                    _ => None,
+                })
+                .fold([0.0; 3], |sum, i| {
+                    add(sum, normalize(sub(positions[i], positions[donor])))
+                });
+            (donor, (0..template.num_atoms()).collect(), scale(normalize(sum), -1.0))
+        }
+    };
+    let length = covalent_radius(data.atomic_num[parent])? + covalent_radius(template.atoms[anchor].number)?;
+
+    // The template around its anchor with the bond to the structure pointing back to the parent atom
+    let to_parent = alignment(outward, scale(direction, -1.0));
+    let local: Vec<[f64; 3]> = fragment
+        .iter()
+        .map(|&i| rotate(&to_parent, sub(positions[i], positions[anchor])))
+        .collect();
+    let center = add(origin, scale(direction, length));
+    let kept: Vec<usize> = (0..num_atoms).filter(|&i| !(replaced && i == index)).collect();
+    let place = |twist: usize| {
+        let rotation = axis_rotation(direction, (twist as f64 / TWIST_STEPS as f64) * std::f64::consts::TAU);
+        local
+            .iter()
+            .map(|&p| add(center, rotate(&rotation, p)))
+            .collect::<Vec<_>>()
+    };
+    let clearance = |placed: &[[f64; 3]]| {
+        placed
+            .iter()
+            .flat_map(|&p| kept.iter().map(move |&i| (p, i)))
+            .map(|(p, i)| {
+                let d = sub(p, position(data, i));
+                dot(d, d)
+            })
+            .fold(f64::INFINITY, f64::min)
+    };
+    // The first of equally good turns, so that the result does not depend on rounding
+    let mut best = (place(0), f64::NEG_INFINITY);
+    for twist in 0..TWIST_STEPS {
+        let placed = place(twist);
+        let score = clearance(&placed);
+        if score > best.1 + 1e-9 {
+            best = (placed, score);
+        }
+    }
+    let placed = best.0;
+
+    let new_index = |i: usize| if replaced && i > index { i - 1 } else { i };
+    let base = kept.len();
+    let fragment_index = |i: usize| base + fragment.iter().position(|&j| j == i).unwrap();
+    let connectivity = data.connectivity.as_ref().map(|connections| {
+        let kept_bonds = connections
+            .iter()
+            .filter(|c| !(replaced && (c.atom_1 == index || c.atom_2 == index)))
+            .map(|c| Connection {
+                atom_1: new_index(c.atom_1),
+                atom_2: new_index(c.atom_2),
+                order: c.order,
+            });
+        let template_bonds = template
+            .bonds()
+            .into_iter()
+            .filter(|c| fragment.contains(&c.atom_1) && fragment.contains(&c.atom_2))
+            .map(|c| Connection {
+                atom_1: fragment_index(c.atom_1),
+                atom_2: fragment_index(c.atom_2),
+                order: c.order,
+            });
+        let new_bond = Connection {
+            atom_1: new_index(parent),
+            atom_2: fragment_index(anchor),
+            order: 1,
+        };
+        kept_bonds
+            .chain(std::iter::once(new_bond))
+            .chain(template_bonds)
+            .collect()
+    });
+
+    let numbers = fragment.iter().map(|&i| template.atoms[i].number);
+    Ok(AtomicCoordinates {
+        atomic_num: kept.iter().map(|&i| data.atomic_num[i]).chain(numbers).collect(),
+        x: kept
+            .iter()
+            .map(|&i| data.x[i])
+            .chain(placed.iter().map(|p| p[0]))
+            .collect(),
+        y: kept
+            .iter()
+            .map(|&i| data.y[i])
+            .chain(placed.iter().map(|p| p[1]))
+            .collect(),
+        z: kept
+            .iter()
+            .map(|&i| data.z[i])
+            .chain(placed.iter().map(|p| p[2]))
+            .collect(),
+        connectivity,
+        charges: data.charges.as_ref().map(|charges| {
+            let kept_charges = kept.iter().map(|&i| charges.get(i).copied().unwrap_or(0.0));
+            kept_charges.chain(fragment.iter().map(|_| 0.0)).collect()
+        }),
+        chains: data.chains.as_ref().map(|chains| {
+            let chain = chains.get(parent).cloned().unwrap_or_default();
+            let kept_chains = kept.iter().map(|&i| chains.get(i).cloned().unwrap_or_default());
+            kept_chains.chain(fragment.iter().map(|_| chain.clone())).collect()
+        }),
+        cell: data.cell,
+        // Atoms of the group have the natural isotopic composition
+        isotopes: data.isotopes.as_ref().map(|isotopes| {
+            let kept_isotopes = kept.iter().map(|&i| isotopes.get(i).copied().unwrap_or(0));
+            kept_isotopes.chain(fragment.iter().map(|_| 0)).collect()
+        }),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_structures::{iron, water};
+
+    /// A hydrogen atom of water is replaced by a methyl group, other atoms and templates cannot be attached.
+    #[test]
+    fn template_attachment() {
+        let methyl = find_template("methyl").unwrap();
+        assert!(attach(&water(), 3, methyl).is_err());
+        assert!(attach(&water(), 0, find_template("water").unwrap()).is_err());
+        let methanol = attach(&water(), 1, methyl).unwrap();
+        assert_eq!(methanol.atomic_num, [8, 1, 6, 1, 1, 1]);
+    }
+
+    /// Ligands bonded through their donor atoms with the lone pair pointing at the metal, a dummy atom bonded to the
+    /// metal is replaced.
+    #[test]
+    fn ligand_attachment() {
+        let iron = iron();
+        let distance = |data: &AtomicCoordinates, i: usize, j: usize| {
+            ((data.x[i] - data.x[j]).powi(2) + (data.y[i] - data.y[j]).powi(2) + (data.z[i] - data.z[j]).powi(2)).sqrt()
+        };
+        let angle = |data: &AtomicCoordinates, i: usize, j: usize, k: usize| {
+            let (a, b, c) = (distance(data, i, j), distance(data, j, k), distance(data, i, k));
+            ((a * a + b * b - c * c) / (2.0 * a * b)).acos().to_degrees()
+        };
+
+        let monoxide = find_template("carbon_monoxide").unwrap();
+        assert_eq!(monoxide.num_attached_atoms(), 2);
+        let carbonyl = attach(&iron, 0, monoxide).unwrap();
+        assert_eq!(carbonyl.atomic_num, [26, 6, 8]);
+        assert!((angle(&carbonyl, 0, 1, 2) - 180.0).abs() < 0.5);
+
+        let pyridine = attach(&iron, 0, find_template("pyridine").unwrap()).unwrap();
+        assert_eq!(pyridine.atomic_num.len(), 12);
+        let nitrogen = pyridine.atomic_num.iter().position(|&number| number == 7).unwrap();
+        let carbons: Vec<usize> = (1..12)
+            .filter(|&i| pyridine.atomic_num[i] == 6 && distance(&pyridine, nitrogen, i) < 1.5)
+            .collect();
+        assert_eq!(carbons.len(), 2);
+        for carbon in carbons {
+            let fe_n_c = angle(&pyridine, 0, nitrogen, carbon);
+            assert!((fe_n_c - 121.0).abs() < 2.0, "{}", fe_n_c);
+        }
+
+        let ammonia = find_template("ammonia").unwrap();
+        let fe_n = distance(&attach(&iron, 0, ammonia).unwrap(), 0, 1);
+        let dummy = AtomicCoordinates {
+            atomic_num: vec![26, 0],
+            x: vec![0.0, 0.0],
+            y: vec![0.0, 0.0],
+            z: vec![0.0, 3.0],
+            connectivity: Some(vec![Connection {
+                atom_1: 0,
+                atom_2: 1,
+                order: 1,
+            }]),
+            charges: None,
+            chains: None,
+            cell: None,
+            isotopes: None,
+        };
+        let ammine = attach(&dummy, 1, ammonia).unwrap();
+        assert_eq!(ammine.atomic_num, [26, 7, 1, 1, 1]);
+        assert!((distance(&ammine, 0, 1) - fe_n).abs() < 1e-6);
+        assert!(ammine.z[1] > 1.5);
+    }
+}
@@ -0,0 +1,77 @@
+// Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
+// Licensed under the MIT License
+
+//! Small structures shared by the unit tests.
+
+use crate::types::{AtomicCoordinates, Connection};
+
+/// Carbon dioxide along the X axis with explicit double bonds.
+pub fn carbon_dioxide() -> AtomicCoordinates {
+    AtomicCoordinates {
+        atomic_num: vec![8, 6, 8],
+        x: vec![-1.16, 0.0, 1.16],
+        y: vec![0.0, 0.0, 0.0],
+        z: vec![0.0, 0.0, 0.0],
+        connectivity: Some(vec![
+            Connection {
+                atom_1: 0,
+                atom_2: 1,
+                order: 2,
+            },
+            Connection {
+                atom_1: 1,
+                atom_2: 2,
+                order: 2,
+            },
+        ]),
+        charges: None,
+        chains: None,
+        cell: None,
+        isotopes: None,
+    }
+}
+
+/// Water in the XY plane with the C2 axis along Y.
+pub fn water() -> AtomicCoordinates {
+    AtomicCoordinates {
+        atomic_num: vec![8, 1, 1],
+        x: vec![0.0, 0.757, -0.757],
+        y: vec![0.0, 0.586, 0.586],
+        z: vec![0.0, 0.0, 0.0],
+        connectivity: None,
+        charges: None,
+        chains: None,
+        cell: None,
+        isotopes: None,
+    }
+}
+
+/// Single iron atom at the origin.
+pub fn iron() -> AtomicCoordinates {
+    AtomicCoordinates {
+        atomic_num: vec![26],
+        x: vec![0.0],
+        y: vec![0.0],
+        z: vec![0.0],
+        connectivity: None,
+        charges: None,
+        chains: None,
+        cell: None,
+        isotopes: None,
+    }
+}
+
+/// Hydrogen peroxide with the H-O-O-H dihedral of 120 degrees, the O-O bond is along the Y axis.
+pub fn hydrogen_peroxide() -> AtomicCoordinates {
+    AtomicCoordinates {
+        atomic_num: vec![1, 8, 8, 1],
+        x: vec![0.9, 0.0, 0.0, -0.45],
+        y: vec![1.0, 0.725, -0.725, -1.0],
+        z: vec![0.0, 0.0, 0.0, 0.779],
+        connectivity: None,
+        charges: None,
+        chains: None,
+        cell: None,
+        isotopes: None,
+    }
+}
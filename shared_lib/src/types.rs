@@ -3,6 +3,9 @@
 
 use serde::{Deserialize, Serialize};
 
+use crate::cell::UnitCell;
+use crate::periodic_table::{element_symbol, get_isotope_mass};
+
 #[derive(Serialize, Deserialize)]
 pub struct Node {
     pub name: String,
@@ -17,14 +20,110 @@ pub struct AtomicCoordinates {
     pub x: Vec<f64>,
     pub y: Vec<f64>,
     pub z: Vec<f64>,
+    /// Bonds given by the file, if absent they are perceived from interatomic distances.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub connectivity: Option<Vec<Connection>>,
+    /// Charges of atoms in elementary charges (formal or partial), if given by the file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub charges: Option<Vec<f64>>,
+    /// Chain (or segment) identifiers of atoms, if given by the file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chains: Option<Vec<String>>,
+    /// Periodic cell of the structure, if given by the file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cell: Option<UnitCell>,
+    /// Mass numbers of isotopically labelled atoms (e.g. 2 for deuterium), 0 for the natural isotopic
+    /// composition, if given by the file or set in the editor.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub isotopes: Option<Vec<u32>>,
+}
+
+impl AtomicCoordinates {
+    /// Mass number of every atom, 0 for the unlabelled ones. Fails if the labels are not given for all atoms or
+    /// a label is no isotope of its element, see `periodic_table::get_isotope_mass`.
+    pub fn mass_numbers(&self) -> Result<Vec<u32>, String> {
+        let num_atoms = self.atomic_num.len();
+        let Some(isotopes) = &self.isotopes else {
+            return Ok(vec![0; num_atoms]);
+        };
+        if isotopes.len() != num_atoms {
+            return Err("Isotopes must be given for all atoms".to_string());
+        }
+        for (i, (&number, &mass_number)) in self.atomic_num.iter().zip(isotopes).enumerate() {
+            if mass_number != 0 && get_isotope_mass(number, mass_number).is_none() {
+                return Err(format!(
+                    "Atom {} ({}) has no isotope {}",
+                    i + 1,
+                    element_symbol(number),
+                    mass_number
+                ));
+            }
+        }
+        Ok(isotopes.clone())
+    }
+}
+
+/// Bond given explicitly by the source file (e.g. MDL bond block or PDB CONECT records).
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct Connection {
+    /// Zero-based index of the first atom.
+    pub atom_1: usize,
+    /// Zero-based index of the second atom.
+    pub atom_2: usize,
+    /// 1 – single, 2 – double, 3 – triple, 4 – aromatic.
+    pub order: u8,
+}
+
+impl Connection {
+    pub const AROMATIC: u8 = 4;
+}
+
+/// Harmonic normal modes of a structure, e.g. from a frequency calculation.
+#[derive(Serialize, Deserialize)]
+pub struct VibrationalModes {
+    /// Wavenumbers of the modes in cm⁻¹, imaginary frequencies are negative.
+    pub frequencies: Vec<f64>,
+    /// Cartesian displacements of the atoms for every mode, `displacements[mode][atom] = [dx, dy, dz]`.
+    pub displacements: Vec<Vec<[f64; 3]>>,
+    /// Equilibrium structure of the modes, given by `mircmd:chemistry:vibrational_modes` nodes so they can be
+    /// shown on their own.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub atomic_coordinates: Option<AtomicCoordinates>,
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct Molecule {
     pub n_atoms: i32,
     pub atomic_num: Vec<i32>,
+    /// Total charge in elementary charges.
     pub charge: i32,
     pub name: String,
+    /// Spin multiplicity 2S + 1, if given by the file. Otherwise the lowest one compatible with the number of
+    /// electrons is assumed, see `properties::lowest_multiplicity`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub multiplicity: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Warning,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Warning {
+    pub severity: Severity,
+    pub message: String,
+    /// One-based line of the source file the warning refers to, if any.
+    #[serde(default)]
+    pub line: Option<usize>,
+}
+
+/// Recoverable issues found while importing a file.
+#[derive(Serialize, Deserialize, Default)]
+pub struct Warnings {
+    pub items: Vec<Warning>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -34,5 +133,55 @@ pub struct VolumeCube {
     pub box_origin: Vec<f64>,
     pub steps_number: Vec<i32>,
     pub steps_size: Vec<Vec<f64>>,
-    pub cube_data: Vec<Vec<Vec<f64>>>,
+    /// Volumetric data in row-major order, use `VolumeCube::index` to address a voxel.
+    /// Empty while the grid is deferred.
+    pub cube_data: Vec<f64>,
+    /// Data set identifier (e.g. orbital number) from the DSET_IDS line of the cube file.
+    #[serde(default)]
+    pub dataset_id: Option<i32>,
+    /// Location of the volumetric data in the source file if its parsing was deferred.
+    #[serde(default)]
+    pub deferred: Option<DeferredGrid>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct DeferredGrid {
+    /// Byte offset of the first line of volumetric data in the (decompressed) file.
+    pub offset: usize,
+    /// Zero-based line number of the first line of volumetric data, used in error messages.
+    pub line: usize,
+    /// Number of interleaved values per voxel.
+    pub num_values: usize,
+    /// Index of this data set among the interleaved values.
+    pub index: usize,
+}
+
+impl VolumeCube {
+    /// Number of voxels along each grid axis.
+    pub fn dims(&self) -> [usize; 3] {
+        [
+            self.steps_number[0].unsigned_abs() as usize,
+            self.steps_number[1].unsigned_abs() as usize,
+            self.steps_number[2].unsigned_abs() as usize,
+        ]
+    }
+
+    /// Strides of `cube_data` along each grid axis.
+    pub fn strides(&self) -> [usize; 3] {
+        let [_, n2, n3] = self.dims();
+        [n2 * n3, n3, 1]
+    }
+
+    pub fn index(&self, i: usize, j: usize, k: usize) -> usize {
+        let [s1, s2, s3] = self.strides();
+        i * s1 + j * s2 + k * s3
+    }
+
+    pub fn value(&self, i: usize, j: usize, k: usize) -> f64 {
+        self.cube_data[self.index(i, j, k)]
+    }
+
+    pub fn is_loaded(&self) -> bool {
+        self.deferred.is_none()
+    }
 }
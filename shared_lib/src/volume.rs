@@ -0,0 +1,135 @@
+// Copyright (c) 2026 Valery Vishnevskiy and Yury Vishnevskiy
+// Licensed under the MIT License
+
+use crate::types::VolumeCube;
+
+const BOHR2ANGSTROM: f64 = 0.529177210903;
+const GRID_TOLERANCE: f64 = 1e-6;
+
+/// Maps grid points of a cube to Cartesian coordinates in Angstroms and back.
+pub struct VolumeGrid<'a> {
+    pub cube: &'a VolumeCube,
+    pub dims: [usize; 3],
+    pub origin: [f64; 3],
+    pub axes: [[f64; 3]; 3],
+    // Converts gradients from grid index space to Cartesian space.
+    // Its columns are the reciprocal axes, so it also converts Cartesian offsets to grid indices.
+    inverse_transpose: [[f64; 3]; 3],
+}
+
+impl<'a> VolumeGrid<'a> {
+    pub fn new(cube: &'a VolumeCube) -> Result<Self, String> {
+        if !cube.is_loaded() {
+            return Err("Volumetric data is not loaded.".to_string());
+        }
+        let dims = cube.dims();
+        if dims.iter().any(|&n| n < 2) || cube.cube_data.len() != dims[0] * dims[1] * dims[2] {
+            return Err("Invalid volumetric grid.".to_string());
+        }
+
+        // Negative number of voxels means the axis is given in Angstroms, otherwise in Bohrs.
+        let unit = |n: i32| if n < 0 { 1.0 } else { BOHR2ANGSTROM };
+        let axes: [[f64; 3]; 3] =
+            std::array::from_fn(|a| std::array::from_fn(|c| cube.steps_size[a][c] * unit(cube.steps_number[a])));
+        let origin = std::array::from_fn(|c| cube.box_origin[c] * unit(cube.steps_number[0]));
+        let inverse_transpose = inverse_transpose(&axes).ok_or_else(|| "Degenerate grid axes.".to_string())?;
+
+        Ok(Self {
+            cube,
+            dims,
+            origin,
+            axes,
+            inverse_transpose,
+        })
+    }
+
+    pub fn value(&self, p: [usize; 3]) -> f64 {
+        self.cube.value(p[0], p[1], p[2])
+    }
+
+    /// Cartesian coordinates of a (possibly fractional) grid point.
+    pub fn position(&self, p: [f64; 3]) -> [f64; 3] {
+        std::array::from_fn(|c| self.origin[c] + (0..3).map(|a| p[a] * self.axes[a][c]).sum::<f64>())
+    }
+
+    /// Fractional grid indices of a Cartesian point.
+    pub fn grid_position(&self, point: [f64; 3]) -> [f64; 3] {
+        let d: [f64; 3] = std::array::from_fn(|c| point[c] - self.origin[c]);
+        std::array::from_fn(|a| (0..3).map(|c| self.inverse_transpose[c][a] * d[c]).sum())
+    }
+
+    /// Gradient at a grid point by central differences (one-sided at the borders).
+    pub fn gradient(&self, p: [usize; 3]) -> [f64; 3] {
+        let mut local = [0.0; 3];
+        for (a, g) in local.iter_mut().enumerate() {
+            let mut lo = p;
+            let mut hi = p;
+            lo[a] = p[a].saturating_sub(1);
+            hi[a] = (p[a] + 1).min(self.dims[a] - 1);
+            *g = (self.value(hi) - self.value(lo)) / (hi[a] - lo[a]) as f64;
+        }
+        std::array::from_fn(|c| (0..3).map(|a| self.inverse_transpose[c][a] * local[a]).sum())
+    }
+
+    /// Trilinear interpolation at a Cartesian point, None outside of the grid.
+    pub fn sample(&self, point: [f64; 3]) -> Option<f64> {
+        let g = self.grid_position(point);
+        let mut base = [0usize; 3];
+        let mut t = [0.0; 3];
+        for a in 0..3 {
+            // Small tolerance keeps points on the faces of the box inside despite rounding errors
+            let last = (self.dims[a] - 1) as f64;
+            if !(-GRID_TOLERANCE..=last + GRID_TOLERANCE).contains(&g[a]) {
+                return None;
+            }
+            let x = g[a].clamp(0.0, last);
+            base[a] = (x.floor() as usize).min(self.dims[a] - 2);
+            t[a] = x - base[a] as f64;
+        }
+
+        let mut result = 0.0;
+        for corner in 0..8 {
+            let o = [corner & 1, (corner >> 1) & 1, (corner >> 2) & 1];
+            let weight: f64 = (0..3).map(|a| if o[a] == 1 { t[a] } else { 1.0 - t[a] }).product();
+            result += weight * self.value([base[0] + o[0], base[1] + o[1], base[2] + o[2]]);
+        }
+        Some(result)
+    }
+
+    /// Cartesian coordinates of the 8 corners of the grid box.
+    pub fn corners(&self) -> [[f64; 3]; 8] {
+        let last = self.dims.map(|n| (n - 1) as f64);
+        std::array::from_fn(|corner| {
+            self.position(std::array::from_fn(
+                |a| if (corner >> a) & 1 == 1 { last[a] } else { 0.0 },
+            ))
+        })
+    }
+
+    /// Minimum and maximum values of the grid.
+    pub fn value_range(&self) -> (f64, f64) {
+        self.cube
+            .cube_data
+            .iter()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), &v| {
+                (min.min(v), max.max(v))
+            })
+    }
+}
+
+fn inverse_transpose(m: &[[f64; 3]; 3]) -> Option<[[f64; 3]; 3]> {
+    // Rows of `m` are the axes, so the columns of the result are the reciprocal axes.
+    let cross = |a: [f64; 3], b: [f64; 3]| {
+        [
+            a[1] * b[2] - a[2] * b[1],
+            a[2] * b[0] - a[0] * b[2],
+            a[0] * b[1] - a[1] * b[0],
+        ]
+    };
+    let r = [cross(m[1], m[2]), cross(m[2], m[0]), cross(m[0], m[1])];
+    let det: f64 = (0..3).map(|c| m[0][c] * r[0][c]).sum();
+    if det.abs() < f64::EPSILON {
+        return None;
+    }
+    Some(std::array::from_fn(|c| std::array::from_fn(|a| r[a][c] / det)))
+}